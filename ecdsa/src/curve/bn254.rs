@@ -0,0 +1,96 @@
+use plonky2::field::bn254_base::Bn254Base;
+use plonky2::field::bn254_scalar::Bn254Scalar;
+use plonky2::field::types::Field;
+use serde::{Deserialize, Serialize};
+
+use crate::curve::curve_types::{AffinePoint, Curve};
+use crate::curve::glv::GlvCurve;
+
+/// The BN254 curve `y^2 = x^3 + 3` over its base field, whose points form the G1 group of the
+/// BN254 pairing.
+#[derive(Debug, Copy, Clone, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Bn254;
+
+impl Curve for Bn254 {
+    type BaseField = Bn254Base;
+    type ScalarField = Bn254Scalar;
+
+    const A: Bn254Base = Bn254Base::ZERO;
+    const B: Bn254Base = Bn254Base([3, 0, 0, 0]);
+    const GENERATOR_AFFINE: AffinePoint<Self> = AffinePoint {
+        x: Bn254Base([1, 0, 0, 0]),
+        y: Bn254Base([2, 0, 0, 0]),
+        zero: false,
+    };
+}
+
+impl GlvCurve for Bn254 {
+    // 2203960485148121921418603742825762020974279258880205651966
+    const GLV_BETA: Bn254Base = Bn254Base([
+        0x5763473177FFFFFE,
+        0xD4F263F1ACDB5C4F,
+        0x59E26BCEA0D48BAC,
+        0x0000000000000000,
+    ]);
+
+    // 4407920970296243842393367215006156084916469457145843978461
+    const GLV_S: Bn254Scalar = Bn254Scalar([
+        0x8B17EA66B99C90DD,
+        0x5BFC41088D8DAAA7,
+        0xB3C4D79D41A91758,
+        0x0000000000000000,
+    ]);
+
+    const GLV_A1: Bn254Scalar = Bn254Scalar([0x89D3256894D213E3, 0, 0, 0]);
+
+    const GLV_MINUS_B1: Bn254Scalar = Bn254Scalar([0x8211BBEB7D4F1128, 0x6F4D8248EEB859FC, 0, 0]);
+
+    const GLV_A2: Bn254Scalar = Bn254Scalar([0x0BE4E1541221250B, 0x6F4D8248EEB859FD, 0, 0]);
+
+    const GLV_B2: Bn254Scalar = Bn254Scalar([0x89D3256894D213E3, 0, 0, 0]);
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::bn254_scalar::Bn254Scalar;
+    use plonky2::field::types::{Field, Sample};
+
+    use crate::curve::bn254::Bn254;
+    use crate::curve::curve_types::{AffinePoint, Curve, CurveScalar, ProjectivePoint};
+
+    #[test]
+    fn test_generator() {
+        let g = Bn254::GENERATOR_AFFINE;
+        assert!(g.is_valid());
+
+        let neg_g = AffinePoint::<Bn254> {
+            x: g.x,
+            y: -g.y,
+            zero: g.zero,
+        };
+        assert!(neg_g.is_valid());
+    }
+
+    #[test]
+    fn test_generator_order() {
+        // `(r - 1) * G = -G`, so `G` generates the group of order `r`.
+        let g = Bn254::GENERATOR_PROJECTIVE;
+        assert_eq!(CurveScalar(-Bn254Scalar::ONE) * g, -g);
+    }
+
+    #[test]
+    fn test_multiplication() {
+        let g = Bn254::GENERATOR_PROJECTIVE;
+        let a = Bn254Scalar::rand();
+        let b = Bn254Scalar::rand();
+        assert_eq!(
+            CurveScalar(a) * g + CurveScalar(b) * g,
+            CurveScalar(a + b) * g
+        );
+        assert_eq!(
+            CurveScalar(Bn254Scalar::from_canonical_u64(3)) * g,
+            g + g + g
+        );
+        assert_eq!(CurveScalar(Bn254Scalar::ZERO) * g, ProjectivePoint::ZERO);
+    }
+}
@@ -2,6 +2,7 @@ use alloc::vec::Vec;
 
 use itertools::Itertools;
 use plonky2::field::types::{Field, PrimeField};
+use plonky2::util::ceil_div_usize;
 use plonky2_maybe_rayon::*;
 
 use crate::curve::curve_summation::affine_multisummation_best;
@@ -166,7 +167,7 @@ pub(crate) fn to_digits<C: Curve>(x: &C::ScalarField, w: usize) -> Vec<usize> {
         .to_u64_digits()
         .iter()
         .cloned()
-        .pad_using(scalar_bits / 64, |_| 0)
+        .pad_using(ceil_div_usize(scalar_bits, 64), |_| 0)
         .collect();
     let mut x_bits = Vec::with_capacity(scalar_bits);
     for i in 0..scalar_bits {
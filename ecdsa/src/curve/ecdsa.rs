@@ -62,12 +62,40 @@ pub fn verify_message<C: Curve>(
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
+    use num::BigUint;
     use plonky2::field::secp256k1_scalar::Secp256K1Scalar;
-    use plonky2::field::types::Sample;
+    use plonky2::field::secp256r1_scalar::Secp256R1Scalar;
+    use plonky2::field::types::{Field, Sample};
 
-    use crate::curve::ecdsa::{sign_message, verify_message, ECDSASecretKey};
+    use crate::curve::curve_types::AffinePoint;
+    use crate::curve::ecdsa::{
+        sign_message, verify_message, ECDSAPublicKey, ECDSASecretKey, ECDSASignature,
+    };
     use crate::curve::secp256k1::Secp256K1;
+    use crate::curve::secp256r1::Secp256R1;
+
+    fn hex_to_field<F: Field>(hex: &str) -> F {
+        F::from_noncanonical_biguint(BigUint::parse_bytes(hex.as_bytes(), 16).unwrap())
+    }
+
+    /// The P-256 key, SHA-256 digest of "sample" and signature from RFC 6979, section A.2.5.
+    pub(crate) fn p256_test_vector() -> (
+        Secp256R1Scalar,
+        ECDSASignature<Secp256R1>,
+        ECDSAPublicKey<Secp256R1>,
+    ) {
+        let pk = ECDSAPublicKey(AffinePoint::nonzero(
+            hex_to_field("60FED4BA255A9D31C961EB74C6356D68C049B8923B61FA6CE669622E60F29FB6"),
+            hex_to_field("7903FE1008B8BC99A41AE9E95628BC64F2F1B20C2D7E9F5177A3C294D4462299"),
+        ));
+        let msg = hex_to_field("AF2BDBE1AA9B6EC1E2ADE1D694F41FC71A831D0268E9891562113D8A62ADD1BF");
+        let sig = ECDSASignature {
+            r: hex_to_field("EFD48B2AACB6A8FD1140DD9CD45E81D69D2C877B56AAF991C34D0EA84EAF3716"),
+            s: hex_to_field("F7CB1C942D657C41D436C7A1B6E29F65F3E900DBB9AFF4064DC4AB2F843ACDA8"),
+        };
+        (msg, sig, pk)
+    }
 
     #[test]
     fn test_ecdsa_native() {
@@ -81,4 +109,29 @@ mod tests {
         let result = verify_message(msg, sig, pk);
         assert!(result);
     }
+
+    #[test]
+    fn test_ecdsa_native_secp256r1() {
+        type C = Secp256R1;
+
+        let msg = Secp256R1Scalar::rand();
+        let sk = ECDSASecretKey::<C>(Secp256R1Scalar::rand());
+        let pk = sk.to_public();
+
+        let sig = sign_message(msg, sk);
+        assert!(verify_message(msg, sig, pk));
+        assert!(!verify_message(msg + Secp256R1Scalar::ONE, sig, pk));
+    }
+
+    #[test]
+    fn test_ecdsa_native_secp256r1_rfc6979() {
+        let (msg, sig, pk) = p256_test_vector();
+        assert!(pk.0.is_valid());
+
+        let sk = ECDSASecretKey::<Secp256R1>(hex_to_field(
+            "C9AFA9D845BA75166B5C215767B1D6934E50C3DB36E89B127B8A622B120F6721",
+        ));
+        assert_eq!(sk.to_public(), pk);
+        assert!(verify_message(msg, sig, pk));
+    }
 }
@@ -0,0 +1,119 @@
+use plonky2::field::ed25519_base::Ed25519Base;
+use plonky2::field::ed25519_scalar::Ed25519Scalar;
+use serde::{Deserialize, Serialize};
+
+use crate::curve::twisted_edwards::{EdwardsPoint, TwistedEdwardsCurve};
+
+/// The twisted Edwards curve `-x^2 + y^2 = 1 - (121665/121666) x^2 y^2` used by Ed25519.
+#[derive(Debug, Copy, Clone, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Ed25519;
+
+impl TwistedEdwardsCurve for Ed25519 {
+    type BaseField = Ed25519Base;
+    type ScalarField = Ed25519Scalar;
+
+    const A: Ed25519Base = Ed25519Base([
+        0xFFFFFFFFFFFFFFEC,
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+        0x7FFFFFFFFFFFFFFF,
+    ]);
+    // 37095705934669439343138083508754565189542113879843219016388785533085940283555
+    const D: Ed25519Base = Ed25519Base([
+        0x75EB4DCA135978A3,
+        0x00700A4D4141D8AB,
+        0x8CC740797779E898,
+        0x52036CEE2B6FFE73,
+    ]);
+    const GENERATOR: EdwardsPoint<Self> = EdwardsPoint {
+        x: ED25519_GENERATOR_X,
+        y: ED25519_GENERATOR_Y,
+    };
+}
+
+// 15112221349535400772501151409588531511454012693041857206046113283949847762202
+const ED25519_GENERATOR_X: Ed25519Base = Ed25519Base([
+    0xC9562D608F25D51A,
+    0x692CC7609525A7B2,
+    0xC0A4E231FDD6DC5C,
+    0x216936D3CD6E53FE,
+]);
+
+/// 46316835694926478169428394003475163141307993866256225615783033603165251855960, i.e. 4/5
+const ED25519_GENERATOR_Y: Ed25519Base = Ed25519Base([
+    0x6666666666666658,
+    0x6666666666666666,
+    0x6666666666666666,
+    0x6666666666666666,
+]);
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::ed25519_base::Ed25519Base;
+    use plonky2::field::ed25519_scalar::Ed25519Scalar;
+    use plonky2::field::types::{Field, Sample};
+
+    use crate::curve::ed25519::Ed25519;
+    use crate::curve::twisted_edwards::{EdwardsPoint, EdwardsScalar, TwistedEdwardsCurve};
+
+    #[test]
+    fn test_curve_constants() {
+        assert_eq!(Ed25519::A, -Ed25519Base::ONE);
+        assert_eq!(
+            Ed25519::D * Ed25519Base::from_canonical_u32(121666),
+            -Ed25519Base::from_canonical_u32(121665)
+        );
+        assert_eq!(
+            Ed25519::GENERATOR.y * Ed25519Base::from_canonical_u32(5),
+            Ed25519Base::from_canonical_u32(4)
+        );
+        assert!(Ed25519::is_complete());
+    }
+
+    #[test]
+    fn test_generator() {
+        let g = Ed25519::GENERATOR;
+        assert!(g.is_valid());
+        assert!((-g).is_valid());
+        assert_eq!(g + EdwardsPoint::ZERO, g);
+        assert_eq!(g + -g, EdwardsPoint::ZERO);
+
+        // The generator has order `l`, so `(l - 1) G = -G`.
+        assert_eq!(EdwardsScalar(-Ed25519Scalar::ONE) * g, -g);
+    }
+
+    #[test]
+    fn test_add_and_double() {
+        let g = Ed25519::GENERATOR;
+        let three = Ed25519Scalar::from_canonical_u64(3);
+        assert_eq!(g.double(), g + g);
+        assert_eq!(EdwardsScalar(three) * g, g.double() + g);
+
+        let a = Ed25519Scalar::rand();
+        let b = Ed25519Scalar::rand();
+        let lhs = EdwardsScalar(a) * g + EdwardsScalar(b) * g;
+        let rhs = EdwardsScalar(a + b) * g;
+        assert!(lhs.is_valid());
+        assert_eq!(lhs, rhs);
+    }
+
+    #[test]
+    fn test_encoding() {
+        let g = Ed25519::GENERATOR;
+        // The standard encoding of the base point.
+        let mut expected = [0x66; 32];
+        expected[0] = 0x58;
+        assert_eq!(g.to_bytes(), expected);
+        assert_eq!(EdwardsPoint::from_bytes(&expected), Some(g));
+
+        let p = EdwardsScalar(Ed25519Scalar::rand()) * g;
+        assert_eq!(EdwardsPoint::<Ed25519>::from_bytes(&p.to_bytes()), Some(p));
+        assert_eq!(
+            EdwardsPoint::<Ed25519>::from_bytes(&(-p).to_bytes()),
+            Some(-p)
+        );
+
+        // 2^255 - 1 is not a canonical `y` coordinate.
+        assert_eq!(EdwardsPoint::<Ed25519>::from_bytes(&[0xff; 32]), None);
+    }
+}
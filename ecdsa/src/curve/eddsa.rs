@@ -0,0 +1,164 @@
+use alloc::vec::Vec;
+
+use num::BigUint;
+use plonky2::field::ed25519_scalar::Ed25519Scalar;
+use plonky2::field::types::{Field, PrimeField, Sample};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+
+use crate::curve::ed25519::Ed25519;
+use crate::curve::twisted_edwards::{EdwardsPoint, EdwardsScalar, TwistedEdwardsCurve};
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct EdDSASignature<C: TwistedEdwardsCurve> {
+    pub r: EdwardsPoint<C>,
+    pub s: C::ScalarField,
+}
+
+impl<C: TwistedEdwardsCurve> EdDSASignature<C> {
+    /// Encodes the signature as `R || S`, with `S` in little-endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let len = EdwardsPoint::<C>::num_encoding_bytes();
+        let mut bytes = self.r.to_bytes();
+        let mut s_bytes = self.s.to_canonical_biguint().to_bytes_le();
+        s_bytes.resize(len, 0);
+        bytes.extend(s_bytes);
+        bytes
+    }
+
+    /// Decodes a signature encoded by `to_bytes`, returning `None` if `R` is not a valid point or
+    /// `S` is not reduced.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let len = EdwardsPoint::<C>::num_encoding_bytes();
+        if bytes.len() != 2 * len {
+            return None;
+        }
+        let r = EdwardsPoint::from_bytes(&bytes[..len])?;
+        let s_biguint = BigUint::from_bytes_le(&bytes[len..]);
+        if s_biguint >= C::ScalarField::order() {
+            return None;
+        }
+        let s = C::ScalarField::from_noncanonical_biguint(s_biguint);
+        Some(Self { r, s })
+    }
+}
+
+/// The secret scalar `a`. Unlike RFC 8032, we do not derive it, or the signing nonces, from a seed.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct EdDSASecretKey<C: TwistedEdwardsCurve>(pub C::ScalarField);
+
+impl<C: TwistedEdwardsCurve> EdDSASecretKey<C> {
+    pub fn to_public(&self) -> EdDSAPublicKey<C> {
+        EdDSAPublicKey(EdwardsScalar(self.0) * C::GENERATOR)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct EdDSAPublicKey<C: TwistedEdwardsCurve>(pub EdwardsPoint<C>);
+
+/// The Ed25519 challenge `SHA-512(R || A || M) mod l`.
+pub fn ed25519_challenge(
+    r: EdwardsPoint<Ed25519>,
+    pk: EdDSAPublicKey<Ed25519>,
+    msg: &[u8],
+) -> Ed25519Scalar {
+    let digest = Sha512::new()
+        .chain_update(r.to_bytes())
+        .chain_update(pk.0.to_bytes())
+        .chain_update(msg)
+        .finalize();
+    Ed25519Scalar::from_noncanonical_biguint(BigUint::from_bytes_le(&digest))
+}
+
+pub fn sign_message(msg: &[u8], sk: EdDSASecretKey<Ed25519>) -> EdDSASignature<Ed25519> {
+    let nonce = Ed25519Scalar::rand();
+    let r = EdwardsScalar(nonce) * Ed25519::GENERATOR;
+    let k = ed25519_challenge(r, sk.to_public(), msg);
+    let s = nonce + k * sk.0;
+
+    EdDSASignature { r, s }
+}
+
+/// Checks the cofactorless verification equation `[S]B = R + [k]A`.
+pub fn verify_message(
+    msg: &[u8],
+    sig: EdDSASignature<Ed25519>,
+    pk: EdDSAPublicKey<Ed25519>,
+) -> bool {
+    let EdDSASignature { r, s } = sig;
+
+    assert!(pk.0.is_valid());
+    assert!(r.is_valid());
+
+    let k = ed25519_challenge(r, pk, msg);
+    EdwardsScalar(s) * Ed25519::GENERATOR == r + EdwardsScalar(k) * pk.0
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use alloc::vec::Vec;
+
+    use plonky2::field::ed25519_scalar::Ed25519Scalar;
+    use plonky2::field::types::{Field, Sample};
+
+    use crate::curve::ed25519::Ed25519;
+    use crate::curve::eddsa::{
+        sign_message, verify_message, EdDSAPublicKey, EdDSASecretKey, EdDSASignature,
+    };
+    use crate::curve::twisted_edwards::EdwardsPoint;
+
+    pub(crate) fn hex_to_bytes(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// Test vectors 1 to 3 from RFC 8032, section 7.1, as `(public key, message, signature)`.
+    pub(crate) const RFC_8032_VECTORS: [(&str, &str, &str); 3] = [
+        (
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            "",
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        ),
+        (
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            "72",
+            "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+        ),
+        (
+            "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+            "af82",
+            "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+        ),
+    ];
+
+    #[test]
+    fn test_eddsa_native() {
+        let msg = b"plonky2";
+        let sk = EdDSASecretKey::<Ed25519>(Ed25519Scalar::rand());
+        let pk = sk.to_public();
+
+        let sig = sign_message(msg, sk);
+        assert!(verify_message(msg, sig, pk));
+        assert!(!verify_message(b"plonky3", sig, pk));
+
+        let bad_sig = EdDSASignature {
+            r: sig.r,
+            s: sig.s + Ed25519Scalar::ONE,
+        };
+        assert!(!verify_message(msg, bad_sig, pk));
+    }
+
+    #[test]
+    fn test_eddsa_rfc_8032() {
+        for (pk, msg, sig) in RFC_8032_VECTORS {
+            let pk =
+                EdDSAPublicKey(EdwardsPoint::<Ed25519>::from_bytes(&hex_to_bytes(pk)).unwrap());
+            let sig_bytes = hex_to_bytes(sig);
+            let sig = EdDSASignature::<Ed25519>::from_bytes(&sig_bytes).unwrap();
+            assert_eq!(sig.to_bytes(), sig_bytes);
+            assert!(verify_message(&hex_to_bytes(msg), sig, pk));
+        }
+    }
+}
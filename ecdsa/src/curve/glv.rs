@@ -1,73 +1,62 @@
 use num::rational::Ratio;
 use num::BigUint;
-use plonky2::field::secp256k1_base::Secp256K1Base;
-use plonky2::field::secp256k1_scalar::Secp256K1Scalar;
 use plonky2::field::types::{Field, PrimeField};
 
 use crate::curve::curve_msm::msm_parallel;
-use crate::curve::curve_types::{AffinePoint, ProjectivePoint};
-use crate::curve::secp256k1::Secp256K1;
-
-pub const GLV_BETA: Secp256K1Base = Secp256K1Base([
-    13923278643952681454,
-    11308619431505398165,
-    7954561588662645993,
-    8856726876819556112,
-]);
-
-pub const GLV_S: Secp256K1Scalar = Secp256K1Scalar([
-    16069571880186789234,
-    1310022930574435960,
-    11900229862571533402,
-    6008836872998760672,
-]);
-
-const A1: Secp256K1Scalar = Secp256K1Scalar([16747920425669159701, 3496713202691238861, 0, 0]);
-
-const MINUS_B1: Secp256K1Scalar =
-    Secp256K1Scalar([8022177200260244675, 16448129721693014056, 0, 0]);
-
-const A2: Secp256K1Scalar = Secp256K1Scalar([6323353552219852760, 1498098850674701302, 1, 0]);
-
-const B2: Secp256K1Scalar = Secp256K1Scalar([16747920425669159701, 3496713202691238861, 0, 0]);
+use crate::curve::curve_types::{AffinePoint, Curve, ProjectivePoint};
+
+/// A curve with an endomorphism `psi: (x, y) |-> (beta * x, y)`, equivalent to scalar
+/// multiplication by `s`, which allows GLV scalar multiplication.
+///
+/// `(a1, b1)` and `(a2, b2)` are a reduced basis of the lattice of `(k1, k2)` with
+/// `k1 + s * k2 = 0`, chosen with `b1 < 0` and `a1, a2, b2 > 0`.
+pub trait GlvCurve: Curve {
+    const GLV_BETA: Self::BaseField;
+    const GLV_S: Self::ScalarField;
+
+    const GLV_A1: Self::ScalarField;
+    const GLV_MINUS_B1: Self::ScalarField;
+    const GLV_A2: Self::ScalarField;
+    const GLV_B2: Self::ScalarField;
+}
 
 /// Algorithm 15.41 in Handbook of Elliptic and Hyperelliptic Curve Cryptography.
 /// Decompose a scalar `k` into two small scalars `k1, k2` with `|k1|, |k2| < √p` that satisfy
 /// `k1 + s * k2 = k`.
 /// Returns `(|k1|, |k2|, k1 < 0, k2 < 0)`.
-pub fn decompose_secp256k1_scalar(
-    k: Secp256K1Scalar,
-) -> (Secp256K1Scalar, Secp256K1Scalar, bool, bool) {
-    let p = Secp256K1Scalar::order();
+pub fn decompose_glv_scalar<C: GlvCurve>(
+    k: C::ScalarField,
+) -> (C::ScalarField, C::ScalarField, bool, bool) {
+    let p = C::ScalarField::order();
     let c1_biguint = Ratio::new(
-        B2.to_canonical_biguint() * k.to_canonical_biguint(),
+        C::GLV_B2.to_canonical_biguint() * k.to_canonical_biguint(),
         p.clone(),
     )
     .round()
     .to_integer();
-    let c1 = Secp256K1Scalar::from_noncanonical_biguint(c1_biguint);
+    let c1 = C::ScalarField::from_noncanonical_biguint(c1_biguint);
     let c2_biguint = Ratio::new(
-        MINUS_B1.to_canonical_biguint() * k.to_canonical_biguint(),
+        C::GLV_MINUS_B1.to_canonical_biguint() * k.to_canonical_biguint(),
         p.clone(),
     )
     .round()
     .to_integer();
-    let c2 = Secp256K1Scalar::from_noncanonical_biguint(c2_biguint);
+    let c2 = C::ScalarField::from_noncanonical_biguint(c2_biguint);
 
-    let k1_raw = k - c1 * A1 - c2 * A2;
-    let k2_raw = c1 * MINUS_B1 - c2 * B2;
-    debug_assert!(k1_raw + GLV_S * k2_raw == k);
+    let k1_raw = k - c1 * C::GLV_A1 - c2 * C::GLV_A2;
+    let k2_raw = c1 * C::GLV_MINUS_B1 - c2 * C::GLV_B2;
+    debug_assert!(k1_raw + C::GLV_S * k2_raw == k);
 
     let two = BigUint::from_slice(&[2]);
     let k1_neg = k1_raw.to_canonical_biguint() > p.clone() / two.clone();
     let k1 = if k1_neg {
-        Secp256K1Scalar::from_noncanonical_biguint(p.clone() - k1_raw.to_canonical_biguint())
+        C::ScalarField::from_noncanonical_biguint(p.clone() - k1_raw.to_canonical_biguint())
     } else {
         k1_raw
     };
     let k2_neg = k2_raw.to_canonical_biguint() > p.clone() / two;
     let k2 = if k2_neg {
-        Secp256K1Scalar::from_noncanonical_biguint(p - k2_raw.to_canonical_biguint())
+        C::ScalarField::from_noncanonical_biguint(p - k2_raw.to_canonical_biguint())
     } else {
         k2_raw
     };
@@ -77,14 +66,13 @@ pub fn decompose_secp256k1_scalar(
 
 /// See Section 15.2.1 in Handbook of Elliptic and Hyperelliptic Curve Cryptography.
 /// GLV scalar multiplication `k * P = k1 * P + k2 * psi(P)`, where `k = k1 + s * k2` is the
-/// decomposition computed in `decompose_secp256k1_scalar(k)` and `psi` is the Secp256k1
-/// endomorphism `psi: (x, y) |-> (beta * x, y)` equivalent to scalar multiplication by `s`.
-pub fn glv_mul(p: ProjectivePoint<Secp256K1>, k: Secp256K1Scalar) -> ProjectivePoint<Secp256K1> {
-    let (k1, k2, k1_neg, k2_neg) = decompose_secp256k1_scalar(k);
+/// decomposition computed in `decompose_glv_scalar(k)` and `psi` is the endomorphism of `C`.
+pub fn glv_mul<C: GlvCurve>(p: ProjectivePoint<C>, k: C::ScalarField) -> ProjectivePoint<C> {
+    let (k1, k2, k1_neg, k2_neg) = decompose_glv_scalar::<C>(k);
 
     let p_affine = p.to_affine();
-    let sp = AffinePoint::<Secp256K1> {
-        x: p_affine.x * GLV_BETA,
+    let sp = AffinePoint::<C> {
+        x: p_affine.x * C::GLV_BETA,
         y: p_affine.y,
         zero: p_affine.zero,
     };
@@ -102,38 +90,60 @@ pub fn glv_mul(p: ProjectivePoint<Secp256K1>, k: Secp256K1Scalar) -> ProjectiveP
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
-    use plonky2::field::secp256k1_scalar::Secp256K1Scalar;
-    use plonky2::field::types::{Field, Sample};
+    use plonky2::field::types::{Field, PrimeField, Sample};
 
-    use crate::curve::curve_types::{Curve, CurveScalar};
-    use crate::curve::glv::{decompose_secp256k1_scalar, glv_mul, GLV_S};
+    use crate::curve::bn254::Bn254;
+    use crate::curve::curve_types::{AffinePoint, CurveScalar};
+    use crate::curve::glv::{decompose_glv_scalar, glv_mul, GlvCurve};
     use crate::curve::secp256k1::Secp256K1;
 
-    #[test]
-    fn test_glv_decompose() -> Result<()> {
-        let k = Secp256K1Scalar::rand();
-        let (k1, k2, k1_neg, k2_neg) = decompose_secp256k1_scalar(k);
-        let one = Secp256K1Scalar::ONE;
+    fn test_glv_decompose<C: GlvCurve>() {
+        let k = C::ScalarField::rand();
+        let (k1, k2, k1_neg, k2_neg) = decompose_glv_scalar::<C>(k);
+        let one = C::ScalarField::ONE;
         let m1 = if k1_neg { -one } else { one };
         let m2 = if k2_neg { -one } else { one };
 
-        assert!(k1 * m1 + GLV_S * k2 * m2 == k);
+        assert!(k1 * m1 + C::GLV_S * k2 * m2 == k);
+        // The decomposed scalars must fit in four 32-bit limbs.
+        assert!(k1.to_canonical_biguint().bits() <= 128);
+        assert!(k2.to_canonical_biguint().bits() <= 128);
+    }
 
-        Ok(())
+    fn test_glv_endomorphism<C: GlvCurve>() {
+        let p = (CurveScalar(C::ScalarField::rand()) * C::GENERATOR_PROJECTIVE).to_affine();
+        let psi_p = AffinePoint::<C>::nonzero(p.x * C::GLV_BETA, p.y);
+
+        assert!(psi_p.to_projective() == CurveScalar(C::GLV_S) * p.to_projective());
     }
 
-    #[test]
-    fn test_glv_mul() -> Result<()> {
+    fn test_glv_mul<C: GlvCurve>() {
         for _ in 0..20 {
-            let k = Secp256K1Scalar::rand();
+            let k = C::ScalarField::rand();
 
-            let p = CurveScalar(Secp256K1Scalar::rand()) * Secp256K1::GENERATOR_PROJECTIVE;
+            let p = CurveScalar(C::ScalarField::rand()) * C::GENERATOR_PROJECTIVE;
 
             let kp = CurveScalar(k) * p;
             let glv = glv_mul(p, k);
 
             assert!(kp == glv);
         }
+    }
+
+    #[test]
+    fn test_glv_secp256k1() -> Result<()> {
+        test_glv_decompose::<Secp256K1>();
+        test_glv_endomorphism::<Secp256K1>();
+        test_glv_mul::<Secp256K1>();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_glv_bn254() -> Result<()> {
+        test_glv_decompose::<Bn254>();
+        test_glv_endomorphism::<Bn254>();
+        test_glv_mul::<Bn254>();
 
         Ok(())
     }
@@ -1,8 +1,13 @@
+pub mod bn254;
 pub mod curve_adds;
 pub mod curve_msm;
 pub mod curve_multiplication;
 pub mod curve_summation;
 pub mod curve_types;
 pub mod ecdsa;
+pub mod ed25519;
+pub mod eddsa;
 pub mod glv;
 pub mod secp256k1;
+pub mod secp256r1;
+pub mod twisted_edwards;
@@ -4,6 +4,7 @@ use plonky2::field::types::Field;
 use serde::{Deserialize, Serialize};
 
 use crate::curve::curve_types::{AffinePoint, Curve};
+use crate::curve::glv::GlvCurve;
 
 #[derive(Debug, Copy, Clone, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct Secp256K1;
@@ -21,6 +22,34 @@ impl Curve for Secp256K1 {
     };
 }
 
+impl GlvCurve for Secp256K1 {
+    const GLV_BETA: Secp256K1Base = Secp256K1Base([
+        13923278643952681454,
+        11308619431505398165,
+        7954561588662645993,
+        8856726876819556112,
+    ]);
+
+    const GLV_S: Secp256K1Scalar = Secp256K1Scalar([
+        16069571880186789234,
+        1310022930574435960,
+        11900229862571533402,
+        6008836872998760672,
+    ]);
+
+    const GLV_A1: Secp256K1Scalar =
+        Secp256K1Scalar([16747920425669159701, 3496713202691238861, 0, 0]);
+
+    const GLV_MINUS_B1: Secp256K1Scalar =
+        Secp256K1Scalar([8022177200260244675, 16448129721693014056, 0, 0]);
+
+    const GLV_A2: Secp256K1Scalar =
+        Secp256K1Scalar([6323353552219852760, 1498098850674701302, 1, 0]);
+
+    const GLV_B2: Secp256K1Scalar =
+        Secp256K1Scalar([16747920425669159701, 3496713202691238861, 0, 0]);
+}
+
 // 55066263022277343669578718895168534326250603453777594175500187360389116729240
 const SECP256K1_GENERATOR_X: Secp256K1Base = Secp256K1Base([
     0x59F2815B16F81798,
@@ -0,0 +1,102 @@
+use plonky2::field::secp256r1_base::Secp256R1Base;
+use plonky2::field::secp256r1_scalar::Secp256R1Scalar;
+use serde::{Deserialize, Serialize};
+
+use crate::curve::curve_types::{AffinePoint, Curve};
+
+/// The secp256r1 curve, also known as NIST P-256. It has no efficient endomorphism, so it does not
+/// implement `GlvCurve`.
+#[derive(Debug, Copy, Clone, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Secp256R1;
+
+impl Curve for Secp256R1 {
+    type BaseField = Secp256R1Base;
+    type ScalarField = Secp256R1Scalar;
+
+    // -3
+    const A: Secp256R1Base = Secp256R1Base([
+        0xFFFFFFFFFFFFFFFC,
+        0x00000000FFFFFFFF,
+        0x0000000000000000,
+        0xFFFFFFFF00000001,
+    ]);
+    // 41058363725152142129326129780047268409114441015993725554835256314039467401291
+    const B: Secp256R1Base = Secp256R1Base([
+        0x3BCE3C3E27D2604B,
+        0x651D06B0CC53B0F6,
+        0xB3EBBD55769886BC,
+        0x5AC635D8AA3A93E7,
+    ]);
+    const GENERATOR_AFFINE: AffinePoint<Self> = AffinePoint {
+        x: SECP256R1_GENERATOR_X,
+        y: SECP256R1_GENERATOR_Y,
+        zero: false,
+    };
+}
+
+// 48439561293906451759052585252797914202762949526041747995844080717082404635286
+const SECP256R1_GENERATOR_X: Secp256R1Base = Secp256R1Base([
+    0xF4A13945D898C296,
+    0x77037D812DEB33A0,
+    0xF8BCE6E563A440F2,
+    0x6B17D1F2E12C4247,
+]);
+
+// 36134250956749795798585127919587881956611106672985015071877198253568414405109
+const SECP256R1_GENERATOR_Y: Secp256R1Base = Secp256R1Base([
+    0xCBB6406837BF51F5,
+    0x2BCE33576B315ECE,
+    0x8EE7EB4A7C0F9E16,
+    0x4FE342E2FE1A7F9B,
+]);
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::secp256r1_base::Secp256R1Base;
+    use plonky2::field::secp256r1_scalar::Secp256R1Scalar;
+    use plonky2::field::types::{Field, Sample};
+
+    use crate::curve::curve_types::{AffinePoint, Curve, CurveScalar};
+    use crate::curve::secp256r1::Secp256R1;
+
+    #[test]
+    fn test_curve_constants() {
+        assert_eq!(Secp256R1::A, -Secp256R1Base::from_canonical_u64(3));
+        assert!(Secp256R1::is_safe_curve());
+    }
+
+    #[test]
+    fn test_generator() {
+        let g = Secp256R1::GENERATOR_AFFINE;
+        assert!(g.is_valid());
+
+        let neg_g = AffinePoint::<Secp256R1> {
+            x: g.x,
+            y: -g.y,
+            zero: g.zero,
+        };
+        assert!(neg_g.is_valid());
+    }
+
+    #[test]
+    fn test_generator_order() {
+        // `(n - 1) * G = -G`, so `G` generates the group of order `n`.
+        let g = Secp256R1::GENERATOR_PROJECTIVE;
+        assert_eq!(CurveScalar(-Secp256R1Scalar::ONE) * g, -g);
+    }
+
+    #[test]
+    fn test_multiplication() {
+        let g = Secp256R1::GENERATOR_PROJECTIVE;
+        let a = Secp256R1Scalar::rand();
+        let b = Secp256R1Scalar::rand();
+        assert_eq!(
+            CurveScalar(a) * g + CurveScalar(b) * g,
+            CurveScalar(a + b) * g
+        );
+        assert_eq!(
+            CurveScalar(Secp256R1Scalar::from_canonical_u64(3)) * g,
+            g + g + g
+        );
+    }
+}
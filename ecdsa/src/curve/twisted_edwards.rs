@@ -0,0 +1,166 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::ops::{Add, Mul, Neg};
+
+use num::{BigUint, Integer};
+use plonky2::field::ops::Square;
+use plonky2::field::types::{Field, PrimeField};
+use plonky2::util::ceil_div_usize;
+use serde::{Deserialize, Serialize};
+
+// To avoid implementation conflicts from associated types,
+// see https://github.com/rust-lang/rust/issues/20400
+pub struct EdwardsScalar<C: TwistedEdwardsCurve>(pub <C as TwistedEdwardsCurve>::ScalarField);
+
+/// A twisted Edwards curve `a x^2 + y^2 = 1 + d x^2 y^2`. `ScalarField` is the order of the prime
+/// subgroup generated by `GENERATOR`, which need not be the whole group.
+pub trait TwistedEdwardsCurve: 'static + Sync + Sized + Copy + Debug {
+    type BaseField: PrimeField;
+    type ScalarField: PrimeField;
+
+    const A: Self::BaseField;
+    const D: Self::BaseField;
+
+    const GENERATOR: EdwardsPoint<Self>;
+
+    /// The addition law is complete if `a` is a square and `d` is not.
+    fn is_complete() -> bool {
+        Self::A.is_quadratic_residue() && !Self::D.is_quadratic_residue()
+    }
+}
+
+/// A point on a twisted Edwards curve, represented in affine coordinates. Unlike in short
+/// Weierstrass form, the identity `(0, 1)` is an ordinary point.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct EdwardsPoint<C: TwistedEdwardsCurve> {
+    pub x: C::BaseField,
+    pub y: C::BaseField,
+}
+
+impl<C: TwistedEdwardsCurve> EdwardsPoint<C> {
+    pub const ZERO: Self = Self {
+        x: C::BaseField::ZERO,
+        y: C::BaseField::ONE,
+    };
+
+    pub fn new(x: C::BaseField, y: C::BaseField) -> Self {
+        let point = Self { x, y };
+        debug_assert!(point.is_valid());
+        point
+    }
+
+    pub fn is_valid(&self) -> bool {
+        let Self { x, y } = *self;
+        let xx = x.square();
+        let yy = y.square();
+        C::A * xx + yy == C::BaseField::ONE + C::D * xx * yy
+    }
+
+    #[must_use]
+    pub fn double(&self) -> Self {
+        let Self { x, y } = *self;
+        let a_xx = C::A * x.square();
+        let yy = y.square();
+        let x3 = (x * y).double() / (a_xx + yy);
+        let y3 = (yy - a_xx) / (C::BaseField::TWO - a_xx - yy);
+        Self { x: x3, y: y3 }
+    }
+
+    /// The number of bytes in an encoded point: the `y` coordinate plus one bit for the sign of
+    /// `x`.
+    pub fn num_encoding_bytes() -> usize {
+        ceil_div_usize(C::BaseField::BITS + 1, 8)
+    }
+
+    /// Encodes the point as in RFC 8032: `y` in little-endian, with the most significant bit of
+    /// the last byte set to the low bit of `x`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0; Self::num_encoding_bytes()];
+        let y_bytes = self.y.to_canonical_biguint().to_bytes_le();
+        bytes[..y_bytes.len()].copy_from_slice(&y_bytes);
+        if self.x.to_canonical_biguint().is_odd() {
+            *bytes.last_mut().unwrap() |= 0x80;
+        }
+        bytes
+    }
+
+    /// Decodes a point encoded by `to_bytes`, returning `None` if the encoding is not canonical or
+    /// does not represent a point on the curve.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::num_encoding_bytes() {
+            return None;
+        }
+        let mut y_bytes = bytes.to_vec();
+        let x_odd = *y_bytes.last().unwrap() & 0x80 != 0;
+        *y_bytes.last_mut().unwrap() &= 0x7f;
+        let y_biguint = BigUint::from_bytes_le(&y_bytes);
+        if y_biguint >= C::BaseField::order() {
+            return None;
+        }
+
+        // x^2 = (1 - y^2) / (a - d y^2)
+        let y = C::BaseField::from_noncanonical_biguint(y_biguint);
+        let yy = y.square();
+        let denominator = C::A - C::D * yy;
+        if denominator.is_zero() {
+            return None;
+        }
+        let x = ((C::BaseField::ONE - yy) / denominator).sqrt()?;
+        if x.is_zero() && x_odd {
+            return None;
+        }
+        let x = if x.to_canonical_biguint().is_odd() == x_odd {
+            x
+        } else {
+            -x
+        };
+
+        Some(Self { x, y })
+    }
+}
+
+impl<C: TwistedEdwardsCurve> Add<EdwardsPoint<C>> for EdwardsPoint<C> {
+    type Output = EdwardsPoint<C>;
+
+    fn add(self, rhs: EdwardsPoint<C>) -> Self::Output {
+        let EdwardsPoint { x: x1, y: y1 } = self;
+        let EdwardsPoint { x: x2, y: y2 } = rhs;
+
+        let x1x2 = x1 * x2;
+        let y1y2 = y1 * y2;
+        let d_x1x2y1y2 = C::D * x1x2 * y1y2;
+        let x3 = (x1 * y2 + y1 * x2) / (C::BaseField::ONE + d_x1x2y1y2);
+        let y3 = (y1y2 - C::A * x1x2) / (C::BaseField::ONE - d_x1x2y1y2);
+        EdwardsPoint { x: x3, y: y3 }
+    }
+}
+
+impl<C: TwistedEdwardsCurve> Neg for EdwardsPoint<C> {
+    type Output = EdwardsPoint<C>;
+
+    fn neg(self) -> Self::Output {
+        EdwardsPoint {
+            x: -self.x,
+            y: self.y,
+        }
+    }
+}
+
+impl<C: TwistedEdwardsCurve> Mul<EdwardsPoint<C>> for EdwardsScalar<C> {
+    type Output = EdwardsPoint<C>;
+
+    fn mul(self, rhs: EdwardsPoint<C>) -> Self::Output {
+        let mut product = EdwardsPoint::ZERO;
+        let mut power = rhs;
+        for limb in self.0.to_canonical_biguint().to_u64_digits() {
+            for j in 0..64 {
+                if (limb >> j) & 1 != 0 {
+                    product = product + power;
+                }
+                power = power.double();
+            }
+        }
+        product
+    }
+}
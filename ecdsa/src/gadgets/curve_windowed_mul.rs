@@ -10,6 +10,7 @@ use plonky2::hash::keccak::KeccakHash;
 use plonky2::iop::target::{BoolTarget, Target};
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::config::{GenericHashOut, Hasher};
+use plonky2::util::ceil_div_usize;
 use plonky2_u32::gadgets::arithmetic_u32::{CircuitBuilderU32, U32Target};
 
 use crate::curve::curve_types::{Curve, CurveScalar};
@@ -75,7 +76,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderWindowedMul<F,
         access_index: Target,
         v: Vec<AffinePointTarget<C>>,
     ) -> AffinePointTarget<C> {
-        let num_limbs = C::BaseField::BITS / 32;
+        let num_limbs = ceil_div_usize(C::BaseField::BITS, 32);
         let zero = self.zero_u32();
         let x_limbs: Vec<Vec<_>> = (0..num_limbs)
             .map(|i| {
@@ -137,9 +138,13 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderWindowedMul<F,
             &GenericHashOut::<F>::to_bytes(&hash_0),
         ));
         let starting_point = CurveScalar(hash_0_scalar) * C::GENERATOR_PROJECTIVE;
+
+        // The scalar's limbs can hold more bits than the field has, and the starting point is
+        // doubled once per bit of the limbs.
+        let windows = self.split_nonnative_to_4_bit_limbs(n);
         let starting_point_multiplied = {
             let mut cur = starting_point;
-            for _ in 0..C::ScalarField::BITS {
+            for _ in 0..windows.len() * WINDOW_SIZE {
                 cur = cur.double();
             }
             cur
@@ -150,7 +155,6 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderWindowedMul<F,
         let precomputation = self.precompute_window(p);
         let zero = self.zero();
 
-        let windows = self.split_nonnative_to_4_bit_limbs(n);
         for i in (0..windows.len()).rev() {
             result = self.curve_repeated_double(&result, WINDOW_SIZE);
             let window = windows[i];
@@ -1,14 +1,12 @@
-use core::marker::PhantomData;
-
 use plonky2::field::extension::Extendable;
-use plonky2::field::secp256k1_scalar::Secp256K1Scalar;
 use plonky2::hash::hash_types::RichField;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 
 use crate::curve::curve_types::Curve;
-use crate::curve::secp256k1::Secp256K1;
+use crate::curve::glv::GlvCurve;
 use crate::gadgets::curve::{AffinePointTarget, CircuitBuilderCurve};
 use crate::gadgets::curve_fixed_base::fixed_base_curve_mul_circuit;
+use crate::gadgets::curve_windowed_mul::CircuitBuilderWindowedMul;
 use crate::gadgets::glv::CircuitBuilderGlv;
 use crate::gadgets::nonnative::{CircuitBuilderNonNative, NonNativeTarget};
 
@@ -24,11 +22,38 @@ pub struct ECDSASignatureTarget<C: Curve> {
     pub s: NonNativeTarget<C::ScalarField>,
 }
 
-pub fn verify_message_circuit<F: RichField + Extendable<D>, const D: usize>(
+/// Verifies an ECDSA signature, using the curve's endomorphism to multiply the public key.
+pub fn verify_message_circuit<F: RichField + Extendable<D>, const D: usize, C: GlvCurve>(
+    builder: &mut CircuitBuilder<F, D>,
+    msg: NonNativeTarget<C::ScalarField>,
+    sig: ECDSASignatureTarget<C>,
+    pk: ECDSAPublicKeyTarget<C>,
+) {
+    verify_message_circuit_with(builder, msg, sig, pk, |builder, p, k| builder.glv_mul(p, k));
+}
+
+/// Verifies an ECDSA signature on a curve without an efficient endomorphism, such as secp256r1.
+pub fn verify_message_circuit_windowed<F: RichField + Extendable<D>, const D: usize, C: Curve>(
     builder: &mut CircuitBuilder<F, D>,
-    msg: NonNativeTarget<Secp256K1Scalar>,
-    sig: ECDSASignatureTarget<Secp256K1>,
-    pk: ECDSAPublicKeyTarget<Secp256K1>,
+    msg: NonNativeTarget<C::ScalarField>,
+    sig: ECDSASignatureTarget<C>,
+    pk: ECDSAPublicKeyTarget<C>,
+) {
+    verify_message_circuit_with(builder, msg, sig, pk, |builder, p, k| {
+        builder.curve_scalar_mul_windowed(p, k)
+    });
+}
+
+fn verify_message_circuit_with<F: RichField + Extendable<D>, const D: usize, C: Curve>(
+    builder: &mut CircuitBuilder<F, D>,
+    msg: NonNativeTarget<C::ScalarField>,
+    sig: ECDSASignatureTarget<C>,
+    pk: ECDSAPublicKeyTarget<C>,
+    pk_mul: impl FnOnce(
+        &mut CircuitBuilder<F, D>,
+        &AffinePointTarget<C>,
+        &NonNativeTarget<C::ScalarField>,
+    ) -> AffinePointTarget<C>,
 ) {
     let ECDSASignatureTarget { r, s } = sig;
 
@@ -38,20 +63,19 @@ pub fn verify_message_circuit<F: RichField + Extendable<D>, const D: usize>(
     let u1 = builder.mul_nonnative(&msg, &c);
     let u2 = builder.mul_nonnative(&r, &c);
 
-    let point1 = fixed_base_curve_mul_circuit(builder, Secp256K1::GENERATOR_AFFINE, &u1);
-    let point2 = builder.glv_mul(&pk.0, &u2);
+    let point1 = fixed_base_curve_mul_circuit(builder, C::GENERATOR_AFFINE, &u1);
+    let point2 = pk_mul(builder, &pk.0, &u2);
     let point = builder.curve_add(&point1, &point2);
 
-    let x = NonNativeTarget::<Secp256K1Scalar> {
-        value: point.x.value,
-        _phantom: PhantomData,
-    };
+    // `r` is the x-coordinate of the point, reduced modulo the order of the curve.
+    let x = builder.reduce::<C::ScalarField>(&point.x.value);
     builder.connect_nonnative(&r, &x);
 }
 
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
+    use plonky2::field::secp256k1_scalar::Secp256K1Scalar;
     use plonky2::field::types::Sample;
     use plonky2::iop::witness::PartialWitness;
     use plonky2::plonk::circuit_data::CircuitConfig;
@@ -59,7 +83,9 @@ mod tests {
 
     use super::*;
     use crate::curve::curve_types::CurveScalar;
+    use crate::curve::ecdsa::tests::p256_test_vector;
     use crate::curve::ecdsa::{sign_message, ECDSAPublicKey, ECDSASecretKey, ECDSASignature};
+    use crate::curve::secp256k1::Secp256K1;
 
     fn test_ecdsa_circuit_with_config(config: CircuitConfig) -> Result<()> {
         const D: usize = 2;
@@ -97,6 +123,31 @@ mod tests {
         data.verify(proof)
     }
 
+    fn test_ecdsa_circuit_secp256r1_with_config(config: CircuitConfig) -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let (msg, sig, pk) = p256_test_vector();
+
+        let msg_target = builder.constant_nonnative(msg);
+        let pk_target = ECDSAPublicKeyTarget(builder.constant_affine_point(pk.0));
+        let sig_target = ECDSASignatureTarget {
+            r: builder.constant_nonnative(sig.r),
+            s: builder.constant_nonnative(sig.s),
+        };
+
+        verify_message_circuit_windowed(&mut builder, msg_target, sig_target, pk_target);
+
+        dbg!(builder.num_gates());
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof)
+    }
+
     #[test]
     #[ignore]
     fn test_ecdsa_circuit_narrow() -> Result<()> {
@@ -108,4 +159,16 @@ mod tests {
     fn test_ecdsa_circuit_wide() -> Result<()> {
         test_ecdsa_circuit_with_config(CircuitConfig::wide_ecc_config())
     }
+
+    #[test]
+    #[ignore]
+    fn test_ecdsa_circuit_secp256r1_narrow() -> Result<()> {
+        test_ecdsa_circuit_secp256r1_with_config(CircuitConfig::standard_ecc_config())
+    }
+
+    #[test]
+    #[ignore]
+    fn test_ecdsa_circuit_secp256r1_wide() -> Result<()> {
+        test_ecdsa_circuit_secp256r1_with_config(CircuitConfig::wide_ecc_config())
+    }
 }
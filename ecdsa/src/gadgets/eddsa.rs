@@ -0,0 +1,211 @@
+use alloc::vec::Vec;
+
+use num::{BigUint, One};
+use plonky2::field::ed25519_scalar::Ed25519Scalar;
+use plonky2::field::extension::Extendable;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::BoolTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2_u32::gadgets::arithmetic_u32::U32Target;
+use plonky2_u32::gadgets::sha512::CircuitBuilderSha512;
+
+use crate::curve::ed25519::Ed25519;
+use crate::curve::twisted_edwards::TwistedEdwardsCurve;
+use crate::gadgets::biguint::{BigUintTarget, CircuitBuilderBiguint};
+use crate::gadgets::nonnative::{CircuitBuilderNonNative, NonNativeTarget};
+use crate::gadgets::twisted_edwards::{CircuitBuilderTwistedEdwards, EdwardsPointTarget};
+
+#[derive(Clone, Debug)]
+pub struct EdDSAPublicKeyTarget<C: TwistedEdwardsCurve>(pub EdwardsPointTarget<C>);
+
+#[derive(Clone, Debug)]
+pub struct EdDSASignatureTarget<C: TwistedEdwardsCurve> {
+    pub r: EdwardsPointTarget<C>,
+    pub s: NonNativeTarget<C::ScalarField>,
+}
+
+/// Asserts that `x` is less than the order of `FF`.
+fn assert_nonnative_reduced<F: RichField + Extendable<D>, const D: usize, FF: Field>(
+    builder: &mut CircuitBuilder<F, D>,
+    x: &NonNativeTarget<FF>,
+) {
+    let max = builder.constant_biguint(&(FF::order() - BigUint::one()));
+    let cmp = builder.cmp_biguint(&x.value, &max);
+    builder.assert_one(cmp.target);
+}
+
+/// Returns the 32-byte encoding of `p` from RFC 8032 as bits, most significant bit of each byte
+/// first, as `sha512` expects. Asserts that the coordinates of `p` are reduced.
+fn encode_point_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    p: &EdwardsPointTarget<Ed25519>,
+) -> Vec<BoolTarget> {
+    assert_nonnative_reduced(builder, &p.x);
+    assert_nonnative_reduced(builder, &p.y);
+
+    // Since `y < 2^255`, its top bit is free to hold the low bit of `x`.
+    let mut bits = builder.split_nonnative_to_bits(&p.y);
+    bits.resize(255, builder._false());
+    let x_bits = builder.split_nonnative_to_bits(&p.x);
+    let x_low_bit = x_bits.first().copied().unwrap_or_else(|| builder._false());
+    bits.push(x_low_bit);
+
+    bits.chunks(8)
+        .flat_map(|byte| byte.iter().rev().copied())
+        .collect()
+}
+
+/// Reverses the byte order of a 32-bit value.
+fn swap_bytes_u32<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    x: U32Target,
+) -> U32Target {
+    let bits = builder.split_le(x.0, 32);
+    let swapped = bits.chunks(8).rev().flatten();
+    U32Target(builder.le_sum(swapped))
+}
+
+/// Computes the Ed25519 challenge `SHA-512(R || A || M) mod l` in-circuit, where `msg` is given as
+/// bits in the order taken by `sha512`. Asserts that the coordinates of `r` and `pk` are reduced,
+/// so that their encodings are canonical.
+pub fn ed25519_challenge_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    r: &EdwardsPointTarget<Ed25519>,
+    pk: &EdDSAPublicKeyTarget<Ed25519>,
+    msg: &[BoolTarget],
+) -> NonNativeTarget<Ed25519Scalar> {
+    let mut hash_input = encode_point_circuit(builder, r);
+    hash_input.extend(encode_point_circuit(builder, &pk.0));
+    hash_input.extend_from_slice(msg);
+    let digest = builder.sha512(&hash_input);
+
+    // The digest is read as a little-endian integer, while `sha512` returns big-endian words.
+    let limbs = digest
+        .iter()
+        .flat_map(|word| [word.hi(), word.lo()])
+        .map(|limb| swap_bytes_u32(builder, limb))
+        .collect();
+    builder.reduce(&BigUintTarget { limbs })
+}
+
+/// Verifies an Ed25519 signature on `msg`, given as bits in the order taken by `sha512`. This
+/// checks the cofactorless equation `[S]B = R + [k]A`, and like RFC 8032 requires `S < l`.
+pub fn verify_eddsa_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    msg: &[BoolTarget],
+    sig: EdDSASignatureTarget<Ed25519>,
+    pk: EdDSAPublicKeyTarget<Ed25519>,
+) {
+    let EdDSASignatureTarget { r, s } = sig;
+
+    builder.edwards_assert_valid(&pk.0);
+    builder.edwards_assert_valid(&r);
+    assert_nonnative_reduced(builder, &s);
+
+    let k = ed25519_challenge_circuit(builder, &r, &pk, msg);
+
+    // [S]B - [k]A
+    let g = builder.constant_edwards_point(Ed25519::GENERATOR);
+    let neg_pk = builder.edwards_neg(&pk.0);
+    let point = builder.edwards_double_scalar_mul(&g, &s, &neg_pk, &k);
+
+    // The coordinates of `R` are reduced, so reduce those of the result before comparing.
+    let x = builder.reduce_nonnative(&point.x);
+    let y = builder.reduce_nonnative(&point.y);
+    builder.connect_nonnative(&r.x, &x);
+    builder.connect_nonnative(&r.y, &y);
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use anyhow::Result;
+    use plonky2::iop::witness::PartialWitness;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    use super::*;
+    use crate::curve::eddsa::tests::{hex_to_bytes, RFC_8032_VECTORS};
+    use crate::curve::eddsa::{ed25519_challenge, EdDSAPublicKey, EdDSASignature};
+    use crate::curve::twisted_edwards::EdwardsPoint;
+
+    fn bytes_to_bits<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        bytes: &[u8],
+    ) -> Vec<BoolTarget> {
+        bytes
+            .iter()
+            .flat_map(|&byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1))
+            .map(|bit| builder.constant_bool(bit))
+            .collect()
+    }
+
+    #[test]
+    fn test_ed25519_challenge_circuit() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let (pk, msg, sig) = RFC_8032_VECTORS[2];
+        let pk = EdDSAPublicKey(EdwardsPoint::<Ed25519>::from_bytes(&hex_to_bytes(pk)).unwrap());
+        let msg = hex_to_bytes(msg);
+        let sig = EdDSASignature::<Ed25519>::from_bytes(&hex_to_bytes(sig)).unwrap();
+
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_ecc_config());
+
+        let r_target = builder.constant_edwards_point(sig.r);
+        let pk_target = EdDSAPublicKeyTarget(builder.constant_edwards_point(pk.0));
+        let msg_target = bytes_to_bits(&mut builder, &msg);
+
+        let k_target = ed25519_challenge_circuit(&mut builder, &r_target, &pk_target, &msg_target);
+        let expected_k = builder.constant_nonnative(ed25519_challenge(sig.r, pk, &msg));
+        builder.connect_nonnative(&k_target, &expected_k);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof)
+    }
+
+    fn test_eddsa_circuit_with_config(config: CircuitConfig) -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let (pk, msg, sig) = RFC_8032_VECTORS[0];
+        let pk = EdDSAPublicKey(EdwardsPoint::<Ed25519>::from_bytes(&hex_to_bytes(pk)).unwrap());
+        let msg = hex_to_bytes(msg);
+        let sig = EdDSASignature::<Ed25519>::from_bytes(&hex_to_bytes(sig)).unwrap();
+
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let msg_target = bytes_to_bits(&mut builder, &msg);
+        let pk_target = EdDSAPublicKeyTarget(builder.constant_edwards_point(pk.0));
+        let sig_target = EdDSASignatureTarget {
+            r: builder.constant_edwards_point(sig.r),
+            s: builder.constant_nonnative(sig.s),
+        };
+
+        verify_eddsa_circuit(&mut builder, &msg_target, sig_target, pk_target);
+
+        dbg!(builder.num_gates());
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof)
+    }
+
+    #[test]
+    #[ignore]
+    fn test_eddsa_circuit_narrow() -> Result<()> {
+        test_eddsa_circuit_with_config(CircuitConfig::standard_ecc_config())
+    }
+
+    #[test]
+    #[ignore]
+    fn test_eddsa_circuit_wide() -> Result<()> {
+        test_eddsa_circuit_with_config(CircuitConfig::wide_ecc_config())
+    }
+}
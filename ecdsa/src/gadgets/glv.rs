@@ -2,64 +2,62 @@ use alloc::vec::Vec;
 use core::marker::PhantomData;
 
 use plonky2::field::extension::Extendable;
-use plonky2::field::secp256k1_base::Secp256K1Base;
-use plonky2::field::secp256k1_scalar::Secp256K1Scalar;
 use plonky2::field::types::{Field, PrimeField};
 use plonky2::hash::hash_types::RichField;
 use plonky2::iop::generator::{GeneratedValues, SimpleGenerator};
 use plonky2::iop::target::{BoolTarget, Target};
 use plonky2::iop::witness::{PartitionWitness, WitnessWrite};
 use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::util::serialization::{Buffer, IoResult, Read, Write};
 
-use crate::curve::glv::{decompose_secp256k1_scalar, GLV_BETA, GLV_S};
-use crate::curve::secp256k1::Secp256K1;
+use crate::curve::glv::{decompose_glv_scalar, GlvCurve};
 use crate::gadgets::biguint::{GeneratedValuesBigUint, WitnessBigUint};
 use crate::gadgets::curve::{AffinePointTarget, CircuitBuilderCurve};
 use crate::gadgets::curve_msm::curve_msm_circuit;
 use crate::gadgets::nonnative::{CircuitBuilderNonNative, NonNativeTarget};
 
 pub trait CircuitBuilderGlv<F: RichField + Extendable<D>, const D: usize> {
-    fn secp256k1_glv_beta(&mut self) -> NonNativeTarget<Secp256K1Base>;
+    fn glv_beta<C: GlvCurve>(&mut self) -> NonNativeTarget<C::BaseField>;
 
-    fn decompose_secp256k1_scalar(
+    fn decompose_glv_scalar<C: GlvCurve>(
         &mut self,
-        k: &NonNativeTarget<Secp256K1Scalar>,
+        k: &NonNativeTarget<C::ScalarField>,
     ) -> (
-        NonNativeTarget<Secp256K1Scalar>,
-        NonNativeTarget<Secp256K1Scalar>,
+        NonNativeTarget<C::ScalarField>,
+        NonNativeTarget<C::ScalarField>,
         BoolTarget,
         BoolTarget,
     );
 
-    fn glv_mul(
+    fn glv_mul<C: GlvCurve>(
         &mut self,
-        p: &AffinePointTarget<Secp256K1>,
-        k: &NonNativeTarget<Secp256K1Scalar>,
-    ) -> AffinePointTarget<Secp256K1>;
+        p: &AffinePointTarget<C>,
+        k: &NonNativeTarget<C::ScalarField>,
+    ) -> AffinePointTarget<C>;
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderGlv<F, D>
     for CircuitBuilder<F, D>
 {
-    fn secp256k1_glv_beta(&mut self) -> NonNativeTarget<Secp256K1Base> {
-        self.constant_nonnative(GLV_BETA)
+    fn glv_beta<C: GlvCurve>(&mut self) -> NonNativeTarget<C::BaseField> {
+        self.constant_nonnative(C::GLV_BETA)
     }
 
-    fn decompose_secp256k1_scalar(
+    fn decompose_glv_scalar<C: GlvCurve>(
         &mut self,
-        k: &NonNativeTarget<Secp256K1Scalar>,
+        k: &NonNativeTarget<C::ScalarField>,
     ) -> (
-        NonNativeTarget<Secp256K1Scalar>,
-        NonNativeTarget<Secp256K1Scalar>,
+        NonNativeTarget<C::ScalarField>,
+        NonNativeTarget<C::ScalarField>,
         BoolTarget,
         BoolTarget,
     ) {
-        let k1 = self.add_virtual_nonnative_target_sized::<Secp256K1Scalar>(4);
-        let k2 = self.add_virtual_nonnative_target_sized::<Secp256K1Scalar>(4);
+        let k1 = self.add_virtual_nonnative_target_sized::<C::ScalarField>(4);
+        let k2 = self.add_virtual_nonnative_target_sized::<C::ScalarField>(4);
         let k1_neg = self.add_virtual_bool_target_unsafe();
         let k2_neg = self.add_virtual_bool_target_unsafe();
 
-        self.add_simple_generator(GLVDecompositionGenerator::<F, D> {
+        self.add_simple_generator(GLVDecompositionGenerator::<F, D, C> {
             k: k.clone(),
             k1: k1.clone(),
             k2: k2.clone(),
@@ -71,7 +69,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderGlv<F, D>
         // Check that `k1_raw + GLV_S * k2_raw == k`.
         let k1_raw = self.nonnative_conditional_neg(&k1, k1_neg);
         let k2_raw = self.nonnative_conditional_neg(&k2, k2_neg);
-        let s = self.constant_nonnative(GLV_S);
+        let s = self.constant_nonnative(C::GLV_S);
         let mut should_be_k = self.mul_nonnative(&s, &k2_raw);
         should_be_k = self.add_nonnative(&should_be_k, &k1_raw);
         self.connect_nonnative(&should_be_k, k);
@@ -79,16 +77,16 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderGlv<F, D>
         (k1, k2, k1_neg, k2_neg)
     }
 
-    fn glv_mul(
+    fn glv_mul<C: GlvCurve>(
         &mut self,
-        p: &AffinePointTarget<Secp256K1>,
-        k: &NonNativeTarget<Secp256K1Scalar>,
-    ) -> AffinePointTarget<Secp256K1> {
-        let (k1, k2, k1_neg, k2_neg) = self.decompose_secp256k1_scalar(k);
+        p: &AffinePointTarget<C>,
+        k: &NonNativeTarget<C::ScalarField>,
+    ) -> AffinePointTarget<C> {
+        let (k1, k2, k1_neg, k2_neg) = self.decompose_glv_scalar::<C>(k);
 
-        let beta = self.secp256k1_glv_beta();
+        let beta = self.glv_beta::<C>();
         let beta_px = self.mul_nonnative(&beta, &p.x);
-        let sp = AffinePointTarget::<Secp256K1> {
+        let sp = AffinePointTarget::<C> {
             x: beta_px,
             y: p.y.clone(),
         };
@@ -100,56 +98,78 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderGlv<F, D>
 }
 
 #[derive(Debug)]
-struct GLVDecompositionGenerator<F: RichField + Extendable<D>, const D: usize> {
-    k: NonNativeTarget<Secp256K1Scalar>,
-    k1: NonNativeTarget<Secp256K1Scalar>,
-    k2: NonNativeTarget<Secp256K1Scalar>,
+pub struct GLVDecompositionGenerator<F: RichField + Extendable<D>, const D: usize, C: GlvCurve> {
+    k: NonNativeTarget<C::ScalarField>,
+    k1: NonNativeTarget<C::ScalarField>,
+    k2: NonNativeTarget<C::ScalarField>,
     k1_neg: BoolTarget,
     k2_neg: BoolTarget,
     _phantom: PhantomData<F>,
 }
 
-impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F>
-    for GLVDecompositionGenerator<F, D>
+impl<F: RichField + Extendable<D>, const D: usize, C: GlvCurve> SimpleGenerator<F>
+    for GLVDecompositionGenerator<F, D, C>
 {
     fn dependencies(&self) -> Vec<Target> {
         self.k.value.limbs.iter().map(|l| l.0).collect()
     }
 
     fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
-        let k = Secp256K1Scalar::from_noncanonical_biguint(
+        let k = C::ScalarField::from_noncanonical_biguint(
             witness.get_biguint_target(self.k.value.clone()),
         );
 
-        let (k1, k2, k1_neg, k2_neg) = decompose_secp256k1_scalar(k);
+        let (k1, k2, k1_neg, k2_neg) = decompose_glv_scalar::<C>(k);
 
         out_buffer.set_biguint_target(&self.k1.value, &k1.to_canonical_biguint());
         out_buffer.set_biguint_target(&self.k2.value, &k2.to_canonical_biguint());
         out_buffer.set_bool_target(self.k1_neg, k1_neg);
         out_buffer.set_bool_target(self.k2_neg, k2_neg);
     }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        self.k.serialize(dst)?;
+        self.k1.serialize(dst)?;
+        self.k2.serialize(dst)?;
+        dst.write_bool_target(self.k1_neg)?;
+        dst.write_bool_target(self.k2_neg)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let k = NonNativeTarget::deserialize(src)?;
+        let k1 = NonNativeTarget::deserialize(src)?;
+        let k2 = NonNativeTarget::deserialize(src)?;
+        let k1_neg = src.read_bool_target()?;
+        let k2_neg = src.read_bool_target()?;
+        Ok(Self {
+            k,
+            k1,
+            k2,
+            k1_neg,
+            k2_neg,
+            _phantom: PhantomData,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
-    use plonky2::field::secp256k1_scalar::Secp256K1Scalar;
     use plonky2::field::types::Sample;
     use plonky2::iop::witness::PartialWitness;
     use plonky2::plonk::circuit_builder::CircuitBuilder;
     use plonky2::plonk::circuit_data::CircuitConfig;
     use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
 
-    use crate::curve::curve_types::{Curve, CurveScalar};
-    use crate::curve::glv::glv_mul;
+    use crate::curve::bn254::Bn254;
+    use crate::curve::curve_types::CurveScalar;
+    use crate::curve::glv::{glv_mul, GlvCurve};
     use crate::curve::secp256k1::Secp256K1;
     use crate::gadgets::curve::CircuitBuilderCurve;
     use crate::gadgets::glv::CircuitBuilderGlv;
     use crate::gadgets::nonnative::CircuitBuilderNonNative;
 
-    #[test]
-    #[ignore]
-    fn test_glv_gadget() -> Result<()> {
+    fn test_glv_gadget<Curve: GlvCurve>() -> Result<()> {
         const D: usize = 2;
         type C = PoseidonGoldilocksConfig;
         type F = <C as GenericConfig<D>>::F;
@@ -160,10 +180,10 @@ mod tests {
         let mut builder = CircuitBuilder::<F, D>::new(config);
 
         let rando =
-            (CurveScalar(Secp256K1Scalar::rand()) * Secp256K1::GENERATOR_PROJECTIVE).to_affine();
+            (CurveScalar(Curve::ScalarField::rand()) * Curve::GENERATOR_PROJECTIVE).to_affine();
         let randot = builder.constant_affine_point(rando);
 
-        let scalar = Secp256K1Scalar::rand();
+        let scalar = Curve::ScalarField::rand();
         let scalar_target = builder.constant_nonnative(scalar);
 
         let rando_glv_scalar = glv_mul(rando.to_projective(), scalar);
@@ -177,4 +197,16 @@ mod tests {
 
         data.verify(proof)
     }
+
+    #[test]
+    #[ignore]
+    fn test_glv_gadget_secp256k1() -> Result<()> {
+        test_glv_gadget::<Secp256K1>()
+    }
+
+    #[test]
+    #[ignore]
+    fn test_glv_gadget_bn254() -> Result<()> {
+        test_glv_gadget::<Bn254>()
+    }
 }
@@ -4,6 +4,8 @@ pub mod curve_fixed_base;
 pub mod curve_msm;
 pub mod curve_windowed_mul;
 pub mod ecdsa;
+pub mod eddsa;
 pub mod glv;
 pub mod nonnative;
 pub mod split_nonnative;
+pub mod twisted_edwards;
@@ -0,0 +1,408 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use plonky2::field::extension::Extendable;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::BoolTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use crate::curve::twisted_edwards::{EdwardsPoint, TwistedEdwardsCurve};
+use crate::gadgets::nonnative::{CircuitBuilderNonNative, NonNativeTarget};
+
+/// A Target representing an affine point on the twisted Edwards curve `C`. The addition law is
+/// complete, so unlike `AffinePointTarget`, the identity and equal or opposite summands need no
+/// special handling.
+#[derive(Clone, Debug)]
+pub struct EdwardsPointTarget<C: TwistedEdwardsCurve> {
+    pub x: NonNativeTarget<C::BaseField>,
+    pub y: NonNativeTarget<C::BaseField>,
+}
+
+impl<C: TwistedEdwardsCurve> EdwardsPointTarget<C> {
+    pub fn to_vec(&self) -> Vec<NonNativeTarget<C::BaseField>> {
+        vec![self.x.clone(), self.y.clone()]
+    }
+}
+
+pub trait CircuitBuilderTwistedEdwards<F: RichField + Extendable<D>, const D: usize> {
+    fn constant_edwards_point<C: TwistedEdwardsCurve>(
+        &mut self,
+        point: EdwardsPoint<C>,
+    ) -> EdwardsPointTarget<C>;
+
+    fn connect_edwards_point<C: TwistedEdwardsCurve>(
+        &mut self,
+        lhs: &EdwardsPointTarget<C>,
+        rhs: &EdwardsPointTarget<C>,
+    );
+
+    fn add_virtual_edwards_point_target<C: TwistedEdwardsCurve>(&mut self)
+        -> EdwardsPointTarget<C>;
+
+    fn edwards_assert_valid<C: TwistedEdwardsCurve>(&mut self, p: &EdwardsPointTarget<C>);
+
+    fn edwards_neg<C: TwistedEdwardsCurve>(
+        &mut self,
+        p: &EdwardsPointTarget<C>,
+    ) -> EdwardsPointTarget<C>;
+
+    /// Returns `p1` if `b` is true, otherwise `p2`.
+    fn if_edwards_point<C: TwistedEdwardsCurve>(
+        &mut self,
+        b: BoolTarget,
+        p1: &EdwardsPointTarget<C>,
+        p2: &EdwardsPointTarget<C>,
+    ) -> EdwardsPointTarget<C>;
+
+    fn edwards_double<C: TwistedEdwardsCurve>(
+        &mut self,
+        p: &EdwardsPointTarget<C>,
+    ) -> EdwardsPointTarget<C>;
+
+    fn edwards_add<C: TwistedEdwardsCurve>(
+        &mut self,
+        p1: &EdwardsPointTarget<C>,
+        p2: &EdwardsPointTarget<C>,
+    ) -> EdwardsPointTarget<C>;
+
+    fn edwards_scalar_mul<C: TwistedEdwardsCurve>(
+        &mut self,
+        p: &EdwardsPointTarget<C>,
+        n: &NonNativeTarget<C::ScalarField>,
+    ) -> EdwardsPointTarget<C>;
+
+    /// Computes `n1 p1 + n2 p2` with a shared chain of doublings.
+    fn edwards_double_scalar_mul<C: TwistedEdwardsCurve>(
+        &mut self,
+        p1: &EdwardsPointTarget<C>,
+        n1: &NonNativeTarget<C::ScalarField>,
+        p2: &EdwardsPointTarget<C>,
+        n2: &NonNativeTarget<C::ScalarField>,
+    ) -> EdwardsPointTarget<C>;
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderTwistedEdwards<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn constant_edwards_point<C: TwistedEdwardsCurve>(
+        &mut self,
+        point: EdwardsPoint<C>,
+    ) -> EdwardsPointTarget<C> {
+        debug_assert!(point.is_valid());
+        EdwardsPointTarget {
+            x: self.constant_nonnative(point.x),
+            y: self.constant_nonnative(point.y),
+        }
+    }
+
+    fn connect_edwards_point<C: TwistedEdwardsCurve>(
+        &mut self,
+        lhs: &EdwardsPointTarget<C>,
+        rhs: &EdwardsPointTarget<C>,
+    ) {
+        self.connect_nonnative(&lhs.x, &rhs.x);
+        self.connect_nonnative(&lhs.y, &rhs.y);
+    }
+
+    fn add_virtual_edwards_point_target<C: TwistedEdwardsCurve>(
+        &mut self,
+    ) -> EdwardsPointTarget<C> {
+        let x = self.add_virtual_nonnative_target();
+        let y = self.add_virtual_nonnative_target();
+
+        EdwardsPointTarget { x, y }
+    }
+
+    fn edwards_assert_valid<C: TwistedEdwardsCurve>(&mut self, p: &EdwardsPointTarget<C>) {
+        let a = self.constant_nonnative(C::A);
+        let d = self.constant_nonnative(C::D);
+        let one = self.constant_nonnative(C::BaseField::ONE);
+
+        let x_squared = self.mul_nonnative(&p.x, &p.x);
+        let y_squared = self.mul_nonnative(&p.y, &p.y);
+        let a_x_squared = self.mul_nonnative(&a, &x_squared);
+        let lhs = self.add_nonnative(&a_x_squared, &y_squared);
+        let x_squared_y_squared = self.mul_nonnative(&x_squared, &y_squared);
+        let d_x_squared_y_squared = self.mul_nonnative(&d, &x_squared_y_squared);
+        let rhs = self.add_nonnative(&one, &d_x_squared_y_squared);
+
+        self.connect_nonnative(&lhs, &rhs);
+    }
+
+    fn edwards_neg<C: TwistedEdwardsCurve>(
+        &mut self,
+        p: &EdwardsPointTarget<C>,
+    ) -> EdwardsPointTarget<C> {
+        let neg_x = self.neg_nonnative(&p.x);
+        EdwardsPointTarget {
+            x: neg_x,
+            y: p.y.clone(),
+        }
+    }
+
+    fn if_edwards_point<C: TwistedEdwardsCurve>(
+        &mut self,
+        b: BoolTarget,
+        p1: &EdwardsPointTarget<C>,
+        p2: &EdwardsPointTarget<C>,
+    ) -> EdwardsPointTarget<C> {
+        EdwardsPointTarget {
+            x: self.if_nonnative(b, &p1.x, &p2.x),
+            y: self.if_nonnative(b, &p1.y, &p2.y),
+        }
+    }
+
+    fn edwards_double<C: TwistedEdwardsCurve>(
+        &mut self,
+        p: &EdwardsPointTarget<C>,
+    ) -> EdwardsPointTarget<C> {
+        let EdwardsPointTarget { x, y } = p;
+        let a = self.constant_nonnative(C::A);
+        let two = self.constant_nonnative(C::BaseField::TWO);
+
+        let x_squared = self.mul_nonnative(x, x);
+        let y_squared = self.mul_nonnative(y, y);
+        let xy = self.mul_nonnative(x, y);
+        let a_x_squared = self.mul_nonnative(&a, &x_squared);
+
+        // x3 = 2xy / (a x^2 + y^2)
+        let x3_numerator = self.add_nonnative(&xy, &xy);
+        let x3_denominator = self.add_nonnative(&a_x_squared, &y_squared);
+        let x3_denominator_inv = self.inv_nonnative(&x3_denominator);
+        let x3 = self.mul_nonnative(&x3_numerator, &x3_denominator_inv);
+
+        // y3 = (y^2 - a x^2) / (2 - a x^2 - y^2)
+        let y3_numerator = self.sub_nonnative(&y_squared, &a_x_squared);
+        let y3_denominator = self.sub_nonnative(&two, &x3_denominator);
+        let y3_denominator_inv = self.inv_nonnative(&y3_denominator);
+        let y3 = self.mul_nonnative(&y3_numerator, &y3_denominator_inv);
+
+        EdwardsPointTarget { x: x3, y: y3 }
+    }
+
+    fn edwards_add<C: TwistedEdwardsCurve>(
+        &mut self,
+        p1: &EdwardsPointTarget<C>,
+        p2: &EdwardsPointTarget<C>,
+    ) -> EdwardsPointTarget<C> {
+        let EdwardsPointTarget { x: x1, y: y1 } = p1;
+        let EdwardsPointTarget { x: x2, y: y2 } = p2;
+        let a = self.constant_nonnative(C::A);
+        let d = self.constant_nonnative(C::D);
+        let one = self.constant_nonnative(C::BaseField::ONE);
+
+        let x1y2 = self.mul_nonnative(x1, y2);
+        let y1x2 = self.mul_nonnative(y1, x2);
+        let x1x2 = self.mul_nonnative(x1, x2);
+        let y1y2 = self.mul_nonnative(y1, y2);
+        let x1x2y1y2 = self.mul_nonnative(&x1x2, &y1y2);
+        let d_x1x2y1y2 = self.mul_nonnative(&d, &x1x2y1y2);
+        let a_x1x2 = self.mul_nonnative(&a, &x1x2);
+
+        // x3 = (x1 y2 + y1 x2) / (1 + d x1 x2 y1 y2)
+        let x3_numerator = self.add_nonnative(&x1y2, &y1x2);
+        let x3_denominator = self.add_nonnative(&one, &d_x1x2y1y2);
+        let x3_denominator_inv = self.inv_nonnative(&x3_denominator);
+        let x3 = self.mul_nonnative(&x3_numerator, &x3_denominator_inv);
+
+        // y3 = (y1 y2 - a x1 x2) / (1 - d x1 x2 y1 y2)
+        let y3_numerator = self.sub_nonnative(&y1y2, &a_x1x2);
+        let y3_denominator = self.sub_nonnative(&one, &d_x1x2y1y2);
+        let y3_denominator_inv = self.inv_nonnative(&y3_denominator);
+        let y3 = self.mul_nonnative(&y3_numerator, &y3_denominator_inv);
+
+        EdwardsPointTarget { x: x3, y: y3 }
+    }
+
+    fn edwards_scalar_mul<C: TwistedEdwardsCurve>(
+        &mut self,
+        p: &EdwardsPointTarget<C>,
+        n: &NonNativeTarget<C::ScalarField>,
+    ) -> EdwardsPointTarget<C> {
+        let bits = self.split_nonnative_to_bits(n);
+
+        let mut result = self.constant_edwards_point(EdwardsPoint::ZERO);
+        for &bit in bits.iter().rev() {
+            result = self.edwards_double(&result);
+            let sum = self.edwards_add(&result, p);
+            result = self.if_edwards_point(bit, &sum, &result);
+        }
+
+        result
+    }
+
+    fn edwards_double_scalar_mul<C: TwistedEdwardsCurve>(
+        &mut self,
+        p1: &EdwardsPointTarget<C>,
+        n1: &NonNativeTarget<C::ScalarField>,
+        p2: &EdwardsPointTarget<C>,
+        n2: &NonNativeTarget<C::ScalarField>,
+    ) -> EdwardsPointTarget<C> {
+        let bits1 = self.split_nonnative_to_bits(n1);
+        let bits2 = self.split_nonnative_to_bits(n2);
+        assert_eq!(bits1.len(), bits2.len());
+
+        let zero = self.constant_edwards_point(EdwardsPoint::ZERO);
+        let p1_plus_p2 = self.edwards_add(p1, p2);
+
+        let mut result = zero.clone();
+        for (&bit1, &bit2) in bits1.iter().zip(&bits2).rev() {
+            result = self.edwards_double(&result);
+            let if_bit1 = self.if_edwards_point(bit1, &p1_plus_p2, p2);
+            let if_not_bit1 = self.if_edwards_point(bit1, p1, &zero);
+            let to_add = self.if_edwards_point(bit2, &if_bit1, &if_not_bit1);
+            result = self.edwards_add(&result, &to_add);
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use plonky2::field::ed25519_scalar::Ed25519Scalar;
+    use plonky2::field::types::{Field, Sample};
+    use plonky2::iop::witness::PartialWitness;
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    use crate::curve::ed25519::Ed25519;
+    use crate::curve::twisted_edwards::{EdwardsPoint, EdwardsScalar, TwistedEdwardsCurve};
+    use crate::gadgets::nonnative::CircuitBuilderNonNative;
+    use crate::gadgets::twisted_edwards::CircuitBuilderTwistedEdwards;
+
+    #[test]
+    fn test_edwards_point_is_valid() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_ecc_config();
+
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let g = Ed25519::GENERATOR;
+        let g_target = builder.constant_edwards_point(g);
+        let neg_g_target = builder.edwards_neg(&g_target);
+        let zero_target = builder.constant_edwards_point(EdwardsPoint::<Ed25519>::ZERO);
+
+        builder.edwards_assert_valid(&g_target);
+        builder.edwards_assert_valid(&neg_g_target);
+        builder.edwards_assert_valid(&zero_target);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+
+        data.verify(proof)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_edwards_point_is_not_valid() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_ecc_config();
+
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let g = Ed25519::GENERATOR;
+        let not_g = EdwardsPoint::<Ed25519> {
+            x: g.x,
+            y: g.y + <Ed25519 as TwistedEdwardsCurve>::BaseField::ONE,
+        };
+        let not_g_target = builder.constant_edwards_point(not_g);
+
+        builder.edwards_assert_valid(&not_g_target);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+
+        data.verify(proof).unwrap()
+    }
+
+    #[test]
+    fn test_edwards_add_and_double() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_ecc_config();
+
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let g = Ed25519::GENERATOR;
+        let p = EdwardsScalar(Ed25519Scalar::rand()) * g;
+        let g_target = builder.constant_edwards_point(g);
+        let p_target = builder.constant_edwards_point(p);
+        let zero_target = builder.constant_edwards_point(EdwardsPoint::<Ed25519>::ZERO);
+        let neg_g_target = builder.edwards_neg(&g_target);
+
+        let sum = builder.edwards_add(&g_target, &p_target);
+        let expected_sum = builder.constant_edwards_point(g + p);
+        builder.connect_edwards_point(&sum, &expected_sum);
+
+        // The addition law is complete, so it also handles doubling, the identity and inverses.
+        let double_g = builder.edwards_double(&g_target);
+        let g_plus_g = builder.edwards_add(&g_target, &g_target);
+        let expected_double_g = builder.constant_edwards_point(g.double());
+        builder.connect_edwards_point(&double_g, &expected_double_g);
+        builder.connect_edwards_point(&g_plus_g, &expected_double_g);
+
+        let g_plus_zero = builder.edwards_add(&g_target, &zero_target);
+        builder.connect_edwards_point(&g_plus_zero, &g_target);
+
+        let g_minus_g = builder.edwards_add(&g_target, &neg_g_target);
+        builder.connect_edwards_point(&g_minus_g, &zero_target);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+
+        data.verify(proof)
+    }
+
+    #[test]
+    #[ignore]
+    fn test_edwards_scalar_mul() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_ecc_config();
+
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let g = Ed25519::GENERATOR;
+        let p = EdwardsScalar(Ed25519Scalar::rand()) * g;
+        let n1 = Ed25519Scalar::rand();
+        let n2 = Ed25519Scalar::rand();
+
+        let g_target = builder.constant_edwards_point(g);
+        let p_target = builder.constant_edwards_point(p);
+        let n1_target = builder.constant_nonnative(n1);
+        let n2_target = builder.constant_nonnative(n2);
+
+        let product = builder.edwards_scalar_mul(&p_target, &n1_target);
+        let expected_product = builder.constant_edwards_point(EdwardsScalar(n1) * p);
+        builder.connect_edwards_point(&product, &expected_product);
+
+        let combination =
+            builder.edwards_double_scalar_mul(&g_target, &n1_target, &p_target, &n2_target);
+        let expected_combination =
+            builder.constant_edwards_point(EdwardsScalar(n1) * g + EdwardsScalar(n2) * p);
+        builder.connect_edwards_point(&combination, &expected_combination);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+
+        data.verify(proof)
+    }
+}
@@ -8,7 +8,7 @@ use crate::config::StarkConfig;
 use crate::cpu::cpu_stark;
 use crate::cpu::cpu_stark::CpuStark;
 use crate::cpu::membus::NUM_GP_CHANNELS;
-use crate::cross_table_lookup::{Column, CrossTableLookup, TableWithColumns};
+use crate::cross_table_lookup::{CrossTableLookup, TableWithColumns};
 use crate::keccak::keccak_stark;
 use crate::keccak::keccak_stark::KeccakStark;
 use crate::keccak_sponge::columns::KECCAK_RATE_BYTES;
@@ -89,18 +89,7 @@ impl Table {
 }
 
 pub(crate) fn all_cross_table_lookups<F: Field>() -> Vec<CrossTableLookup<F>> {
-    let mut ctls = vec![ctl_keccak_sponge(), ctl_keccak(), ctl_logic(), ctl_memory()];
-    // TODO: Some CTLs temporarily disabled while we get them working.
-    disable_ctl(&mut ctls[0]);
-    disable_ctl(&mut ctls[3]);
-    ctls
-}
-
-fn disable_ctl<F: Field>(ctl: &mut CrossTableLookup<F>) {
-    for table in &mut ctl.looking_tables {
-        table.filter_column = Some(Column::zero());
-    }
-    ctl.looked_table.filter_column = Some(Column::zero());
+    vec![ctl_keccak_sponge(), ctl_keccak(), ctl_logic(), ctl_memory()]
 }
 
 fn ctl_keccak<F: Field>() -> CrossTableLookup<F> {
@@ -182,3 +171,277 @@ fn ctl_memory<F: Field>() -> CrossTableLookup<F> {
     );
     CrossTableLookup::new(all_lookers, memory_looked)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use anyhow::Result;
+    use eth_trie_utils::partial_trie::{Nibbles, PartialTrie};
+    use ethereum_types::{Bloom, H256, U256};
+    use hex_literal::hex;
+    use keccak_hash::keccak;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::polynomial::PolynomialValues;
+    use plonky2::field::types::{Field, PrimeField64};
+    use plonky2::util::timing::TimingTree;
+
+    use crate::all_stark::{AllStark, Table, NUM_TABLES};
+    use crate::config::StarkConfig;
+    use crate::cpu::columns::COL_MAP;
+    use crate::cross_table_lookup::testutils::check_ctls;
+    use crate::generation::mpt::{AccountRlp, LegacyReceiptRlp, ReceiptRlp};
+    use crate::generation::{generate_traces, GenerationInputs, TrieInputs};
+    use crate::keccak_sponge::columns::KECCAK_SPONGE_COL_MAP;
+    use crate::keccak_sponge::keccak_sponge_stark;
+    use crate::memory::columns::{value_limb, IS_READ, TIMESTAMP};
+    use crate::memory::memory_stark;
+    use crate::proof::{BlockMetadata, PublicValues, TrieRoots};
+    use crate::stark_testing::test_stark_trace_constraints;
+    use crate::verifier::public_values_memory_rows;
+
+    type F = GoldilocksField;
+    const D: usize = 2;
+
+    /// Generates the traces of a simple transfer from an EOA to an empty account, and the public
+    /// values they claim.
+    fn simple_transfer_traces(
+        all_stark: &AllStark<F, D>,
+    ) -> ([Vec<PolynomialValues<F>>; NUM_TABLES], PublicValues) {
+        let sender = hex!("2c7536e3605d9c16a7a3d7b1898e529396a65c23");
+        let to = hex!("a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0");
+        let sender_state_key = keccak(sender);
+        let to_state_key = keccak(to);
+        let sender_nibbles = Nibbles::from_bytes_be(sender_state_key.as_bytes()).unwrap();
+        let to_nibbles = Nibbles::from_bytes_be(to_state_key.as_bytes()).unwrap();
+        let value = U256::from(100u32);
+
+        let sender_account_before = AccountRlp {
+            nonce: 5.into(),
+            balance: U256::from(10).pow(30.into()),
+            storage_root: PartialTrie::Empty.calc_hash(),
+            code_hash: keccak([]),
+        };
+        let state_trie_before = PartialTrie::Leaf {
+            nibbles: sender_nibbles,
+            value: rlp::encode(&sender_account_before).to_vec(),
+        };
+        let txn = hex!("f861050a8255f094a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0648242421ba02c89eb757d9deeb1f5b3859a9d4d679951ef610ac47ad4608dc142beb1b7e313a05af7e9fbab825455d36c36c7f4cfcafbeafa9a77bdff936b52afb36d4fe4bcdd");
+
+        // The intrinsic gas of a transfer with two non-zero bytes of data.
+        let gas_used = 21_000 + 2 * 16;
+        let state_trie_after = {
+            let sender_account_after = AccountRlp {
+                balance: sender_account_before.balance - value - gas_used * 10,
+                nonce: sender_account_before.nonce + 1,
+                ..sender_account_before
+            };
+            let to_account_after = AccountRlp {
+                balance: value,
+                ..AccountRlp::default()
+            };
+
+            let mut children = core::array::from_fn(|_| PartialTrie::Empty.into());
+            children[sender_nibbles.get_nibble(0) as usize] = PartialTrie::Leaf {
+                nibbles: sender_nibbles.truncate_n_nibbles_front(1),
+                value: rlp::encode(&sender_account_after).to_vec(),
+            }
+            .into();
+            children[to_nibbles.get_nibble(0) as usize] = PartialTrie::Leaf {
+                nibbles: to_nibbles.truncate_n_nibbles_front(1),
+                value: rlp::encode(&to_account_after).to_vec(),
+            }
+            .into();
+            PartialTrie::Branch {
+                children,
+                value: vec![],
+            }
+        };
+        let receipt = ReceiptRlp {
+            txn_type: 0,
+            receipt: LegacyReceiptRlp {
+                status: true,
+                cum_gas_used: gas_used.into(),
+                bloom: Bloom::zero(),
+                logs: vec![],
+            },
+        };
+        let receipts_trie_after = PartialTrie::Leaf {
+            nibbles: Nibbles::from_bytes_be(&rlp::encode(&0u64)).unwrap(),
+            value: receipt.encode(),
+        };
+
+        let inputs = GenerationInputs {
+            signed_txns: vec![txn.to_vec()],
+            txn_number_before: 0.into(),
+            gas_used_before: 0.into(),
+            gas_used_after: gas_used.into(),
+            tries: TrieInputs {
+                state_trie: state_trie_before,
+                transactions_trie: PartialTrie::Empty,
+                receipts_trie: PartialTrie::Empty,
+                storage_tries: vec![],
+            },
+            contract_code: HashMap::new(),
+            block_metadata: BlockMetadata::default(),
+            trie_roots_after: TrieRoots {
+                state_root: state_trie_after.calc_hash(),
+                transactions_root: PartialTrie::Empty.calc_hash(),
+                receipts_root: receipts_trie_after.calc_hash(),
+            },
+            block_hashes: vec![],
+        };
+        let config = StarkConfig::standard_fast_config();
+        generate_traces(all_stark, inputs, &config, &mut TimingTree::default())
+    }
+
+    #[test]
+    fn test_honest_traces_are_accepted() -> Result<()> {
+        let all_stark = AllStark::<F, D>::default();
+        let (traces, public_values) = simple_transfer_traces(&all_stark);
+
+        test_stark_trace_constraints(&all_stark.cpu_stark, &traces[Table::Cpu as usize])?;
+        test_stark_trace_constraints(&all_stark.keccak_stark, &traces[Table::Keccak as usize])?;
+        test_stark_trace_constraints(
+            &all_stark.keccak_sponge_stark,
+            &traces[Table::KeccakSponge as usize],
+        )?;
+        test_stark_trace_constraints(&all_stark.logic_stark, &traces[Table::Logic as usize])?;
+        test_stark_trace_constraints(&all_stark.memory_stark, &traces[Table::Memory as usize])?;
+        check_ctls(
+            &traces,
+            &all_stark.cross_table_lookups,
+            &public_values_memory_rows(&public_values),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_tampered_cpu_trace_is_rejected() {
+        let all_stark = AllStark::<F, D>::default();
+        let (mut traces, _) = simple_transfer_traces(&all_stark);
+        let cpu_trace = &mut traces[Table::Cpu as usize];
+
+        // Skip an instruction after the first ADD.
+        let row = first_row_with_op(cpu_trace, COL_MAP.op.add) + 1;
+        cpu_trace[COL_MAP.program_counter].values[row] += F::ONE;
+
+        assert!(test_stark_trace_constraints(&all_stark.cpu_stark, cpu_trace).is_err());
+    }
+
+    /// The index of the first CPU cycle which runs the operation with the given flag column.
+    fn first_row_with_op(cpu_trace: &[PolynomialValues<F>], op: usize) -> usize {
+        cpu_trace[op]
+            .values
+            .iter()
+            .position(|x| x.is_one())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_tampered_opcode_bits_are_rejected() {
+        let all_stark = AllStark::<F, D>::default();
+        let (mut traces, _) = simple_transfer_traces(&all_stark);
+        let cpu_trace = &mut traces[Table::Cpu as usize];
+
+        // Turn the first ADD (0x01) into a STOP (0x00), keeping its flag. ADD's flag covers a single
+        // opcode, so all 8 of its bits are checked, not just the highest one.
+        let row = first_row_with_op(cpu_trace, COL_MAP.op.add);
+        let bit = &mut cpu_trace[COL_MAP.opcode_bits[0]].values[row];
+        *bit = F::ONE - *bit;
+
+        assert!(test_stark_trace_constraints(&all_stark.cpu_stark, cpu_trace).is_err());
+    }
+
+    #[test]
+    fn test_tampered_stack_channel_is_rejected() {
+        let all_stark = AllStark::<F, D>::default();
+        let (mut traces, _) = simple_transfer_traces(&all_stark);
+        let cpu_trace = &mut traces[Table::Cpu as usize];
+
+        // Read the third operand of the first MULMOD from the wrong stack slot. Ternary operations
+        // pop three operands, so this channel's address is checked too.
+        let row = first_row_with_op(cpu_trace, COL_MAP.op.mulmod);
+        cpu_trace[COL_MAP.mem_channels[2].addr_virtual].values[row] += F::ONE;
+
+        assert!(test_stark_trace_constraints(&all_stark.cpu_stark, cpu_trace).is_err());
+    }
+
+    #[test]
+    fn test_tampered_stack_len_is_rejected() {
+        let all_stark = AllStark::<F, D>::default();
+        let (mut traces, _) = simple_transfer_traces(&all_stark);
+        let cpu_trace = &mut traces[Table::Cpu as usize];
+
+        // Claim a longer stack at the first PUSH, which must then write past the pushed value.
+        let row = first_row_with_op(cpu_trace, COL_MAP.op.push);
+        cpu_trace[COL_MAP.stack_len].values[row] += F::ONE;
+
+        assert!(test_stark_trace_constraints(&all_stark.cpu_stark, cpu_trace).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "CTL #3")]
+    fn test_tampered_memory_read_is_rejected() {
+        let all_stark = AllStark::<F, D>::default();
+        let (mut traces, public_values) = simple_transfer_traces(&all_stark);
+        let memory_trace = &mut traces[Table::Memory as usize];
+
+        // Change the value returned by the last memory read.
+        let filter = memory_stark::ctl_filter::<F>();
+        let row = (0..memory_trace[TIMESTAMP].len())
+            .filter(|&i| filter.eval_table(memory_trace, i).is_one())
+            .filter(|&i| memory_trace[IS_READ].values[i].is_one())
+            .max_by_key(|&i| memory_trace[TIMESTAMP].values[i].to_canonical_u64())
+            .unwrap();
+        memory_trace[value_limb(0)].values[row] += F::ONE;
+
+        check_ctls(
+            &traces,
+            &all_stark.cross_table_lookups,
+            &public_values_memory_rows(&public_values),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "CTL #3")]
+    fn test_tampered_public_values_are_rejected() {
+        let all_stark = AllStark::<F, D>::default();
+        let (traces, mut public_values) = simple_transfer_traces(&all_stark);
+
+        // Claim a different post-state than the one the kernel computed and checked.
+        public_values.trie_roots_after.state_root = H256::zero();
+
+        check_ctls(
+            &traces,
+            &all_stark.cross_table_lookups,
+            &public_values_memory_rows(&public_values),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "CTL #0")]
+    fn test_tampered_keccak_digest_is_rejected() {
+        let all_stark = AllStark::<F, D>::default();
+        let (mut traces, public_values) = simple_transfer_traces(&all_stark);
+        let sponge_trace = &mut traces[Table::KeccakSponge as usize];
+
+        // Change one byte of the first digest.
+        let filter = keccak_sponge_stark::ctl_looked_filter::<F>();
+        let row = (0..sponge_trace[0].len())
+            .find(|&i| filter.eval_table(sponge_trace, i).is_one())
+            .unwrap();
+        let byte =
+            &mut sponge_trace[KECCAK_SPONGE_COL_MAP.updated_digest_state_bytes[0]].values[row];
+        *byte = F::from_canonical_u64(255) - *byte;
+
+        assert!(
+            test_stark_trace_constraints(&all_stark.keccak_sponge_stark, sponge_trace).is_err()
+        );
+        check_ctls(
+            &traces,
+            &all_stark.cross_table_lookups,
+            &public_values_memory_rows(&public_values),
+        );
+    }
+}
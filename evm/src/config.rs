@@ -9,6 +9,16 @@ pub struct StarkConfig {
     pub num_challenges: usize,
 
     pub fri_config: FriConfig,
+
+    /// Whether proofs should hide the trace. If so, each committed polynomial `p` is randomized as
+    /// `p + Z_H r`, where `Z_H` is the zero polynomial of the trace domain `H` and `r` is random
+    /// of degree less than `n = |H|`. This leaves `p` unchanged on `H`, but doubles its degree, so
+    /// FRI is run for degree `2n`. Merkle leaves are also salted, so that commitments are hiding.
+    ///
+    /// Openings reveal nothing about the trace as long as `n` exceeds the number of evaluations
+    /// revealed per polynomial, i.e. the out-of-domain openings plus the FRI queries; provers
+    /// reject shorter traces.
+    pub zero_knowledge: bool,
 }
 
 impl StarkConfig {
@@ -25,10 +35,46 @@ impl StarkConfig {
                 reduction_strategy: FriReductionStrategy::ConstantArityBits(4, 5),
                 num_query_rounds: 84,
             },
+            zero_knowledge: false,
         }
     }
 
+    /// A zero-knowledge variant of `standard_fast_config`. It uses a rate of 4, since the
+    /// quotient polynomials have a higher degree relative to the committed polynomials, which
+    /// also halves the number of queries.
+    /// Targets ~100 bit conjectured security.
+    pub fn standard_fast_zk_config() -> Self {
+        Self {
+            security_bits: 100,
+            num_challenges: 2,
+            fri_config: FriConfig {
+                rate_bits: 2,
+                cap_height: 4,
+                proof_of_work_bits: 16,
+                reduction_strategy: FriReductionStrategy::ConstantArityBits(4, 5),
+                num_query_rounds: 42,
+            },
+            zero_knowledge: true,
+        }
+    }
+
+    /// The log of the ratio between the degree of the committed polynomials and the trace length.
+    pub fn blinding_degree_bits(&self) -> usize {
+        usize::from(self.zero_knowledge)
+    }
+
+    /// The number of evaluations of each committed polynomial revealed by a proof: the openings
+    /// at `zeta` and `g * zeta`, and one per FRI query. In zero-knowledge mode, traces must be
+    /// longer than this for the openings to hide them.
+    pub fn num_revealed_evaluations(&self) -> usize {
+        2 + self.fri_config.num_query_rounds
+    }
+
+    /// The FRI parameters for a trace of length `2^degree_bits`.
     pub(crate) fn fri_params(&self, degree_bits: usize) -> FriParams {
-        self.fri_config.fri_params(degree_bits, false)
+        self.fri_config.fri_params(
+            degree_bits + self.blinding_degree_bits(),
+            self.zero_knowledge,
+        )
     }
 }
@@ -52,7 +52,8 @@ pub struct OpsColumnsView<T: Copy> {
     pub mload_general: T,
     pub mstore_general: T,
 
-    pub syscall: T, // Note: This column must be 0 when is_cpu_cycle = 0.
+    pub syscall: T,   // Note: This column must be 0 when is_cpu_cycle = 0.
+    pub exception: T, // Note: This column must be 0 when is_cpu_cycle = 0.
 }
 
 // `u8` is guaranteed to have a `size_of` of 1.
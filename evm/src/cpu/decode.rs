@@ -20,8 +20,9 @@ use crate::cpu::columns::{CpuColumnsView, COL_MAP};
 /// The exception is the PANIC instruction which is user-only without a corresponding kernel block.
 /// This makes the proof unverifiable when PANIC is executed in kernel mode, which is the intended
 /// behavior.
-/// Note: invalid opcodes are not represented here. _Any_ opcode is permitted to decode to
-/// `is_invalid`. The kernel then verifies that the opcode was _actually_ invalid.
+/// Note: invalid opcodes are not represented here. _Any_ opcode is permitted to raise an
+/// exception. The kernel then verifies that the exception was _actually_ warranted; see
+/// `exception.asm`.
 const OPCODES: [(u8, usize, bool, usize); 36] = [
     // (start index of block, number of top bits to check (log2), kernel-only, flag column)
     (0x01, 0, false, COL_MAP.op.add),
@@ -62,32 +63,9 @@ const OPCODES: [(u8, usize, bool, usize); 36] = [
     (0xfc, 0, true, COL_MAP.op.mstore_general),
 ];
 
-/// Bitfield of invalid opcodes, in little-endian order.
-pub(crate) const fn invalid_opcodes_user() -> [u8; 32] {
-    let mut res = [u8::MAX; 32]; // Start with all opcodes marked invalid.
-
-    let mut i = 0;
-    while i < OPCODES.len() {
-        let (block_start, lb_block_len, kernel_only, _) = OPCODES[i];
-        i += 1;
-
-        if kernel_only {
-            continue;
-        }
-
-        let block_len = 1 << lb_block_len;
-        let block_start = block_start as usize;
-        let block_end = block_start + block_len;
-        let mut j = block_start;
-        while j < block_end {
-            let byte = j / u8::BITS as usize;
-            let bit = j % u8::BITS as usize;
-            res[byte] &= !(1 << bit); // Mark opcode as invalid by zeroing the bit.
-            j += 1;
-        }
-    }
-    res
-}
+/// Flags which may be set whatever the opcode. The kernel's syscall jumptable decides which opcodes
+/// are actually handled by a syscall, and the kernel verifies that an exception was warranted.
+const OPCODE_AGNOSTIC_FLAGS: [usize; 2] = [COL_MAP.op.syscall, COL_MAP.op.exception];
 
 pub fn generate<F: RichField>(lv: &mut CpuColumnsView<F>) {
     let cycle_filter = lv.is_cpu_cycle;
@@ -166,15 +144,16 @@ pub fn eval_packed_generic<P: PackedField>(
 
     // Check that the instruction flags are valid.
     // First, check that they are all either 0 or 1.
-    for (_, _, _, flag_col) in OPCODES {
+    let flag_cols = OPCODES
+        .into_iter()
+        .map(|(_, _, _, flag_col)| flag_col)
+        .chain(OPCODE_AGNOSTIC_FLAGS);
+    for flag_col in flag_cols.clone() {
         let flag = lv[flag_col];
         yield_constr.constraint(cycle_filter * flag * (flag - P::ONES));
     }
     // Now check that they sum to 0 or 1.
-    let flag_sum: P = OPCODES
-        .into_iter()
-        .map(|(_, _, _, flag_col)| lv[flag_col])
-        .sum::<P>();
+    let flag_sum: P = flag_cols.map(|flag_col| lv[flag_col]).sum::<P>();
     yield_constr.constraint(cycle_filter * flag_sum * (flag_sum - P::ONES));
 
     // Finally, classify all opcodes, together with the kernel flag, into blocks
@@ -191,7 +170,7 @@ pub fn eval_packed_generic<P: PackedField>(
             .into_iter()
             .zip(bits_from_opcode(oc))
             .rev()
-            .take(block_length + 1)
+            .take(8 - block_length)
             .map(|(row_bit, flag_bit)| match flag_bit {
                 // 1 if the bit does not match, and 0 otherwise
                 false => row_bit,
@@ -232,7 +211,11 @@ pub fn eval_ext_circuit<F: RichField + Extendable<D>, const D: usize>(
 
     // Check that the instruction flags are valid.
     // First, check that they are all either 0 or 1.
-    for (_, _, _, flag_col) in OPCODES {
+    let flag_cols = OPCODES
+        .into_iter()
+        .map(|(_, _, _, flag_col)| flag_col)
+        .chain(OPCODE_AGNOSTIC_FLAGS);
+    for flag_col in flag_cols.clone() {
         let flag = lv[flag_col];
         let constr = builder.mul_sub_extension(flag, flag, flag);
         let constr = builder.mul_extension(cycle_filter, constr);
@@ -241,7 +224,7 @@ pub fn eval_ext_circuit<F: RichField + Extendable<D>, const D: usize>(
     // Now check that they sum to 0 or 1.
     {
         let mut flag_sum = builder.zero_extension();
-        for (_, _, _, flag_col) in OPCODES {
+        for flag_col in flag_cols {
             let flag = lv[flag_col];
             flag_sum = builder.add_extension(flag_sum, flag);
         }
@@ -264,7 +247,7 @@ pub fn eval_ext_circuit<F: RichField + Extendable<D>, const D: usize>(
             .into_iter()
             .zip(bits_from_opcode(oc))
             .rev()
-            .take(block_length + 1)
+            .take(8 - block_length)
             .fold(builder.zero_extension(), |cumul, (row_bit, flag_bit)| {
                 let to_add = match flag_bit {
                     false => row_bit,
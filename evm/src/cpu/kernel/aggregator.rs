@@ -12,15 +12,26 @@ pub static KERNEL: Lazy<Kernel> = Lazy::new(combined_kernel);
 pub(crate) fn combined_kernel() -> Kernel {
     let files = vec![
         include_str!("asm/core/bootloader.asm"),
+        include_str!("asm/core/call.asm"),
+        include_str!("asm/core/checkpoint.asm"),
         include_str!("asm/core/create.asm"),
         include_str!("asm/core/create_addresses.asm"),
+        include_str!("asm/core/exception.asm"),
         include_str!("asm/core/intrinsic_gas.asm"),
-        include_str!("asm/core/invalid.asm"),
         include_str!("asm/core/jumpdest_analysis.asm"),
+        include_str!("asm/core/log.asm"),
         include_str!("asm/core/nonce.asm"),
+        include_str!("asm/core/precompiles/blake2_f.asm"),
+        include_str!("asm/core/precompiles/bn_add.asm"),
+        include_str!("asm/core/precompiles/bn_mul.asm"),
+        include_str!("asm/core/precompiles/ecrec.asm"),
+        include_str!("asm/core/precompiles/id.asm"),
+        include_str!("asm/core/precompiles/main.asm"),
+        include_str!("asm/core/precompiles/rip160.asm"),
+        include_str!("asm/core/precompiles/sha256.asm"),
         include_str!("asm/core/process_txn.asm"),
+        include_str!("asm/core/receipt.asm"),
         include_str!("asm/core/syscall.asm"),
-        include_str!("asm/core/syscall_stubs.asm"),
         include_str!("asm/core/terminate.asm"),
         include_str!("asm/core/transfer.asm"),
         include_str!("asm/core/util.asm"),
@@ -93,6 +104,7 @@ pub(crate) fn combined_kernel() -> Kernel {
         include_str!("asm/rlp/num_bytes.asm"),
         include_str!("asm/rlp/read_to_memory.asm"),
         include_str!("asm/shift.asm"),
+        include_str!("asm/signed.asm"),
         include_str!("asm/transactions/router.asm"),
         include_str!("asm/transactions/type_0.asm"),
         include_str!("asm/transactions/type_1.asm"),
@@ -55,57 +55,45 @@ global extcodesize:
 global extcodecopy:
     // stack: address, dest_offset, offset, size, retdest
     %stack (address, dest_offset, offset, size, retdest)
-        -> (address, 0, @SEGMENT_KERNEL_ACCOUNT_CODE, extcodecopy_contd, size, offset, dest_offset, retdest)
+        -> (address, 0, @SEGMENT_KERNEL_ACCOUNT_CODE, extcodecopy_contd, dest_offset, offset, size, retdest)
     %jump(load_code)
 
 extcodecopy_contd:
-    // stack: code_length, size, offset, dest_offset, retdest
+    // stack: code_length, dest_offset, offset, size, retdest
+    // Copy from `SEGMENT_KERNEL_ACCOUNT_CODE`, reading zeros past `code_length`. This is necessary
+    // since the segment might have been clobbered by previous calls.
+    %stack (code_length) -> (0, @SEGMENT_KERNEL_ACCOUNT_CODE, code_length)
+    %jump(copy_to_main_memory)
+
+global sys_extcodesize:
+    // stack: kexit_info, address
+    %charge_account_access
     SWAP1
-    // stack: size, code_length, offset, dest_offset, retdest
-    PUSH 0
-
-// Loop copying the `code[offset]` to `memory[dest_offset]` until `i==size`.
-// Each iteration increments `offset, dest_offset, i`.
-// TODO: Consider implementing this with memcpy.
-extcodecopy_loop:
-    // stack: i, size, code_length, offset, dest_offset, retdest
-    DUP2 DUP2 EQ
-    // stack: i == size, i, size, code_length, offset, dest_offset, retdest
-    %jumpi(extcodecopy_end)
-    %stack (i, size, code_length, offset, dest_offset, retdest)
-        -> (offset, code_length, offset, code_length, dest_offset, i, size, retdest)
-    LT
-    // stack: offset < code_length, offset, code_length, dest_offset, i, size, retdest
-    DUP2
-    // stack: offset, offset < code_length, offset, code_length, dest_offset, i, size, retdest
-    %mload_current(@SEGMENT_KERNEL_ACCOUNT_CODE)
-    // stack: opcode, offset < code_length, offset, code_length, dest_offset, i, size, retdest
-    %stack (opcode, offset_lt_code_length, offset, code_length, dest_offset, i, size, retdest)
-        -> (offset_lt_code_length, 0, opcode, offset, code_length, dest_offset, i, size, retdest)
-    // If `offset >= code_length`, use `opcode=0`. Necessary since `SEGMENT_KERNEL_ACCOUNT_CODE` might be clobbered from previous calls.
-    %select_bool
-    // stack: opcode, offset, code_length, dest_offset, i, size, retdest
-    DUP4
-    // stack: dest_offset, opcode, offset, code_length, dest_offset, i, size, retdest
-    %mstore_main
-    // stack: offset, code_length, dest_offset, i, size, retdest
-    %increment
-    // stack: offset+1, code_length, dest_offset, i, size, retdest
-    SWAP2
-    // stack: dest_offset, code_length, offset+1, i, size, retdest
-    %increment
-    // stack: dest_offset+1, code_length, offset+1, i, size, retdest
-    SWAP3
-    // stack: i, code_length, offset+1, dest_offset+1, size, retdest
-    %increment
-    // stack: i+1, code_length, offset+1, dest_offset+1, size, retdest
-    %stack (i, code_length, offset, dest_offset, size, retdest) -> (i, size, code_length, offset, dest_offset, retdest)
-    %jump(extcodecopy_loop)
-
-extcodecopy_end:
-    %stack (i, size, code_length, offset, dest_offset, retdest) -> (retdest)
-    JUMP
+    %extcodesize
+    // stack: extcodesize(address), kexit_info
+    SWAP1
+    EXIT_KERNEL
 
+global sys_extcodehash:
+    // stack: kexit_info, address
+    %charge_account_access
+    SWAP1
+    %extcodehash
+    // stack: extcodehash(address), kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_extcodecopy:
+    // stack: kexit_info, address, dest_offset, offset, size
+    %charge_account_access
+    DUP5
+    %charge_copy_gas
+    %stack (kexit_info, address, dest_offset, offset, size)
+        -> (dest_offset, size, kexit_info, address, dest_offset, offset, size)
+    %expand_memory
+    %stack (kexit_info, address, dest_offset, offset, size)
+        -> (address, dest_offset, offset, size, copy_exit, kexit_info)
+    %jump(extcodecopy)
 
 // Loads the code at `address` into memory, at the given context and segment, starting at offset 0.
 // Checks that the hash of the loaded code corresponds to the `codehash` in the state trie.
@@ -116,6 +104,10 @@ global load_code:
     JUMP
 load_code_ctd:
     // stack: codehash, ctx, segment, retdest
+    // A codehash of 0 means that the account doesn't exist, in which case the code is empty.
+    DUP1
+    ISZERO
+    %jumpi(load_code_non_existent_account)
     PROVER_INPUT(account_code::length)
     // stack: code_length, codehash, ctx, segment, retdest
     PUSH 0
@@ -147,3 +139,7 @@ load_code_check:
     // stack: shouldbecodehash, codehash, retdest, code_length
     %assert_eq
     JUMP
+
+load_code_non_existent_account:
+    %stack (codehash, ctx, segment, retdest) -> (retdest, 0)
+    JUMP
@@ -9,6 +9,13 @@ global balance:
     // stack: balance, retdest
     SWAP1 JUMP
 
+// Convenience macro to call balance and return where we left off.
+%macro balance
+    %stack (address) -> (address, %%after)
+    %jump(balance)
+%%after:
+%endmacro
+
 retzero:
     %stack (account_ptr, retdest) -> (retdest, 0)
     JUMP
@@ -21,3 +28,19 @@ global selfbalance:
     // stack: balance, address, retdest
     JUMP
 
+global sys_balance:
+    // stack: kexit_info, address
+    %charge_account_access
+    %stack (kexit_info, address) -> (address, sys_balance_contd, kexit_info)
+    %jump(balance)
+
+global sys_selfbalance:
+    // stack: kexit_info
+    %charge_gas_const(@GAS_LOW)
+    %stack (kexit_info) -> (sys_balance_contd, kexit_info)
+    %jump(selfbalance)
+
+sys_balance_contd:
+    // stack: balance, kexit_info
+    SWAP1
+    EXIT_KERNEL
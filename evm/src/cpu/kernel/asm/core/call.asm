@@ -1,22 +1,22 @@
 // Handlers for call-like operations, namely CALL, CALLCODE, STATICCALL and DELEGATECALL.
 
 // Creates a new sub context and executes the code of the given account.
-global call:
-    // stack: gas, address, value, args_offset, args_size, ret_offset, ret_size, retdest
+global sys_call:
+    // stack: kexit_info, gas, address, value, args_offset, args_size, ret_offset, ret_size
     %address
-    %stack (self, gas, address, value)
-           // These are (static, should_transfer_value, value, sender, address, code_addr, gas)
-        -> (0, 1, value, self, address, address, gas)
+    %stack (self, kexit_info, gas, address, value)
+           // These are (kexit_info, static, should_transfer_value, value, sender, address, code_addr, gas)
+        -> (kexit_info, 0, 1, value, self, address, address, gas)
     %jump(call_common)
 
 // Creates a new sub context as if calling itself, but with the code of the
 // given account. In particular the storage remains the same.
-global call_code:
-    // stack: gas, address, value, args_offset, args_size, ret_offset, ret_size, retdest
+global sys_callcode:
+    // stack: kexit_info, gas, address, value, args_offset, args_size, ret_offset, ret_size
     %address
-    %stack (self, gas, address, value)
-           // These are (static, should_transfer_value, value, sender, address, code_addr, gas)
-        -> (0, 1, value, self, self, address, gas)
+    %stack (self, kexit_info, gas, address, value)
+           // These are (kexit_info, static, should_transfer_value, value, sender, address, code_addr, gas)
+        -> (kexit_info, 0, 1, value, self, self, address, gas)
     %jump(call_common)
 
 // Creates a new sub context and executes the code of the given account.
@@ -24,90 +24,241 @@ global call_code:
 // instructions or sending ETH in the sub context. The disallowed instructions
 // are CREATE, CREATE2, LOG0, LOG1, LOG2, LOG3, LOG4, SSTORE, SELFDESTRUCT and
 // CALL if the value sent is not 0.
-global static_all:
-    // stack: gas, address, args_offset, args_size, ret_offset, ret_size, retdest
+global sys_staticcall:
+    // stack: kexit_info, gas, address, args_offset, args_size, ret_offset, ret_size
     %address
-    %stack (self, gas, address)
-           // These are (static, should_transfer_value, value, sender, address, code_addr, gas)
-        -> (1, 0, 0, self, address, address, gas)
+    %stack (self, kexit_info, gas, address)
+           // These are (kexit_info, static, should_transfer_value, value, sender, address, code_addr, gas)
+        -> (kexit_info, 1, 0, 0, self, address, address, gas)
     %jump(call_common)
 
 // Creates a new sub context as if calling itself, but with the code of the
 // given account. In particular the storage, the current sender and the current
 // value remain the same.
-global delegate_call:
-    // stack: gas, address, args_offset, args_size, ret_offset, ret_size, retdest
+global sys_delegatecall:
+    // stack: kexit_info, gas, address, args_offset, args_size, ret_offset, ret_size
     %address
     %sender
     %callvalue
-    %stack (self, sender, value, gas, address)
-           // These are (static, should_transfer_value, value, sender, address, code_addr, gas)
-        -> (0, 0, value, sender, self, address, gas)
+    %stack (value, sender, self, kexit_info, gas, address)
+           // These are (kexit_info, static, should_transfer_value, value, sender, address, code_addr, gas)
+        -> (kexit_info, 0, 0, value, sender, self, address, gas)
     %jump(call_common)
 
-// Pre stack: static, should_transfer_value, value, sender, address, code_addr, gas, args_offset, args_size, ret_offset, ret_size, retdest
-// Post stack: success, leftover_gas
-global call_common:
-    // stack: static, should_transfer_value, value, sender, address, code_addr, gas, args_offset, args_size, ret_offset, ret_size, retdest
-    %create_context
-    // Store the static flag in metadata.
-    %stack (new_ctx, static) -> (new_ctx, @SEGMENT_CONTEXT_METADATA, @CTX_METADATA_STATIC, static, new_ctx)
+// Stores the given value in the given metadata field of the new context.
+%macro set_new_ctx_metadata(field)
+    // stack: value, new_ctx
+    PUSH $field
+    PUSH @SEGMENT_CONTEXT_METADATA
+    DUP4 // new_ctx
     MSTORE_GENERAL
-    // stack: new_ctx, should_transfer_value, value, sender, address, code_addr, gas, args_offset, args_size, ret_offset, ret_size, retdest
+    // stack: new_ctx
+%endmacro
 
-    // Store the address in metadata.
-    %stack (new_ctx, should_transfer_value, value, sender, address)
-        -> (new_ctx, @SEGMENT_CONTEXT_METADATA, @CTX_METADATA_ADDRESS, address,
-            new_ctx, should_transfer_value, value, sender, address)
-    MSTORE_GENERAL
-    // stack: new_ctx, should_transfer_value, value, sender, address, code_addr, gas, args_offset, args_size, ret_offset, ret_size, retdest
+// Pre stack: kexit_info, static, should_transfer_value, value, sender, address, code_addr, gas,
+//            args_offset, args_size, ret_offset, ret_size
+// Post stack: (empty, since we either exit the kernel or switch to the new context)
+global call_common:
+    // A context is static if it was created by STATICCALL, or if its parent is static.
+    SWAP1
+    %mload_context_metadata(@CTX_METADATA_STATIC)
+    OR
+    SWAP1
+    // Only value sent to another account counts as a transfer.
+    DUP4
+    ISZERO
+    ISZERO
+    DUP4
+    AND
+    %stack (transfers_value, kexit_info, static, should_transfer_value)
+        -> (kexit_info, static, transfers_value)
+    // stack: kexit_info, static, transfers_value, value, sender, address, code_addr, gas, args_offset, args_size, ret_offset, ret_size
 
-    // Store the caller in metadata.
-    %stack (new_ctx, should_transfer_value, value, sender)
-        -> (new_ctx, @SEGMENT_CONTEXT_METADATA, @CTX_METADATA_CALLER, sender,
-            new_ctx, should_transfer_value, value, sender)
-    MSTORE_GENERAL
-    // stack: new_ctx, should_transfer_value, value, sender, address, code_addr, gas, args_offset, args_size, ret_offset, ret_size, retdest
+    // Transferring value is a state modification, so it is forbidden in a static context.
+    DUP3
+    DUP3
+    AND
+    %jumpi(fault_exception)
 
-    // Store the call value field in metadata.
-    %stack (new_ctx, should_transfer_value, value, sender, address) =
-        -> (new_ctx, @SEGMENT_CONTEXT_METADATA, @CTX_METADATA_CALL_VALUE, value,
-            should_transfer_value, sender, address, value, new_ctx)
-    MSTORE_GENERAL
-    // stack: should_transfer_value, sender, address, value, new_ctx, code_addr, gas, args_offset, args_size, ret_offset, ret_size, retdest
+    %charge_account_access
+    DUP3
+    %mul_const(@GAS_CALLVALUE)
+    %charge_gas
+    // Sending value to an account which doesn't exist creates it.
+    DUP6 // address
+    %mpt_read_state_trie
+    ISZERO
+    DUP4 // transfers_value
+    AND
+    %mul_const(@GAS_NEWACCOUNT)
+    %charge_gas
+
+    // Expand memory to cover both the input and the output.
+    %stack (kexit_info, static, transfers_value, value, sender, address, code_addr, gas, args_offset, args_size)
+        -> (args_offset, args_size, kexit_info, static, transfers_value, value, sender, address, code_addr, gas, args_offset, args_size)
+    %expand_memory
+    DUP12 // ret_size
+    DUP12 // ret_offset
+    %expand_memory
+    // stack: kexit_info, static, transfers_value, value, sender, address, code_addr, gas, args_offset, args_size, ret_offset, ret_size
+
+    // The callee gets at most all but one 64th of the remaining gas (EIP-150).
+    DUP1
+    %shr_const(192)
+    // stack: gas_used, kexit_info, ...
+    %mload_context_metadata(@CTX_METADATA_GAS_LIMIT)
+    DUP2
+    DUP2
+    LT
+    // stack: gas_limit < gas_used, gas_limit, gas_used, kexit_info, ...
+    %jumpi(fault_exception)
+    SUB
+    // stack: remaining_gas, kexit_info, ...
+    DUP1
+    %div_const(64)
+    SWAP1
+    SUB
+    // stack: max_child_gas, kexit_info, static, transfers_value, value, sender, address, code_addr, gas, ...
+    DUP9 // gas
+    %min
+    %stack (child_gas, kexit_info, static, transfers_value, value, sender, address, code_addr, gas)
+        -> (child_gas, kexit_info, child_gas, static, transfers_value, value, sender, address, code_addr)
+    %charge_gas
+    // The callee also gets a stipend when value is sent, which the caller doesn't pay for.
+    %stack (kexit_info, child_gas, static, transfers_value)
+        -> (transfers_value, child_gas, kexit_info, static, transfers_value)
+    %mul_const(@GAS_CALLSTIPEND)
+    ADD
+    SWAP1
+    // stack: kexit_info, child_gas, static, transfers_value, value, sender, address, code_addr, args_offset, args_size, ret_offset, ret_size
+
+    // The call fails without running any code if the caller can't afford the value, or if the
+    // call depth limit is reached.
+    DUP6 // sender
+    %balance
+    DUP6 // value
+    GT
+    DUP5 // transfers_value
+    AND
+    %mload_context_metadata(@CTX_METADATA_CALL_DEPTH)
+    %ge_const(@CALL_STACK_LIMIT)
+    OR
+    %jumpi(call_fail_early)
 
+    %create_context
+    %checkpoint
+    // stack: new_ctx, kexit_info, child_gas, static, transfers_value, value, sender, address, code_addr, args_offset, args_size, ret_offset, ret_size
+    DUP4 %set_new_ctx_metadata(@CTX_METADATA_STATIC)
+    DUP8 %set_new_ctx_metadata(@CTX_METADATA_ADDRESS)
+    DUP7 %set_new_ctx_metadata(@CTX_METADATA_CALLER)
+    DUP6 %set_new_ctx_metadata(@CTX_METADATA_CALL_VALUE)
+    DUP3 %set_new_ctx_metadata(@CTX_METADATA_GAS_LIMIT)
+    DUP11 %set_new_ctx_metadata(@CTX_METADATA_CALLDATA_SIZE)
+    GET_CONTEXT %set_new_ctx_metadata(@CTX_METADATA_PARENT_CONTEXT)
+    PUSH after_call %set_new_ctx_metadata(@CTX_METADATA_PARENT_PC)
+    %mload_context_metadata(@CTX_METADATA_CALL_DEPTH)
+    %increment
+    %set_new_ctx_metadata(@CTX_METADATA_CALL_DEPTH)
+
+    // The transfer happens after the checkpoint, so that it is reverted if the call fails.
+    DUP6 // value
+    DUP9 // address
+    DUP9 // sender
+    DUP8 // transfers_value
     %maybe_transfer_eth
-    // stack: new_ctx, code_addr, gas, args_offset, args_size, ret_offset, ret_size, retdest
+    // The sender's balance was checked above, so the transfer can't fail.
+    %jumpi(panic)
+    // stack: new_ctx, kexit_info, child_gas, static, transfers_value, value, sender, address, code_addr, args_offset, args_size, ret_offset, ret_size
 
-    // Store parent context in metadata.
-    GET_CONTEXT
-    PUSH @CTX_METADATA_PARENT_CONTEXT
-    PUSH @SEGMENT_CONTEXT_METADATA
-    DUP4 // new_ctx
-    MSTORE_GENERAL
-    // stack: new_ctx, code_addr, gas, args_offset, args_size, ret_offset, ret_size, retdest
+    // Precompiles are run by the kernel, in the caller's context.
+    DUP9 // code_addr
+    %is_precompile
+    %jumpi(call_precompile)
 
-    // Store parent PC = after_call.
-    %stack (new_ctx) -> (new_ctx, @SEGMENT_CONTEXT_METADATA, @CTX_METADATA_PARENT_PC, after_call, new_ctx)
-    MSTORE_GENERAL
-    // stack: new_ctx, code_addr, gas, args_offset, args_size, ret_offset, ret_size, retdest
+    // Copy the input to the new context's calldata.
+    GET_CONTEXT
+    %stack (ctx, new_ctx, kexit_info, child_gas, static, transfers_value, value, sender, address, code_addr, args_offset, args_size)
+        -> (new_ctx, @SEGMENT_CALLDATA, 0, ctx, @SEGMENT_MAIN_MEMORY, args_offset, args_size,
+            call_calldata_copied, new_ctx, kexit_info, code_addr)
+    %jump(memcpy)
 
-    // TODO: Populate CALLDATA
-    // TODO: Save parent gas and set child gas
-    // TODO: Populate code
+call_calldata_copied:
+    // stack: new_ctx, kexit_info, code_addr, ret_offset, ret_size
+    %stack (new_ctx, kexit_info, code_addr)
+        -> (code_addr, new_ctx, @SEGMENT_CODE, call_code_loaded, new_ctx, kexit_info)
+    %jump(load_code)
 
-    // TODO: Temporary, remove after above steps are done.
-    %stack (new_ctx, code_addr, gas, args_offset, args_size) -> (new_ctx)
-    // stack: new_ctx, ret_offset, ret_size, retdest
+call_code_loaded:
+    // stack: code_len, new_ctx, kexit_info, ret_offset, ret_size
+    %stack (code_len, new_ctx)
+        -> (code_len, new_ctx, code_len, call_jumpdests_analysed, new_ctx)
+    %set_new_ctx_metadata(@CTX_METADATA_CODE_SIZE)
+    // stack: new_ctx, code_len, call_jumpdests_analysed, new_ctx, kexit_info, ret_offset, ret_size
+    %jump(jumpdest_analysis)
 
-    // Now, switch to the new context and go to usermode with PC=0.
-    DUP1 // new_ctx
+call_jumpdests_analysed:
+    // stack: new_ctx, kexit_info, ret_offset, ret_size
+    // Switch to the new context and go to usermode with PC=0. The rest of the stack stays in our
+    // context, for `after_call`.
     SET_CONTEXT
-    PUSH 0 // jump dest
+    PUSH 0 // kexit_info
+    EXIT_KERNEL
+
+call_precompile:
+    // stack: new_ctx, kexit_info, child_gas, static, transfers_value, value, sender, address, code_addr, args_offset, args_size, ret_offset, ret_size
+    GET_CONTEXT
+    %stack (ctx, new_ctx, kexit_info, child_gas, static, transfers_value, value, sender, address, code_addr, args_offset, args_size)
+        -> (code_addr, child_gas, ctx, @SEGMENT_MAIN_MEMORY, args_offset, args_size,
+            call_precompile_after, new_ctx, kexit_info)
+    %jump(handle_precompile)
+
+call_precompile_after:
+    // stack: success, leftover_gas, new_ctx, kexit_info, ret_offset, ret_size
+    // A failed precompile reverts the value transfer.
+    DUP1
+    %jumpi(call_precompile_succeeded)
+    DUP3
+    %revert_to_checkpoint
+call_precompile_succeeded:
+    %stack (success, leftover_gas, new_ctx) -> (success, leftover_gas)
+    %jump(after_call)
+
+call_fail_early:
+    // stack: kexit_info, child_gas, static, transfers_value, value, sender, address, code_addr, args_offset, args_size, ret_offset, ret_size
+    // The caller gets the gas it gave back, and no returndata.
+    PUSH 0
+    %mstore_context_metadata(@CTX_METADATA_RETURNDATA_SIZE)
+    %stack (kexit_info, child_gas, static, transfers_value, value, sender, address, code_addr, args_offset, args_size, ret_offset, ret_size)
+        -> (child_gas, kexit_info, 0)
+    %refund_gas
+    // stack: kexit_info, success = 0
     EXIT_KERNEL
 
+// Returns to the caller, once the callee has terminated or the precompile has run.
 after_call:
-    // stack: new_ctx, ret_offset, ret_size, retdest
-    // TODO: Set RETURNDATA.
-    // TODO: Return to caller w/ EXIT_KERNEL.
-    // TODO: Return leftover gas
+    // stack: success, leftover_gas, kexit_info, ret_offset, ret_size
+    %stack (success, leftover_gas, kexit_info) -> (leftover_gas, kexit_info, success)
+    %refund_gas
+    // stack: kexit_info, success, ret_offset, ret_size
+    // Copy the returndata to the caller's memory, truncated to ret_size.
+    %returndatasize
+    DUP5 // ret_size
+    %min
+    GET_CONTEXT
+    %stack (ctx, len, kexit_info, success, ret_offset, ret_size)
+        -> (ctx, @SEGMENT_MAIN_MEMORY, ret_offset, ctx, @SEGMENT_RETURNDATA, 0, len,
+            after_call_copied, kexit_info, success)
+    %jump(memcpy)
+
+after_call_copied:
+    // stack: kexit_info, success
+    EXIT_KERNEL
+
+// Gives back gas to the current context, i.e. subtracts it from the gas used in kexit_info.
+%macro refund_gas
+    // stack: gas, kexit_info
+    %shl_const(192)
+    SWAP1
+    SUB
+    // stack: kexit_info
+%endmacro
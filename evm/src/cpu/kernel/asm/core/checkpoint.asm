@@ -0,0 +1,105 @@
+// Checkpoints of the state modified by a context, so that it can be reverted.
+//
+// Tries are copy-on-write, so most state changes only move @GLOBAL_METADATA_STATE_TRIE_ROOT, and
+// logs are append-only. The exceptions are balance and nonce updates, which overwrite account data
+// in place; these are recorded in @SEGMENT_JOURNAL as (pointer, old value) pairs. A checkpoint is
+// thus the state trie root pointer, the journal size and the logs sizes, stored in the context
+// metadata of the context which may have to be reverted.
+
+// Records the current value at the given trie data pointer, before it gets overwritten in place.
+%macro journal_trie_data_write
+    // stack: ptr
+    DUP1
+    DUP1
+    %mload_trie_data
+    // stack: old_value, ptr, ptr
+    %mload_global_metadata(@GLOBAL_METADATA_JOURNAL_LEN)
+    %stack (journal_len, old_value, ptr) -> (journal_len, ptr, journal_len, old_value, journal_len)
+    %mstore_kernel(@SEGMENT_JOURNAL)
+    // stack: journal_len, old_value, journal_len, ptr
+    %increment
+    %mstore_kernel(@SEGMENT_JOURNAL)
+    // stack: journal_len, ptr
+    %add_const(2)
+    %mstore_global_metadata(@GLOBAL_METADATA_JOURNAL_LEN)
+    // stack: ptr
+%endmacro
+
+%macro save_checkpoint_field(global_field, ctx_field)
+    // stack: ctx
+    %mload_global_metadata($global_field)
+    PUSH $ctx_field
+    PUSH @SEGMENT_CONTEXT_METADATA
+    DUP4 // ctx
+    MSTORE_GENERAL
+    // stack: ctx
+%endmacro
+
+%macro restore_checkpoint_field(ctx_field, global_field)
+    // stack: ctx
+    PUSH $ctx_field
+    PUSH @SEGMENT_CONTEXT_METADATA
+    DUP3 // ctx
+    MLOAD_GENERAL
+    %mstore_global_metadata($global_field)
+    // stack: ctx
+%endmacro
+
+// Saves a checkpoint of the current state in the metadata of the given context.
+%macro checkpoint
+    // stack: ctx
+    %save_checkpoint_field(@GLOBAL_METADATA_STATE_TRIE_ROOT, @CTX_METADATA_STATE_TRIE_CHECKPOINT_PTR)
+    %save_checkpoint_field(@GLOBAL_METADATA_JOURNAL_LEN, @CTX_METADATA_JOURNAL_CHECKPOINT)
+    %save_checkpoint_field(@GLOBAL_METADATA_LOGS_LEN, @CTX_METADATA_LOGS_CHECKPOINT)
+    %save_checkpoint_field(@GLOBAL_METADATA_LOGS_DATA_LEN, @CTX_METADATA_LOGS_DATA_CHECKPOINT)
+    // stack: ctx
+%endmacro
+
+// Reverts the state to the checkpoint saved in the metadata of the given context.
+// Pre stack: ctx, retdest
+// Post stack: (empty)
+global revert_to_checkpoint:
+    // stack: ctx, retdest
+    %restore_checkpoint_field(@CTX_METADATA_STATE_TRIE_CHECKPOINT_PTR, @GLOBAL_METADATA_STATE_TRIE_ROOT)
+    %restore_checkpoint_field(@CTX_METADATA_LOGS_CHECKPOINT, @GLOBAL_METADATA_LOGS_LEN)
+    %restore_checkpoint_field(@CTX_METADATA_LOGS_DATA_CHECKPOINT, @GLOBAL_METADATA_LOGS_DATA_LEN)
+    PUSH @CTX_METADATA_JOURNAL_CHECKPOINT
+    PUSH @SEGMENT_CONTEXT_METADATA
+    DUP3 // ctx
+    MLOAD_GENERAL
+    // stack: journal_checkpoint, ctx, retdest
+    SWAP1
+    POP
+    %mload_global_metadata(@GLOBAL_METADATA_JOURNAL_LEN)
+
+    // Undo the journaled writes, most recent first.
+revert_to_checkpoint_loop:
+    // stack: journal_len, journal_checkpoint, retdest
+    DUP2
+    DUP2
+    EQ
+    %jumpi(revert_to_checkpoint_done)
+    %sub_const(2)
+    // stack: journal_len', journal_checkpoint, retdest
+    DUP1
+    %increment
+    %mload_kernel(@SEGMENT_JOURNAL)
+    // stack: old_value, journal_len', journal_checkpoint, retdest
+    DUP2
+    %mload_kernel(@SEGMENT_JOURNAL)
+    // stack: ptr, old_value, journal_len', journal_checkpoint, retdest
+    %mstore_trie_data
+    %jump(revert_to_checkpoint_loop)
+
+revert_to_checkpoint_done:
+    // stack: journal_checkpoint, journal_checkpoint, retdest
+    %mstore_global_metadata(@GLOBAL_METADATA_JOURNAL_LEN)
+    POP
+    JUMP
+
+// Convenience macro to call revert_to_checkpoint and return where we left off.
+%macro revert_to_checkpoint
+    %stack (ctx) -> (ctx, %%after)
+    %jump(revert_to_checkpoint)
+%%after:
+%endmacro
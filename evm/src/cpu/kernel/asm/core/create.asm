@@ -53,6 +53,14 @@ create_inner:
 
     %create_context
     // stack: new_ctx, address, CODE_ADDR, code_len, retdest
+
+    // Store the size of the initialization code, which is the code of the new context.
+    %stack (new_ctx, address, CODE_ADDR: 3, code_len)
+        -> (new_ctx, @SEGMENT_CONTEXT_METADATA, @CTX_METADATA_CODE_SIZE, code_len,
+            new_ctx, address, CODE_ADDR, code_len)
+    MSTORE_GENERAL
+    // stack: new_ctx, address, CODE_ADDR, code_len, retdest
+
     %stack (new_ctx, address, src_ctx, src_segment, src_offset, code_len)
         -> (new_ctx, @SEGMENT_CODE, 0,
             src_ctx, src_segment, src_offset,
@@ -79,7 +87,10 @@ run_constructor:
     EXIT_KERNEL
 
 after_constructor:
-    // stack: address, retdest
+    // stack: success, leftover_gas, address, retdest
     // TODO: If code was returned, store it in the account.
+    // TODO: Use the success flag, and refund the leftover gas.
+    %pop2
+    // stack: address, retdest
     SWAP1
     JUMP
@@ -0,0 +1,191 @@
+// Handlers for exceptions raised by user code, such as running out of gas or jumping to an invalid
+// destination. Each of them halts the current context, see `fault_exception`.
+//
+// When user code faults, the CPU traps to the handler of the exception in `exception_jumptable`,
+// with the trap info
+//     pc | (stack_len << 64) | (gas_used << 192)
+// on top of the user's stack, where pc is the address of the faulting instruction and stack_len is
+// the length of the stack before the trap. The CPU lets the prover raise any exception on any
+// instruction, so each handler must first verify that the exception was warranted, and panic
+// otherwise.
+
+global exception_jumptable:
+    // exception 0: out of gas
+    JUMPTABLE exc_out_of_gas
+
+    // exception 1: invalid opcode
+    JUMPTABLE exc_invalid_opcode
+
+    // exception 2: stack underflow
+    JUMPTABLE exc_stack_underflow
+
+    // exception 3: invalid jump destination
+    JUMPTABLE exc_invalid_jump_destination
+
+    // exception 4: invalid jumpi destination
+    JUMPTABLE exc_invalid_jumpi_destination
+
+    // exception 5: stack overflow
+    JUMPTABLE exc_stack_overflow
+
+    // exceptions 6 and 7: unused
+    JUMPTABLE panic
+    JUMPTABLE panic
+
+global exc_out_of_gas:
+    // stack: trap_info
+    DUP1 %opcode_from_exc_trap_info
+    %mload_kernel_code(gas_cost_for_opcode)
+    // stack: gas_cost, trap_info
+    SWAP1 %shr_const(192)
+    ADD
+    // stack: gas_used + gas_cost
+    %mload_context_metadata(@CTX_METADATA_GAS_LIMIT)
+    // stack: gas_limit, gas_used + gas_cost
+    LT
+    // stack: gas_limit < gas_used + gas_cost
+    %jumpi(fault_exception)
+    PANIC
+
+global exc_invalid_opcode:
+    // stack: trap_info
+    %opcode_from_exc_trap_info
+    // stack: opcode
+    PUSH @INVALID_OPCODES_USER
+    // stack: invalid_opcodes_user, opcode
+    SWAP1
+    // stack: opcode, invalid_opcodes_user
+    SHR
+    %and_const(1)
+    // stack: opcode_is_invalid
+    %jumpi(fault_exception)
+    PANIC
+
+global exc_stack_underflow:
+    // stack: trap_info
+    DUP1 %opcode_from_exc_trap_info
+    %mload_kernel_code(min_stack_len_for_opcode)
+    // stack: min_stack_len, trap_info
+    SWAP1 %stack_len_from_exc_trap_info
+    // stack: stack_len, min_stack_len
+    LT
+    // stack: stack_len < min_stack_len
+    %jumpi(fault_exception)
+    PANIC
+
+global exc_invalid_jump_destination:
+    // stack: trap_info, jump_dest
+    %opcode_from_exc_trap_info
+    %eq_const(0x56) // JUMP
+    ISZERO %jumpi(panic)
+    // stack: jump_dest
+    %jump(verify_invalid_jump_destination)
+
+global exc_invalid_jumpi_destination:
+    // stack: trap_info, jump_dest, condition
+    %opcode_from_exc_trap_info
+    %eq_const(0x57) // JUMPI
+    ISZERO %jumpi(panic)
+    // stack: jump_dest, condition
+    // If the condition is zero, we don't jump, so the destination doesn't matter.
+    SWAP1 ISZERO %jumpi(panic)
+    // stack: jump_dest
+    %jump(verify_invalid_jump_destination)
+
+global exc_stack_overflow:
+    // stack: trap_info
+    DUP1 %opcode_from_exc_trap_info
+    %mload_kernel_code(stack_len_increased)
+    // stack: stack_len_increased, trap_info
+    SWAP1 %stack_len_from_exc_trap_info
+    // stack: stack_len, stack_len_increased
+    %ge_const(1024)
+    // stack: stack_len >= 1024, stack_len_increased
+    MUL // cheaper than AND
+    %jumpi(fault_exception)
+    PANIC
+
+// Pre stack: jump_dest
+// Post stack: (empty, since we halt the current context)
+verify_invalid_jump_destination:
+    // stack: jump_dest
+    // Destinations which don't fit in 32 bits are out of the code, so they are always invalid.
+    DUP1 %gt_const(0xffffffff)
+    %jumpi(fault_exception)
+    // stack: jump_dest
+    %mload_current(@SEGMENT_JUMPDEST_BITS)
+    // stack: is_valid_jump_destination
+    %jumpi(panic)
+    %jump(fault_exception)
+
+%macro opcode_from_exc_trap_info
+    // stack: trap_info
+    %and_const(0xffffffff)
+    // stack: pc
+    %mload_current_code
+    // stack: opcode
+%endmacro
+
+%macro stack_len_from_exc_trap_info
+    // stack: trap_info
+    %shr_const(64)
+    %and_const(0xffffffff)
+    // stack: stack_len
+%endmacro
+
+// The static gas cost of each native instruction; the other instructions charge gas in the kernel.
+global gas_cost_for_opcode:
+    BYTES 0, 3, 5, 3, 5, 0, 5, 0, 8, 8, 0, 0, 0, 0, 0, 0 // 0x00-0x0f
+    BYTES 3, 3, 0, 0, 3, 3, 3, 3, 3, 3, 3, 3, 3, 0, 0, 0 // 0x10-0x1f
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0x20-0x2f
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0x30-0x3f
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0x40-0x4f
+    BYTES 2, 0, 0, 0, 0, 0, 8, 10, 2, 0, 0, 1, 0, 0, 0, 0 // 0x50-0x5f
+    BYTES 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3 // 0x60-0x6f
+    BYTES 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3 // 0x70-0x7f
+    BYTES 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3 // 0x80-0x8f
+    BYTES 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3 // 0x90-0x9f
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0xa0-0xaf
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0xb0-0xbf
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0xc0-0xcf
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0xd0-0xdf
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0xe0-0xef
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0xf0-0xff
+
+// The number of stack items read by each instruction which is valid in user mode.
+global min_stack_len_for_opcode:
+    BYTES 0, 2, 2, 2, 2, 2, 2, 2, 3, 3, 2, 2, 0, 0, 0, 0 // 0x00-0x0f
+    BYTES 2, 2, 2, 2, 2, 1, 2, 2, 2, 1, 2, 2, 2, 2, 0, 0 // 0x10-0x1f
+    BYTES 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0x20-0x2f
+    BYTES 0, 1, 0, 0, 0, 1, 0, 3, 0, 3, 0, 1, 4, 0, 3, 1 // 0x30-0x3f
+    BYTES 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0x40-0x4f
+    BYTES 1, 1, 2, 2, 1, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0 // 0x50-0x5f
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0x60-0x6f
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0x70-0x7f
+    BYTES 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16 // 0x80-0x8f
+    BYTES 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17 // 0x90-0x9f
+    BYTES 2, 3, 4, 5, 6, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0xa0-0xaf
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0xb0-0xbf
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0xc0-0xcf
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0xd0-0xdf
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0xe0-0xef
+    BYTES 3, 7, 7, 2, 6, 4, 0, 0, 0, 0, 6, 0, 0, 2, 0, 1 // 0xf0-0xff
+
+// Whether each instruction which is valid in user mode increases the length of the stack.
+global stack_len_increased:
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0x00-0x0f
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0x10-0x1f
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0x20-0x2f
+    BYTES 1, 0, 1, 1, 1, 0, 1, 0, 1, 0, 1, 0, 0, 1, 0, 0 // 0x30-0x3f
+    BYTES 0, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0 // 0x40-0x4f
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 0, 0, 0, 0, 0 // 0x50-0x5f
+    BYTES 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1 // 0x60-0x6f
+    BYTES 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1 // 0x70-0x7f
+    BYTES 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1 // 0x80-0x8f
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0x90-0x9f
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0xa0-0xaf
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0xb0-0xbf
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0xc0-0xcf
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0xd0-0xdf
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0xe0-0xef
+    BYTES 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 // 0xf0-0xff
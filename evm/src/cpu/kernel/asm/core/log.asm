@@ -0,0 +1,147 @@
+// Handlers for the LOG0-LOG4 syscalls.
+//
+// Each log is appended to @SEGMENT_LOGS_DATA in the format in which it is
+// stored in a receipt, namely
+//     payload_len, address, num_topics, topics[num_topics], data_len, data[data_len]
+// where payload_len is the length of the log's RLP payload, and data is stored
+// one byte per cell. A pointer to the log is appended to @SEGMENT_LOGS. At the
+// end of the transaction, the logs are moved into its receipt; see receipt.asm.
+
+global sys_log0:
+    %log(0)
+global sys_log1:
+    %log(1)
+global sys_log2:
+    %log(2)
+global sys_log3:
+    %log(3)
+global sys_log4:
+    %log(4)
+
+%macro log(num_topics)
+    // stack: kexit_info, offset, size, topics
+    // Logs are a state modification, so they are forbidden in a static context.
+    %mload_context_metadata(@CTX_METADATA_STATIC)
+    %jumpi(fault_exception)
+    %stack (kexit_info, offset, size) -> (offset, size, kexit_info, offset, size)
+    %expand_memory
+    // stack: kexit_info, offset, size, topics
+    %charge_gas_const(@GAS_LOG)
+    PUSH $num_topics
+    %mul_const(@GAS_LOGTOPIC)
+    %charge_gas
+    DUP3
+    %mul_const(@GAS_LOGDATA)
+    %charge_gas
+    // stack: kexit_info, offset, size, topics
+    PUSH $num_topics
+    %jump(log_n)
+%endmacro
+
+// Pre stack: num_topics, kexit_info, offset, size, topics
+// Post stack: (empty, since we exit the kernel)
+global log_n:
+    // stack: num_topics, kexit_info, offset, size, topics
+    %mload_global_metadata(@GLOBAL_METADATA_LOGS_DATA_LEN)
+    // stack: log_ptr, num_topics, kexit_info, offset, size, topics
+    DUP1
+    %mload_global_metadata(@GLOBAL_METADATA_LOGS_LEN)
+    // stack: logs_len, log_ptr, log_ptr, num_topics, kexit_info, offset, size, topics
+    DUP1 %increment %mstore_global_metadata(@GLOBAL_METADATA_LOGS_LEN)
+    // stack: logs_len, log_ptr, log_ptr, num_topics, kexit_info, offset, size, topics
+    %mstore_kernel(@SEGMENT_LOGS)
+    // stack: log_ptr, num_topics, kexit_info, offset, size, topics
+
+    // The payload length is written last, once we know the length of the data.
+    %address
+    DUP2 %increment
+    %mstore_kernel(@SEGMENT_LOGS_DATA)
+    DUP2
+    DUP2 %add_const(2)
+    %mstore_kernel(@SEGMENT_LOGS_DATA)
+    // stack: log_ptr, num_topics, kexit_info, offset, size, topics
+
+    // The data is written before the topics, which are at the bottom of the stack.
+    DUP2 DUP2 %add_const(3) ADD
+    // stack: data_len_ptr, log_ptr, num_topics, kexit_info, offset, size, topics
+    DUP6 DUP2
+    %mstore_kernel(@SEGMENT_LOGS_DATA)
+    %increment
+    // stack: data_ptr, log_ptr, num_topics, kexit_info, offset, size, topics
+    DUP6 DUP2 ADD
+    %mstore_global_metadata(@GLOBAL_METADATA_LOGS_DATA_LEN)
+    PUSH log_n_data_copied
+    DUP7 DUP7
+    PUSH @SEGMENT_MAIN_MEMORY
+    GET_CONTEXT
+    DUP6
+    PUSH @SEGMENT_LOGS_DATA
+    PUSH 0
+    // stack: DST, SRC, size, log_n_data_copied, data_ptr, log_ptr, num_topics, kexit_info, offset, size, topics
+    %jump(memcpy)
+
+log_n_data_copied:
+    // stack: data_ptr, log_ptr, num_topics, kexit_info, offset, size, topics
+    DUP6 SWAP1
+    %rlp_logs_data_string_len
+    // stack: data_rlp_len, log_ptr, num_topics, kexit_info, offset, size, topics
+    // Each topic is encoded as 0xa0 followed by its 32 bytes.
+    DUP3 %mul_const(33)
+    %rlp_list_len
+    ADD
+    // The address is encoded as 0x94 followed by its 20 bytes.
+    %add_const(21)
+    // stack: payload_len, log_ptr, num_topics, kexit_info, offset, size, topics
+    DUP2
+    %mstore_kernel(@SEGMENT_LOGS_DATA)
+    // stack: log_ptr, num_topics, kexit_info, offset, size, topics
+    %add_const(3)
+    %stack (topic_ptr, num_topics, kexit_info, offset, size) -> (num_topics, topic_ptr, kexit_info)
+log_n_topics_loop:
+    // stack: remaining_topics, topic_ptr, kexit_info, topics
+    DUP1 ISZERO
+    %jumpi(log_n_after_topics)
+    %decrement
+    %stack (remaining_topics, topic_ptr, kexit_info, topic)
+        -> (topic_ptr, topic, remaining_topics, topic_ptr, kexit_info)
+    %mstore_kernel(@SEGMENT_LOGS_DATA)
+    // stack: remaining_topics, topic_ptr, kexit_info, topics
+    SWAP1 %increment SWAP1
+    %jump(log_n_topics_loop)
+
+log_n_after_topics:
+    // stack: 0, topic_ptr, kexit_info
+    %pop2
+    // stack: kexit_info
+    EXIT_KERNEL
+
+// Computes the length of the RLP encoding of a string stored in
+// @SEGMENT_LOGS_DATA, one byte per cell.
+%macro rlp_logs_data_string_len
+    // stack: ptr, len
+    DUP2 %eq_const(1)
+    SWAP1
+    %mload_kernel(@SEGMENT_LOGS_DATA)
+    %lt_const(0x80)
+    MUL // cheaper than AND
+    // stack: single_small_byte, len
+    %jumpi(%%single_small_byte)
+    // stack: len
+    DUP1 %gt_const(55)
+    %jumpi(%%large)
+    // Medium case; the prefix is 0x80 + len.
+    %increment
+    %jump(%%finish)
+%%large:
+    // Large case; the prefix is 0xb7 + len_of_len, followed by len.
+    DUP1 %num_bytes
+    ADD
+    %increment
+    %jump(%%finish)
+%%single_small_byte:
+    // stack: len
+    // The string is a single byte less than 0x80, which is its own encoding.
+    POP
+    PUSH 1
+%%finish:
+%endmacro
@@ -27,6 +27,7 @@ global increment_nonce:
     %increment
     SWAP1
     // stack: nonce_ptr, nonce', retdest
+    %journal_trie_data_write
     %mstore_trie_data
     // stack: retdest
     JUMP
@@ -0,0 +1,168 @@
+// The BLAKE2 compression function F precompile, at address 0x09, as specified in EIP-152.
+//
+// The input is exactly 213 bytes: rounds (4 bytes, big-endian), h (8 little-endian u64s),
+// m (16 little-endian u64s), t (2 little-endian u64s) and f (1 byte, 0 or 1). The output is the
+// new h, as 8 little-endian u64s. The gas cost is the number of rounds.
+//
+// We reuse the G functions of our BLAKE2b implementation, whose working memory is addressed
+// relative to the number of blocks stored at @SEGMENT_KERNEL_GENERAL[0]. Setting it to 0 gives
+// the following layout:
+//     { 2-9: h, 10-25: internal state, 26-41: m, 64-276: input }
+
+// Copies `count` little-endian u64s from @SEGMENT_KERNEL_GENERAL[src..] to consecutive addresses
+// @SEGMENT_KERNEL_GENERAL[dst..].
+%macro blake2_f_load_u64s(src, dst, count)
+    PUSH 0
+%%loop:
+    // stack: i
+    DUP1
+    %eq_const($count)
+    %jumpi(%%end)
+    // stack: i
+    DUP1
+    %mul_const(8)
+    %add_const($src)
+    %mload_kernel_general_u64_LE
+    // stack: word, i
+    DUP2
+    %add_const($dst)
+    %mstore_kernel_general
+    // stack: i
+    %increment
+    %jump(%%loop)
+%%end:
+    // stack: i
+    POP
+%endmacro
+
+// Loads the number of rounds, a big-endian u32 at the start of the input.
+%macro blake2_f_rounds
+    PUSH 64
+    %mload_kernel_general_u32
+    // stack: rounds
+%endmacro
+
+global precompile_blake2_f:
+    // stack: gas, IN: 4, retdest
+    DUP5
+    %eq_const(213)
+    ISZERO
+    %jumpi(precompile_blake2_f_invalid_length)
+    // stack: gas, IN: 4, retdest
+    %stack (gas, in: 4) -> (64, 213, in, precompile_blake2_f_input_loaded, gas, in)
+    %jump(load_precompile_input)
+precompile_blake2_f_invalid_length:
+    // stack: gas, IN: 4, retdest
+    %pop5
+    %jump(precompile_fail)
+
+precompile_blake2_f_input_loaded:
+    // stack: gas, IN: 4, retdest
+    %blake2_f_rounds
+    // stack: rounds, gas, IN: 4, retdest
+    %mul_const(@BLAKE2_F_ROUND_GAS)
+    %charge_precompile_gas
+    // stack: leftover_gas, IN: 4, retdest
+    %stack (leftover_gas, in: 4) -> (leftover_gas)
+    // stack: leftover_gas, retdest
+
+    // The final block indicator f must be 0 or 1.
+    %mload_kernel_general(276)
+    // stack: f, leftover_gas, retdest
+    DUP1
+    %gt_const(1)
+    %jumpi(precompile_blake2_f_invalid_f)
+
+    // stack: f, leftover_gas, retdest
+    PUSH 0
+    %mstore_kernel_general(0)
+    %blake2_f_load_u64s(68, 2, 8)
+    %blake2_f_load_u64s(132, 26, 16)
+
+    // First eight words of the internal state: h_0, ..., h_7.
+    %blake2_f_load_u64s(68, 10, 8)
+    // Next four words: IV_0, ..., IV_3.
+    %blake2b_iv_i(0)
+    %mstore_kernel_general(18)
+    %blake2b_iv_i(1)
+    %mstore_kernel_general(19)
+    %blake2b_iv_i(2)
+    %mstore_kernel_general(20)
+    %blake2b_iv_i(3)
+    %mstore_kernel_general(21)
+    // Last four words: IV_4, ..., IV_7, XOR'd with (t_0, t_1, invert_if_f, 0).
+    PUSH 260
+    %mload_kernel_general_u64_LE
+    %blake2b_iv_i(4)
+    XOR
+    %mstore_kernel_general(22)
+    PUSH 268
+    %mload_kernel_general_u64_LE
+    %blake2b_iv_i(5)
+    XOR
+    %mstore_kernel_general(23)
+    // stack: f, leftover_gas, retdest
+    %mul_const(0xFFFFFFFFFFFFFFFF)
+    %blake2b_iv_i(6)
+    XOR
+    %mstore_kernel_general(24)
+    %blake2b_iv_i(7)
+    %mstore_kernel_general(25)
+
+    // stack: leftover_gas, retdest
+    %blake2b_internal_state_addr
+    PUSH 0
+    // stack: round = 0, start, leftover_gas, retdest
+precompile_blake2_f_round_loop:
+    // stack: round, start, leftover_gas, retdest
+    DUP1
+    %blake2_f_rounds
+    // stack: rounds, round, round, start, leftover_gas, retdest
+    EQ
+    %jumpi(precompile_blake2_f_rounds_done)
+    // stack: round, start, leftover_gas, retdest
+    %call_blake2b_g_function(0, 4, 8, 12, 0, 1)
+    %call_blake2b_g_function(1, 5, 9, 13, 2, 3)
+    %call_blake2b_g_function(2, 6, 10, 14, 4, 5)
+    %call_blake2b_g_function(3, 7, 11, 15, 6, 7)
+    %call_blake2b_g_function(0, 5, 10, 15, 8, 9)
+    %call_blake2b_g_function(1, 6, 11, 12, 10, 11)
+    %call_blake2b_g_function(2, 7, 8, 13, 12, 13)
+    %call_blake2b_g_function(3, 4, 9, 14, 14, 15)
+    // stack: round, start, leftover_gas, retdest
+    %increment
+    %jump(precompile_blake2_f_round_loop)
+
+precompile_blake2_f_rounds_done:
+    // stack: round, start, leftover_gas, retdest
+    %pop2
+    // stack: leftover_gas, retdest
+    // Output h_i' = h_i ^ v_i ^ v_(i+8), with each word in little-endian order.
+    %blake2b_generate_new_hash_value(3)
+    %reverse_bytes_u64
+    %blake2b_generate_new_hash_value(2)
+    %reverse_bytes_u64
+    %blake2b_generate_new_hash_value(1)
+    %reverse_bytes_u64
+    %blake2b_generate_new_hash_value(0)
+    %reverse_bytes_u64
+    %u64s_to_u256
+    %mstore_returndata_word(0)
+    %blake2b_generate_new_hash_value(7)
+    %reverse_bytes_u64
+    %blake2b_generate_new_hash_value(6)
+    %reverse_bytes_u64
+    %blake2b_generate_new_hash_value(5)
+    %reverse_bytes_u64
+    %blake2b_generate_new_hash_value(4)
+    %reverse_bytes_u64
+    %u64s_to_u256
+    %mstore_returndata_word(32)
+    // stack: leftover_gas, retdest
+    PUSH 64
+    %jump(precompile_success)
+
+precompile_blake2_f_invalid_f:
+    // stack: f, leftover_gas, retdest
+    %pop2
+    %jump(precompile_fail)
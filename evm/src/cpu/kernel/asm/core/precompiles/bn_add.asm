@@ -0,0 +1,36 @@
+// The BN254 addition precompile, at address 0x06.
+// Input: x0, y0, x1, y1, as 32-byte words. Output: x, y, as 32-byte words.
+// Fails if either point is not on the curve.
+global precompile_bn_add:
+    // stack: gas, IN: 4, retdest
+    PUSH @BN_ADD_GAS
+    %charge_precompile_gas
+    // stack: leftover_gas, IN: 4, retdest
+    %stack (leftover_gas, in: 4) -> (0, 128, in, precompile_bn_add_input_loaded, leftover_gas)
+    %jump(load_precompile_input)
+precompile_bn_add_input_loaded:
+    // stack: leftover_gas, retdest
+    %mload_precompile_word(96)
+    %mload_precompile_word(64)
+    %mload_precompile_word(32)
+    %mload_precompile_word(0)
+    // stack: x0, y0, x1, y1, leftover_gas, retdest
+    %stack (x0, y0, x1, y1) -> (x0, y0, x1, y1, precompile_bn_contd)
+    %jump(bn_add)
+
+// Shared by the BN254 addition and multiplication precompiles.
+global precompile_bn_contd:
+    // stack: x, y, leftover_gas, retdest
+    DUP1
+    %eq_const(@U256_MAX)
+    %jumpi(precompile_bn_invalid_input)
+    // stack: x, y, leftover_gas, retdest
+    %mstore_returndata_word(0)
+    %mstore_returndata_word(32)
+    // stack: leftover_gas, retdest
+    PUSH 64
+    %jump(precompile_success)
+precompile_bn_invalid_input:
+    // stack: x, y, leftover_gas, retdest
+    %pop3
+    %jump(precompile_fail)
@@ -0,0 +1,18 @@
+// The BN254 scalar multiplication precompile, at address 0x07.
+// Input: x, y, s, as 32-byte words. Output: s * (x, y), as two 32-byte words.
+// Fails if the point is not on the curve.
+global precompile_bn_mul:
+    // stack: gas, IN: 4, retdest
+    PUSH @BN_MUL_GAS
+    %charge_precompile_gas
+    // stack: leftover_gas, IN: 4, retdest
+    %stack (leftover_gas, in: 4) -> (0, 96, in, precompile_bn_mul_input_loaded, leftover_gas)
+    %jump(load_precompile_input)
+precompile_bn_mul_input_loaded:
+    // stack: leftover_gas, retdest
+    %mload_precompile_word(64)
+    %mload_precompile_word(32)
+    %mload_precompile_word(0)
+    // stack: x, y, s, leftover_gas, retdest
+    %stack (x, y, s) -> (x, y, s, precompile_bn_contd)
+    %jump(bn_mul)
@@ -0,0 +1,34 @@
+// The ecrecover precompile, at address 0x01.
+// Input: hash, v, r, s, as 32-byte words. Output: the recovered address, left-padded to 32 bytes.
+// An invalid signature is not a failure, but results in empty returndata.
+global precompile_ecrec:
+    // stack: gas, IN: 4, retdest
+    PUSH @ECREC_GAS
+    %charge_precompile_gas
+    // stack: leftover_gas, IN: 4, retdest
+    %stack (leftover_gas, in: 4) -> (0, 128, in, precompile_ecrec_input_loaded, leftover_gas)
+    %jump(load_precompile_input)
+precompile_ecrec_input_loaded:
+    // stack: leftover_gas, retdest
+    %mload_precompile_word(96)
+    %mload_precompile_word(64)
+    %mload_precompile_word(32)
+    %mload_precompile_word(0)
+    // stack: hash, v, r, s, leftover_gas, retdest
+    %stack (hash, v, r, s) -> (hash, v, r, s, precompile_ecrec_contd)
+    %jump(ecrecover)
+precompile_ecrec_contd:
+    // stack: address, leftover_gas, retdest
+    DUP1
+    %eq_const(@U256_MAX)
+    %jumpi(precompile_ecrec_invalid_signature)
+    // stack: address, leftover_gas, retdest
+    %mstore_returndata_word(0)
+    // stack: leftover_gas, retdest
+    PUSH 32
+    %jump(precompile_success)
+precompile_ecrec_invalid_signature:
+    // stack: U256_MAX, leftover_gas, retdest
+    POP
+    PUSH 0
+    %jump(precompile_success)
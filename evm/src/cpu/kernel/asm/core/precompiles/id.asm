@@ -0,0 +1,15 @@
+// The identity precompile, at address 0x04, which returns its input.
+global precompile_id:
+    // stack: gas, IN: 4, retdest
+    DUP5
+    %precompile_word_gas(@ID_STATIC_GAS, @ID_DYNAMIC_GAS)
+    %charge_precompile_gas
+    // stack: leftover_gas, input_ctx, input_segment, input_offset, input_size, retdest
+    GET_CONTEXT
+    %stack (ctx, leftover_gas, input_ctx, input_segment, input_offset, input_size)
+        -> (ctx, @SEGMENT_RETURNDATA, 0, input_ctx, input_segment, input_offset, input_size,
+            precompile_id_contd, input_size, leftover_gas)
+    %jump(memcpy)
+precompile_id_contd:
+    // stack: input_size, leftover_gas, retdest
+    %jump(precompile_success)
@@ -0,0 +1,171 @@
+// Routing and shared helpers for the precompiled contracts.
+//
+// Precompiles run inside the kernel, in the caller's context. Each handler reads its input from
+//     IN = (input_ctx, input_segment, input_offset, input_size),
+// writes its output to the current context's @SEGMENT_RETURNDATA, and sets
+// @CTX_METADATA_RETURNDATA_SIZE accordingly. On failure, all the gas given to the precompile is
+// consumed and the returndata is empty.
+
+// Returns 1 if the given address is a precompile, i.e. if 1 <= address <= 9, and 0 otherwise.
+%macro is_precompile
+    // stack: address
+    %decrement
+    // stack: address - 1 (wraps around for address = 0)
+    %lt_const(@BLAKE2_F)
+    // stack: is_precompile
+%endmacro
+
+%macro route_precompile(address, handler)
+    // stack: address, gas, IN: 4, retdest
+    DUP1
+    %eq_const($address)
+    %jumpi(%%route)
+    %jump(%%next)
+%%route:
+    POP
+    // stack: gas, IN: 4, retdest
+    %jump($handler)
+%%next:
+%endmacro
+
+// Runs the precompile at the given address.
+// Pre stack: address, gas, IN: 4, retdest
+// Post stack: success, leftover_gas
+global handle_precompile:
+    // stack: address, gas, IN: 4, retdest
+    %route_precompile(@ECREC, precompile_ecrec)
+    %route_precompile(@SHA256, precompile_sha256)
+    %route_precompile(@RIP160, precompile_rip160)
+    %route_precompile(@ID, precompile_id)
+    %route_precompile(@BN_ADD, precompile_bn_add)
+    %route_precompile(@BN_MUL, precompile_bn_mul)
+    %route_precompile(@BLAKE2_F, precompile_blake2_f)
+    // The remaining addresses are EXPMOD and SNARKV.
+    %jump(precompile_unsupported)
+
+// EXPMOD and SNARKV are not implemented yet. Rather than treat them as accounts without code, which
+// would succeed with empty output, a call to either of them fails like a precompile given invalid
+// input: it consumes all the gas given to it and returns no data. Blocks whose transactions call
+// them successfully on Ethereum therefore cannot be proven.
+// TODO: Support EXPMOD and SNARKV.
+global precompile_unsupported:
+    // stack: address, gas, IN: 4, retdest
+    %pop6
+    // stack: retdest
+    %jump(precompile_fail)
+
+// Charges the given cost from the gas given to the precompile, failing if it is insufficient.
+%macro charge_precompile_gas
+    // stack: cost, gas, IN: 4, retdest
+    DUP2
+    DUP2
+    GT
+    // stack: cost > gas, cost, gas, IN: 4, retdest
+    %jumpi(precompile_out_of_gas)
+    // stack: cost, gas, IN: 4, retdest
+    SWAP1
+    SUB
+    // stack: leftover_gas, IN: 4, retdest
+%endmacro
+
+// Returns ceil(size / 32) * dynamic_gas + static_gas.
+%macro precompile_word_gas(static_gas, dynamic_gas)
+    // stack: size
+    %add_const(31)
+    %div_const(32)
+    %mul_const($dynamic_gas)
+    %add_const($static_gas)
+    // stack: cost
+%endmacro
+
+global precompile_out_of_gas:
+    // stack: cost, gas, IN: 4, retdest
+    %pop6
+    // stack: retdest
+    %jump(precompile_fail)
+
+// Fails the precompile, consuming all the gas given to it.
+// Pre stack: retdest
+// Post stack: success = 0, leftover_gas = 0
+global precompile_fail:
+    // stack: retdest
+    PUSH 0
+    %mstore_context_metadata(@CTX_METADATA_RETURNDATA_SIZE)
+    %stack (retdest) -> (retdest, 0, 0)
+    JUMP
+
+// Pre stack: returndata_size, leftover_gas, retdest
+// Post stack: success = 1, leftover_gas
+global precompile_success:
+    // stack: returndata_size, leftover_gas, retdest
+    %mstore_context_metadata(@CTX_METADATA_RETURNDATA_SIZE)
+    %stack (leftover_gas, retdest) -> (retdest, 1, leftover_gas)
+    JUMP
+
+// Copies the first `len` bytes of the input to @SEGMENT_KERNEL_GENERAL, starting at `virt`.
+// Bytes past the end of the input are read as zero.
+// Pre stack: virt, len, IN: 4, retdest
+// Post stack: (empty)
+global load_precompile_input:
+    // stack: virt, len, IN: 4, retdest
+    PUSH 0 // i = 0
+load_precompile_input_loop:
+    // stack: i, virt, len, input_ctx, input_segment, input_offset, input_size, retdest
+    DUP3
+    DUP2
+    EQ
+    // stack: i == len, i, virt, len, IN: 4, retdest
+    %jumpi(load_precompile_input_finish)
+    // stack: i, virt, len, input_ctx, input_segment, input_offset, input_size, retdest
+    DUP7
+    DUP2
+    LT
+    // stack: i < input_size, i, virt, len, IN: 4, retdest
+    %jumpi(load_precompile_input_read)
+    // stack: i, virt, len, IN: 4, retdest
+    PUSH 0
+    %jump(load_precompile_input_store)
+load_precompile_input_read:
+    // stack: i, virt, len, input_ctx, input_segment, input_offset, input_size, retdest
+    DUP1
+    DUP7
+    ADD
+    DUP6
+    DUP6
+    // stack: input_ctx, input_segment, input_offset + i, i, virt, len, IN: 4, retdest
+    MLOAD_GENERAL
+load_precompile_input_store:
+    // stack: byte, i, virt, len, IN: 4, retdest
+    DUP3
+    DUP3
+    ADD
+    // stack: virt + i, byte, i, virt, len, IN: 4, retdest
+    %mstore_kernel_general
+    // stack: i, virt, len, IN: 4, retdest
+    %increment
+    %jump(load_precompile_input_loop)
+load_precompile_input_finish:
+    // stack: i, virt, len, IN: 4, retdest
+    %pop7
+    // stack: retdest
+    JUMP
+
+// Loads the 32-byte big-endian word at @SEGMENT_KERNEL_GENERAL[virt..virt + 32].
+%macro mload_precompile_word(virt)
+    %stack () -> (0, @SEGMENT_KERNEL_GENERAL, $virt, 32, %%after)
+    %jump(mload_packing)
+%%after:
+    // stack: word
+%endmacro
+
+// Stores the given word, big-endian, at @SEGMENT_RETURNDATA[offset..offset + 32] in the current
+// context.
+%macro mstore_returndata_word(offset)
+    // stack: word
+    GET_CONTEXT
+    %stack (ctx, word) -> (ctx, @SEGMENT_RETURNDATA, $offset, word, 32, %%after)
+    %jump(mstore_unpacking)
+%%after:
+    // stack: offset'
+    POP
+%endmacro
@@ -0,0 +1,23 @@
+// The RIPEMD-160 precompile, at address 0x03.
+// The output is the 20-byte hash, left-padded to 32 bytes.
+global precompile_rip160:
+    // stack: gas, IN: 4, retdest
+    DUP5
+    %precompile_word_gas(@RIP160_STATIC_GAS, @RIP160_DYNAMIC_GAS)
+    %charge_precompile_gas
+    // stack: leftover_gas, IN: 4, retdest
+    // The ripemd routine uses @SEGMENT_KERNEL_GENERAL[0..136] as scratch space, so we put the
+    // input after it.
+    DUP5
+    %stack (size, leftover_gas, in: 4) -> (200, size, in, precompile_rip160_input_loaded, size, leftover_gas)
+    %jump(load_precompile_input)
+precompile_rip160_input_loaded:
+    // stack: size, leftover_gas, retdest
+    %stack (size) -> (200, size, precompile_rip160_contd)
+    %jump(ripemd)
+precompile_rip160_contd:
+    // stack: hash, leftover_gas, retdest
+    %mstore_returndata_word(0)
+    // stack: leftover_gas, retdest
+    PUSH 32
+    %jump(precompile_success)
@@ -0,0 +1,21 @@
+// The SHA-256 precompile, at address 0x02.
+global precompile_sha256:
+    // stack: gas, IN: 4, retdest
+    DUP5
+    %precompile_word_gas(@SHA256_STATIC_GAS, @SHA256_DYNAMIC_GAS)
+    %charge_precompile_gas
+    // stack: leftover_gas, IN: 4, retdest
+    // The sha2 routine expects its input at @SEGMENT_KERNEL_GENERAL[1..].
+    DUP5
+    %stack (size, leftover_gas, in: 4) -> (1, size, in, precompile_sha256_input_loaded, size, leftover_gas)
+    %jump(load_precompile_input)
+precompile_sha256_input_loaded:
+    // stack: size, leftover_gas, retdest
+    %stack (size) -> (0, size, precompile_sha256_contd)
+    %jump(sha2)
+precompile_sha256_contd:
+    // stack: hash, leftover_gas, retdest
+    %mstore_returndata_word(0)
+    // stack: leftover_gas, retdest
+    PUSH 32
+    %jump(precompile_success)
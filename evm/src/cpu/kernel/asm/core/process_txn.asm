@@ -1,13 +1,11 @@
 // After the transaction data has been parsed into a normalized set of fields
 // (see NormalizedTxnField), this routine processes the transaction.
 
-// TODO: Save checkpoints in @CTX_METADATA_STATE_TRIE_CHECKPOINT_PTR and @SEGMENT_STORAGE_TRIE_CHECKPOINT_PTRS.
-
 // Pre stack: retdest
 // Post stack: (empty)
 global process_normalized_txn:
     // stack: retdest
-    PUSH 0 // TODO: Load block's base fee
+    %mload_global_metadata(@GLOBAL_METADATA_BLOCK_BASE_FEE)
     %mload_txn_field(@TXN_FIELD_MAX_PRIORITY_FEE_PER_GAS)
     ADD
     // stack: priority_fee + base_fee, retdest
@@ -23,7 +21,15 @@ global process_normalized_txn:
 
 global validate:
     // stack: intrinsic_gas, retdest
-    POP // TODO: Assert gas_limit >= intrinsic_gas.
+    DUP1
+    %mload_txn_field(@TXN_FIELD_GAS_LIMIT)
+    // stack: gas_limit, intrinsic_gas, intrinsic_gas, retdest
+    LT
+    // stack: gas_limit < intrinsic_gas, intrinsic_gas, retdest
+global txn_failure_insufficient_gas_limit:
+    %jumpi(panic)
+    // stack: intrinsic_gas, retdest
+    %mstore_txn_field(@TXN_FIELD_INTRINSIC_GAS)
     // stack: retdest
     // TODO: Check that txn nonce matches account nonce.
     // TODO: Assert nonce is correct.
@@ -73,10 +79,18 @@ global process_contract_creation_txn:
 global process_contract_creation_txn_after_create:
     // stack: new_address, retdest
     POP
-    JUMP
+    // TODO: Use the success flag of the constructor, and refund its leftover gas.
+    PUSH 0 // leftover_gas
+    PUSH 1 // success
+    %jump(process_receipt)
 
 global process_message_txn:
     // stack: retdest
+    // If the transaction fails, its state changes are reverted to this checkpoint, which the
+    // kernel's context keeps. The gas payment and nonce increment above are kept.
+    PUSH 0
+    %checkpoint
+    POP
     %mload_txn_field(@TXN_FIELD_VALUE)
     %mload_txn_field(@TXN_FIELD_TO)
     %mload_txn_field(@TXN_FIELD_ORIGIN)
@@ -86,7 +100,10 @@ global process_message_txn:
     %jumpi(process_message_txn_insufficient_balance)
     // stack: retdest
 
-    // TODO: Handle precompiles.
+    // If to is a precompile, run it on the transaction data.
+    %mload_txn_field(@TXN_FIELD_TO) %is_precompile
+    // stack: is_precompile, retdest
+    %jumpi(process_message_txn_precompile)
 
     // If to's code is empty, return.
     %mload_txn_field(@TXN_FIELD_TO) %ext_code_empty
@@ -96,6 +113,7 @@ global process_message_txn:
     // Otherwise, load to's code and execute it in a new context.
     // stack: retdest
     %create_context
+    %checkpoint
     // stack: new_ctx, retdest
     PUSH process_message_txn_code_loaded
     PUSH @SEGMENT_CODE
@@ -104,17 +122,51 @@ global process_message_txn:
     // stack: address, new_ctx, segment, process_message_txn_code_loaded, new_ctx, retdest
     %jump(load_code)
 
+global process_message_txn_precompile:
+    // stack: retdest
+    %mload_txn_field(@TXN_FIELD_DATA_LEN)
+    %mload_txn_field(@TXN_FIELD_INTRINSIC_GAS)
+    %mload_txn_field(@TXN_FIELD_GAS_LIMIT)
+    SUB
+    %mload_txn_field(@TXN_FIELD_TO)
+    // stack: address, gas, data_len, retdest
+    %stack (address, gas, data_len)
+        -> (address, gas, 0, @SEGMENT_TXN_DATA, 0, data_len, process_message_txn_precompile_done)
+    %jump(handle_precompile)
+
+process_message_txn_precompile_done:
+    // stack: success, leftover_gas, retdest
+    %jump(process_message_txn_after_call)
+
 global process_message_txn_insufficient_balance:
     // stack: retdest
     PANIC // TODO
 
 global process_message_txn_return:
-    // TODO: Return leftover gas?
-    JUMP
+    // stack: retdest
+    // Only the intrinsic gas was used.
+    %mload_txn_field(@TXN_FIELD_INTRINSIC_GAS)
+    %mload_txn_field(@TXN_FIELD_GAS_LIMIT)
+    SUB
+    // stack: leftover_gas, retdest
+    PUSH 1 // success
+    %jump(process_message_txn_refund_gas)
 
 global process_message_txn_code_loaded:
     // stack: code_len, new_ctx, retdest
-    POP
+    // Mark the valid jump destinations in the new context's code.
+    %stack (code_len, new_ctx)
+        -> (new_ctx, code_len, process_message_txn_jumpdests_analysed, code_len, new_ctx)
+    %jump(jumpdest_analysis)
+
+process_message_txn_jumpdests_analysed:
+    // stack: code_len, new_ctx, retdest
+
+    // Store the code size in metadata.
+    PUSH @CTX_METADATA_CODE_SIZE
+    PUSH @SEGMENT_CONTEXT_METADATA
+    DUP4 // new_ctx
+    MSTORE_GENERAL
     // stack: new_ctx, retdest
 
     // Store the address in metadata.
@@ -159,10 +211,33 @@ global process_message_txn_code_loaded:
     MSTORE_GENERAL
     // stack: new_ctx, retdest
 
-    // TODO: Populate CALLDATA
+    // Store the gas available to the new context, i.e. the gas limit minus the intrinsic gas.
+    %mload_txn_field(@TXN_FIELD_INTRINSIC_GAS)
+    %mload_txn_field(@TXN_FIELD_GAS_LIMIT)
+    SUB
+    PUSH @CTX_METADATA_GAS_LIMIT
+    PUSH @SEGMENT_CONTEXT_METADATA
+    DUP4 // new_ctx
+    MSTORE_GENERAL
+    // stack: new_ctx, retdest
 
-    // TODO: Save parent gas and set child gas
+    // Store the calldata size in metadata.
+    %mload_txn_field(@TXN_FIELD_DATA_LEN)
+    PUSH @CTX_METADATA_CALLDATA_SIZE
+    PUSH @SEGMENT_CONTEXT_METADATA
+    DUP4 // new_ctx
+    MSTORE_GENERAL
+    // stack: new_ctx, retdest
+
+    // Copy the transaction data to the new context's calldata.
+    %mload_txn_field(@TXN_FIELD_DATA_LEN)
+    %stack (data_len, new_ctx)
+        -> (new_ctx, @SEGMENT_CALLDATA, 0, 0, @SEGMENT_TXN_DATA, 0, data_len,
+            process_message_txn_calldata_copied, new_ctx)
+    %jump(memcpy)
 
+process_message_txn_calldata_copied:
+    // stack: new_ctx, retdest
     // Now, switch to the new context and go to usermode with PC=0.
     SET_CONTEXT
     // stack: retdest
@@ -170,7 +245,23 @@ global process_message_txn_code_loaded:
     EXIT_KERNEL
 
 global process_message_txn_after_call:
-    // stack: success, retdest
-    // TODO: Return leftover gas? Or handled by termination instructions?
-    POP // Pop success for now. Will go into the receipt when we support that.
-    JUMP
+    // stack: success, leftover_gas, retdest
+    DUP1 %jumpi(process_message_txn_refund_gas)
+    // stack: success, leftover_gas, retdest
+    // The transaction failed, so all of its state changes, including the value it transferred,
+    // are reverted.
+    PUSH 0
+    %revert_to_checkpoint
+    // stack: success, leftover_gas, retdest
+
+global process_message_txn_refund_gas:
+    // stack: success, leftover_gas, retdest
+    // The sender gets the leftover gas back, at the price it paid for it.
+    DUP2
+    %mload_txn_field(@TXN_FIELD_COMPUTED_FEE_PER_GAS)
+    MUL
+    // stack: refund, success, leftover_gas, retdest
+    %mload_txn_field(@TXN_FIELD_ORIGIN)
+    %add_eth
+    // stack: success, leftover_gas, retdest
+    %jump(process_receipt)
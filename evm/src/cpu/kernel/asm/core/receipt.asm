@@ -0,0 +1,206 @@
+// Builds the receipt of the transaction that was just processed, and inserts
+// it into the receipt trie with key rlp(txn_number).
+//
+// A receipt is stored in @SEGMENT_TRIE_DATA as
+//     payload_len, txn_type, status, cum_gas_used, bloom[256], logs_payload_len, num_logs, logs
+// where payload_len and logs_payload_len are the lengths of the RLP payloads of
+// the receipt and of its list of logs, bloom is stored one byte per cell, and
+// each log is stored in the format of @SEGMENT_LOGS_DATA (see log.asm). A
+// txn_type of 0 denotes a legacy receipt, which has no type prefix.
+
+// Pre stack: status, leftover_gas, retdest
+// Post stack: (empty)
+global process_receipt:
+    // stack: status, leftover_gas, retdest
+    SWAP1
+    %mload_txn_field(@TXN_FIELD_GAS_LIMIT)
+    SUB
+    // stack: gas_used, status, retdest
+    %mload_global_metadata(@GLOBAL_METADATA_BLOCK_GAS_USED)
+    ADD
+    DUP1 %mstore_global_metadata(@GLOBAL_METADATA_BLOCK_GAS_USED)
+    // stack: cum_gas_used, status, retdest
+
+    // The logs of a failed transaction are discarded.
+    DUP2 %jumpi(process_receipt_build)
+    PUSH 0 %mstore_global_metadata(@GLOBAL_METADATA_LOGS_LEN)
+    PUSH 0 %mstore_global_metadata(@GLOBAL_METADATA_LOGS_DATA_LEN)
+
+process_receipt_build:
+    // stack: cum_gas_used, status, retdest
+    %get_trie_data_size
+    // stack: receipt_ptr, cum_gas_used, status, retdest
+    PUSH 0 %append_to_trie_data // payload_len, which we write once we know it
+    // TODO: Set the transaction type once typed transactions are supported.
+    PUSH 0 %append_to_trie_data // txn_type
+    DUP3 %append_to_trie_data // status
+    DUP2 %append_to_trie_data // cum_gas_used
+    // The bloom filter starts out empty. Trie data is append-only, so the
+    // memory past its current end is still zero, and we can just skip over it.
+    %get_trie_data_size
+    %add_const(256)
+    %set_trie_data_size
+    PUSH 0 %append_to_trie_data // logs_payload_len, which we write once we know it
+    %mload_global_metadata(@GLOBAL_METADATA_LOGS_LEN)
+    %append_to_trie_data // num_logs
+
+    // Copy the logs into the receipt.
+    %get_trie_data_size
+    %mload_global_metadata(@GLOBAL_METADATA_LOGS_DATA_LEN)
+    // stack: logs_data_len, logs_ptr, receipt_ptr, cum_gas_used, status, retdest
+    DUP2 DUP2 ADD
+    %set_trie_data_size
+    %stack (logs_data_len, logs_ptr)
+        -> (0, @SEGMENT_TRIE_DATA, logs_ptr, 0, @SEGMENT_LOGS_DATA, 0, logs_data_len,
+            process_receipt_logs_copied, logs_ptr)
+    %jump(memcpy)
+
+process_receipt_logs_copied:
+    // stack: logs_ptr, receipt_ptr, cum_gas_used, status, retdest
+    // Go through the logs, to compute the bloom filter and the length of the
+    // RLP payload of the list of logs.
+    PUSH 0 // logs_payload_len
+    PUSH 0 // i
+process_receipt_logs_loop:
+    // stack: i, logs_payload_len, logs_ptr, receipt_ptr, cum_gas_used, status, retdest
+    DUP1
+    %mload_global_metadata(@GLOBAL_METADATA_LOGS_LEN)
+    EQ
+    %jumpi(process_receipt_after_logs)
+    DUP1 %mload_kernel(@SEGMENT_LOGS)
+    DUP4 ADD
+    // stack: log_ptr, i, logs_payload_len, logs_ptr, receipt_ptr, cum_gas_used, status, retdest
+    DUP1 %mload_trie_data
+    %rlp_list_len
+    DUP4 ADD
+    SWAP3 POP
+    // stack: log_ptr, i, logs_payload_len', logs_ptr, receipt_ptr, cum_gas_used, status, retdest
+
+    // Add the log's address and topics to the bloom filter.
+    DUP5 %add_const(4)
+    // stack: bloom_ptr, log_ptr, i, logs_payload_len, logs_ptr, receipt_ptr, cum_gas_used, status, retdest
+    DUP2 %increment %mload_trie_data
+    %keccak256_word(20)
+    DUP2 %add_to_bloom
+    DUP2 %add_const(2) %mload_trie_data
+    DUP3 %add_const(3)
+process_receipt_topics_loop:
+    // stack: topic_ptr, remaining_topics, bloom_ptr, log_ptr, i, logs_payload_len, logs_ptr, receipt_ptr, cum_gas_used, status, retdest
+    DUP2 ISZERO
+    %jumpi(process_receipt_after_topics)
+    DUP1 %mload_trie_data
+    %keccak256_word(32)
+    DUP4 %add_to_bloom
+    %increment
+    SWAP1 %decrement SWAP1
+    %jump(process_receipt_topics_loop)
+process_receipt_after_topics:
+    // stack: topic_ptr, 0, bloom_ptr, log_ptr, i, logs_payload_len, logs_ptr, receipt_ptr, cum_gas_used, status, retdest
+    %pop4
+    %increment
+    %jump(process_receipt_logs_loop)
+
+process_receipt_after_logs:
+    // stack: i, logs_payload_len, logs_ptr, receipt_ptr, cum_gas_used, status, retdest
+    DUP2
+    DUP5 %add_const(260)
+    %mstore_trie_data
+    %stack (i, logs_payload_len, logs_ptr, receipt_ptr, cum_gas_used, status)
+        -> (logs_payload_len, cum_gas_used, status, receipt_ptr)
+    %rlp_list_len
+    SWAP1 %rlp_scalar_len ADD
+    SWAP1 %rlp_scalar_len ADD
+    // The bloom filter is encoded as 0xb90100 followed by its 256 bytes.
+    %add_const(259)
+    // stack: payload_len, receipt_ptr, retdest
+    DUP2
+    %mstore_trie_data
+    // stack: receipt_ptr, retdest
+    %mload_global_metadata(@GLOBAL_METADATA_TXN_NUMBER)
+    %receipt_trie_key
+    %stack (num_nibbles, key, receipt_ptr)
+        -> (num_nibbles, key, receipt_ptr, process_receipt_inserted)
+    %jump(mpt_insert_receipt_trie)
+
+process_receipt_inserted:
+    // stack: retdest
+    %mload_global_metadata(@GLOBAL_METADATA_TXN_NUMBER)
+    %increment
+    %mstore_global_metadata(@GLOBAL_METADATA_TXN_NUMBER)
+    PUSH 0 %mstore_global_metadata(@GLOBAL_METADATA_LOGS_LEN)
+    PUSH 0 %mstore_global_metadata(@GLOBAL_METADATA_LOGS_DATA_LEN)
+    JUMP
+
+// Computes the key of a transaction's receipt in the receipt trie, namely
+// rlp(txn_number), along with its length in nibbles.
+%macro receipt_trie_key
+    // stack: txn_number
+    DUP1 %gt_const(0x7f)
+    %jumpi(%%medium)
+    // The encoding of 0 is 0x80, and other scalars below 0x80 are their own encoding.
+    DUP1 ISZERO
+    %mul_const(0x80)
+    ADD
+    PUSH 2
+    %jump(%%finish)
+%%medium:
+    // The encoding is 0x80 + num_bytes, followed by the scalar's bytes.
+    // stack: txn_number
+    DUP1 %num_bytes
+    DUP1 %add_const(0x80)
+    DUP2 %mul_const(8)
+    SHL
+    // stack: prefix << (8 * num_bytes), num_bytes, txn_number
+    %stack (prefix, num_bytes, txn_number) -> (num_bytes, prefix, txn_number)
+    %increment
+    %mul_const(2)
+    // stack: num_nibbles, prefix, txn_number
+    SWAP2
+    ADD
+    SWAP1
+%%finish:
+    // stack: num_nibbles, key
+%endmacro
+
+// Adds an item to a bloom filter stored in @SEGMENT_TRIE_DATA, one byte per
+// cell, given the Keccak hash of the item. The low 11 bits of each of the first
+// three pairs of bytes of the hash select a bit to set, where bit i lives in
+// byte 255 - i / 8 of the filter.
+//
+// Pre stack: bloom_ptr, hash, retdest
+// Post stack: (empty)
+global add_to_bloom:
+    // stack: bloom_ptr, hash, retdest
+    DUP2 %shr_const(240) %add_bloom_bit
+    DUP2 %shr_const(224) %add_bloom_bit
+    DUP2 %shr_const(208) %add_bloom_bit
+    // stack: bloom_ptr, hash, retdest
+    %pop2
+    JUMP
+
+%macro add_to_bloom
+    %stack (bloom_ptr, hash) -> (bloom_ptr, hash, %%after)
+    %jump(add_to_bloom)
+%%after:
+%endmacro
+
+%macro add_bloom_bit
+    // stack: bits, bloom_ptr
+    %and_const(0x7ff)
+    DUP1 %shr_const(3)
+    PUSH 255
+    SUB
+    DUP3 ADD
+    // stack: byte_ptr, bit_index, bloom_ptr
+    SWAP1
+    %and_const(7)
+    PUSH 1
+    SWAP1
+    SHL
+    // stack: mask, byte_ptr, bloom_ptr
+    DUP2 %mload_trie_data
+    OR
+    SWAP1
+    %mstore_trie_data
+    // stack: bloom_ptr
+%endmacro
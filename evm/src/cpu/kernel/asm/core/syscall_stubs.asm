@@ -1,87 +0,0 @@
-// Labels for unimplemented syscalls to make the kernel assemble.
-// Each label should be removed from this file once it is implemented.
-
-global sys_sdiv:
-    PANIC
-global sys_smod:
-    PANIC
-global sys_signextend:
-    PANIC
-global sys_slt:
-    PANIC
-global sys_sgt:
-    PANIC
-global sys_sar:
-    PANIC
-global sys_address:
-    PANIC
-global sys_balance:
-    PANIC
-global sys_origin:
-    PANIC
-global sys_caller:
-    PANIC
-global sys_callvalue:
-    PANIC
-global sys_calldataload:
-    PANIC
-global sys_calldatasize:
-    PANIC
-global sys_calldatacopy:
-    PANIC
-global sys_codesize:
-    PANIC
-global sys_codecopy:
-    PANIC
-global sys_gasprice:
-    PANIC
-global sys_extcodesize:
-    PANIC
-global sys_extcodecopy:
-    PANIC
-global sys_returndatasize:
-    PANIC
-global sys_returndatacopy:
-    PANIC
-global sys_extcodehash:
-    PANIC
-global sys_blockhash:
-    PANIC
-global sys_coinbase:
-    PANIC
-global sys_timestamp:
-    PANIC
-global sys_number:
-    PANIC
-global sys_prevrandao:
-    PANIC
-global sys_gaslimit:
-    PANIC
-global sys_chainid:
-    PANIC
-global sys_selfbalance:
-    PANIC
-global sys_basefee:
-    PANIC
-global sys_msize:
-    PANIC
-global sys_gas:
-    PANIC
-global sys_log0:
-    PANIC
-global sys_log1:
-    PANIC
-global sys_log2:
-    PANIC
-global sys_log3:
-    PANIC
-global sys_log4:
-    PANIC
-global sys_call:
-    PANIC
-global sys_callcode:
-    PANIC
-global sys_delegatecall:
-    PANIC
-global sys_staticcall:
-    PANIC
@@ -2,29 +2,52 @@
 // RETURN, SELFDESTRUCT, REVERT, and exceptions such as stack underflow.
 
 global sys_stop:
-    // TODO: Set parent context's CTX_METADATA_RETURNDATA_SIZE to 0.
-    // TODO: Refund unused gas to parent.
+    // stack: kexit_info
+    %leftover_gas
+    // stack: leftover_gas
+    PUSH 0
+    %set_parent_returndata_size
     PUSH 1 // success
     %jump(terminate_common)
 
 global sys_return:
-    // TODO: Set parent context's CTX_METADATA_RETURNDATA_SIZE.
-    // TODO: Copy returned memory to parent context's RETURNDATA (but not if we're returning from a constructor?)
-    // TODO: Copy returned memory to parent context's memory (as specified in their call instruction)
-    // TODO: Refund unused gas to parent.
+    // stack: kexit_info, offset, size
+    // TODO: Don't copy the returned memory to the parent's RETURNDATA when returning from a
+    // constructor, but store it as the new account's code.
+    %stack (kexit_info, offset, size) -> (offset, size, kexit_info, offset, size)
+    %expand_memory
+    %leftover_gas
+    %stack (leftover_gas, offset, size) -> (offset, size, sys_return_finish, leftover_gas)
+    %jump(return_data_to_parent)
+sys_return_finish:
+    // stack: leftover_gas
     PUSH 1 // success
     %jump(terminate_common)
 
 global sys_selfdestruct:
-    %consume_gas_const(@GAS_SELFDESTRUCT)
+    // stack: kexit_info, recipient
+    %charge_gas_const(@GAS_SELFDESTRUCT)
     // TODO: Destroy account.
-    // TODO: Refund unused gas to parent.
+    %leftover_gas
+    // stack: leftover_gas, recipient
+    SWAP1
+    POP
+    PUSH 0
+    %set_parent_returndata_size
     PUSH 1 // success
     %jump(terminate_common)
 
 global sys_revert:
-    // TODO: Refund unused gas to parent.
-    // TODO: Revert state changes.
+    // stack: kexit_info, offset, size
+    %stack (kexit_info, offset, size) -> (offset, size, kexit_info, offset, size)
+    %expand_memory
+    %leftover_gas
+    %stack (leftover_gas, offset, size) -> (offset, size, sys_revert_finish, leftover_gas)
+    %jump(return_data_to_parent)
+sys_revert_finish:
+    // stack: leftover_gas
+    GET_CONTEXT
+    %revert_to_checkpoint
     PUSH 0 // success
     %jump(terminate_common)
 
@@ -36,17 +59,66 @@ global sys_revert:
 // - the new stack size would be larger than 1024, or
 // - state modification is attempted during a static call
 global fault_exception:
-    // TODO: Revert state changes.
+    GET_CONTEXT
+    %revert_to_checkpoint
+    PUSH 0
+    %set_parent_returndata_size
+    // A faulting context consumes all of its gas.
+    PUSH 0 // leftover_gas
     PUSH 0 // success
     %jump(terminate_common)
 
+// Returns the gas which the current context has left, given the kexit_info of the instruction
+// terminating it. Faults if the context used more gas than it was given.
+%macro leftover_gas
+    // stack: kexit_info
+    %shr_const(192)
+    // stack: gas_used
+    %mload_context_metadata(@CTX_METADATA_GAS_LIMIT)
+    // stack: gas_limit, gas_used
+    DUP2
+    DUP2
+    LT
+    // stack: gas_limit < gas_used, gas_limit, gas_used
+    %jumpi(fault_exception)
+    SUB
+    // stack: leftover_gas
+%endmacro
+
+// Sets the size of the parent context's returndata.
+%macro set_parent_returndata_size
+    // stack: size
+    PUSH @CTX_METADATA_RETURNDATA_SIZE
+    PUSH @SEGMENT_CONTEXT_METADATA
+    %mload_context_metadata(@CTX_METADATA_PARENT_CONTEXT)
+    MSTORE_GENERAL
+    // stack: (empty)
+%endmacro
+
+// Copies the given range of the current context's memory to the parent context's returndata.
+// Pre stack: offset, size, retdest
+// Post stack: (empty)
+return_data_to_parent:
+    // stack: offset, size, retdest
+    DUP2
+    %set_parent_returndata_size
+    GET_CONTEXT
+    %mload_context_metadata(@CTX_METADATA_PARENT_CONTEXT)
+    %stack (parent_ctx, ctx, offset, size)
+        -> (parent_ctx, @SEGMENT_RETURNDATA, 0, ctx, @SEGMENT_MAIN_MEMORY, offset, size)
+    %jump(memcpy)
+
 terminate_common:
-    // stack: success
-    // We want to move the success flag from our (child) context's stack to the
-    // parent context's stack. We will write it to memory, specifically
-    // SEGMENT_KERNEL_GENERAL[0], then load it after the context switch.
+    // stack: success, leftover_gas
+    // We want to move the success flag and the leftover gas from our (child) context's stack to
+    // the parent context's stack. We will write them to memory, specifically
+    // SEGMENT_KERNEL_GENERAL[0] and SEGMENT_KERNEL_GENERAL[2], then load them after the context
+    // switch.
     PUSH 0
-    // stack: 0, success
+    // stack: 0, success, leftover_gas
+    %mstore_kernel_general
+    // stack: leftover_gas
+    PUSH 2
     %mstore_kernel_general
     // stack: (empty)
 
@@ -62,9 +134,10 @@ terminate_common:
     SET_CONTEXT
     // stack: (empty)
 
-    // Load the success flag and parent PC that we stored in SEGMENT_KERNEL_GENERAL.
+    // Load the leftover gas, success flag and parent PC that we stored in SEGMENT_KERNEL_GENERAL.
+    PUSH 2 %mload_kernel_general
     PUSH 0 %mload_kernel_general
     PUSH 1 %mload_kernel_general
 
-    // stack: parent_pc, success
+    // stack: parent_pc, success, leftover_gas
     JUMP
@@ -24,11 +24,12 @@ global transfer_eth_failure:
 %endmacro
 
 // Pre stack: should_transfer, from, to, amount
-// Post stack: (empty)
+// Post stack: status (0 indicates success)
 %macro maybe_transfer_eth
     %jumpi(%%transfer)
     // We're skipping the transfer, so just pop the arguments and return.
     %pop3
+    PUSH 0
     %jump(%%after)
 %%transfer:
     %transfer_eth
@@ -54,6 +55,7 @@ global deduct_eth:
     SUB
     SWAP1
     // stack: balance_ptr, balance - amount, retdest, 0
+    %journal_trie_data_write
     %mstore_trie_data
     // stack: retdest, 0
     JUMP
@@ -87,6 +89,7 @@ global add_eth:
     // stack: new_balance, balance_ptr, retdest
     SWAP1
     // stack: balance_ptr, new_balance, retdest
+    %journal_trie_data_write
     %mstore_trie_data
     // stack: retdest
     JUMP
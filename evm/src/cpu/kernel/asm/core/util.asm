@@ -30,3 +30,14 @@
     // If there is no "to" field, then this is a contract creation.
     // stack: to == 0
 %endmacro
+
+// Charges for accessing another account's state, as BALANCE, EXTCODESIZE, EXTCODEHASH, EXTCODECOPY
+// and the CALL family do.
+// TODO: EIP-2929 only charges @GAS_COLDACCOUNTACCESS for the first access to an address in a
+// transaction, and @GAS_WARMACCESS afterwards. Until we track accessed addresses, every access is
+// charged as cold.
+%macro charge_account_access
+    // stack: kexit_info
+    %charge_gas_const(@GAS_COLDACCOUNTACCESS)
+    // stack: kexit_info
+%endmacro
@@ -106,19 +106,18 @@ ecdsa_after_precompute_loop_end:
 // Take a public key (PKx, PKy) and return the associated address KECCAK256(PKx || PKy)[-20:].
 pubkey_to_addr:
     // stack: PKx, PKy, retdest
-    PUSH 0
-    // stack: 0, PKx, PKy, retdest
-    MSTORE // TODO: switch to kernel memory (like `%mstore_kernel(@SEGMENT_KERNEL_GENERAL)`).
-    // stack: PKy, retdest
-    PUSH 0x20
-    // stack: 0x20, PKy, retdest
-    MSTORE
-    // stack: retdest
-    PUSH 0x40
-    // stack: 0x40, retdest
-    PUSH 0
-    // stack: 0, 0x40, retdest
-    KECCAK256
+    // Write PKx || PKy to @SEGMENT_KERNEL_GENERAL[0..64], so that the caller's memory is untouched.
+    %stack (PKx) -> (0, @SEGMENT_KERNEL_GENERAL, 0, PKx, 32, pubkey_to_addr_after_x)
+    %jump(mstore_unpacking)
+pubkey_to_addr_after_x:
+    // stack: offset, PKy, retdest
+    %stack (offset, PKy) -> (0, @SEGMENT_KERNEL_GENERAL, offset, PKy, 32, pubkey_to_addr_after_y)
+    %jump(mstore_unpacking)
+pubkey_to_addr_after_y:
+    // stack: offset, retdest
+    POP
+    %stack () -> (0, @SEGMENT_KERNEL_GENERAL, 0, 64) // context, segment, offset, len
+    KECCAK_GENERAL
     // stack: hash, retdest
     PUSH 0xffffffffffffffffffffffffffffffffffffffff
     // stack: 2^160-1, hash, retdest
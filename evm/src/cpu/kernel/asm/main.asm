@@ -2,14 +2,16 @@ global main:
     // First, initialise the shift table
     %shift_table_init
 
-    // Second, load all MPT data from the prover.
+    // Second, load all MPT data from the prover. The block metadata, the block's progress, the
+    // recent block hashes and the trie roots are public values, which are already in memory.
     PUSH hash_initial_tries
     %jump(load_all_mpts)
 
 hash_initial_tries:
-    %mpt_hash_state_trie   %mstore_global_metadata(@GLOBAL_METADATA_STATE_TRIE_DIGEST_BEFORE)
-    %mpt_hash_txn_trie     %mstore_global_metadata(@GLOBAL_METADATA_TXN_TRIE_DIGEST_BEFORE)
-    %mpt_hash_receipt_trie %mstore_global_metadata(@GLOBAL_METADATA_RECEIPT_TRIE_DIGEST_BEFORE)
+    // Check that the tries we loaded match the public trie roots.
+    %mpt_hash_state_trie   %mload_global_metadata(@GLOBAL_METADATA_STATE_TRIE_DIGEST_BEFORE)   %assert_eq
+    %mpt_hash_txn_trie     %mload_global_metadata(@GLOBAL_METADATA_TXN_TRIE_DIGEST_BEFORE)     %assert_eq
+    %mpt_hash_receipt_trie %mload_global_metadata(@GLOBAL_METADATA_RECEIPT_TRIE_DIGEST_BEFORE) %assert_eq
 
 global txn_loop:
     // If the prover has no more txns for us to process, halt.
@@ -21,7 +23,11 @@ global txn_loop:
     %jump(route_txn)
 
 global hash_final_tries:
-    %mpt_hash_state_trie   %mstore_global_metadata(@GLOBAL_METADATA_STATE_TRIE_DIGEST_AFTER)
-    %mpt_hash_txn_trie     %mstore_global_metadata(@GLOBAL_METADATA_TXN_TRIE_DIGEST_AFTER)
-    %mpt_hash_receipt_trie %mstore_global_metadata(@GLOBAL_METADATA_RECEIPT_TRIE_DIGEST_AFTER)
+    // Check that the resulting tries match the public trie roots.
+    %mpt_hash_state_trie   %mload_global_metadata(@GLOBAL_METADATA_STATE_TRIE_DIGEST_AFTER)   %assert_eq
+    %mpt_hash_txn_trie     %mload_global_metadata(@GLOBAL_METADATA_TXN_TRIE_DIGEST_AFTER)     %assert_eq
+    %mpt_hash_receipt_trie %mload_global_metadata(@GLOBAL_METADATA_RECEIPT_TRIE_DIGEST_AFTER) %assert_eq
+    // Check that the block's progress matches the public values too.
+    %mload_global_metadata(@GLOBAL_METADATA_TXN_NUMBER) %mload_global_metadata(@GLOBAL_METADATA_TXN_NUMBER_AFTER)         %assert_eq
+    %mload_global_metadata(@GLOBAL_METADATA_BLOCK_GAS_USED) %mload_global_metadata(@GLOBAL_METADATA_BLOCK_GAS_USED_AFTER) %assert_eq
     %jump(halt)
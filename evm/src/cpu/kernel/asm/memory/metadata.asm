@@ -65,3 +65,191 @@
     %mstore_context_metadata(@CTX_METADATA_MSIZE)
 %endmacro
 
+
+%macro caller
+    %mload_context_metadata(@CTX_METADATA_CALLER)
+%endmacro
+
+%macro calldatasize
+    %mload_context_metadata(@CTX_METADATA_CALLDATA_SIZE)
+%endmacro
+
+%macro returndatasize
+    %mload_context_metadata(@CTX_METADATA_RETURNDATA_SIZE)
+%endmacro
+
+global sys_address:
+    // stack: kexit_info
+    %charge_gas_const(@GAS_BASE)
+    %address
+    // stack: address, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_origin:
+    // stack: kexit_info
+    %charge_gas_const(@GAS_BASE)
+    %mload_txn_field(@TXN_FIELD_ORIGIN)
+    // stack: origin, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_caller:
+    // stack: kexit_info
+    %charge_gas_const(@GAS_BASE)
+    %caller
+    // stack: caller, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_callvalue:
+    // stack: kexit_info
+    %charge_gas_const(@GAS_BASE)
+    %callvalue
+    // stack: callvalue, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_calldatasize:
+    // stack: kexit_info
+    %charge_gas_const(@GAS_BASE)
+    %calldatasize
+    // stack: calldatasize, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_codesize:
+    // stack: kexit_info
+    %charge_gas_const(@GAS_BASE)
+    %mload_context_metadata(@CTX_METADATA_CODE_SIZE)
+    // stack: codesize, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_gasprice:
+    // stack: kexit_info
+    %charge_gas_const(@GAS_BASE)
+    %mload_txn_field(@TXN_FIELD_COMPUTED_FEE_PER_GAS)
+    // stack: gasprice, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_returndatasize:
+    // stack: kexit_info
+    %charge_gas_const(@GAS_BASE)
+    %returndatasize
+    // stack: returndatasize, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_msize:
+    // stack: kexit_info
+    %charge_gas_const(@GAS_BASE)
+    %msize
+    // stack: msize, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_gas:
+    // stack: kexit_info
+    %charge_gas_const(@GAS_BASE)
+    // The gas used so far in this context is stored in the top 64 bits of kexit_info.
+    DUP1
+    %shr_const(192)
+    // stack: gas_used, kexit_info
+    %mload_context_metadata(@CTX_METADATA_GAS_LIMIT)
+    SUB
+    // stack: gas_remaining, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_coinbase:
+    // stack: kexit_info
+    %charge_gas_const(@GAS_BASE)
+    %mload_global_metadata(@GLOBAL_METADATA_BLOCK_BENEFICIARY)
+    // stack: coinbase, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_timestamp:
+    // stack: kexit_info
+    %charge_gas_const(@GAS_BASE)
+    %mload_global_metadata(@GLOBAL_METADATA_BLOCK_TIMESTAMP)
+    // stack: timestamp, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_number:
+    // stack: kexit_info
+    %charge_gas_const(@GAS_BASE)
+    %mload_global_metadata(@GLOBAL_METADATA_BLOCK_NUMBER)
+    // stack: number, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_prevrandao:
+    // stack: kexit_info
+    %charge_gas_const(@GAS_BASE)
+    %mload_global_metadata(@GLOBAL_METADATA_BLOCK_DIFFICULTY)
+    // stack: prevrandao, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_gaslimit:
+    // stack: kexit_info
+    %charge_gas_const(@GAS_BASE)
+    %mload_global_metadata(@GLOBAL_METADATA_BLOCK_GAS_LIMIT)
+    // stack: gaslimit, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_chainid:
+    // stack: kexit_info
+    %charge_gas_const(@GAS_BASE)
+    %mload_global_metadata(@GLOBAL_METADATA_BLOCK_CHAIN_ID)
+    // stack: chainid, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_basefee:
+    // stack: kexit_info
+    %charge_gas_const(@GAS_BASE)
+    %mload_global_metadata(@GLOBAL_METADATA_BLOCK_BASE_FEE)
+    // stack: basefee, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+// Returns the hash of one of the 256 most recent complete blocks, or 0 if the given block number
+// is out of this range.
+global sys_blockhash:
+    // stack: kexit_info, block_number
+    %charge_gas_const(@GAS_BLOCKHASH)
+    %mload_global_metadata(@GLOBAL_METADATA_BLOCK_NUMBER)
+    // stack: current_block_number, kexit_info, block_number
+    %stack (cur, kexit_info, block_number) -> (cur, block_number, cur, block_number, kexit_info)
+    GT
+    // stack: cur > block_number, cur, block_number, kexit_info
+    ISZERO
+    %jumpi(blockhash_future)
+    // stack: cur, block_number, kexit_info
+    SUB
+    // stack: depth, kexit_info
+    DUP1
+    %gt_const(256)
+    %jumpi(blockhash_too_old)
+    // stack: depth, kexit_info
+    // The hashes are stored oldest first, so the hash of block `cur - depth` is at `256 - depth`.
+    PUSH 256
+    SUB
+    %mload_kernel(@SEGMENT_BLOCK_HASHES)
+    // stack: hash, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+blockhash_future:
+    %stack (cur, block_number, kexit_info) -> (kexit_info, 0)
+    EXIT_KERNEL
+
+blockhash_too_old:
+    %stack (depth, kexit_info) -> (kexit_info, 0)
+    EXIT_KERNEL
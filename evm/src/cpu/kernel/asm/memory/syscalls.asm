@@ -80,3 +80,218 @@ global sys_mstore8:
     %mstore_current(@SEGMENT_MAIN_MEMORY)
     // stack: kexit_info
     EXIT_KERNEL
+
+// Charges the gas for copying `size` bytes, i.e. `GAS_COPY` per (partial) 32-byte word.
+%macro charge_copy_gas
+    // stack: size, kexit_info
+    %add_const(31)
+    %div_const(32)
+    %mul_const(@GAS_COPY)
+    // stack: copy_gas, kexit_info
+    %charge_gas
+    // stack: kexit_info
+%endmacro
+
+// Charges the gas for expanding the current context's memory to cover `size` bytes starting at
+// `offset`, and records the new memory size. Accesses of zero bytes never expand memory.
+%macro expand_memory
+    // stack: offset, size, kexit_info
+    DUP2
+    ISZERO
+    %jumpi(%%no_expansion)
+    // Expanding memory this far would cost more gas than a block can provide.
+    DUP1 %gt_const(0xffffffff) %jumpi(fault_exception)
+    DUP2 %gt_const(0xffffffff) %jumpi(fault_exception)
+    ADD
+    %add_const(31)
+    %div_const(32)
+    // stack: new_num_words, kexit_info
+    %msize
+    %div_const(32)
+    // stack: old_num_words, new_num_words, kexit_info
+    DUP2
+    DUP2
+    LT
+    ISZERO
+    // stack: old_num_words >= new_num_words, old_num_words, new_num_words, kexit_info
+    %jumpi(%%no_expansion)
+    %memory_cost
+    SWAP1
+    // stack: new_num_words, old_cost, kexit_info
+    DUP1
+    %mul_const(32)
+    %mstore_context_metadata(@CTX_METADATA_MSIZE)
+    %memory_cost
+    SUB
+    // stack: new_cost - old_cost, kexit_info
+    %charge_gas
+    %jump(%%after)
+%%no_expansion:
+    POP
+    POP
+%%after:
+    // stack: kexit_info
+%endmacro
+
+// The total cost of a memory of `num_words` words, i.e. `3 * num_words + num_words^2 / 512`.
+%macro memory_cost
+    // stack: num_words
+    DUP1
+    DUP1
+    MUL
+    %div_const(512)
+    // stack: num_words^2 / 512, num_words
+    SWAP1
+    %mul_const(@GAS_MEMORY)
+    ADD
+%endmacro
+
+global sys_calldataload:
+    // stack: kexit_info, i
+    %charge_gas_const(@GAS_VERYLOW)
+    %calldatasize
+    // stack: calldatasize, kexit_info, i
+    DUP3
+    LT
+    // stack: i < calldatasize, kexit_info, i
+    ISZERO
+    %jumpi(calldataload_out_of_bounds)
+    // stack: kexit_info, i
+    PUSH 0 // acc = 0
+    // stack: acc, kexit_info, i
+    // Bytes past the end of the calldata are zero, so we can read all 32 bytes.
+    %rep 32
+        %shl_const(8)
+        DUP3
+        %mload_current(@SEGMENT_CALLDATA)
+        ADD
+        // stack: acc, kexit_info, i
+        SWAP2
+        %increment
+        SWAP2
+    %endrep
+    %stack (acc, kexit_info, i) -> (kexit_info, acc)
+    EXIT_KERNEL
+
+calldataload_out_of_bounds:
+    %stack (kexit_info, i) -> (kexit_info, 0)
+    EXIT_KERNEL
+
+global sys_calldatacopy:
+    // stack: kexit_info, dest_offset, offset, size
+    %charge_gas_const(@GAS_VERYLOW)
+    DUP4
+    %charge_copy_gas
+    // stack: kexit_info, dest_offset, offset, size
+    %stack (kexit_info, dest_offset, offset, size)
+        -> (dest_offset, size, kexit_info, dest_offset, offset, size)
+    %expand_memory
+    // stack: kexit_info, dest_offset, offset, size
+    %calldatasize
+    GET_CONTEXT
+    %stack (ctx, calldatasize, kexit_info, dest_offset, offset, size)
+        -> (ctx, @SEGMENT_CALLDATA, calldatasize, dest_offset, offset, size, copy_exit, kexit_info)
+    %jump(copy_to_main_memory)
+
+global sys_codecopy:
+    // stack: kexit_info, dest_offset, offset, size
+    %charge_gas_const(@GAS_VERYLOW)
+    DUP4
+    %charge_copy_gas
+    // stack: kexit_info, dest_offset, offset, size
+    %stack (kexit_info, dest_offset, offset, size)
+        -> (dest_offset, size, kexit_info, dest_offset, offset, size)
+    %expand_memory
+    // stack: kexit_info, dest_offset, offset, size
+    %mload_context_metadata(@CTX_METADATA_CODE_SIZE)
+    GET_CONTEXT
+    %stack (ctx, codesize, kexit_info, dest_offset, offset, size)
+        -> (ctx, @SEGMENT_CODE, codesize, dest_offset, offset, size, copy_exit, kexit_info)
+    %jump(copy_to_main_memory)
+
+global sys_returndatacopy:
+    // stack: kexit_info, dest_offset, offset, size
+    %charge_gas_const(@GAS_VERYLOW)
+    DUP4
+    %charge_copy_gas
+    // stack: kexit_info, dest_offset, offset, size
+    %stack (kexit_info, dest_offset, offset, size)
+        -> (dest_offset, size, kexit_info, dest_offset, offset, size)
+    %expand_memory
+    // stack: kexit_info, dest_offset, offset, size
+    // Unlike the other copy instructions, reading past the end of the returndata is an error.
+    %returndatasize
+    // stack: returndatasize, kexit_info, dest_offset, offset, size
+    DUP4
+    DUP2
+    LT
+    // stack: returndatasize < offset, returndatasize, kexit_info, dest_offset, offset, size
+    %jumpi(fault_exception)
+    DUP4
+    DUP2
+    SUB
+    // stack: returndatasize - offset, returndatasize, kexit_info, dest_offset, offset, size
+    DUP6
+    GT
+    // stack: size > returndatasize - offset, returndatasize, kexit_info, dest_offset, offset, size
+    %jumpi(fault_exception)
+    GET_CONTEXT
+    %stack (ctx, returndatasize, kexit_info, dest_offset, offset, size)
+        -> (ctx, @SEGMENT_RETURNDATA, returndatasize, dest_offset, offset, size, copy_exit, kexit_info)
+    %jump(copy_to_main_memory)
+
+global copy_exit:
+    // stack: kexit_info
+    EXIT_KERNEL
+
+// Copies `size` bytes from SRC = (src_ctx, src_segment, offset) to the main memory of the current
+// context, starting at `dest_offset`. Source bytes at or past `src_len` are read as zero, as
+// specified by the EVM's copy instructions.
+// Pre stack: src_ctx, src_segment, src_len, dest_offset, offset, size, retdest
+// Post stack: (empty)
+global copy_to_main_memory:
+    // stack: src_ctx, src_segment, src_len, dest_offset, offset, size, retdest
+    PUSH 0 // i = 0
+
+copy_to_main_memory_loop:
+    // stack: i, src_ctx, src_segment, src_len, dest_offset, offset, size, retdest
+    DUP7
+    DUP2
+    EQ
+    // stack: i == size, i, src_ctx, src_segment, src_len, dest_offset, offset, size, retdest
+    %jumpi(copy_to_main_memory_end)
+    DUP6
+    DUP2
+    ADD
+    // stack: offset + i, i, src_ctx, src_segment, src_len, dest_offset, offset, size, retdest
+    DUP5
+    DUP2
+    LT
+    // stack: offset + i < src_len, offset + i, i, src_ctx, src_segment, src_len, dest_offset, offset, size, retdest
+    %jumpi(copy_to_main_memory_read)
+    // We don't read past `src_len`, since `offset` may be arbitrarily large.
+    POP
+    PUSH 0
+    %jump(copy_to_main_memory_write)
+
+copy_to_main_memory_read:
+    // stack: offset + i, i, src_ctx, src_segment, src_len, dest_offset, offset, size, retdest
+    DUP4
+    DUP4
+    // stack: src_ctx, src_segment, offset + i, i, src_ctx, src_segment, src_len, dest_offset, offset, size, retdest
+    MLOAD_GENERAL
+
+copy_to_main_memory_write:
+    // stack: byte, i, src_ctx, src_segment, src_len, dest_offset, offset, size, retdest
+    DUP6
+    DUP3
+    ADD
+    // stack: dest_offset + i, byte, i, src_ctx, src_segment, src_len, dest_offset, offset, size, retdest
+    %mstore_main
+    // stack: i, src_ctx, src_segment, src_len, dest_offset, offset, size, retdest
+    %increment
+    %jump(copy_to_main_memory_loop)
+
+copy_to_main_memory_end:
+    %stack (i, src_ctx, src_segment, src_len, dest_offset, offset, size, retdest) -> (retdest)
+    JUMP
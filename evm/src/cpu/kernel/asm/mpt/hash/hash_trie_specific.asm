@@ -91,8 +91,120 @@ encode_account_after_hash_storage_trie:
 global encode_txn:
     PANIC // TODO
 
+// Encodes a receipt, stored in the format described in receipt.asm. The value
+// of a receipt trie leaf is the receipt's encoding, i.e. its transaction type
+// (unless it is a legacy receipt) followed by its RLP list, which we encode as
+// an RLP string.
 global encode_receipt:
-    PANIC // TODO
+    // stack: rlp_pos, value_ptr, retdest
+    DUP2 %mload_trie_data // payload_len = value[0]
+    %rlp_list_len
+    DUP3 %increment %mload_trie_data // txn_type = value[1]
+    ISZERO ISZERO
+    ADD
+    // stack: receipt_len, rlp_pos, value_ptr, retdest
+    SWAP1
+    %encode_rlp_multi_byte_string_prefix
+    // stack: rlp_pos_2, value_ptr, retdest
+    DUP2 %increment %mload_trie_data // txn_type = value[1]
+    DUP1 ISZERO
+    %jumpi(encode_receipt_legacy)
+    // stack: txn_type, rlp_pos_2, value_ptr, retdest
+    DUP2
+    %mstore_rlp
+    %increment
+    %jump(encode_receipt_after_type)
+encode_receipt_legacy:
+    // stack: txn_type, rlp_pos_2, value_ptr, retdest
+    POP
+encode_receipt_after_type:
+    // stack: rlp_pos_3, value_ptr, retdest
+    DUP2 %mload_trie_data // payload_len = value[0]
+    SWAP1 %encode_rlp_list_prefix
+    DUP2 %add_const(2) %mload_trie_data // status = value[2]
+    SWAP1 %encode_rlp_scalar
+    DUP2 %add_const(3) %mload_trie_data // cum_gas_used = value[3]
+    SWAP1 %encode_rlp_scalar
+    // stack: rlp_pos_4, value_ptr, retdest
+    PUSH 256
+    SWAP1
+    %encode_rlp_multi_byte_string_prefix
+    DUP2 %add_const(4) // bloom_ptr = value_ptr + 4
+    %stack (bloom_ptr, rlp_pos, value_ptr)
+        -> (0, @SEGMENT_RLP_RAW, rlp_pos, 0, @SEGMENT_TRIE_DATA, bloom_ptr, 256,
+            encode_receipt_after_bloom, rlp_pos, value_ptr)
+    %jump(memcpy)
+encode_receipt_after_bloom:
+    // stack: rlp_pos_5, value_ptr, retdest
+    %add_const(256)
+    DUP2 %add_const(260) %mload_trie_data // logs_payload_len = value[260]
+    SWAP1 %encode_rlp_list_prefix
+    // stack: rlp_pos_6, value_ptr, retdest
+    DUP2 %add_const(262) // log_ptr = value_ptr + 262
+    DUP3 %add_const(261) %mload_trie_data // num_logs = value[261]
+    %stack (num_logs, log_ptr, rlp_pos, value_ptr) -> (num_logs, rlp_pos, log_ptr)
+encode_receipt_logs_loop:
+    // stack: remaining_logs, rlp_pos, log_ptr, retdest
+    DUP1 ISZERO
+    %jumpi(encode_receipt_end)
+    %decrement
+    %stack (remaining_logs, rlp_pos, log_ptr)
+        -> (rlp_pos, log_ptr, encode_receipt_after_log, remaining_logs)
+    %jump(encode_receipt_log)
+encode_receipt_after_log:
+    // stack: rlp_pos, log_ptr, remaining_logs, retdest
+    %stack (rlp_pos, log_ptr, remaining_logs) -> (remaining_logs, rlp_pos, log_ptr)
+    %jump(encode_receipt_logs_loop)
+encode_receipt_end:
+    // stack: 0, rlp_pos, log_ptr, retdest
+    %stack (remaining_logs, rlp_pos, log_ptr, retdest) -> (retdest, rlp_pos)
+    JUMP
+
+// Encodes a log stored in a receipt, and returns a pointer to the next log.
+// Pre stack: rlp_pos, log_ptr, retdest
+// Post stack: rlp_pos', next_log_ptr
+encode_receipt_log:
+    // stack: rlp_pos, log_ptr, retdest
+    DUP2 %mload_trie_data // payload_len = log[0]
+    SWAP1 %encode_rlp_list_prefix
+    DUP2 %increment %mload_trie_data // address = log[1]
+    SWAP1 %encode_rlp_160
+    // stack: rlp_pos, log_ptr, retdest
+    DUP2 %add_const(2) %mload_trie_data // num_topics = log[2]
+    DUP1 %mul_const(33)
+    %stack (topics_payload_len, num_topics, rlp_pos, log_ptr)
+        -> (rlp_pos, topics_payload_len, num_topics, log_ptr)
+    %encode_rlp_list_prefix
+    // stack: rlp_pos, num_topics, log_ptr, retdest
+    %stack (rlp_pos, num_topics, log_ptr) -> (num_topics, rlp_pos, log_ptr)
+    SWAP2 %add_const(3) SWAP2 // topic_ptr = log_ptr + 3
+encode_receipt_log_topics_loop:
+    // stack: remaining_topics, rlp_pos, topic_ptr, retdest
+    DUP1 ISZERO
+    %jumpi(encode_receipt_log_data)
+    %decrement
+    DUP3 %mload_trie_data
+    DUP3 %encode_rlp_256
+    // stack: rlp_pos', remaining_topics, rlp_pos, topic_ptr, retdest
+    SWAP2 POP
+    SWAP2 %increment SWAP2
+    %jump(encode_receipt_log_topics_loop)
+encode_receipt_log_data:
+    // stack: 0, rlp_pos, data_len_ptr, retdest
+    POP
+    DUP2 %mload_trie_data
+    DUP3 %increment
+    %stack (data_ptr, data_len, rlp_pos, data_len_ptr)
+        -> (rlp_pos, 0, @SEGMENT_TRIE_DATA, data_ptr, data_len,
+            encode_receipt_log_end, data_ptr, data_len)
+    %jump(encode_rlp_string)
+encode_receipt_log_end:
+    // stack: rlp_pos', data_ptr, data_len, retdest
+    SWAP2
+    ADD
+    // stack: next_log_ptr, rlp_pos', retdest
+    %stack (next_log_ptr, rlp_pos, retdest) -> (retdest, rlp_pos, next_log_ptr)
+    JUMP
 
 global encode_storage_value:
     // stack: rlp_pos, value_ptr, retdest
@@ -31,9 +31,22 @@ global mpt_load_txn_trie_value:
     // stack: retdest
     PANIC // TODO
 
+// Receipts are variable-length, so the prover first gives us the number of
+// words to load. See receipt.asm for the format of a receipt.
 global mpt_load_receipt_trie_value:
     // stack: retdest
-    PANIC // TODO
+    PROVER_INPUT(mpt)
+    // stack: len, retdest
+mpt_load_receipt_trie_value_loop:
+    DUP1 ISZERO
+    %jumpi(mpt_load_receipt_trie_value_end)
+    PROVER_INPUT(mpt) %append_to_trie_data
+    %decrement
+    %jump(mpt_load_receipt_trie_value_loop)
+mpt_load_receipt_trie_value_end:
+    // stack: 0, retdest
+    POP
+    JUMP
 
 global mpt_load_storage_trie_value:
     // stack: retdest
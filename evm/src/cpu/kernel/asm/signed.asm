@@ -0,0 +1,232 @@
+// Signed arithmetic on 256-bit two's complement integers, namely SDIV, SMOD, SIGNEXTEND, SLT,
+// SGT and SAR.
+
+// Returns 1 if x is negative, i.e. if its sign bit is set, and 0 otherwise.
+%macro is_neg
+    // stack: x
+    %shr_const(255)
+    // stack: x < 0
+%endmacro
+
+// Returns -x, i.e. 0 - x mod 2^256.
+%macro neg
+    // stack: x
+    PUSH 0
+    SUB
+    // stack: -x
+%endmacro
+
+// Returns -x if pred is 1, and x if pred is 0.
+%macro neg_if
+    // stack: pred, x
+    SWAP1
+    DUP1
+    %neg
+    // stack: -x, x, pred
+    SWAP2
+    // stack: pred, x, -x
+    %select_bool
+    // stack: pred ? -x : x
+%endmacro
+
+// Returns |x|. Note that |-2^255| = 2^255 when interpreted as an unsigned integer.
+%macro abs
+    // stack: x
+    DUP1
+    %is_neg
+    // stack: x < 0, x
+    %neg_if
+    // stack: |x|
+%endmacro
+
+// Flips the sign bit of x, which maps the signed order to the unsigned order.
+%macro flip_sign_bit
+    // stack: x
+    PUSH 0x8000000000000000000000000000000000000000000000000000000000000000
+    XOR
+    // stack: x ^ 2^255
+%endmacro
+
+// Signed division, rounding towards zero. Returns 0 if b = 0.
+// Note that -2^255 / -1 = -2^255, as the result wraps around.
+%macro sdiv
+    // stack: a, b
+    DUP2
+    %is_neg
+    DUP2
+    %is_neg
+    XOR
+    // stack: sign(a) != sign(b), a, b
+    %stack (neg, a, b) -> (b, a, neg)
+    %abs
+    SWAP1
+    %abs
+    // stack: |a|, |b|, neg
+    DIV
+    // stack: |a| / |b|, neg
+    SWAP1
+    %neg_if
+    // stack: a / b
+%endmacro
+
+// Signed modulo, where the result takes the sign of a. Returns 0 if b = 0.
+%macro smod
+    // stack: a, b
+    DUP1
+    %is_neg
+    // stack: a < 0, a, b
+    %stack (neg, a, b) -> (b, a, neg)
+    %abs
+    SWAP1
+    %abs
+    // stack: |a|, |b|, a < 0
+    MOD
+    // stack: |a| % |b|, a < 0
+    SWAP1
+    %neg_if
+    // stack: a % b
+%endmacro
+
+// Extends the sign of the (b + 1)-byte integer x to a 256-bit integer.
+%macro signextend
+    // stack: b, x
+    DUP1
+    %gt_const(30)
+    // stack: b > 30, b, x
+    %jumpi(%%unchanged)
+    // stack: b, x
+    %mul_const(8)
+    %add_const(7)
+    // stack: bit, x
+    DUP2
+    DUP2
+    SHR
+    %and_const(1)
+    // stack: sign, bit, x
+    SWAP1
+    %increment
+    PUSH 1
+    SWAP1
+    SHL
+    %decrement
+    // stack: mask, sign, x
+    SWAP1
+    %jumpi(%%negative)
+    // stack: mask, x
+    AND
+    // stack: x & mask
+    %jump(%%end)
+%%negative:
+    // stack: mask, x
+    NOT
+    OR
+    // stack: x | !mask
+    %jump(%%end)
+%%unchanged:
+    // stack: b, x
+    POP
+    // stack: x
+%%end:
+%endmacro
+
+// Signed a < b.
+%macro slt
+    // stack: a, b
+    %flip_sign_bit
+    SWAP1
+    %flip_sign_bit
+    // stack: b ^ 2^255, a ^ 2^255
+    GT
+    // stack: a < b
+%endmacro
+
+// Signed a > b.
+%macro sgt
+    // stack: a, b
+    %flip_sign_bit
+    SWAP1
+    %flip_sign_bit
+    // stack: b ^ 2^255, a ^ 2^255
+    LT
+    // stack: a > b
+%endmacro
+
+// Arithmetic shift right, i.e. a right shift which fills the vacated bits with the sign bit.
+%macro sar
+    // stack: shift, value
+    // Shifting by 255 or more bits gives the same result, so we cap the shift to avoid any
+    // surprise with larger shifts.
+    PUSH 255
+    %min
+    // stack: min(shift, 255), value
+    DUP2
+    %is_neg
+    %jumpi(%%negative)
+    // stack: shift, value
+    SHR
+    // stack: value >> shift
+    %jump(%%end)
+%%negative:
+    // stack: shift, value
+    SWAP1
+    NOT
+    SWAP1
+    SHR
+    NOT
+    // stack: !(!value >> shift)
+%%end:
+%endmacro
+
+global sys_sdiv:
+    // stack: kexit_info, a, b
+    %charge_gas_const(@GAS_LOW)
+    %stack (kexit_info, a, b) -> (a, b, kexit_info)
+    %sdiv
+    // stack: a / b, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_smod:
+    // stack: kexit_info, a, b
+    %charge_gas_const(@GAS_LOW)
+    %stack (kexit_info, a, b) -> (a, b, kexit_info)
+    %smod
+    // stack: a % b, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_signextend:
+    // stack: kexit_info, b, x
+    %charge_gas_const(@GAS_LOW)
+    %stack (kexit_info, b, x) -> (b, x, kexit_info)
+    %signextend
+    // stack: signextend(b, x), kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_slt:
+    // stack: kexit_info, a, b
+    %charge_gas_const(@GAS_VERYLOW)
+    %stack (kexit_info, a, b) -> (a, b, kexit_info)
+    %slt
+    // stack: a < b, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_sgt:
+    // stack: kexit_info, a, b
+    %charge_gas_const(@GAS_VERYLOW)
+    %stack (kexit_info, a, b) -> (a, b, kexit_info)
+    %sgt
+    // stack: a > b, kexit_info
+    SWAP1
+    EXIT_KERNEL
+
+global sys_sar:
+    // stack: kexit_info, shift, value
+    %charge_gas_const(@GAS_VERYLOW)
+    %stack (kexit_info, shift, value) -> (shift, value, kexit_info)
+    %sar
+    // stack: value >> shift, kexit_info
+    SWAP1
+    EXIT_KERNEL
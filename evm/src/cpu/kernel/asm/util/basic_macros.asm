@@ -219,8 +219,8 @@
     DUP2
     DUP2
     // stack: x, y, x, y
-    LT
-    // stack: x < y, x, y
+    GT
+    // stack: x > y, x, y
     %select_bool
     // stack: min
 %endmacro
@@ -230,8 +230,8 @@
     DUP2
     DUP2
     // stack: x, y, x, y
-    GT
-    // stack: x > y, x, y
+    LT
+    // stack: x < y, x, y
     %select_bool
     // stack: max
 %endmacro
@@ -357,6 +357,16 @@
     ADD
 %endmacro
 
+// Charge a constant amount of gas.
+// Arguments:
+//   stack[0]: syscall info
+// Returns:
+//   new syscall info
+%macro charge_gas_const(gas)
+    PUSH $gas
+    %charge_gas
+%endmacro
+
 // Charge gas and exit kernel code.
 // Arguments:
 //   stack[0]: gas to be charged
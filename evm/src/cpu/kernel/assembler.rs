@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use ethereum_types::U256;
+use ethereum_types::{BigEndianHash, U256};
 use itertools::{izip, Itertools};
 use keccak_hash::keccak;
 use log::debug;
@@ -23,8 +23,8 @@ pub(crate) const BYTES_PER_OFFSET: u8 = 3;
 pub struct Kernel {
     pub(crate) code: Vec<u8>,
 
-    /// Computed using `hash_kernel`. It is encoded as `u32` limbs for convenience, since we deal
-    /// with `u32` limbs in our Keccak table.
+    /// Computed using `hash_kernel`. It is encoded as little-endian `u32` limbs of the hash, read
+    /// as a big-endian `U256`, since this is how the CPU sees Keccak digests.
     pub(crate) code_hash: [u32; 8],
 
     pub(crate) global_labels: HashMap<String, usize>,
@@ -40,10 +40,9 @@ impl Kernel {
         global_labels: HashMap<String, usize>,
         prover_inputs: HashMap<usize, ProverInputFn>,
     ) -> Self {
-        let code_hash_bytes = keccak(&code).0;
-        let code_hash = core::array::from_fn(|i| {
-            u32::from_le_bytes(core::array::from_fn(|j| code_hash_bytes[i * 4 + j]))
-        });
+        // Split the hash into 32-bit limbs, the same way KECCAK_GENERAL's output is laid out.
+        let code_hash_u256 = keccak(&code).into_uint();
+        let code_hash = core::array::from_fn(|i| (code_hash_u256 >> (32 * i)).low_u32());
         let ordered_labels = global_labels
             .keys()
             .cloned()
@@ -26,10 +26,21 @@ pub(crate) enum ContextMetadata {
     /// Size of the active main memory.
     MSize = 10,
     StackSize = 11,
+    /// The gas available to this context when it was created.
+    GasLimit = 12,
+    /// The size of the journal at the creation of this context, i.e. the number of in-place trie
+    /// data writes to keep when reverting it.
+    JournalCheckpoint = 13,
+    /// The number of logs at the creation of this context.
+    LogsCheckpoint = 14,
+    /// The size of the logs data at the creation of this context.
+    LogsDataCheckpoint = 15,
+    /// The number of calls which led to this context, i.e. 0 for the context of the transaction.
+    CallDepth = 16,
 }
 
 impl ContextMetadata {
-    pub(crate) const COUNT: usize = 12;
+    pub(crate) const COUNT: usize = 17;
 
     pub(crate) fn all() -> [Self; Self::COUNT] {
         [
@@ -45,6 +56,11 @@ impl ContextMetadata {
             Self::StateTrieCheckpointPointer,
             Self::MSize,
             Self::StackSize,
+            Self::GasLimit,
+            Self::JournalCheckpoint,
+            Self::LogsCheckpoint,
+            Self::LogsDataCheckpoint,
+            Self::CallDepth,
         ]
     }
 
@@ -63,6 +79,11 @@ impl ContextMetadata {
             ContextMetadata::StateTrieCheckpointPointer => "CTX_METADATA_STATE_TRIE_CHECKPOINT_PTR",
             ContextMetadata::MSize => "CTX_METADATA_MSIZE",
             ContextMetadata::StackSize => "CTX_METADATA_STACK_SIZE",
+            ContextMetadata::GasLimit => "CTX_METADATA_GAS_LIMIT",
+            ContextMetadata::JournalCheckpoint => "CTX_METADATA_JOURNAL_CHECKPOINT",
+            ContextMetadata::LogsCheckpoint => "CTX_METADATA_LOGS_CHECKPOINT",
+            ContextMetadata::LogsDataCheckpoint => "CTX_METADATA_LOGS_DATA_CHECKPOINT",
+            ContextMetadata::CallDepth => "CTX_METADATA_CALL_DEPTH",
         }
     }
 }
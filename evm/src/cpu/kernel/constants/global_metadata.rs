@@ -30,10 +30,37 @@ pub(crate) enum GlobalMetadata {
     /// The sizes of the `TrieEncodedChild` and `TrieEncodedChildLen` buffers. In other words, the
     /// next available offset in these buffers.
     TrieEncodedChildSize = 14,
+
+    // Block metadata.
+    BlockBeneficiary = 15,
+    BlockTimestamp = 16,
+    BlockNumber = 17,
+    BlockDifficulty = 18,
+    BlockGasLimit = 19,
+    BlockChainId = 20,
+    BlockBaseFee = 21,
+
+    /// The number of transactions processed so far in the block, i.e. the index of the current
+    /// transaction. This is also the key of its receipt in the receipt trie, before RLP encoding.
+    /// It starts at the public number of transactions processed before these ones.
+    TxnNumber = 22,
+    /// The cumulative gas used by the transactions processed so far in the block. It starts at the
+    /// public gas used before these transactions.
+    BlockGasUsed = 23,
+    /// The number of logs emitted by the current transaction so far.
+    LogsLen = 24,
+    /// The size of the `LogsData` segment, i.e. the next available offset in it.
+    LogsDataLen = 25,
+    /// The size of the `Journal` segment.
+    JournalLen = 26,
+
+    // The block's progress after these transactions.
+    TxnNumberAfter = 27,
+    BlockGasUsedAfter = 28,
 }
 
 impl GlobalMetadata {
-    pub(crate) const COUNT: usize = 13;
+    pub(crate) const COUNT: usize = 27;
 
     pub(crate) fn all() -> [Self; Self::COUNT] {
         [
@@ -50,6 +77,20 @@ impl GlobalMetadata {
             Self::TransactionTrieRootDigestAfter,
             Self::ReceiptTrieRootDigestAfter,
             Self::TrieEncodedChildSize,
+            Self::BlockBeneficiary,
+            Self::BlockTimestamp,
+            Self::BlockNumber,
+            Self::BlockDifficulty,
+            Self::BlockGasLimit,
+            Self::BlockChainId,
+            Self::BlockBaseFee,
+            Self::TxnNumber,
+            Self::BlockGasUsed,
+            Self::LogsLen,
+            Self::LogsDataLen,
+            Self::JournalLen,
+            Self::TxnNumberAfter,
+            Self::BlockGasUsedAfter,
         ]
     }
 
@@ -77,6 +118,20 @@ impl GlobalMetadata {
                 "GLOBAL_METADATA_RECEIPT_TRIE_DIGEST_AFTER"
             }
             GlobalMetadata::TrieEncodedChildSize => "TRIE_ENCODED_CHILD_SIZE",
+            GlobalMetadata::BlockBeneficiary => "GLOBAL_METADATA_BLOCK_BENEFICIARY",
+            GlobalMetadata::BlockTimestamp => "GLOBAL_METADATA_BLOCK_TIMESTAMP",
+            GlobalMetadata::BlockNumber => "GLOBAL_METADATA_BLOCK_NUMBER",
+            GlobalMetadata::BlockDifficulty => "GLOBAL_METADATA_BLOCK_DIFFICULTY",
+            GlobalMetadata::BlockGasLimit => "GLOBAL_METADATA_BLOCK_GAS_LIMIT",
+            GlobalMetadata::BlockChainId => "GLOBAL_METADATA_BLOCK_CHAIN_ID",
+            GlobalMetadata::BlockBaseFee => "GLOBAL_METADATA_BLOCK_BASE_FEE",
+            GlobalMetadata::TxnNumber => "GLOBAL_METADATA_TXN_NUMBER",
+            GlobalMetadata::BlockGasUsed => "GLOBAL_METADATA_BLOCK_GAS_USED",
+            GlobalMetadata::LogsLen => "GLOBAL_METADATA_LOGS_LEN",
+            GlobalMetadata::LogsDataLen => "GLOBAL_METADATA_LOGS_DATA_LEN",
+            GlobalMetadata::JournalLen => "GLOBAL_METADATA_JOURNAL_LEN",
+            GlobalMetadata::TxnNumberAfter => "GLOBAL_METADATA_TXN_NUMBER_AFTER",
+            GlobalMetadata::BlockGasUsedAfter => "GLOBAL_METADATA_BLOCK_GAS_USED_AFTER",
         }
     }
 }
@@ -3,12 +3,12 @@ use std::collections::HashMap;
 use ethereum_types::U256;
 use hex_literal::hex;
 
-use crate::cpu::decode::invalid_opcodes_user;
 use crate::cpu::kernel::constants::context_metadata::ContextMetadata;
 use crate::cpu::kernel::constants::global_metadata::GlobalMetadata;
 use crate::cpu::kernel::constants::trie_type::PartialTrieType;
 use crate::cpu::kernel::constants::txn_fields::NormalizedTxnField;
 use crate::memory::segments::Segment;
+use crate::witness::transition::invalid_opcodes_user;
 
 pub(crate) mod context_metadata;
 pub(crate) mod global_metadata;
@@ -28,6 +28,14 @@ pub fn evm_constants() -> HashMap<String, U256> {
         c.insert(name.into(), U256::from(value));
     }
 
+    for (name, value) in PRECOMPILES {
+        c.insert(name.into(), U256::from(value));
+    }
+
+    for (name, value) in PRECOMPILES_GAS {
+        c.insert(name.into(), U256::from(value));
+    }
+
     for segment in Segment::all() {
         c.insert(segment.var_name().into(), (segment as u32).into());
     }
@@ -43,6 +51,7 @@ pub fn evm_constants() -> HashMap<String, U256> {
     for trie_type in PartialTrieType::all() {
         c.insert(trie_type.var_name().into(), (trie_type as u32).into());
     }
+    c.insert("CALL_STACK_LIMIT".into(), U256::from(CALL_STACK_LIMIT));
     c.insert(
         "INVALID_OPCODES_USER".into(),
         U256::from_little_endian(&invalid_opcodes_user()),
@@ -177,3 +186,33 @@ const GAS_CONSTANTS: [(&str, u16); 36] = [
     ("GAS_COPY", 3),
     ("GAS_BLOCKHASH", 20),
 ];
+
+/// The maximum depth of nested calls. A call from a context at this depth fails.
+const CALL_STACK_LIMIT: u32 = 1024;
+
+/// Addresses of the precompiled contracts.
+const PRECOMPILES: [(&str, u16); 9] = [
+    ("ECREC", 1),
+    ("SHA256", 2),
+    ("RIP160", 3),
+    ("ID", 4),
+    ("EXPMOD", 5),
+    ("BN_ADD", 6),
+    ("BN_MUL", 7),
+    ("SNARKV", 8),
+    ("BLAKE2_F", 9),
+];
+
+/// Gas costs of the precompiled contracts. Dynamic costs are charged per 32-byte word of input.
+const PRECOMPILES_GAS: [(&str, u16); 10] = [
+    ("ECREC_GAS", 3_000),
+    ("SHA256_STATIC_GAS", 60),
+    ("SHA256_DYNAMIC_GAS", 12),
+    ("RIP160_STATIC_GAS", 600),
+    ("RIP160_DYNAMIC_GAS", 120),
+    ("ID_STATIC_GAS", 15),
+    ("ID_DYNAMIC_GAS", 3),
+    ("BN_ADD_GAS", 150),
+    ("BN_MUL_GAS", 6_000),
+    ("BLAKE2_F_ROUND_GAS", 1),
+];
@@ -21,10 +21,13 @@ pub(crate) enum NormalizedTxnField {
     R = 11,
     S = 12,
     Origin = 13,
+    /// The intrinsic gas cost of the transaction, which is charged before any code is executed.
+    /// This is not technically a transaction field, as it is computed from the other fields.
+    IntrinsicGas = 14,
 }
 
 impl NormalizedTxnField {
-    pub(crate) const COUNT: usize = 14;
+    pub(crate) const COUNT: usize = 15;
 
     pub(crate) fn all() -> [Self; Self::COUNT] {
         [
@@ -42,6 +45,7 @@ impl NormalizedTxnField {
             Self::R,
             Self::S,
             Self::Origin,
+            Self::IntrinsicGas,
         ]
     }
 
@@ -62,6 +66,7 @@ impl NormalizedTxnField {
             NormalizedTxnField::R => "TXN_FIELD_R",
             NormalizedTxnField::S => "TXN_FIELD_S",
             NormalizedTxnField::Origin => "TXN_FIELD_ORIGIN",
+            NormalizedTxnField::IntrinsicGas => "TXN_FIELD_INTRINSIC_GAS",
         }
     }
 }
@@ -8,6 +8,7 @@ use keccak_hash::keccak;
 use plonky2::field::goldilocks_field::GoldilocksField;
 
 use crate::cpu::kernel::aggregator::KERNEL;
+use crate::cpu::kernel::assembler::BYTES_PER_OFFSET;
 use crate::cpu::kernel::constants::context_metadata::ContextMetadata;
 use crate::cpu::kernel::constants::global_metadata::GlobalMetadata;
 use crate::cpu::kernel::constants::txn_fields::NormalizedTxnField;
@@ -151,7 +152,9 @@ impl<'a> Interpreter<'a> {
     }
 
     fn code(&self) -> &MemorySegmentState {
-        &self.generation_state.memory.contexts[self.context].segments[Segment::Code as usize]
+        // The kernel code lives in context 0, whatever the current context.
+        let code_context = if self.kernel_mode { 0 } else { self.context };
+        &self.generation_state.memory.contexts[code_context].segments[Segment::Code as usize]
     }
 
     fn code_slice(&self, n: usize) -> Vec<u8> {
@@ -186,6 +189,30 @@ impl<'a> Interpreter<'a> {
             .set(field as usize, value)
     }
 
+    pub(crate) fn get_context_metadata_field(
+        &self,
+        context: usize,
+        field: ContextMetadata,
+    ) -> U256 {
+        self.generation_state.memory.get(MemoryAddress::new(
+            context,
+            Segment::ContextMetadata,
+            field as usize,
+        ))
+    }
+
+    pub(crate) fn set_context_metadata_field(
+        &mut self,
+        context: usize,
+        field: ContextMetadata,
+        value: U256,
+    ) {
+        self.generation_state.memory.set(
+            MemoryAddress::new(context, Segment::ContextMetadata, field as usize),
+            value,
+        )
+    }
+
     pub(crate) fn get_trie_data(&self) -> &[U256] {
         &self.generation_state.memory.contexts[0].segments[Segment::TrieData as usize].content
     }
@@ -274,99 +301,100 @@ impl<'a> Interpreter<'a> {
         self.opcode_count[opcode as usize] += 1;
         self.incr(1);
         match opcode {
-            0x00 => self.run_stop(),                                    // "STOP",
-            0x01 => self.run_add(),                                     // "ADD",
-            0x02 => self.run_mul(),                                     // "MUL",
-            0x03 => self.run_sub(),                                     // "SUB",
-            0x04 => self.run_div(),                                     // "DIV",
-            0x05 => todo!(),                                            // "SDIV",
-            0x06 => self.run_mod(),                                     // "MOD",
-            0x07 => todo!(),                                            // "SMOD",
-            0x08 => self.run_addmod(),                                  // "ADDMOD",
-            0x09 => self.run_mulmod(),                                  // "MULMOD",
-            0x0a => self.run_exp(),                                     // "EXP",
-            0x0b => todo!(),                                            // "SIGNEXTEND",
-            0x0c => self.run_addfp254(),                                // "ADDFP254",
-            0x0d => self.run_mulfp254(),                                // "MULFP254",
-            0x0e => self.run_subfp254(),                                // "SUBFP254",
-            0x10 => self.run_lt(),                                      // "LT",
-            0x11 => self.run_gt(),                                      // "GT",
-            0x12 => todo!(),                                            // "SLT",
-            0x13 => todo!(),                                            // "SGT",
-            0x14 => self.run_eq(),                                      // "EQ",
-            0x15 => self.run_iszero(),                                  // "ISZERO",
-            0x16 => self.run_and(),                                     // "AND",
-            0x17 => self.run_or(),                                      // "OR",
-            0x18 => self.run_xor(),                                     // "XOR",
-            0x19 => self.run_not(),                                     // "NOT",
-            0x1a => self.run_byte(),                                    // "BYTE",
-            0x1b => self.run_shl(),                                     // "SHL",
-            0x1c => self.run_shr(),                                     // "SHR",
-            0x1d => todo!(),                                            // "SAR",
-            0x20 => self.run_keccak256(),                               // "KECCAK256",
-            0x21 => self.run_keccak_general(),                          // "KECCAK_GENERAL",
-            0x30 => todo!(),                                            // "ADDRESS",
-            0x31 => todo!(),                                            // "BALANCE",
-            0x32 => todo!(),                                            // "ORIGIN",
-            0x33 => todo!(),                                            // "CALLER",
-            0x34 => self.run_callvalue(),                               // "CALLVALUE",
-            0x35 => self.run_calldataload(),                            // "CALLDATALOAD",
-            0x36 => self.run_calldatasize(),                            // "CALLDATASIZE",
-            0x37 => self.run_calldatacopy(),                            // "CALLDATACOPY",
-            0x38 => todo!(),                                            // "CODESIZE",
-            0x39 => todo!(),                                            // "CODECOPY",
-            0x3a => todo!(),                                            // "GASPRICE",
-            0x3b => todo!(),                                            // "EXTCODESIZE",
-            0x3c => todo!(),                                            // "EXTCODECOPY",
-            0x3d => todo!(),                                            // "RETURNDATASIZE",
-            0x3e => todo!(),                                            // "RETURNDATACOPY",
-            0x3f => todo!(),                                            // "EXTCODEHASH",
-            0x40 => todo!(),                                            // "BLOCKHASH",
-            0x41 => todo!(),                                            // "COINBASE",
-            0x42 => todo!(),                                            // "TIMESTAMP",
-            0x43 => todo!(),                                            // "NUMBER",
-            0x44 => todo!(),                                            // "DIFFICULTY",
-            0x45 => todo!(),                                            // "GASLIMIT",
-            0x46 => todo!(),                                            // "CHAINID",
-            0x48 => todo!(),                                            // "BASEFEE",
-            0x49 => self.run_prover_input()?,                           // "PROVER_INPUT",
-            0x50 => self.run_pop(),                                     // "POP",
-            0x51 => self.run_mload(),                                   // "MLOAD",
-            0x52 => self.run_mstore(),                                  // "MSTORE",
-            0x53 => self.run_mstore8(),                                 // "MSTORE8",
-            0x54 => todo!(),                                            // "SLOAD",
-            0x55 => todo!(),                                            // "SSTORE",
-            0x56 => self.run_jump(),                                    // "JUMP",
-            0x57 => self.run_jumpi(),                                   // "JUMPI",
-            0x58 => self.run_pc(),                                      // "PC",
-            0x59 => self.run_msize(),                                   // "MSIZE",
-            0x5a => todo!(),                                            // "GAS",
-            0x5b => self.run_jumpdest(),                                // "JUMPDEST",
-            x if (0x60..0x80).contains(&x) => self.run_push(x - 0x5f),  // "PUSH"
-            x if (0x80..0x90).contains(&x) => self.run_dup(x - 0x7f),   // "DUP"
+            // In user mode, STOP returns to the parent context through the kernel, as on the CPU.
+            0x00 if self.kernel_mode => self.run_stop(), // "STOP",
+            0x00 => self.run_syscall(opcode),            // "STOP",
+            0x01 => self.run_add(),                      // "ADD",
+            0x02 => self.run_mul(),                      // "MUL",
+            0x03 => self.run_sub(),                      // "SUB",
+            0x04 => self.run_div(),                      // "DIV",
+            0x05 => self.run_sdiv(),                     // "SDIV",
+            0x06 => self.run_mod(),                      // "MOD",
+            0x07 => self.run_smod(),                     // "SMOD",
+            0x08 => self.run_addmod(),                   // "ADDMOD",
+            0x09 => self.run_mulmod(),                   // "MULMOD",
+            0x0a => self.run_exp(),                      // "EXP",
+            0x0b => self.run_signextend(),               // "SIGNEXTEND",
+            0x0c => self.run_addfp254(),                 // "ADDFP254",
+            0x0d => self.run_mulfp254(),                 // "MULFP254",
+            0x0e => self.run_subfp254(),                 // "SUBFP254",
+            0x10 => self.run_lt(),                       // "LT",
+            0x11 => self.run_gt(),                       // "GT",
+            0x12 => self.run_slt(),                      // "SLT",
+            0x13 => self.run_sgt(),                      // "SGT",
+            0x14 => self.run_eq(),                       // "EQ",
+            0x15 => self.run_iszero(),                   // "ISZERO",
+            0x16 => self.run_and(),                      // "AND",
+            0x17 => self.run_or(),                       // "OR",
+            0x18 => self.run_xor(),                      // "XOR",
+            0x19 => self.run_not(),                      // "NOT",
+            0x1a => self.run_byte(),                     // "BYTE",
+            0x1b => self.run_shl(),                      // "SHL",
+            0x1c => self.run_shr(),                      // "SHR",
+            0x1d => self.run_sar(),                      // "SAR",
+            0x20 => self.run_keccak256(),                // "KECCAK256",
+            0x21 => self.run_keccak_general(),           // "KECCAK_GENERAL",
+            0x30 => self.run_syscall(opcode),            // "ADDRESS",
+            0x31 => self.run_syscall(opcode),            // "BALANCE",
+            0x32 => self.run_syscall(opcode),            // "ORIGIN",
+            0x33 => self.run_syscall(opcode),            // "CALLER",
+            0x34 => self.run_syscall(opcode),            // "CALLVALUE",
+            0x35 => self.run_syscall(opcode),            // "CALLDATALOAD",
+            0x36 => self.run_syscall(opcode),            // "CALLDATASIZE",
+            0x37 => self.run_syscall(opcode),            // "CALLDATACOPY",
+            0x38 => self.run_syscall(opcode),            // "CODESIZE",
+            0x39 => self.run_syscall(opcode),            // "CODECOPY",
+            0x3a => self.run_syscall(opcode),            // "GASPRICE",
+            0x3b => self.run_syscall(opcode),            // "EXTCODESIZE",
+            0x3c => self.run_syscall(opcode),            // "EXTCODECOPY",
+            0x3d => self.run_syscall(opcode),            // "RETURNDATASIZE",
+            0x3e => self.run_syscall(opcode),            // "RETURNDATACOPY",
+            0x3f => self.run_syscall(opcode),            // "EXTCODEHASH",
+            0x40 => self.run_syscall(opcode),            // "BLOCKHASH",
+            0x41 => self.run_syscall(opcode),            // "COINBASE",
+            0x42 => self.run_syscall(opcode),            // "TIMESTAMP",
+            0x43 => self.run_syscall(opcode),            // "NUMBER",
+            0x44 => self.run_syscall(opcode),            // "DIFFICULTY",
+            0x45 => self.run_syscall(opcode),            // "GASLIMIT",
+            0x46 => self.run_syscall(opcode),            // "CHAINID",
+            0x48 => self.run_syscall(opcode),            // "BASEFEE",
+            0x49 => self.run_prover_input()?,            // "PROVER_INPUT",
+            0x50 => self.run_pop(),                      // "POP",
+            0x51 => self.run_mload(),                    // "MLOAD",
+            0x52 => self.run_mstore(),                   // "MSTORE",
+            0x53 => self.run_mstore8(),                  // "MSTORE8",
+            0x54 => self.run_syscall(opcode),            // "SLOAD",
+            0x55 => self.run_syscall(opcode),            // "SSTORE",
+            0x56 => self.run_jump(),                     // "JUMP",
+            0x57 => self.run_jumpi(),                    // "JUMPI",
+            0x58 => self.run_pc(),                       // "PC",
+            0x59 => self.run_msize(),                    // "MSIZE",
+            0x5a => self.run_syscall(opcode),            // "GAS",
+            0x5b => self.run_jumpdest(),                 // "JUMPDEST",
+            x if (0x60..0x80).contains(&x) => self.run_push(x - 0x5f), // "PUSH"
+            x if (0x80..0x90).contains(&x) => self.run_dup(x - 0x7f), // "DUP"
             x if (0x90..0xa0).contains(&x) => self.run_swap(x - 0x8f)?, // "SWAP"
-            0xa0 => todo!(),                                            // "LOG0",
-            0xa1 => todo!(),                                            // "LOG1",
-            0xa2 => todo!(),                                            // "LOG2",
-            0xa3 => todo!(),                                            // "LOG3",
-            0xa4 => todo!(),                                            // "LOG4",
-            0xa5 => bail!("Executed PANIC"),                            // "PANIC",
-            0xf0 => todo!(),                                            // "CREATE",
-            0xf1 => todo!(),                                            // "CALL",
-            0xf2 => todo!(),                                            // "CALLCODE",
-            0xf3 => todo!(),                                            // "RETURN",
-            0xf4 => todo!(),                                            // "DELEGATECALL",
-            0xf5 => todo!(),                                            // "CREATE2",
-            0xf6 => self.run_get_context(),                             // "GET_CONTEXT",
-            0xf7 => self.run_set_context(),                             // "SET_CONTEXT",
-            0xf8 => todo!(),                                            // "CONSUME_GAS",
-            0xf9 => todo!(),                                            // "EXIT_KERNEL",
-            0xfa => todo!(),                                            // "STATICCALL",
-            0xfb => self.run_mload_general(),                           // "MLOAD_GENERAL",
-            0xfc => self.run_mstore_general(),                          // "MSTORE_GENERAL",
-            0xfd => todo!(),                                            // "REVERT",
-            0xfe => bail!("Executed INVALID"),                          // "INVALID",
-            0xff => todo!(),                                            // "SELFDESTRUCT",
+            0xa0 => self.run_syscall(opcode),            // "LOG0",
+            0xa1 => self.run_syscall(opcode),            // "LOG1",
+            0xa2 => self.run_syscall(opcode),            // "LOG2",
+            0xa3 => self.run_syscall(opcode),            // "LOG3",
+            0xa4 => self.run_syscall(opcode),            // "LOG4",
+            0xa5 => bail!("Executed PANIC"),             // "PANIC",
+            0xf0 => self.run_syscall(opcode),            // "CREATE",
+            0xf1 => self.run_syscall(opcode),            // "CALL",
+            0xf2 => self.run_syscall(opcode),            // "CALLCODE",
+            0xf3 => self.run_syscall(opcode),            // "RETURN",
+            0xf4 => self.run_syscall(opcode),            // "DELEGATECALL",
+            0xf5 => self.run_syscall(opcode),            // "CREATE2",
+            0xf6 => self.run_get_context(),              // "GET_CONTEXT",
+            0xf7 => self.run_set_context(),              // "SET_CONTEXT",
+            0xf9 => self.run_exit_kernel(),              // "EXIT_KERNEL",
+            0xfa => self.run_syscall(opcode),            // "STATICCALL",
+            0xfb => self.run_mload_general(),            // "MLOAD_GENERAL",
+            0xfc => self.run_mstore_general(),           // "MSTORE_GENERAL",
+            0xfd => self.run_syscall(opcode),            // "REVERT",
+            0xfe => bail!("Executed INVALID"),           // "INVALID",
+            0xff => self.run_syscall(opcode),            // "SELFDESTRUCT",
             _ => bail!("Unrecognized opcode {}.", opcode),
         };
 
@@ -438,6 +466,36 @@ impl<'a> Interpreter<'a> {
         self.push(if y.is_zero() { U256::zero() } else { x / y });
     }
 
+    fn run_sdiv(&mut self) {
+        let x = self.pop();
+        let y = self.pop();
+        self.push(if y.is_zero() {
+            U256::zero()
+        } else {
+            let quotient = abs(x) / abs(y);
+            if is_neg(x) != is_neg(y) {
+                neg(quotient)
+            } else {
+                quotient
+            }
+        });
+    }
+
+    fn run_smod(&mut self) {
+        let x = self.pop();
+        let y = self.pop();
+        self.push(if y.is_zero() {
+            U256::zero()
+        } else {
+            let remainder = abs(x) % abs(y);
+            if is_neg(x) {
+                neg(remainder)
+            } else {
+                remainder
+            }
+        });
+    }
+
     fn run_mod(&mut self) {
         let x = self.pop();
         let y = self.pop();
@@ -484,6 +542,26 @@ impl<'a> Interpreter<'a> {
         self.push_bool(x > y);
     }
 
+    fn run_slt(&mut self) {
+        let x = self.pop();
+        let y = self.pop();
+        self.push_bool(match (is_neg(x), is_neg(y)) {
+            (true, false) => true,
+            (false, true) => false,
+            _ => x < y,
+        });
+    }
+
+    fn run_sgt(&mut self) {
+        let x = self.pop();
+        let y = self.pop();
+        self.push_bool(match (is_neg(x), is_neg(y)) {
+            (true, false) => false,
+            (false, true) => true,
+            _ => x > y,
+        });
+    }
+
     fn run_eq(&mut self) {
         let x = self.pop();
         let y = self.pop();
@@ -541,6 +619,43 @@ impl<'a> Interpreter<'a> {
         self.push(value >> shift);
     }
 
+    fn run_sar(&mut self) {
+        let shift = self.pop();
+        let value = self.pop();
+        let result = if shift >= U256::from(256) {
+            if is_neg(value) {
+                U256::MAX
+            } else {
+                U256::zero()
+            }
+        } else {
+            let shift = shift.as_usize();
+            let shifted = value >> shift;
+            if is_neg(value) && shift > 0 {
+                shifted | (U256::MAX << (256 - shift))
+            } else {
+                shifted
+            }
+        };
+        self.push(result);
+    }
+
+    fn run_signextend(&mut self) {
+        let b = self.pop();
+        let x = self.pop();
+        if b >= U256::from(31) {
+            self.push(x);
+            return;
+        }
+        let sign_bit = 8 * b.as_usize() + 7;
+        let low_mask = (U256::one() << (sign_bit + 1)) - 1;
+        self.push(if x.bit(sign_bit) {
+            x | !low_mask
+        } else {
+            x & low_mask
+        });
+    }
+
     fn run_keccak256(&mut self) {
         let offset = self.pop().as_usize();
         let size = self.pop().as_usize();
@@ -576,56 +691,6 @@ impl<'a> Interpreter<'a> {
         self.push(U256::from_big_endian(hash.as_bytes()));
     }
 
-    fn run_callvalue(&mut self) {
-        self.push(
-            self.generation_state.memory.contexts[self.context].segments
-                [Segment::ContextMetadata as usize]
-                .get(ContextMetadata::CallValue as usize),
-        )
-    }
-
-    fn run_calldataload(&mut self) {
-        let offset = self.pop().as_usize();
-        let value = U256::from_big_endian(
-            &(0..32)
-                .map(|i| {
-                    self.generation_state
-                        .memory
-                        .mload_general(self.context, Segment::Calldata, offset + i)
-                        .byte(0)
-                })
-                .collect::<Vec<_>>(),
-        );
-        self.push(value);
-    }
-
-    fn run_calldatasize(&mut self) {
-        self.push(
-            self.generation_state.memory.contexts[self.context].segments
-                [Segment::ContextMetadata as usize]
-                .get(ContextMetadata::CalldataSize as usize),
-        )
-    }
-
-    fn run_calldatacopy(&mut self) {
-        let dest_offset = self.pop().as_usize();
-        let offset = self.pop().as_usize();
-        let size = self.pop().as_usize();
-        for i in 0..size {
-            let calldata_byte = self.generation_state.memory.mload_general(
-                self.context,
-                Segment::Calldata,
-                offset + i,
-            );
-            self.generation_state.memory.mstore_general(
-                self.context,
-                Segment::MainMemory,
-                dest_offset + i,
-                calldata_byte,
-            );
-        }
-    }
-
     fn run_prover_input(&mut self) -> anyhow::Result<()> {
         let prover_input_fn = self
             .prover_inputs_map
@@ -740,13 +805,64 @@ impl<'a> Interpreter<'a> {
         Ok(())
     }
 
+    /// Jumps to the kernel's handler for `opcode`, pushing the same syscall info as the CPU does.
+    fn run_syscall(&mut self, opcode: u8) {
+        let handler_addr_addr = KERNEL.global_labels["syscall_jumptable"]
+            + (opcode as usize) * BYTES_PER_OFFSET as usize;
+        let handler_addr = (0..BYTES_PER_OFFSET as usize).fold(0, |acc, i| {
+            let byte =
+                self.generation_state
+                    .memory
+                    .mload_general(0, Segment::Code, handler_addr_addr + i);
+            (acc << 8) + byte.as_usize()
+        });
+
+        let registers = &self.generation_state.registers;
+        let syscall_info = U256::from(registers.program_counter)
+            + (U256::from(u64::from(self.kernel_mode)) << 32)
+            + (U256::from(registers.gas_used) << 192);
+        self.kernel_mode = true;
+        self.generation_state.registers.is_kernel = true;
+        self.generation_state.registers.gas_used = 0;
+        self.push(syscall_info);
+        self.jump_to(handler_addr);
+    }
+
     fn run_get_context(&mut self) {
         self.push(self.context.into());
     }
 
+    /// Switches to the given context, saving the stack size of the current one in its metadata and
+    /// restoring the new one's, as the CPU does.
     fn run_set_context(&mut self) {
-        let x = self.pop();
-        self.context = x.as_usize();
+        let new_ctx = self.pop().as_usize();
+        let sp_field = ContextMetadata::StackSize as usize;
+        let old_sp_addr = MemoryAddress::new(self.context, Segment::ContextMetadata, sp_field);
+        let new_sp_addr = MemoryAddress::new(new_ctx, Segment::ContextMetadata, sp_field);
+        self.generation_state
+            .memory
+            .set(old_sp_addr, self.stack_len().into());
+        let new_sp = self.generation_state.memory.get(new_sp_addr).as_usize();
+
+        self.context = new_ctx;
+        self.generation_state.registers.context = new_ctx;
+        self.generation_state.registers.stack_len = new_sp;
+    }
+
+    fn run_exit_kernel(&mut self) {
+        let kexit_info = self.pop();
+        let kexit_info_u64 = kexit_info.0[0];
+        let program_counter = kexit_info_u64 as u32 as usize;
+        let is_kernel_mode_val = (kexit_info_u64 >> 32) as u32;
+        assert!(is_kernel_mode_val == 0 || is_kernel_mode_val == 1);
+        self.kernel_mode = is_kernel_mode_val != 0;
+        self.generation_state.registers.is_kernel = self.kernel_mode;
+        self.generation_state.registers.gas_used = kexit_info.0[3];
+        self.generation_state.registers.program_counter = program_counter;
+
+        if self.halt_offsets.contains(&program_counter) {
+            self.running = false;
+        }
     }
 
     fn run_mload_general(&mut self) {
@@ -776,6 +892,25 @@ impl<'a> Interpreter<'a> {
     }
 }
 
+/// Whether `x` is negative when interpreted as a two's complement integer.
+fn is_neg(x: U256) -> bool {
+    x.bit(255)
+}
+
+/// The two's complement negation of `x`.
+fn neg(x: U256) -> U256 {
+    (!x).overflowing_add(U256::one()).0
+}
+
+/// The absolute value of `x` when interpreted as a two's complement integer.
+fn abs(x: U256) -> U256 {
+    if is_neg(x) {
+        neg(x)
+    } else {
+        x
+    }
+}
+
 /// Return the (ordered) JUMPDEST offsets in the code.
 fn find_jumpdests(code: &[u8]) -> Vec<usize> {
     let mut offset = 0;
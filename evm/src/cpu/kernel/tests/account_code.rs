@@ -31,7 +31,7 @@ fn random_code() -> Vec<u8> {
 
 // Stolen from `tests/mpt/insert.rs`
 // Prepare the interpreter by inserting the account in the state trie.
-fn prepare_interpreter(
+pub(crate) fn prepare_interpreter(
     interpreter: &mut Interpreter,
     address: Address,
     account: &AccountRlp,
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use eth_trie_utils::partial_trie::PartialTrie;
+use ethereum_types::{Address, U256};
+use keccak_hash::keccak;
+
+use crate::cpu::kernel::aggregator::KERNEL;
+use crate::cpu::kernel::constants::context_metadata::ContextMetadata;
+use crate::cpu::kernel::constants::global_metadata::GlobalMetadata;
+use crate::cpu::kernel::interpreter::Interpreter;
+use crate::cpu::kernel::opcodes::{get_opcode, get_push_opcode};
+use crate::cpu::kernel::tests::account_code::prepare_interpreter;
+use crate::generation::mpt::AccountRlp;
+use crate::memory::segments::Segment;
+use crate::witness::memory::MemoryAddress;
+
+const GAS_COLDACCOUNTACCESS: u64 = 2_600;
+const GAS_CALLVALUE: u64 = 9_000;
+const GAS_CALLSTIPEND: u64 = 2_300;
+const GAS_NEWACCOUNT: u64 = 25_000;
+
+/// The context making the calls.
+const CALLER_CONTEXT: usize = 1;
+
+const INPUT: [u8; 5] = [1, 2, 3, 4, 5];
+const ARGS_OFFSET: usize = 0;
+const RET_OFFSET: usize = 32;
+
+fn memory_cost(num_words: u64) -> u64 {
+    3 * num_words + num_words * num_words / 512
+}
+
+/// Prepares an interpreter to run the given call syscall from `CALLER_CONTEXT`, with `INPUT` in
+/// the caller's memory as the call's input, and room for as much output at `RET_OFFSET`.
+fn call_interpreter(
+    interpreter: &mut Interpreter,
+    syscall: &str,
+    address: Address,
+    value: Option<U256>,
+) {
+    interpreter.generation_state.registers.program_counter = KERNEL.global_labels[syscall];
+    interpreter.context = CALLER_CONTEXT;
+    interpreter.generation_state.registers.context = CALLER_CONTEXT;
+    interpreter.set_global_metadata_field(GlobalMetadata::LargestContext, CALLER_CONTEXT.into());
+    interpreter.set_context_metadata_field(
+        CALLER_CONTEXT,
+        ContextMetadata::GasLimit,
+        100_000.into(),
+    );
+    for (i, &byte) in INPUT.iter().enumerate() {
+        interpreter.generation_state.memory.set(
+            MemoryAddress::new(CALLER_CONTEXT, Segment::MainMemory, ARGS_OFFSET + i),
+            byte.into(),
+        );
+    }
+
+    interpreter.push(INPUT.len().into()); // ret_size
+    interpreter.push(RET_OFFSET.into());
+    interpreter.push(INPUT.len().into()); // args_size
+    interpreter.push(ARGS_OFFSET.into());
+    if let Some(value) = value {
+        interpreter.push(value);
+    }
+    interpreter.push(U256::from_big_endian(address.as_bytes()));
+    interpreter.push(50_000.into()); // gas
+    interpreter.push(0xDEADBEEFu32.into()); // kexit_info
+}
+
+fn caller_output(interpreter: &Interpreter) -> Vec<u8> {
+    (0..INPUT.len())
+        .map(|i| {
+            interpreter
+                .generation_state
+                .memory
+                .mload_general(CALLER_CONTEXT, Segment::MainMemory, RET_OFFSET + i)
+                .as_u32() as u8
+        })
+        .collect()
+}
+
+#[test]
+fn test_call_precompile() -> Result<()> {
+    // The identity precompile returns its input.
+    let mut interpreter = Interpreter::new_with_kernel(0, vec![]);
+    call_interpreter(
+        &mut interpreter,
+        "sys_call",
+        Address::from_low_u64_be(4),
+        Some(0.into()),
+    );
+    interpreter.run()?;
+
+    assert_eq!(interpreter.stack(), vec![U256::one()]);
+    assert_eq!(caller_output(&interpreter), INPUT);
+    assert_eq!(
+        interpreter.get_context_metadata_field(CALLER_CONTEXT, ContextMetadata::ReturndataSize),
+        INPUT.len().into()
+    );
+    // The caller only pays for the gas the precompile used, and the memory holding the output.
+    let id_gas = 15 + 3;
+    assert_eq!(
+        interpreter.generation_state.registers.gas_used,
+        GAS_COLDACCOUNTACCESS + memory_cost(2) + id_gas
+    );
+    Ok(())
+}
+
+#[test]
+fn test_call_unsupported_precompile() -> Result<()> {
+    // EXPMOD isn't supported, so calling it fails and consumes all the gas given to it.
+    let mut interpreter = Interpreter::new_with_kernel(0, vec![]);
+    call_interpreter(
+        &mut interpreter,
+        "sys_call",
+        Address::from_low_u64_be(5),
+        Some(0.into()),
+    );
+    interpreter.run()?;
+
+    assert_eq!(interpreter.stack(), vec![U256::zero()]);
+    assert_eq!(caller_output(&interpreter), [0; INPUT.len()]);
+    assert_eq!(
+        interpreter.get_context_metadata_field(CALLER_CONTEXT, ContextMetadata::ReturndataSize),
+        0.into()
+    );
+    assert_eq!(
+        interpreter.generation_state.registers.gas_used,
+        GAS_COLDACCOUNTACCESS + memory_cost(2) + 50_000
+    );
+    Ok(())
+}
+
+#[test]
+fn test_call_runs_code() -> Result<()> {
+    // The callee returns its calldata.
+    let push1 = get_push_opcode(1);
+    let calldatasize = get_opcode("CALLDATASIZE");
+    let code = vec![
+        calldatasize,
+        push1,
+        0,
+        push1,
+        0,
+        get_opcode("CALLDATACOPY"),
+        calldatasize,
+        push1,
+        0,
+        get_opcode("RETURN"),
+    ];
+    let callee = Address::from_low_u64_be(0xC0DE);
+    let account = AccountRlp {
+        nonce: 1.into(),
+        balance: 0.into(),
+        storage_root: PartialTrie::Empty.calc_hash(),
+        code_hash: keccak(&code),
+    };
+
+    for syscall in [
+        "sys_call",
+        "sys_callcode",
+        "sys_delegatecall",
+        "sys_staticcall",
+    ] {
+        let mut interpreter = Interpreter::new_with_kernel(0, vec![]);
+        prepare_interpreter(&mut interpreter, callee, &account)?;
+        interpreter.pop();
+        interpreter.generation_state.inputs.contract_code =
+            HashMap::from([(keccak(&code), code.clone())]);
+        let value = match syscall {
+            "sys_call" | "sys_callcode" => Some(0.into()),
+            _ => None,
+        };
+        call_interpreter(&mut interpreter, syscall, callee, value);
+        interpreter.run()?;
+
+        assert_eq!(interpreter.stack(), vec![U256::one()], "{syscall}");
+        assert_eq!(caller_output(&interpreter), INPUT, "{syscall}");
+        // Only syscalls are charged in the interpreter, so the callee pays for its CALLDATASIZEs
+        // and its CALLDATACOPY of a word into fresh memory, and gives the rest of its gas back.
+        let callee_gas = 2 * 2 + (3 + 3 + memory_cost(1));
+        assert_eq!(
+            interpreter.generation_state.registers.gas_used,
+            GAS_COLDACCOUNTACCESS + memory_cost(2) + callee_gas,
+            "{syscall}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_call_insufficient_balance() -> Result<()> {
+    // The caller has no account, so it can't send any value. The call fails without running the
+    // callee, and gives its gas back, including the stipend.
+    let mut interpreter = Interpreter::new_with_kernel(0, vec![]);
+    call_interpreter(
+        &mut interpreter,
+        "sys_call",
+        Address::from_low_u64_be(0xC0DE),
+        Some(1.into()),
+    );
+    interpreter.run()?;
+
+    assert_eq!(interpreter.stack(), vec![U256::zero()]);
+    assert_eq!(
+        interpreter.generation_state.registers.gas_used,
+        GAS_COLDACCOUNTACCESS + GAS_CALLVALUE + GAS_NEWACCOUNT + memory_cost(2) - GAS_CALLSTIPEND
+    );
+    Ok(())
+}
+
+#[test]
+fn test_static_call_with_value() -> Result<()> {
+    // Sending value from a static context is an exceptional halt of the caller.
+    let fault_exception = KERNEL.global_labels["fault_exception"];
+    let mut interpreter = Interpreter::new_with_kernel(0, vec![]);
+    call_interpreter(
+        &mut interpreter,
+        "sys_call",
+        Address::from_low_u64_be(0xC0DE),
+        Some(1.into()),
+    );
+    interpreter.set_context_metadata_field(CALLER_CONTEXT, ContextMetadata::Static, 1.into());
+    interpreter.halt_offsets.push(fault_exception);
+    interpreter.run()?;
+
+    assert_eq!(
+        interpreter.generation_state.registers.program_counter,
+        fault_exception
+    );
+    Ok(())
+}
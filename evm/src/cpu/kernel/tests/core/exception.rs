@@ -0,0 +1,230 @@
+use ethereum_types::U256;
+
+use crate::cpu::kernel::aggregator::KERNEL;
+use crate::cpu::kernel::constants::context_metadata::ContextMetadata;
+use crate::cpu::kernel::interpreter::Interpreter;
+use crate::cpu::kernel::opcodes::{get_opcode, get_push_opcode};
+use crate::memory::segments::Segment;
+use crate::witness::memory::MemoryAddress;
+
+const CONTEXT: usize = 3; // arbitrary
+const GAS_LIMIT: u64 = 100;
+
+const OUT_OF_GAS: usize = 0;
+const INVALID_OPCODE: usize = 1;
+const STACK_UNDERFLOW: usize = 2;
+const INVALID_JUMP_DESTINATION: usize = 3;
+const INVALID_JUMPI_DESTINATION: usize = 4;
+const STACK_OVERFLOW: usize = 5;
+
+/// Traps to the handler of the given exception, as if it had been raised by the instruction at
+/// `pc` in user code, and returns whether the handler accepted the exception, i.e. halted the
+/// context rather than panicking.
+///
+/// `stack_len` is the length of the user stack recorded in the trap info, while `stack` holds the
+/// top items of that stack, top first.
+fn exception_accepted(
+    exc_code: usize,
+    code: &[u8],
+    pc: usize,
+    stack_len: usize,
+    stack: &[U256],
+    gas_used: u64,
+) -> bool {
+    let jumptable = KERNEL.global_labels["exception_jumptable"] + 3 * exc_code;
+    let handler = KERNEL.code[jumptable..jumptable + 3]
+        .iter()
+        .fold(0, |acc, &byte| (acc << 8) + byte as usize);
+    let fault_exception = KERNEL.global_labels["fault_exception"];
+
+    let mut interpreter = Interpreter::new_with_kernel(handler, vec![]);
+    interpreter.halt_offsets.push(fault_exception);
+    interpreter.set_code(CONTEXT, code.to_vec());
+    interpreter.context = CONTEXT;
+    interpreter.generation_state.registers.context = CONTEXT;
+    interpreter.set_context_metadata_field(CONTEXT, ContextMetadata::GasLimit, GAS_LIMIT.into());
+    // Only the instructions at these offsets are valid jump destinations.
+    for offset in [2, 4] {
+        interpreter.generation_state.memory.set(
+            MemoryAddress::new(CONTEXT, Segment::JumpdestBits, offset),
+            U256::one(),
+        );
+    }
+
+    for &item in stack.iter().rev() {
+        interpreter.push(item);
+    }
+    let trap_info = U256::from(pc) + (U256::from(stack_len) << 64) + (U256::from(gas_used) << 192);
+    interpreter.push(trap_info);
+
+    match interpreter.run() {
+        Ok(()) => {
+            assert_eq!(
+                interpreter.generation_state.registers.program_counter,
+                fault_exception
+            );
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[test]
+fn test_out_of_gas() {
+    let push1 = get_push_opcode(1);
+    let code = [push1, 1, push1, 2, get_opcode("ADD"), get_opcode("SLOAD")];
+
+    // ADD costs 3 gas.
+    assert!(exception_accepted(OUT_OF_GAS, &code, 4, 2, &[], 98));
+    assert!(!exception_accepted(OUT_OF_GAS, &code, 4, 2, &[], 97));
+    // Syscalls charge their gas in the kernel, so they only fault once the gas is exhausted.
+    assert!(exception_accepted(OUT_OF_GAS, &code, 5, 1, &[], 101));
+    assert!(!exception_accepted(OUT_OF_GAS, &code, 5, 1, &[], 100));
+}
+
+#[test]
+fn test_invalid_opcode() {
+    let code = [0xfe, 0x0c, get_opcode("ADD"), get_opcode("SLOAD")];
+
+    assert!(exception_accepted(INVALID_OPCODE, &code, 0, 0, &[], 0));
+    // Kernel-only instructions are invalid in user mode.
+    assert!(exception_accepted(INVALID_OPCODE, &code, 1, 0, &[], 0));
+    assert!(!exception_accepted(INVALID_OPCODE, &code, 2, 2, &[], 0));
+    assert!(!exception_accepted(INVALID_OPCODE, &code, 3, 1, &[], 0));
+}
+
+#[test]
+fn test_stack_underflow() {
+    let code = [
+        get_opcode("ADD"),
+        get_opcode("SWAP16"),
+        get_opcode("SSTORE"),
+    ];
+
+    assert!(exception_accepted(STACK_UNDERFLOW, &code, 0, 1, &[], 0));
+    assert!(!exception_accepted(STACK_UNDERFLOW, &code, 0, 2, &[], 0));
+    assert!(exception_accepted(STACK_UNDERFLOW, &code, 1, 16, &[], 0));
+    assert!(!exception_accepted(STACK_UNDERFLOW, &code, 1, 17, &[], 0));
+    assert!(exception_accepted(STACK_UNDERFLOW, &code, 2, 1, &[], 0));
+    assert!(!exception_accepted(STACK_UNDERFLOW, &code, 2, 2, &[], 0));
+}
+
+#[test]
+fn test_stack_overflow() {
+    let code = [
+        get_push_opcode(1),
+        0,
+        get_opcode("POP"),
+        get_opcode("ADDRESS"),
+    ];
+
+    assert!(exception_accepted(STACK_OVERFLOW, &code, 0, 1024, &[], 0));
+    assert!(!exception_accepted(STACK_OVERFLOW, &code, 0, 1023, &[], 0));
+    assert!(!exception_accepted(STACK_OVERFLOW, &code, 2, 1024, &[], 0));
+    assert!(exception_accepted(STACK_OVERFLOW, &code, 3, 1024, &[], 0));
+}
+
+#[test]
+fn test_invalid_jump_destination() {
+    let jumpdest = get_opcode("JUMPDEST");
+    let code = [
+        get_opcode("JUMP"),
+        get_opcode("ADD"),
+        jumpdest,
+        get_opcode("ADD"),
+        jumpdest,
+    ];
+
+    assert!(exception_accepted(
+        INVALID_JUMP_DESTINATION,
+        &code,
+        0,
+        1,
+        &[3.into()],
+        0
+    ));
+    assert!(!exception_accepted(
+        INVALID_JUMP_DESTINATION,
+        &code,
+        0,
+        1,
+        &[4.into()],
+        0
+    ));
+    // The upper bits of a destination are not ignored.
+    let dest = (U256::one() << 32) + 4;
+    assert!(exception_accepted(
+        INVALID_JUMP_DESTINATION,
+        &code,
+        0,
+        1,
+        &[dest],
+        0
+    ));
+    // The faulting instruction must be a JUMP.
+    assert!(!exception_accepted(
+        INVALID_JUMP_DESTINATION,
+        &code,
+        1,
+        2,
+        &[3.into()],
+        0
+    ));
+}
+
+#[test]
+fn test_invalid_jumpi_destination() {
+    let jumpdest = get_opcode("JUMPDEST");
+    let code = [
+        get_opcode("JUMPI"),
+        get_opcode("JUMP"),
+        jumpdest,
+        get_opcode("ADD"),
+        jumpdest,
+    ];
+    let one = U256::one();
+
+    assert!(exception_accepted(
+        INVALID_JUMPI_DESTINATION,
+        &code,
+        0,
+        2,
+        &[3.into(), one],
+        0
+    ));
+    assert!(!exception_accepted(
+        INVALID_JUMPI_DESTINATION,
+        &code,
+        0,
+        2,
+        &[2.into(), one],
+        0
+    ));
+    // A JUMPI whose condition is zero doesn't jump, so it can't fault.
+    let zero = U256::zero();
+    assert!(!exception_accepted(
+        INVALID_JUMPI_DESTINATION,
+        &code,
+        0,
+        2,
+        &[3.into(), zero],
+        0
+    ));
+    // The faulting instruction must be a JUMPI.
+    assert!(!exception_accepted(
+        INVALID_JUMPI_DESTINATION,
+        &code,
+        1,
+        2,
+        &[3.into(), one],
+        0
+    ));
+}
+
+#[test]
+fn test_unused_exception_codes() {
+    let code = [get_opcode("ADD")];
+    for exc_code in [6, 7] {
+        assert!(!exception_accepted(exc_code, &code, 0, 0, &[], 0));
+    }
+}
@@ -25,3 +25,27 @@ fn test_intrinsic_gas() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_gas_limit_covers_intrinsic_gas() -> Result<()> {
+    let validate = KERNEL.global_labels["validate"];
+    // Validation is done once the kernel starts charging the sender for gas.
+    let deduct_eth = KERNEL.global_labels["deduct_eth"];
+    let run_validate = |gas_limit: u32| -> Result<Interpreter> {
+        let initial_stack = vec![0xdeadbeefu32.into(), GAS_TX.into()];
+        let mut interpreter = Interpreter::new_with_kernel(validate, initial_stack);
+        interpreter.halt_offsets.push(deduct_eth);
+        interpreter.set_txn_field(NormalizedTxnField::GasLimit, gas_limit.into());
+        interpreter.run()?;
+        Ok(interpreter)
+    };
+
+    let interpreter = run_validate(GAS_TX)?;
+    assert_eq!(
+        interpreter.get_txn_field(NormalizedTxnField::IntrinsicGas),
+        GAS_TX.into()
+    );
+    assert!(run_validate(GAS_TX - 1).is_err());
+
+    Ok(())
+}
@@ -1,3 +1,9 @@
+mod call;
 mod create_addresses;
+mod exception;
 mod intrinsic_gas;
 mod jumpdest_analysis;
+mod precompiles;
+mod process_txn;
+mod receipt;
+mod syscalls;
@@ -0,0 +1,281 @@
+use anyhow::Result;
+use blake2::Blake2b512;
+use ethereum_types::U256;
+use rand::{thread_rng, Rng};
+use ripemd::{Digest, Ripemd160};
+use sha2::Sha256;
+
+use crate::cpu::kernel::aggregator::KERNEL;
+use crate::cpu::kernel::constants::context_metadata::ContextMetadata;
+use crate::cpu::kernel::interpreter::Interpreter;
+use crate::cpu::kernel::tests::u256ify;
+use crate::memory::segments::Segment;
+use crate::witness::memory::MemoryAddress;
+
+/// The result of running a precompile: whether it succeeded, the leftover gas and the returndata.
+struct PrecompileResult {
+    success: bool,
+    leftover_gas: u64,
+    returndata: Vec<u8>,
+}
+
+fn run_precompile(address: u8, gas: u64, input: &[u8]) -> Result<PrecompileResult> {
+    let handle_precompile = KERNEL.global_labels["handle_precompile"];
+    // The input is read from the transaction data of the kernel context.
+    let initial_stack = vec![
+        0xDEADBEEFu32.into(),
+        input.len().into(),
+        0.into(),
+        (Segment::TxnData as usize).into(),
+        0.into(),
+        gas.into(),
+        address.into(),
+    ];
+    let mut interpreter = Interpreter::new_with_kernel(handle_precompile, initial_stack);
+    for (i, &byte) in input.iter().enumerate() {
+        interpreter
+            .generation_state
+            .memory
+            .set(MemoryAddress::new(0, Segment::TxnData, i), byte.into());
+    }
+    interpreter.run()?;
+
+    let stack = interpreter.stack();
+    assert_eq!(stack.len(), 2);
+    let memory = &interpreter.generation_state.memory;
+    let returndata_size = memory
+        .mload_general(
+            0,
+            Segment::ContextMetadata,
+            ContextMetadata::ReturndataSize as usize,
+        )
+        .as_usize();
+    let returndata = (0..returndata_size)
+        .map(|i| memory.mload_general(0, Segment::Returndata, i).as_u32() as u8)
+        .collect();
+    Ok(PrecompileResult {
+        success: !stack[1].is_zero(),
+        leftover_gas: stack[0].as_u64(),
+        returndata,
+    })
+}
+
+fn words(values: &[U256]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|v| {
+            let mut bytes = [0; 32];
+            v.to_big_endian(&mut bytes);
+            bytes
+        })
+        .collect()
+}
+
+fn random_input(max_len: usize) -> Vec<u8> {
+    let mut rng = thread_rng();
+    let len = rng.gen_range(0..max_len);
+    (0..len).map(|_| rng.gen()).collect()
+}
+
+#[test]
+fn test_ecrec() -> Result<()> {
+    let input = words(&u256ify([
+        "0x55f77e8909b1f1c9531c4a309bb2d40388e9ed4b87830c8f90363c6b36255fb9",
+        "0x1b",
+        "0xd667c5a20fa899b253924099e10ae92998626718585b8171eb98de468bbebc",
+        "0x58351f48ce34bf134ee611fb5bf255a5733f0029561d345a7d46bfa344b60ac0",
+    ])?);
+    let result = run_precompile(1, 5000, &input)?;
+    assert!(result.success);
+    assert_eq!(result.leftover_gas, 2000);
+    assert_eq!(
+        result.returndata,
+        words(&u256ify(["0x67f3c0Da351384838d7F7641AB0fCAcF853E1844"])?)
+    );
+
+    // An invalid signature succeeds with empty output.
+    let mut invalid_input = input;
+    invalid_input[63] = 0x42; // v not in {27, 28}
+    let result = run_precompile(1, 5000, &invalid_input)?;
+    assert!(result.success);
+    assert_eq!(result.leftover_gas, 2000);
+    assert!(result.returndata.is_empty());
+
+    // Not enough gas.
+    let result = run_precompile(1, 2999, &invalid_input)?;
+    assert!(!result.success);
+    assert_eq!(result.leftover_gas, 0);
+    Ok(())
+}
+
+#[test]
+fn test_sha256() -> Result<()> {
+    let input = random_input(300);
+    let gas_cost = 60 + 12 * ((input.len() as u64 + 31) / 32);
+    let result = run_precompile(2, 10_000, &input)?;
+    assert!(result.success);
+    assert_eq!(result.leftover_gas, 10_000 - gas_cost);
+    assert_eq!(result.returndata, Sha256::digest(&input).to_vec());
+
+    let result = run_precompile(2, gas_cost - 1, &input)?;
+    assert!(!result.success);
+    assert_eq!(result.leftover_gas, 0);
+    assert!(result.returndata.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_rip160() -> Result<()> {
+    let input = random_input(300);
+    let gas_cost = 600 + 120 * ((input.len() as u64 + 31) / 32);
+    let result = run_precompile(3, 100_000, &input)?;
+    assert!(result.success);
+    assert_eq!(result.leftover_gas, 100_000 - gas_cost);
+    let mut expected = vec![0; 12];
+    expected.extend(Ripemd160::digest(&input));
+    assert_eq!(result.returndata, expected);
+    Ok(())
+}
+
+#[test]
+fn test_id() -> Result<()> {
+    let input = random_input(300);
+    let gas_cost = 15 + 3 * ((input.len() as u64 + 31) / 32);
+    let result = run_precompile(4, 1000, &input)?;
+    assert!(result.success);
+    assert_eq!(result.leftover_gas, 1000 - gas_cost);
+    assert_eq!(result.returndata, input);
+    Ok(())
+}
+
+#[test]
+fn test_unsupported_precompiles() -> Result<()> {
+    // EXPMOD and SNARKV aren't supported, so they fail rather than succeed with empty output.
+    for address in [5, 8] {
+        let result = run_precompile(address, 100_000, &[1, 2, 3])?;
+        assert!(!result.success);
+        assert_eq!(result.leftover_gas, 0);
+        assert!(result.returndata.is_empty());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_bn_add() -> Result<()> {
+    let point0 = [
+        "0x1feee7ec986e198890cb83be8b8ba09ee953b3f149db6d9bfdaa5c308a33e58d",
+        "0x2051cc9a9edd46231604fd88f351e95ec72a285be93e289ac59cb48561efb2c6",
+    ];
+    let point1 = [
+        "0x15b64d0a5f329fb672029298be8050f444626e6de11903caffa74b388075be1b",
+        "0x2d9e07340bd5cd7b70687b98f2500ff930a89a30d7b6a3e04b1b4d345319d234",
+    ];
+    let point2 = [
+        "0x18659c0e0a8fedcb8747cf463fc7cfa05f667d84e771d0a9521fc1a550688f0c",
+        "0x283ed10b42703e187e7a808aeb45c6b457bc4cc7d704e53b3348a1e3b0bfa55b",
+    ];
+    let input = words(&u256ify(point0.into_iter().chain(point1))?);
+    let result = run_precompile(6, 1000, &input)?;
+    assert!(result.success);
+    assert_eq!(result.leftover_gas, 850);
+    assert_eq!(result.returndata, words(&u256ify(point2)?));
+
+    // A missing second point is read as the identity.
+    let result = run_precompile(6, 1000, &input[..64])?;
+    assert!(result.success);
+    assert_eq!(result.returndata, words(&u256ify(point0)?));
+
+    // A point which is not on the curve makes the precompile fail.
+    let input = words(&u256ify(point0.into_iter().chain(["0x0", "0x3"]))?);
+    let result = run_precompile(6, 1000, &input)?;
+    assert!(!result.success);
+    assert_eq!(result.leftover_gas, 0);
+    assert!(result.returndata.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_bn_mul() -> Result<()> {
+    let point0 = [
+        "0x1feee7ec986e198890cb83be8b8ba09ee953b3f149db6d9bfdaa5c308a33e58d",
+        "0x2051cc9a9edd46231604fd88f351e95ec72a285be93e289ac59cb48561efb2c6",
+    ];
+    let s = "0xabb2a34c0e7956cfe6cef9ddb7e810c45ea19a6ebadd79c21959af09f5ba480a";
+    let point4 = [
+        "0xe519344959cc17021fe98878f947f5c1b1675325533a620c1684cfa6367e6c0",
+        "0x7496a7575b0b6a821e19ce780ecc3e0b156e605327798693defeb9f265b7a6f",
+    ];
+    let input = words(&u256ify(point0.into_iter().chain([s]))?);
+    let result = run_precompile(7, 10_000, &input)?;
+    assert!(result.success);
+    assert_eq!(result.leftover_gas, 4000);
+    assert_eq!(result.returndata, words(&u256ify(point4)?));
+
+    let input = words(&u256ify(["0x0", "0x3", s])?);
+    let result = run_precompile(7, 10_000, &input)?;
+    assert!(!result.success);
+    assert_eq!(result.leftover_gas, 0);
+    Ok(())
+}
+
+const BLAKE2B_IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// The BLAKE2F input which compresses the single, final block of BLAKE2b-512("abc").
+fn blake2_f_abc_input(rounds: u32) -> Vec<u8> {
+    let mut input = rounds.to_be_bytes().to_vec();
+    let mut h = BLAKE2B_IV;
+    h[0] ^= 0x01010040;
+    input.extend(h.iter().flat_map(|x| x.to_le_bytes()));
+    let mut m = [0u8; 128];
+    m[..3].copy_from_slice(b"abc");
+    input.extend(m);
+    input.extend(3u64.to_le_bytes());
+    input.extend(0u64.to_le_bytes());
+    input.push(1);
+    input
+}
+
+#[test]
+fn test_blake2_f() -> Result<()> {
+    let input = blake2_f_abc_input(12);
+    let result = run_precompile(9, 100, &input)?;
+    assert!(result.success);
+    assert_eq!(result.leftover_gas, 88);
+    assert_eq!(result.returndata, Blake2b512::digest(b"abc").to_vec());
+
+    // With no rounds, h'_i = h_i ^ v_i ^ v_(i+8) = v_(i+8).
+    let input = blake2_f_abc_input(0);
+    let result = run_precompile(9, 100, &input)?;
+    assert!(result.success);
+    assert_eq!(result.leftover_gas, 100);
+    let mut expected = BLAKE2B_IV;
+    expected[4] ^= 3;
+    expected[6] ^= u64::MAX;
+    let expected: Vec<u8> = expected.iter().flat_map(|x| x.to_le_bytes()).collect();
+    assert_eq!(result.returndata, expected);
+
+    // The input must be exactly 213 bytes long.
+    let result = run_precompile(9, 100, &input[..212])?;
+    assert!(!result.success);
+
+    // The final block indicator must be 0 or 1.
+    let mut invalid_input = blake2_f_abc_input(12);
+    invalid_input[212] = 2;
+    let result = run_precompile(9, 100, &invalid_input)?;
+    assert!(!result.success);
+    assert_eq!(result.leftover_gas, 0);
+
+    // Not enough gas for the rounds.
+    let result = run_precompile(9, 11, &blake2_f_abc_input(12))?;
+    assert!(!result.success);
+    Ok(())
+}
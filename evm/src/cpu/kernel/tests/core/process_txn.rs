@@ -0,0 +1,32 @@
+use anyhow::Result;
+use ethereum_types::U256;
+
+use crate::cpu::kernel::aggregator::KERNEL;
+use crate::cpu::kernel::constants::global_metadata::GlobalMetadata;
+use crate::cpu::kernel::constants::txn_fields::NormalizedTxnField;
+use crate::cpu::kernel::interpreter::Interpreter;
+
+#[test]
+fn test_computed_fee_per_gas() -> Result<()> {
+    let process_normalized_txn = KERNEL.global_labels["process_normalized_txn"];
+    let intrinsic_gas = KERNEL.global_labels["intrinsic_gas"];
+
+    // The fee is the base fee plus the priority fee, capped at the max fee.
+    for (max_priority_fee, max_fee, expected_fee) in [(5, 100, 15), (5, 12, 12), (0, 10, 10)] {
+        let mut interpreter =
+            Interpreter::new_with_kernel(process_normalized_txn, vec![0xdeadbeefu32.into()]);
+        interpreter.halt_offsets.push(intrinsic_gas);
+        interpreter.set_global_metadata_field(GlobalMetadata::BlockBaseFee, 10.into());
+        interpreter.set_txn_field(
+            NormalizedTxnField::MaxPriorityFeePerGas,
+            max_priority_fee.into(),
+        );
+        interpreter.set_txn_field(NormalizedTxnField::MaxFeePerGas, max_fee.into());
+        interpreter.run()?;
+        assert_eq!(
+            interpreter.get_txn_field(NormalizedTxnField::ComputedFeePerGas),
+            U256::from(expected_fee)
+        );
+    }
+    Ok(())
+}
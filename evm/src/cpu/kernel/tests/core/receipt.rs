@@ -0,0 +1,255 @@
+use anyhow::Result;
+use eth_trie_utils::partial_trie::{Nibbles, PartialTrie};
+use ethereum_types::{Address, BigEndianHash, Bloom, BloomInput, H256, U256};
+
+use crate::cpu::kernel::aggregator::KERNEL;
+use crate::cpu::kernel::constants::context_metadata::ContextMetadata;
+use crate::cpu::kernel::constants::global_metadata::GlobalMetadata;
+use crate::cpu::kernel::constants::txn_fields::NormalizedTxnField;
+use crate::cpu::kernel::interpreter::Interpreter;
+use crate::generation::mpt::{
+    all_mpt_prover_inputs_reversed, LegacyReceiptRlp, LogRlp, ReceiptRlp,
+};
+use crate::generation::TrieInputs;
+use crate::memory::segments::Segment;
+use crate::witness::memory::MemoryAddress;
+
+/// The kexit_info of our syscalls, which return to the halt offset in kernel mode.
+const KEXIT_INFO: u64 = 0xDEADBEEF | (1 << 32);
+
+fn receipt_key(txn_number: u64) -> Nibbles {
+    Nibbles::from_bytes_be(&rlp::encode(&txn_number)).unwrap()
+}
+
+fn test_receipt(txn_type: u8, logs: usize) -> ReceiptRlp {
+    let logs = (0..logs)
+        .map(|i| LogRlp {
+            address: Address::from_low_u64_be(0x1234 + i as u64),
+            topics: (0..i).map(|j| H256::from_low_u64_be(j as u64)).collect(),
+            data: vec![0xab; 60 * i].into(),
+        })
+        .collect::<Vec<_>>();
+    let mut bloom = Bloom::zero();
+    for log in &logs {
+        bloom.accrue(BloomInput::Raw(log.address.as_bytes()));
+        for topic in &log.topics {
+            bloom.accrue(BloomInput::Raw(topic.as_bytes()));
+        }
+    }
+    ReceiptRlp {
+        txn_type,
+        receipt: LegacyReceiptRlp {
+            status: true,
+            cum_gas_used: 21_000.into(),
+            bloom,
+            logs,
+        },
+    }
+}
+
+/// Loads the given receipt trie into an interpreter, and checks that the kernel hashes it
+/// correctly.
+fn hash_receipt_trie(interpreter: &mut Interpreter, expected_trie: &PartialTrie) -> Result<()> {
+    interpreter.generation_state.registers.program_counter =
+        KERNEL.global_labels["mpt_hash_receipt_trie"];
+    interpreter.push(0xDEADBEEFu32.into());
+    interpreter.run()?;
+    assert_eq!(interpreter.stack().len(), 1);
+    let hash = H256::from_uint(&interpreter.stack()[0]);
+    assert_eq!(hash, expected_trie.calc_hash());
+    Ok(())
+}
+
+fn load_receipt_trie(receipts_trie: PartialTrie) -> Result<Interpreter<'static>> {
+    let load_all_mpts = KERNEL.global_labels["load_all_mpts"];
+    let trie_inputs = TrieInputs {
+        receipts_trie,
+        ..Default::default()
+    };
+    let mut interpreter = Interpreter::new_with_kernel(load_all_mpts, vec![0xDEADBEEFu32.into()]);
+    interpreter.generation_state.mpt_prover_inputs = all_mpt_prover_inputs_reversed(&trie_inputs);
+    interpreter.run()?;
+    assert_eq!(interpreter.stack(), vec![]);
+    Ok(interpreter)
+}
+
+#[test]
+fn test_hash_receipt_trie() -> Result<()> {
+    let mut receipts_trie = PartialTrie::Empty;
+    receipts_trie.insert(receipt_key(0), test_receipt(0, 0).encode());
+    receipts_trie.insert(receipt_key(1), test_receipt(2, 3).encode());
+    receipts_trie.insert(receipt_key(200), test_receipt(0, 2).encode());
+
+    let mut interpreter = load_receipt_trie(receipts_trie.clone())?;
+    hash_receipt_trie(&mut interpreter, &receipts_trie)
+}
+
+/// Emits the given logs, then builds the receipt of a transaction with the given status and
+/// number, and checks the resulting receipt trie. The logs, including the memory expansion they
+/// cause, should cost `expected_logs_gas`.
+fn test_process_receipt(
+    logs: &[LogRlp],
+    status: bool,
+    txn_number: u64,
+    expected_logs_gas: u64,
+) -> Result<()> {
+    let mut receipts_trie = PartialTrie::Empty;
+    receipts_trie.insert(receipt_key(txn_number - 1), test_receipt(0, 1).encode());
+    let mut interpreter = load_receipt_trie(receipts_trie.clone())?;
+
+    let mut logs_gas = 0;
+    for log in logs {
+        let data_offset = 100;
+        for (i, &byte) in log.data.iter().enumerate() {
+            interpreter.generation_state.memory.set(
+                MemoryAddress::new(0, Segment::MainMemory, data_offset + i),
+                byte.into(),
+            );
+        }
+        interpreter.set_context_metadata_field(
+            0,
+            ContextMetadata::Address,
+            U256::from_big_endian(log.address.as_bytes()),
+        );
+        interpreter.generation_state.registers.program_counter =
+            KERNEL.global_labels[&format!("sys_log{}", log.topics.len())];
+        for topic in log.topics.iter().rev() {
+            interpreter.push(topic.into_uint());
+        }
+        interpreter.push(log.data.len().into());
+        interpreter.push(data_offset.into());
+        interpreter.push(KEXIT_INFO.into());
+        interpreter.run()?;
+        assert_eq!(interpreter.stack(), vec![]);
+        // Our kexit_info starts out with no gas used, so this is the cost of the LOG alone.
+        logs_gas += interpreter.generation_state.registers.gas_used;
+    }
+    assert_eq!(logs_gas, expected_logs_gas);
+
+    // The transaction's intrinsic gas, plus its logs.
+    let txn_gas_used = 21_000 + logs_gas;
+    let gas_limit = 50_000;
+    let leftover_gas = gas_limit - txn_gas_used;
+    let gas_used_before = 100_000;
+    interpreter.set_txn_field(NormalizedTxnField::GasLimit, gas_limit.into());
+    interpreter.set_global_metadata_field(GlobalMetadata::TxnNumber, txn_number.into());
+    interpreter.set_global_metadata_field(GlobalMetadata::BlockGasUsed, gas_used_before.into());
+    interpreter.generation_state.registers.program_counter =
+        KERNEL.global_labels["process_receipt"];
+    interpreter.push(0xDEADBEEFu32.into());
+    interpreter.push(leftover_gas.into());
+    interpreter.push((status as u8).into());
+    interpreter.run()?;
+    assert_eq!(interpreter.stack(), vec![]);
+
+    let cum_gas_used = gas_used_before + 21_000 + expected_logs_gas;
+    let kept_logs = if status { logs.to_vec() } else { vec![] };
+    let mut bloom = Bloom::zero();
+    for log in &kept_logs {
+        bloom.accrue(BloomInput::Raw(log.address.as_bytes()));
+        for topic in &log.topics {
+            bloom.accrue(BloomInput::Raw(topic.as_bytes()));
+        }
+    }
+    let receipt = ReceiptRlp {
+        txn_type: 0,
+        receipt: LegacyReceiptRlp {
+            status,
+            cum_gas_used: cum_gas_used.into(),
+            bloom,
+            logs: kept_logs,
+        },
+    };
+    receipts_trie.insert(receipt_key(txn_number), receipt.encode());
+
+    let read_metadata = |field| {
+        interpreter.generation_state.memory.mload_general(
+            0,
+            Segment::GlobalMetadata,
+            field as usize,
+        )
+    };
+    assert_eq!(
+        read_metadata(GlobalMetadata::TxnNumber),
+        (txn_number + 1).into()
+    );
+    assert_eq!(
+        read_metadata(GlobalMetadata::BlockGasUsed),
+        cum_gas_used.into()
+    );
+    assert_eq!(read_metadata(GlobalMetadata::LogsLen), U256::zero());
+    assert_eq!(read_metadata(GlobalMetadata::LogsDataLen), U256::zero());
+
+    hash_receipt_trie(&mut interpreter, &receipts_trie)
+}
+
+fn test_logs() -> Vec<LogRlp> {
+    vec![
+        LogRlp {
+            address: Address::from_low_u64_be(0xabcd),
+            topics: vec![],
+            data: vec![].into(),
+        },
+        LogRlp {
+            address: Address::from_low_u64_be(0xabcd),
+            topics: vec![H256::random(), H256::random()],
+            data: vec![0x42].into(),
+        },
+        LogRlp {
+            address: Address::random(),
+            topics: vec![H256::random(); 4],
+            data: vec![0x99; 100].into(),
+        },
+    ]
+}
+
+// The logs of `test_logs()`, all at offset 100. The first one has no data, so it doesn't expand
+// memory. The second one expands it to 4 words, costing 12 gas, and the third one to 7 words,
+// costing 21 gas in total.
+const TEST_LOGS_GAS: u64 = 375 + (375 + 2 * 375 + 8 + 12) + (375 + 4 * 375 + 100 * 8 + 9);
+// The last log of `test_logs()` alone, which expands memory from 0 to 7 words.
+const LAST_TEST_LOG_GAS: u64 = 375 + 4 * 375 + 100 * 8 + 21;
+
+#[test]
+fn test_process_receipt_with_logs() -> Result<()> {
+    test_process_receipt(&test_logs(), true, 1, TEST_LOGS_GAS)?;
+    test_process_receipt(&test_logs()[2..], true, 300, LAST_TEST_LOG_GAS)
+}
+
+#[test]
+fn test_process_receipt_failed_txn() -> Result<()> {
+    test_process_receipt(&test_logs(), false, 128, TEST_LOGS_GAS)
+}
+
+#[test]
+fn test_log_with_out_of_bounds_offset() -> Result<()> {
+    let fault_exception = KERNEL.global_labels["fault_exception"];
+    let mut interpreter = Interpreter::new_with_kernel(
+        KERNEL.global_labels["sys_log0"],
+        vec![1.into(), (1u64 << 32).into(), KEXIT_INFO.into()],
+    );
+    interpreter.halt_offsets.push(fault_exception);
+    interpreter.run()?;
+    assert_eq!(
+        interpreter.generation_state.registers.program_counter,
+        fault_exception
+    );
+    Ok(())
+}
+
+#[test]
+fn test_log_in_static_context() -> Result<()> {
+    let fault_exception = KERNEL.global_labels["fault_exception"];
+    let mut interpreter = Interpreter::new_with_kernel(
+        KERNEL.global_labels["sys_log0"],
+        vec![0.into(), 0.into(), KEXIT_INFO.into()],
+    );
+    interpreter.halt_offsets.push(fault_exception);
+    interpreter.set_context_metadata_field(0, ContextMetadata::Static, U256::one());
+    interpreter.run()?;
+    assert_eq!(
+        interpreter.generation_state.registers.program_counter,
+        fault_exception
+    );
+    Ok(())
+}
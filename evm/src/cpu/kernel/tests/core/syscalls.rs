@@ -0,0 +1,381 @@
+use anyhow::Result;
+use ethereum_types::U256;
+
+use crate::cpu::kernel::aggregator::KERNEL;
+use crate::cpu::kernel::constants::context_metadata::ContextMetadata;
+use crate::cpu::kernel::constants::global_metadata::GlobalMetadata;
+use crate::cpu::kernel::constants::txn_fields::NormalizedTxnField;
+use crate::cpu::kernel::interpreter::Interpreter;
+use crate::cpu::kernel::opcodes::{get_opcode, get_push_opcode};
+use crate::memory::segments::Segment;
+use crate::witness::memory::MemoryAddress;
+
+const GAS_BASE: u64 = 2;
+const GAS_VERYLOW: u64 = 3;
+const GAS_BLOCKHASH: u64 = 20;
+
+fn syscall_interpreter(label: &str, args: Vec<U256>) -> Interpreter<'static> {
+    let syscall = KERNEL.global_labels[label];
+    // The kexit_info is on top of the stack, followed by the syscall arguments in order.
+    let mut initial_stack = args;
+    initial_stack.reverse();
+    initial_stack.push(0xDEADBEEFu32.into());
+    Interpreter::new_with_kernel(syscall, initial_stack)
+}
+
+fn set_memory_bytes(interpreter: &mut Interpreter, segment: Segment, bytes: &[u8]) {
+    for (i, &byte) in bytes.iter().enumerate() {
+        interpreter
+            .generation_state
+            .memory
+            .set(MemoryAddress::new(0, segment, i), byte.into());
+    }
+}
+
+fn main_memory_bytes(interpreter: &Interpreter, len: usize) -> Vec<u8> {
+    (0..len)
+        .map(|i| {
+            interpreter
+                .generation_state
+                .memory
+                .mload_general(0, Segment::MainMemory, i)
+                .as_u32() as u8
+        })
+        .collect()
+}
+
+#[test]
+fn test_context_metadata_syscalls() -> Result<()> {
+    let cases = [
+        ("sys_address", ContextMetadata::Address),
+        ("sys_caller", ContextMetadata::Caller),
+        ("sys_callvalue", ContextMetadata::CallValue),
+        ("sys_calldatasize", ContextMetadata::CalldataSize),
+        ("sys_codesize", ContextMetadata::CodeSize),
+        ("sys_returndatasize", ContextMetadata::ReturndataSize),
+        ("sys_msize", ContextMetadata::MSize),
+    ];
+    for (label, field) in cases {
+        let value = U256::from(0x1234 + field as usize);
+        let mut interpreter = syscall_interpreter(label, vec![]);
+        interpreter.set_context_metadata_field(0, field, value);
+        interpreter.run()?;
+        assert_eq!(interpreter.stack(), vec![value], "{label}");
+        assert_eq!(interpreter.generation_state.registers.gas_used, GAS_BASE);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_txn_field_syscalls() -> Result<()> {
+    let cases = [
+        ("sys_origin", NormalizedTxnField::Origin),
+        ("sys_gasprice", NormalizedTxnField::ComputedFeePerGas),
+    ];
+    for (label, field) in cases {
+        let value = U256::from(0xabcd + field as usize);
+        let mut interpreter = syscall_interpreter(label, vec![]);
+        interpreter.set_txn_field(field, value);
+        interpreter.run()?;
+        assert_eq!(interpreter.stack(), vec![value], "{label}");
+        assert_eq!(interpreter.generation_state.registers.gas_used, GAS_BASE);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_block_syscalls() -> Result<()> {
+    let cases = [
+        ("sys_coinbase", GlobalMetadata::BlockBeneficiary),
+        ("sys_timestamp", GlobalMetadata::BlockTimestamp),
+        ("sys_number", GlobalMetadata::BlockNumber),
+        ("sys_prevrandao", GlobalMetadata::BlockDifficulty),
+        ("sys_gaslimit", GlobalMetadata::BlockGasLimit),
+        ("sys_chainid", GlobalMetadata::BlockChainId),
+        ("sys_basefee", GlobalMetadata::BlockBaseFee),
+    ];
+    for (label, field) in cases {
+        let value = U256::from(0x5678 + field as usize);
+        let mut interpreter = syscall_interpreter(label, vec![]);
+        interpreter.set_global_metadata_field(field, value);
+        interpreter.run()?;
+        assert_eq!(interpreter.stack(), vec![value], "{label}");
+        assert_eq!(interpreter.generation_state.registers.gas_used, GAS_BASE);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_gas() -> Result<()> {
+    let sys_gas = KERNEL.global_labels["sys_gas"];
+    let gas_used_before = 1000u64;
+    let kexit_info = U256::from(0xDEADBEEFu32) + (U256::from(gas_used_before) << 192);
+    let mut interpreter = Interpreter::new_with_kernel(sys_gas, vec![kexit_info]);
+    interpreter.set_context_metadata_field(0, ContextMetadata::GasLimit, 50_000.into());
+    interpreter.run()?;
+
+    let gas_used = gas_used_before + GAS_BASE;
+    assert_eq!(interpreter.stack(), vec![(50_000 - gas_used).into()]);
+    assert_eq!(interpreter.generation_state.registers.gas_used, gas_used);
+    Ok(())
+}
+
+#[test]
+fn test_calldataload() -> Result<()> {
+    let calldata: Vec<u8> = (1..=40).collect();
+
+    // A word entirely inside the calldata.
+    let mut interpreter = syscall_interpreter("sys_calldataload", vec![4.into()]);
+    set_memory_bytes(&mut interpreter, Segment::Calldata, &calldata);
+    interpreter.set_context_metadata_field(0, ContextMetadata::CalldataSize, 40.into());
+    interpreter.run()?;
+    assert_eq!(
+        interpreter.stack(),
+        vec![U256::from_big_endian(&calldata[4..36])]
+    );
+    assert_eq!(interpreter.generation_state.registers.gas_used, GAS_VERYLOW);
+
+    // A word running past the end of the calldata is padded with zeros.
+    let mut interpreter = syscall_interpreter("sys_calldataload", vec![20.into()]);
+    set_memory_bytes(&mut interpreter, Segment::Calldata, &calldata);
+    interpreter.set_context_metadata_field(0, ContextMetadata::CalldataSize, 40.into());
+    interpreter.run()?;
+    let mut expected = [0u8; 32];
+    expected[..20].copy_from_slice(&calldata[20..]);
+    assert_eq!(interpreter.stack(), vec![U256::from_big_endian(&expected)]);
+
+    // An offset past the end of the calldata, including a huge one, reads zero.
+    for offset in [U256::from(40), U256::MAX] {
+        let mut interpreter = syscall_interpreter("sys_calldataload", vec![offset]);
+        set_memory_bytes(&mut interpreter, Segment::Calldata, &calldata);
+        interpreter.set_context_metadata_field(0, ContextMetadata::CalldataSize, 40.into());
+        interpreter.run()?;
+        assert_eq!(interpreter.stack(), vec![U256::zero()]);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_calldatacopy() -> Result<()> {
+    let calldata = [0xaa, 0xbb, 0xcc, 0xdd];
+    let (dest_offset, offset, size) = (3, 2, 5);
+    let mut interpreter = syscall_interpreter(
+        "sys_calldatacopy",
+        vec![dest_offset.into(), offset.into(), size.into()],
+    );
+    set_memory_bytes(&mut interpreter, Segment::Calldata, &calldata);
+    interpreter.set_context_metadata_field(0, ContextMetadata::CalldataSize, 4.into());
+    interpreter.run()?;
+
+    assert_eq!(interpreter.stack(), vec![]);
+    assert_eq!(
+        main_memory_bytes(&interpreter, 8),
+        vec![0, 0, 0, 0xcc, 0xdd, 0, 0, 0]
+    );
+    // The static cost, plus 3 gas for the single copied word, plus 3 gas to expand memory by a word.
+    assert_eq!(interpreter.generation_state.registers.gas_used, 9);
+    Ok(())
+}
+
+#[test]
+fn test_copy_memory_expansion() -> Result<()> {
+    let run_codecopy = |msize: usize, dest_offset: usize, size: usize| -> Result<(U256, u64)> {
+        let mut interpreter = syscall_interpreter(
+            "sys_codecopy",
+            vec![dest_offset.into(), 0.into(), size.into()],
+        );
+        interpreter.set_context_metadata_field(0, ContextMetadata::MSize, msize.into());
+        interpreter.run()?;
+        let msize = interpreter.get_context_metadata_field(0, ContextMetadata::MSize);
+        Ok((msize, interpreter.generation_state.registers.gas_used))
+    };
+    let copy_gas = |size: u64| GAS_VERYLOW + 3 * ((size + 31) / 32);
+    let memory_cost = |num_words: u64| 3 * num_words + num_words * num_words / 512;
+
+    // Copying zero bytes never expands memory, whatever the offset.
+    assert_eq!(run_codecopy(0, 1 << 20, 0)?, (0.into(), copy_gas(0)));
+    // Memory grows to the first multiple of 32 bytes covering the copied range.
+    assert_eq!(
+        run_codecopy(0, 40, 30)?,
+        (96.into(), copy_gas(30) + memory_cost(3))
+    );
+    // Only the growth beyond the current size is charged.
+    assert_eq!(
+        run_codecopy(64, 1000, 24)?,
+        (1024.into(), copy_gas(24) + memory_cost(32) - memory_cost(2))
+    );
+    assert_eq!(run_codecopy(1024, 0, 32)?, (1024.into(), copy_gas(32)));
+    Ok(())
+}
+
+#[test]
+fn test_codecopy() -> Result<()> {
+    // We run in the kernel context, so the copied code is a prefix of the kernel's code.
+    let mut interpreter = syscall_interpreter("sys_codecopy", vec![0.into(), 1.into(), 6.into()]);
+    interpreter.set_context_metadata_field(0, ContextMetadata::CodeSize, 5.into());
+    interpreter.run()?;
+
+    assert_eq!(interpreter.stack(), vec![]);
+    let mut expected = KERNEL.code[1..5].to_vec();
+    expected.extend([0, 0]);
+    assert_eq!(main_memory_bytes(&interpreter, 6), expected);
+    Ok(())
+}
+
+#[test]
+fn test_returndatacopy() -> Result<()> {
+    let returndata = [1, 2, 3, 4];
+    let mut interpreter =
+        syscall_interpreter("sys_returndatacopy", vec![0.into(), 1.into(), 3.into()]);
+    set_memory_bytes(&mut interpreter, Segment::Returndata, &returndata);
+    interpreter.set_context_metadata_field(0, ContextMetadata::ReturndataSize, 4.into());
+    interpreter.run()?;
+    assert_eq!(interpreter.stack(), vec![]);
+    assert_eq!(main_memory_bytes(&interpreter, 3), vec![2, 3, 4]);
+
+    // Reading past the end of the returndata is an exceptional halt.
+    let fault_exception = KERNEL.global_labels["fault_exception"];
+    for (offset, size) in [(1, 4), (5, 0), (2, usize::MAX)] {
+        let mut interpreter = syscall_interpreter(
+            "sys_returndatacopy",
+            vec![0.into(), offset.into(), size.into()],
+        );
+        interpreter.halt_offsets.push(fault_exception);
+        interpreter.set_context_metadata_field(0, ContextMetadata::ReturndataSize, 4.into());
+        interpreter.run()?;
+        assert_eq!(
+            interpreter.generation_state.registers.program_counter,
+            fault_exception
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_blockhash() -> Result<()> {
+    let current_block = 1000u64;
+    let run_blockhash = |block_number: u64| -> Result<Vec<U256>> {
+        let mut interpreter = syscall_interpreter("sys_blockhash", vec![block_number.into()]);
+        interpreter.set_global_metadata_field(GlobalMetadata::BlockNumber, current_block.into());
+        for i in 0..256 {
+            // The hash of block `current_block - 256 + i` is stored at index `i`.
+            let hash = U256::from(current_block - 256 + i as u64) + 0xabc;
+            interpreter
+                .generation_state
+                .memory
+                .set(MemoryAddress::new(0, Segment::BlockHashes, i), hash);
+        }
+        interpreter.run()?;
+        assert_eq!(
+            interpreter.generation_state.registers.gas_used,
+            GAS_BLOCKHASH
+        );
+        Ok(interpreter.stack().to_vec())
+    };
+
+    assert_eq!(run_blockhash(999)?, vec![U256::from(999 + 0xabc)]);
+    assert_eq!(run_blockhash(744)?, vec![U256::from(744 + 0xabc)]);
+    // The current block, future blocks, and blocks older than 256 have no hash available.
+    assert_eq!(run_blockhash(1000)?, vec![U256::zero()]);
+    assert_eq!(run_blockhash(2000)?, vec![U256::zero()]);
+    assert_eq!(run_blockhash(743)?, vec![U256::zero()]);
+    Ok(())
+}
+
+#[test]
+fn test_user_code_traps_into_syscalls() -> Result<()> {
+    const CONTEXT: usize = 1;
+    let (timestamp, number) = (U256::from(1234), U256::from(5678));
+
+    // `sys_timestamp` returns to the user code at offset 0, whose NUMBER must trap back into the
+    // kernel through the syscall jumptable.
+    let mut interpreter =
+        Interpreter::new_with_kernel(KERNEL.global_labels["sys_timestamp"], vec![]);
+    interpreter.set_code(CONTEXT, vec![get_opcode("NUMBER"), get_opcode("STOP")]);
+    interpreter
+        .halt_offsets
+        .push(KERNEL.global_labels["sys_stop"]);
+    interpreter.context = CONTEXT;
+    interpreter.generation_state.registers.context = CONTEXT;
+    interpreter.set_context_metadata_field(CONTEXT, ContextMetadata::GasLimit, 100.into());
+    interpreter.set_global_metadata_field(GlobalMetadata::BlockTimestamp, timestamp);
+    interpreter.set_global_metadata_field(GlobalMetadata::BlockNumber, number);
+    // A kexit_info for user mode, at offset 0, with no gas used yet.
+    interpreter.push(U256::zero());
+    interpreter.run()?;
+
+    // STOP traps into `sys_stop` too, with the user-mode syscall info of the instruction after it.
+    let stop_syscall_info = U256::from(2) + (U256::from(2 * GAS_BASE) << 192);
+    assert_eq!(
+        interpreter.stack(),
+        vec![timestamp, number, stop_syscall_info]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_user_calldata_opcodes_charge_kernel_gas() -> Result<()> {
+    const CONTEXT: usize = 1;
+    let calldata = [0xaa, 0xbb, 0xcc, 0xdd];
+    let callvalue = U256::from(777);
+
+    // `sys_callvalue` returns to the user code at offset 0, which runs the other calldata opcodes
+    // and CALLVALUE again. They must all trap into their syscalls, which charge their gas.
+    let mut interpreter =
+        Interpreter::new_with_kernel(KERNEL.global_labels["sys_callvalue"], vec![]);
+    let code = vec![
+        get_opcode("CALLDATASIZE"),
+        get_push_opcode(1),
+        1,
+        get_opcode("CALLDATALOAD"),
+        get_push_opcode(1),
+        4,
+        get_push_opcode(1),
+        0,
+        get_push_opcode(1),
+        0,
+        get_opcode("CALLDATACOPY"),
+        get_opcode("CALLVALUE"),
+        get_opcode("STOP"),
+    ];
+    let code_len = code.len();
+    interpreter.set_code(CONTEXT, code);
+    interpreter
+        .halt_offsets
+        .push(KERNEL.global_labels["sys_stop"]);
+    interpreter.context = CONTEXT;
+    interpreter.generation_state.registers.context = CONTEXT;
+    interpreter.set_context_metadata_field(CONTEXT, ContextMetadata::GasLimit, 100.into());
+    interpreter.set_context_metadata_field(CONTEXT, ContextMetadata::CallValue, callvalue);
+    interpreter.set_context_metadata_field(CONTEXT, ContextMetadata::CalldataSize, 4.into());
+    for (i, &byte) in calldata.iter().enumerate() {
+        interpreter.generation_state.memory.set(
+            MemoryAddress::new(CONTEXT, Segment::Calldata, i),
+            byte.into(),
+        );
+    }
+    // A kexit_info for user mode, at offset 0, with no gas used yet.
+    interpreter.push(U256::zero());
+    interpreter.run()?;
+
+    // CALLVALUE and CALLDATASIZE cost `GAS_BASE`, CALLDATALOAD `GAS_VERYLOW`, and copying a word
+    // of calldata costs `GAS_VERYLOW`, 3 for the word, and 3 to expand memory by a word.
+    let gas_used = 2 * GAS_BASE + GAS_BASE + GAS_VERYLOW + (GAS_VERYLOW + 3 + 3);
+    let stop_syscall_info = U256::from(code_len) + (U256::from(gas_used) << 192);
+    let word = U256::from_big_endian(&[0xbb, 0xcc, 0xdd]) << (29 * 8);
+    assert_eq!(
+        interpreter.stack(),
+        vec![callvalue, 4.into(), word, callvalue, stop_syscall_info]
+    );
+    let copied = (0..4)
+        .map(|i| {
+            interpreter
+                .generation_state
+                .memory
+                .mload_general(CONTEXT, Segment::MainMemory, i)
+                .as_u32() as u8
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(copied, calldata);
+    Ok(())
+}
@@ -0,0 +1,177 @@
+use anyhow::Result;
+use ethereum_types::U256;
+use rand::{thread_rng, Rng};
+
+use crate::cpu::kernel::aggregator::KERNEL;
+use crate::cpu::kernel::interpreter::{run, run_interpreter};
+
+/// Some values where signed operations are likely to go wrong.
+fn interesting_values() -> Vec<U256> {
+    let sign_bit = U256::one() << 255;
+    vec![
+        U256::zero(),
+        U256::one(),
+        U256::from(2),
+        U256::from(7),
+        U256::from(0x80),
+        U256::from(0xff),
+        U256::from(0x7fff),
+        U256::from(255),
+        U256::from(256),
+        sign_bit,
+        sign_bit - 1,
+        sign_bit + 1,
+        U256::MAX,
+        U256::MAX - 1,
+        U256::MAX - 6,
+    ]
+}
+
+/// Runs the syscall handler of the given signed opcode, and checks that it agrees with the
+/// interpreter's implementation of the opcode, and that it charges `gas`.
+fn test_signed_opcode(opcode: u8, label: &str, gas: u64) -> Result<()> {
+    let sys_label = KERNEL.global_labels[label];
+    let mut rng = thread_rng();
+    let mut inputs = interesting_values();
+    inputs.extend((0..5).map(|_| U256(rng.gen())));
+
+    for &x in &inputs {
+        for &y in &inputs {
+            let kexit_info = 0xDEADBEEFu32.into();
+            let interpreter = run_interpreter(sys_label, vec![y, x, kexit_info])?;
+            let stack_with_kernel = interpreter.stack().to_vec();
+            assert_eq!(interpreter.generation_state.registers.gas_used, gas);
+
+            let code = [opcode, 0x63, 0xde, 0xad, 0xbe, 0xef, 0x56]; // OPCODE, PUSH4 deadbeef, JUMP
+            let stack_with_opcode = run(&code, 0, vec![y, x], &KERNEL.prover_inputs)?
+                .stack()
+                .to_vec();
+            assert_eq!(
+                stack_with_kernel, stack_with_opcode,
+                "{label} disagrees with the opcode for inputs {x:#x}, {y:#x}"
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Runs the given signed syscall on `x, y`, and returns its output.
+fn run_signed_syscall(label: &str, x: U256, y: U256) -> Result<U256> {
+    let sys_label = KERNEL.global_labels[label];
+    let kexit_info = 0xDEADBEEFu32.into();
+    let stack = run_interpreter(sys_label, vec![y, x, kexit_info])?
+        .stack()
+        .to_vec();
+    assert_eq!(stack.len(), 1);
+    Ok(stack[0])
+}
+
+fn minus(x: u64) -> U256 {
+    (!U256::from(x)).overflowing_add(U256::one()).0
+}
+
+#[test]
+fn test_sdiv() -> Result<()> {
+    assert_eq!(
+        run_signed_syscall("sys_sdiv", minus(7), 2.into())?,
+        minus(3)
+    );
+    assert_eq!(
+        run_signed_syscall("sys_sdiv", 7.into(), minus(2))?,
+        minus(3)
+    );
+    assert_eq!(
+        run_signed_syscall("sys_sdiv", minus(7), minus(2))?,
+        3.into()
+    );
+    // -2^255 / -1 overflows back to -2^255.
+    let min = U256::one() << 255;
+    assert_eq!(run_signed_syscall("sys_sdiv", min, U256::MAX)?, min);
+    assert_eq!(
+        run_signed_syscall("sys_sdiv", minus(7), U256::zero())?,
+        U256::zero()
+    );
+    test_signed_opcode(0x05, "sys_sdiv", 5)
+}
+
+#[test]
+fn test_smod() -> Result<()> {
+    assert_eq!(
+        run_signed_syscall("sys_smod", minus(7), 2.into())?,
+        minus(1)
+    );
+    assert_eq!(
+        run_signed_syscall("sys_smod", 7.into(), minus(2))?,
+        1.into()
+    );
+    assert_eq!(
+        run_signed_syscall("sys_smod", minus(8), minus(3))?,
+        minus(2)
+    );
+    assert_eq!(
+        run_signed_syscall("sys_smod", minus(7), U256::zero())?,
+        U256::zero()
+    );
+    test_signed_opcode(0x07, "sys_smod", 5)
+}
+
+#[test]
+fn test_signextend() -> Result<()> {
+    assert_eq!(
+        run_signed_syscall("sys_signextend", 0.into(), 0xff.into())?,
+        U256::MAX
+    );
+    assert_eq!(
+        run_signed_syscall("sys_signextend", 0.into(), 0x17f.into())?,
+        0x7f.into()
+    );
+    assert_eq!(
+        run_signed_syscall("sys_signextend", 1.into(), 0x8000.into())?,
+        minus(0x8000)
+    );
+    assert_eq!(
+        run_signed_syscall("sys_signextend", 31.into(), 0x8000.into())?,
+        0x8000.into()
+    );
+    test_signed_opcode(0x0b, "sys_signextend", 5)
+}
+
+#[test]
+fn test_slt() -> Result<()> {
+    assert_eq!(
+        run_signed_syscall("sys_slt", minus(1), 0.into())?,
+        U256::one()
+    );
+    assert_eq!(
+        run_signed_syscall("sys_slt", 0.into(), minus(1))?,
+        U256::zero()
+    );
+    test_signed_opcode(0x12, "sys_slt", 3)
+}
+
+#[test]
+fn test_sgt() -> Result<()> {
+    assert_eq!(
+        run_signed_syscall("sys_sgt", 0.into(), minus(1))?,
+        U256::one()
+    );
+    assert_eq!(
+        run_signed_syscall("sys_sgt", minus(2), minus(1))?,
+        U256::zero()
+    );
+    test_signed_opcode(0x13, "sys_sgt", 3)
+}
+
+#[test]
+fn test_sar() -> Result<()> {
+    assert_eq!(run_signed_syscall("sys_sar", 1.into(), minus(4))?, minus(2));
+    assert_eq!(
+        run_signed_syscall("sys_sar", 300.into(), minus(4))?,
+        U256::MAX
+    );
+    assert_eq!(
+        run_signed_syscall("sys_sar", 4.into(), 0x100.into())?,
+        0x10.into()
+    );
+    test_signed_opcode(0x1d, "sys_sar", 3)
+}
@@ -29,7 +29,7 @@ const BASIC_BINARY_OP: Option<StackBehavior> = Some(StackBehavior {
     disable_other_channels: true,
 });
 const BASIC_TERNARY_OP: Option<StackBehavior> = Some(StackBehavior {
-    num_pops: 2,
+    num_pops: 3,
     pushes: true,
     disable_other_channels: true,
 });
@@ -60,11 +60,32 @@ const STACK_BEHAVIORS: OpsColumnsView<Option<StackBehavior>> = OpsColumnsView {
     xor: BASIC_BINARY_OP,
     not: BASIC_UNARY_OP,
     byte: BASIC_BINARY_OP,
-    shl: BASIC_BINARY_OP,
-    shr: BASIC_BINARY_OP,
-    keccak_general: None, // TODO
-    prover_input: None,   // TODO
-    pop: None,            // TODO
+    shl: Some(StackBehavior {
+        num_pops: 2,
+        pushes: true,
+        disable_other_channels: false,
+    }),
+    shr: Some(StackBehavior {
+        num_pops: 2,
+        pushes: true,
+        disable_other_channels: false,
+    }),
+    keccak_general: Some(StackBehavior {
+        num_pops: 4,
+        pushes: true,
+        disable_other_channels: true,
+    }),
+    prover_input: Some(StackBehavior {
+        num_pops: 0,
+        pushes: true,
+        disable_other_channels: true,
+    }),
+    // POP discards the top of the stack without reading it.
+    pop: Some(StackBehavior {
+        num_pops: 0,
+        pushes: false,
+        disable_other_channels: true,
+    }),
     jump: Some(StackBehavior {
         num_pops: 1,
         pushes: false,
@@ -85,7 +106,12 @@ const STACK_BEHAVIORS: OpsColumnsView<Option<StackBehavior>> = OpsColumnsView {
         pushes: false,
         disable_other_channels: true,
     }),
-    push: None, // TODO
+    // PUSH reads its immediate value from the code directly, not through a memory channel.
+    push: Some(StackBehavior {
+        num_pops: 0,
+        pushes: true,
+        disable_other_channels: true,
+    }),
     dup: None,
     swap: None,
     get_context: Some(StackBehavior {
@@ -114,6 +140,11 @@ const STACK_BEHAVIORS: OpsColumnsView<Option<StackBehavior>> = OpsColumnsView {
         pushes: true,
         disable_other_channels: false,
     }),
+    exception: Some(StackBehavior {
+        num_pops: 0,
+        pushes: true,
+        disable_other_channels: false,
+    }),
 };
 
 fn eval_packed_one<P: PackedField>(
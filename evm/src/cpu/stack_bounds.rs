@@ -4,11 +4,10 @@
 //! occur. For example, if `is_add` is set but an addition would underflow, these constraints would
 //! make the proof unverifiable.
 //!
-//! Faults are handled under a separate operation flag, `is_exception` (this is still TODO), which
-//! traps to the kernel. The kernel then handles the exception. However, before it may do so, the
-//! kernel must verify in software that an exception did in fact occur (i.e. the trap was
-//! warranted) and `PANIC` otherwise; this prevents the prover from faking an exception on a valid
-//! operation.
+//! Faults are handled under a separate operation flag, `exception`, which traps to the kernel. The
+//! kernel then handles the exception. However, before it may do so, the kernel must verify in
+//! software that an exception did in fact occur (i.e. the trap was warranted) and `PANIC`
+//! otherwise; this prevents the prover from faking an exception on a valid operation.
 
 use plonky2::field::extension::Extendable;
 use plonky2::field::packed::PackedField;
@@ -31,8 +30,7 @@ const DECREMENTING_FLAGS: [usize; 1] = [COL_MAP.op.pop];
 // Operations that increase the stack length by 1, but excluding:
 //  - privileged (kernel-only) operations (superfluous; doesn't affect correctness),
 //  - operations that from userspace to the kernel (required for correctness).
-// TODO: This list is incomplete.
-const INCREMENTING_FLAGS: [usize; 2] = [COL_MAP.op.pc, COL_MAP.op.dup];
+const INCREMENTING_FLAGS: [usize; 3] = [COL_MAP.op.pc, COL_MAP.op.dup, COL_MAP.op.push];
 
 /// Calculates `lv.stack_len_bounds_aux`. Note that this must be run after decode.
 pub fn generate<F: Field>(lv: &mut CpuColumnsView<F>) {
@@ -1,6 +1,10 @@
 //! Handle instructions that are implemented in terms of system calls.
 //!
 //! These are usually the ones that are too complicated to implement in one CPU table row.
+//!
+//! Exceptions raised by user code trap to the kernel in the same way, except that the handler is
+//! looked up by exception code rather than by opcode. The kernel then verifies that the exception
+//! was warranted, and halts the current context.
 
 use plonky2::field::extension::Extendable;
 use plonky2::field::packed::PackedField;
@@ -24,12 +28,30 @@ pub fn eval_packed<P: PackedField>(
     nv: &CpuColumnsView<P>,
     yield_constr: &mut ConstraintConsumer<P>,
 ) {
-    let filter = lv.op.syscall;
+    let filter_syscall = lv.op.syscall;
+    let filter_exception = lv.op.exception;
+    let filter = filter_syscall + filter_exception;
+
+    // Exceptions are only raised by user code; the kernel is not supposed to fault.
+    yield_constr.constraint(filter_exception * lv.is_kernel_mode);
+
+    // The exception code, in {0, ..., 7}.
+    let exc_code_bits = lv.general.exception().exc_code_bits;
+    for bit in exc_code_bits {
+        yield_constr.constraint(filter_exception * bit * (bit - P::ONES));
+    }
+    let exc_code: P = exc_code_bits
+        .into_iter()
+        .enumerate()
+        .map(|(i, bit)| bit * P::Scalar::from_canonical_u64(1 << i))
+        .sum();
 
     // Look up the handler in memory
     let code_segment = P::Scalar::from_canonical_usize(Segment::Code as usize);
     let syscall_jumptable_start =
         P::Scalar::from_canonical_usize(KERNEL.global_labels["syscall_jumptable"]);
+    let exception_jumptable_start =
+        P::Scalar::from_canonical_usize(KERNEL.global_labels["exception_jumptable"]);
     let opcode: P = lv
         .opcode_bits
         .into_iter()
@@ -38,6 +60,8 @@ pub fn eval_packed<P: PackedField>(
         .sum();
     let opcode_handler_addr_start =
         syscall_jumptable_start + opcode * P::Scalar::from_canonical_usize(BYTES_PER_OFFSET);
+    let exc_handler_addr_start =
+        exception_jumptable_start + exc_code * P::Scalar::from_canonical_usize(BYTES_PER_OFFSET);
     for (i, channel) in lv.mem_channels[0..BYTES_PER_OFFSET].iter().enumerate() {
         yield_constr.constraint(filter * (channel.used - P::ONES));
         yield_constr.constraint(filter * (channel.is_read - P::ONES));
@@ -47,8 +71,10 @@ pub fn eval_packed<P: PackedField>(
         yield_constr.constraint(filter * (channel.addr_segment - code_segment));
 
         // Set address, using a separate channel for each of the `BYTES_PER_OFFSET` limbs.
-        let limb_address = opcode_handler_addr_start + P::Scalar::from_canonical_usize(i);
-        yield_constr.constraint(filter * (channel.addr_virtual - limb_address));
+        let limb_address_syscall = opcode_handler_addr_start + P::Scalar::from_canonical_usize(i);
+        let limb_address_exception = exc_handler_addr_start + P::Scalar::from_canonical_usize(i);
+        yield_constr.constraint(filter_syscall * (channel.addr_virtual - limb_address_syscall));
+        yield_constr.constraint(filter_exception * (channel.addr_virtual - limb_address_exception));
     }
 
     // Disable unused channels (the last channel is used to push to the stack)
@@ -74,15 +100,22 @@ pub fn eval_packed<P: PackedField>(
 
     // This memory channel is constrained in `stack.rs`.
     let output = lv.mem_channels[NUM_GP_CHANNELS - 1].value;
-    // Push to stack: current PC + 1 (limb 0), kernel flag (limb 1), gas counter (limbs 6 and 7).
-    yield_constr.constraint(filter * (output[0] - (lv.program_counter + P::ONES)));
-    yield_constr.constraint(filter * (output[1] - lv.is_kernel_mode));
+    // Push to stack for a syscall: current PC + 1 (limb 0), kernel flag (limb 1), gas counter
+    // (limbs 6 and 7).
+    yield_constr.constraint(filter_syscall * (output[0] - (lv.program_counter + P::ONES)));
+    yield_constr.constraint(filter_syscall * (output[1] - lv.is_kernel_mode));
+    yield_constr.constraint(filter_syscall * output[2]);
+    // Push to stack for an exception: current PC (limb 0), stack length (limb 2), gas counter
+    // (limbs 6 and 7). The kernel flag is known to be 0.
+    yield_constr.constraint(filter_exception * (output[0] - lv.program_counter));
+    yield_constr.constraint(filter_exception * output[1]);
+    yield_constr.constraint(filter_exception * (output[2] - lv.stack_len));
     yield_constr.constraint(filter * (output[6] - lv.gas));
     // TODO: Range check `output[6]`.
     yield_constr.constraint(filter * output[7]); // High limb of gas is zero.
 
     // Zero the rest of that register
-    for &limb in &output[2..6] {
+    for &limb in &output[3..6] {
         yield_constr.constraint(filter * limb);
     }
 }
@@ -93,13 +126,38 @@ pub fn eval_ext_circuit<F: RichField + Extendable<D>, const D: usize>(
     nv: &CpuColumnsView<ExtensionTarget<D>>,
     yield_constr: &mut RecursiveConstraintConsumer<F, D>,
 ) {
-    let filter = lv.op.syscall;
+    let filter_syscall = lv.op.syscall;
+    let filter_exception = lv.op.exception;
+    let filter = builder.add_extension(filter_syscall, filter_exception);
+
+    // Exceptions are only raised by user code; the kernel is not supposed to fault.
+    {
+        let constr = builder.mul_extension(filter_exception, lv.is_kernel_mode);
+        yield_constr.constraint(builder, constr);
+    }
+
+    // The exception code, in {0, ..., 7}.
+    let exc_code_bits = lv.general.exception().exc_code_bits;
+    for bit in exc_code_bits {
+        let constr = builder.mul_sub_extension(bit, bit, bit);
+        let constr = builder.mul_extension(filter_exception, constr);
+        yield_constr.constraint(builder, constr);
+    }
+    let exc_code = exc_code_bits
+        .into_iter()
+        .rev()
+        .fold(builder.zero_extension(), |cumul, bit| {
+            builder.mul_const_add_extension(F::TWO, cumul, bit)
+        });
 
     // Look up the handler in memory
     let code_segment = F::from_canonical_usize(Segment::Code as usize);
     let syscall_jumptable_start = builder.constant_extension(
         F::from_canonical_usize(KERNEL.global_labels["syscall_jumptable"]).into(),
     );
+    let exception_jumptable_start = builder.constant_extension(
+        F::from_canonical_usize(KERNEL.global_labels["exception_jumptable"]).into(),
+    );
     let opcode = lv
         .opcode_bits
         .into_iter()
@@ -112,6 +170,11 @@ pub fn eval_ext_circuit<F: RichField + Extendable<D>, const D: usize>(
         opcode,
         syscall_jumptable_start,
     );
+    let exc_handler_addr_start = builder.mul_const_add_extension(
+        F::from_canonical_usize(BYTES_PER_OFFSET),
+        exc_code,
+        exception_jumptable_start,
+    );
     for (i, channel) in lv.mem_channels[0..BYTES_PER_OFFSET].iter().enumerate() {
         {
             let constr = builder.mul_sub_extension(filter, channel.used, filter);
@@ -144,9 +207,20 @@ pub fn eval_ext_circuit<F: RichField + Extendable<D>, const D: usize>(
             let constr = builder.arithmetic_extension(
                 F::ONE,
                 -F::from_canonical_usize(i),
-                filter,
+                filter_syscall,
                 diff,
-                filter,
+                filter_syscall,
+            );
+            yield_constr.constraint(builder, constr);
+        }
+        {
+            let diff = builder.sub_extension(channel.addr_virtual, exc_handler_addr_start);
+            let constr = builder.arithmetic_extension(
+                F::ONE,
+                -F::from_canonical_usize(i),
+                filter_exception,
+                diff,
+                filter_exception,
             );
             yield_constr.constraint(builder, constr);
         }
@@ -190,16 +264,37 @@ pub fn eval_ext_circuit<F: RichField + Extendable<D>, const D: usize>(
 
     // This memory channel is constrained in `stack.rs`.
     let output = lv.mem_channels[NUM_GP_CHANNELS - 1].value;
-    // Push to stack: current PC + 1 (limb 0), kernel flag (limb 1), gas counter (limbs 6 and 7).
+    // Push to stack for a syscall: current PC + 1 (limb 0), kernel flag (limb 1), gas counter
+    // (limbs 6 and 7).
     {
         let pc_plus_1 = builder.add_const_extension(lv.program_counter, F::ONE);
         let diff = builder.sub_extension(output[0], pc_plus_1);
-        let constr = builder.mul_extension(filter, diff);
+        let constr = builder.mul_extension(filter_syscall, diff);
         yield_constr.constraint(builder, constr);
     }
     {
         let diff = builder.sub_extension(output[1], lv.is_kernel_mode);
-        let constr = builder.mul_extension(filter, diff);
+        let constr = builder.mul_extension(filter_syscall, diff);
+        yield_constr.constraint(builder, constr);
+    }
+    {
+        let constr = builder.mul_extension(filter_syscall, output[2]);
+        yield_constr.constraint(builder, constr);
+    }
+    // Push to stack for an exception: current PC (limb 0), stack length (limb 2), gas counter
+    // (limbs 6 and 7). The kernel flag is known to be 0.
+    {
+        let diff = builder.sub_extension(output[0], lv.program_counter);
+        let constr = builder.mul_extension(filter_exception, diff);
+        yield_constr.constraint(builder, constr);
+    }
+    {
+        let constr = builder.mul_extension(filter_exception, output[1]);
+        yield_constr.constraint(builder, constr);
+    }
+    {
+        let diff = builder.sub_extension(output[2], lv.stack_len);
+        let constr = builder.mul_extension(filter_exception, diff);
         yield_constr.constraint(builder, constr);
     }
     {
@@ -215,7 +310,7 @@ pub fn eval_ext_circuit<F: RichField + Extendable<D>, const D: usize>(
     }
 
     // Zero the rest of that register
-    for &limb in &output[2..6] {
+    for &limb in &output[3..6] {
         let constr = builder.mul_extension(filter, limb);
         yield_constr.constraint(builder, constr);
     }
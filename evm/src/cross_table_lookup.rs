@@ -539,9 +539,13 @@ pub(crate) fn eval_cross_table_lookup_checks_circuit<
     }
 }
 
+/// Checks the final values of the CTL `Z` polynomials. `ctl_extra_looking_products` holds, for each
+/// looked table and each challenge, the product of the combined rows which are looked up in that
+/// table by the verifier itself rather than by another table.
 pub(crate) fn verify_cross_table_lookups<F: RichField + Extendable<D>, const D: usize>(
     cross_table_lookups: &[CrossTableLookup<F>],
     ctl_zs_lasts: [Vec<F>; NUM_TABLES],
+    ctl_extra_looking_products: &[Vec<F>; NUM_TABLES],
     config: &StarkConfig,
 ) -> Result<()> {
     let mut ctl_zs_openings = ctl_zs_lasts.iter().map(|v| v.iter()).collect::<Vec<_>>();
@@ -550,11 +554,14 @@ pub(crate) fn verify_cross_table_lookups<F: RichField + Extendable<D>, const D:
         looked_table,
     } in cross_table_lookups.iter()
     {
-        for _ in 0..config.num_challenges {
+        let extra_products = &ctl_extra_looking_products[looked_table.table as usize];
+        assert_eq!(extra_products.len(), config.num_challenges);
+        for &extra_product in extra_products {
             let looking_zs_prod = looking_tables
                 .iter()
                 .map(|table| *ctl_zs_openings[table.table as usize].next().unwrap())
-                .product::<F>();
+                .product::<F>()
+                * extra_product;
             let looked_z = *ctl_zs_openings[looked_table.table as usize].next().unwrap();
 
             ensure!(
@@ -572,6 +579,7 @@ pub(crate) fn verify_cross_table_lookups_circuit<F: RichField + Extendable<D>, c
     builder: &mut CircuitBuilder<F, D>,
     cross_table_lookups: Vec<CrossTableLookup<F>>,
     ctl_zs_lasts: [Vec<Target>; NUM_TABLES],
+    ctl_extra_looking_products: &[Vec<Target>; NUM_TABLES],
     inner_config: &StarkConfig,
 ) {
     let mut ctl_zs_openings = ctl_zs_lasts.iter().map(|v| v.iter()).collect::<Vec<_>>();
@@ -580,11 +588,14 @@ pub(crate) fn verify_cross_table_lookups_circuit<F: RichField + Extendable<D>, c
         looked_table,
     } in cross_table_lookups.into_iter()
     {
-        for _ in 0..inner_config.num_challenges {
+        let extra_products = &ctl_extra_looking_products[looked_table.table as usize];
+        assert_eq!(extra_products.len(), inner_config.num_challenges);
+        for &extra_product in extra_products {
             let looking_zs_prod = builder.mul_many(
                 looking_tables
                     .iter()
-                    .map(|table| *ctl_zs_openings[table.table as usize].next().unwrap()),
+                    .map(|table| *ctl_zs_openings[table.table as usize].next().unwrap())
+                    .chain([extra_product]),
             );
             let looked_z = *ctl_zs_openings[looked_table.table as usize].next().unwrap();
             builder.connect(looking_zs_prod, looked_z);
@@ -606,13 +617,16 @@ pub(crate) mod testutils {
     type MultiSet<F> = HashMap<Vec<F>, Vec<(Table, usize)>>;
 
     /// Check that the provided traces and cross-table lookups are consistent.
+    /// `extra_memory_looking_values` are memory rows looked up by the verifier rather than by a
+    /// table, see `public_values_memory_rows`.
     #[allow(unused)] // TODO: used later?
     pub(crate) fn check_ctls<F: Field>(
         trace_poly_values: &[Vec<PolynomialValues<F>>],
         cross_table_lookups: &[CrossTableLookup<F>],
+        extra_memory_looking_values: &[Vec<F>],
     ) {
         for (i, ctl) in cross_table_lookups.iter().enumerate() {
-            check_ctl(trace_poly_values, ctl, i);
+            check_ctl(trace_poly_values, ctl, i, extra_memory_looking_values);
         }
     }
 
@@ -620,6 +634,7 @@ pub(crate) mod testutils {
         trace_poly_values: &[Vec<PolynomialValues<F>>],
         ctl: &CrossTableLookup<F>,
         ctl_index: usize,
+        extra_memory_looking_values: &[Vec<F>],
     ) {
         let CrossTableLookup {
             looking_tables,
@@ -636,6 +651,16 @@ pub(crate) mod testutils {
         }
         process_table(trace_poly_values, looked_table, &mut looked_multiset);
 
+        if looked_table.table == Table::Memory {
+            // The verifier's rows have no location, so we report them as `usize::MAX`.
+            for row in extra_memory_looking_values {
+                looking_multiset
+                    .entry(row.to_vec())
+                    .or_default()
+                    .push((Table::Memory, usize::MAX));
+            }
+        }
+
         let empty = &vec![];
         // Check that every row in the looking tables appears in the looked table the same number of times.
         for (row, looking_locations) in &looking_multiset {
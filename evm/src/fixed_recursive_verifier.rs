@@ -1,9 +1,12 @@
 use std::collections::BTreeMap;
 use std::ops::Range;
 
+use eth_trie_utils::partial_trie::PartialTrie;
 use itertools::Itertools;
 use plonky2::field::extension::Extendable;
 use plonky2::fri::FriParams;
+use plonky2::gates::constant::ConstantGate;
+use plonky2::gates::gate::GateRef;
 use plonky2::gates::noop::NoopGate;
 use plonky2::hash::hash_types::RichField;
 use plonky2::hash::hashing::SPONGE_WIDTH;
@@ -16,7 +19,9 @@ use plonky2::plonk::circuit_data::{
 };
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
 use plonky2::plonk::proof::{ProofWithPublicInputs, ProofWithPublicInputsTarget};
+use plonky2::plonk::prover_handle::ProverHandle;
 use plonky2::recursion::cyclic_recursion::check_cyclic_proof_verifier_data;
+use plonky2::recursion::dummy_circuit::cyclic_base_proof;
 use plonky2::util::timing::TimingTree;
 use plonky2_util::log2_ceil;
 
@@ -30,13 +35,18 @@ use crate::keccak_sponge::keccak_sponge_stark::KeccakSpongeStark;
 use crate::logic::LogicStark;
 use crate::memory::memory_stark::MemoryStark;
 use crate::permutation::{get_grand_product_challenge_set_target, GrandProductChallengeSet};
-use crate::proof::StarkProofWithMetadata;
-use crate::prover::prove;
+use crate::proof::{
+    AllProof, BlockMetadataTarget, ExtraBlockDataTarget, PublicValues, PublicValuesTarget,
+    StarkProofWithMetadata, TrieRootsTarget,
+};
+use crate::prover::prove_with_handle;
 use crate::recursive_verifier::{
-    add_common_recursion_gates, recursive_stark_circuit, PlonkWrapperCircuit, PublicInputs,
+    add_common_recursion_gates, add_virtual_public_values, ctl_extra_looking_products_circuit,
+    recursive_stark_circuit, set_public_value_targets, PlonkWrapperCircuit, PublicInputs,
     StarkWrapperCircuit,
 };
 use crate::stark::Stark;
+use crate::util::h256_limbs;
 
 /// The recursion threshold. We end a chain of recursive proofs once we reach this size.
 const THRESHOLD_DEGREE_BITS: usize = 13;
@@ -68,6 +78,9 @@ where
 {
     circuit: CircuitData<F, C, D>,
     proof_with_pis: [ProofWithPublicInputsTarget<D>; NUM_TABLES],
+    /// The EVM's public values, i.e. the trie roots before and after the transactions, and the
+    /// block metadata.
+    public_values: PublicValuesTarget,
     /// For each table, various inner circuits may be used depending on the initial table size.
     /// This target holds the index of the circuit (within `final_circuits()`) that was used.
     index_verifier_data: [Target; NUM_TABLES],
@@ -95,6 +108,18 @@ pub struct AggregationChildTarget<const D: usize> {
     evm_proof: ProofWithPublicInputsTarget<D>,
 }
 
+impl<const D: usize> AggregationChildTarget<D> {
+    /// The public values of whichever inner proof this child verifies.
+    fn public_values<F: RichField + Extendable<D>>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> PublicValuesTarget {
+        let agg_pv = PublicValuesTarget::from_public_inputs(&self.agg_proof.public_inputs);
+        let evm_pv = PublicValuesTarget::from_public_inputs(&self.evm_proof.public_inputs);
+        PublicValuesTarget::select(builder, self.is_agg, agg_pv, evm_pv)
+    }
+}
+
 pub struct BlockCircuitData<F, C, const D: usize>
 where
     F: RichField + Extendable<D>,
@@ -119,44 +144,45 @@ where
     [(); LogicStark::<F, D>::COLUMNS]:,
     [(); MemoryStark::<F, D>::COLUMNS]:,
 {
-    /// Preprocess all recursive circuits used by the system.
+    /// Preprocess all recursive circuits used by the system. For each table, `degree_bits_ranges`
+    /// gives the range of `degree_bits` of the STARK proofs the circuits can verify.
     pub fn new(
         all_stark: &AllStark<F, D>,
-        degree_bits_range: Range<usize>,
+        degree_bits_ranges: &[Range<usize>; NUM_TABLES],
         stark_config: &StarkConfig,
     ) -> Self {
         let cpu = RecursiveCircuitsForTable::new(
             Table::Cpu,
             &all_stark.cpu_stark,
-            degree_bits_range.clone(),
+            degree_bits_ranges[Table::Cpu as usize].clone(),
             &all_stark.cross_table_lookups,
             stark_config,
         );
         let keccak = RecursiveCircuitsForTable::new(
             Table::Keccak,
             &all_stark.keccak_stark,
-            degree_bits_range.clone(),
+            degree_bits_ranges[Table::Keccak as usize].clone(),
             &all_stark.cross_table_lookups,
             stark_config,
         );
         let keccak_sponge = RecursiveCircuitsForTable::new(
             Table::KeccakSponge,
             &all_stark.keccak_sponge_stark,
-            degree_bits_range.clone(),
+            degree_bits_ranges[Table::KeccakSponge as usize].clone(),
             &all_stark.cross_table_lookups,
             stark_config,
         );
         let logic = RecursiveCircuitsForTable::new(
             Table::Logic,
             &all_stark.logic_stark,
-            degree_bits_range.clone(),
+            degree_bits_ranges[Table::Logic as usize].clone(),
             &all_stark.cross_table_lookups,
             stark_config,
         );
         let memory = RecursiveCircuitsForTable::new(
             Table::Memory,
             &all_stark.memory_stark,
-            degree_bits_range,
+            degree_bits_ranges[Table::Memory as usize].clone(),
             &all_stark.cross_table_lookups,
             stark_config,
         );
@@ -181,6 +207,7 @@ where
             core::array::from_fn(|i| &by_table[i].final_circuits()[0].common);
 
         let mut builder = CircuitBuilder::new(CircuitConfig::standard_recursion_config());
+        let public_values = add_virtual_public_values(&mut builder);
         let recursive_proofs =
             core::array::from_fn(|i| builder.add_virtual_proof_with_pis(inner_common_data[i]));
         let pis: [_; NUM_TABLES] = core::array::from_fn(|i| {
@@ -194,6 +221,7 @@ where
                 challenger.observe_elements(h);
             }
         }
+        challenger.observe_elements(&public_values.to_targets());
         let ctl_challenges = get_grand_product_challenge_set_target(
             &mut builder,
             &mut challenger,
@@ -227,11 +255,15 @@ where
             }
         }
 
-        // Verify the CTL checks.
+        // Verify the CTL checks. The public values are bound to the STARK proofs by the memory CTL,
+        // which has to account for their writes to memory.
+        let ctl_extra_looking_products =
+            ctl_extra_looking_products_circuit(&mut builder, &public_values, &ctl_challenges);
         verify_cross_table_lookups_circuit::<F, D>(
             &mut builder,
             all_cross_table_lookups(),
             pis.map(|p| p.ctl_zs_last),
+            &ctl_extra_looking_products,
             stark_config,
         );
 
@@ -265,10 +297,17 @@ where
         // We want EVM root proofs to have the exact same structure as aggregation proofs, so we add
         // public inputs for cyclic verification, even though they'll be ignored.
         let cyclic_vk = builder.add_verifier_data_public_inputs();
+        // The dummy circuit, which the block circuit verifies when a block has no parent, stores its
+        // constants in a `ConstantGate`. It must have the same gates as aggregation proofs, so these
+        // need one too.
+        builder.add_gate_to_gate_set(GateRef::new(ConstantGate::new(
+            builder.config.num_constants,
+        )));
 
         RootCircuitData {
             circuit: builder.build(),
             proof_with_pis: recursive_proofs,
+            public_values,
             index_verifier_data,
             cyclic_vk,
         }
@@ -278,10 +317,81 @@ where
         root: &RootCircuitData<F, C, D>,
     ) -> AggregationCircuitData<F, C, D> {
         let mut builder = CircuitBuilder::<F, D>::new(root.circuit.common.config.clone());
+        // The public values of the aggregated transactions: the trie roots before the first one and
+        // after the last one, and the block metadata they share.
+        let public_values = add_virtual_public_values(&mut builder);
         let cyclic_vk = builder.add_verifier_data_public_inputs();
         let lhs = Self::add_agg_child(&mut builder, root);
         let rhs = Self::add_agg_child(&mut builder, root);
 
+        // The transactions on the right must follow those on the left, within the same block.
+        let lhs_public_values = lhs.public_values(&mut builder);
+        let rhs_public_values = rhs.public_values(&mut builder);
+        TrieRootsTarget::connect(
+            &mut builder,
+            &public_values.trie_roots_before,
+            &lhs_public_values.trie_roots_before,
+        );
+        TrieRootsTarget::connect(
+            &mut builder,
+            &lhs_public_values.trie_roots_after,
+            &rhs_public_values.trie_roots_before,
+        );
+        TrieRootsTarget::connect(
+            &mut builder,
+            &public_values.trie_roots_after,
+            &rhs_public_values.trie_roots_after,
+        );
+        BlockMetadataTarget::connect(
+            &mut builder,
+            &public_values.block_metadata,
+            &lhs_public_values.block_metadata,
+        );
+        BlockMetadataTarget::connect(
+            &mut builder,
+            &public_values.block_metadata,
+            &rhs_public_values.block_metadata,
+        );
+
+        // The block's progress continues from the left transactions to the right ones.
+        let extra_block_data = &public_values.extra_block_data;
+        let lhs_extra_block_data = &lhs_public_values.extra_block_data;
+        let rhs_extra_block_data = &rhs_public_values.extra_block_data;
+        builder.connect(
+            extra_block_data.txn_number_before,
+            lhs_extra_block_data.txn_number_before,
+        );
+        builder.connect(
+            lhs_extra_block_data.txn_number_after,
+            rhs_extra_block_data.txn_number_before,
+        );
+        builder.connect(
+            extra_block_data.txn_number_after,
+            rhs_extra_block_data.txn_number_after,
+        );
+        builder.connect(
+            extra_block_data.gas_used_before,
+            lhs_extra_block_data.gas_used_before,
+        );
+        builder.connect(
+            lhs_extra_block_data.gas_used_after,
+            rhs_extra_block_data.gas_used_before,
+        );
+        builder.connect(
+            extra_block_data.gas_used_after,
+            rhs_extra_block_data.gas_used_after,
+        );
+        ExtraBlockDataTarget::connect_block_hashes(
+            &mut builder,
+            extra_block_data,
+            lhs_extra_block_data,
+        );
+        ExtraBlockDataTarget::connect_block_hashes(
+            &mut builder,
+            extra_block_data,
+            rhs_extra_block_data,
+        );
+
         // Pad to match the root circuit's degree.
         while log2_ceil(builder.num_gates()) < root.circuit.common.degree_bits() {
             builder.add_gate(NoopGate, vec![]);
@@ -329,6 +439,9 @@ where
         };
 
         let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        // The public values of the chain segment proven so far: the trie roots before its first
+        // block and after its last block, and the metadata of its last block.
+        let public_values = add_virtual_public_values(&mut builder);
         let has_parent_block = builder.add_virtual_bool_target_safe();
         let parent_block_proof = builder.add_virtual_proof_with_pis(&expected_common_data);
         let agg_root_proof = builder.add_virtual_proof_with_pis(&agg.circuit.common);
@@ -344,6 +457,61 @@ where
 
         let agg_verifier_data = builder.constant_verifier_data(&agg.circuit.verifier_only);
         builder.verify_proof::<C>(&agg_root_proof, &agg_verifier_data, &agg.circuit.common);
+        // The aggregation proof must also claim the aggregation circuit as the one its inner proofs
+        // were checked against, which is what `verify_aggregation` checks outside of a circuit.
+        let agg_cyclic_vk = VerifierCircuitTarget::from_slice::<F, D>(
+            &agg_root_proof.public_inputs,
+            &agg.circuit.common,
+        )
+        .expect("Failed to read the aggregation proof's verifier data");
+        builder.connect_verifier_data(&agg_cyclic_vk, &agg_verifier_data);
+
+        let parent_pv = PublicValuesTarget::from_public_inputs(&parent_block_proof.public_inputs);
+        let agg_pv = PublicValuesTarget::from_public_inputs(&agg_root_proof.public_inputs);
+        Self::connect_block_proof(&mut builder, has_parent_block, &parent_pv, &agg_pv);
+
+        // The aggregated transactions must be all of the block's: they start from its empty
+        // transactions and receipts tries, with no transactions processed and no gas used yet.
+        let empty_root = h256_limbs::<F>(PartialTrie::Empty.calc_hash());
+        for root in [
+            agg_pv.trie_roots_before.transactions_root,
+            agg_pv.trie_roots_before.receipts_root,
+        ] {
+            for (limb, expected) in root.into_iter().zip(empty_root) {
+                let expected = builder.constant(expected);
+                builder.connect(limb, expected);
+            }
+        }
+        builder.assert_zero(agg_pv.extra_block_data.txn_number_before);
+        builder.assert_zero(agg_pv.extra_block_data.gas_used_before);
+
+        // The chain segment starts where the parent's does, or with this block if it has no parent.
+        let trie_roots_before = TrieRootsTarget::select(
+            &mut builder,
+            has_parent_block,
+            parent_pv.trie_roots_before,
+            agg_pv.trie_roots_before,
+        );
+        TrieRootsTarget::connect(
+            &mut builder,
+            &public_values.trie_roots_before,
+            &trie_roots_before,
+        );
+        TrieRootsTarget::connect(
+            &mut builder,
+            &public_values.trie_roots_after,
+            &agg_pv.trie_roots_after,
+        );
+        BlockMetadataTarget::connect(
+            &mut builder,
+            &public_values.block_metadata,
+            &agg_pv.block_metadata,
+        );
+        ExtraBlockDataTarget::connect(
+            &mut builder,
+            &public_values.extra_block_data,
+            &agg_pv.extra_block_data,
+        );
 
         let circuit = builder.build::<C>();
         BlockCircuitData {
@@ -355,6 +523,50 @@ where
         }
     }
 
+    /// If the block has a parent, checks that it continues the parent's chain segment: the block
+    /// starts from the parent's final state root, has the next block number on the same chain, and
+    /// shares the parent's ancestors.
+    fn connect_block_proof(
+        builder: &mut CircuitBuilder<F, D>,
+        has_parent_block: BoolTarget,
+        parent: &PublicValuesTarget,
+        block: &PublicValuesTarget,
+    ) {
+        // Between blocks, only the state trie carries over; each block has its own transactions
+        // and receipts tries.
+        for (&parent_limb, &limb) in parent
+            .trie_roots_after
+            .state_root
+            .iter()
+            .zip(&block.trie_roots_before.state_root)
+        {
+            let expected = builder.select(has_parent_block, parent_limb, limb);
+            builder.connect(expected, limb);
+        }
+
+        let parent_block_number = parent.block_metadata.block_number;
+        let block_number = block.block_metadata.block_number;
+        let next_block_number = builder.add_const(parent_block_number, F::ONE);
+        let expected = builder.select(has_parent_block, next_block_number, block_number);
+        builder.connect(expected, block_number);
+
+        let parent_chain_id = parent.block_metadata.block_chain_id;
+        let chain_id = block.block_metadata.block_chain_id;
+        let expected = builder.select(has_parent_block, parent_chain_id, chain_id);
+        builder.connect(expected, chain_id);
+
+        // The block's ancestors are its parent's, shifted by one to make room for the parent.
+        for (parent_hash, hash) in parent.extra_block_data.block_hashes[1..]
+            .iter()
+            .zip(&block.extra_block_data.block_hashes)
+        {
+            for (&parent_limb, &limb) in parent_hash.iter().zip(hash) {
+                let expected = builder.select(has_parent_block, parent_limb, limb);
+                builder.connect(expected, limb);
+            }
+        }
+    }
+
     /// Create a proof for each STARK, then combine them, eventually culminating in a root proof.
     pub fn prove_root(
         &self,
@@ -363,33 +575,94 @@ where
         generation_inputs: GenerationInputs,
         timing: &mut TimingTree,
     ) -> anyhow::Result<ProofWithPublicInputs<F, C, D>> {
-        let all_proof = prove::<F, C, D>(all_stark, config, generation_inputs, timing)?;
+        self.prove_root_with_handle(
+            all_stark,
+            config,
+            generation_inputs,
+            &ProverHandle::default(),
+            timing,
+        )
+    }
+
+    /// Like [`prove_root`](Self::prove_root), but reports the progress of the STARK proofs and of
+    /// each recursive proof to `handle`, can be cancelled through it, and stores or resumes from
+    /// checkpoints if it has a checkpoint directory.
+    pub fn prove_root_with_handle(
+        &self,
+        all_stark: &AllStark<F, D>,
+        config: &StarkConfig,
+        generation_inputs: GenerationInputs,
+        handle: &ProverHandle,
+        timing: &mut TimingTree,
+    ) -> anyhow::Result<ProofWithPublicInputs<F, C, D>> {
+        let all_proof =
+            prove_with_handle::<F, C, D>(all_stark, config, generation_inputs, handle, timing)?;
+        self.prove_root_from_stark_proofs(config, &all_proof, handle)
+    }
+
+    /// Wraps per-table STARK proofs, and the public values they claim, into a root proof.
+    fn prove_root_from_stark_proofs(
+        &self,
+        config: &StarkConfig,
+        all_proof: &AllProof<F, C, D>,
+        handle: &ProverHandle,
+    ) -> anyhow::Result<ProofWithPublicInputs<F, C, D>> {
+        let shrunk_proofs = self.shrink_stark_proofs(config, all_proof, handle)?;
+        let mut root_inputs = self.root_witness(&shrunk_proofs, &all_proof.public_values);
+        root_inputs.set_verifier_data_target(
+            &self.root.cyclic_vk,
+            &self.aggregation.circuit.verifier_only,
+        );
+        self.root.circuit.prove_with_handle(root_inputs, handle)
+    }
+
+    /// Shrinks each table's STARK proof, along with the index of the final circuit which did so.
+    fn shrink_stark_proofs(
+        &self,
+        config: &StarkConfig,
+        all_proof: &AllProof<F, C, D>,
+        handle: &ProverHandle,
+    ) -> anyhow::Result<Vec<(usize, ProofWithPublicInputs<F, C, D>)>> {
+        (0..NUM_TABLES)
+            .map(|table| {
+                let stark_proof = &all_proof.stark_proofs[table];
+                let original_degree_bits = stark_proof.proof.recover_degree_bits(config);
+                let table_circuits = &self.by_table[table];
+                let shrunk_proof = table_circuits.by_stark_size[&original_degree_bits].shrink(
+                    stark_proof,
+                    &all_proof.ctl_challenges,
+                    handle,
+                )?;
+                let index_verifier_data = table_circuits
+                    .by_stark_size
+                    .keys()
+                    .position(|&size| size == original_degree_bits)
+                    .unwrap();
+                Ok((index_verifier_data, shrunk_proof))
+            })
+            .collect()
+    }
+
+    /// The root circuit's witness for the given shrunk proofs and public values, except for its
+    /// cyclic verifier data.
+    fn root_witness(
+        &self,
+        shrunk_proofs: &[(usize, ProofWithPublicInputs<F, C, D>)],
+        public_values: &PublicValues,
+    ) -> PartialWitness<F> {
         let mut root_inputs = PartialWitness::new();
 
-        for table in 0..NUM_TABLES {
-            let stark_proof = &all_proof.stark_proofs[table];
-            let original_degree_bits = stark_proof.proof.recover_degree_bits(config);
-            let table_circuits = &self.by_table[table];
-            let shrunk_proof = table_circuits.by_stark_size[&original_degree_bits]
-                .shrink(stark_proof, &all_proof.ctl_challenges)?;
-            let index_verifier_data = table_circuits
-                .by_stark_size
-                .keys()
-                .position(|&size| size == original_degree_bits)
-                .unwrap();
+        for (table, (index_verifier_data, shrunk_proof)) in shrunk_proofs.iter().enumerate() {
             root_inputs.set_target(
                 self.root.index_verifier_data[table],
-                F::from_canonical_usize(index_verifier_data),
+                F::from_canonical_usize(*index_verifier_data),
             );
-            root_inputs.set_proof_with_pis_target(&self.root.proof_with_pis[table], &shrunk_proof);
+            root_inputs.set_proof_with_pis_target(&self.root.proof_with_pis[table], shrunk_proof);
         }
 
-        root_inputs.set_verifier_data_target(
-            &self.root.cyclic_vk,
-            &self.aggregation.circuit.verifier_only,
-        );
+        set_public_value_targets(&mut root_inputs, &self.root.public_values, public_values);
 
-        self.root.circuit.prove(root_inputs)
+        root_inputs
     }
 
     pub fn verify_root(&self, agg_proof: ProofWithPublicInputs<F, C, D>) -> anyhow::Result<()> {
@@ -403,6 +676,44 @@ where
         rhs_is_agg: bool,
         rhs_proof: &ProofWithPublicInputs<F, C, D>,
     ) -> anyhow::Result<ProofWithPublicInputs<F, C, D>> {
+        self.prove_aggregation_with_handle(
+            lhs_is_agg,
+            lhs_proof,
+            rhs_is_agg,
+            rhs_proof,
+            &ProverHandle::default(),
+        )
+    }
+
+    /// Like [`prove_aggregation`](Self::prove_aggregation), but proves with the given
+    /// [`ProverHandle`].
+    pub fn prove_aggregation_with_handle(
+        &self,
+        lhs_is_agg: bool,
+        lhs_proof: &ProofWithPublicInputs<F, C, D>,
+        rhs_is_agg: bool,
+        rhs_proof: &ProofWithPublicInputs<F, C, D>,
+        handle: &ProverHandle,
+    ) -> anyhow::Result<ProofWithPublicInputs<F, C, D>> {
+        let mut agg_inputs = self.aggregation_witness(lhs_is_agg, lhs_proof, rhs_is_agg, rhs_proof);
+        agg_inputs.set_verifier_data_target(
+            &self.aggregation.cyclic_vk,
+            &self.aggregation.circuit.verifier_only,
+        );
+        self.aggregation
+            .circuit
+            .prove_with_handle(agg_inputs, handle)
+    }
+
+    /// The aggregation circuit's witness for the given inner proofs, except for its cyclic verifier
+    /// data.
+    fn aggregation_witness(
+        &self,
+        lhs_is_agg: bool,
+        lhs_proof: &ProofWithPublicInputs<F, C, D>,
+        rhs_is_agg: bool,
+        rhs_proof: &ProofWithPublicInputs<F, C, D>,
+    ) -> PartialWitness<F> {
         let mut agg_inputs = PartialWitness::new();
 
         agg_inputs.set_bool_target(self.aggregation.lhs.is_agg, lhs_is_agg);
@@ -413,12 +724,7 @@ where
         agg_inputs.set_proof_with_pis_target(&self.aggregation.rhs.agg_proof, rhs_proof);
         agg_inputs.set_proof_with_pis_target(&self.aggregation.rhs.evm_proof, rhs_proof);
 
-        agg_inputs.set_verifier_data_target(
-            &self.aggregation.cyclic_vk,
-            &self.aggregation.circuit.verifier_only,
-        );
-
-        self.aggregation.circuit.prove(agg_inputs)
+        agg_inputs
     }
 
     pub fn verify_aggregation(
@@ -433,10 +739,26 @@ where
         )
     }
 
+    /// Proves a block on top of its parent block proof, if any, so that the resulting proof attests
+    /// the whole chain segment. The aggregation proof must cover all of the block's transactions.
     pub fn prove_block(
         &self,
         opt_parent_block_proof: Option<&ProofWithPublicInputs<F, C, D>>,
         agg_root_proof: &ProofWithPublicInputs<F, C, D>,
+    ) -> anyhow::Result<ProofWithPublicInputs<F, C, D>> {
+        self.prove_block_with_handle(
+            opt_parent_block_proof,
+            agg_root_proof,
+            &ProverHandle::default(),
+        )
+    }
+
+    /// Like [`prove_block`](Self::prove_block), but proves with the given [`ProverHandle`].
+    pub fn prove_block_with_handle(
+        &self,
+        opt_parent_block_proof: Option<&ProofWithPublicInputs<F, C, D>>,
+        agg_root_proof: &ProofWithPublicInputs<F, C, D>,
+        handle: &ProverHandle,
     ) -> anyhow::Result<ProofWithPublicInputs<F, C, D>> {
         let mut block_inputs = PartialWitness::new();
 
@@ -447,6 +769,15 @@ where
         if let Some(parent_block_proof) = opt_parent_block_proof {
             block_inputs
                 .set_proof_with_pis_target(&self.block.parent_block_proof, parent_block_proof);
+        } else {
+            // The circuit verifies a dummy proof instead, but still reads the parent's public
+            // inputs, which must claim the block circuit's verifier data.
+            let base_proof = cyclic_base_proof(
+                &self.block.circuit.common,
+                &self.block.circuit.verifier_only,
+                Default::default(),
+            );
+            block_inputs.set_proof_with_pis_target(&self.block.parent_block_proof, &base_proof);
         }
 
         block_inputs.set_proof_with_pis_target(&self.block.agg_root_proof, agg_root_proof);
@@ -454,7 +785,7 @@ where
         block_inputs
             .set_verifier_data_target(&self.block.cyclic_vk, &self.block.circuit.verifier_only);
 
-        self.block.circuit.prove(block_inputs)
+        self.block.circuit.prove_with_handle(block_inputs, handle)
     }
 
     pub fn verify_block(&self, block_proof: &ProofWithPublicInputs<F, C, D>) -> anyhow::Result<()> {
@@ -608,12 +939,13 @@ where
         &self,
         stark_proof_with_metadata: &StarkProofWithMetadata<F, C, D>,
         ctl_challenges: &GrandProductChallengeSet<F>,
+        handle: &ProverHandle,
     ) -> anyhow::Result<ProofWithPublicInputs<F, C, D>> {
-        let mut proof = self
-            .initial_wrapper
-            .prove(stark_proof_with_metadata, ctl_challenges)?;
+        let mut proof =
+            self.initial_wrapper
+                .prove(stark_proof_with_metadata, ctl_challenges, handle)?;
         for wrapper_circuit in &self.shrinking_wrappers {
-            proof = wrapper_circuit.prove(&proof)?;
+            proof = wrapper_circuit.prove(&proof, handle)?;
         }
         Ok(proof)
     }
@@ -628,3 +960,275 @@ fn shrinking_config() -> CircuitConfig {
         ..CircuitConfig::standard_recursion_config()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use anyhow::Result;
+    use eth_trie_utils::partial_trie::{Nibbles, PartialTrie};
+    use ethereum_types::{H256, U256};
+    use keccak_hash::keccak;
+    use once_cell::sync::Lazy;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::WitnessWrite;
+    use plonky2::plonk::circuit_data::VerifierOnlyCircuitData;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+    use plonky2::plonk::proof::ProofWithPublicInputs;
+    use plonky2::plonk::prover_handle::ProverHandle;
+    use plonky2::util::timing::TimingTree;
+
+    use crate::all_stark::AllStark;
+    use crate::config::StarkConfig;
+    use crate::fixed_recursive_verifier::AllRecursiveCircuits;
+    use crate::generation::mpt::AccountRlp;
+    use crate::generation::{GenerationInputs, TrieInputs};
+    use crate::proof::{AllProof, BlockMetadata, PublicValues, TrieRoots};
+    use crate::prover::prove_with_handle;
+
+    type F = GoldilocksField;
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+
+    static ALL_STARK: Lazy<AllStark<F, D>> = Lazy::new(AllStark::default);
+
+    /// The circuits of the tables' sizes for an empty block, whatever its state trie.
+    static ALL_CIRCUITS: Lazy<AllRecursiveCircuits<F, C, D>> = Lazy::new(|| {
+        AllRecursiveCircuits::new(
+            &ALL_STARK,
+            &[15..16, 14..15, 10..11, 12..13, 18..19],
+            &StarkConfig::standard_fast_config(),
+        )
+    });
+
+    /// The STARK proofs of an empty block 1 with an empty state trie.
+    static EMPTY_BLOCK_PROOFS: Lazy<AllProof<F, C, D>> =
+        Lazy::new(|| prove_empty_block(1, PartialTrie::Empty));
+
+    /// The shrunk proofs of `EMPTY_BLOCK_PROOFS`.
+    static EMPTY_BLOCK_SHRUNK_PROOFS: Lazy<Vec<(usize, ProofWithPublicInputs<F, C, D>)>> =
+        Lazy::new(|| {
+            ALL_CIRCUITS
+                .shrink_stark_proofs(
+                    &StarkConfig::standard_fast_config(),
+                    &EMPTY_BLOCK_PROOFS,
+                    &ProverHandle::default(),
+                )
+                .unwrap()
+        });
+
+    /// An aggregation proof of `EMPTY_BLOCK_PROOFS`.
+    static EMPTY_BLOCK_AGG_PROOF: Lazy<ProofWithPublicInputs<F, C, D>> = Lazy::new(|| {
+        let root_proof = prove_root(
+            &EMPTY_BLOCK_PROOFS.public_values,
+            &ALL_CIRCUITS.aggregation.circuit.verifier_only,
+        )
+        .unwrap();
+        ALL_CIRCUITS
+            .prove_aggregation(false, &root_proof, false, &root_proof)
+            .unwrap()
+    });
+
+    /// Proves a block without transactions, which leaves `state_trie` as it is.
+    fn prove_empty_block(block_number: u64, state_trie: PartialTrie) -> AllProof<F, C, D> {
+        let empty_root = PartialTrie::Empty.calc_hash();
+        let inputs = GenerationInputs {
+            signed_txns: vec![],
+            txn_number_before: 0.into(),
+            gas_used_before: 0.into(),
+            gas_used_after: 0.into(),
+            tries: TrieInputs {
+                state_trie: state_trie.clone(),
+                transactions_trie: PartialTrie::Empty,
+                receipts_trie: PartialTrie::Empty,
+                storage_tries: vec![],
+            },
+            contract_code: HashMap::new(),
+            block_metadata: BlockMetadata {
+                block_number: block_number.into(),
+                ..BlockMetadata::default()
+            },
+            trie_roots_after: TrieRoots {
+                state_root: state_trie.calc_hash(),
+                transactions_root: empty_root,
+                receipts_root: empty_root,
+            },
+            block_hashes: vec![],
+        };
+
+        let config = StarkConfig::standard_fast_config();
+        prove_with_handle::<F, C, D>(
+            &ALL_STARK,
+            &config,
+            inputs,
+            &ProverHandle::default(),
+            &mut TimingTree::default(),
+        )
+        .unwrap()
+    }
+
+    /// Proves the root circuit for the shrunk `EMPTY_BLOCK_PROOFS`, claiming the given public values
+    /// and cyclic verifier data.
+    fn prove_root(
+        public_values: &PublicValues,
+        cyclic_vk: &VerifierOnlyCircuitData<C, D>,
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        let mut root_inputs = ALL_CIRCUITS.root_witness(&EMPTY_BLOCK_SHRUNK_PROOFS, public_values);
+        root_inputs.set_verifier_data_target(&ALL_CIRCUITS.root.cyclic_vk, cyclic_vk);
+        ALL_CIRCUITS.root.circuit.prove(root_inputs)
+    }
+
+    /// Proves an empty block on top of no parent.
+    fn prove_empty_block_proof(
+        block_number: u64,
+        state_trie: PartialTrie,
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        let config = StarkConfig::standard_fast_config();
+        let all_proof = prove_empty_block(block_number, state_trie);
+        let root_proof = ALL_CIRCUITS.prove_root_from_stark_proofs(
+            &config,
+            &all_proof,
+            &ProverHandle::default(),
+        )?;
+        let agg_proof = ALL_CIRCUITS.prove_aggregation(false, &root_proof, false, &root_proof)?;
+        ALL_CIRCUITS.prove_block(None, &agg_proof)
+    }
+
+    /// Runs `prove`, and returns its proof unless it fails or panics.
+    fn try_prove(
+        prove: impl FnOnce() -> Result<ProofWithPublicInputs<F, C, D>>,
+    ) -> Option<ProofWithPublicInputs<F, C, D>> {
+        catch_unwind(AssertUnwindSafe(prove)).ok()?.ok()
+    }
+
+    /// Runs `prove` on a witness which should not be provable, and asserts that it is rejected:
+    /// either `prove` panics because two copies of a wire were given different values, which is
+    /// how the witness generation reports a violated copy constraint, or it returns a proof which
+    /// `verify` rejects. Returns that proof, if any.
+    fn assert_rejected(
+        prove: impl FnOnce() -> Result<ProofWithPublicInputs<F, C, D>>,
+        verify: impl FnOnce(ProofWithPublicInputs<F, C, D>) -> Result<()>,
+    ) -> Option<ProofWithPublicInputs<F, C, D>> {
+        match catch_unwind(AssertUnwindSafe(prove)) {
+            Ok(Ok(proof)) => {
+                assert!(
+                    verify(proof.clone()).is_err(),
+                    "an invalid proof was accepted"
+                );
+                Some(proof)
+            }
+            Ok(Err(e)) => panic!("proving failed with an unexpected error: {e:?}"),
+            Err(payload) => {
+                let message = payload
+                    .downcast_ref::<String>()
+                    .map(String::as_str)
+                    .or_else(|| payload.downcast_ref::<&str>().copied())
+                    .unwrap_or_default();
+                assert!(
+                    message.contains("was set twice with different values"),
+                    "proving panicked with an unexpected message: {message}"
+                );
+                None
+            }
+        }
+    }
+
+    /// A root proof whose public values were changed after the STARKs were proven must not be
+    /// accepted by the root circuit, nor by the aggregation and block circuits built on it.
+    #[test]
+    fn test_tampered_public_values_are_rejected() -> Result<()> {
+        ALL_CIRCUITS.verify_aggregation(&EMPTY_BLOCK_AGG_PROOF)?;
+
+        let tamperings: [fn(&mut PublicValues); 4] = [
+            |pv| pv.trie_roots_after.state_root = H256::zero(),
+            |pv| pv.block_metadata.block_number += 1.into(),
+            |pv| pv.extra_block_data.txn_number_after += 1.into(),
+            |pv| pv.extra_block_data.block_hashes[0] = H256::repeat_byte(1),
+        ];
+        for tamper in tamperings {
+            let mut tampered = EMPTY_BLOCK_PROOFS.public_values.clone();
+            tamper(&mut tampered);
+
+            let agg_vk = &ALL_CIRCUITS.aggregation.circuit.verifier_only;
+            let root_proof = assert_rejected(
+                || prove_root(&tampered, agg_vk),
+                |proof| ALL_CIRCUITS.verify_root(proof),
+            );
+            // A rejected root proof must also be rejected by the circuits recursing on it.
+            let agg_proof = root_proof.and_then(|root_proof| {
+                assert_rejected(
+                    || ALL_CIRCUITS.prove_aggregation(false, &root_proof, false, &root_proof),
+                    |proof| ALL_CIRCUITS.verify_aggregation(&proof),
+                )
+            });
+            if let Some(agg_proof) = agg_proof {
+                assert_rejected(
+                    || ALL_CIRCUITS.prove_block(None, &agg_proof),
+                    |proof| ALL_CIRCUITS.verify_block(&proof),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// An aggregation proof which claims the verifier data of another circuit passes the
+    /// aggregation circuit itself, but must not be accepted by the block circuit.
+    #[test]
+    fn test_foreign_aggregation_verifier_data_is_rejected() -> Result<()> {
+        let foreign_vk = &ALL_CIRCUITS.block.circuit.verifier_only;
+        let root_proof = prove_root(&EMPTY_BLOCK_PROOFS.public_values, foreign_vk)?;
+
+        let mut agg_inputs =
+            ALL_CIRCUITS.aggregation_witness(false, &root_proof, false, &root_proof);
+        agg_inputs.set_verifier_data_target(&ALL_CIRCUITS.aggregation.cyclic_vk, foreign_vk);
+        let agg_proof = ALL_CIRCUITS.aggregation.circuit.prove(agg_inputs)?;
+        ALL_CIRCUITS.aggregation.circuit.verify(agg_proof.clone())?;
+        assert!(ALL_CIRCUITS.verify_aggregation(&agg_proof).is_err());
+
+        assert!(try_prove(|| ALL_CIRCUITS.prove_block(None, &agg_proof)).is_none());
+        Ok(())
+    }
+
+    /// A block must have the number following its parent's.
+    #[test]
+    fn test_block_with_wrong_number_is_rejected() -> Result<()> {
+        let agg_proof = &*EMPTY_BLOCK_AGG_PROOF;
+
+        let parent_block_proof = prove_empty_block_proof(0, PartialTrie::Empty)?;
+        let block_proof = ALL_CIRCUITS.prove_block(Some(&parent_block_proof), agg_proof)?;
+        ALL_CIRCUITS.verify_block(&block_proof)?;
+
+        // The block can't follow itself.
+        let parent_block_proof = ALL_CIRCUITS.prove_block(None, agg_proof)?;
+        ALL_CIRCUITS.verify_block(&parent_block_proof)?;
+        assert!(
+            try_prove(|| ALL_CIRCUITS.prove_block(Some(&parent_block_proof), agg_proof)).is_none()
+        );
+        Ok(())
+    }
+
+    /// A block must start from its parent's final state root.
+    #[test]
+    fn test_block_with_wrong_parent_state_root_is_rejected() -> Result<()> {
+        let account = AccountRlp {
+            nonce: 0.into(),
+            balance: U256::one(),
+            storage_root: PartialTrie::Empty.calc_hash(),
+            code_hash: keccak([]),
+        };
+        let state_trie = PartialTrie::Leaf {
+            nibbles: Nibbles::from_bytes_be(keccak([0xab; 20]).as_bytes()).unwrap(),
+            value: rlp::encode(&account).to_vec(),
+        };
+        let parent_block_proof = prove_empty_block_proof(0, state_trie)?;
+        ALL_CIRCUITS.verify_block(&parent_block_proof)?;
+
+        assert!(try_prove(|| {
+            ALL_CIRCUITS.prove_block(Some(&parent_block_proof), &EMPTY_BLOCK_AGG_PROOF)
+        })
+        .is_none());
+        Ok(())
+    }
+}
@@ -1,28 +1,24 @@
 use std::collections::HashMap;
 
 use eth_trie_utils::partial_trie::PartialTrie;
-use ethereum_types::{Address, BigEndianHash, H256};
+use ethereum_types::{Address, H256, U256};
 use plonky2::field::extension::Extendable;
 use plonky2::field::polynomial::PolynomialValues;
+use plonky2::field::types::Field;
 use plonky2::hash::hash_types::RichField;
 use plonky2::timed;
 use plonky2::util::timing::TimingTree;
 use serde::{Deserialize, Serialize};
-use GlobalMetadata::{
-    ReceiptTrieRootDigestAfter, ReceiptTrieRootDigestBefore, StateTrieRootDigestAfter,
-    StateTrieRootDigestBefore, TransactionTrieRootDigestAfter, TransactionTrieRootDigestBefore,
-};
 
 use crate::all_stark::{AllStark, NUM_TABLES};
 use crate::config::StarkConfig;
 use crate::cpu::bootstrap_kernel::generate_bootstrap_kernel;
 use crate::cpu::kernel::aggregator::KERNEL;
-use crate::cpu::kernel::constants::global_metadata::GlobalMetadata;
 use crate::generation::state::GenerationState;
-use crate::memory::segments::Segment;
-use crate::proof::{BlockMetadata, PublicValues, TrieRoots};
-use crate::witness::memory::MemoryAddress;
+use crate::proof::{BlockMetadata, ExtraBlockData, PublicValues, TrieRoots, NUM_BLOCK_HASHES};
+use crate::witness::memory::{MemoryAddress, MemoryChannel};
 use crate::witness::transition::transition;
+use crate::witness::util::mem_write_log;
 
 pub mod mpt;
 pub(crate) mod prover_input;
@@ -34,6 +30,17 @@ pub(crate) mod state;
 pub struct GenerationInputs {
     pub signed_txns: Vec<Vec<u8>>,
 
+    /// The number of transactions of the block processed before these ones. The receipt of the
+    /// first of these transactions has key `rlp(txn_number_before)` in the receipt trie.
+    pub txn_number_before: U256,
+
+    /// The cumulative gas used by the transactions of the block processed before these ones.
+    pub gas_used_before: U256,
+
+    /// The cumulative gas used by the transactions of the block processed up to and including these
+    /// ones. The kernel checks that it matches the gas these transactions use.
+    pub gas_used_after: U256,
+
     pub tries: TrieInputs,
 
     /// Mapping between smart contract code hashes and the contract byte code.
@@ -41,6 +48,14 @@ pub struct GenerationInputs {
     pub contract_code: HashMap<H256, Vec<u8>>,
 
     pub block_metadata: BlockMetadata,
+
+    /// The roots of the tries after these transactions. The kernel checks that they match the
+    /// tries it computes.
+    pub trie_roots_after: TrieRoots,
+
+    /// The hashes of the most recent ancestors of the block, oldest first. Only the last 256 of
+    /// them can be accessed, through the `BLOCKHASH` instruction.
+    pub block_hashes: Vec<H256>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
@@ -62,13 +77,20 @@ pub struct TrieInputs {
     pub storage_tries: Vec<(Address, PartialTrie)>,
 }
 
+/// The channel with which the public values are written to memory, at clock 0.
+pub(crate) const PUBLIC_VALUES_CHANNEL: MemoryChannel = MemoryChannel::GeneralPurpose(0);
+
 pub(crate) fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
     all_stark: &AllStark<F, D>,
     inputs: GenerationInputs,
     config: &StarkConfig,
     timing: &mut TimingTree,
 ) -> ([Vec<PolynomialValues<F>>; NUM_TABLES], PublicValues) {
-    let mut state = GenerationState::<F>::new(inputs.clone(), &KERNEL.code);
+    let public_values = public_values(&inputs);
+
+    let mut state = GenerationState::<F>::new(inputs, &KERNEL.code);
+
+    apply_public_values_memops(&mut state, &public_values);
 
     generate_bootstrap_kernel::<F>(&mut state);
 
@@ -79,31 +101,6 @@ pub(crate) fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
         state.traces.checkpoint()
     );
 
-    let read_metadata = |field| {
-        state.memory.get(MemoryAddress::new(
-            0,
-            Segment::GlobalMetadata,
-            field as usize,
-        ))
-    };
-
-    let trie_roots_before = TrieRoots {
-        state_root: H256::from_uint(&read_metadata(StateTrieRootDigestBefore)),
-        transactions_root: H256::from_uint(&read_metadata(TransactionTrieRootDigestBefore)),
-        receipts_root: H256::from_uint(&read_metadata(ReceiptTrieRootDigestBefore)),
-    };
-    let trie_roots_after = TrieRoots {
-        state_root: H256::from_uint(&read_metadata(StateTrieRootDigestAfter)),
-        transactions_root: H256::from_uint(&read_metadata(TransactionTrieRootDigestAfter)),
-        receipts_root: H256::from_uint(&read_metadata(ReceiptTrieRootDigestAfter)),
-    };
-
-    let public_values = PublicValues {
-        trie_roots_before,
-        trie_roots_after,
-        block_metadata: inputs.block_metadata,
-    };
-
     let tables = timed!(
         timing,
         "convert trace data to tables",
@@ -112,6 +109,62 @@ pub(crate) fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
     (tables, public_values)
 }
 
+/// The public values of a proof of the given inputs. They only depend on the inputs, since the
+/// kernel checks that its execution matches them.
+pub(crate) fn public_values(inputs: &GenerationInputs) -> PublicValues {
+    PublicValues {
+        trie_roots_before: TrieRoots {
+            state_root: inputs.tries.state_trie.calc_hash(),
+            transactions_root: inputs.tries.transactions_trie.calc_hash(),
+            receipts_root: inputs.tries.receipts_trie.calc_hash(),
+        },
+        trie_roots_after: inputs.trie_roots_after.clone(),
+        block_metadata: inputs.block_metadata.clone(),
+        extra_block_data: ExtraBlockData {
+            txn_number_before: inputs.txn_number_before,
+            txn_number_after: inputs.txn_number_before + inputs.signed_txns.len(),
+            gas_used_before: inputs.gas_used_before,
+            gas_used_after: inputs.gas_used_after,
+            block_hashes: recent_block_hashes(&inputs.block_hashes),
+        },
+    }
+}
+
+/// Writes the public values to the kernel's memory before the first CPU cycle. These writes aren't
+/// looked up by any CPU row; the verifier accounts for them in the memory CTL instead, which binds
+/// the public values to the memory the kernel runs on.
+fn apply_public_values_memops<F: Field>(
+    state: &mut GenerationState<F>,
+    public_values: &PublicValues,
+) {
+    let ops = public_values
+        .memory_values()
+        .into_iter()
+        .map(|(segment, offset, value)| {
+            mem_write_log(
+                PUBLIC_VALUES_CHANNEL,
+                MemoryAddress::new(0, segment, offset),
+                state,
+                value,
+            )
+        })
+        .collect::<Vec<_>>();
+    state.memory.apply_ops(&ops);
+    for op in ops {
+        state.traces.push_memory(op);
+    }
+}
+
+/// The hashes of the `NUM_BLOCK_HASHES` most recent ancestors of the block, oldest first, given all
+/// the known ones. Missing hashes are zero.
+fn recent_block_hashes(block_hashes: &[H256]) -> [H256; NUM_BLOCK_HASHES] {
+    let num_hashes = block_hashes.len().min(NUM_BLOCK_HASHES);
+    let mut recent_hashes = [H256::zero(); NUM_BLOCK_HASHES];
+    recent_hashes[NUM_BLOCK_HASHES - num_hashes..]
+        .copy_from_slice(&block_hashes[block_hashes.len() - num_hashes..]);
+    recent_hashes
+}
+
 fn simulate_cpu<F: RichField + Extendable<D>, const D: usize>(state: &mut GenerationState<F>) {
     let halt_pc0 = KERNEL.global_labels["halt_pc0"];
     let halt_pc1 = KERNEL.global_labels["halt_pc1"];
@@ -1,8 +1,10 @@
 use std::collections::HashMap;
 
+use bytes::Bytes;
 use eth_trie_utils::partial_trie::{Nibbles, PartialTrie};
-use ethereum_types::{BigEndianHash, H256, U256};
+use ethereum_types::{Address, BigEndianHash, Bloom, H256, U256};
 use keccak_hash::keccak;
+use rlp::{DecoderError, Rlp};
 use rlp_derive::{RlpDecodable, RlpEncodable};
 
 use crate::cpu::kernel::constants::trie_type::PartialTrieType;
@@ -27,6 +29,54 @@ impl Default for AccountRlp {
     }
 }
 
+#[derive(RlpEncodable, RlpDecodable, Clone, Debug, Eq, PartialEq)]
+pub struct LogRlp {
+    pub address: Address,
+    pub topics: Vec<H256>,
+    pub data: Bytes,
+}
+
+#[derive(RlpEncodable, RlpDecodable, Clone, Debug, Eq, PartialEq)]
+pub struct LegacyReceiptRlp {
+    pub status: bool,
+    pub cum_gas_used: U256,
+    pub bloom: Bloom,
+    pub logs: Vec<LogRlp>,
+}
+
+/// A receipt, as stored in the receipt trie. Typed receipts (see EIP-2718) are prefixed by their
+/// transaction type, while legacy receipts are plain RLP lists.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReceiptRlp {
+    /// The transaction type, or 0 for a legacy receipt.
+    pub txn_type: u8,
+    pub receipt: LegacyReceiptRlp,
+}
+
+impl ReceiptRlp {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        if self.txn_type != 0 {
+            bytes.push(self.txn_type);
+        }
+        bytes.extend(rlp::encode(&self.receipt));
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecoderError> {
+        match bytes.first() {
+            Some(&txn_type) if txn_type < 0x80 => Ok(Self {
+                txn_type,
+                receipt: rlp::decode(&bytes[1..])?,
+            }),
+            _ => Ok(Self {
+                txn_type: 0,
+                receipt: rlp::decode(bytes)?,
+            }),
+        }
+    }
+}
+
 pub(crate) fn all_mpt_prover_inputs_reversed(trie_inputs: &TrieInputs) -> Vec<U256> {
     let mut inputs = all_mpt_prover_inputs(trie_inputs);
     inputs.reverse();
@@ -57,10 +107,11 @@ pub(crate) fn all_mpt_prover_inputs(trie_inputs: &TrieInputs) -> Vec<U256> {
         rlp::decode_list(rlp)
     });
 
-    mpt_prover_inputs(&trie_inputs.receipts_trie, &mut prover_inputs, &|_rlp| {
-        // TODO: Decode receipt RLP.
-        vec![]
-    });
+    mpt_prover_inputs(
+        &trie_inputs.receipts_trie,
+        &mut prover_inputs,
+        &parse_receipt_value,
+    );
 
     prover_inputs
 }
@@ -156,8 +207,9 @@ pub(crate) fn mpt_prover_inputs_state_trie(
             } = account;
 
             let storage_hash_only = PartialTrie::Hash(storage_root);
+            let merged_key = key.merge_nibbles(nibbles);
             let storage_trie: &PartialTrie = storage_tries_by_state_key
-                .get(&key)
+                .get(&merged_key)
                 .copied()
                 .unwrap_or(&storage_hash_only);
 
@@ -179,6 +231,44 @@ fn parse_storage_value(value_rlp: &[u8]) -> Vec<U256> {
     vec![value]
 }
 
+/// Serializes a receipt in the format expected by `mpt_load_receipt_trie_value`, i.e. its length
+/// followed by the receipt as described in `receipt.asm`.
+fn parse_receipt_value(value_rlp: &[u8]) -> Vec<U256> {
+    let ReceiptRlp { txn_type, receipt } = ReceiptRlp::decode(value_rlp).expect("Decoding failed");
+    let payload_len = |rlp: &[u8]| Rlp::new(rlp).payload_info().unwrap().value_len;
+
+    let mut logs = vec![];
+    for log in &receipt.logs {
+        logs.push(payload_len(&rlp::encode(log)).into());
+        logs.push(U256::from_big_endian(log.address.as_bytes()));
+        logs.push(log.topics.len().into());
+        logs.extend(log.topics.iter().map(|topic| topic.into_uint()));
+        logs.push(log.data.len().into());
+        logs.extend(log.data.iter().map(|&byte| U256::from(byte)));
+    }
+
+    let mut value = vec![
+        payload_len(&rlp::encode(&receipt)).into(),
+        txn_type.into(),
+        (receipt.status as u8).into(),
+        receipt.cum_gas_used,
+    ];
+    value.extend(
+        receipt
+            .bloom
+            .as_bytes()
+            .iter()
+            .map(|&byte| U256::from(byte)),
+    );
+    value.push(payload_len(&rlp::encode_list(&receipt.logs)).into());
+    value.push(receipt.logs.len().into());
+    value.extend(logs);
+
+    let mut prover_inputs = vec![value.len().into()];
+    prover_inputs.extend(value);
+    prover_inputs
+}
+
 fn empty_nibbles() -> Nibbles {
     Nibbles {
         count: 0,
@@ -1,3 +1,4 @@
+use anyhow::Result;
 use plonky2::field::extension::Extendable;
 use plonky2::fri::proof::{FriProof, FriProofTarget};
 use plonky2::hash::hash_types::RichField;
@@ -5,7 +6,7 @@ use plonky2::iop::challenger::{Challenger, RecursiveChallenger};
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
 
-use crate::all_stark::{AllStark, NUM_TABLES};
+use crate::all_stark::AllStark;
 use crate::config::StarkConfig;
 use crate::permutation::{
     get_grand_product_challenge_set, get_n_grand_product_challenge_sets,
@@ -19,14 +20,15 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> A
         &self,
         all_stark: &AllStark<F, D>,
         config: &StarkConfig,
-    ) -> AllProofChallenges<F, D> {
+    ) -> Result<AllProofChallenges<F, D>> {
+        let degree_bits = self.degree_bits(config)?;
         let mut challenger = Challenger::<F, C::Hasher>::new();
 
         for proof in &self.stark_proofs {
             challenger.observe_cap(&proof.proof.trace_cap);
         }
 
-        // TODO: Observe public values.
+        challenger.observe_elements(&self.public_values.to_public_inputs());
 
         let ctl_challenges =
             get_grand_product_challenge_set(&mut challenger, config.num_challenges);
@@ -34,55 +36,19 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> A
         let num_permutation_zs = all_stark.nums_permutation_zs(config);
         let num_permutation_batch_sizes = all_stark.permutation_batch_sizes();
 
-        AllProofChallenges {
+        Ok(AllProofChallenges {
             stark_challenges: core::array::from_fn(|i| {
                 challenger.compact();
                 self.stark_proofs[i].proof.get_challenges(
                     &mut challenger,
                     num_permutation_zs[i] > 0,
                     num_permutation_batch_sizes[i],
+                    degree_bits[i],
                     config,
                 )
             }),
             ctl_challenges,
-        }
-    }
-
-    #[allow(unused)] // TODO: should be used soon
-    pub(crate) fn get_challenger_states(
-        &self,
-        all_stark: &AllStark<F, D>,
-        config: &StarkConfig,
-    ) -> AllChallengerState<F, D> {
-        let mut challenger = Challenger::<F, C::Hasher>::new();
-
-        for proof in &self.stark_proofs {
-            challenger.observe_cap(&proof.proof.trace_cap);
-        }
-
-        // TODO: Observe public values.
-
-        let ctl_challenges =
-            get_grand_product_challenge_set(&mut challenger, config.num_challenges);
-
-        let num_permutation_zs = all_stark.nums_permutation_zs(config);
-        let num_permutation_batch_sizes = all_stark.permutation_batch_sizes();
-
-        let mut challenger_states = vec![challenger.compact()];
-        for i in 0..NUM_TABLES {
-            self.stark_proofs[i].proof.get_challenges(
-                &mut challenger,
-                num_permutation_zs[i] > 0,
-                num_permutation_batch_sizes[i],
-                config,
-            );
-            challenger_states.push(challenger.compact());
-        }
-
-        AllChallengerState {
-            states: challenger_states.try_into().unwrap(),
-            ctl_challenges,
-        }
+        })
     }
 }
 
@@ -91,16 +57,16 @@ where
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
 {
-    /// Computes all Fiat-Shamir challenges used in the STARK proof.
+    /// Computes all Fiat-Shamir challenges used in the STARK proof of a table of `2^degree_bits`
+    /// rows.
     pub(crate) fn get_challenges(
         &self,
         challenger: &mut Challenger<F, C::Hasher>,
         stark_use_permutation: bool,
         stark_permutation_batch_size: usize,
+        degree_bits: usize,
         config: &StarkConfig,
     ) -> StarkProofChallenges<F, D> {
-        let degree_bits = self.recover_degree_bits(config);
-
         let StarkProof {
             permutation_ctl_zs_cap,
             quotient_polys_cap,
@@ -142,7 +108,7 @@ where
                 commit_phase_merkle_caps,
                 final_poly,
                 *pow_witness,
-                degree_bits,
+                degree_bits + config.blinding_degree_bits(),
                 &config.fri_config,
             ),
         }
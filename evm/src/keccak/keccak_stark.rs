@@ -608,6 +608,7 @@ mod tests {
     use plonky2::fri::oracle::PolynomialBatch;
     use plonky2::iop::challenger::Challenger;
     use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2::plonk::prover_handle::ProverHandle;
     use plonky2::timed;
     use plonky2::util::timing::TimingTree;
     use tiny_keccak::keccakf;
@@ -617,7 +618,7 @@ mod tests {
     use crate::keccak::columns::reg_output_limb;
     use crate::keccak::keccak_stark::{KeccakStark, NUM_INPUTS, NUM_ROUNDS};
     use crate::permutation::GrandProductChallenge;
-    use crate::prover::prove_single_table;
+    use crate::prover::{prove_single_table, EvmCheckpoint};
     use crate::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
 
     #[test]
@@ -740,6 +741,8 @@ mod tests {
             &trace_commitments,
             &ctl_data,
             &mut Challenger::new(),
+            &mut EvmCheckpoint::disabled(&ProverHandle::default()),
+            &[&trace_commitments],
             &mut timing,
         )?;
 
@@ -9,6 +9,8 @@ pub(crate) const KECCAK_RATE_BYTES: usize = 136;
 pub(crate) const KECCAK_RATE_U32S: usize = KECCAK_RATE_BYTES / 4;
 pub(crate) const KECCAK_CAPACITY_BYTES: usize = 64;
 pub(crate) const KECCAK_CAPACITY_U32S: usize = KECCAK_CAPACITY_BYTES / 4;
+pub(crate) const KECCAK_DIGEST_BYTES: usize = 32;
+pub(crate) const KECCAK_DIGEST_U32S: usize = KECCAK_DIGEST_BYTES / 4;
 
 #[repr(C)]
 #[derive(Eq, PartialEq, Debug)]
@@ -57,6 +59,21 @@ pub(crate) struct KeccakSpongeColumnsView<T: Copy> {
     /// The entire state (rate + capacity) of the sponge, encoded as 32-bit chunks, after the
     /// permutation is applied.
     pub updated_state_u32s: [T; KECCAK_WIDTH_U32S],
+
+    /// The first `KECCAK_DIGEST_BYTES` bytes of the state after the permutation is applied, i.e.
+    /// the digest if this is a final block. They are range-checked, and their little-endian
+    /// combinations must match the first `KECCAK_DIGEST_U32S` entries of `updated_state_u32s`.
+    pub updated_digest_state_bytes: [T; KECCAK_DIGEST_BYTES],
+
+    /// A counter used to range-check the digest bytes; it is 0 in the first row, increases by 0 or
+    /// 1 in each row, and ends at 255.
+    pub range_counter: T,
+
+    /// The digest bytes, permuted for the range-check lookups.
+    pub digest_bytes_permuted: [T; KECCAK_DIGEST_BYTES],
+
+    /// `range_counter`, permuted for the range-check lookup of each digest byte.
+    pub range_counter_permuted: [T; KECCAK_DIGEST_BYTES],
 }
 
 // `u8` is guaranteed to have a `size_of` of 1.
@@ -12,20 +12,35 @@ use plonky2::hash::hash_types::RichField;
 use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::timed;
 use plonky2::util::timing::TimingTree;
+use plonky2::util::transpose;
 use plonky2_util::ceil_div_usize;
 
 use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
 use crate::cpu::kernel::keccak_util::keccakf_u32s;
 use crate::cross_table_lookup::Column;
 use crate::keccak_sponge::columns::*;
+use crate::lookup::{eval_lookups, eval_lookups_circuit, permuted_cols};
+use crate::permutation::PermutationPair;
 use crate::stark::Stark;
-use crate::util::trace_rows_to_poly_values;
 use crate::vars::{StarkEvaluationTargets, StarkEvaluationVars};
 use crate::witness::memory::MemoryAddress;
 
+/// Strict upper bound for the range-checked digest bytes.
+const BYTE_RANGE_MAX: usize = 1 << 8;
+
 pub(crate) fn ctl_looked_data<F: Field>() -> Vec<Column<F>> {
     let cols = KECCAK_SPONGE_COL_MAP;
-    let outputs = Column::singles(&cols.updated_state_u32s[..8]);
+    // The CPU sees the digest as a big-endian `U256`, split into 32-bit little-endian limbs. So
+    // limb `i` is made of the digest bytes `28 - 4i..32 - 4i`, the first of which is the most
+    // significant.
+    let outputs = (0..KECCAK_DIGEST_U32S).rev().map(|i| {
+        Column::linear_combination(
+            cols.updated_digest_state_bytes[i * 4..(i + 1) * 4]
+                .iter()
+                .enumerate()
+                .map(|(j, &c)| (c, F::from_canonical_u64(1 << (24 - 8 * j)))),
+        )
+    });
     Column::singles([
         cols.context,
         cols.segment,
@@ -137,7 +152,7 @@ pub(crate) fn ctl_looking_memory_filter<F: Field>(i: usize) -> Column<F> {
     // - this is a full input block, or
     // - this is a final block of length `i` or greater
     let cols = KECCAK_SPONGE_COL_MAP;
-    Column::sum(once(&cols.is_full_input_block).chain(&cols.is_final_input_len[i..]))
+    Column::sum(once(&cols.is_full_input_block).chain(&cols.is_final_input_len[i + 1..]))
 }
 
 /// CTL filter for looking at XORs in the logic table.
@@ -184,13 +199,35 @@ impl<F: RichField + Extendable<D>, const D: usize> KeccakSpongeStark<F, D> {
             self.generate_trace_rows(operations, min_rows)
         );
 
-        let trace_polys = timed!(
-            timing,
-            "convert to PolynomialValues",
-            trace_rows_to_poly_values(trace_rows)
-        );
+        let trace_row_vecs: Vec<_> = trace_rows.into_iter().map(|row| row.to_vec()).collect();
+        let mut trace_col_vecs = transpose(&trace_row_vecs);
+
+        // The range checks work better in column-major form.
+        Self::generate_range_checks(&mut trace_col_vecs);
 
-        trace_polys
+        trace_col_vecs
+            .into_iter()
+            .map(|column| PolynomialValues::new(column))
+            .collect()
+    }
+
+    /// Generates the `range_counter` column, and the permuted columns used to range-check the
+    /// digest bytes, given a trace in column-major form.
+    fn generate_range_checks(cols: &mut [Vec<F>]) {
+        let height = cols[0].len();
+        let counter = KECCAK_SPONGE_COL_MAP.range_counter;
+        cols[counter] = (0..height)
+            .map(|i| F::from_canonical_usize(i.min(BYTE_RANGE_MAX - 1)))
+            .collect();
+
+        for i in 0..KECCAK_DIGEST_BYTES {
+            let (col_perm, table_perm) = permuted_cols(
+                &cols[KECCAK_SPONGE_COL_MAP.updated_digest_state_bytes[i]],
+                &cols[counter],
+            );
+            cols[KECCAK_SPONGE_COL_MAP.digest_bytes_permuted[i]] = col_perm;
+            cols[KECCAK_SPONGE_COL_MAP.range_counter_permuted[i]] = table_perm;
+        }
     }
 
     fn generate_trace_rows(
@@ -202,7 +239,12 @@ impl<F: RichField + Extendable<D>, const D: usize> KeccakSpongeStark<F, D> {
         for op in operations {
             rows.extend(self.generate_rows_for_op(op));
         }
-        let padded_rows = rows.len().max(min_rows).next_power_of_two();
+        // We need at least `BYTE_RANGE_MAX` rows for the range checks.
+        let padded_rows = rows
+            .len()
+            .max(min_rows)
+            .max(BYTE_RANGE_MAX)
+            .next_power_of_two();
         for _ in rows.len()..padded_rows {
             rows.push(self.generate_padding_row());
         }
@@ -346,6 +388,13 @@ impl<F: RichField + Extendable<D>, const D: usize> KeccakSpongeStark<F, D> {
 
         keccakf_u32s(&mut sponge_state);
         row.updated_state_u32s = sponge_state.map(F::from_canonical_u32);
+        let digest_bytes = sponge_state[..KECCAK_DIGEST_U32S]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect_vec();
+        for (col, byte) in row.updated_digest_state_bytes.iter_mut().zip(digest_bytes) {
+            *col = F::from_canonical_u8(byte);
+        }
     }
 
     fn generate_padding_row(&self) -> [F; NUM_KECCAK_SPONGE_COLUMNS] {
@@ -361,45 +410,358 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for KeccakSpongeS
     fn eval_packed_generic<FE, P, const D2: usize>(
         &self,
         vars: StarkEvaluationVars<FE, P, { Self::COLUMNS }>,
-        _yield_constr: &mut ConstraintConsumer<P>,
+        yield_constr: &mut ConstraintConsumer<P>,
     ) where
         FE: FieldExtension<D2, BaseField = F>,
         P: PackedField<Scalar = FE>,
     {
-        let _local_values: &KeccakSpongeColumnsView<P> = vars.local_values.borrow();
+        let local_values: &KeccakSpongeColumnsView<P> = vars.local_values.borrow();
+        let next_values: &KeccakSpongeColumnsView<P> = vars.next_values.borrow();
+
+        // Range-check the digest bytes.
+        for i in 0..KECCAK_DIGEST_BYTES {
+            eval_lookups(
+                vars,
+                yield_constr,
+                KECCAK_SPONGE_COL_MAP.digest_bytes_permuted[i],
+                KECCAK_SPONGE_COL_MAP.range_counter_permuted[i],
+            );
+        }
 
-        // TODO: Each flag (full-input block, final block or implied dummy flag) must be boolean.
-        // TODO: before_rate_bits, block_bits and is_final_input_len must contain booleans.
+        // The range counter must start at 0, increment by 0 or 1 in each row, and end at
+        // `BYTE_RANGE_MAX - 1`.
+        let rc1 = local_values.range_counter;
+        let rc2 = next_values.range_counter;
+        yield_constr.constraint_first_row(rc1);
+        let incr = rc2 - rc1;
+        yield_constr.constraint_transition(incr * incr - incr);
+        let range_max = P::Scalar::from_canonical_usize(BYTE_RANGE_MAX - 1);
+        yield_constr.constraint_last_row(rc1 - range_max);
+
+        // The digest bytes must be the little-endian decomposition of the first entries of the
+        // updated state.
+        for i in 0..KECCAK_DIGEST_U32S {
+            let recombined: P = local_values.updated_digest_state_bytes[i * 4..(i + 1) * 4]
+                .iter()
+                .enumerate()
+                .map(|(j, &byte)| byte * P::Scalar::from_canonical_u64(1 << (8 * j)))
+                .sum();
+            yield_constr.constraint(local_values.updated_state_u32s[i] - recombined);
+        }
 
-        // TODO: Sum of is_final_input_len should equal is_final_block (which will be 0 or 1).
+        // Each flag (full-input block, final block or implied dummy flag) must be boolean.
+        let is_full_input_block = local_values.is_full_input_block;
+        yield_constr.constraint(is_full_input_block * (is_full_input_block - P::ONES));
 
-        // TODO: If this is the first row, the original sponge state should be 0 and already_absorbed_bytes = 0.
-        // TODO: If this is a final block, the next row's original sponge state should be 0 and already_absorbed_bytes = 0.
+        let is_final_block = local_values.is_final_block;
+        yield_constr.constraint(is_final_block * (is_final_block - P::ONES));
 
-        // TODO: If this is a full-input block, the next row's address, time and len must match.
-        // TODO: If this is a full-input block, the next row's "before" should match our "after" state.
-        // TODO: If this is a full-input block, the next row's already_absorbed_bytes should be ours plus 136.
+        let is_dummy = P::ONES - is_full_input_block - is_final_block;
+        yield_constr.constraint(is_full_input_block * is_final_block);
 
-        // TODO: A dummy row is always followed by another dummy row, so the prover can't put dummy rows "in between" to avoid the above checks.
+        // is_final_input_len must contain booleans, which sum to is_final_block.
+        for &is_final_len in local_values.is_final_input_len.iter() {
+            yield_constr.constraint(is_final_len * (is_final_len - P::ONES));
+        }
+        let is_final_input_len_sum: P = local_values.is_final_input_len.iter().copied().sum();
+        yield_constr.constraint(is_final_input_len_sum - is_final_block);
+
+        // If this is the first row, the original sponge state should be 0 and
+        // already_absorbed_bytes = 0.
+        let already_absorbed_bytes = local_values.already_absorbed_bytes;
+        yield_constr.constraint_first_row(already_absorbed_bytes);
+        for &original_rate_elem in local_values.original_rate_u32s.iter() {
+            yield_constr.constraint_first_row(original_rate_elem);
+        }
+        for &original_capacity_elem in local_values.original_capacity_u32s.iter() {
+            yield_constr.constraint_first_row(original_capacity_elem);
+        }
+
+        // If this is a final block, the next row's original sponge state should be 0 and
+        // already_absorbed_bytes = 0.
+        yield_constr.constraint_transition(is_final_block * next_values.already_absorbed_bytes);
+        for &original_rate_elem in next_values.original_rate_u32s.iter() {
+            yield_constr.constraint_transition(is_final_block * original_rate_elem);
+        }
+        for &original_capacity_elem in next_values.original_capacity_u32s.iter() {
+            yield_constr.constraint_transition(is_final_block * original_capacity_elem);
+        }
+
+        // If this is a full-input block, the next row's address, time and len must match.
+        yield_constr.constraint_transition(
+            is_full_input_block * (local_values.context - next_values.context),
+        );
+        yield_constr.constraint_transition(
+            is_full_input_block * (local_values.segment - next_values.segment),
+        );
+        yield_constr
+            .constraint_transition(is_full_input_block * (local_values.virt - next_values.virt));
+        yield_constr.constraint_transition(
+            is_full_input_block * (local_values.timestamp - next_values.timestamp),
+        );
+        yield_constr
+            .constraint_transition(is_full_input_block * (local_values.len - next_values.len));
+
+        // If this is a full-input block, the next row's "before" should match our "after" state.
+        for (&current_after, &next_before) in local_values.updated_state_u32s[..KECCAK_RATE_U32S]
+            .iter()
+            .zip(next_values.original_rate_u32s.iter())
+        {
+            yield_constr.constraint_transition(is_full_input_block * (next_before - current_after));
+        }
+        for (&current_after, &next_before) in local_values.updated_state_u32s[KECCAK_RATE_U32S..]
+            .iter()
+            .zip(next_values.original_capacity_u32s.iter())
+        {
+            yield_constr.constraint_transition(is_full_input_block * (next_before - current_after));
+        }
+
+        // If this is a full-input block, the next row's already_absorbed_bytes should be ours plus
+        // `KECCAK_RATE_BYTES`.
+        yield_constr.constraint_transition(
+            is_full_input_block
+                * (already_absorbed_bytes + P::Scalar::from_canonical_usize(KECCAK_RATE_BYTES)
+                    - next_values.already_absorbed_bytes),
+        );
 
-        // TODO: is_final_input_len implies `len - already_absorbed == i`.
+        // A dummy row is always followed by another dummy row, so the prover can't put dummy rows
+        // "in between" to avoid the above checks.
+        yield_constr.constraint_transition(
+            is_dummy * (next_values.is_full_input_block + next_values.is_final_block),
+        );
+
+        // If this is a final block, is_final_input_len implies `len - already_absorbed == i`.
+        let offset = local_values.len - already_absorbed_bytes;
+        for (i, &is_final_len) in local_values.is_final_input_len.iter().enumerate() {
+            let entry_match = offset - P::Scalar::from_canonical_usize(i);
+            yield_constr.constraint(is_final_len * entry_match);
+        }
+
+        // If this is a final block, the block must be padded with the pad10*1 rule: the first byte
+        // after the input is 1, the following ones are 0, and the most significant bit of the last
+        // byte is set. Both 1s land in the last byte if there is a single byte of padding.
+        let mut is_after_first_padding_byte = P::ZEROS;
+        for (&block_byte, &is_final_len) in local_values.block_bytes[..KECCAK_RATE_BYTES - 1]
+            .iter()
+            .zip(local_values.is_final_input_len.iter())
+        {
+            yield_constr.constraint(is_after_first_padding_byte * block_byte);
+            yield_constr.constraint(is_final_len * (block_byte - P::ONES));
+            is_after_first_padding_byte += is_final_len;
+        }
+        let last_byte = local_values.block_bytes[KECCAK_RATE_BYTES - 1];
+        let expected_last_byte = P::Scalar::from_canonical_u8(0b10000000);
+        yield_constr.constraint(
+            is_final_block
+                * (last_byte
+                    - expected_last_byte
+                    - local_values.is_final_input_len[KECCAK_RATE_BYTES - 1]),
+        );
     }
 
     fn eval_ext_circuit(
         &self,
-        _builder: &mut plonky2::plonk::circuit_builder::CircuitBuilder<F, D>,
+        builder: &mut plonky2::plonk::circuit_builder::CircuitBuilder<F, D>,
         vars: StarkEvaluationTargets<D, { Self::COLUMNS }>,
-        _yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
     ) {
-        let _local_values: &KeccakSpongeColumnsView<ExtensionTarget<D>> =
-            vars.local_values.borrow();
+        let local_values: &KeccakSpongeColumnsView<ExtensionTarget<D>> = vars.local_values.borrow();
+        let next_values: &KeccakSpongeColumnsView<ExtensionTarget<D>> = vars.next_values.borrow();
+
+        // Range-check the digest bytes.
+        for i in 0..KECCAK_DIGEST_BYTES {
+            eval_lookups_circuit(
+                builder,
+                vars,
+                yield_constr,
+                KECCAK_SPONGE_COL_MAP.digest_bytes_permuted[i],
+                KECCAK_SPONGE_COL_MAP.range_counter_permuted[i],
+            );
+        }
+
+        // The range counter must start at 0, increment by 0 or 1 in each row, and end at
+        // `BYTE_RANGE_MAX - 1`.
+        let rc1 = local_values.range_counter;
+        let rc2 = next_values.range_counter;
+        yield_constr.constraint_first_row(builder, rc1);
+        let incr = builder.sub_extension(rc2, rc1);
+        let t = builder.mul_sub_extension(incr, incr, incr);
+        yield_constr.constraint_transition(builder, t);
+        let range_max =
+            builder.constant_extension(F::Extension::from_canonical_usize(BYTE_RANGE_MAX - 1));
+        let t = builder.sub_extension(rc1, range_max);
+        yield_constr.constraint_last_row(builder, t);
+
+        // The digest bytes must be the little-endian decomposition of the first entries of the
+        // updated state.
+        for i in 0..KECCAK_DIGEST_U32S {
+            let recombined = local_values.updated_digest_state_bytes[i * 4..(i + 1) * 4]
+                .iter()
+                .enumerate()
+                .fold(builder.zero_extension(), |acc, (j, &byte)| {
+                    builder.mul_const_add_extension(F::from_canonical_u64(1 << (8 * j)), byte, acc)
+                });
+            let t = builder.sub_extension(local_values.updated_state_u32s[i], recombined);
+            yield_constr.constraint(builder, t);
+        }
+
+        let one = builder.one_extension();
+
+        // Each flag (full-input block, final block or implied dummy flag) must be boolean.
+        let is_full_input_block = local_values.is_full_input_block;
+        let constraint = builder.mul_sub_extension(
+            is_full_input_block,
+            is_full_input_block,
+            is_full_input_block,
+        );
+        yield_constr.constraint(builder, constraint);
+
+        let is_final_block = local_values.is_final_block;
+        let constraint = builder.mul_sub_extension(is_final_block, is_final_block, is_final_block);
+        yield_constr.constraint(builder, constraint);
 
-        // TODO
+        let is_dummy = {
+            let tmp = builder.sub_extension(one, is_final_block);
+            builder.sub_extension(tmp, is_full_input_block)
+        };
+        let constraint = builder.mul_extension(is_full_input_block, is_final_block);
+        yield_constr.constraint(builder, constraint);
+
+        // is_final_input_len must contain booleans, which sum to is_final_block.
+        for &is_final_len in local_values.is_final_input_len.iter() {
+            let constraint = builder.mul_sub_extension(is_final_len, is_final_len, is_final_len);
+            yield_constr.constraint(builder, constraint);
+        }
+        let is_final_input_len_sum = builder.add_many_extension(local_values.is_final_input_len);
+        let constraint = builder.sub_extension(is_final_input_len_sum, is_final_block);
+        yield_constr.constraint(builder, constraint);
+
+        // If this is the first row, the original sponge state should be 0 and
+        // already_absorbed_bytes = 0.
+        let already_absorbed_bytes = local_values.already_absorbed_bytes;
+        yield_constr.constraint_first_row(builder, already_absorbed_bytes);
+        for &original_rate_elem in local_values.original_rate_u32s.iter() {
+            yield_constr.constraint_first_row(builder, original_rate_elem);
+        }
+        for &original_capacity_elem in local_values.original_capacity_u32s.iter() {
+            yield_constr.constraint_first_row(builder, original_capacity_elem);
+        }
+
+        // If this is a final block, the next row's original sponge state should be 0 and
+        // already_absorbed_bytes = 0.
+        let constraint = builder.mul_extension(is_final_block, next_values.already_absorbed_bytes);
+        yield_constr.constraint_transition(builder, constraint);
+        for &original_rate_elem in next_values.original_rate_u32s.iter() {
+            let constraint = builder.mul_extension(is_final_block, original_rate_elem);
+            yield_constr.constraint_transition(builder, constraint);
+        }
+        for &original_capacity_elem in next_values.original_capacity_u32s.iter() {
+            let constraint = builder.mul_extension(is_final_block, original_capacity_elem);
+            yield_constr.constraint_transition(builder, constraint);
+        }
+
+        // If this is a full-input block, the next row's address, time and len must match.
+        for (local, next) in [
+            (local_values.context, next_values.context),
+            (local_values.segment, next_values.segment),
+            (local_values.virt, next_values.virt),
+            (local_values.timestamp, next_values.timestamp),
+            (local_values.len, next_values.len),
+        ] {
+            let diff = builder.sub_extension(local, next);
+            let constraint = builder.mul_extension(is_full_input_block, diff);
+            yield_constr.constraint_transition(builder, constraint);
+        }
+
+        // If this is a full-input block, the next row's "before" should match our "after" state.
+        for (&current_after, &next_before) in local_values.updated_state_u32s[..KECCAK_RATE_U32S]
+            .iter()
+            .zip(next_values.original_rate_u32s.iter())
+        {
+            let diff = builder.sub_extension(next_before, current_after);
+            let constraint = builder.mul_extension(is_full_input_block, diff);
+            yield_constr.constraint_transition(builder, constraint);
+        }
+        for (&current_after, &next_before) in local_values.updated_state_u32s[KECCAK_RATE_U32S..]
+            .iter()
+            .zip(next_values.original_capacity_u32s.iter())
+        {
+            let diff = builder.sub_extension(next_before, current_after);
+            let constraint = builder.mul_extension(is_full_input_block, diff);
+            yield_constr.constraint_transition(builder, constraint);
+        }
+
+        // If this is a full-input block, the next row's already_absorbed_bytes should be ours plus
+        // `KECCAK_RATE_BYTES`.
+        let absorbed_bytes = builder.add_const_extension(
+            already_absorbed_bytes,
+            F::from_canonical_usize(KECCAK_RATE_BYTES),
+        );
+        let absorbed_diff =
+            builder.sub_extension(absorbed_bytes, next_values.already_absorbed_bytes);
+        let constraint = builder.mul_extension(is_full_input_block, absorbed_diff);
+        yield_constr.constraint_transition(builder, constraint);
+
+        // A dummy row is always followed by another dummy row, so the prover can't put dummy rows
+        // "in between" to avoid the above checks.
+        let next_is_not_dummy =
+            builder.add_extension(next_values.is_full_input_block, next_values.is_final_block);
+        let constraint = builder.mul_extension(is_dummy, next_is_not_dummy);
+        yield_constr.constraint_transition(builder, constraint);
+
+        // If this is a final block, is_final_input_len implies `len - already_absorbed == i`.
+        let offset = builder.sub_extension(local_values.len, already_absorbed_bytes);
+        for (i, &is_final_len) in local_values.is_final_input_len.iter().enumerate() {
+            let index = builder.constant_extension(F::from_canonical_usize(i).into());
+            let entry_match = builder.sub_extension(offset, index);
+            let constraint = builder.mul_extension(is_final_len, entry_match);
+            yield_constr.constraint(builder, constraint);
+        }
+
+        // If this is a final block, the block must be padded with the pad10*1 rule: the first byte
+        // after the input is 1, the following ones are 0, and the most significant bit of the last
+        // byte is set. Both 1s land in the last byte if there is a single byte of padding.
+        let mut is_after_first_padding_byte = builder.zero_extension();
+        for (&block_byte, &is_final_len) in local_values.block_bytes[..KECCAK_RATE_BYTES - 1]
+            .iter()
+            .zip(local_values.is_final_input_len.iter())
+        {
+            let constraint = builder.mul_extension(is_after_first_padding_byte, block_byte);
+            yield_constr.constraint(builder, constraint);
+            let constraint = builder.mul_sub_extension(is_final_len, block_byte, is_final_len);
+            yield_constr.constraint(builder, constraint);
+            is_after_first_padding_byte =
+                builder.add_extension(is_after_first_padding_byte, is_final_len);
+        }
+        let last_byte = local_values.block_bytes[KECCAK_RATE_BYTES - 1];
+        let last_byte_diff =
+            builder.add_const_extension(last_byte, -F::from_canonical_u8(0b10000000));
+        let last_byte_diff = builder.sub_extension(
+            last_byte_diff,
+            local_values.is_final_input_len[KECCAK_RATE_BYTES - 1],
+        );
+        let constraint = builder.mul_extension(is_final_block, last_byte_diff);
+        yield_constr.constraint(builder, constraint);
     }
 
     fn constraint_degree(&self) -> usize {
         3
     }
+
+    fn permutation_pairs(&self) -> Vec<PermutationPair> {
+        let cols = KECCAK_SPONGE_COL_MAP;
+        (0..KECCAK_DIGEST_BYTES)
+            .flat_map(|i| {
+                [
+                    PermutationPair::singletons(
+                        cols.updated_digest_state_bytes[i],
+                        cols.digest_bytes_permuted[i],
+                    ),
+                    PermutationPair::singletons(cols.range_counter_permuted[i], cols.range_counter),
+                ]
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -410,13 +772,17 @@ mod tests {
     use itertools::Itertools;
     use keccak_hash::keccak;
     use plonky2::field::goldilocks_field::GoldilocksField;
-    use plonky2::field::types::PrimeField64;
+    use plonky2::field::polynomial::PolynomialValues;
+    use plonky2::field::types::{Field, PrimeField64};
     use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2::util::timing::TimingTree;
 
-    use crate::keccak_sponge::columns::KeccakSpongeColumnsView;
+    use crate::keccak_sponge::columns::{KeccakSpongeColumnsView, KECCAK_SPONGE_COL_MAP};
     use crate::keccak_sponge::keccak_sponge_stark::{KeccakSpongeOp, KeccakSpongeStark};
     use crate::memory::segments::Segment;
-    use crate::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
+    use crate::stark_testing::{
+        test_stark_circuit_constraints, test_stark_low_degree, test_stark_trace_constraints,
+    };
     use crate::witness::memory::MemoryAddress;
 
     #[test]
@@ -471,4 +837,121 @@ mod tests {
         assert_eq!(output, expected_output.0);
         Ok(())
     }
+
+    const D: usize = 2;
+    type F = GoldilocksField;
+    type S = KeccakSpongeStark<F, D>;
+
+    /// Generates a trace hashing a 300-byte input, which spans two full-input blocks and a final
+    /// block in rows 0 to 2, and a 135-byte input, whose final block in row 3 has a single
+    /// padding byte.
+    fn honest_trace() -> Vec<PolynomialValues<F>> {
+        let ops = [300, 135]
+            .into_iter()
+            .enumerate()
+            .map(|(i, len)| KeccakSpongeOp {
+                base_address: MemoryAddress {
+                    context: 0,
+                    segment: Segment::MainMemory as usize,
+                    virt: 1000 * i,
+                },
+                timestamp: 10 * i,
+                input: (0..len).map(|j| j as u8).collect(),
+            })
+            .collect();
+        S::default().generate_trace(ops, 8, &mut TimingTree::default())
+    }
+
+    /// Tampers with an honest trace, and checks that the result violates the constraints.
+    fn test_tampered_trace(tamper: impl FnOnce(&mut [PolynomialValues<F>])) {
+        let mut trace = honest_trace();
+        tamper(&mut trace);
+        assert!(test_stark_trace_constraints(&S::default(), &trace).is_err());
+    }
+
+    #[test]
+    fn test_honest_trace() -> Result<()> {
+        test_stark_trace_constraints(&S::default(), &honest_trace())
+    }
+
+    #[test]
+    fn test_non_boolean_flag() {
+        test_tampered_trace(|trace| {
+            trace[KECCAK_SPONGE_COL_MAP.is_full_input_block].values[0] = F::TWO;
+        });
+    }
+
+    #[test]
+    fn test_full_and_final_block() {
+        test_tampered_trace(|trace| {
+            trace[KECCAK_SPONGE_COL_MAP.is_final_block].values[0] = F::ONE;
+        });
+    }
+
+    #[test]
+    fn test_nonzero_initial_state() {
+        test_tampered_trace(|trace| {
+            trace[KECCAK_SPONGE_COL_MAP.original_capacity_u32s[0]].values[0] = F::ONE;
+        });
+    }
+
+    #[test]
+    fn test_nonzero_state_after_final_block() {
+        test_tampered_trace(|trace| {
+            trace[KECCAK_SPONGE_COL_MAP.original_rate_u32s[0]].values[3] = F::ONE;
+        });
+    }
+
+    #[test]
+    fn test_broken_block_chaining() {
+        test_tampered_trace(|trace| {
+            trace[KECCAK_SPONGE_COL_MAP.original_rate_u32s[0]].values[1] += F::ONE;
+        });
+    }
+
+    #[test]
+    fn test_address_change_between_blocks() {
+        test_tampered_trace(|trace| {
+            trace[KECCAK_SPONGE_COL_MAP.virt].values[1] += F::ONE;
+        });
+    }
+
+    #[test]
+    fn test_wrong_already_absorbed_bytes() {
+        test_tampered_trace(|trace| {
+            trace[KECCAK_SPONGE_COL_MAP.already_absorbed_bytes].values[1] += F::ONE;
+        });
+    }
+
+    #[test]
+    fn test_dummy_row_before_real_row() {
+        test_tampered_trace(|trace| {
+            for column in trace.iter_mut() {
+                column.values[0] = F::ZERO;
+            }
+        });
+    }
+
+    #[test]
+    fn test_wrong_final_input_len() {
+        test_tampered_trace(|trace| {
+            trace[KECCAK_SPONGE_COL_MAP.is_final_input_len[28]].values[2] = F::ZERO;
+            trace[KECCAK_SPONGE_COL_MAP.is_final_input_len[27]].values[2] = F::ONE;
+        });
+    }
+
+    #[test]
+    fn test_forged_padding() {
+        test_tampered_trace(|trace| {
+            trace[KECCAK_SPONGE_COL_MAP.block_bytes[29]].values[2] = F::ONE;
+        });
+    }
+
+    #[test]
+    fn test_forged_single_padding_byte() {
+        test_tampered_trace(|trace| {
+            trace[KECCAK_SPONGE_COL_MAP.block_bytes[135]].values[3] =
+                F::from_canonical_u8(0b10000000);
+        });
+    }
 }
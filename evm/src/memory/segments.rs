@@ -43,10 +43,20 @@ pub enum Segment {
     BnWnafA = 19,
     BnWnafB = 20,
     BnTableQ = 21,
+    /// The hashes of the 256 most recent ancestors of the current block, oldest first.
+    BlockHashes = 22,
+    /// Pointers to the logs emitted by the current transaction, within `LogsData`.
+    Logs = 23,
+    /// The logs emitted by the current transaction, in the format in which they are stored in a
+    /// receipt; see `process_receipt`.
+    LogsData = 24,
+    /// The in-place writes to `TrieData` made so far, as (pointer, old value) pairs, so that they
+    /// can be undone when a context reverts.
+    Journal = 25,
 }
 
 impl Segment {
-    pub(crate) const COUNT: usize = 22;
+    pub(crate) const COUNT: usize = 26;
 
     pub(crate) fn all() -> [Self; Self::COUNT] {
         [
@@ -72,6 +82,10 @@ impl Segment {
             Self::BnWnafA,
             Self::BnWnafB,
             Self::BnTableQ,
+            Self::BlockHashes,
+            Self::Logs,
+            Self::LogsData,
+            Self::Journal,
         ]
     }
 
@@ -100,6 +114,10 @@ impl Segment {
             Segment::BnWnafA => "SEGMENT_KERNEL_BN_WNAF_A",
             Segment::BnWnafB => "SEGMENT_KERNEL_BN_WNAF_B",
             Segment::BnTableQ => "SEGMENT_KERNEL_BN_TABLE_Q",
+            Segment::BlockHashes => "SEGMENT_BLOCK_HASHES",
+            Segment::Logs => "SEGMENT_LOGS",
+            Segment::LogsData => "SEGMENT_LOGS_DATA",
+            Segment::Journal => "SEGMENT_JOURNAL",
         }
     }
 
@@ -128,6 +146,10 @@ impl Segment {
             Segment::BnWnafA => 8,
             Segment::BnWnafB => 8,
             Segment::BnTableQ => 256,
+            Segment::BlockHashes => 256,
+            Segment::Logs => 256,
+            Segment::LogsData => 256,
+            Segment::Journal => 256,
         }
     }
 }
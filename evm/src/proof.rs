@@ -1,6 +1,8 @@
+use anyhow::{anyhow, Result};
 use ethereum_types::{Address, H256, U256};
 use itertools::Itertools;
 use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::types::Field;
 use plonky2::fri::oracle::PolynomialBatch;
 use plonky2::fri::proof::{FriChallenges, FriChallengesTarget, FriProof, FriProofTarget};
 use plonky2::fri::structure::{
@@ -10,14 +12,19 @@ use plonky2::hash::hash_types::{MerkleCapTarget, RichField};
 use plonky2::hash::hashing::SPONGE_WIDTH;
 use plonky2::hash::merkle_tree::MerkleCap;
 use plonky2::iop::ext_target::ExtensionTarget;
-use plonky2::iop::target::Target;
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::config::GenericConfig;
 use plonky2_maybe_rayon::*;
 use serde::{Deserialize, Serialize};
 
 use crate::all_stark::NUM_TABLES;
 use crate::config::StarkConfig;
+use crate::cpu::kernel::constants::global_metadata::GlobalMetadata;
+use crate::memory::segments::Segment;
+use crate::memory::VALUE_LIMBS;
 use crate::permutation::GrandProductChallengeSet;
+use crate::util::{h160_limbs, h256_limbs};
 
 /// A STARK proof for each table, plus some metadata used to create recursive wrapper proofs.
 #[derive(Debug, Clone)]
@@ -28,8 +35,17 @@ pub struct AllProof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, co
 }
 
 impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> AllProof<F, C, D> {
-    pub fn degree_bits(&self, config: &StarkConfig) -> [usize; NUM_TABLES] {
-        core::array::from_fn(|i| self.stark_proofs[i].proof.recover_degree_bits(config))
+    /// Recovers the degree of each table from the depth of the Merkle proofs of its opening proof,
+    /// failing if they are missing or too short.
+    pub fn degree_bits(&self, config: &StarkConfig) -> Result<[usize; NUM_TABLES]> {
+        let mut degree_bits = [0; NUM_TABLES];
+        for (i, bits) in degree_bits.iter_mut().enumerate() {
+            *bits = self.stark_proofs[i]
+                .proof
+                .try_recover_degree_bits(config)
+                .ok_or_else(|| anyhow!("Opening proof of table {i} is malformed"))?;
+        }
+        Ok(degree_bits)
     }
 }
 
@@ -38,23 +54,157 @@ pub(crate) struct AllProofChallenges<F: RichField + Extendable<D>, const D: usiz
     pub ctl_challenges: GrandProductChallengeSet<F>,
 }
 
-#[allow(unused)] // TODO: should be used soon
-pub(crate) struct AllChallengerState<F: RichField + Extendable<D>, const D: usize> {
-    /// Sponge state of the challenger before starting each proof,
-    /// along with the final state after all proofs are done. This final state isn't strictly needed.
-    pub states: [[F; SPONGE_WIDTH]; NUM_TABLES + 1],
-    pub ctl_challenges: GrandProductChallengeSet<F>,
-}
+/// The number of the block's most recent ancestors whose hashes the `BLOCKHASH` instruction can read.
+pub const NUM_BLOCK_HASHES: usize = 256;
 
 /// Memory values which are public.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct PublicValues {
     pub trie_roots_before: TrieRoots,
     pub trie_roots_after: TrieRoots,
     pub block_metadata: BlockMetadata,
+    pub extra_block_data: ExtraBlockData,
 }
 
-#[derive(Debug, Clone, Default)]
+impl PublicValues {
+    /// The public values as field elements, in the order in which `add_virtual_public_values`
+    /// registers them as public inputs.
+    pub(crate) fn to_public_inputs<F: Field>(&self) -> Vec<F> {
+        let trie_roots = |roots: &TrieRoots| {
+            [
+                roots.state_root,
+                roots.transactions_root,
+                roots.receipts_root,
+            ]
+            .into_iter()
+            .flat_map(h256_limbs::<F>)
+        };
+        let BlockMetadata {
+            block_beneficiary,
+            block_timestamp,
+            block_number,
+            block_difficulty,
+            block_gaslimit,
+            block_chain_id,
+            block_base_fee,
+        } = &self.block_metadata;
+        let ExtraBlockData {
+            txn_number_before,
+            txn_number_after,
+            gas_used_before,
+            gas_used_after,
+            block_hashes,
+        } = &self.extra_block_data;
+
+        trie_roots(&self.trie_roots_before)
+            .chain(trie_roots(&self.trie_roots_after))
+            .chain(h160_limbs(*block_beneficiary))
+            .chain(
+                [
+                    block_timestamp,
+                    block_number,
+                    block_difficulty,
+                    block_gaslimit,
+                    block_chain_id,
+                    block_base_fee,
+                    txn_number_before,
+                    txn_number_after,
+                    gas_used_before,
+                    gas_used_after,
+                ]
+                .map(|x| F::from_canonical_u64(x.as_u64())),
+            )
+            .chain(block_hashes.iter().flat_map(|&hash| h256_limbs::<F>(hash)))
+            .collect()
+    }
+
+    /// The kernel's memory cells which hold the public values, along with their values: global
+    /// metadata fields, and the block hashes. The prover writes them to memory before the kernel
+    /// starts, and the verifier accounts for these writes in the memory CTL.
+    pub(crate) fn memory_values(&self) -> Vec<(Segment, usize, U256)> {
+        let BlockMetadata {
+            block_beneficiary,
+            block_timestamp,
+            block_number,
+            block_difficulty,
+            block_gaslimit,
+            block_chain_id,
+            block_base_fee,
+        } = &self.block_metadata;
+        let TrieRoots {
+            state_root: state_root_before,
+            transactions_root: transactions_root_before,
+            receipts_root: receipts_root_before,
+        } = &self.trie_roots_before;
+        let TrieRoots {
+            state_root: state_root_after,
+            transactions_root: transactions_root_after,
+            receipts_root: receipts_root_after,
+        } = &self.trie_roots_after;
+        let ExtraBlockData {
+            txn_number_before,
+            txn_number_after,
+            gas_used_before,
+            gas_used_after,
+            block_hashes,
+        } = &self.extra_block_data;
+        let h256 = |h: &H256| U256::from_big_endian(&h.0);
+
+        let global_metadata = [
+            (
+                GlobalMetadata::BlockBeneficiary,
+                U256::from_big_endian(&block_beneficiary.0),
+            ),
+            (GlobalMetadata::BlockTimestamp, *block_timestamp),
+            (GlobalMetadata::BlockNumber, *block_number),
+            (GlobalMetadata::BlockDifficulty, *block_difficulty),
+            (GlobalMetadata::BlockGasLimit, *block_gaslimit),
+            (GlobalMetadata::BlockChainId, *block_chain_id),
+            (GlobalMetadata::BlockBaseFee, *block_base_fee),
+            (
+                GlobalMetadata::StateTrieRootDigestBefore,
+                h256(state_root_before),
+            ),
+            (
+                GlobalMetadata::TransactionTrieRootDigestBefore,
+                h256(transactions_root_before),
+            ),
+            (
+                GlobalMetadata::ReceiptTrieRootDigestBefore,
+                h256(receipts_root_before),
+            ),
+            (
+                GlobalMetadata::StateTrieRootDigestAfter,
+                h256(state_root_after),
+            ),
+            (
+                GlobalMetadata::TransactionTrieRootDigestAfter,
+                h256(transactions_root_after),
+            ),
+            (
+                GlobalMetadata::ReceiptTrieRootDigestAfter,
+                h256(receipts_root_after),
+            ),
+            (GlobalMetadata::TxnNumber, *txn_number_before),
+            (GlobalMetadata::TxnNumberAfter, *txn_number_after),
+            (GlobalMetadata::BlockGasUsed, *gas_used_before),
+            (GlobalMetadata::BlockGasUsedAfter, *gas_used_after),
+        ];
+
+        global_metadata
+            .into_iter()
+            .map(|(field, value)| (Segment::GlobalMetadata, field as usize, value))
+            .chain(
+                block_hashes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, hash)| (Segment::BlockHashes, i, h256(hash))),
+            )
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct TrieRoots {
     pub state_root: H256,
     pub transactions_root: H256,
@@ -72,12 +222,30 @@ pub struct BlockMetadata {
     pub block_base_fee: U256,
 }
 
+/// The block's progress before and after the transactions, and the hashes of its most recent
+/// ancestors.
+#[derive(Debug, Clone)]
+pub struct ExtraBlockData {
+    /// The number of transactions of the block processed before these ones.
+    pub txn_number_before: U256,
+    /// The number of transactions of the block processed up to and including these ones.
+    pub txn_number_after: U256,
+    /// The cumulative gas used by the transactions of the block processed before these ones.
+    pub gas_used_before: U256,
+    /// The cumulative gas used by the transactions of the block processed up to and including these
+    /// ones.
+    pub gas_used_after: U256,
+    /// The hashes of the block's most recent ancestors, oldest first. Missing hashes are zero.
+    pub block_hashes: [H256; NUM_BLOCK_HASHES],
+}
+
 /// Memory values which are public.
 /// Note: All the larger integers are encoded with 32-bit limbs in little-endian order.
 pub struct PublicValuesTarget {
     pub trie_roots_before: TrieRootsTarget,
     pub trie_roots_after: TrieRootsTarget,
     pub block_metadata: BlockMetadataTarget,
+    pub extra_block_data: ExtraBlockDataTarget,
 }
 
 pub struct TrieRootsTarget {
@@ -96,6 +264,368 @@ pub struct BlockMetadataTarget {
     pub block_base_fee: Target,
 }
 
+pub struct ExtraBlockDataTarget {
+    pub txn_number_before: Target,
+    pub txn_number_after: Target,
+    pub gas_used_before: Target,
+    pub gas_used_after: Target,
+    pub block_hashes: [[Target; 8]; NUM_BLOCK_HASHES],
+}
+
+impl PublicValuesTarget {
+    /// The public value targets, in the order in which `add_virtual_public_values` registers them
+    /// as public inputs.
+    pub(crate) fn to_targets(&self) -> Vec<Target> {
+        let trie_roots = |roots: &TrieRootsTarget| {
+            [
+                roots.state_root,
+                roots.transactions_root,
+                roots.receipts_root,
+            ]
+            .into_iter()
+            .flatten()
+        };
+        let BlockMetadataTarget {
+            block_beneficiary,
+            block_timestamp,
+            block_number,
+            block_difficulty,
+            block_gaslimit,
+            block_chain_id,
+            block_base_fee,
+        } = self.block_metadata;
+        let ExtraBlockDataTarget {
+            txn_number_before,
+            txn_number_after,
+            gas_used_before,
+            gas_used_after,
+            block_hashes,
+        } = self.extra_block_data;
+
+        trie_roots(&self.trie_roots_before)
+            .chain(trie_roots(&self.trie_roots_after))
+            .chain(block_beneficiary)
+            .chain([
+                block_timestamp,
+                block_number,
+                block_difficulty,
+                block_gaslimit,
+                block_chain_id,
+                block_base_fee,
+                txn_number_before,
+                txn_number_after,
+                gas_used_before,
+                gas_used_after,
+            ])
+            .chain(block_hashes.into_iter().flatten())
+            .collect()
+    }
+
+    /// Like `PublicValues::memory_values`, with each value given by its 32-bit limbs.
+    pub(crate) fn memory_values<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Vec<(Segment, usize, [Target; VALUE_LIMBS])> {
+        let zero = builder.zero();
+        let mut u64_limbs = |x: Target| {
+            let [lo, hi] = u64_limbs_circuit(builder, x);
+            let mut limbs = [zero; VALUE_LIMBS];
+            limbs[0] = lo;
+            limbs[1] = hi;
+            limbs
+        };
+        let BlockMetadataTarget {
+            block_beneficiary,
+            block_timestamp,
+            block_number,
+            block_difficulty,
+            block_gaslimit,
+            block_chain_id,
+            block_base_fee,
+        } = self.block_metadata;
+        let ExtraBlockDataTarget {
+            txn_number_before,
+            txn_number_after,
+            gas_used_before,
+            gas_used_after,
+            block_hashes,
+        } = self.extra_block_data;
+        let mut beneficiary = [zero; VALUE_LIMBS];
+        beneficiary[..5].copy_from_slice(&block_beneficiary);
+
+        let global_metadata = [
+            (GlobalMetadata::BlockBeneficiary, beneficiary),
+            (GlobalMetadata::BlockTimestamp, u64_limbs(block_timestamp)),
+            (GlobalMetadata::BlockNumber, u64_limbs(block_number)),
+            (GlobalMetadata::BlockDifficulty, u64_limbs(block_difficulty)),
+            (GlobalMetadata::BlockGasLimit, u64_limbs(block_gaslimit)),
+            (GlobalMetadata::BlockChainId, u64_limbs(block_chain_id)),
+            (GlobalMetadata::BlockBaseFee, u64_limbs(block_base_fee)),
+            (
+                GlobalMetadata::StateTrieRootDigestBefore,
+                self.trie_roots_before.state_root,
+            ),
+            (
+                GlobalMetadata::TransactionTrieRootDigestBefore,
+                self.trie_roots_before.transactions_root,
+            ),
+            (
+                GlobalMetadata::ReceiptTrieRootDigestBefore,
+                self.trie_roots_before.receipts_root,
+            ),
+            (
+                GlobalMetadata::StateTrieRootDigestAfter,
+                self.trie_roots_after.state_root,
+            ),
+            (
+                GlobalMetadata::TransactionTrieRootDigestAfter,
+                self.trie_roots_after.transactions_root,
+            ),
+            (
+                GlobalMetadata::ReceiptTrieRootDigestAfter,
+                self.trie_roots_after.receipts_root,
+            ),
+            (GlobalMetadata::TxnNumber, u64_limbs(txn_number_before)),
+            (GlobalMetadata::TxnNumberAfter, u64_limbs(txn_number_after)),
+            (GlobalMetadata::BlockGasUsed, u64_limbs(gas_used_before)),
+            (GlobalMetadata::BlockGasUsedAfter, u64_limbs(gas_used_after)),
+        ];
+
+        global_metadata
+            .into_iter()
+            .map(|(field, limbs)| (Segment::GlobalMetadata, field as usize, limbs))
+            .chain(
+                block_hashes
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, limbs)| (Segment::BlockHashes, i, limbs)),
+            )
+            .collect()
+    }
+
+    /// Reads public values from the public inputs of a proof, in the order in which
+    /// `add_virtual_public_values` registers them.
+    pub(crate) fn from_public_inputs(pis: &[Target]) -> Self {
+        let (trie_roots_before, rest) = pis.split_at(TrieRootsTarget::SIZE);
+        let (trie_roots_after, rest) = rest.split_at(TrieRootsTarget::SIZE);
+        let (block_metadata, rest) = rest.split_at(BlockMetadataTarget::SIZE);
+        Self {
+            trie_roots_before: TrieRootsTarget::from_public_inputs(trie_roots_before),
+            trie_roots_after: TrieRootsTarget::from_public_inputs(trie_roots_after),
+            block_metadata: BlockMetadataTarget::from_public_inputs(block_metadata),
+            extra_block_data: ExtraBlockDataTarget::from_public_inputs(
+                &rest[..ExtraBlockDataTarget::SIZE],
+            ),
+        }
+    }
+
+    /// Returns `pv0` if `condition` is true, and `pv1` otherwise.
+    pub(crate) fn select<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        condition: BoolTarget,
+        pv0: Self,
+        pv1: Self,
+    ) -> Self {
+        Self {
+            trie_roots_before: TrieRootsTarget::select(
+                builder,
+                condition,
+                pv0.trie_roots_before,
+                pv1.trie_roots_before,
+            ),
+            trie_roots_after: TrieRootsTarget::select(
+                builder,
+                condition,
+                pv0.trie_roots_after,
+                pv1.trie_roots_after,
+            ),
+            block_metadata: BlockMetadataTarget::select(
+                builder,
+                condition,
+                pv0.block_metadata,
+                pv1.block_metadata,
+            ),
+            extra_block_data: ExtraBlockDataTarget::select(
+                builder,
+                condition,
+                pv0.extra_block_data,
+                pv1.extra_block_data,
+            ),
+        }
+    }
+}
+
+impl TrieRootsTarget {
+    pub(crate) const SIZE: usize = 24;
+
+    pub(crate) fn from_public_inputs(pis: &[Target]) -> Self {
+        Self {
+            state_root: pis[0..8].try_into().unwrap(),
+            transactions_root: pis[8..16].try_into().unwrap(),
+            receipts_root: pis[16..24].try_into().unwrap(),
+        }
+    }
+
+    pub(crate) fn select<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        condition: BoolTarget,
+        tr0: Self,
+        tr1: Self,
+    ) -> Self {
+        let mut select_limbs = |limbs0: [Target; 8], limbs1: [Target; 8]| {
+            core::array::from_fn(|i| builder.select(condition, limbs0[i], limbs1[i]))
+        };
+        Self {
+            state_root: select_limbs(tr0.state_root, tr1.state_root),
+            transactions_root: select_limbs(tr0.transactions_root, tr1.transactions_root),
+            receipts_root: select_limbs(tr0.receipts_root, tr1.receipts_root),
+        }
+    }
+
+    pub(crate) fn connect<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        tr0: &Self,
+        tr1: &Self,
+    ) {
+        for i in 0..8 {
+            builder.connect(tr0.state_root[i], tr1.state_root[i]);
+            builder.connect(tr0.transactions_root[i], tr1.transactions_root[i]);
+            builder.connect(tr0.receipts_root[i], tr1.receipts_root[i]);
+        }
+    }
+}
+
+impl BlockMetadataTarget {
+    pub(crate) const SIZE: usize = 11;
+
+    pub(crate) fn from_public_inputs(pis: &[Target]) -> Self {
+        Self {
+            block_beneficiary: pis[0..5].try_into().unwrap(),
+            block_timestamp: pis[5],
+            block_number: pis[6],
+            block_difficulty: pis[7],
+            block_gaslimit: pis[8],
+            block_chain_id: pis[9],
+            block_base_fee: pis[10],
+        }
+    }
+
+    pub(crate) fn select<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        condition: BoolTarget,
+        bm0: Self,
+        bm1: Self,
+    ) -> Self {
+        Self {
+            block_beneficiary: core::array::from_fn(|i| {
+                builder.select(
+                    condition,
+                    bm0.block_beneficiary[i],
+                    bm1.block_beneficiary[i],
+                )
+            }),
+            block_timestamp: builder.select(condition, bm0.block_timestamp, bm1.block_timestamp),
+            block_number: builder.select(condition, bm0.block_number, bm1.block_number),
+            block_difficulty: builder.select(condition, bm0.block_difficulty, bm1.block_difficulty),
+            block_gaslimit: builder.select(condition, bm0.block_gaslimit, bm1.block_gaslimit),
+            block_chain_id: builder.select(condition, bm0.block_chain_id, bm1.block_chain_id),
+            block_base_fee: builder.select(condition, bm0.block_base_fee, bm1.block_base_fee),
+        }
+    }
+
+    pub(crate) fn connect<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        bm0: &Self,
+        bm1: &Self,
+    ) {
+        for i in 0..5 {
+            builder.connect(bm0.block_beneficiary[i], bm1.block_beneficiary[i]);
+        }
+        builder.connect(bm0.block_timestamp, bm1.block_timestamp);
+        builder.connect(bm0.block_number, bm1.block_number);
+        builder.connect(bm0.block_difficulty, bm1.block_difficulty);
+        builder.connect(bm0.block_gaslimit, bm1.block_gaslimit);
+        builder.connect(bm0.block_chain_id, bm1.block_chain_id);
+        builder.connect(bm0.block_base_fee, bm1.block_base_fee);
+    }
+}
+
+impl ExtraBlockDataTarget {
+    pub(crate) const SIZE: usize = 4 + 8 * NUM_BLOCK_HASHES;
+
+    pub(crate) fn from_public_inputs(pis: &[Target]) -> Self {
+        Self {
+            txn_number_before: pis[0],
+            txn_number_after: pis[1],
+            gas_used_before: pis[2],
+            gas_used_after: pis[3],
+            block_hashes: core::array::from_fn(|i| pis[4 + 8 * i..12 + 8 * i].try_into().unwrap()),
+        }
+    }
+
+    pub(crate) fn select<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        condition: BoolTarget,
+        ed0: Self,
+        ed1: Self,
+    ) -> Self {
+        Self {
+            txn_number_before: builder.select(
+                condition,
+                ed0.txn_number_before,
+                ed1.txn_number_before,
+            ),
+            txn_number_after: builder.select(condition, ed0.txn_number_after, ed1.txn_number_after),
+            gas_used_before: builder.select(condition, ed0.gas_used_before, ed1.gas_used_before),
+            gas_used_after: builder.select(condition, ed0.gas_used_after, ed1.gas_used_after),
+            block_hashes: core::array::from_fn(|i| {
+                core::array::from_fn(|j| {
+                    builder.select(condition, ed0.block_hashes[i][j], ed1.block_hashes[i][j])
+                })
+            }),
+        }
+    }
+
+    pub(crate) fn connect<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        ed0: &Self,
+        ed1: &Self,
+    ) {
+        builder.connect(ed0.txn_number_before, ed1.txn_number_before);
+        builder.connect(ed0.txn_number_after, ed1.txn_number_after);
+        builder.connect(ed0.gas_used_before, ed1.gas_used_before);
+        builder.connect(ed0.gas_used_after, ed1.gas_used_after);
+        Self::connect_block_hashes(builder, ed0, ed1);
+    }
+
+    /// Connects the block hashes of two sets of transactions, which must be in the same block.
+    pub(crate) fn connect_block_hashes<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        ed0: &Self,
+        ed1: &Self,
+    ) {
+        for (hash0, hash1) in ed0.block_hashes.iter().zip(&ed1.block_hashes) {
+            for (&limb0, &limb1) in hash0.iter().zip(hash1) {
+                builder.connect(limb0, limb1);
+            }
+        }
+    }
+}
+
+/// Splits a field element into its canonical 32-bit limbs, low limb first.
+fn u64_limbs_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    x: Target,
+) -> [Target; 2] {
+    let (lo, hi) = builder.split_low_high(x, 32, 64);
+    // `lo + 2^32 hi` could also be `x + p` when `hi = 2^32 - 1`, which only `lo = 0` rules out.
+    let max_hi = builder.constant(F::from_canonical_u32(u32::MAX));
+    let hi_is_max = builder.is_equal(hi, max_hi);
+    let non_canonical = builder.mul(hi_is_max.target, lo);
+    builder.assert_zero(non_canonical);
+    [lo, hi]
+}
+
 #[derive(Debug, Clone)]
 pub struct StarkProof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> {
     /// Merkle cap of LDEs of trace values.
@@ -119,7 +649,7 @@ where
     C: GenericConfig<D, F = F>,
 {
     pub(crate) init_challenger_state: [F; SPONGE_WIDTH],
-    pub(crate) proof: StarkProof<F, C, D>,
+    pub proof: StarkProof<F, C, D>,
 }
 
 impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> StarkProof<F, C, D> {
@@ -130,7 +660,22 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> S
             .evals_proofs[0]
             .1;
         let lde_bits = config.fri_config.cap_height + initial_merkle_proof.siblings.len();
-        lde_bits - config.fri_config.rate_bits
+        lde_bits - config.fri_config.rate_bits - config.blinding_degree_bits()
+    }
+
+    /// Like `recover_degree_bits`, but returns `None` instead of panicking if the opening proof
+    /// has no Merkle proofs, or ones too short for the configured rate.
+    pub(crate) fn try_recover_degree_bits(&self, config: &StarkConfig) -> Option<usize> {
+        let initial_merkle_proof = &self
+            .opening_proof
+            .query_round_proofs
+            .first()?
+            .initial_trees_proof
+            .evals_proofs
+            .first()?
+            .1;
+        let lde_bits = config.fri_config.cap_height + initial_merkle_proof.siblings.len();
+        lde_bits.checked_sub(config.fri_config.rate_bits + config.blinding_degree_bits())
     }
 
     pub fn num_ctl_zs(&self) -> usize {
@@ -154,7 +699,7 @@ impl<const D: usize> StarkProofTarget<D> {
             .evals_proofs[0]
             .1;
         let lde_bits = config.fri_config.cap_height + initial_merkle_proof.siblings.len();
-        lde_bits - config.fri_config.rate_bits
+        lde_bits - config.fri_config.rate_bits - config.blinding_degree_bits()
     }
 }
 
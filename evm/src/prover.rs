@@ -12,7 +12,8 @@ use plonky2::field::zero_poly_coset::ZeroPolyOnCoset;
 use plonky2::fri::oracle::PolynomialBatch;
 use plonky2::hash::hash_types::RichField;
 use plonky2::iop::challenger::Challenger;
-use plonky2::plonk::config::{GenericConfig, Hasher};
+use plonky2::plonk::config::{GenericConfig, GenericHashOut, Hasher};
+use plonky2::plonk::prover_handle::{ProverHandle, ProvingPhase};
 use plonky2::timed;
 use plonky2::util::timing::TimingTree;
 use plonky2::util::transpose;
@@ -25,7 +26,7 @@ use crate::constraint_consumer::ConstraintConsumer;
 use crate::cpu::cpu_stark::CpuStark;
 use crate::cpu::kernel::aggregator::KERNEL;
 use crate::cross_table_lookup::{cross_table_lookup_data, CtlCheckVars, CtlData};
-use crate::generation::{generate_traces, GenerationInputs};
+use crate::generation::{generate_traces, public_values, GenerationInputs};
 use crate::keccak::keccak_stark::KeccakStark;
 use crate::keccak_sponge::keccak_sponge_stark::KeccakSpongeStark;
 use crate::logic::LogicStark;
@@ -56,13 +57,42 @@ where
     [(); LogicStark::<F, D>::COLUMNS]:,
     [(); MemoryStark::<F, D>::COLUMNS]:,
 {
-    timed!(timing, "build kernel", Lazy::force(&KERNEL));
-    let (traces, public_values) = timed!(
+    prove_with_handle(all_stark, config, inputs, &ProverHandle::default(), timing)
+}
+
+/// Like [`prove`], but reports progress to `handle`, stops with a
+/// [`ProvingCancelled`](plonky2::plonk::prover_handle::ProvingCancelled) error once it is
+/// cancelled, and stores or resumes from checkpoints if it has a checkpoint directory. Checkpoints
+/// hold the traces in the clear, so they are refused in zero-knowledge mode.
+pub fn prove_with_handle<F, C, const D: usize>(
+    all_stark: &AllStark<F, D>,
+    config: &StarkConfig,
+    inputs: GenerationInputs,
+    handle: &ProverHandle,
+    timing: &mut TimingTree,
+) -> Result<AllProof<F, C, D>>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    [(); C::Hasher::HASH_SIZE]:,
+    [(); CpuStark::<F, D>::COLUMNS]:,
+    [(); KeccakStark::<F, D>::COLUMNS]:,
+    [(); KeccakSpongeStark::<F, D>::COLUMNS]:,
+    [(); LogicStark::<F, D>::COLUMNS]:,
+    [(); MemoryStark::<F, D>::COLUMNS]:,
+{
+    handle.enter_phase(ProvingPhase::Witness)?;
+    let public_values = public_values(&inputs);
+    let mut checkpoint = EvmCheckpoint::load(handle, config, &public_values)?;
+    let traces = generate_or_resume_traces(all_stark, config, inputs, &checkpoint, timing);
+    prove_with_traces(
+        all_stark,
+        config,
+        traces,
+        public_values,
+        &mut checkpoint,
         timing,
-        "generate all traces",
-        generate_traces(all_stark, inputs, config, timing)
-    );
-    prove_with_traces(all_stark, config, traces, public_values, timing)
+    )
 }
 
 /// Compute all STARK proofs.
@@ -71,6 +101,7 @@ pub(crate) fn prove_with_traces<F, C, const D: usize>(
     config: &StarkConfig,
     trace_poly_values: [Vec<PolynomialValues<F>>; NUM_TABLES],
     public_values: PublicValues,
+    checkpoint: &mut EvmCheckpoint<F, C, D>,
     timing: &mut TimingTree,
 ) -> Result<AllProof<F, C, D>>
 where
@@ -83,34 +114,236 @@ where
     [(); LogicStark::<F, D>::COLUMNS]:,
     [(); MemoryStark::<F, D>::COLUMNS]:,
 {
-    let rate_bits = config.fri_config.rate_bits;
-    let cap_height = config.fri_config.cap_height;
+    let (trace_commitments, mut challenger, ctl_challenges, ctl_data_per_table) =
+        commit_all_traces(
+            all_stark,
+            config,
+            &trace_poly_values,
+            &public_values,
+            checkpoint,
+            timing,
+        )?;
 
-    let trace_commitments = timed!(
+    let stark_proofs = timed!(
         timing,
-        "compute all trace commitments",
-        trace_poly_values
-            .iter()
-            .zip_eq(Table::all())
-            .map(|(trace, table)| {
-                timed!(
-                    timing,
-                    &format!("compute trace commitment for {:?}", table),
-                    PolynomialBatch::<F, C, D>::from_values(
-                        // TODO: Cloning this isn't great; consider having `from_values` accept a reference,
-                        // or having `compute_permutation_z_polys` read trace values from the `PolynomialBatch`.
-                        trace.clone(),
-                        rate_bits,
-                        false,
-                        cap_height,
-                        timing,
-                        None,
-                    )
-                )
-            })
-            .collect::<Vec<_>>()
+        "compute all proofs given commitments",
+        prove_with_commitments(
+            all_stark,
+            config,
+            trace_poly_values,
+            trace_commitments,
+            ctl_data_per_table,
+            &mut challenger,
+            checkpoint,
+            timing
+        )?
     );
 
+    checkpoint.remove();
+    Ok(AllProof {
+        stark_proofs,
+        ctl_challenges,
+        public_values,
+    })
+}
+
+/// Generates the traces of all tables, or recovers them from the checkpoint of an interrupted
+/// proof.
+fn generate_or_resume_traces<F, C, const D: usize>(
+    all_stark: &AllStark<F, D>,
+    config: &StarkConfig,
+    inputs: GenerationInputs,
+    checkpoint: &EvmCheckpoint<F, C, D>,
+    timing: &mut TimingTree,
+) -> [Vec<PolynomialValues<F>>; NUM_TABLES]
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    timed!(timing, "build kernel", Lazy::force(&KERNEL));
+    match checkpoint.trace_commitments() {
+        // Without blinding, the trace polynomials have degree `n`, and the traces are their values
+        // over `H`.
+        Some(trace_commitments) => timed!(
+            timing,
+            "recover all traces from checkpoint",
+            core::array::from_fn(|i| {
+                trace_commitments[i]
+                    .polynomials
+                    .par_iter()
+                    .map(|poly| poly.clone().fft())
+                    .collect()
+            })
+        ),
+        None => {
+            timed!(
+                timing,
+                "generate all traces",
+                generate_traces(all_stark, inputs, config, timing)
+            )
+            .0
+        }
+    }
+}
+
+/// The checkpoints of an EVM proof, stored through a [`ProverHandle`]. The prover commits in a
+/// fixed order: to the traces of all tables, then to the permutation and CTL Zs and to the quotient
+/// of each table in turn. A checkpoint holds the commitments made so far, and a later proof of the
+/// same public values resumes from them. The traces themselves are recovered from the trace
+/// commitments.
+pub(crate) struct EvmCheckpoint<'a, F, C, const D: usize>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    handle: &'a ProverHandle,
+    /// `None` for proofs which don't store checkpoints.
+    id: Option<Vec<u8>>,
+    public_inputs: Vec<F>,
+    /// The checkpointed commitments which haven't been resumed yet.
+    saved: std::vec::IntoIter<PolynomialBatch<F, C, D>>,
+}
+
+impl<'a, F, C, const D: usize> EvmCheckpoint<'a, F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    /// Loads the checkpoint of a proof of the given public values, if there is one.
+    fn load(
+        handle: &'a ProverHandle,
+        config: &StarkConfig,
+        public_values: &PublicValues,
+    ) -> Result<Self> {
+        ensure!(
+            !(config.zero_knowledge && handle.has_checkpoint_dir()),
+            "Checkpoints would store the traces of a zero-knowledge proof in the clear"
+        );
+
+        // The public values fix the statement, including the transactions through the transactions
+        // trie root. The kernel, the configuration and the hasher fix the commitments proving it.
+        let public_inputs = public_values.to_public_inputs::<F>();
+        let mut elements = KERNEL.code_hash.map(F::from_canonical_u32).to_vec();
+        elements.extend(
+            [
+                config.fri_config.rate_bits,
+                config.fri_config.cap_height,
+                config.num_challenges,
+            ]
+            .map(F::from_canonical_usize),
+        );
+        elements.extend(type_name::<C>().bytes().map(F::from_canonical_u8));
+        elements.extend(&public_inputs);
+        let id = C::InnerHasher::hash_no_pad(&elements).to_bytes();
+
+        let saved = handle
+            .load_checkpoint::<F, C, D>(&id)
+            .filter(|checkpoint| checkpoint.public_inputs == public_inputs)
+            .map(|checkpoint| checkpoint.commitments)
+            .unwrap_or_default();
+        Ok(Self {
+            handle,
+            id: Some(id),
+            public_inputs,
+            saved: saved.into_iter(),
+        })
+    }
+
+    /// Proves without storing or resuming checkpoints, as when proving a single table.
+    #[cfg(test)]
+    pub(crate) fn disabled(handle: &'a ProverHandle) -> Self {
+        Self {
+            handle,
+            id: None,
+            public_inputs: vec![],
+            saved: Vec::new().into_iter(),
+        }
+    }
+
+    /// The checkpointed trace commitments, if the interrupted proof got that far.
+    fn trace_commitments(&self) -> Option<&[PolynomialBatch<F, C, D>]> {
+        self.saved.as_slice().get(..NUM_TABLES)
+    }
+
+    /// Takes the checkpointed trace commitments, if there are any.
+    fn resume_trace_commitments(&mut self) -> Option<Vec<PolynomialBatch<F, C, D>>> {
+        self.trace_commitments()?;
+        Some(self.saved.by_ref().take(NUM_TABLES).collect())
+    }
+
+    /// Takes the next checkpointed commitment, if the interrupted proof got that far.
+    fn resume(&mut self) -> Option<PolynomialBatch<F, C, D>> {
+        self.saved.next()
+    }
+
+    /// Stores all the commitments made so far, in order.
+    fn save(&self, commitments: &[&PolynomialBatch<F, C, D>]) {
+        if let Some(id) = &self.id {
+            self.handle
+                .save_checkpoint(id, &self.public_inputs, &[], commitments);
+        }
+    }
+
+    /// Deletes the checkpoint once the proof is done.
+    fn remove(&self) {
+        if let Some(id) = &self.id {
+            self.handle.remove_checkpoint(id);
+        }
+    }
+}
+
+type AllTraceCommitments<F, C, const D: usize> = (
+    Vec<PolynomialBatch<F, C, D>>,
+    Challenger<F, <C as GenericConfig<D>>::Hasher>,
+    GrandProductChallengeSet<F>,
+    [CtlData<F>; NUM_TABLES],
+);
+
+/// Commits to the trace of each STARK, and computes the CTL data with challenges drawn from the
+/// returned challenger.
+fn commit_all_traces<F, C, const D: usize>(
+    all_stark: &AllStark<F, D>,
+    config: &StarkConfig,
+    trace_poly_values: &[Vec<PolynomialValues<F>>; NUM_TABLES],
+    public_values: &PublicValues,
+    checkpoint: &mut EvmCheckpoint<F, C, D>,
+    timing: &mut TimingTree,
+) -> Result<AllTraceCommitments<F, C, D>>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    checkpoint.handle.enter_phase(ProvingPhase::Commitment)?;
+    let trace_commitments = match checkpoint.resume_trace_commitments() {
+        Some(trace_commitments) => trace_commitments,
+        None => {
+            let trace_commitments = timed!(
+                timing,
+                "compute all trace commitments",
+                trace_poly_values
+                    .iter()
+                    .zip_eq(Table::all())
+                    .map(|(trace, table)| {
+                        timed!(
+                            timing,
+                            &format!("compute trace commitment for {:?}", table),
+                            // TODO: Cloning this isn't great; consider having `commit_values` accept a reference,
+                            // or having `compute_permutation_z_polys` read trace values from the `PolynomialBatch`.
+                            commit_values::<F, C, D>(
+                                trace.clone(),
+                                config,
+                                checkpoint.handle,
+                                timing
+                            )
+                        )
+                    })
+                    .collect::<Result<Vec<_>>>()?
+            );
+            checkpoint.save(&trace_commitments.iter().collect::<Vec<_>>());
+            trace_commitments
+        }
+    };
+
     let trace_caps = trace_commitments
         .iter()
         .map(|c| c.merkle_tree.cap.clone())
@@ -119,37 +352,25 @@ where
     for cap in &trace_caps {
         challenger.observe_cap(cap);
     }
+    challenger.observe_elements(&public_values.to_public_inputs());
 
     let ctl_challenges = get_grand_product_challenge_set(&mut challenger, config.num_challenges);
     let ctl_data_per_table = timed!(
         timing,
         "compute CTL data",
         cross_table_lookup_data::<F, D>(
-            &trace_poly_values,
+            trace_poly_values,
             &all_stark.cross_table_lookups,
             &ctl_challenges,
         )
     );
 
-    let stark_proofs = timed!(
-        timing,
-        "compute all proofs given commitments",
-        prove_with_commitments(
-            all_stark,
-            config,
-            trace_poly_values,
-            trace_commitments,
-            ctl_data_per_table,
-            &mut challenger,
-            timing
-        )?
-    );
-
-    Ok(AllProof {
-        stark_proofs,
+    Ok((
+        trace_commitments,
+        challenger,
         ctl_challenges,
-        public_values,
-    })
+        ctl_data_per_table,
+    ))
 }
 
 fn prove_with_commitments<F, C, const D: usize>(
@@ -159,6 +380,7 @@ fn prove_with_commitments<F, C, const D: usize>(
     trace_commitments: Vec<PolynomialBatch<F, C, D>>,
     ctl_data_per_table: [CtlData<F>; NUM_TABLES],
     challenger: &mut Challenger<F, C::Hasher>,
+    checkpoint: &mut EvmCheckpoint<F, C, D>,
     timing: &mut TimingTree,
 ) -> Result<[StarkProofWithMetadata<F, C, D>; NUM_TABLES]>
 where
@@ -171,7 +393,9 @@ where
     [(); LogicStark::<F, D>::COLUMNS]:,
     [(); MemoryStark::<F, D>::COLUMNS]:,
 {
-    let cpu_proof = timed!(
+    // The commitments made so far, in the order in which they are checkpointed.
+    let mut commitments = trace_commitments.iter().collect::<Vec<_>>();
+    let (cpu_proof, cpu_commitments) = timed!(
         timing,
         "prove CPU STARK",
         prove_single_table(
@@ -181,10 +405,13 @@ where
             &trace_commitments[Table::Cpu as usize],
             &ctl_data_per_table[Table::Cpu as usize],
             challenger,
+            checkpoint,
+            &commitments,
             timing,
         )?
     );
-    let keccak_proof = timed!(
+    commitments.extend(&cpu_commitments);
+    let (keccak_proof, keccak_commitments) = timed!(
         timing,
         "prove Keccak STARK",
         prove_single_table(
@@ -194,10 +421,13 @@ where
             &trace_commitments[Table::Keccak as usize],
             &ctl_data_per_table[Table::Keccak as usize],
             challenger,
+            checkpoint,
+            &commitments,
             timing,
         )?
     );
-    let keccak_sponge_proof = timed!(
+    commitments.extend(&keccak_commitments);
+    let (keccak_sponge_proof, keccak_sponge_commitments) = timed!(
         timing,
         "prove Keccak sponge STARK",
         prove_single_table(
@@ -207,10 +437,13 @@ where
             &trace_commitments[Table::KeccakSponge as usize],
             &ctl_data_per_table[Table::KeccakSponge as usize],
             challenger,
+            checkpoint,
+            &commitments,
             timing,
         )?
     );
-    let logic_proof = timed!(
+    commitments.extend(&keccak_sponge_commitments);
+    let (logic_proof, logic_commitments) = timed!(
         timing,
         "prove logic STARK",
         prove_single_table(
@@ -220,10 +453,13 @@ where
             &trace_commitments[Table::Logic as usize],
             &ctl_data_per_table[Table::Logic as usize],
             challenger,
+            checkpoint,
+            &commitments,
             timing,
         )?
     );
-    let memory_proof = timed!(
+    commitments.extend(&logic_commitments);
+    let (memory_proof, _) = timed!(
         timing,
         "prove memory STARK",
         prove_single_table(
@@ -233,6 +469,8 @@ where
             &trace_commitments[Table::Memory as usize],
             &ctl_data_per_table[Table::Memory as usize],
             challenger,
+            checkpoint,
+            &commitments,
             timing,
         )?
     );
@@ -245,7 +483,72 @@ where
     ])
 }
 
-/// Compute proof for a single STARK table.
+/// Commits to the polynomials interpolating `values` over the trace domain `H`.
+///
+/// In zero-knowledge mode, each polynomial `p` is replaced by `p + Z_H r` for a random `r` of
+/// degree less than `|H|`, which has the same values on `H`, and the Merkle leaves are salted.
+fn commit_values<F, C, const D: usize>(
+    values: Vec<PolynomialValues<F>>,
+    config: &StarkConfig,
+    handle: &ProverHandle,
+    timing: &mut TimingTree,
+) -> Result<PolynomialBatch<F, C, D>>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    let rate_bits = config.fri_config.rate_bits;
+    let cap_height = config.fri_config.cap_height;
+    if !config.zero_knowledge {
+        return PolynomialBatch::from_values_with_storage(
+            values,
+            rate_bits,
+            false,
+            cap_height,
+            timing,
+            None,
+            handle.commitment_storage(),
+        );
+    }
+
+    let revealed = config.num_revealed_evaluations();
+    if let Some(v) = values.first() {
+        ensure!(
+            v.len() > revealed,
+            "A trace of length {} can't hide {revealed} revealed evaluations",
+            v.len()
+        );
+    }
+
+    let coeffs = timed!(
+        timing,
+        "IFFT + randomize",
+        values
+            .into_par_iter()
+            .map(|v| {
+                let n = v.len();
+                let coeffs = v.ifft().coeffs;
+                let r = F::rand_vec(n);
+                // `p + (X^n - 1) r` has coefficients `p_i - r_i` and `r_i` at `i` and `n + i`.
+                let low = coeffs.iter().zip(&r).map(|(&c, &r)| c - r);
+                PolynomialCoeffs::new(low.chain(r.iter().copied()).collect())
+            })
+            .collect::<Vec<_>>()
+    );
+    PolynomialBatch::from_coeffs_with_storage(
+        coeffs,
+        rate_bits,
+        true,
+        cap_height,
+        timing,
+        None,
+        handle.commitment_storage(),
+    )
+}
+
+/// Compute proof for a single STARK table, along with its permutation and CTL Zs commitment and its
+/// quotient commitment. `prior_commitments` are the commitments made before this table's, which
+/// are checkpointed along with them.
 pub(crate) fn prove_single_table<F, C, S, const D: usize>(
     stark: &S,
     config: &StarkConfig,
@@ -253,8 +556,13 @@ pub(crate) fn prove_single_table<F, C, S, const D: usize>(
     trace_commitment: &PolynomialBatch<F, C, D>,
     ctl_data: &CtlData<F>,
     challenger: &mut Challenger<F, C::Hasher>,
+    checkpoint: &mut EvmCheckpoint<F, C, D>,
+    prior_commitments: &[&PolynomialBatch<F, C, D>],
     timing: &mut TimingTree,
-) -> Result<StarkProofWithMetadata<F, C, D>>
+) -> Result<(
+    StarkProofWithMetadata<F, C, D>,
+    [PolynomialBatch<F, C, D>; 2],
+)>
 where
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
@@ -268,10 +576,12 @@ where
     let rate_bits = config.fri_config.rate_bits;
     let cap_height = config.fri_config.cap_height;
     assert!(
-        fri_params.total_arities() <= degree_bits + rate_bits - cap_height,
+        fri_params.total_arities() <= fri_params.lde_bits() - cap_height,
         "FRI total reduction arity is too large.",
     );
+    let handle = checkpoint.handle;
 
+    handle.enter_phase(ProvingPhase::Commitment)?;
     let init_challenger_state = challenger.compact();
 
     // Permutation arguments.
@@ -282,97 +592,120 @@ where
             stark.permutation_batch_size(),
         )
     });
-    let permutation_zs = permutation_challenges.as_ref().map(|challenges| {
-        timed!(
-            timing,
-            "compute permutation Z(x) polys",
-            compute_permutation_z_polys::<F, S, D>(stark, config, trace_poly_values, challenges)
-        )
-    });
-    let num_permutation_zs = permutation_zs.as_ref().map(|v| v.len()).unwrap_or(0);
+    let num_permutation_zs = stark.num_permutation_batches(config);
 
-    let z_polys = match permutation_zs {
-        None => ctl_data.z_polys(),
-        Some(mut permutation_zs) => {
-            permutation_zs.extend(ctl_data.z_polys());
-            permutation_zs
+    let permutation_ctl_zs_commitment = match checkpoint.resume() {
+        Some(commitment) => commitment,
+        None => {
+            let permutation_zs = permutation_challenges.as_ref().map(|challenges| {
+                timed!(
+                    timing,
+                    "compute permutation Z(x) polys",
+                    compute_permutation_z_polys::<F, S, D>(
+                        stark,
+                        config,
+                        trace_poly_values,
+                        challenges
+                    )
+                )
+            });
+
+            let z_polys = match permutation_zs {
+                None => ctl_data.z_polys(),
+                Some(mut permutation_zs) => {
+                    permutation_zs.extend(ctl_data.z_polys());
+                    permutation_zs
+                }
+            };
+            assert!(!z_polys.is_empty(), "No CTL?");
+
+            let permutation_ctl_zs_commitment = timed!(
+                timing,
+                "compute Zs commitment",
+                commit_values(z_polys, config, handle, timing)?
+            );
+            checkpoint.save(&[prior_commitments, &[&permutation_ctl_zs_commitment]].concat());
+            permutation_ctl_zs_commitment
         }
     };
-    assert!(!z_polys.is_empty(), "No CTL?");
-
-    let permutation_ctl_zs_commitment = timed!(
-        timing,
-        "compute Zs commitment",
-        PolynomialBatch::from_values(
-            z_polys,
-            rate_bits,
-            false,
-            config.fri_config.cap_height,
-            timing,
-            None,
-        )
-    );
 
     let permutation_ctl_zs_cap = permutation_ctl_zs_commitment.merkle_tree.cap.clone();
     challenger.observe_cap(&permutation_ctl_zs_cap);
 
     let alphas = challenger.get_n_challenges(config.num_challenges);
-    if cfg!(test) {
-        check_constraints(
-            stark,
-            trace_commitment,
-            &permutation_ctl_zs_commitment,
-            permutation_challenges.as_ref(),
-            ctl_data,
-            alphas.clone(),
-            degree_bits,
-            num_permutation_zs,
-            config,
-        );
-    }
-    let quotient_polys = timed!(
-        timing,
-        "compute quotient polys",
-        compute_quotient_polys::<F, <F as Packable>::Packing, C, S, D>(
-            stark,
-            trace_commitment,
-            &permutation_ctl_zs_commitment,
-            permutation_challenges.as_ref(),
-            ctl_data,
-            alphas,
-            degree_bits,
-            num_permutation_zs,
-            config,
-        )
-    );
-    let all_quotient_chunks = timed!(
-        timing,
-        "split quotient polys",
-        quotient_polys
-            .into_par_iter()
-            .flat_map(|mut quotient_poly| {
-                quotient_poly
-                    .trim_to_len(degree * stark.quotient_degree_factor())
-                    .expect(
-                        "Quotient has failed, the vanishing polynomial is not divisible by Z_H",
-                    );
-                // Split quotient into degree-n chunks.
-                quotient_poly.chunks(degree)
-            })
-            .collect()
-    );
-    let quotient_commitment = timed!(
-        timing,
-        "compute quotient commitment",
-        PolynomialBatch::from_coeffs(
-            all_quotient_chunks,
-            rate_bits,
-            false,
-            config.fri_config.cap_height,
-            timing,
-            None,
-        )
-    );
+    handle.enter_phase(ProvingPhase::Quotient)?;
+    let quotient_commitment = match checkpoint.resume() {
+        Some(commitment) => commitment,
+        None => {
+            if cfg!(test) {
+                check_constraints(
+                    stark,
+                    trace_commitment,
+                    &permutation_ctl_zs_commitment,
+                    permutation_challenges.as_ref(),
+                    ctl_data,
+                    alphas.clone(),
+                    degree_bits,
+                    num_permutation_zs,
+                    config,
+                );
+            }
+            let quotient_polys = timed!(
+                timing,
+                "compute quotient polys",
+                compute_quotient_polys::<F, <F as Packable>::Packing, C, S, D>(
+                    stark,
+                    trace_commitment,
+                    &permutation_ctl_zs_commitment,
+                    permutation_challenges.as_ref(),
+                    ctl_data,
+                    alphas,
+                    degree_bits,
+                    num_permutation_zs,
+                    config,
+                )
+            );
+            // The committed polynomials have degree `chunk_size`, which is `2n` in zero-knowledge mode.
+            let chunk_size = degree << config.blinding_degree_bits();
+            let all_quotient_chunks = timed!(
+                timing,
+                "split quotient polys",
+                quotient_polys
+                    .into_par_iter()
+                    .flat_map(|mut quotient_poly| {
+                        quotient_poly
+                            .trim_to_len(chunk_size * stark.num_quotient_chunks(config))
+                            .expect(
+                                "Quotient has failed, the vanishing polynomial is not divisible by Z_H",
+                            );
+                        // Split quotient into chunks of the committed degree.
+                        quotient_poly.chunks(chunk_size)
+                    })
+                    .collect()
+            );
+            let quotient_commitment = timed!(
+                timing,
+                "compute quotient commitment",
+                PolynomialBatch::from_coeffs_with_storage(
+                    all_quotient_chunks,
+                    rate_bits,
+                    config.zero_knowledge,
+                    cap_height,
+                    timing,
+                    None,
+                    handle.commitment_storage(),
+                )?
+            );
+            checkpoint.save(
+                &[
+                    prior_commitments,
+                    &[&permutation_ctl_zs_commitment, &quotient_commitment],
+                ]
+                .concat(),
+            );
+            quotient_commitment
+        }
+    };
     let quotient_polys_cap = quotient_commitment.merkle_tree.cap.clone();
     challenger.observe_cap(&quotient_polys_cap);
 
@@ -393,7 +726,7 @@ where
         &permutation_ctl_zs_commitment,
         &quotient_commitment,
         degree_bits,
-        stark.num_permutation_batches(config),
+        num_permutation_zs,
     );
     challenger.observe_openings(&openings.to_fri_openings());
 
@@ -403,6 +736,7 @@ where
         &quotient_commitment,
     ];
 
+    handle.enter_phase(ProvingPhase::Fri)?;
     let opening_proof = timed!(
         timing,
         "compute openings proof",
@@ -422,10 +756,13 @@ where
         openings,
         opening_proof,
     };
-    Ok(StarkProofWithMetadata {
-        init_challenger_state,
-        proof,
-    })
+    Ok((
+        StarkProofWithMetadata {
+            init_challenger_state,
+            proof,
+        },
+        [permutation_ctl_zs_commitment, quotient_commitment],
+    ))
 }
 
 /// Computes the quotient polynomials `(sum alpha^i C_i(x)) / Z_H(x)` for `alpha` in `alphas`,
@@ -451,12 +788,16 @@ where
     let degree = 1 << degree_bits;
     let rate_bits = config.fri_config.rate_bits;
 
-    let quotient_degree_bits = log2_ceil(stark.quotient_degree_factor());
+    // The LDEs of the committed polynomials have `2^lde_bits` points per point of `H`.
+    let lde_bits = rate_bits + config.blinding_degree_bits();
+
+    let quotient_degree_bits =
+        log2_ceil(stark.num_quotient_chunks(config)) + config.blinding_degree_bits();
     assert!(
-        quotient_degree_bits <= rate_bits,
+        quotient_degree_bits <= lde_bits,
         "Having constraints of degree higher than the rate is not supported yet."
     );
-    let step = 1 << (rate_bits - quotient_degree_bits);
+    let step = 1 << (lde_bits - quotient_degree_bits);
     // When opening the `Z`s polys at the "next" point, need to look at the point `next_step` steps away.
     let next_step = 1 << quotient_degree_bits;
 
@@ -596,12 +937,21 @@ fn check_constraints<'a, F, C, S, const D: usize>(
 
     let subgroup = F::two_adic_subgroup(degree_bits + rate_bits);
 
-    // Get the evaluations of a batch of polynomials over our subgroup.
+    // Get the evaluations of a batch of polynomials over our subgroup. In zero-knowledge mode, the
+    // committed polynomials have twice the degree, so we skip every other point of their FFT.
     let get_subgroup_evals = |comm: &PolynomialBatch<F, C, D>| -> Vec<Vec<F>> {
         let values = comm
             .polynomials
             .par_iter()
-            .map(|coeffs| coeffs.clone().fft().values)
+            .map(|coeffs| {
+                coeffs
+                    .clone()
+                    .fft()
+                    .values
+                    .into_iter()
+                    .step_by(1 << config.blinding_degree_bits())
+                    .collect()
+            })
             .collect::<Vec<_>>();
         transpose(&values)
     };
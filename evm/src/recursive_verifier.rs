@@ -17,7 +17,9 @@ use plonky2::iop::witness::{PartialWitness, Witness, WitnessWrite};
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::circuit_data::{CircuitConfig, CircuitData, VerifierCircuitData};
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
+use plonky2::plonk::plonk_common::salt_size;
 use plonky2::plonk::proof::{ProofWithPublicInputs, ProofWithPublicInputsTarget};
+use plonky2::plonk::prover_handle::ProverHandle;
 use plonky2::util::reducing::ReducingFactorTarget;
 use plonky2::with_context;
 use plonky2_util::log2_ceil;
@@ -26,19 +28,21 @@ use crate::all_stark::{Table, NUM_TABLES};
 use crate::config::StarkConfig;
 use crate::constraint_consumer::RecursiveConstraintConsumer;
 use crate::cross_table_lookup::{verify_cross_table_lookups, CrossTableLookup, CtlCheckVarsTarget};
+use crate::generation::PUBLIC_VALUES_CHANNEL;
 use crate::permutation::{
     get_grand_product_challenge_set, GrandProductChallenge, GrandProductChallengeSet,
     PermutationCheckDataTarget,
 };
 use crate::proof::{
-    BlockMetadata, BlockMetadataTarget, PublicValues, PublicValuesTarget, StarkOpeningSetTarget,
-    StarkProof, StarkProofChallengesTarget, StarkProofTarget, StarkProofWithMetadata, TrieRoots,
-    TrieRootsTarget,
+    BlockMetadata, BlockMetadataTarget, ExtraBlockData, ExtraBlockDataTarget, PublicValues,
+    PublicValuesTarget, StarkOpeningSetTarget, StarkProof, StarkProofChallengesTarget,
+    StarkProofTarget, StarkProofWithMetadata, TrieRoots, TrieRootsTarget,
 };
 use crate::stark::Stark;
 use crate::util::{h160_limbs, h256_limbs};
 use crate::vanishing_poly::eval_vanishing_poly_circuit;
 use crate::vars::StarkEvaluationTargets;
+use crate::verifier::ctl_extra_looking_products;
 
 /// Table-wise recursive proofs of an `AllProof`.
 pub struct RecursiveAllProof<
@@ -47,6 +51,7 @@ pub struct RecursiveAllProof<
     const D: usize,
 > {
     pub recursive_proofs: [ProofWithPublicInputs<F, C, D>; NUM_TABLES],
+    pub public_values: PublicValues,
 }
 
 pub(crate) struct PublicInputs<T: Copy + Eq + PartialEq + Debug> {
@@ -117,6 +122,7 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
                 challenger.observe_elements(h);
             }
         }
+        challenger.observe_elements(&self.public_values.to_public_inputs());
         let ctl_challenges =
             get_grand_product_challenge_set(&mut challenger, inner_config.num_challenges);
         // Check that the correct CTL challenges are used in every proof.
@@ -135,6 +141,7 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         verify_cross_table_lookups::<F, D>(
             &cross_table_lookups,
             pis.map(|p| p.ctl_zs_last),
+            &ctl_extra_looking_products(&self.public_values, &ctl_challenges),
             inner_config,
         )?;
 
@@ -169,6 +176,7 @@ where
         &self,
         proof_with_metadata: &StarkProofWithMetadata<F, C, D>,
         ctl_challenges: &GrandProductChallengeSet<F>,
+        handle: &ProverHandle,
     ) -> Result<ProofWithPublicInputs<F, C, D>> {
         let mut inputs = PartialWitness::new();
 
@@ -194,7 +202,7 @@ where
             proof_with_metadata.init_challenger_state,
         );
 
-        self.circuit.prove(inputs)
+        self.circuit.prove_with_handle(inputs, handle)
     }
 }
 
@@ -217,10 +225,11 @@ where
     pub(crate) fn prove(
         &self,
         proof: &ProofWithPublicInputs<F, C, D>,
+        handle: &ProverHandle,
     ) -> Result<ProofWithPublicInputs<F, C, D>> {
         let mut inputs = PartialWitness::new();
         inputs.set_proof_with_pis_target(&self.proof_with_pis_target, proof);
-        self.circuit.prove(inputs)
+        self.circuit.prove_with_handle(inputs, handle)
     }
 }
 
@@ -406,9 +415,13 @@ fn verify_stark_proof_with_challenges_circuit<
     let vanishing_polys_zeta = consumer.accumulators();
 
     // Check each polynomial identity, of the form `vanishing(x) = Z_H(x) quotient(x)`, at zeta.
-    let mut scale = ReducingFactorTarget::new(zeta_pow_deg);
+    // In zero-knowledge mode, the quotient chunks have degree `2n`, so they are recombined with
+    // powers of `zeta^{2n}`.
+    let zeta_pow_chunk_deg =
+        builder.exp_power_of_2_extension(zeta_pow_deg, inner_config.blinding_degree_bits());
+    let mut scale = ReducingFactorTarget::new(zeta_pow_chunk_deg);
     for (i, chunk) in quotient_polys
-        .chunks(stark.quotient_degree_factor())
+        .chunks(stark.num_quotient_chunks(inner_config))
         .enumerate()
     {
         let recombined_quotient = scale.reduce(chunk, builder);
@@ -459,26 +472,65 @@ fn eval_l_0_and_l_last_circuit<F: RichField + Extendable<D>, const D: usize>(
     )
 }
 
-#[allow(unused)] // TODO: used later?
+/// Like `ctl_extra_looking_products`, with the public values given by targets.
+pub(crate) fn ctl_extra_looking_products_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    public_values: &PublicValuesTarget,
+    ctl_challenges: &GrandProductChallengeSet<Target>,
+) -> [Vec<Target>; NUM_TABLES] {
+    let zero = builder.zero();
+    let timestamp = builder.constant(F::from_canonical_usize(PUBLIC_VALUES_CHANNEL.index()));
+    let rows = public_values
+        .memory_values(builder)
+        .into_iter()
+        .map(|(segment, offset, limbs)| {
+            let mut row = vec![zero, zero];
+            row.push(builder.constant(F::from_canonical_usize(segment as usize)));
+            row.push(builder.constant(F::from_canonical_usize(offset)));
+            row.extend(limbs);
+            row.push(timestamp);
+            row
+        })
+        .collect_vec();
+
+    let one = builder.one();
+    let mut products = [(); NUM_TABLES].map(|_| vec![one; ctl_challenges.challenges.len()]);
+    products[Table::Memory as usize] = ctl_challenges
+        .challenges
+        .iter()
+        .map(|challenge| {
+            let combined = rows
+                .iter()
+                .map(|row| challenge.combine_base_circuit(builder, row))
+                .collect_vec();
+            builder.mul_many(combined)
+        })
+        .collect();
+    products
+}
+
+/// Adds the targets of the EVM's public values, registering them as public inputs.
 pub(crate) fn add_virtual_public_values<F: RichField + Extendable<D>, const D: usize>(
     builder: &mut CircuitBuilder<F, D>,
 ) -> PublicValuesTarget {
     let trie_roots_before = add_virtual_trie_roots(builder);
     let trie_roots_after = add_virtual_trie_roots(builder);
     let block_metadata = add_virtual_block_metadata(builder);
+    let extra_block_data = add_virtual_extra_block_data(builder);
     PublicValuesTarget {
         trie_roots_before,
         trie_roots_after,
         block_metadata,
+        extra_block_data,
     }
 }
 
 pub(crate) fn add_virtual_trie_roots<F: RichField + Extendable<D>, const D: usize>(
     builder: &mut CircuitBuilder<F, D>,
 ) -> TrieRootsTarget {
-    let state_root = builder.add_virtual_target_arr();
-    let transactions_root = builder.add_virtual_target_arr();
-    let receipts_root = builder.add_virtual_target_arr();
+    let state_root = builder.add_virtual_public_input_arr();
+    let transactions_root = builder.add_virtual_public_input_arr();
+    let receipts_root = builder.add_virtual_public_input_arr();
     TrieRootsTarget {
         state_root,
         transactions_root,
@@ -489,13 +541,13 @@ pub(crate) fn add_virtual_trie_roots<F: RichField + Extendable<D>, const D: usiz
 pub(crate) fn add_virtual_block_metadata<F: RichField + Extendable<D>, const D: usize>(
     builder: &mut CircuitBuilder<F, D>,
 ) -> BlockMetadataTarget {
-    let block_beneficiary = builder.add_virtual_target_arr();
-    let block_timestamp = builder.add_virtual_target();
-    let block_number = builder.add_virtual_target();
-    let block_difficulty = builder.add_virtual_target();
-    let block_gaslimit = builder.add_virtual_target();
-    let block_chain_id = builder.add_virtual_target();
-    let block_base_fee = builder.add_virtual_target();
+    let block_beneficiary = builder.add_virtual_public_input_arr();
+    let block_timestamp = builder.add_virtual_public_input();
+    let block_number = builder.add_virtual_public_input();
+    let block_difficulty = builder.add_virtual_public_input();
+    let block_gaslimit = builder.add_virtual_public_input();
+    let block_chain_id = builder.add_virtual_public_input();
+    let block_base_fee = builder.add_virtual_public_input();
     BlockMetadataTarget {
         block_beneficiary,
         block_timestamp,
@@ -507,6 +559,23 @@ pub(crate) fn add_virtual_block_metadata<F: RichField + Extendable<D>, const D:
     }
 }
 
+pub(crate) fn add_virtual_extra_block_data<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+) -> ExtraBlockDataTarget {
+    let txn_number_before = builder.add_virtual_public_input();
+    let txn_number_after = builder.add_virtual_public_input();
+    let gas_used_before = builder.add_virtual_public_input();
+    let gas_used_after = builder.add_virtual_public_input();
+    let block_hashes = core::array::from_fn(|_| builder.add_virtual_public_input_arr());
+    ExtraBlockDataTarget {
+        txn_number_before,
+        txn_number_after,
+        gas_used_before,
+        gas_used_after,
+        block_hashes,
+    }
+}
+
 pub(crate) fn add_virtual_stark_proof<
     F: RichField + Extendable<D>,
     S: Stark<F, D>,
@@ -521,11 +590,12 @@ pub(crate) fn add_virtual_stark_proof<
     let fri_params = config.fri_params(degree_bits);
     let cap_height = fri_params.config.cap_height;
 
-    let num_leaves_per_oracle = vec![
+    let num_leaves_per_oracle = [
         S::COLUMNS,
         stark.num_permutation_batches(config) + num_ctl_zs,
-        stark.quotient_degree_factor() * config.num_challenges,
-    ];
+        stark.num_quotient_polys(config),
+    ]
+    .map(|num_polys| num_polys + salt_size(config.zero_knowledge));
 
     let permutation_zs_cap = builder.add_virtual_cap(cap_height);
 
@@ -544,7 +614,6 @@ fn add_virtual_stark_opening_set<F: RichField + Extendable<D>, S: Stark<F, D>, c
     num_ctl_zs: usize,
     config: &StarkConfig,
 ) -> StarkOpeningSetTarget<D> {
-    let num_challenges = config.num_challenges;
     StarkOpeningSetTarget {
         local_values: builder.add_virtual_extension_targets(S::COLUMNS),
         next_values: builder.add_virtual_extension_targets(S::COLUMNS),
@@ -553,8 +622,7 @@ fn add_virtual_stark_opening_set<F: RichField + Extendable<D>, S: Stark<F, D>, c
         permutation_ctl_zs_next: builder
             .add_virtual_extension_targets(stark.num_permutation_batches(config) + num_ctl_zs),
         ctl_zs_last: builder.add_virtual_targets(num_ctl_zs),
-        quotient_polys: builder
-            .add_virtual_extension_targets(stark.quotient_degree_factor() * num_challenges),
+        quotient_polys: builder.add_virtual_extension_targets(stark.num_quotient_polys(config)),
     }
 }
 
@@ -584,7 +652,6 @@ pub(crate) fn set_stark_proof_target<F, C: GenericConfig<D, F = F>, W, const D:
     set_fri_proof_target(witness, &proof_target.opening_proof, &proof.opening_proof);
 }
 
-#[allow(unused)] // TODO: used later?
 pub(crate) fn set_public_value_targets<F, W, const D: usize>(
     witness: &mut W,
     public_values_target: &PublicValuesTarget,
@@ -608,6 +675,11 @@ pub(crate) fn set_public_value_targets<F, W, const D: usize>(
         &public_values_target.block_metadata,
         &public_values.block_metadata,
     );
+    set_extra_block_data_target(
+        witness,
+        &public_values_target.extra_block_data,
+        &public_values.extra_block_data,
+    );
 }
 
 pub(crate) fn set_trie_roots_target<F, W, const D: usize>(
@@ -669,3 +741,36 @@ pub(crate) fn set_block_metadata_target<F, W, const D: usize>(
         F::from_canonical_u64(block_metadata.block_base_fee.as_u64()),
     );
 }
+
+pub(crate) fn set_extra_block_data_target<F, W, const D: usize>(
+    witness: &mut W,
+    extra_block_data_target: &ExtraBlockDataTarget,
+    extra_block_data: &ExtraBlockData,
+) where
+    F: RichField + Extendable<D>,
+    W: Witness<F>,
+{
+    witness.set_target(
+        extra_block_data_target.txn_number_before,
+        F::from_canonical_u64(extra_block_data.txn_number_before.as_u64()),
+    );
+    witness.set_target(
+        extra_block_data_target.txn_number_after,
+        F::from_canonical_u64(extra_block_data.txn_number_after.as_u64()),
+    );
+    witness.set_target(
+        extra_block_data_target.gas_used_before,
+        F::from_canonical_u64(extra_block_data.gas_used_before.as_u64()),
+    );
+    witness.set_target(
+        extra_block_data_target.gas_used_after,
+        F::from_canonical_u64(extra_block_data.gas_used_after.as_u64()),
+    );
+    for (&hash_target, &hash) in extra_block_data_target
+        .block_hashes
+        .iter()
+        .zip(&extra_block_data.block_hashes)
+    {
+        witness.set_target_arr(hash_target, h256_limbs(hash));
+    }
+}
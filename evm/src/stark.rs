@@ -75,8 +75,14 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         1.max(self.constraint_degree() - 1)
     }
 
+    /// The number of chunks each quotient polynomial is split into. In zero-knowledge mode, the
+    /// committed polynomials have twice the trace length, so one more chunk is needed.
+    fn num_quotient_chunks(&self, config: &StarkConfig) -> usize {
+        1.max(self.constraint_degree() - 1 + config.blinding_degree_bits())
+    }
+
     fn num_quotient_polys(&self, config: &StarkConfig) -> usize {
-        self.quotient_degree_factor() * config.num_challenges
+        self.num_quotient_chunks(config) * config.num_challenges
     }
 
     /// Computes the FRI instance used to prove this Stark.
@@ -90,7 +96,7 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
     ) -> FriInstanceInfo<F, D> {
         let trace_oracle = FriOracleInfo {
             num_polys: Self::COLUMNS,
-            blinding: false,
+            blinding: config.zero_knowledge,
         };
         let trace_info = FriPolynomialInfo::from_range(TRACE_ORACLE_INDEX, 0..Self::COLUMNS);
 
@@ -98,7 +104,7 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         let num_perutation_ctl_polys = num_permutation_batches + num_ctl_zs;
         let permutation_ctl_oracle = FriOracleInfo {
             num_polys: num_perutation_ctl_polys,
-            blinding: false,
+            blinding: config.zero_knowledge,
         };
         let permutation_ctl_zs_info = FriPolynomialInfo::from_range(
             PERMUTATION_CTL_ORACLE_INDEX,
@@ -113,7 +119,7 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         let num_quotient_polys = self.num_quotient_polys(config);
         let quotient_oracle = FriOracleInfo {
             num_polys: num_quotient_polys,
-            blinding: false,
+            blinding: config.zero_knowledge,
         };
         let quotient_info =
             FriPolynomialInfo::from_range(QUOTIENT_ORACLE_INDEX, 0..num_quotient_polys);
@@ -153,7 +159,7 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
     ) -> FriInstanceInfoTarget<D> {
         let trace_oracle = FriOracleInfo {
             num_polys: Self::COLUMNS,
-            blinding: false,
+            blinding: inner_config.zero_knowledge,
         };
         let trace_info = FriPolynomialInfo::from_range(TRACE_ORACLE_INDEX, 0..Self::COLUMNS);
 
@@ -161,7 +167,7 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         let num_perutation_ctl_polys = num_permutation_batches + num_ctl_zs;
         let permutation_ctl_oracle = FriOracleInfo {
             num_polys: num_perutation_ctl_polys,
-            blinding: false,
+            blinding: inner_config.zero_knowledge,
         };
         let permutation_ctl_zs_info = FriPolynomialInfo::from_range(
             PERMUTATION_CTL_ORACLE_INDEX,
@@ -176,7 +182,7 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         let num_quotient_polys = self.num_quotient_polys(inner_config);
         let quotient_oracle = FriOracleInfo {
             num_polys: num_quotient_polys,
-            blinding: false,
+            blinding: inner_config.zero_knowledge,
         };
         let quotient_info =
             FriPolynomialInfo::from_range(QUOTIENT_ORACLE_INDEX, 0..num_quotient_polys);
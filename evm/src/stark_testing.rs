@@ -149,6 +149,40 @@ where
     data.verify(proof)
 }
 
+/// Tests that every row of the given trace satisfies the constraints imposed by the given STARK,
+/// ignoring permutation arguments and cross-table lookups.
+pub fn test_stark_trace_constraints<F: RichField + Extendable<D>, S: Stark<F, D>, const D: usize>(
+    stark: &S,
+    trace: &[PolynomialValues<F>],
+) -> Result<()>
+where
+    [(); S::COLUMNS]:,
+{
+    let rows = transpose(&trace.iter().map(|c| c.values.clone()).collect::<Vec<_>>());
+    let n = rows.len();
+    let alpha = F::rand();
+    for i in 0..n {
+        let vars = StarkEvaluationVars {
+            local_values: rows[i].as_slice().try_into().unwrap(),
+            next_values: rows[(i + 1) % n].as_slice().try_into().unwrap(),
+        };
+        let mut consumer = ConstraintConsumer::<F>::new(
+            vec![alpha],
+            F::from_bool(i != n - 1),
+            F::from_bool(i == 0),
+            F::from_bool(i == n - 1),
+        );
+        stark.eval_packed_base(vars, &mut consumer);
+        ensure!(
+            consumer.accumulators()[0].is_zero(),
+            "Constraint failed in row {}",
+            i
+        );
+    }
+
+    Ok(())
+}
+
 fn random_low_degree_matrix<F: Field>(num_polys: usize, rate_bits: usize) -> Vec<Vec<F>> {
     let polys = (0..num_polys)
         .map(|_| random_low_degree_values(rate_bits))
@@ -45,7 +45,6 @@ pub fn trace_rows_to_poly_values<F: Field, const COLUMNS: usize>(
         .collect()
 }
 
-#[allow(unused)] // TODO: Remove?
 /// Returns the 32-bit little-endian limbs of a `U256`.
 pub(crate) fn u256_limbs<F: Field>(u256: U256) -> [F; 8] {
     u256.0
@@ -61,24 +60,16 @@ pub(crate) fn u256_limbs<F: Field>(u256: U256) -> [F; 8] {
         .unwrap()
 }
 
-/// Returns the 32-bit little-endian limbs of a `H256`.
+/// Returns the 32-bit little-endian limbs of a `H256`, read as a big-endian integer like the kernel
+/// does. These are the limbs with which it is stored in memory.
 pub(crate) fn h256_limbs<F: Field>(h256: H256) -> [F; 8] {
-    h256.0
-        .chunks(4)
-        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
-        .map(F::from_canonical_u32)
-        .collect_vec()
-        .try_into()
-        .unwrap()
+    u256_limbs(U256::from_big_endian(&h256.0))
 }
 
-/// Returns the 32-bit limbs of a `U160`.
+/// Returns the 32-bit little-endian limbs of a `H160`, read as a big-endian integer like the kernel
+/// does.
 pub(crate) fn h160_limbs<F: Field>(h160: H160) -> [F; 5] {
-    h160.0
-        .chunks(4)
-        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
-        .map(F::from_canonical_u32)
-        .collect_vec()
+    u256_limbs(U256::from_big_endian(&h160.0))[..5]
         .try_into()
         .unwrap()
 }
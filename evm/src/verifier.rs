@@ -8,20 +8,22 @@ use plonky2::hash::hash_types::RichField;
 use plonky2::plonk::config::{GenericConfig, Hasher};
 use plonky2::plonk::plonk_common::reduce_with_powers;
 
-use crate::all_stark::{AllStark, Table};
+use crate::all_stark::{AllStark, Table, NUM_TABLES};
 use crate::config::StarkConfig;
 use crate::constraint_consumer::ConstraintConsumer;
 use crate::cpu::cpu_stark::CpuStark;
 use crate::cross_table_lookup::{verify_cross_table_lookups, CtlCheckVars};
+use crate::generation::PUBLIC_VALUES_CHANNEL;
 use crate::keccak::keccak_stark::KeccakStark;
 use crate::keccak_sponge::keccak_sponge_stark::KeccakSpongeStark;
 use crate::logic::LogicStark;
 use crate::memory::memory_stark::MemoryStark;
-use crate::permutation::PermutationCheckVars;
+use crate::permutation::{GrandProductChallengeSet, PermutationCheckVars};
 use crate::proof::{
-    AllProof, AllProofChallenges, StarkOpeningSet, StarkProof, StarkProofChallenges,
+    AllProof, AllProofChallenges, PublicValues, StarkOpeningSet, StarkProof, StarkProofChallenges,
 };
 use crate::stark::Stark;
+use crate::util::u256_limbs;
 use crate::vanishing_poly::eval_vanishing_poly;
 use crate::vars::StarkEvaluationVars;
 
@@ -41,9 +43,10 @@ where
     let AllProofChallenges {
         stark_challenges,
         ctl_challenges,
-    } = all_proof.get_challenges(all_stark, config);
+    } = all_proof.get_challenges(all_stark, config)?;
 
     let nums_permutation_zs = all_stark.nums_permutation_zs(config);
+    let degree_bits = all_proof.degree_bits(config)?;
 
     let AllStark {
         cpu_stark,
@@ -64,6 +67,7 @@ where
     verify_stark_proof_with_challenges(
         cpu_stark,
         &all_proof.stark_proofs[Table::Cpu as usize].proof,
+        degree_bits[Table::Cpu as usize],
         &stark_challenges[Table::Cpu as usize],
         &ctl_vars_per_table[Table::Cpu as usize],
         config,
@@ -71,6 +75,7 @@ where
     verify_stark_proof_with_challenges(
         keccak_stark,
         &all_proof.stark_proofs[Table::Keccak as usize].proof,
+        degree_bits[Table::Keccak as usize],
         &stark_challenges[Table::Keccak as usize],
         &ctl_vars_per_table[Table::Keccak as usize],
         config,
@@ -78,32 +83,74 @@ where
     verify_stark_proof_with_challenges(
         keccak_sponge_stark,
         &all_proof.stark_proofs[Table::KeccakSponge as usize].proof,
+        degree_bits[Table::KeccakSponge as usize],
         &stark_challenges[Table::KeccakSponge as usize],
         &ctl_vars_per_table[Table::KeccakSponge as usize],
         config,
     )?;
-    verify_stark_proof_with_challenges(
-        memory_stark,
-        &all_proof.stark_proofs[Table::Memory as usize].proof,
-        &stark_challenges[Table::Memory as usize],
-        &ctl_vars_per_table[Table::Memory as usize],
-        config,
-    )?;
     verify_stark_proof_with_challenges(
         logic_stark,
         &all_proof.stark_proofs[Table::Logic as usize].proof,
+        degree_bits[Table::Logic as usize],
         &stark_challenges[Table::Logic as usize],
         &ctl_vars_per_table[Table::Logic as usize],
         config,
     )?;
+    verify_stark_proof_with_challenges(
+        memory_stark,
+        &all_proof.stark_proofs[Table::Memory as usize].proof,
+        degree_bits[Table::Memory as usize],
+        &stark_challenges[Table::Memory as usize],
+        &ctl_vars_per_table[Table::Memory as usize],
+        config,
+    )?;
 
     verify_cross_table_lookups::<F, D>(
         cross_table_lookups,
         all_proof.stark_proofs.map(|p| p.proof.openings.ctl_zs_last),
+        &ctl_extra_looking_products(&all_proof.public_values, &ctl_challenges),
         config,
     )
 }
 
+/// The memory CTL rows of the writes of the public values to the kernel's global metadata and block
+/// hashes, made before the first CPU cycle. The memory table contains these writes, but no other
+/// table looks them up, so the verifier does.
+pub(crate) fn public_values_memory_rows<F: Field>(public_values: &PublicValues) -> Vec<Vec<F>> {
+    let timestamp = F::from_canonical_usize(PUBLIC_VALUES_CHANNEL.index());
+    public_values
+        .memory_values()
+        .into_iter()
+        .map(|(segment, offset, value)| {
+            let mut row = vec![
+                F::ZERO,
+                F::ZERO,
+                F::from_canonical_usize(segment as usize),
+                F::from_canonical_usize(offset),
+            ];
+            row.extend(u256_limbs::<F>(value));
+            row.push(timestamp);
+            row
+        })
+        .collect()
+}
+
+/// For each table and each CTL challenge, the product of the combined rows which the verifier looks
+/// up in that table, i.e. the `public_values_memory_rows` for the memory table.
+pub(crate) fn ctl_extra_looking_products<F: RichField>(
+    public_values: &PublicValues,
+    ctl_challenges: &GrandProductChallengeSet<F>,
+) -> [Vec<F>; NUM_TABLES] {
+    let rows = public_values_memory_rows::<F>(public_values);
+    let mut products = [(); NUM_TABLES].map(|_| vec![F::ONE; ctl_challenges.challenges.len()]);
+    products[Table::Memory as usize] = ctl_challenges
+        .challenges
+        .iter()
+        .map(|challenge| rows.iter().map(|row| challenge.combine(row)).product())
+        .collect();
+    products
+}
+
 pub(crate) fn verify_stark_proof_with_challenges<
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
@@ -112,6 +159,7 @@ pub(crate) fn verify_stark_proof_with_challenges<
 >(
     stark: &S,
     proof: &StarkProof<F, C, D>,
+    degree_bits: usize,
     challenges: &StarkProofChallenges<F, D>,
     ctl_vars: &[CtlCheckVars<F, F::Extension, F::Extension, D>],
     config: &StarkConfig,
@@ -135,7 +183,6 @@ where
         next_values: &next_values.to_vec().try_into().unwrap(),
     };
 
-    let degree_bits = proof.recover_degree_bits(config);
     let (l_0, l_last) = eval_l_0_and_l_last(degree_bits, challenges.stark_zeta);
     let last = F::primitive_root_of_unity(degree_bits).inverse();
     let z_last = challenges.stark_zeta - last.into();
@@ -168,17 +215,21 @@ where
     // Check each polynomial identity, of the form `vanishing(x) = Z_H(x) quotient(x)`, at zeta.
     let zeta_pow_deg = challenges.stark_zeta.exp_power_of_2(degree_bits);
     let z_h_zeta = zeta_pow_deg - F::Extension::ONE;
-    // `quotient_polys_zeta` holds `num_challenges * quotient_degree_factor` evaluations.
-    // Each chunk of `quotient_degree_factor` holds the evaluations of `t_0(zeta),...,t_{quotient_degree_factor-1}(zeta)`
-    // where the "real" quotient polynomial is `t(X) = t_0(X) + t_1(X)*X^n + t_2(X)*X^{2n} + ...`.
-    // So to reconstruct `t(zeta)` we can compute `reduce_with_powers(chunk, zeta^n)` for each
-    // `quotient_degree_factor`-sized chunk of the original evaluations.
+    // `quotient_polys_zeta` holds `num_challenges * num_quotient_chunks` evaluations.
+    // Each chunk of `num_quotient_chunks` holds the evaluations of `t_0(zeta),...,t_{num_quotient_chunks-1}(zeta)`
+    // where the "real" quotient polynomial is `t(X) = t_0(X) + t_1(X)*X^m + t_2(X)*X^{2m} + ...`,
+    // and `m` is the degree of the committed polynomials, i.e. `n`, or `2n` in zero-knowledge mode.
+    // So to reconstruct `t(zeta)` we can compute `reduce_with_powers(chunk, zeta^m)` for each
+    // `num_quotient_chunks`-sized chunk of the original evaluations.
+    let zeta_pow_chunk_deg = challenges
+        .stark_zeta
+        .exp_power_of_2(degree_bits + config.blinding_degree_bits());
     for (i, chunk) in quotient_polys
-        .chunks(stark.quotient_degree_factor())
+        .chunks(stark.num_quotient_chunks(config))
         .enumerate()
     {
         ensure!(
-            vanishing_polys_zeta[i] == z_h_zeta * reduce_with_powers(chunk, zeta_pow_deg),
+            vanishing_polys_zeta[i] == z_h_zeta * reduce_with_powers(chunk, zeta_pow_chunk_deg),
             "Mismatch between evaluation and opening of quotient polynomial"
         );
     }
@@ -239,9 +290,7 @@ where
         quotient_polys,
     } = openings;
 
-    let degree_bits = proof.recover_degree_bits(config);
-    let fri_params = config.fri_params(degree_bits);
-    let cap_height = fri_params.config.cap_height;
+    let cap_height = config.fri_config.cap_height;
     let num_zs = num_ctl_zs + stark.num_permutation_batches(config);
 
     ensure!(trace_cap.height() == cap_height);
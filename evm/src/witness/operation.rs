@@ -465,9 +465,14 @@ fn append_shift<F: Field>(
     } else {
         // The shift constraints still expect the address to be set, even though no read will occur.
         let mut channel = &mut row.mem_channels[LOOKUP_CHANNEL];
+        channel.is_read = F::ONE;
         channel.addr_context = F::from_canonical_usize(lookup_addr.context);
         channel.addr_segment = F::from_canonical_usize(lookup_addr.segment);
         channel.addr_virtual = F::from_canonical_usize(lookup_addr.virt);
+
+        // Extra field required by the constraints for large shifts.
+        let high_limb_sum = row.mem_channels[0].value[1..].iter().copied().sum::<F>();
+        row.general.shift_mut().high_limb_sum_inv = high_limb_sum.inverse();
     }
 
     state.traces.push_memory(log_in0);
@@ -535,11 +540,74 @@ pub(crate) fn generate_syscall<F: Field>(
     let syscall_info = U256::from(state.registers.program_counter + 1)
         + (U256::from(u64::from(state.registers.is_kernel)) << 32)
         + (U256::from(state.registers.gas_used) << 192);
+    // The syscall info is pushed in kernel mode, so it may exceed the user stack limit. Syscalls
+    // which push to the user stack are checked for overflow before they are executed.
+    state.registers.is_kernel = true;
     let log_out = stack_push_log_and_fill(state, &mut row, syscall_info)?;
 
     state.registers.program_counter = new_program_counter;
     log::debug!("Syscall to {}", KERNEL.offset_name(new_program_counter));
+    state.registers.gas_used = 0;
+
+    state.traces.push_memory(log_in0);
+    state.traces.push_memory(log_in1);
+    state.traces.push_memory(log_in2);
+    state.traces.push_memory(log_out);
+    state.traces.push_cpu(row);
+
+    Ok(())
+}
+
+pub(crate) fn generate_exception<F: Field>(
+    exc_code: u8,
+    state: &mut GenerationState<F>,
+    mut row: CpuColumnsView<F>,
+) -> Result<(), ProgramError> {
+    if TryInto::<u32>::try_into(state.registers.gas_used).is_err() {
+        panic!();
+    }
+
+    row.general.exception_mut().exc_code_bits =
+        [0, 1, 2].map(|i| F::from_bool(exc_code & (1 << i) != 0));
+
+    let handler_jumptable_addr = KERNEL.global_labels["exception_jumptable"];
+    let handler_addr_addr =
+        handler_jumptable_addr + (exc_code as usize) * (BYTES_PER_OFFSET as usize);
+    assert_eq!(BYTES_PER_OFFSET, 3, "Code below assumes 3 bytes per offset");
+    let (handler_addr0, log_in0) = mem_read_gp_with_log_and_fill(
+        0,
+        MemoryAddress::new(0, Segment::Code, handler_addr_addr),
+        state,
+        &mut row,
+    );
+    let (handler_addr1, log_in1) = mem_read_gp_with_log_and_fill(
+        1,
+        MemoryAddress::new(0, Segment::Code, handler_addr_addr + 1),
+        state,
+        &mut row,
+    );
+    let (handler_addr2, log_in2) = mem_read_gp_with_log_and_fill(
+        2,
+        MemoryAddress::new(0, Segment::Code, handler_addr_addr + 2),
+        state,
+        &mut row,
+    );
+
+    let handler_addr = (handler_addr0 << 16) + (handler_addr1 << 8) + handler_addr2;
+    let new_program_counter = handler_addr.as_usize();
+
+    // Unlike a syscall, an exception returns the address of the faulting instruction itself, and
+    // the length of the user stack, which the handler needs to check for stack exceptions.
+    let exc_info = U256::from(state.registers.program_counter)
+        + (U256::from(state.registers.stack_len) << 64)
+        + (U256::from(state.registers.gas_used) << 192);
+    // Exceptions are only raised in user mode, but the exception info is pushed in kernel mode, so
+    // that it may be pushed on a full stack.
     state.registers.is_kernel = true;
+    let log_out = stack_push_log_and_fill(state, &mut row, exc_info)?;
+
+    state.registers.program_counter = new_program_counter;
+    log::debug!("Exception to {}", KERNEL.offset_name(new_program_counter));
     state.registers.gas_used = 0;
 
     state.traces.push_memory(log_in0);
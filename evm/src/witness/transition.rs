@@ -1,9 +1,13 @@
+use ethereum_types::U256;
 use itertools::Itertools;
 use log::log_enabled;
 use plonky2::field::types::Field;
 
 use crate::cpu::columns::CpuColumnsView;
 use crate::cpu::kernel::aggregator::KERNEL;
+use crate::cpu::kernel::constants::context_metadata::ContextMetadata;
+use crate::cpu::stack_bounds;
+use crate::cpu::stack_bounds::MAX_USER_STACK_SIZE;
 use crate::generation::state::GenerationState;
 use crate::memory::segments::Segment;
 use crate::witness::errors::ProgramError;
@@ -108,6 +112,7 @@ fn decode(registers: RegistersState, opcode: u8) -> Result<Operation, ProgramErr
         (0x57, _) => Ok(Operation::Jumpi),
         (0x58, _) => Ok(Operation::Pc),
         (0x59, _) => Ok(Operation::Syscall(opcode)),
+        (0x5a, _) => Ok(Operation::Syscall(opcode)),
         (0x5b, _) => Ok(Operation::Jumpdest),
         (0x60..=0x7f, _) => Ok(Operation::Push(opcode & 0x1f)),
         (0x80..=0x8f, _) => Ok(Operation::Dup(opcode & 0xf)),
@@ -117,7 +122,7 @@ fn decode(registers: RegistersState, opcode: u8) -> Result<Operation, ProgramErr
         (0xa2, _) => Ok(Operation::Syscall(opcode)),
         (0xa3, _) => Ok(Operation::Syscall(opcode)),
         (0xa4, _) => Ok(Operation::Syscall(opcode)),
-        (0xa5, _) => panic!(
+        (0xa5, true) => panic!(
             "Kernel panic at {}",
             KERNEL.offset_name(registers.program_counter)
         ),
@@ -135,11 +140,60 @@ fn decode(registers: RegistersState, opcode: u8) -> Result<Operation, ProgramErr
         (0xfc, true) => Ok(Operation::MstoreGeneral),
         (0xfd, _) => Ok(Operation::Syscall(opcode)),
         (0xff, _) => Ok(Operation::Syscall(opcode)),
-        _ => {
-            log::warn!("Invalid opcode: {}", opcode);
-            Err(ProgramError::InvalidOpcode)
+        _ => Err(ProgramError::InvalidOpcode),
+    }
+}
+
+/// Bitfield of the opcodes which are invalid in user mode, in little-endian order.
+pub(crate) fn invalid_opcodes_user() -> [u8; 32] {
+    let registers = RegistersState {
+        program_counter: 0,
+        is_kernel: false,
+        stack_len: 0,
+        context: 0,
+        gas_used: 0,
+    };
+
+    let mut res = [0; 32];
+    for opcode in 0..=u8::MAX {
+        if decode(registers, opcode).is_err() {
+            res[opcode as usize / 8] |= 1 << (opcode % 8);
         }
     }
+    res
+}
+
+/// Reads the entry for `opcode` in one of the kernel's per-opcode tables; see `exception.asm`.
+fn kernel_opcode_table(label: &str, opcode: u8) -> u8 {
+    KERNEL.code[KERNEL.global_labels[label] + opcode as usize]
+}
+
+/// Checks for the exceptions which user code may raise before executing an instruction, namely
+/// stack underflow, stack overflow and running out of gas. The kernel's exception handlers verify
+/// these using the same tables.
+fn check_user_exceptions<F: Field>(
+    state: &GenerationState<F>,
+    opcode: u8,
+    op: Operation,
+) -> Result<(), ProgramError> {
+    let stack_len = state.registers.stack_len;
+    if stack_len < kernel_opcode_table("min_stack_len_for_opcode", opcode) as usize {
+        return Err(ProgramError::StackUnderflow);
+    }
+    if kernel_opcode_table("stack_len_increased", opcode) != 0 && stack_len >= MAX_USER_STACK_SIZE {
+        return Err(ProgramError::StackOverflow);
+    }
+
+    let gas_limit = state.memory.get(MemoryAddress::new(
+        state.registers.context,
+        Segment::ContextMetadata,
+        ContextMetadata::GasLimit as usize,
+    ));
+    if U256::from(state.registers.gas_used + gas_to_charge(op)) > gas_limit {
+        return Err(ProgramError::OutOfGas);
+    }
+
+    Ok(())
 }
 
 fn fill_op_flag<F: Field>(op: Operation, row: &mut CpuColumnsView<F>) {
@@ -233,7 +287,8 @@ fn perform_op<F: Field>(
     Ok(())
 }
 
-fn try_perform_instruction<F: Field>(state: &mut GenerationState<F>) -> Result<(), ProgramError> {
+/// Fills in the CPU row fields which are common to all instructions, and reads the opcode.
+fn base_row<F: Field>(state: &mut GenerationState<F>) -> (CpuColumnsView<F>, u8) {
     let mut row: CpuColumnsView<F> = CpuColumnsView::default();
     row.is_cpu_cycle = F::ONE;
     row.clock = F::from_canonical_usize(state.traces.clock());
@@ -244,15 +299,22 @@ fn try_perform_instruction<F: Field>(state: &mut GenerationState<F>) -> Result<(
     row.stack_len = F::from_canonical_usize(state.registers.stack_len);
 
     let opcode = read_code_memory(state, &mut row);
+    (row, opcode)
+}
+
+fn try_perform_instruction<F: Field>(state: &mut GenerationState<F>) -> Result<(), ProgramError> {
+    let (mut row, opcode) = base_row(state);
     let op = decode(state.registers, opcode)?;
 
     if state.registers.is_kernel {
         log_kernel_instruction(state, op);
     } else {
         log::debug!("User instruction: {:?}", op);
+        check_user_exceptions(state, opcode, op)?;
     }
 
     fill_op_flag(op, &mut row);
+    stack_bounds::generate(&mut row); // Must come after `fill_op_flag`.
 
     perform_op(state, op, row)
 }
@@ -288,8 +350,32 @@ fn log_kernel_instruction<F: Field>(state: &mut GenerationState<F>, op: Operatio
     assert!(pc < KERNEL.code.len(), "Kernel PC is out of range: {}", pc);
 }
 
-fn handle_error<F: Field>(_state: &mut GenerationState<F>) {
-    todo!("generation for exception handling is not implemented");
+/// Traps to the kernel's handler for the given exception, which halts the current context.
+fn handle_error<F: Field>(state: &mut GenerationState<F>, err: ProgramError) {
+    let exc_code: u8 = match err {
+        ProgramError::OutOfGas => 0,
+        ProgramError::InvalidOpcode => 1,
+        ProgramError::StackUnderflow => 2,
+        ProgramError::InvalidJumpDestination => 3,
+        ProgramError::InvalidJumpiDestination => 4,
+        ProgramError::StackOverflow => 5,
+    };
+    log::debug!(
+        "Exception {:?} at pc={} in context {}",
+        err,
+        state.registers.program_counter,
+        state.registers.context
+    );
+
+    let checkpoint = state.checkpoint();
+    let (mut row, _) = base_row(state);
+    row.op.exception = F::ONE;
+    stack_bounds::generate(&mut row); // Must come after setting the op flag.
+    generate_exception(exc_code, state, row).expect("an exception cannot overflow the stack");
+
+    state
+        .memory
+        .apply_ops(state.traces.mem_ops_since(checkpoint.traces));
 }
 
 pub(crate) fn transition<F: Field>(state: &mut GenerationState<F>) {
@@ -308,7 +394,7 @@ pub(crate) fn transition<F: Field>(state: &mut GenerationState<F>) {
                 panic!("exception in kernel mode at {}: {:?}", offset_name, e);
             }
             state.rollback(checkpoint);
-            handle_error(state)
+            handle_error(state, e)
         }
     }
 }
@@ -3,7 +3,7 @@ use std::time::Duration;
 
 use env_logger::{try_init_from_env, Env, DEFAULT_FILTER_ENV};
 use eth_trie_utils::partial_trie::{Nibbles, PartialTrie};
-use ethereum_types::U256;
+use ethereum_types::{Bloom, U256};
 use hex_literal::hex;
 use keccak_hash::keccak;
 use plonky2::field::goldilocks_field::GoldilocksField;
@@ -12,9 +12,9 @@ use plonky2::util::timing::TimingTree;
 use plonky2_evm::all_stark::AllStark;
 use plonky2_evm::config::StarkConfig;
 use plonky2_evm::cpu::kernel::opcodes::{get_opcode, get_push_opcode};
-use plonky2_evm::generation::mpt::AccountRlp;
+use plonky2_evm::generation::mpt::{AccountRlp, LegacyReceiptRlp, ReceiptRlp};
 use plonky2_evm::generation::{GenerationInputs, TrieInputs};
-use plonky2_evm::proof::BlockMetadata;
+use plonky2_evm::proof::{BlockMetadata, TrieRoots};
 use plonky2_evm::prover::prove;
 use plonky2_evm::verifier::verify_proof;
 
@@ -87,21 +87,12 @@ fn test_basic_smart_contract() -> anyhow::Result<()> {
 
     let mut contract_code = HashMap::new();
     contract_code.insert(code_hash, code.to_vec());
-    let inputs = GenerationInputs {
-        signed_txns: vec![txn.to_vec()],
-        tries: tries_before,
-        contract_code,
-        block_metadata,
-    };
-
-    let mut timing = TimingTree::new("prove", log::Level::Debug);
-    let proof = prove::<F, C, D>(&all_stark, &config, inputs, &mut timing)?;
-    timing.filter(Duration::from_millis(100)).print();
-
+    // The intrinsic gas of a call with two non-zero bytes of data, plus the contract's two PUSH1s
+    // and its ADD.
+    let gas_used = 21_000 + 2 * 16 + 3 * 3;
     let expected_state_trie_after = {
         let sender_account_after = AccountRlp {
-            // TODO: Should be 21k; 1k gas should be refunded.
-            balance: sender_account_before.balance - value - 22_000 * 10,
+            balance: sender_account_before.balance - value - gas_used * 10,
             nonce: sender_account_before.nonce + 1,
             ..sender_account_before
         };
@@ -128,10 +119,39 @@ fn test_basic_smart_contract() -> anyhow::Result<()> {
         }
     };
 
-    assert_eq!(
-        proof.public_values.trie_roots_after.state_root,
-        expected_state_trie_after.calc_hash()
-    );
+    let receipt = ReceiptRlp {
+        txn_type: 0,
+        receipt: LegacyReceiptRlp {
+            status: true,
+            cum_gas_used: gas_used.into(),
+            bloom: Bloom::zero(),
+            logs: vec![],
+        },
+    };
+    let expected_receipts_trie_after = PartialTrie::Leaf {
+        nibbles: Nibbles::from_bytes_be(&rlp::encode(&0u64)).unwrap(),
+        value: receipt.encode(),
+    };
+
+    let inputs = GenerationInputs {
+        signed_txns: vec![txn.to_vec()],
+        txn_number_before: 0.into(),
+        gas_used_before: 0.into(),
+        gas_used_after: gas_used.into(),
+        tries: tries_before,
+        contract_code,
+        block_metadata,
+        trie_roots_after: TrieRoots {
+            state_root: expected_state_trie_after.calc_hash(),
+            transactions_root: PartialTrie::Empty.calc_hash(),
+            receipts_root: expected_receipts_trie_after.calc_hash(),
+        },
+        block_hashes: vec![],
+    };
+
+    let mut timing = TimingTree::new("prove", log::Level::Debug);
+    let proof = prove::<F, C, D>(&all_stark, &config, inputs, &mut timing)?;
+    timing.filter(Duration::from_millis(100)).print();
 
     verify_proof(&all_stark, proof, &config)
 }
@@ -10,7 +10,7 @@ use plonky2_evm::all_stark::AllStark;
 use plonky2_evm::config::StarkConfig;
 use plonky2_evm::fixed_recursive_verifier::AllRecursiveCircuits;
 use plonky2_evm::generation::{GenerationInputs, TrieInputs};
-use plonky2_evm::proof::BlockMetadata;
+use plonky2_evm::proof::{BlockMetadata, TrieRoots};
 use plonky2_evm::prover::prove;
 use plonky2_evm::verifier::verify_proof;
 
@@ -40,6 +40,9 @@ fn test_empty_txn_list() -> anyhow::Result<()> {
 
     let inputs = GenerationInputs {
         signed_txns: vec![],
+        txn_number_before: 0.into(),
+        gas_used_before: 0.into(),
+        gas_used_after: 0.into(),
         tries: TrieInputs {
             state_trie,
             transactions_trie,
@@ -48,12 +51,18 @@ fn test_empty_txn_list() -> anyhow::Result<()> {
         },
         contract_code: HashMap::new(),
         block_metadata,
+        trie_roots_after: TrieRoots {
+            state_root: state_trie_root,
+            transactions_root: txns_trie_root,
+            receipts_root: receipts_trie_root,
+        },
+        block_hashes: vec![],
     };
 
     let mut timing = TimingTree::new("prove", log::Level::Debug);
     // TODO: This is redundant; prove_root below calls this prove method internally.
-    // Just keeping it for now because the root proof returned by prove_root doesn't contain public
-    // values yet, and we want those for the assertions below.
+    // Just keeping it for now because the root proof returned by prove_root only exposes its public
+    // values as raw public inputs, and we want them as `PublicValues` for the assertions below.
     let proof = prove::<F, C, D>(&all_stark, &config, inputs.clone(), &mut timing)?;
     timing.filter(Duration::from_millis(100)).print();
 
@@ -84,12 +93,34 @@ fn test_empty_txn_list() -> anyhow::Result<()> {
 
     verify_proof(&all_stark, proof, &config)?;
 
-    let all_circuits = AllRecursiveCircuits::<F, C, D>::new(&all_stark, 9..19, &config);
-    let root_proof = all_circuits.prove_root(&all_stark, &config, inputs, &mut timing)?;
+    let all_circuits = AllRecursiveCircuits::<F, C, D>::new(
+        &all_stark,
+        &[15..16, 14..15, 10..11, 12..13, 18..19],
+        &config,
+    );
+    let root_proof = all_circuits.prove_root(&all_stark, &config, inputs.clone(), &mut timing)?;
     all_circuits.verify_root(root_proof.clone())?;
 
     let agg_proof = all_circuits.prove_aggregation(false, &root_proof, false, &root_proof)?;
-    all_circuits.verify_aggregation(&agg_proof)
+    all_circuits.verify_aggregation(&agg_proof)?;
+
+    let block_proof = all_circuits.prove_block(None, &agg_proof)?;
+    all_circuits.verify_block(&block_proof)?;
+
+    // Chain a second empty block on top of the first one. It starts from the same state root, and
+    // has the next block number.
+    let next_inputs = GenerationInputs {
+        block_metadata: BlockMetadata {
+            block_number: inputs.block_metadata.block_number + 1,
+            ..inputs.block_metadata.clone()
+        },
+        ..inputs
+    };
+    let next_root_proof = all_circuits.prove_root(&all_stark, &config, next_inputs, &mut timing)?;
+    let next_agg_proof =
+        all_circuits.prove_aggregation(false, &next_root_proof, false, &next_root_proof)?;
+    let next_block_proof = all_circuits.prove_block(Some(&block_proof), &next_agg_proof)?;
+    all_circuits.verify_block(&next_block_proof)
 }
 
 fn init_logger() {
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use env_logger::{try_init_from_env, Env, DEFAULT_FILTER_ENV};
+use eth_trie_utils::partial_trie::{Nibbles, PartialTrie};
+use ethereum_types::{Bloom, U256};
+use hex_literal::hex;
+use keccak_hash::keccak;
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::plonk::config::PoseidonGoldilocksConfig;
+use plonky2::util::timing::TimingTree;
+use plonky2_evm::all_stark::AllStark;
+use plonky2_evm::config::StarkConfig;
+use plonky2_evm::cpu::kernel::opcodes::{get_opcode, get_push_opcode};
+use plonky2_evm::generation::mpt::{AccountRlp, LegacyReceiptRlp, ReceiptRlp};
+use plonky2_evm::generation::{GenerationInputs, TrieInputs};
+use plonky2_evm::proof::{BlockMetadata, TrieRoots};
+use plonky2_evm::prover::prove;
+use plonky2_evm::verifier::verify_proof;
+
+type F = GoldilocksField;
+const D: usize = 2;
+type C = PoseidonGoldilocksConfig;
+
+/// Test a transaction whose contract faults on an invalid jump. The transaction should fail,
+/// burning all of its gas, rather than making witness generation fail.
+#[test]
+fn test_failed_txn() -> anyhow::Result<()> {
+    let push1 = get_push_opcode(1);
+    let jump = get_opcode("JUMP");
+    let add = get_opcode("ADD");
+    let stop = get_opcode("STOP");
+    // Offset 3 holds a PUSH1 rather than a JUMPDEST, so the jump is invalid.
+    let code = [push1, 3, jump, push1, 4, push1, 5, add, stop];
+    prove_failed_txn(&code)
+}
+
+/// Test a transaction whose contract writes to storage before hitting an invalid opcode. The
+/// storage write should be reverted along with the rest of the transaction's state changes.
+#[test]
+fn test_failed_txn_reverts_storage() -> anyhow::Result<()> {
+    let push1 = get_push_opcode(1);
+    let sstore = get_opcode("SSTORE");
+    let invalid = get_opcode("INVALID");
+    let code = [push1, 1, push1, 0, sstore, invalid];
+    prove_failed_txn(&code)
+}
+
+/// Proves a transaction calling the given code, which is expected to fail, leaving the state as it
+/// was before the call.
+fn prove_failed_txn(code: &[u8]) -> anyhow::Result<()> {
+    init_logger();
+
+    let all_stark = AllStark::<F, D>::default();
+    let config = StarkConfig::standard_fast_config();
+
+    let sender = hex!("2c7536e3605d9c16a7a3d7b1898e529396a65c23");
+    let to = hex!("a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0");
+    let sender_state_key = keccak(sender);
+    let to_state_key = keccak(to);
+    let sender_nibbles = Nibbles::from_bytes_be(sender_state_key.as_bytes()).unwrap();
+    let to_nibbles = Nibbles::from_bytes_be(to_state_key.as_bytes()).unwrap();
+
+    let code_hash = keccak(code);
+
+    let sender_account_before = AccountRlp {
+        nonce: 5.into(),
+        balance: eth_to_wei(100_000.into()),
+        ..AccountRlp::default()
+    };
+
+    let to_account_before = AccountRlp {
+        code_hash,
+        ..AccountRlp::default()
+    };
+
+    let state_trie_before = {
+        let mut children = core::array::from_fn(|_| PartialTrie::Empty.into());
+        children[sender_nibbles.get_nibble(0) as usize] = PartialTrie::Leaf {
+            nibbles: sender_nibbles.truncate_n_nibbles_front(1),
+            value: rlp::encode(&sender_account_before).to_vec(),
+        }
+        .into();
+        children[to_nibbles.get_nibble(0) as usize] = PartialTrie::Leaf {
+            nibbles: to_nibbles.truncate_n_nibbles_front(1),
+            value: rlp::encode(&to_account_before).to_vec(),
+        }
+        .into();
+        PartialTrie::Branch {
+            children,
+            value: vec![],
+        }
+    };
+
+    let tries_before = TrieInputs {
+        state_trie: state_trie_before,
+        transactions_trie: PartialTrie::Empty,
+        receipts_trie: PartialTrie::Empty,
+        storage_tries: vec![(to.into(), PartialTrie::Empty)],
+    };
+
+    // Generated using a little py-evm script.
+    let txn = hex!("f861050a8255f094a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0648242421ba02c89eb757d9deeb1f5b3859a9d4d679951ef610ac47ad4608dc142beb1b7e313a05af7e9fbab825455d36c36c7f4cfcafbeafa9a77bdff936b52afb36d4fe4bcdd");
+
+    let block_metadata = BlockMetadata::default();
+
+    let mut contract_code = HashMap::new();
+    contract_code.insert(code_hash, code.to_vec());
+    let expected_state_trie_after = {
+        let sender_account_after = AccountRlp {
+            // A failed transaction consumes its whole gas limit, and its value is returned.
+            balance: sender_account_before.balance - 22_000 * 10,
+            nonce: sender_account_before.nonce + 1,
+            ..sender_account_before
+        };
+        let to_account_after = to_account_before;
+
+        let mut children = core::array::from_fn(|_| PartialTrie::Empty.into());
+        children[sender_nibbles.get_nibble(0) as usize] = PartialTrie::Leaf {
+            nibbles: sender_nibbles.truncate_n_nibbles_front(1),
+            value: rlp::encode(&sender_account_after).to_vec(),
+        }
+        .into();
+        children[to_nibbles.get_nibble(0) as usize] = PartialTrie::Leaf {
+            nibbles: to_nibbles.truncate_n_nibbles_front(1),
+            value: rlp::encode(&to_account_after).to_vec(),
+        }
+        .into();
+        // TODO: Beneficiary should receive gas...
+        PartialTrie::Branch {
+            children,
+            value: vec![],
+        }
+    };
+
+    let receipt = ReceiptRlp {
+        txn_type: 0,
+        receipt: LegacyReceiptRlp {
+            status: false,
+            cum_gas_used: 22_000.into(),
+            bloom: Bloom::zero(),
+            logs: vec![],
+        },
+    };
+    let expected_receipts_trie_after = PartialTrie::Leaf {
+        nibbles: Nibbles::from_bytes_be(&rlp::encode(&0u64)).unwrap(),
+        value: receipt.encode(),
+    };
+
+    let inputs = GenerationInputs {
+        signed_txns: vec![txn.to_vec()],
+        txn_number_before: 0.into(),
+        gas_used_before: 0.into(),
+        gas_used_after: 22_000.into(),
+        tries: tries_before,
+        contract_code,
+        block_metadata,
+        trie_roots_after: TrieRoots {
+            state_root: expected_state_trie_after.calc_hash(),
+            transactions_root: PartialTrie::Empty.calc_hash(),
+            receipts_root: expected_receipts_trie_after.calc_hash(),
+        },
+        block_hashes: vec![],
+    };
+
+    let mut timing = TimingTree::new("prove", log::Level::Debug);
+    let proof = prove::<F, C, D>(&all_stark, &config, inputs, &mut timing)?;
+    timing.filter(Duration::from_millis(100)).print();
+
+    verify_proof(&all_stark, proof, &config)
+}
+
+fn eth_to_wei(eth: U256) -> U256 {
+    // 1 ether = 10^18 wei.
+    eth * U256::from(10).pow(18.into())
+}
+
+fn init_logger() {
+    let _ = try_init_from_env(Env::default().filter_or(DEFAULT_FILTER_ENV, "info"));
+}
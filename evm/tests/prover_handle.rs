@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use eth_trie_utils::partial_trie::PartialTrie;
+use itertools::Itertools;
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::plonk::config::PoseidonGoldilocksConfig;
+use plonky2::plonk::prover_handle::{ProverHandle, ProvingCancelled, ProvingPhase};
+use plonky2::util::timing::TimingTree;
+use plonky2_evm::all_stark::{AllStark, Table};
+use plonky2_evm::config::StarkConfig;
+use plonky2_evm::generation::{GenerationInputs, TrieInputs};
+use plonky2_evm::proof::{BlockMetadata, TrieRoots};
+use plonky2_evm::prover::prove_with_handle;
+use plonky2_evm::verifier::verify_proof;
+
+type F = GoldilocksField;
+const D: usize = 2;
+type C = PoseidonGoldilocksConfig;
+
+/// The inputs of an empty list of transactions, the cheapest block segment to prove.
+fn empty_txn_list_inputs() -> GenerationInputs {
+    let state_trie = PartialTrie::Empty;
+    let transactions_trie = PartialTrie::Empty;
+    let receipts_trie = PartialTrie::Empty;
+    let trie_roots_after = TrieRoots {
+        state_root: state_trie.calc_hash(),
+        transactions_root: transactions_trie.calc_hash(),
+        receipts_root: receipts_trie.calc_hash(),
+    };
+
+    GenerationInputs {
+        signed_txns: vec![],
+        txn_number_before: 0.into(),
+        gas_used_before: 0.into(),
+        gas_used_after: 0.into(),
+        tries: TrieInputs {
+            state_trie,
+            transactions_trie,
+            receipts_trie,
+            storage_tries: vec![],
+        },
+        contract_code: HashMap::new(),
+        block_metadata: BlockMetadata::default(),
+        trie_roots_after,
+        block_hashes: vec![],
+    }
+}
+
+#[test]
+fn test_cancel() {
+    let all_stark = AllStark::<F, D>::default();
+    let config = StarkConfig::standard_fast_config();
+
+    let phases = Arc::new(Mutex::new(Vec::new()));
+    let handle = ProverHandle::new();
+    let handle_clone = handle.clone();
+    let handle = handle.with_progress({
+        let phases = phases.clone();
+        move |phase| {
+            phases.lock().unwrap().push(phase);
+            if phase == ProvingPhase::Quotient {
+                handle_clone.cancel();
+            }
+        }
+    });
+
+    let err = prove_with_handle::<F, C, D>(
+        &all_stark,
+        &config,
+        empty_txn_list_inputs(),
+        &handle,
+        &mut TimingTree::default(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ProvingCancelled>(),
+        Some(&ProvingCancelled)
+    );
+    // The trace commitments and the first table's Zs commitment, then its quotient.
+    assert_eq!(
+        *phases.lock().unwrap(),
+        [
+            ProvingPhase::Witness,
+            ProvingPhase::Commitment,
+            ProvingPhase::Commitment,
+            ProvingPhase::Quotient
+        ]
+    );
+}
+
+#[test]
+fn test_resume_from_checkpoint() -> anyhow::Result<()> {
+    let all_stark = AllStark::<F, D>::default();
+    let config = StarkConfig::standard_fast_config();
+    let dir = std::env::temp_dir().join(format!("evm_checkpoints_{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+
+    // Interrupt the proof once all commitments are checkpointed, i.e. at the FRI proof of the
+    // last table.
+    let num_tables = Table::Memory as usize + 1;
+    let handle = ProverHandle::new().with_checkpoint_dir(&dir);
+    let handle_clone = handle.clone();
+    let fri_proofs_started = AtomicUsize::new(0);
+    let handle = handle.with_progress(move |phase| {
+        if phase == ProvingPhase::Fri
+            && fri_proofs_started.fetch_add(1, Ordering::Relaxed) + 1 == num_tables
+        {
+            handle_clone.cancel();
+        }
+    });
+    assert!(prove_with_handle::<F, C, D>(
+        &all_stark,
+        &config,
+        empty_txn_list_inputs(),
+        &handle,
+        &mut TimingTree::default(),
+    )
+    .is_err());
+    let checkpoint_path = std::fs::read_dir(&dir)?.exactly_one().unwrap()?.path();
+    let id = hex::decode(checkpoint_path.file_stem().unwrap().to_str().unwrap())?;
+    let checkpoint = handle.load_checkpoint::<F, C, D>(&id).unwrap();
+
+    // Resuming reuses the checkpointed commitments, and removes the checkpoint when done.
+    let handle = ProverHandle::new().with_checkpoint_dir(&dir);
+    let proof = prove_with_handle::<F, C, D>(
+        &all_stark,
+        &config,
+        empty_txn_list_inputs(),
+        &handle,
+        &mut TimingTree::default(),
+    )?;
+    assert_eq!(std::fs::read_dir(&dir)?.count(), 0);
+    std::fs::remove_dir(&dir)?;
+
+    // The trace commitments of all tables, then the Zs and quotient commitments of each table.
+    assert_eq!(checkpoint.commitments.len(), 3 * num_tables);
+    let (trace_commitments, table_commitments) = checkpoint.commitments.split_at(num_tables);
+    for (i, stark_proof) in proof.stark_proofs.iter().enumerate() {
+        let table_proof = &stark_proof.proof;
+        assert_eq!(trace_commitments[i].merkle_tree.cap, table_proof.trace_cap);
+        assert_eq!(
+            table_commitments[2 * i].merkle_tree.cap,
+            table_proof.permutation_ctl_zs_cap
+        );
+        assert_eq!(
+            table_commitments[2 * i + 1].merkle_tree.cap,
+            table_proof.quotient_polys_cap
+        );
+    }
+    verify_proof(&all_stark, proof, &config)
+}
+
+#[test]
+fn test_checkpoint_refused_with_zero_knowledge() {
+    let all_stark = AllStark::<F, D>::default();
+    let config = StarkConfig::standard_fast_zk_config();
+    let dir = std::env::temp_dir().join(format!("evm_zk_checkpoints_{}", std::process::id()));
+    let handle = ProverHandle::new().with_checkpoint_dir(&dir);
+
+    assert!(prove_with_handle::<F, C, D>(
+        &all_stark,
+        &config,
+        empty_txn_list_inputs(),
+        &handle,
+        &mut TimingTree::default(),
+    )
+    .is_err());
+    assert!(!dir.exists());
+}
@@ -0,0 +1,261 @@
+use num::bigint::BigUint;
+
+pub(crate) fn biguint_from_array(arr: [u64; 4]) -> BigUint {
+    BigUint::from_slice(&[
+        arr[0] as u32,
+        (arr[0] >> 32) as u32,
+        arr[1] as u32,
+        (arr[1] >> 32) as u32,
+        arr[2] as u32,
+        (arr[2] >> 32) as u32,
+        arr[3] as u32,
+        (arr[3] >> 32) as u32,
+    ])
+}
+
+/// Defines a prime field of order below 2^256, represented by four `u64` limbs and implemented with
+/// `BigUint` arithmetic, like `Secp256K1Base`. Unlike there, elements are always reduced, so that
+/// the limbs stay canonical and inputs of 2^256 or more can be converted.
+///
+/// The order is given as little-endian `u32` limbs, the other constants as little-endian `u64`
+/// limbs.
+macro_rules! big_prime_field {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident;
+        order: [$($order:expr),* $(,)?],
+        neg_one: [$($neg_one:expr),* $(,)?],
+        two_adicity: $two_adicity:expr,
+        multiplicative_group_generator: $generator:expr,
+        power_of_two_generator: [$($power_of_two_generator:expr),* $(,)?],
+        bits: $bits:expr $(,)?
+    ) => {
+        use alloc::vec::Vec;
+        use core::fmt::{self, Debug, Display, Formatter};
+        use core::hash::{Hash, Hasher};
+        use core::iter::{Product, Sum};
+        use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+        use itertools::Itertools;
+        use num::bigint::BigUint;
+        use num::{Integer, One};
+        use serde::{Deserialize, Serialize};
+
+        use $crate::big_prime_field::biguint_from_array;
+        use $crate::types::{Field, PrimeField, Sample};
+
+        $(#[$attr])*
+        #[derive(Copy, Clone, Serialize, Deserialize)]
+        pub struct $name(pub [u64; 4]);
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::ZERO
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                self.to_canonical_biguint() == other.to_canonical_biguint()
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl Hash for $name {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.to_canonical_biguint().hash(state)
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                Display::fmt(&self.to_canonical_biguint(), f)
+            }
+        }
+
+        impl Debug for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                Debug::fmt(&self.to_canonical_biguint(), f)
+            }
+        }
+
+        impl Sample for $name {
+            #[inline]
+            fn sample<R>(rng: &mut R) -> Self
+            where
+                R: rand::RngCore + ?Sized,
+            {
+                use num::bigint::RandBigInt;
+                Self::from_noncanonical_biguint(rng.gen_biguint_below(&Self::order()))
+            }
+        }
+
+        impl Field for $name {
+            const ZERO: Self = Self([0; 4]);
+            const ONE: Self = Self([1, 0, 0, 0]);
+            const TWO: Self = Self([2, 0, 0, 0]);
+            const NEG_ONE: Self = Self([$($neg_one),*]);
+
+            const TWO_ADICITY: usize = $two_adicity;
+            const CHARACTERISTIC_TWO_ADICITY: usize = Self::TWO_ADICITY;
+
+            const MULTIPLICATIVE_GROUP_GENERATOR: Self = Self([$generator, 0, 0, 0]);
+            const POWER_OF_TWO_GENERATOR: Self = Self([$($power_of_two_generator),*]);
+
+            const BITS: usize = $bits;
+
+            fn order() -> BigUint {
+                BigUint::from_slice(&[$($order),*])
+            }
+            fn characteristic() -> BigUint {
+                Self::order()
+            }
+
+            fn try_inverse(&self) -> Option<Self> {
+                if self.is_zero() {
+                    return None;
+                }
+
+                // Fermat's Little Theorem
+                Some(self.exp_biguint(&(Self::order() - BigUint::one() - BigUint::one())))
+            }
+
+            fn from_noncanonical_biguint(val: BigUint) -> Self {
+                Self(
+                    val.mod_floor(&Self::order())
+                        .to_u64_digits()
+                        .into_iter()
+                        .pad_using(4, |_| 0)
+                        .collect::<Vec<_>>()[..]
+                        .try_into()
+                        .expect("error converting to u64 array"),
+                )
+            }
+
+            #[inline]
+            fn from_canonical_u64(n: u64) -> Self {
+                Self([n, 0, 0, 0])
+            }
+
+            #[inline]
+            fn from_noncanonical_u128(n: u128) -> Self {
+                Self([n as u64, (n >> 64) as u64, 0, 0])
+            }
+
+            #[inline]
+            fn from_noncanonical_u96(n: (u64, u32)) -> Self {
+                Self([n.0, n.1 as u64, 0, 0])
+            }
+        }
+
+        impl PrimeField for $name {
+            fn to_canonical_biguint(&self) -> BigUint {
+                let mut result = biguint_from_array(self.0);
+                if result >= Self::order() {
+                    result -= Self::order();
+                }
+                result
+            }
+        }
+
+        impl Neg for $name {
+            type Output = Self;
+
+            #[inline]
+            fn neg(self) -> Self {
+                if self.is_zero() {
+                    Self::ZERO
+                } else {
+                    Self::from_noncanonical_biguint(Self::order() - self.to_canonical_biguint())
+                }
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+
+            #[inline]
+            fn add(self, rhs: Self) -> Self {
+                let mut result = self.to_canonical_biguint() + rhs.to_canonical_biguint();
+                if result >= Self::order() {
+                    result -= Self::order();
+                }
+                Self::from_noncanonical_biguint(result)
+            }
+        }
+
+        impl AddAssign for $name {
+            #[inline]
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl Sum for $name {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(Self::ZERO, |acc, x| acc + x)
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+
+            #[inline]
+            #[allow(clippy::suspicious_arithmetic_impl)]
+            fn sub(self, rhs: Self) -> Self {
+                self + -rhs
+            }
+        }
+
+        impl SubAssign for $name {
+            #[inline]
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl Mul for $name {
+            type Output = Self;
+
+            #[inline]
+            fn mul(self, rhs: Self) -> Self {
+                Self::from_noncanonical_biguint(
+                    (self.to_canonical_biguint() * rhs.to_canonical_biguint())
+                        .mod_floor(&Self::order()),
+                )
+            }
+        }
+
+        impl MulAssign for $name {
+            #[inline]
+            fn mul_assign(&mut self, rhs: Self) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl Product for $name {
+            #[inline]
+            fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.reduce(|acc, x| acc * x).unwrap_or(Self::ONE)
+            }
+        }
+
+        impl Div for $name {
+            type Output = Self;
+
+            #[allow(clippy::suspicious_arithmetic_impl)]
+            fn div(self, rhs: Self) -> Self::Output {
+                self * rhs.inverse()
+            }
+        }
+
+        impl DivAssign for $name {
+            fn div_assign(&mut self, rhs: Self) {
+                *self = *self / rhs;
+            }
+        }
+    };
+}
+
+pub(crate) use big_prime_field;
@@ -0,0 +1,46 @@
+use crate::big_prime_field::big_prime_field;
+
+big_prime_field! {
+    /// The base field of the BN254 elliptic curve, over which its G1 group is defined.
+    ///
+    /// Its order is
+    /// ```ignore
+    /// P = 0x30644E72 E131A029 B85045B6 8181585D 97816A91 6871CA8D 3C208C16 D87CFD47
+    ///   = 21888242871839275222246405745257275088696311157297823662689037894645226208583
+    /// ```
+    pub struct Bn254Base;
+
+    order: [
+        0xD87CFD47, 0x3C208C16, 0x6871CA8D, 0x97816A91, 0x8181585D, 0xB85045B6, 0xE131A029,
+        0x30644E72,
+    ],
+    neg_one: [
+        0x3C208C16D87CFD46,
+        0x97816A916871CA8D,
+        0xB85045B68181585D,
+        0x30644E72E131A029,
+    ],
+    two_adicity: 1,
+
+    // Sage: `g = GF(p).multiplicative_generator()`
+    multiplicative_group_generator: 3,
+
+    // P is 3 mod 4, so the only nontrivial 2^k-th root of unity is -1.
+    // Sage: `g_2 = power_mod(g, (p - 1) // 2^1, p)`
+    // 21888242871839275222246405745257275088696311157297823662689037894645226208582
+    power_of_two_generator: [
+        0x3C208C16D87CFD46,
+        0x97816A916871CA8D,
+        0xB85045B68181585D,
+        0x30644E72E131A029,
+    ],
+
+    bits: 254,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_field_arithmetic;
+
+    test_field_arithmetic!(crate::bn254_base::Bn254Base);
+}
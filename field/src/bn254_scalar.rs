@@ -0,0 +1,45 @@
+use crate::big_prime_field::big_prime_field;
+
+big_prime_field! {
+    /// The scalar field of the BN254 elliptic curve, i.e. the native field of SNARKs over BN254.
+    ///
+    /// Its order is
+    /// ```ignore
+    /// P = 0x30644E72 E131A029 B85045B6 8181585D 2833E848 79B97091 43E1F593 F0000001
+    ///   = 21888242871839275222246405745257275088548364400416034343698204186575808495617
+    /// ```
+    pub struct Bn254Scalar;
+
+    order: [
+        0xF0000001, 0x43E1F593, 0x79B97091, 0x2833E848, 0x8181585D, 0xB85045B6, 0xE131A029,
+        0x30644E72,
+    ],
+    neg_one: [
+        0x43E1F593F0000000,
+        0x2833E84879B97091,
+        0xB85045B68181585D,
+        0x30644E72E131A029,
+    ],
+    two_adicity: 28,
+
+    // Sage: `g = GF(p).multiplicative_generator()`
+    multiplicative_group_generator: 5,
+
+    // Sage: `g_2 = power_mod(g, (p - 1) // 2^28, p)`
+    // 19103219067921713944291392827692070036145651957329286315305642004821462161904
+    power_of_two_generator: [
+        0x9BD61B6E725B19F0,
+        0x402D111E41112ED4,
+        0x00E0A7EB8EF62ABC,
+        0x2A3C09F0A58A7E85,
+    ],
+
+    bits: 254,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_field_arithmetic;
+
+    test_field_arithmetic!(crate::bn254_scalar::Bn254Scalar);
+}
@@ -0,0 +1,45 @@
+use crate::big_prime_field::big_prime_field;
+
+big_prime_field! {
+    /// The base field of the Ed25519 twisted Edwards curve.
+    ///
+    /// Its order is
+    /// ```ignore
+    /// P = 2**255 - 19
+    /// ```
+    pub struct Ed25519Base;
+
+    order: [
+        0xFFFFFFED, 0xFFFFFFFF, 0xFFFFFFFF, 0xFFFFFFFF, 0xFFFFFFFF, 0xFFFFFFFF, 0xFFFFFFFF,
+        0x7FFFFFFF,
+    ],
+    neg_one: [
+        0xFFFFFFFFFFFFFFEC,
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+        0x7FFFFFFFFFFFFFFF,
+    ],
+    two_adicity: 2,
+
+    // Sage: `g = GF(p).multiplicative_generator()`
+    multiplicative_group_generator: 2,
+
+    // This is the square root of -1 used in Ed25519 point decompression.
+    // Sage: `g_2 = power_mod(g, (p - 1) // 2^2, p)`
+    // 19681161376707505956807079304988542015446066515923890162744021073123829784752
+    power_of_two_generator: [
+        0xC4EE1B274A0EA0B0,
+        0x2F431806AD2FE478,
+        0x2B4D00993DFBD7A7,
+        0x2B8324804FC1DF0B,
+    ],
+
+    bits: 255,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_field_arithmetic;
+
+    test_field_arithmetic!(crate::ed25519_base::Ed25519Base);
+}
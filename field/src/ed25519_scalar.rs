@@ -0,0 +1,47 @@
+use crate::big_prime_field::big_prime_field;
+
+big_prime_field! {
+    /// The scalar field of the Ed25519 twisted Edwards curve, i.e. the order of its prime-order
+    /// subgroup.
+    ///
+    /// Its order is
+    /// ```ignore
+    /// P = 2**252 + 27742317777372353535851937790883648493
+    /// ```
+    pub struct Ed25519Scalar;
+
+    order: [
+        0x5CF5D3ED, 0x5812631A, 0xA2F79CD6, 0x14DEF9DE, 0x00000000, 0x00000000, 0x00000000,
+        0x10000000,
+    ],
+    neg_one: [
+        0x5812631A5CF5D3EC,
+        0x14DEF9DEA2F79CD6,
+        0x0000000000000000,
+        0x1000000000000000,
+    ],
+    two_adicity: 2,
+
+    // p - 1 = 2^2 * 3 * 11 * 198211423230930754013084525763697
+    //           * 276602624281642239937218680557139826668747
+    // Sage: `g = GF(p).multiplicative_generator()`
+    multiplicative_group_generator: 2,
+
+    // Sage: `g_2 = power_mod(g, (p - 1) // 2^2, p)`
+    // 4202356475871964119699734399548423449193549369991576068503119564443318355924
+    power_of_two_generator: [
+        0xBE8775DFEBBE07D4,
+        0x0EF0565342CE83FE,
+        0x7D3D6D60ABC1C27A,
+        0x094A7310E07981E7,
+    ],
+
+    bits: 253,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_field_arithmetic;
+
+    test_field_arithmetic!(crate::ed25519_scalar::Ed25519Scalar);
+}
@@ -1,7 +1,8 @@
 use alloc::vec::Vec;
 use core::cmp::{max, min};
 
-use plonky2_util::{log2_strict, reverse_index_bits_in_place};
+use plonky2_maybe_rayon::*;
+use plonky2_util::{log2_strict, reverse_index_bits_in_place, transpose_square_blocks_in_place};
 use unroll::unroll_for_loops;
 
 use crate::packable::Packable;
@@ -11,6 +12,30 @@ use crate::types::Field;
 
 pub type FftRootTable<F> = Vec<Vec<F>>;
 
+/// With the `four_step_fft` feature, FFTs of at least this (log) size use `fft_four_step`, whose
+/// small FFTs run in parallel, instead of `fft_classic`. Whether and from which size it is faster
+/// depends on the number of threads and the caches of the machine, so the feature is off by
+/// default; the `fft_algorithms` benchmark of `plonky2/benches/ffts.rs` compares both.
+#[cfg(feature = "four_step_fft")]
+const FOUR_STEP_MIN_LOG_SIZE: usize = 20;
+
+/// Whether an FFT of the given (log) size should use `fft_four_step`, i.e. whether it is enabled,
+/// large enough, and other threads are idle. Inside a rayon task, like the FFTs of a batch of
+/// polynomials, the other threads are busy with their own tasks.
+fn use_four_step(lg_n: usize) -> bool {
+    #[cfg(feature = "four_step_fft")]
+    {
+        lg_n >= FOUR_STEP_MIN_LOG_SIZE
+            && rayon::current_num_threads() > 1
+            && rayon::current_thread_index().is_none()
+    }
+    #[cfg(not(feature = "four_step_fft"))]
+    {
+        let _ = lg_n;
+        false
+    }
+}
+
 pub fn fft_root_table<F: Field>(n: usize) -> FftRootTable<F> {
     let lg_n = log2_strict(n);
     // bases[i] = g^2^i, for i = 0, ..., lg_n - 1
@@ -45,7 +70,11 @@ fn fft_dispatch<F: Field>(
     };
     let used_root_table = root_table.or(computed_root_table.as_ref()).unwrap();
 
-    fft_classic(input, zero_factor.unwrap_or(0), used_root_table);
+    if use_four_step(log2_strict(input.len())) {
+        fft_four_step(input, zero_factor.unwrap_or(0), used_root_table);
+    } else {
+        fft_classic(input, zero_factor.unwrap_or(0), used_root_table);
+    }
 }
 
 #[inline]
@@ -100,7 +129,7 @@ fn fft_classic_simd<P: PackedField>(
     values: &mut [P::Scalar],
     r: usize,
     lg_n: usize,
-    root_table: &FftRootTable<P::Scalar>,
+    root_table: &[Vec<P::Scalar>],
 ) {
     let lg_packed_width = log2_strict(P::WIDTH); // 0 when P is a scalar.
     let packed_values = P::pack_slice_mut(values);
@@ -166,7 +195,7 @@ fn fft_classic_simd<P: PackedField>(
 /// The parameter r signifies that the first 1/2^r of the entries of
 /// input may be non-zero, but the last 1 - 1/2^r entries are
 /// definitely zero.
-pub(crate) fn fft_classic<F: Field>(values: &mut [F], r: usize, root_table: &FftRootTable<F>) {
+pub(crate) fn fft_classic<F: Field>(values: &mut [F], r: usize, root_table: &[Vec<F>]) {
     reverse_index_bits_in_place(values);
 
     let n = values.len();
@@ -205,16 +234,86 @@ pub(crate) fn fft_classic<F: Field>(values: &mut [F], r: usize, root_table: &Fft
     }
 }
 
+/// Four-step FFT, as described in "FFTs in external or hierarchical memory" by Bailey.
+///
+/// An input of size `n = m * c * m`, with `c` 1 or 2, is viewed as an `m` by `c * m` matrix, so the
+/// FFT splits into `c * m` FFTs of size `m` on the columns, a multiplication by twiddle factors, and
+/// `m` FFTs of size `c * m` on the rows. Transposing the square blocks of the matrix in between lets
+/// every small FFT run on contiguous memory, and the small FFTs run in parallel.
+///
+/// As in `fft_classic`, the parameter `r` signifies that the last `1 - 1/2^r` of the entries of the
+/// input are zero.
+pub fn fft_four_step<F: Field>(values: &mut [F], r: usize, root_table: &[Vec<F>]) {
+    let n = values.len();
+    let lg_n = log2_strict(n);
+    assert_eq!(
+        root_table.len(),
+        lg_n,
+        "Expected root table of length {}, but it was {}.",
+        lg_n,
+        root_table.len()
+    );
+
+    let lg_m = lg_n / 2;
+    let m = 1 << lg_m;
+    let row_len = n >> lg_m;
+    // The root table of size `m` is a prefix of the one of size `n`.
+    let small_root_table = &root_table[..lg_m];
+
+    // With `j = j1 + row_len * j2`, the input is zero for `j2 >= m / 2^r`. After the transposition,
+    // the `m` entries at `row_len * j1' + m * b` hold `a[j1 + row_len * j2]` for all `j2`, where
+    // `j1 = j1' + m * b`.
+    transpose_square_blocks_in_place(values, lg_m);
+    values
+        .par_chunks_mut(row_len)
+        .enumerate()
+        .for_each(|(j1_lo, row)| {
+            for (column, j1) in row.chunks_mut(m).zip((j1_lo..).step_by(m)) {
+                fft_classic(column, r.min(lg_m), small_root_table);
+                // Multiply entry `k2` by the twiddle factor `omega^(j1 * k2)`. Since
+                // `j1 < n / 2`, `omega^j1` is in the last row of the root table.
+                let twiddle = root_table[lg_n - 1][j1];
+                for (x, t) in column.iter_mut().zip(twiddle.powers()) {
+                    *x *= t;
+                }
+            }
+        });
+
+    // Row `k2` now holds the twiddled results for all `j1`. Their FFTs of size `row_len` give
+    // `A[k2 + m * k1]`.
+    transpose_square_blocks_in_place(values, lg_m);
+    values.par_chunks_mut(row_len).for_each(|row| {
+        if row_len > m {
+            // Decimation in frequency: with `x' = x[..m]` and `x'' = x[m..]`, the even outputs are
+            // the FFT of `x' + x''` and the odd outputs are the FFT of `(x' - x'') * omega'^i`,
+            // where `omega'` has order `2 * m`.
+            let (lo, hi) = row.split_at_mut(m);
+            for ((u, v), &omega) in lo.iter_mut().zip(hi.iter_mut()).zip(&root_table[lg_m]) {
+                let (x, y) = (*u, *v);
+                *u = x + y;
+                *v = (x - y) * omega;
+            }
+        }
+        for half in row.chunks_mut(m) {
+            fft_classic(half, 0, small_root_table);
+        }
+    });
+
+    // The entry at `row_len * k2 + m * b + k1'` holds `A[k2 + m * (c * k1' + b)]`. Transposing puts
+    // it at index `row_len * k1' + m * b + k2`, which is the same.
+    transpose_square_blocks_in_place(values, lg_m);
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::vec::Vec;
 
     use plonky2_util::{log2_ceil, log2_strict};
 
-    use crate::fft::{fft, fft_with_options, ifft};
+    use crate::fft::{fft, fft_classic, fft_four_step, fft_root_table, fft_with_options, ifft};
     use crate::goldilocks_field::GoldilocksField;
     use crate::polynomial::{PolynomialCoeffs, PolynomialValues};
-    use crate::types::Field;
+    use crate::types::{Field, Sample};
 
     #[test]
     fn fft_and_ifft() {
@@ -252,6 +351,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn fft_four_step_matches_classic() {
+        type F = GoldilocksField;
+        for lg_n in (1..=9).chain([14, 15]) {
+            let root_table = fft_root_table(1 << lg_n);
+            let coeffs = F::rand_vec(1 << lg_n);
+
+            let mut classic = coeffs.clone();
+            fft_classic(&mut classic, 0, &root_table);
+            let mut four_step = coeffs.clone();
+            fft_four_step(&mut four_step, 0, &root_table);
+            assert_eq!(classic, four_step, "mismatch at size 2^{lg_n}");
+
+            for r in 1..=lg_n {
+                let mut zero_tail = coeffs.clone();
+                zero_tail[1 << (lg_n - r)..].fill(F::ZERO);
+                let mut classic = zero_tail.clone();
+                fft_classic(&mut classic, 0, &root_table);
+                let mut four_step = zero_tail;
+                fft_four_step(&mut four_step, r, &root_table);
+                assert_eq!(
+                    classic, four_step,
+                    "mismatch at size 2^{lg_n}, zero factor {r}"
+                );
+            }
+
+            let values = fft(PolynomialCoeffs::new(coeffs.clone()));
+            assert_eq!(ifft(values).coeffs, coeffs);
+        }
+    }
+
     fn evaluate_naive<F: Field>(coefficients: &PolynomialCoeffs<F>) -> PolynomialValues<F> {
         let degree = coefficients.len();
         let degree_padded = 1 << log2_ceil(degree);
@@ -12,12 +12,17 @@
 
 extern crate alloc;
 
+mod big_prime_field;
 mod inversion;
 
 pub(crate) mod arch;
 
 pub mod batch_util;
+pub mod bn254_base;
+pub mod bn254_scalar;
 pub mod cosets;
+pub mod ed25519_base;
+pub mod ed25519_scalar;
 pub mod extension;
 pub mod fft;
 pub mod goldilocks_extensions;
@@ -29,6 +34,8 @@ pub mod packed;
 pub mod polynomial;
 pub mod secp256k1_base;
 pub mod secp256k1_scalar;
+pub mod secp256r1_base;
+pub mod secp256r1_scalar;
 pub mod types;
 pub mod zero_poly_coset;
 
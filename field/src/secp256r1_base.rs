@@ -0,0 +1,45 @@
+use crate::big_prime_field::big_prime_field;
+
+big_prime_field! {
+    /// The base field of the secp256r1 (NIST P-256) elliptic curve.
+    ///
+    /// Its order is
+    /// ```ignore
+    /// P = 2**256 - 2**224 + 2**192 + 2**96 - 1
+    /// ```
+    pub struct Secp256R1Base;
+
+    order: [
+        0xFFFFFFFF, 0xFFFFFFFF, 0xFFFFFFFF, 0x00000000, 0x00000000, 0x00000000, 0x00000001,
+        0xFFFFFFFF,
+    ],
+    neg_one: [
+        0xFFFFFFFFFFFFFFFE,
+        0x00000000FFFFFFFF,
+        0x0000000000000000,
+        0xFFFFFFFF00000001,
+    ],
+    two_adicity: 1,
+
+    // Sage: `g = GF(p).multiplicative_generator()`
+    multiplicative_group_generator: 6,
+
+    // P is 3 mod 4, so the only nontrivial 2^k-th root of unity is -1.
+    // Sage: `g_2 = power_mod(g, (p - 1) // 2^1, p)`
+    // 115792089210356248762697446949407573530086143415290314195533631308867097853950
+    power_of_two_generator: [
+        0xFFFFFFFFFFFFFFFE,
+        0x00000000FFFFFFFF,
+        0x0000000000000000,
+        0xFFFFFFFF00000001,
+    ],
+
+    bits: 256,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_field_arithmetic;
+
+    test_field_arithmetic!(crate::secp256r1_base::Secp256R1Base);
+}
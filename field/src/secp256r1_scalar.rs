@@ -0,0 +1,45 @@
+use crate::big_prime_field::big_prime_field;
+
+big_prime_field! {
+    /// The scalar field of the secp256r1 (NIST P-256) elliptic curve.
+    ///
+    /// Its order is
+    /// ```ignore
+    /// P = 0xFFFFFFFF 00000000 FFFFFFFF FFFFFFFF BCE6FAAD A7179E84 F3B9CAC2 FC632551
+    ///   = 115792089210356248762697446949407573529996955224135760342422259061068512044369
+    /// ```
+    pub struct Secp256R1Scalar;
+
+    order: [
+        0xFC632551, 0xF3B9CAC2, 0xA7179E84, 0xBCE6FAAD, 0xFFFFFFFF, 0xFFFFFFFF, 0x00000000,
+        0xFFFFFFFF,
+    ],
+    neg_one: [
+        0xF3B9CAC2FC632550,
+        0xBCE6FAADA7179E84,
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFF00000000,
+    ],
+    two_adicity: 4,
+
+    // Sage: `g = GF(p).multiplicative_generator()`
+    multiplicative_group_generator: 7,
+
+    // Sage: `g_2 = power_mod(g, (p - 1) // 2^4, p)`
+    // 115695789336771192084080718687965001507772259361175921799893286721837170845186
+    power_of_two_generator: [
+        0x0592D7FBB41E6602,
+        0x1546CAD004378DAF,
+        0xBA807ACE842A3DFC,
+        0xFFC97F062A770992,
+    ],
+
+    bits: 256,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_field_arithmetic;
+
+    test_field_arithmetic!(crate::secp256r1_scalar::Secp256R1Scalar);
+}
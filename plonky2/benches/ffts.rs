@@ -1,6 +1,7 @@
 mod allocator;
 
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use plonky2::field::fft::{fft_four_step, fft_root_table};
 use plonky2::field::goldilocks_field::GoldilocksField;
 use plonky2::field::polynomial::PolynomialCoeffs;
 use plonky2::field::types::Field;
@@ -9,7 +10,7 @@ use tynm::type_name;
 pub(crate) fn bench_ffts<F: Field>(c: &mut Criterion) {
     let mut group = c.benchmark_group(&format!("fft<{}>", type_name::<F>()));
 
-    for size_log in [13, 14, 15, 16] {
+    for size_log in [13, 14, 15, 16, 18, 20, 22] {
         let size = 1 << size_log;
         group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
             let coeffs = PolynomialCoeffs::new(F::rand_vec(size));
@@ -18,12 +19,31 @@ pub(crate) fn bench_ffts<F: Field>(c: &mut Criterion) {
     }
 }
 
+/// Compares `fft` with `fft_four_step`, which `fft` only uses with the `four_step_fft` feature of
+/// `plonky2_field`. Without that feature, `fft` is the classic radix-2 FFT.
+pub(crate) fn bench_fft_algorithms<F: Field>(c: &mut Criterion) {
+    let mut group = c.benchmark_group(&format!("fft_algorithms<{}>", type_name::<F>()));
+
+    for size_log in [12, 14, 15, 16, 17, 18, 20, 22] {
+        let size = 1 << size_log;
+        let root_table = fft_root_table::<F>(size);
+        let values = F::rand_vec(size);
+        group.bench_with_input(BenchmarkId::new("default", size), &size, |b, _| {
+            let coeffs = PolynomialCoeffs::new(values.clone());
+            b.iter(|| coeffs.clone().fft_with_options(None, Some(&root_table)));
+        });
+        group.bench_with_input(BenchmarkId::new("four_step", size), &size, |b, _| {
+            b.iter(|| fft_four_step(&mut values.clone(), 0, &root_table));
+        });
+    }
+}
+
 pub(crate) fn bench_ldes<F: Field>(c: &mut Criterion) {
     const RATE_BITS: usize = 3;
 
     let mut group = c.benchmark_group(&format!("lde<{}>", type_name::<F>()));
 
-    for size_log in [13, 14, 15, 16] {
+    for size_log in [13, 14, 15, 16, 18, 20, 22] {
         let orig_size = 1 << (size_log - RATE_BITS);
         let lde_size = 1 << size_log;
 
@@ -39,6 +59,7 @@ pub(crate) fn bench_ldes<F: Field>(c: &mut Criterion) {
 
 fn criterion_benchmark(c: &mut Criterion) {
     bench_ffts::<GoldilocksField>(c);
+    bench_fft_algorithms::<GoldilocksField>(c);
     bench_ldes::<GoldilocksField>(c);
 }
 
@@ -2,13 +2,37 @@ use core::marker::PhantomData;
 
 use anyhow::Result;
 use plonky2::field::types::{PrimeField, Sample};
+use plonky2::gadgets::arithmetic::EqualityGenerator;
+use plonky2::gadgets::arithmetic_extension::QuotientGeneratorExtension;
+use plonky2::gadgets::range_check::LowHighGenerator;
+use plonky2::gadgets::split_base::BaseSumGenerator;
+use plonky2::gadgets::split_join::{SplitGenerator, WireSplitGenerator};
+use plonky2::gates::arithmetic_base::ArithmeticBaseGenerator;
+use plonky2::gates::arithmetic_extension::ArithmeticExtensionGenerator;
+use plonky2::gates::base_sum::BaseSplitGenerator;
+use plonky2::gates::coset_interpolation::InterpolationGenerator;
+use plonky2::gates::exponentiation::ExponentiationGenerator;
+use plonky2::gates::multiplication_extension::MulExtensionGenerator;
+use plonky2::gates::poseidon::PoseidonGenerator;
+use plonky2::gates::poseidon_mds::PoseidonMdsGenerator;
+use plonky2::gates::random_access::RandomAccessGenerator;
+use plonky2::gates::reducing::ReducingGenerator;
+use plonky2::gates::reducing_extension::ReducingGenerator as ReducingExtensionGenerator;
 use plonky2::hash::hash_types::RichField;
-use plonky2::iop::generator::{GeneratedValues, SimpleGenerator};
+use plonky2::impl_generator_serializer;
+use plonky2::iop::generator::{
+    ConstantGenerator, CopyGenerator, GeneratedValues, NonzeroTestGenerator, RandomValueGenerator,
+    SimpleGenerator,
+};
 use plonky2::iop::target::Target;
 use plonky2::iop::witness::{PartialWitness, PartitionWitness, Witness, WitnessWrite};
 use plonky2::plonk::circuit_builder::CircuitBuilder;
-use plonky2::plonk::circuit_data::CircuitConfig;
+use plonky2::plonk::circuit_data::{CircuitConfig, CircuitData};
 use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+use plonky2::recursion::dummy_circuit::DummyProofGenerator;
+use plonky2::util::serialization::{
+    Buffer, DefaultGateSerializer, IoResult, Read, WitnessGeneratorSerializer, Write,
+};
 use plonky2_field::extension::Extendable;
 
 /// A generator used by the prover to calculate the square root (`x`) of a given value
@@ -35,6 +59,57 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F>
 
         out_buffer.set_target(self.x, x);
     }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_target(self.x)?;
+        dst.write_target(self.x_squared)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let x = src.read_target()?;
+        let x_squared = src.read_target()?;
+        Ok(Self {
+            x,
+            x_squared,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+/// A generator serializer which supports the default generators, as well as
+/// `SquareRootGenerator`. The default generators are listed first, so that their tags
+/// match the ones used by `DefaultGeneratorSerializer`.
+#[derive(Default)]
+struct CustomGeneratorSerializer;
+
+impl<F: RichField + Extendable<D>, const D: usize> WitnessGeneratorSerializer<F, D>
+    for CustomGeneratorSerializer
+{
+    impl_generator_serializer! {
+        ArithmeticBaseGenerator<F, D>,
+        ArithmeticExtensionGenerator<F, D>,
+        BaseSplitGenerator<2>,
+        BaseSumGenerator<2>,
+        ConstantGenerator<F>,
+        CopyGenerator,
+        DummyProofGenerator<F>,
+        EqualityGenerator,
+        ExponentiationGenerator<F, D>,
+        InterpolationGenerator<F, D>,
+        LowHighGenerator,
+        MulExtensionGenerator<F, D>,
+        NonzeroTestGenerator,
+        PoseidonGenerator<F, D>,
+        PoseidonMdsGenerator<D>,
+        QuotientGeneratorExtension<D>,
+        RandomAccessGenerator<F, D>,
+        RandomValueGenerator,
+        ReducingGenerator<D>,
+        ReducingExtensionGenerator<D>,
+        SplitGenerator,
+        WireSplitGenerator,
+        SquareRootGenerator<F, D>,
+    }
 }
 
 /// An example of using Plonky2 to prove a statement of the form
@@ -72,6 +147,19 @@ fn main() -> Result<()> {
     pw.set_target(x_squared, x_squared_value);
 
     let data = builder.build::<C>();
+
+    // The circuit data can be stored and reloaded later, provided the serializers know about every
+    // gate and generator it uses.
+    let gate_serializer = DefaultGateSerializer;
+    let generator_serializer = CustomGeneratorSerializer;
+    let data_bytes = data
+        .to_bytes(&gate_serializer, &generator_serializer)
+        .map_err(anyhow::Error::msg)?;
+    println!("Circuit data length: {} bytes", data_bytes.len());
+    let data =
+        CircuitData::<F, C, D>::from_bytes(&data_bytes, &gate_serializer, &generator_serializer)
+            .map_err(anyhow::Error::msg)?;
+
     let proof = data.prove(pw.clone())?;
 
     let x_squared_actual = proof.public_inputs[0];
@@ -0,0 +1,196 @@
+//! Computes the proven and conjectured security of a FRI configuration, and optionally the
+//! cheapest configuration reaching a target security level.
+//!
+//! Every parameter has a default matching `CircuitConfig::standard_recursion_config` over the
+//! Goldilocks field, so e.g. `security_level --degree-bits 16 --target-bits 100` only overrides
+//! the circuit size and asks for a suggestion.
+
+use std::env;
+use std::process::exit;
+
+use anyhow::{anyhow, bail, Context, Result};
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::field::types::Field64;
+use plonky2::fri::reduction_strategies::FriReductionStrategy;
+use plonky2::fri::security::{
+    cheapest_config, security_report, SecurityBits, SoundnessParams, SuggestedConfig,
+};
+use plonky2::fri::FriConfig;
+
+const USAGE: &str = "\
+Usage: security_level [OPTIONS]
+
+FRI options:
+    --rate-bits <N>             log2 of the inverse rate [default: 3]
+    --num-query-rounds <N>      number of FRI queries [default: 28]
+    --proof-of-work-bits <N>    bits of proof of work [default: 16]
+    --cap-height <N>            height of Merkle caps [default: 4]
+    --arity-bits <N>            log2 of the arity of each FRI reduction [default: 4]
+    --final-poly-bits <N>       log2 of the maximum final polynomial length [default: 5]
+
+Protocol options:
+    --field-bits <N>            log2 of the base field order [default: 63, for Goldilocks]
+    --extension-degree <N>      degree of the extension field [default: 2]
+    --degree-bits <N>           log2 of the trace length [default: 12]
+    --num-polys <N>             number of polynomials opened with FRI [default: 300]
+    --constraint-degree <N>     maximum constraint degree [default: 8]
+    --num-constraints <N>       number of constraints [default: 123]
+    --num-challenges <N>        repetitions of base field challenges [default: 2]
+    --permutation-width <N>     columns in the permutation argument, 0 if none [default: 80]
+    --lookup-width <N>          lookups and table entries per row, 0 if none [default: 0]
+
+Search options:
+    --target-bits <N>           suggest the cheapest configuration reaching this security
+    --proven                    target proven rather than conjectured security
+    --max-rate-bits <N>         largest rate_bits to consider [default: 4]
+    --max-proof-of-work-bits <N>
+                                largest proof_of_work_bits to consider [default: 20]
+";
+
+struct Args {
+    config: FriConfig,
+    params: SoundnessParams,
+    target_bits: Option<f64>,
+    proven: bool,
+    max_rate_bits: usize,
+    max_proof_of_work_bits: u32,
+}
+
+fn parse_args() -> Result<Args> {
+    let mut rate_bits = 3;
+    let mut num_query_rounds = 28;
+    let mut proof_of_work_bits = 16;
+    let mut cap_height = 4;
+    let mut arity_bits = 4;
+    let mut final_poly_bits = 5;
+    let mut params = SoundnessParams {
+        field_bits: GoldilocksField::ORDER.ilog2() as usize,
+        extension_degree: 2,
+        degree_bits: 12,
+        num_polys: 300,
+        constraint_degree: 8,
+        num_constraints: 123,
+        num_challenges: 2,
+        permutation_width: 80,
+        lookup_width: 0,
+    };
+    let mut target_bits = None;
+    let mut proven = false;
+    let mut max_rate_bits = 4;
+    let mut max_proof_of_work_bits = 20;
+
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        if flag == "--help" || flag == "-h" {
+            print!("{USAGE}");
+            exit(0);
+        }
+        if flag == "--proven" {
+            proven = true;
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| anyhow!("Missing value for {flag}"))?;
+        let int = || -> Result<usize> {
+            value
+                .parse()
+                .with_context(|| format!("Invalid value for {flag}: {value}"))
+        };
+        match flag.as_str() {
+            "--rate-bits" => rate_bits = int()?,
+            "--num-query-rounds" => num_query_rounds = int()?,
+            "--proof-of-work-bits" => proof_of_work_bits = int()? as u32,
+            "--cap-height" => cap_height = int()?,
+            "--arity-bits" => arity_bits = int()?,
+            "--final-poly-bits" => final_poly_bits = int()?,
+            "--field-bits" => params.field_bits = int()?,
+            "--extension-degree" => params.extension_degree = int()?,
+            "--degree-bits" => params.degree_bits = int()?,
+            "--num-polys" => params.num_polys = int()?,
+            "--constraint-degree" => params.constraint_degree = int()?,
+            "--num-constraints" => params.num_constraints = int()?,
+            "--num-challenges" => params.num_challenges = int()?,
+            "--permutation-width" => params.permutation_width = int()?,
+            "--lookup-width" => params.lookup_width = int()?,
+            "--target-bits" => target_bits = Some(int()? as f64),
+            "--max-rate-bits" => max_rate_bits = int()?,
+            "--max-proof-of-work-bits" => max_proof_of_work_bits = int()? as u32,
+            _ => bail!("Unknown option {flag}\n\n{USAGE}"),
+        }
+    }
+
+    let config = FriConfig {
+        rate_bits,
+        cap_height,
+        proof_of_work_bits,
+        reduction_strategy: FriReductionStrategy::ConstantArityBits(arity_bits, final_poly_bits),
+        num_query_rounds,
+    };
+    Ok(Args {
+        config,
+        params,
+        target_bits,
+        proven,
+        max_rate_bits,
+        max_proof_of_work_bits,
+    })
+}
+
+fn print_bits(name: &str, bits: &SecurityBits) {
+    println!("{name}:");
+    println!("    FRI queries      {:7.1}", bits.fri_queries);
+    println!("    FRI commit phase {:7.1}", bits.fri_commit);
+    println!("    DEEP openings    {:7.1}", bits.deep);
+    println!("    constraints      {:7.1}", bits.constraints);
+    if let Some(permutation) = bits.permutation {
+        println!("    permutation      {permutation:7.1}");
+    }
+    if let Some(lookup) = bits.lookup {
+        println!("    lookups          {lookup:7.1}");
+    }
+    println!("    total            {:7.1}", bits.total());
+}
+
+fn print_config(config: &FriConfig) {
+    println!(
+        "rate_bits = {}, num_query_rounds = {}, proof_of_work_bits = {}",
+        config.rate_bits, config.num_query_rounds, config.proof_of_work_bits
+    );
+}
+
+fn main() -> Result<()> {
+    let args = parse_args()?;
+
+    print_config(&args.config);
+    let report = security_report(&args.config, &args.params);
+    print_bits(
+        &format!("Proven security (Johnson bound, m = {})", report.johnson_m),
+        &report.proven,
+    );
+    print_bits("Conjectured security", &report.conjectured);
+
+    if let Some(target_bits) = args.target_bits {
+        let kind = if args.proven { "proven" } else { "conjectured" };
+        println!();
+        match cheapest_config(
+            &args.config,
+            &args.params,
+            target_bits,
+            args.proven,
+            args.max_rate_bits,
+            args.max_proof_of_work_bits,
+        ) {
+            Some(SuggestedConfig {
+                fri_config,
+                num_challenges,
+            }) => {
+                println!("Cheapest configuration with {target_bits} bits of {kind} security:");
+                print_config(&fri_config);
+                println!("num_challenges = {num_challenges}");
+            }
+            None => println!("No configuration reaches {target_bits} bits of {kind} security."),
+        }
+    }
+    Ok(())
+}
@@ -8,6 +8,8 @@ pub mod proof;
 pub mod prover;
 pub mod recursive_verifier;
 pub mod reduction_strategies;
+#[cfg(feature = "std")]
+pub mod security;
 pub mod structure;
 mod validate_shape;
 pub mod verifier;
@@ -49,6 +51,47 @@ impl FriConfig {
         }
     }
 
+    /// FRI parameters for a batched proof of polynomials of the given degrees, sorted in
+    /// non-increasing order, as `verify_batch_fri_proof` expects. The reductions go through every
+    /// degree, with arities no larger than those of the reduction strategy, which then picks the
+    /// reductions below the smallest degree.
+    pub fn batch_fri_params(&self, degree_bits: &[usize], hiding: bool) -> FriParams {
+        assert!(
+            degree_bits.windows(2).all(|w| w[0] >= w[1]),
+            "Degrees must be sorted in non-increasing order."
+        );
+        let max_arity_bits = match &self.reduction_strategy {
+            FriReductionStrategy::Fixed(arity_bits) => {
+                arity_bits.iter().copied().max().unwrap_or(1)
+            }
+            &FriReductionStrategy::ConstantArityBits(arity_bits, _) => arity_bits,
+            FriReductionStrategy::MinSize(opt_max_arity_bits) => opt_max_arity_bits.unwrap_or(4),
+        };
+
+        let mut reduction_arity_bits = Vec::new();
+        for w in degree_bits.windows(2) {
+            let mut bits = w[0] - w[1];
+            while bits > 0 {
+                let arity_bits = bits.min(max_arity_bits);
+                reduction_arity_bits.push(arity_bits);
+                bits -= arity_bits;
+            }
+        }
+        let min_degree_bits = *degree_bits.last().unwrap();
+        reduction_arity_bits.extend(self.reduction_strategy.reduction_arity_bits(
+            min_degree_bits,
+            self.rate_bits,
+            self.cap_height,
+            self.num_query_rounds,
+        ));
+        FriParams {
+            config: self.clone(),
+            hiding,
+            degree_bits: degree_bits[0],
+            reduction_arity_bits,
+        }
+    }
+
     pub fn num_cap_elements(&self) -> usize {
         1 << self.cap_height
     }
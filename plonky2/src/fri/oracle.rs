@@ -1,5 +1,9 @@
 use alloc::format;
 use alloc::vec::Vec;
+#[cfg(feature = "disk_storage")]
+use core::mem::size_of;
+#[cfg(feature = "disk_storage")]
+use std::path::{Path, PathBuf};
 
 use itertools::Itertools;
 use plonky2_field::types::Field;
@@ -10,7 +14,7 @@ use crate::field::fft::FftRootTable;
 use crate::field::packed::PackedField;
 use crate::field::polynomial::{PolynomialCoeffs, PolynomialValues};
 use crate::fri::proof::FriProof;
-use crate::fri::prover::fri_proof;
+use crate::fri::prover::{batch_fri_proof, fri_proof};
 use crate::fri::structure::{FriBatchInfo, FriInstanceInfo};
 use crate::fri::FriParams;
 use crate::hash::hash_types::RichField;
@@ -18,6 +22,8 @@ use crate::hash::merkle_tree::MerkleTree;
 use crate::iop::challenger::Challenger;
 use crate::plonk::config::GenericConfig;
 use crate::timed;
+#[cfg(feature = "disk_storage")]
+use crate::util::disk_vec::DiskVec;
 use crate::util::reducing::ReducingFactor;
 use crate::util::timing::TimingTree;
 use crate::util::{log2_strict, reverse_bits, reverse_index_bits_in_place, transpose};
@@ -25,6 +31,29 @@ use crate::util::{log2_strict, reverse_bits, reverse_index_bits_in_place, transp
 /// Four (~64 bit) field elements gives ~128 bit security.
 pub const SALT_SIZE: usize = 4;
 
+/// Where a [`PolynomialBatch`] stores its LDE values and Merkle tree.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum CommitmentStorage {
+    /// Keep everything in memory.
+    #[default]
+    Memory,
+    /// Write the LDE values and Merkle digests to memory-mapped files in `dir`, so that the OS can
+    /// page them out. At most `max_lde_bytes` of LDE values are computed in memory at a time.
+    #[cfg(feature = "disk_storage")]
+    Disk { dir: PathBuf, max_lde_bytes: usize },
+}
+
+impl CommitmentStorage {
+    /// Disk storage in `dir`, computing up to 1 GiB of LDE values at a time.
+    #[cfg(feature = "disk_storage")]
+    pub fn disk<P: Into<PathBuf>>(dir: P) -> Self {
+        Self::Disk {
+            dir: dir.into(),
+            max_lde_bytes: 1 << 30,
+        }
+    }
+}
+
 /// Represents a FRI oracle, i.e. a batch of polynomials which have been Merklized.
 pub struct PolynomialBatch<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
 {
@@ -96,6 +125,141 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         }
     }
 
+    /// Like `from_values`, but stores the LDE values and Merkle tree in `storage`.
+    pub fn from_values_with_storage(
+        values: Vec<PolynomialValues<F>>,
+        rate_bits: usize,
+        blinding: bool,
+        cap_height: usize,
+        timing: &mut TimingTree,
+        fft_root_table: Option<&FftRootTable<F>>,
+        storage: &CommitmentStorage,
+    ) -> anyhow::Result<Self> {
+        let coeffs = timed!(
+            timing,
+            "IFFT",
+            values.into_par_iter().map(|v| v.ifft()).collect::<Vec<_>>()
+        );
+
+        Self::from_coeffs_with_storage(
+            coeffs,
+            rate_bits,
+            blinding,
+            cap_height,
+            timing,
+            fft_root_table,
+            storage,
+        )
+    }
+
+    /// Like `from_coeffs`, but stores the LDE values and Merkle tree in `storage`.
+    pub fn from_coeffs_with_storage(
+        polynomials: Vec<PolynomialCoeffs<F>>,
+        rate_bits: usize,
+        blinding: bool,
+        cap_height: usize,
+        timing: &mut TimingTree,
+        fft_root_table: Option<&FftRootTable<F>>,
+        storage: &CommitmentStorage,
+    ) -> anyhow::Result<Self> {
+        match storage {
+            CommitmentStorage::Memory => Ok(Self::from_coeffs(
+                polynomials,
+                rate_bits,
+                blinding,
+                cap_height,
+                timing,
+                fft_root_table,
+            )),
+            #[cfg(feature = "disk_storage")]
+            CommitmentStorage::Disk { dir, max_lde_bytes } => Self::from_coeffs_on_disk(
+                polynomials,
+                rate_bits,
+                blinding,
+                cap_height,
+                timing,
+                fft_root_table,
+                dir,
+                *max_lde_bytes,
+            ),
+        }
+    }
+
+    /// Computes the LDEs a few polynomials at a time, and writes them straight into the leaves of
+    /// a disk-backed Merkle tree.
+    #[cfg(feature = "disk_storage")]
+    #[allow(clippy::too_many_arguments)]
+    fn from_coeffs_on_disk(
+        polynomials: Vec<PolynomialCoeffs<F>>,
+        rate_bits: usize,
+        blinding: bool,
+        cap_height: usize,
+        timing: &mut TimingTree,
+        fft_root_table: Option<&FftRootTable<F>>,
+        dir: &Path,
+        max_lde_bytes: usize,
+    ) -> anyhow::Result<Self> {
+        let degree = polynomials[0].len();
+        let lde_size = degree << rate_bits;
+        let num_polys = polynomials.len();
+        let salt_size = if blinding { SALT_SIZE } else { 0 };
+        let leaf_len = num_polys + salt_size;
+        let mut leaves = DiskVec::<F>::new_uninit(dir, lde_size * leaf_len)?;
+
+        // The number of LDEs held in memory at once.
+        let batch_size = (max_lde_bytes / (lde_size * size_of::<F>())).max(1);
+        timed!(timing, "FFT + blinding, write LDEs to disk", {
+            for (batch_index, batch) in polynomials.chunks(batch_size).enumerate() {
+                let ldes = batch
+                    .par_iter()
+                    .map(|p| {
+                        assert_eq!(p.len(), degree, "Polynomial degrees inconsistent");
+                        let mut lde = p
+                            .lde(rate_bits)
+                            .coset_fft_with_options(
+                                F::coset_shift(),
+                                Some(rate_bits),
+                                fft_root_table,
+                            )
+                            .values;
+                        reverse_index_bits_in_place(&mut lde);
+                        lde
+                    })
+                    .collect::<Vec<_>>();
+                let offset = batch_index * batch_size;
+                leaves
+                    .par_chunks_mut(leaf_len)
+                    .enumerate()
+                    .for_each(|(i, leaf)| {
+                        for (x, lde) in leaf[offset..].iter_mut().zip(&ldes) {
+                            x.write(lde[i]);
+                        }
+                    });
+            }
+            leaves.par_chunks_mut(leaf_len).for_each(|leaf| {
+                for x in &mut leaf[num_polys..] {
+                    x.write(F::rand());
+                }
+            });
+        });
+
+        // SAFETY: Every LDE value and salt element was written above.
+        let leaves = unsafe { leaves.assume_init() };
+        let merkle_tree = timed!(
+            timing,
+            "build Merkle tree",
+            MerkleTree::new_on_disk(leaves, leaf_len, cap_height)?
+        );
+
+        Ok(Self {
+            polynomials,
+            merkle_tree,
+            degree_log: log2_strict(degree),
+            rate_bits,
+            blinding,
+        })
+    }
+
     fn lde_values(
         polynomials: &[PolynomialCoeffs<F>],
         rate_bits: usize,
@@ -167,6 +331,104 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
     ) -> FriProof<F, C::Hasher, D> {
         assert!(D > 1, "Not implemented for D=1.");
         let alpha = challenger.get_extension_challenge::<D>();
+        let final_poly = Self::fri_reduced_poly(&[instance], oracles, alpha, timing);
+
+        let lde_final_poly = final_poly.lde(fri_params.config.rate_bits);
+        let lde_final_values = timed!(
+            timing,
+            &format!("perform final FFT {}", lde_final_poly.len()),
+            lde_final_poly.coset_fft(F::coset_shift().into())
+        );
+
+        let fri_proof = fri_proof::<F, C, D>(
+            &oracles
+                .par_iter()
+                .map(|c| &c.merkle_tree)
+                .collect::<Vec<_>>(),
+            lde_final_poly,
+            lde_final_values,
+            challenger,
+            fri_params,
+            timing,
+        );
+
+        fri_proof
+    }
+
+    /// Produces a single FRI proof for several instances whose oracles may have different degrees.
+    ///
+    /// Every instance indexes into the shared `oracles` list, and all oracles of an instance must
+    /// have the same degree. Instances must be sorted by non-increasing degree, and `fri_params`
+    /// are those of the largest degree; its reductions must pass through every smaller degree.
+    /// Instances of equal degree are combined as in `prove_openings`, while each smaller degree is
+    /// folded into the FRI codeword at the matching reduction.
+    pub fn batch_prove_openings(
+        instances: &[FriInstanceInfo<F, D>],
+        oracles: &[&Self],
+        challenger: &mut Challenger<F, C::Hasher>,
+        fri_params: &FriParams,
+        timing: &mut TimingTree,
+    ) -> FriProof<F, C::Hasher, D> {
+        assert!(D > 1, "Not implemented for D=1.");
+        let instance_degree_bits = instances
+            .iter()
+            .map(|instance| {
+                instance
+                    .batches
+                    .iter()
+                    .flat_map(|batch| &batch.polynomials)
+                    .map(|p| oracles[p.oracle_index].degree_log)
+                    .dedup()
+                    .exactly_one()
+                    .unwrap_or_else(|_| {
+                        panic!("All oracles of an instance must have the same degree.")
+                    })
+            })
+            .collect::<Vec<_>>();
+        assert!(
+            instance_degree_bits.windows(2).all(|w| w[0] >= w[1]),
+            "Instances must be sorted by non-increasing degree."
+        );
+        assert_eq!(instance_degree_bits[0], fri_params.degree_bits);
+
+        let alpha = challenger.get_extension_challenge::<D>();
+        let lde_polys = instances
+            .iter()
+            .zip(&instance_degree_bits)
+            .group_by(|&(_, degree_bits)| degree_bits)
+            .into_iter()
+            .map(|(_, group)| {
+                let group = group.map(|(instance, _)| instance).collect::<Vec<_>>();
+                Self::fri_reduced_poly(&group, oracles, alpha, timing)
+                    .lde(fri_params.config.rate_bits)
+            })
+            .collect::<Vec<_>>();
+        let lde_values = timed!(
+            timing,
+            &format!("perform final FFT {}", lde_polys[0].len()),
+            lde_polys[0].coset_fft(F::coset_shift().into())
+        );
+
+        batch_fri_proof::<F, C, D>(
+            &oracles
+                .par_iter()
+                .map(|c| &c.merkle_tree)
+                .collect::<Vec<_>>(),
+            lde_polys,
+            lde_values,
+            challenger,
+            fri_params,
+            timing,
+        )
+    }
+
+    /// Computes the polynomial that goes into FRI for the given instances.
+    fn fri_reduced_poly(
+        instances: &[&FriInstanceInfo<F, D>],
+        oracles: &[&Self],
+        alpha: F::Extension,
+        timing: &mut TimingTree,
+    ) -> PolynomialCoeffs<F::Extension> {
         let mut alpha = ReducingFactor::new(alpha);
 
         // Final low-degree polynomial that goes into FRI.
@@ -179,7 +441,7 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         // where the `k_i`s are chosen such that each power of `alpha` appears only once in the final sum.
         // There are usually two batches for the openings at `zeta` and `g * zeta`.
         // The oracles used in Plonky2 are given in `FRI_ORACLES` in `plonky2/src/plonk/plonk_common.rs`.
-        for FriBatchInfo { point, polynomials } in &instance.batches {
+        for FriBatchInfo { point, polynomials } in instances.iter().flat_map(|i| &i.batches) {
             // Collect the coefficients of all the polynomials in `polynomials`.
             let polys_coeff = polynomials.iter().map(|fri_poly| {
                 &oracles[fri_poly.oracle_index].polynomials[fri_poly.polynomial_index]
@@ -195,25 +457,342 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
             final_poly += quotient;
         }
 
-        let lde_final_poly = final_poly.lde(fri_params.config.rate_bits);
-        let lde_final_values = timed!(
-            timing,
-            &format!("perform final FFT {}", lde_final_poly.len()),
-            lde_final_poly.coset_fft(F::coset_shift().into())
+        final_poly
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::extension::FieldExtension;
+    use crate::field::types::Sample;
+    use crate::fri::reduction_strategies::FriReductionStrategy;
+    use crate::fri::structure::{
+        FriBatchInfoTarget, FriInstanceInfoTarget, FriOpeningBatch, FriOpeningBatchTarget,
+        FriOpenings, FriOpeningsTarget, FriOracleInfo, FriPolynomialInfo,
+    };
+    use crate::fri::verifier::verify_batch_fri_proof;
+    use crate::fri::witness_util::set_fri_proof_target;
+    use crate::fri::FriConfig;
+    use crate::hash::merkle_tree::MerkleCap;
+    #[cfg(feature = "disk_storage")]
+    use crate::hash::merkle_tree::MerkleLeaves;
+    use crate::iop::challenger::RecursiveChallenger;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type FE = <F as Extendable<D>>::Extension;
+    type H = <C as GenericConfig<D>>::Hasher;
+
+    /// A batched FRI proof, along with the data needed to verify it.
+    struct BatchFri {
+        degree_bits: Vec<usize>,
+        instances: Vec<FriInstanceInfo<F, D>>,
+        openings: Vec<FriOpenings<F, D>>,
+        caps: Vec<MerkleCap<F, H>>,
+        proof: FriProof<F, H, D>,
+        fri_params: FriParams,
+    }
+
+    /// Commits to oracles of 2^8, 2^8, 2^5 and 2^3 coefficients, opens each of them in its own
+    /// instance, and proves the openings in a single batched FRI proof.
+    fn batch_fri_proof() -> BatchFri {
+        let degree_bits = [8, 8, 5, 3];
+        let num_polys = [3, 2, 4, 1];
+        let config = FriConfig {
+            rate_bits: 2,
+            cap_height: 1,
+            proof_of_work_bits: 2,
+            reduction_strategy: FriReductionStrategy::Fixed(vec![2, 1, 2]),
+            num_query_rounds: 10,
+        };
+        let fri_params = config.fri_params(degree_bits[0], false);
+        let mut timing = TimingTree::default();
+
+        let oracles = degree_bits
+            .iter()
+            .zip(num_polys)
+            .map(|(&bits, n)| {
+                let polys = (0..n)
+                    .map(|_| PolynomialCoeffs::new(F::rand_vec(1 << bits)))
+                    .collect();
+                PolynomialBatch::<F, C, D>::from_coeffs(
+                    polys,
+                    config.rate_bits,
+                    false,
+                    config.cap_height,
+                    &mut timing,
+                    None,
+                )
+            })
+            .collect::<Vec<_>>();
+        let oracle_infos = num_polys
+            .iter()
+            .map(|&num_polys| FriOracleInfo {
+                num_polys,
+                blinding: false,
+            })
+            .collect::<Vec<_>>();
+
+        // Each instance opens its oracle at `zeta`, and its first polynomial at `g * zeta`.
+        let zeta = FE::rand();
+        let mut instances = Vec::new();
+        let mut openings = Vec::new();
+        for (i, (oracle, &bits)) in oracles.iter().zip(&degree_bits).enumerate() {
+            let g = F::primitive_root_of_unity(bits);
+            let points = [zeta, zeta * <FE as FieldExtension<D>>::from_basefield(g)];
+            let polys = [
+                FriPolynomialInfo::from_range(i, 0..num_polys[i]),
+                FriPolynomialInfo::from_range(i, 0..1),
+            ];
+            openings.push(FriOpenings {
+                batches: points
+                    .iter()
+                    .zip(&polys)
+                    .map(|(&point, polys)| FriOpeningBatch {
+                        values: polys
+                            .iter()
+                            .map(|p| {
+                                oracle.polynomials[p.polynomial_index]
+                                    .to_extension::<D>()
+                                    .eval(point)
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            });
+            instances.push(FriInstanceInfo {
+                oracles: oracle_infos.clone(),
+                batches: points
+                    .into_iter()
+                    .zip(polys)
+                    .map(|(point, polynomials)| FriBatchInfo { point, polynomials })
+                    .collect(),
+            });
+        }
+
+        let mut challenger = Challenger::<F, H>::new();
+        for oracle in &oracles {
+            challenger.observe_cap(&oracle.merkle_tree.cap);
+        }
+        for os in &openings {
+            challenger.observe_openings(os);
+        }
+        let proof = PolynomialBatch::batch_prove_openings(
+            &instances,
+            &oracles.iter().collect::<Vec<_>>(),
+            &mut challenger,
+            &fri_params,
+            &mut timing,
         );
 
-        let fri_proof = fri_proof::<F, C, D>(
-            &oracles
-                .par_iter()
-                .map(|c| &c.merkle_tree)
-                .collect::<Vec<_>>(),
-            lde_final_poly,
-            lde_final_values,
-            challenger,
+        BatchFri {
+            degree_bits: degree_bits.to_vec(),
+            instances,
+            openings,
+            caps: oracles.iter().map(|o| o.merkle_tree.cap.clone()).collect(),
+            proof,
             fri_params,
-            timing,
+        }
+    }
+
+    /// Returns whether the batched proof verifies after applying `tamper` to the openings.
+    fn batch_fri(tamper: impl Fn(&mut [FriOpenings<F, D>])) -> Result<()> {
+        let BatchFri {
+            degree_bits,
+            instances,
+            mut openings,
+            caps,
+            proof,
+            fri_params,
+        } = batch_fri_proof();
+        tamper(&mut openings);
+        let mut challenger = Challenger::<F, H>::new();
+        for cap in &caps {
+            challenger.observe_cap(cap);
+        }
+        for os in &openings {
+            challenger.observe_openings(os);
+        }
+        let challenges = challenger.fri_challenges::<C, D>(
+            &proof.commit_phase_merkle_caps,
+            &proof.final_poly,
+            proof.pow_witness,
+            degree_bits[0],
+            &fri_params.config,
         );
+        verify_batch_fri_proof::<F, C, D>(
+            &degree_bits,
+            &instances,
+            &openings,
+            &challenges,
+            &caps,
+            &proof,
+            &fri_params,
+        )
+    }
 
-        fri_proof
+    /// Like `batch_fri`, but verifies the batched proof in a circuit.
+    fn batch_fri_circuit(tamper: impl Fn(&mut [FriOpenings<F, D>])) -> Result<()> {
+        let BatchFri {
+            degree_bits,
+            instances,
+            mut openings,
+            caps,
+            proof,
+            fri_params,
+        } = batch_fri_proof();
+        tamper(&mut openings);
+        let fri_config = &fri_params.config;
+
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let instances_t = instances
+            .iter()
+            .map(|instance| FriInstanceInfoTarget {
+                oracles: instance.oracles.clone(),
+                batches: instance
+                    .batches
+                    .iter()
+                    .map(|batch| FriBatchInfoTarget {
+                        point: builder.constant_extension(batch.point),
+                        polynomials: batch.polynomials.clone(),
+                    })
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        let openings_t = openings
+            .iter()
+            .map(|os| FriOpeningsTarget {
+                batches: os
+                    .batches
+                    .iter()
+                    .map(|batch| FriOpeningBatchTarget {
+                        values: builder.add_virtual_extension_targets(batch.values.len()),
+                    })
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        let caps_t = caps
+            .iter()
+            .map(|_| builder.add_virtual_cap(fri_config.cap_height))
+            .collect::<Vec<_>>();
+        let num_leaves_per_oracle = instances[0]
+            .oracles
+            .iter()
+            .map(|o| o.num_polys)
+            .collect::<Vec<_>>();
+        let proof_t =
+            builder.add_virtual_batch_fri_proof(&num_leaves_per_oracle, &degree_bits, &fri_params);
+
+        let mut challenger = RecursiveChallenger::<F, H, D>::new(&mut builder);
+        for cap in &caps_t {
+            challenger.observe_cap(cap);
+        }
+        for os in &openings_t {
+            challenger.observe_openings(os);
+        }
+        let challenges = challenger.fri_challenges(
+            &mut builder,
+            &proof_t.commit_phase_merkle_caps,
+            &proof_t.final_poly,
+            proof_t.pow_witness,
+            fri_config,
+        );
+        builder.verify_batch_fri_proof::<C>(
+            &degree_bits,
+            &instances_t,
+            &openings_t,
+            &challenges,
+            &caps_t,
+            &proof_t,
+            &fri_params,
+        );
+
+        let mut pw = PartialWitness::new();
+        for (cap_t, cap) in caps_t.iter().zip(&caps) {
+            pw.set_cap_target(cap_t, cap);
+        }
+        for (os_t, os) in openings_t.iter().zip(&openings) {
+            for (batch_t, batch) in os_t.batches.iter().zip(&os.batches) {
+                pw.set_extension_targets(&batch_t.values, &batch.values);
+            }
+        }
+        set_fri_proof_target(&mut pw, &proof_t, &proof);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)
+    }
+
+    #[test]
+    fn test_batch_prove_openings() -> Result<()> {
+        batch_fri(|_| {})
+    }
+
+    #[test]
+    fn test_batch_prove_openings_wrong_opening() {
+        for i in 0..4 {
+            assert!(batch_fri(|openings| openings[i].batches[1].values[0] += FE::ONE).is_err());
+        }
+    }
+
+    #[test]
+    fn test_batch_prove_openings_circuit() -> Result<()> {
+        batch_fri_circuit(|_| {})
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_batch_prove_openings_circuit_wrong_opening() {
+        // The smallest instance is only folded in after the last reduction.
+        batch_fri_circuit(|openings| openings[3].batches[1].values[0] += FE::ONE).unwrap();
+    }
+
+    #[cfg(feature = "disk_storage")]
+    #[test]
+    fn test_disk_storage_matches_memory() -> Result<()> {
+        let polys = (0..5)
+            .map(|_| PolynomialCoeffs::new(F::rand_vec(1 << 6)))
+            .collect::<Vec<_>>();
+        // Small enough that the LDEs are computed two at a time.
+        let storage = CommitmentStorage::Disk {
+            dir: std::env::temp_dir(),
+            max_lde_bytes: 2 << (6 + 2 + 3),
+        };
+        let mut timing = TimingTree::default();
+        let memory =
+            PolynomialBatch::<F, C, D>::from_coeffs(polys.clone(), 2, false, 2, &mut timing, None);
+        let disk = PolynomialBatch::<F, C, D>::from_coeffs_with_storage(
+            polys,
+            2,
+            false,
+            2,
+            &mut timing,
+            None,
+            &storage,
+        )?;
+
+        assert!(matches!(disk.merkle_tree.leaves, MerkleLeaves::Disk { .. }));
+        assert_eq!(disk.merkle_tree.cap, memory.merkle_tree.cap);
+        assert_eq!(*disk.merkle_tree.digests, *memory.merkle_tree.digests);
+        for i in 0..1 << 8 {
+            assert_eq!(disk.get_lde_values(i, 1), memory.get_lde_values(i, 1));
+            assert_eq!(disk.merkle_tree.prove(i), memory.merkle_tree.prove(i));
+        }
+
+        // Clones are in memory, unless they're made with `try_clone`.
+        let leaves = &disk.merkle_tree.leaves;
+        assert!(matches!(leaves.clone(), MerkleLeaves::Memory(_)));
+        assert!(matches!(leaves.try_clone()?, MerkleLeaves::Disk { .. }));
+        assert!(leaves.clone().iter().eq(leaves.iter()));
+        assert!(leaves.try_clone()?.iter().eq(leaves.iter()));
+        Ok(())
     }
 }
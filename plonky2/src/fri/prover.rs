@@ -1,9 +1,11 @@
+use alloc::vec;
 use alloc::vec::Vec;
 
 use plonky2_maybe_rayon::*;
 
-use crate::field::extension::{flatten, unflatten, Extendable};
+use crate::field::extension::{flatten, unflatten, Extendable, FieldExtension};
 use crate::field::polynomial::{PolynomialCoeffs, PolynomialValues};
+use crate::field::types::Field;
 use crate::fri::proof::{FriInitialTreeProof, FriProof, FriQueryRound, FriQueryStep};
 use crate::fri::{FriConfig, FriParams};
 use crate::hash::hash_types::RichField;
@@ -13,8 +15,8 @@ use crate::iop::challenger::Challenger;
 use crate::plonk::config::{GenericConfig, Hasher};
 use crate::plonk::plonk_common::reduce_with_powers;
 use crate::timed;
-use crate::util::reverse_index_bits_in_place;
 use crate::util::timing::TimingTree;
+use crate::util::{log2_strict, reverse_index_bits_in_place};
 
 /// Builds a FRI proof.
 pub fn fri_proof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
@@ -26,9 +28,37 @@ pub fn fri_proof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const
     challenger: &mut Challenger<F, C::Hasher>,
     fri_params: &FriParams,
     timing: &mut TimingTree,
+) -> FriProof<F, C::Hasher, D> {
+    batch_fri_proof::<F, C, D>(
+        initial_merkle_trees,
+        vec![lde_polynomial_coeffs],
+        lde_polynomial_values,
+        challenger,
+        fri_params,
+        timing,
+    )
+}
+
+/// Builds a FRI proof for polynomials of different degrees.
+///
+/// `lde_polynomial_coeffs` holds one polynomial per LDE size, sorted by strictly decreasing size,
+/// and `lde_polynomial_values` is the evaluation of the first (largest) one. Each smaller
+/// polynomial is folded in right after the reduction which brings the codeword down to its size,
+/// so `fri_params` must be those of the largest polynomial and its reductions must pass through
+/// every smaller size. Initial Merkle trees may be smaller than the LDE domain, in which case they
+/// are opened at the query index of the matching reduction.
+pub fn batch_fri_proof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
+    initial_merkle_trees: &[&MerkleTree<F, C::Hasher>],
+    mut lde_polynomial_coeffs: Vec<PolynomialCoeffs<F::Extension>>,
+    lde_polynomial_values: PolynomialValues<F::Extension>,
+    challenger: &mut Challenger<F, C::Hasher>,
+    fri_params: &FriParams,
+    timing: &mut TimingTree,
 ) -> FriProof<F, C::Hasher, D> {
     let n = lde_polynomial_values.len();
-    assert_eq!(lde_polynomial_coeffs.len(), n);
+    assert_eq!(lde_polynomial_coeffs[0].len(), n);
+    let batch_coeffs = lde_polynomial_coeffs.split_off(1);
+    let lde_polynomial_coeffs = lde_polynomial_coeffs.pop().unwrap();
 
     // Commit phase
     let (trees, final_coeffs) = timed!(
@@ -37,6 +67,7 @@ pub fn fri_proof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const
         fri_committed_trees::<F, C, D>(
             lde_polynomial_coeffs,
             lde_polynomial_values,
+            &batch_coeffs,
             challenger,
             fri_params,
         )
@@ -69,10 +100,12 @@ type FriCommitedTrees<F, C, const D: usize> = (
 fn fri_committed_trees<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
     mut coeffs: PolynomialCoeffs<F::Extension>,
     mut values: PolynomialValues<F::Extension>,
+    batch_coeffs: &[PolynomialCoeffs<F::Extension>],
     challenger: &mut Challenger<F, C::Hasher>,
     fri_params: &FriParams,
 ) -> FriCommitedTrees<F, C, D> {
     let mut trees = Vec::new();
+    let mut batch_coeffs = batch_coeffs.iter().peekable();
 
     let mut shift = F::MULTIPLICATIVE_GROUP_GENERATOR;
     for arity_bits in &fri_params.reduction_arity_bits {
@@ -99,8 +132,26 @@ fn fri_committed_trees<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>,
                 .collect::<Vec<_>>(),
         );
         shift = shift.exp_u64(arity as u64);
+
+        if let Some(batch_poly) = batch_coeffs.next_if(|p| p.len() == coeffs.len()) {
+            // The smaller codeword was computed on the coset `g H` rather than `shift H`, so at
+            // index `i` it holds the evaluation of `Q(g / shift * X)`, which has the same degree
+            // as `Q`. We fold in that polynomial, weighted by `beta^arity`.
+            let beta_arity = beta.exp_u64(arity as u64);
+            let ratio = F::MULTIPLICATIVE_GROUP_GENERATOR / shift;
+            coeffs
+                .coeffs
+                .iter_mut()
+                .zip(&batch_poly.coeffs)
+                .zip(ratio.powers())
+                .for_each(|((c, &q), r)| *c += beta_arity * F::Extension::from_basefield(r) * q);
+        }
         values = coeffs.coset_fft(shift.into())
     }
+    assert!(
+        batch_coeffs.next().is_none(),
+        "Polynomial sizes must be reached by the FRI reductions, in decreasing order."
+    );
 
     // The coefficients being removed here should always be zero.
     coeffs
@@ -194,9 +245,13 @@ fn fri_prover_query_round<
     fri_params: &FriParams,
 ) -> FriQueryRound<F, C::Hasher, D> {
     let mut query_steps = Vec::new();
+    let lde_bits = fri_params.lde_bits();
     let initial_proof = initial_merkle_trees
         .iter()
-        .map(|t| (t.get(x_index).to_vec(), t.prove(x_index)))
+        .map(|t| {
+            let index = x_index >> (lde_bits - log2_strict(t.leaves.len()));
+            (t.get(index).to_vec(), t.prove(index))
+        })
         .collect::<Vec<_>>();
     for (i, tree) in trees.iter().enumerate() {
         let arity_bits = fri_params.reduction_arity_bits[i];
@@ -9,6 +9,7 @@ use crate::fri::proof::{
     FriQueryStepTarget,
 };
 use crate::fri::structure::{FriBatchInfoTarget, FriInstanceInfoTarget, FriOpeningsTarget};
+use crate::fri::verifier::{batch_fri_groups, batch_fri_oracle_lde_bits};
 use crate::fri::{FriConfig, FriParams};
 use crate::gates::coset_interpolation::CosetInterpolationGate;
 use crate::gates::gate::Gate;
@@ -218,29 +219,54 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             params.config.cap_height + proof.evals_proofs[0].1.siblings.len()
                 - params.config.rate_bits
         );
+        self.fri_combine_initial_instances(
+            &[(instance, precomputed_reduced_evals)],
+            proof,
+            alpha,
+            subgroup_x,
+            params,
+        )
+    }
+
+    /// Combines the initial openings of several instances of the same degree, in the same way as
+    /// the prover combines their polynomials.
+    fn fri_combine_initial_instances(
+        &mut self,
+        instances: &[(
+            &FriInstanceInfoTarget<D>,
+            &PrecomputedReducedOpeningsTarget<D>,
+        )],
+        proof: &FriInitialTreeProofTarget,
+        alpha: ExtensionTarget<D>,
+        subgroup_x: Target,
+        params: &FriParams,
+    ) -> ExtensionTarget<D> {
+        assert!(D > 1, "Not implemented for D=1.");
         let subgroup_x = self.convert_to_ext(subgroup_x);
         let mut alpha = ReducingFactorTarget::new(alpha);
         let mut sum = self.zero_extension();
 
-        for (batch, reduced_openings) in instance
-            .batches
-            .iter()
-            .zip(&precomputed_reduced_evals.reduced_openings_at_point)
-        {
-            let FriBatchInfoTarget { point, polynomials } = batch;
-            let evals = polynomials
+        for (instance, precomputed_reduced_evals) in instances {
+            for (batch, reduced_openings) in instance
+                .batches
                 .iter()
-                .map(|p| {
-                    let poly_blinding = instance.oracles[p.oracle_index].blinding;
-                    let salted = params.hiding && poly_blinding;
-                    proof.unsalted_eval(p.oracle_index, p.polynomial_index, salted)
-                })
-                .collect_vec();
-            let reduced_evals = alpha.reduce_base(&evals, self);
-            let numerator = self.sub_extension(reduced_evals, *reduced_openings);
-            let denominator = self.sub_extension(subgroup_x, *point);
-            sum = alpha.shift(sum, self);
-            sum = self.div_add_extension(numerator, denominator, sum);
+                .zip(&precomputed_reduced_evals.reduced_openings_at_point)
+            {
+                let FriBatchInfoTarget { point, polynomials } = batch;
+                let evals = polynomials
+                    .iter()
+                    .map(|p| {
+                        let poly_blinding = instance.oracles[p.oracle_index].blinding;
+                        let salted = params.hiding && poly_blinding;
+                        proof.unsalted_eval(p.oracle_index, p.polynomial_index, salted)
+                    })
+                    .collect_vec();
+                let reduced_evals = alpha.reduce_base(&evals, self);
+                let numerator = self.sub_extension(reduced_evals, *reduced_openings);
+                let denominator = self.sub_extension(subgroup_x, *point);
+                sum = alpha.shift(sum, self);
+                sum = self.div_add_extension(numerator, denominator, sum);
+            }
         }
 
         sum
@@ -360,6 +386,264 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         self.connect_extension(eval, old_eval);
     }
 
+    /// Verifies a FRI proof produced by `PolynomialBatch::batch_prove_openings`. See
+    /// [`verify_batch_fri_proof`](crate::fri::verifier::verify_batch_fri_proof) for the meaning of
+    /// `degree_bits`, which is fixed by the circuit.
+    pub fn verify_batch_fri_proof<C: GenericConfig<D, F = F>>(
+        &mut self,
+        degree_bits: &[usize],
+        instances: &[FriInstanceInfoTarget<D>],
+        openings: &[FriOpeningsTarget<D>],
+        challenges: &FriChallengesTarget<D>,
+        initial_merkle_caps: &[MerkleCapTarget],
+        proof: &FriProofTarget<D>,
+        params: &FriParams,
+    ) where
+        C::Hasher: AlgebraicHasher<F>,
+    {
+        assert!(
+            !instances.is_empty()
+                && instances.len() == degree_bits.len()
+                && instances.len() == openings.len(),
+            "Number of instances, degrees and openings do not match."
+        );
+        let groups = batch_fri_groups(degree_bits, params).unwrap();
+        let oracle_lde_bits = batch_fri_oracle_lde_bits(
+            degree_bits,
+            instances
+                .iter()
+                .map(|instance| instance.batches.iter().flat_map(|b| &b.polynomials)),
+            initial_merkle_caps.len(),
+            params,
+        )
+        .unwrap();
+
+        if let Some(max_arity_bits) = params.max_arity_bits() {
+            self.check_recursion_config(max_arity_bits);
+        }
+
+        debug_assert_eq!(
+            params.final_poly_len(),
+            proof.final_poly.len(),
+            "Final polynomial has wrong degree."
+        );
+
+        with_context!(
+            self,
+            "check PoW",
+            self.fri_verify_proof_of_work(challenges.fri_pow_response, &params.config)
+        );
+
+        // Check that parameters are coherent.
+        debug_assert_eq!(
+            params.config.num_query_rounds,
+            proof.query_round_proofs.len(),
+            "Number of query rounds does not match config."
+        );
+
+        let precomputed_reduced_evals = with_context!(
+            self,
+            "precompute reduced evaluations",
+            openings
+                .iter()
+                .map(|os| {
+                    PrecomputedReducedOpeningsTarget::from_os_and_alpha(
+                        os,
+                        challenges.fri_alpha,
+                        self,
+                    )
+                })
+                .collect::<Vec<_>>()
+        );
+        let groups = groups
+            .iter()
+            .map(|(round, group)| {
+                let instances = group
+                    .iter()
+                    .map(|&i| (&instances[i], &precomputed_reduced_evals[i]))
+                    .collect::<Vec<_>>();
+                (*round, instances)
+            })
+            .collect::<Vec<_>>();
+
+        for (i, round_proof) in proof.query_round_proofs.iter().enumerate() {
+            // As in `verify_fri_proof`, only record a context for the second FRI query.
+            let level = if i == 1 {
+                log::Level::Debug
+            } else {
+                log::Level::Trace
+            };
+
+            let num_queries = proof.query_round_proofs.len();
+            with_context!(
+                self,
+                level,
+                &format!("verify one (of {num_queries}) query rounds"),
+                self.batch_fri_verifier_query_round::<C>(
+                    &groups,
+                    challenges,
+                    initial_merkle_caps,
+                    &oracle_lde_bits,
+                    proof,
+                    challenges.fri_query_indices[i],
+                    round_proof,
+                    params,
+                )
+            );
+        }
+    }
+
+    fn batch_fri_verifier_query_round<C: GenericConfig<D, F = F>>(
+        &mut self,
+        groups: &[BatchFriGroupTarget<D>],
+        challenges: &FriChallengesTarget<D>,
+        initial_merkle_caps: &[MerkleCapTarget],
+        oracle_lde_bits: &[usize],
+        proof: &FriProofTarget<D>,
+        x_index: Target,
+        round_proof: &FriQueryRoundTarget<D>,
+        params: &FriParams,
+    ) where
+        C::Hasher: AlgebraicHasher<F>,
+    {
+        let lde_bits = params.lde_bits();
+
+        // Note that this `low_bits` decomposition permits non-canonical binary encodings. Here we
+        // verify that this has a negligible impact on soundness error.
+        Self::assert_noncanonical_indices_ok(&params.config);
+        let mut x_index_bits = self.low_bits(x_index, lde_bits, F::BITS);
+
+        // Smaller oracles are opened at `x_index >> (lde_bits - bits)`, whose top bits, and so
+        // whose cap index, are those of `x_index`.
+        let cap_index =
+            self.le_sum(x_index_bits[x_index_bits.len() - params.config.cap_height..].iter());
+        with_context!(self, "check FRI initial proof", {
+            for (i, (((evals, merkle_proof), cap), &bits)) in round_proof
+                .initial_trees_proof
+                .evals_proofs
+                .iter()
+                .zip(initial_merkle_caps)
+                .zip(oracle_lde_bits)
+                .enumerate()
+            {
+                with_context!(
+                    self,
+                    &format!("verify {i}'th initial Merkle proof"),
+                    self.verify_merkle_proof_to_cap_with_cap_index::<C::Hasher>(
+                        evals.clone(),
+                        &x_index_bits[lde_bits - bits..],
+                        cap_index,
+                        cap,
+                        merkle_proof
+                    )
+                );
+            }
+        });
+
+        // The point of the oracles of the given size at the current index. Smaller oracles are
+        // evaluated on `g H` rather than on the coset of the folded codeword; see `batch_fri_proof`.
+        let oracle_x = |builder: &mut Self, x_index_bits: &[BoolTarget]| {
+            let g = builder.constant(F::coset_shift());
+            let phi = F::primitive_root_of_unity(x_index_bits.len());
+            let phi = builder.exp_from_bits_const_base(phi, x_index_bits.iter().rev());
+            builder.mul(g, phi)
+        };
+        let combine_group = |builder: &mut Self, round: usize, x: Target| {
+            groups
+                .iter()
+                .find(|(r, _)| *r == round)
+                .map(|(_, instances)| {
+                    builder.fri_combine_initial_instances(
+                        instances,
+                        &round_proof.initial_trees_proof,
+                        challenges.fri_alpha,
+                        x,
+                        params,
+                    )
+                })
+        };
+
+        // `subgroup_x` is `subgroup[x_index]`, i.e., the actual field element in the domain.
+        let mut subgroup_x = with_context!(
+            self,
+            "compute x from its index",
+            oracle_x(self, &x_index_bits)
+        );
+
+        // old_eval is the last derived evaluation; it will be checked for consistency with its
+        // committed "parent" value in the next iteration.
+        let mut old_eval = with_context!(
+            self,
+            "combine initial oracles",
+            combine_group(self, 0, subgroup_x).unwrap()
+        );
+
+        for (i, &arity_bits) in params.reduction_arity_bits.iter().enumerate() {
+            let evals = &round_proof.steps[i].evals;
+
+            // Split x_index into the index of the coset x is in, and the index of x within that coset.
+            let coset_index_bits = x_index_bits[arity_bits..].to_vec();
+            let x_index_within_coset_bits = &x_index_bits[..arity_bits];
+            let x_index_within_coset = self.le_sum(x_index_within_coset_bits.iter());
+
+            // Check consistency with our old evaluation from the previous round.
+            let new_eval = self.random_access_extension(x_index_within_coset, evals.clone());
+            self.connect_extension(new_eval, old_eval);
+
+            // Infer P(y) from {P(x)}_{x^arity=y}.
+            old_eval = with_context!(
+                self,
+                "infer evaluation using interpolation",
+                self.compute_evaluation(
+                    subgroup_x,
+                    x_index_within_coset_bits,
+                    arity_bits,
+                    evals,
+                    challenges.fri_betas[i],
+                )
+            );
+
+            with_context!(
+                self,
+                "verify FRI round Merkle proof.",
+                self.verify_merkle_proof_to_cap_with_cap_index::<C::Hasher>(
+                    flatten_target(evals),
+                    &coset_index_bits,
+                    cap_index,
+                    &proof.commit_phase_merkle_caps[i],
+                    &round_proof.steps[i].merkle_proof,
+                )
+            );
+
+            // Update the point x to x^arity.
+            subgroup_x = self.exp_power_of_2(subgroup_x, arity_bits);
+
+            x_index_bits = coset_index_bits;
+
+            // Fold in the smaller instances which have reached the size of the codeword.
+            if groups.iter().any(|(r, _)| *r == i + 1) {
+                let eval = with_context!(self, "combine smaller oracles", {
+                    let x = oracle_x(self, &x_index_bits);
+                    combine_group(self, i + 1, x).unwrap()
+                });
+                let beta_arity = self.exp_power_of_2_extension(challenges.fri_betas[i], arity_bits);
+                old_eval = self.mul_add_extension(beta_arity, eval, old_eval);
+            }
+        }
+
+        // Final check of FRI. After all the reductions, we check that the final polynomial is equal
+        // to the one sent by the prover.
+        let eval = with_context!(
+            self,
+            &format!(
+                "evaluate final polynomial of length {}",
+                proof.final_poly.len()
+            ),
+            proof.final_poly.eval_scalar(self, subgroup_x)
+        );
+        self.connect_extension(eval, old_eval);
+    }
+
     /// We decompose FRI query indices into bits without verifying that the decomposition given by
     /// the prover is the canonical one. In particular, if `x_index < 2^field_bits - p`, then the
     /// prover could supply the binary encoding of either `x_index` or `x_index + p`, since the are
@@ -384,6 +668,17 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         &mut self,
         num_leaves_per_oracle: &[usize],
         params: &FriParams,
+    ) -> FriProofTarget<D> {
+        let oracle_degree_bits = vec![params.degree_bits; num_leaves_per_oracle.len()];
+        self.add_virtual_batch_fri_proof(num_leaves_per_oracle, &oracle_degree_bits, params)
+    }
+
+    /// Adds a FRI proof for `verify_batch_fri_proof`, whose oracles have the given degrees.
+    pub fn add_virtual_batch_fri_proof(
+        &mut self,
+        num_leaves_per_oracle: &[usize],
+        oracle_degree_bits: &[usize],
+        params: &FriParams,
     ) -> FriProofTarget<D> {
         let cap_height = params.config.cap_height;
         let num_queries = params.config.num_query_rounds;
@@ -391,7 +686,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             .map(|_| self.add_virtual_cap(cap_height))
             .collect();
         let query_round_proofs = (0..num_queries)
-            .map(|_| self.add_virtual_fri_query(num_leaves_per_oracle, params))
+            .map(|_| self.add_virtual_fri_query(num_leaves_per_oracle, oracle_degree_bits, params))
             .collect();
         let final_poly = self.add_virtual_poly_coeff_ext(params.final_poly_len());
         let pow_witness = self.add_virtual_target();
@@ -406,14 +701,23 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     fn add_virtual_fri_query(
         &mut self,
         num_leaves_per_oracle: &[usize],
+        oracle_degree_bits: &[usize],
         params: &FriParams,
     ) -> FriQueryRoundTarget<D> {
         let cap_height = params.config.cap_height;
         assert!(params.lde_bits() >= cap_height);
         let mut merkle_proof_len = params.lde_bits() - cap_height;
 
-        let initial_trees_proof =
-            self.add_virtual_fri_initial_trees_proof(num_leaves_per_oracle, merkle_proof_len);
+        let initial_merkle_proof_lens = oracle_degree_bits
+            .iter()
+            .map(|&bits| {
+                let lde_bits = bits + params.config.rate_bits;
+                assert!(lde_bits >= cap_height);
+                lde_bits - cap_height
+            })
+            .collect::<Vec<_>>();
+        let initial_trees_proof = self
+            .add_virtual_fri_initial_trees_proof(num_leaves_per_oracle, &initial_merkle_proof_lens);
 
         let mut steps = vec![];
         for &arity_bits in &params.reduction_arity_bits {
@@ -431,11 +735,12 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     fn add_virtual_fri_initial_trees_proof(
         &mut self,
         num_leaves_per_oracle: &[usize],
-        initial_merkle_proof_len: usize,
+        initial_merkle_proof_lens: &[usize],
     ) -> FriInitialTreeProofTarget {
         let evals_proofs = num_leaves_per_oracle
             .iter()
-            .map(|&num_oracle_leaves| {
+            .zip_eq(initial_merkle_proof_lens)
+            .map(|(&num_oracle_leaves, &initial_merkle_proof_len)| {
                 let leaves = self.add_virtual_targets(num_oracle_leaves);
                 let merkle_proof = self.add_virtual_merkle_proof(initial_merkle_proof_len);
                 (leaves, merkle_proof)
@@ -456,6 +761,16 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     }
 }
 
+/// Instances of the same degree, along with the number of reductions after which they are folded
+/// into the FRI codeword.
+type BatchFriGroupTarget<'a, const D: usize> = (
+    usize,
+    Vec<(
+        &'a FriInstanceInfoTarget<D>,
+        &'a PrecomputedReducedOpeningsTarget<D>,
+    )>,
+);
+
 /// For each opening point, holds the reduced (by `alpha`) evaluations of each polynomial that's
 /// opened at that point.
 #[derive(Clone)]
@@ -0,0 +1,376 @@
+//! Estimates of the soundness of FRI-based proofs, following the ethSTARK documentation and
+//! "A summary on the FRI low degree test" (Haböck, 2022).
+//!
+//! Two estimates are given. The proven one relies on the Johnson bound for Reed-Solomon codes,
+//! i.e. on the list-decoding regime, where a cheating prover can be consistent with a list of
+//! codewords. The conjectured one relies on the ethSTARK conjecture that FRI is sound up to the
+//! capacity bound, and that each query contributes `rate_bits` bits of security.
+//!
+//! Each source of error is reported separately, in bits, and combined with a union bound.
+
+use alloc::vec::Vec;
+
+use crate::fri::FriConfig;
+
+/// The smallest Johnson parameter `m` considered; see [`SecurityReport::johnson_m`].
+const MIN_JOHNSON_M: usize = 3;
+/// The largest Johnson parameter `m` considered.
+const MAX_JOHNSON_M: usize = 128;
+/// The largest number of challenges considered by [`cheapest_config`].
+const MAX_NUM_CHALLENGES: usize = 4;
+
+/// The parameters of an IOP, other than its FRI configuration, on which its soundness depends.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SoundnessParams {
+    /// `log2` of the order of the base field, rounded down.
+    pub field_bits: usize,
+    /// The degree of the extension field from which FRI and out-of-domain challenges are drawn.
+    pub extension_degree: usize,
+    /// `log2` of the degree of the polynomials committed to, i.e. of FRI's degree bound.
+    pub degree_bits: usize,
+    /// The number of polynomials opened with a single batched FRI proof.
+    pub num_polys: usize,
+    /// The maximum degree of the constraints, relative to the degree of the committed polynomials.
+    pub constraint_degree: usize,
+    /// The number of constraints combined into each quotient polynomial.
+    pub num_constraints: usize,
+    /// The number of independent repetitions of the challenges drawn from the base field, i.e. of
+    /// the constraint combination and of the permutation and lookup arguments.
+    pub num_challenges: usize,
+    /// The number of columns in the permutation argument, or 0 if there is none.
+    pub permutation_width: usize,
+    /// The number of lookups and table entries per row in the lookup argument, or 0 if there is
+    /// none.
+    pub lookup_width: usize,
+}
+
+impl SoundnessParams {
+    fn extension_field_bits(&self) -> f64 {
+        (self.field_bits * self.extension_degree) as f64
+    }
+}
+
+/// The soundness of each part of the protocol, in bits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecurityBits {
+    /// FRI's query phase, including the proof of work.
+    pub fri_queries: f64,
+    /// FRI's commit phase, i.e. batching and folding.
+    pub fri_commit: f64,
+    /// The out-of-domain (DEEP) openings.
+    pub deep: f64,
+    /// The random linear combination of the constraints.
+    pub constraints: f64,
+    /// The permutation argument, if any.
+    pub permutation: Option<f64>,
+    /// The lookup argument, if any.
+    pub lookup: Option<f64>,
+}
+
+impl SecurityBits {
+    /// The overall soundness, from a union bound over all sources of error.
+    pub fn total(&self) -> f64 {
+        let error: f64 = [
+            Some(self.fri_queries),
+            Some(self.fri_commit),
+            Some(self.deep),
+            Some(self.constraints),
+            self.permutation,
+            self.lookup,
+        ]
+        .into_iter()
+        .flatten()
+        .map(|bits| (-bits).exp2())
+        .sum();
+        -error.log2()
+    }
+}
+
+/// Proven and conjectured soundness of a FRI configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecurityReport {
+    pub proven: SecurityBits,
+    pub conjectured: SecurityBits,
+    /// The Johnson parameter `m` used for the proven bound. The proximity parameter is
+    /// `(1 + 1/2m) sqrt(rate)`, and `m` is chosen to maximize the proven soundness.
+    pub johnson_m: usize,
+}
+
+/// Computes the soundness of a proof with FRI configuration `config` and parameters `params`.
+pub fn security_report(config: &FriConfig, params: &SoundnessParams) -> SecurityReport {
+    let (johnson_m, proven) = (MIN_JOHNSON_M..=MAX_JOHNSON_M)
+        .map(|m| (m, proven_security(config, params, m)))
+        .max_by(|(_, a), (_, b)| a.total().total_cmp(&b.total()))
+        .unwrap();
+    SecurityReport {
+        proven,
+        conjectured: conjectured_security(config, params),
+        johnson_m,
+    }
+}
+
+fn conjectured_security(config: &FriConfig, params: &SoundnessParams) -> SecurityBits {
+    let rate_bits = config.rate_bits as f64;
+    let lde_bits = (params.degree_bits + config.rate_bits) as f64;
+    let fri_queries = config.num_query_rounds as f64 * rate_bits + config.proof_of_work_bits as f64;
+    // Batching and folding each fail with probability about `|D| / |K|`, where `D` is the LDE
+    // domain and `K` the extension field.
+    let fri_commit =
+        params.extension_field_bits() - lde_bits - (params.num_polys.max(1) as f64).log2();
+    non_fri_security(params, fri_queries, fri_commit, 1.0)
+}
+
+fn proven_security(config: &FriConfig, params: &SoundnessParams, m: usize) -> SecurityBits {
+    let m = m as f64;
+    let rate = config.rate();
+    let lde_size = (1u64 << (params.degree_bits + config.rate_bits)) as f64;
+    let extension_field_bits = params.extension_field_bits();
+
+    // Each query passes with probability at most `alpha`, the proximity parameter.
+    let alpha = (1.0 + 0.5 / m) * rate.sqrt();
+    let fri_queries =
+        -(config.num_query_rounds as f64) * alpha.log2() + config.proof_of_work_bits as f64;
+
+    // Batching with powers of a single challenge multiplies the proximity gap error by the
+    // number of polynomials, and each folding step contributes an error linear in its arity.
+    let arities: f64 = config
+        .fri_params(params.degree_bits, false)
+        .reduction_arity_bits
+        .iter()
+        .map(|&bits| (1u64 << bits) as f64)
+        .sum();
+    let batching_error = params.num_polys.max(1) as f64 * (m + 0.5).powi(7)
+        / (3.0 * rate.powf(1.5))
+        * lde_size.powi(2);
+    let folding_error = (2.0 * m + 1.0) * (lde_size + 1.0) / rate.sqrt() * arities;
+    let fri_commit = extension_field_bits - (batching_error + folding_error).log2();
+
+    // A cheating prover may be consistent with any codeword in the list around its oracles.
+    let list_size = (m + 0.5) / rate.sqrt();
+    non_fri_security(params, fri_queries, fri_commit, list_size)
+}
+
+/// Fills in the errors of the IOP itself, given the FRI errors and the size of the list of
+/// codewords a cheating prover may choose from.
+fn non_fri_security(
+    params: &SoundnessParams,
+    fri_queries: f64,
+    fri_commit: f64,
+    list_size: f64,
+) -> SecurityBits {
+    let field_bits = params.field_bits as f64;
+    let degree = (1u64 << params.degree_bits) as f64;
+    let num_challenges = params.num_challenges as f64;
+
+    // The quotient has degree about `constraint_degree * n`, and is opened at two points.
+    let deep_degree = params.constraint_degree as f64 * (degree + 1.0) + degree - 1.0;
+    let deep = params.extension_field_bits() - (list_size.powi(2) * deep_degree).log2();
+    let repeated = |error: f64| num_challenges * (field_bits - (list_size * error).log2());
+    let constraints = repeated(params.num_constraints.max(1) as f64);
+    let argument = |width: usize| (width > 0).then(|| repeated(width as f64 * degree));
+
+    SecurityBits {
+        fri_queries: fri_queries.max(0.0),
+        fri_commit: fri_commit.max(0.0),
+        deep: deep.max(0.0),
+        constraints: constraints.max(0.0),
+        permutation: argument(params.permutation_width).map(|bits| bits.max(0.0)),
+        lookup: argument(params.lookup_width).map(|bits| bits.max(0.0)),
+    }
+}
+
+/// A configuration suggested by [`cheapest_config`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SuggestedConfig {
+    pub fri_config: FriConfig,
+    pub num_challenges: usize,
+}
+
+/// Finds the cheapest variant of `config` and `params.num_challenges` reaching `target_bits` bits
+/// of security, proven or conjectured, with a rate of at least `2^-max_rate_bits` and at most
+/// `max_proof_of_work_bits` bits of proof of work. Returns `None` if no such variant exists, e.g.
+/// because the field is too small.
+///
+/// The fewest challenges that can reach the target are used, since each repetition adds
+/// polynomials to every proof. The cost of a FRI configuration is then the number of hashes in
+/// the Merkle proofs of its queries into the initial oracles, which dominates both proof size and
+/// recursive verification. Ties are broken in favour of cheaper proving, i.e. lower rates and less
+/// proof of work. The cap height and reduction strategy of `config` are kept.
+pub fn cheapest_config(
+    config: &FriConfig,
+    params: &SoundnessParams,
+    target_bits: f64,
+    proven: bool,
+    max_rate_bits: usize,
+    max_proof_of_work_bits: u32,
+) -> Option<SuggestedConfig> {
+    (1..=MAX_NUM_CHALLENGES).find_map(|num_challenges| {
+        let params = SoundnessParams {
+            num_challenges,
+            ..params.clone()
+        };
+        let mut candidates = Vec::new();
+        for rate_bits in 1..=max_rate_bits {
+            for proof_of_work_bits in 0..=max_proof_of_work_bits {
+                let candidate = FriConfig {
+                    rate_bits,
+                    proof_of_work_bits,
+                    num_query_rounds: 0,
+                    ..config.clone()
+                };
+                if let Some(num_query_rounds) =
+                    min_query_rounds(&candidate, &params, target_bits, proven)
+                {
+                    candidates.push(FriConfig {
+                        num_query_rounds,
+                        ..candidate
+                    });
+                }
+            }
+        }
+        let cost = |c: &FriConfig| {
+            let path_length = (params.degree_bits + c.rate_bits).saturating_sub(c.cap_height);
+            (
+                c.num_query_rounds * path_length,
+                c.rate_bits,
+                c.proof_of_work_bits,
+            )
+        };
+        candidates
+            .into_iter()
+            .min_by_key(cost)
+            .map(|fri_config| SuggestedConfig {
+                fri_config,
+                num_challenges,
+            })
+    })
+}
+
+/// The minimum number of query rounds for `config` to reach `target_bits` bits of security.
+fn min_query_rounds(
+    config: &FriConfig,
+    params: &SoundnessParams,
+    target_bits: f64,
+    proven: bool,
+) -> Option<usize> {
+    // The other errors don't depend on the number of queries, so we can solve for the largest
+    // query error which keeps the total error below the target.
+    let solve = |security: SecurityBits, bits_per_query: f64| {
+        let other_errors = SecurityBits {
+            fri_queries: f64::INFINITY,
+            ..security
+        };
+        let budget = (-target_bits).exp2() - (-other_errors.total()).exp2();
+        if budget <= 0.0 {
+            return None;
+        }
+        let query_bits = -budget.log2() - config.proof_of_work_bits as f64;
+        Some((query_bits.max(0.0) / bits_per_query).ceil() as usize)
+    };
+    if proven {
+        (MIN_JOHNSON_M..=MAX_JOHNSON_M)
+            .filter_map(|m| {
+                let m_f64 = m as f64;
+                let alpha = (1.0 + 0.5 / m_f64) * config.rate().sqrt();
+                solve(proven_security(config, params, m), -alpha.log2())
+            })
+            .min()
+    } else {
+        solve(
+            conjectured_security(config, params),
+            config.rate_bits as f64,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fri::reduction_strategies::FriReductionStrategy;
+
+    fn params() -> SoundnessParams {
+        SoundnessParams {
+            field_bits: 63,
+            extension_degree: 2,
+            degree_bits: 12,
+            num_polys: 300,
+            constraint_degree: 8,
+            num_constraints: 123,
+            num_challenges: 2,
+            permutation_width: 80,
+            lookup_width: 0,
+        }
+    }
+
+    fn config() -> FriConfig {
+        FriConfig {
+            rate_bits: 3,
+            cap_height: 4,
+            proof_of_work_bits: 16,
+            reduction_strategy: FriReductionStrategy::ConstantArityBits(4, 5),
+            num_query_rounds: 28,
+        }
+    }
+
+    #[test]
+    fn test_standard_recursion_config() {
+        let report = security_report(&config(), &params());
+        // 28 queries at rate 1/8 with 16 bits of proof of work.
+        assert_eq!(report.conjectured.fri_queries, 100.0);
+        // The Johnson bound only gives about `rate_bits / 2` bits per query.
+        assert!(report.proven.fri_queries < 60.0);
+        assert!(report.proven.total() < report.conjectured.total());
+        assert!(report.proven.lookup.is_none());
+
+        // The union bound loses at most `log2(5)` bits over the weakest part.
+        let conjectured = &report.conjectured;
+        let weakest = [
+            conjectured.fri_queries,
+            conjectured.fri_commit,
+            conjectured.deep,
+            conjectured.constraints,
+            conjectured.permutation.unwrap(),
+        ]
+        .into_iter()
+        .fold(f64::INFINITY, f64::min);
+        assert!(conjectured.total() <= weakest);
+        assert!(conjectured.total() >= weakest - 5f64.log2());
+    }
+
+    #[test]
+    fn test_cheapest_config() {
+        // The Johnson bound gives much less than the conjectured security over this field.
+        for (proven, target_bits) in [(false, 100.0), (true, 60.0)] {
+            let bits = |config: &FriConfig, num_challenges| {
+                let params = SoundnessParams {
+                    num_challenges,
+                    ..params()
+                };
+                let report = security_report(config, &params);
+                if proven {
+                    report.proven.total()
+                } else {
+                    report.conjectured.total()
+                }
+            };
+
+            let SuggestedConfig {
+                fri_config,
+                num_challenges,
+            } = cheapest_config(&config(), &params(), target_bits, proven, 4, 20).unwrap();
+            assert!(bits(&fri_config, num_challenges) >= target_bits);
+            if !proven {
+                // The permutation argument needs a third repetition to reach 100 bits.
+                assert_eq!(num_challenges, 3);
+            }
+
+            // One query fewer falls short of the target.
+            let weaker = FriConfig {
+                num_query_rounds: fri_config.num_query_rounds - 1,
+                ..fri_config
+            };
+            assert!(bits(&weaker, num_challenges) < target_bits);
+        }
+        assert!(cheapest_config(&config(), &params(), 100.0, true, 4, 20).is_none());
+    }
+}
@@ -1,3 +1,5 @@
+use alloc::vec;
+
 use anyhow::ensure;
 
 use crate::field::extension::Extendable;
@@ -17,6 +19,29 @@ where
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
 {
+    let oracle_lde_bits = vec![params.lde_bits(); instance.oracles.len()];
+    validate_batch_fri_proof_shape::<F, C, D>(
+        proof,
+        core::slice::from_ref(instance),
+        &oracle_lde_bits,
+        params,
+    )
+}
+
+/// Validates the shape of a batched FRI proof, whose oracles may be smaller than the LDE domain.
+pub(crate) fn validate_batch_fri_proof_shape<F, C, const D: usize>(
+    proof: &FriProof<F, C::Hasher, D>,
+    instances: &[FriInstanceInfo<F, D>],
+    oracle_lde_bits: &[usize],
+    params: &FriParams,
+) -> anyhow::Result<()>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    let oracles = &instances[0].oracles;
+    ensure!(oracles.len() == oracle_lde_bits.len());
+
     let FriProof {
         commit_phase_merkle_caps,
         query_round_proofs,
@@ -35,14 +60,15 @@ where
             steps,
         } = query_round;
 
-        ensure!(initial_trees_proof.evals_proofs.len() == instance.oracles.len());
-        for ((leaf, merkle_proof), oracle) in initial_trees_proof
+        ensure!(initial_trees_proof.evals_proofs.len() == oracles.len());
+        for (((leaf, merkle_proof), oracle), &lde_bits) in initial_trees_proof
             .evals_proofs
             .iter()
-            .zip(&instance.oracles)
+            .zip(oracles)
+            .zip(oracle_lde_bits)
         {
             ensure!(leaf.len() == oracle.num_polys + salt_size(oracle.blinding && params.hiding));
-            ensure!(merkle_proof.len() + cap_height == params.lde_bits());
+            ensure!(merkle_proof.len() + cap_height == lde_bits);
         }
 
         ensure!(steps.len() == params.reduction_arity_bits.len());
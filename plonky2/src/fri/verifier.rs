@@ -1,13 +1,14 @@
+use alloc::vec;
 use alloc::vec::Vec;
 
-use anyhow::{ensure, Result};
+use anyhow::{anyhow, ensure, Result};
 
 use crate::field::extension::{flatten, Extendable, FieldExtension};
 use crate::field::interpolation::{barycentric_weights, interpolate};
 use crate::field::types::Field;
 use crate::fri::proof::{FriChallenges, FriInitialTreeProof, FriProof, FriQueryRound};
-use crate::fri::structure::{FriBatchInfo, FriInstanceInfo, FriOpenings};
-use crate::fri::validate_shape::validate_fri_proof_shape;
+use crate::fri::structure::{FriBatchInfo, FriInstanceInfo, FriOpenings, FriPolynomialInfo};
+use crate::fri::validate_shape::{validate_batch_fri_proof_shape, validate_fri_proof_shape};
 use crate::fri::{FriConfig, FriParams};
 use crate::hash::hash_types::RichField;
 use crate::hash::merkle_proofs::verify_merkle_proof_to_cap;
@@ -130,31 +131,55 @@ pub(crate) fn fri_combine_initial<
     subgroup_x: F,
     precomputed_reduced_evals: &PrecomputedReducedOpenings<F, D>,
     params: &FriParams,
+) -> F::Extension {
+    fri_combine_initial_instances::<F, C, D>(
+        &[(instance, precomputed_reduced_evals)],
+        proof,
+        alpha,
+        subgroup_x,
+        params,
+    )
+}
+
+/// Combines the initial openings of several instances of the same degree, in the same way as the
+/// prover combines their polynomials.
+fn fri_combine_initial_instances<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    instances: &[(&FriInstanceInfo<F, D>, &PrecomputedReducedOpenings<F, D>)],
+    proof: &FriInitialTreeProof<F, C::Hasher>,
+    alpha: F::Extension,
+    subgroup_x: F,
+    params: &FriParams,
 ) -> F::Extension {
     assert!(D > 1, "Not implemented for D=1.");
     let subgroup_x = F::Extension::from_basefield(subgroup_x);
     let mut alpha = ReducingFactor::new(alpha);
     let mut sum = F::Extension::ZERO;
 
-    for (batch, reduced_openings) in instance
-        .batches
-        .iter()
-        .zip(&precomputed_reduced_evals.reduced_openings_at_point)
-    {
-        let FriBatchInfo { point, polynomials } = batch;
-        let evals = polynomials
+    for (instance, precomputed_reduced_evals) in instances {
+        for (batch, reduced_openings) in instance
+            .batches
             .iter()
-            .map(|p| {
-                let poly_blinding = instance.oracles[p.oracle_index].blinding;
-                let salted = params.hiding && poly_blinding;
-                proof.unsalted_eval(p.oracle_index, p.polynomial_index, salted)
-            })
-            .map(F::Extension::from_basefield);
-        let reduced_evals = alpha.reduce(evals);
-        let numerator = reduced_evals - *reduced_openings;
-        let denominator = subgroup_x - *point;
-        sum = alpha.shift(sum);
-        sum += numerator / denominator;
+            .zip(&precomputed_reduced_evals.reduced_openings_at_point)
+        {
+            let FriBatchInfo { point, polynomials } = batch;
+            let evals = polynomials
+                .iter()
+                .map(|p| {
+                    let poly_blinding = instance.oracles[p.oracle_index].blinding;
+                    let salted = params.hiding && poly_blinding;
+                    proof.unsalted_eval(p.oracle_index, p.polynomial_index, salted)
+                })
+                .map(F::Extension::from_basefield);
+            let reduced_evals = alpha.reduce(evals);
+            let numerator = reduced_evals - *reduced_openings;
+            let denominator = subgroup_x - *point;
+            sum = alpha.shift(sum);
+            sum += numerator / denominator;
+        }
     }
 
     sum
@@ -239,6 +264,263 @@ fn fri_verifier_query_round<
     Ok(())
 }
 
+/// Verifies a FRI proof produced by `PolynomialBatch::batch_prove_openings`.
+///
+/// `degree_bits` holds the degree of each instance, sorted in non-increasing order, and `params`
+/// are the FRI parameters of the largest degree. All instances index into the same list of
+/// oracles, which their `oracles` fields must describe.
+pub fn verify_batch_fri_proof<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    degree_bits: &[usize],
+    instances: &[FriInstanceInfo<F, D>],
+    openings: &[FriOpenings<F, D>],
+    challenges: &FriChallenges<F, D>,
+    initial_merkle_caps: &[MerkleCap<F, C::Hasher>],
+    proof: &FriProof<F, C::Hasher, D>,
+    params: &FriParams,
+) -> Result<()> {
+    ensure!(
+        !instances.is_empty()
+            && instances.len() == degree_bits.len()
+            && instances.len() == openings.len(),
+        "Number of instances, degrees and openings do not match."
+    );
+    let groups = batch_fri_groups(degree_bits, params)?;
+    let oracle_lde_bits = batch_fri_oracle_lde_bits(
+        degree_bits,
+        instances
+            .iter()
+            .map(|instance| instance.batches.iter().flat_map(|b| &b.polynomials)),
+        initial_merkle_caps.len(),
+        params,
+    )?;
+
+    validate_batch_fri_proof_shape::<F, C, D>(proof, instances, &oracle_lde_bits, params)?;
+
+    // Check PoW.
+    fri_verify_proof_of_work(challenges.fri_pow_response, &params.config)?;
+
+    // Check that parameters are coherent.
+    ensure!(
+        params.config.num_query_rounds == proof.query_round_proofs.len(),
+        "Number of query rounds does not match config."
+    );
+
+    let precomputed_reduced_evals = openings
+        .iter()
+        .map(|os| PrecomputedReducedOpenings::from_os_and_alpha(os, challenges.fri_alpha))
+        .collect::<Vec<_>>();
+    let groups = groups
+        .iter()
+        .map(|(round, group)| {
+            let instances = group
+                .iter()
+                .map(|&i| (&instances[i], &precomputed_reduced_evals[i]))
+                .collect::<Vec<_>>();
+            (*round, instances)
+        })
+        .collect::<Vec<_>>();
+    for (&x_index, round_proof) in challenges
+        .fri_query_indices
+        .iter()
+        .zip(&proof.query_round_proofs)
+    {
+        batch_fri_verifier_query_round::<F, C, D>(
+            &groups,
+            challenges,
+            initial_merkle_caps,
+            &oracle_lde_bits,
+            proof,
+            x_index,
+            round_proof,
+            params,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// The LDE size of each oracle of a batched FRI proof, taken from the degree of the instances which
+/// open it. `instance_polynomials` holds the polynomials opened by each instance.
+pub(crate) fn batch_fri_oracle_lde_bits<'a>(
+    degree_bits: &[usize],
+    instance_polynomials: impl IntoIterator<Item = impl IntoIterator<Item = &'a FriPolynomialInfo>>,
+    num_oracles: usize,
+    params: &FriParams,
+) -> Result<Vec<usize>> {
+    let mut oracle_degree_bits = vec![None; num_oracles];
+    for (polynomials, &instance_degree_bits) in instance_polynomials.into_iter().zip(degree_bits) {
+        for p in polynomials {
+            let oracle_degree_bits = oracle_degree_bits
+                .get_mut(p.oracle_index)
+                .ok_or_else(|| anyhow!("Oracle index out of range."))?;
+            ensure!(
+                *oracle_degree_bits.get_or_insert(instance_degree_bits) == instance_degree_bits,
+                "Oracle opened by instances of different degrees."
+            );
+        }
+    }
+    oracle_degree_bits
+        .into_iter()
+        .map(|d| {
+            d.map(|d| d + params.config.rate_bits)
+                .ok_or_else(|| anyhow!("Oracle not opened by any instance."))
+        })
+        .collect()
+}
+
+/// For each distinct degree in `degree_bits`, the number of reductions after which it is folded
+/// in, and the indices of the instances of that degree.
+pub(crate) fn batch_fri_groups(
+    degree_bits: &[usize],
+    params: &FriParams,
+) -> Result<Vec<(usize, Vec<usize>)>> {
+    ensure!(
+        degree_bits[0] == params.degree_bits && degree_bits.windows(2).all(|w| w[0] >= w[1]),
+        "Instance degrees must be non-increasing, starting at the degree of the FRI parameters."
+    );
+    let mut groups: Vec<(usize, Vec<usize>)> = Vec::new();
+    for (i, &instance_degree_bits) in degree_bits.iter().enumerate() {
+        match groups.last_mut() {
+            Some((_, group)) if degree_bits[group[0]] == instance_degree_bits => group.push(i),
+            _ => {
+                let round = if instance_degree_bits == params.degree_bits {
+                    Some(0)
+                } else {
+                    params
+                        .reduction_arity_bits
+                        .iter()
+                        .scan(params.degree_bits, |bits, &arity_bits| {
+                            *bits -= arity_bits;
+                            Some(*bits)
+                        })
+                        .position(|bits| bits == instance_degree_bits)
+                        .map(|i| i + 1)
+                }
+                .ok_or_else(|| anyhow!("Instance degree not reached by the FRI reductions."))?;
+                groups.push((round, vec![i]));
+            }
+        }
+    }
+    Ok(groups)
+}
+
+/// Instances of the same degree, along with the number of reductions after which they are folded
+/// into the FRI codeword.
+type BatchFriGroup<'a, F, const D: usize> = (
+    usize,
+    Vec<(
+        &'a FriInstanceInfo<F, D>,
+        &'a PrecomputedReducedOpenings<F, D>,
+    )>,
+);
+
+fn batch_fri_verifier_query_round<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    groups: &[BatchFriGroup<F, D>],
+    challenges: &FriChallenges<F, D>,
+    initial_merkle_caps: &[MerkleCap<F, C::Hasher>],
+    oracle_lde_bits: &[usize],
+    proof: &FriProof<F, C::Hasher, D>,
+    mut x_index: usize,
+    round_proof: &FriQueryRound<F, C::Hasher, D>,
+    params: &FriParams,
+) -> Result<()> {
+    let lde_bits = params.lde_bits();
+    for (((evals, merkle_proof), cap), &bits) in round_proof
+        .initial_trees_proof
+        .evals_proofs
+        .iter()
+        .zip(initial_merkle_caps)
+        .zip(oracle_lde_bits)
+    {
+        verify_merkle_proof_to_cap::<F, C::Hasher>(
+            evals.clone(),
+            x_index >> (lde_bits - bits),
+            cap,
+            merkle_proof,
+        )?;
+    }
+
+    // The point of the oracles of the given size at the current index. Smaller oracles are
+    // evaluated on `g H` rather than on the coset of the folded codeword; see `batch_fri_proof`.
+    let oracle_x = |x_index: usize, log_n: usize| {
+        F::MULTIPLICATIVE_GROUP_GENERATOR
+            * F::primitive_root_of_unity(log_n).exp_u64(reverse_bits(x_index, log_n) as u64)
+    };
+    let combine_group = |round: usize, x_index: usize, log_n: usize| {
+        groups
+            .iter()
+            .find(|(r, _)| *r == round)
+            .map(|(_, instances)| {
+                fri_combine_initial_instances::<F, C, D>(
+                    instances,
+                    &round_proof.initial_trees_proof,
+                    challenges.fri_alpha,
+                    oracle_x(x_index, log_n),
+                    params,
+                )
+            })
+    };
+
+    let mut log_n = lde_bits;
+    let mut subgroup_x = oracle_x(x_index, log_n);
+    let mut old_eval = combine_group(0, x_index, log_n).unwrap();
+
+    for (i, &arity_bits) in params.reduction_arity_bits.iter().enumerate() {
+        let arity = 1 << arity_bits;
+        let evals = &round_proof.steps[i].evals;
+
+        // Split x_index into the index of the coset x is in, and the index of x within that coset.
+        let coset_index = x_index >> arity_bits;
+        let x_index_within_coset = x_index & (arity - 1);
+
+        // Check consistency with our old evaluation from the previous round.
+        ensure!(evals[x_index_within_coset] == old_eval);
+
+        // Infer P(y) from {P(x)}_{x^arity=y}.
+        old_eval = compute_evaluation(
+            subgroup_x,
+            x_index_within_coset,
+            arity_bits,
+            evals,
+            challenges.fri_betas[i],
+        );
+
+        verify_merkle_proof_to_cap::<F, C::Hasher>(
+            flatten(evals),
+            coset_index,
+            &proof.commit_phase_merkle_caps[i],
+            &round_proof.steps[i].merkle_proof,
+        )?;
+
+        // Update the point x to x^arity.
+        subgroup_x = subgroup_x.exp_power_of_2(arity_bits);
+        x_index = coset_index;
+        log_n -= arity_bits;
+
+        // Fold in the smaller instances which have reached the size of the codeword.
+        if let Some(eval) = combine_group(i + 1, x_index, log_n) {
+            old_eval += challenges.fri_betas[i].exp_u64(arity as u64) * eval;
+        }
+    }
+
+    // Final check of FRI. After all the reductions, we check that the final polynomial is equal
+    // to the one sent by the prover.
+    ensure!(
+        proof.final_poly.eval(subgroup_x.into()) == old_eval,
+        "Final polynomial evaluation is invalid."
+    );
+
+    Ok(())
+}
+
 /// For each opening point, holds the reduced (by `alpha`) evaluations of each polynomial that's
 /// opened at that point.
 #[derive(Clone, Debug)]
@@ -13,6 +13,7 @@ use crate::iop::target::Target;
 use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
 use crate::plonk::circuit_builder::CircuitBuilder;
 use crate::util::bits_u64;
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
 
 impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     pub fn arithmetic_extension(
@@ -494,7 +495,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
 }
 
 #[derive(Debug)]
-struct QuotientGeneratorExtension<const D: usize> {
+pub struct QuotientGeneratorExtension<const D: usize> {
     numerator: ExtensionTarget<D>,
     denominator: ExtensionTarget<D>,
     quotient: ExtensionTarget<D>,
@@ -515,6 +516,23 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F>
         let quotient = num / dem;
         out_buffer.set_extension_target(self.quotient, quotient)
     }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_target_ext(self.numerator)?;
+        dst.write_target_ext(self.denominator)?;
+        dst.write_target_ext(self.quotient)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let numerator = src.read_target_ext()?;
+        let denominator = src.read_target_ext()?;
+        let quotient = src.read_target_ext()?;
+        Ok(Self {
+            numerator,
+            denominator,
+            quotient,
+        })
+    }
 }
 
 /// An iterator over the powers of a certain base element `b`: `b^0, b^1, b^2, ...`.
@@ -0,0 +1,339 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use itertools::Itertools;
+
+use crate::field::extension::Extendable;
+use crate::gates::keccak_chi::KeccakChiGate;
+use crate::gates::keccak_column_parity::KeccakColumnParityGate;
+use crate::gates::keccak_theta::KeccakThetaGate;
+use crate::hash::hash_types::RichField;
+use crate::iop::target::{BoolTarget, Target};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::util::ceil_div_usize;
+
+/// The number of bits in the Keccak-f[1600] state.
+pub const KECCAK_WIDTH_BITS: usize = 1600;
+
+/// The number of bits absorbed per permutation by Keccak-256.
+pub const KECCAK256_RATE_BITS: usize = 1088;
+
+const KECCAK_ROUNDS: usize = 24;
+
+/// The rotation offsets of the rho step, indexed by `x + 5 * y`.
+const RHO_OFFSETS: [usize; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// The round constants of the iota step.
+const ROUND_CONSTANTS: [u64; KECCAK_ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808A,
+    0x8000000080008000,
+    0x000000000000808B,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008A,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000A,
+    0x000000008000808B,
+    0x800000000000008B,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800A,
+    0x800000008000000A,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// The index of bit `z` of lane `(x, y)` in a Keccak state. Lanes are stored in the order in which
+/// they are absorbed, and bits in little-endian order, so that the state is the concatenation of
+/// the little-endian bits of its bytes.
+fn bit_index(x: usize, y: usize, z: usize) -> usize {
+    64 * (x + 5 * y) + z
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Applies the Keccak-f[1600] permutation to a state given as bits, where bit `z` of lane
+    /// `(x, y)` is at index `64 * (x + 5 * y) + z`. The state bits are assumed to be boolean.
+    ///
+    /// Theta uses `KeccakColumnParityGate` and `KeccakThetaGate`, and chi uses `KeccakChiGate`.
+    /// Rho and pi only permute bits, so they are free, and iota negates the few bits set in the
+    /// round constant.
+    pub fn keccak_f(&mut self, state: &[BoolTarget]) -> Vec<BoolTarget> {
+        assert_eq!(state.len(), KECCAK_WIDTH_BITS);
+        let mut state = state.to_vec();
+        for round_constant in ROUND_CONSTANTS {
+            state = self.keccak_round(&state, round_constant);
+        }
+        state
+    }
+
+    fn keccak_round(&mut self, a: &[BoolTarget], round_constant: u64) -> Vec<BoolTarget> {
+        // Theta.
+        let c = (0..5)
+            .flat_map(|x| (0..64).map(move |z| (x, z)))
+            .map(|(x, z)| {
+                let column = (0..5).map(|y| a[bit_index(x, y, z)]).collect_vec();
+                self.keccak_column_parity(&column)
+            })
+            .collect_vec();
+        let parity = |x: usize, z: usize| c[64 * (x % 5) + z % 64];
+        let mut a_theta = a.to_vec();
+        for x in 0..5 {
+            for z in 0..64 {
+                let column = (0..5).map(|y| a[bit_index(x, y, z)]).collect_vec();
+                let outputs = self.keccak_theta(&column, parity(x + 4, z), parity(x + 1, z + 63));
+                for (y, output) in outputs.into_iter().enumerate() {
+                    a_theta[bit_index(x, y, z)] = output;
+                }
+            }
+        }
+
+        // Rho and pi: B[y, 2x + 3y] = ROT(A[x, y], r[x, y]).
+        let mut b = a_theta.clone();
+        for x in 0..5 {
+            for y in 0..5 {
+                let offset = RHO_OFFSETS[x + 5 * y];
+                for z in 0..64 {
+                    b[bit_index(y, (2 * x + 3 * y) % 5, (z + offset) % 64)] =
+                        a_theta[bit_index(x, y, z)];
+                }
+            }
+        }
+
+        // Chi.
+        let mut a_chi = b.clone();
+        for y in 0..5 {
+            for z in 0..64 {
+                let row = (0..5).map(|x| b[bit_index(x, y, z)]).collect_vec();
+                let outputs = self.keccak_chi(&row);
+                for (x, output) in outputs.into_iter().enumerate() {
+                    a_chi[bit_index(x, y, z)] = output;
+                }
+            }
+        }
+
+        // Iota.
+        for z in 0..64 {
+            if (round_constant >> z) & 1 == 1 {
+                a_chi[bit_index(0, 0, z)] = self.not(a_chi[bit_index(0, 0, z)]);
+            }
+        }
+
+        a_chi
+    }
+
+    fn keccak_column_parity(&mut self, column: &[BoolTarget]) -> BoolTarget {
+        let gate = KeccakColumnParityGate::new_from_config(&self.config);
+        let (row, i) = self.find_slot(gate, &[], &[]);
+        for (j, bit) in column.iter().enumerate() {
+            self.connect(
+                bit.target,
+                Target::wire(row, KeccakColumnParityGate::wire_ith_input_jth_bit(i, j)),
+            );
+        }
+        // `new_unsafe` is safe here because the gate forces the parity to be in `{0, 1}`.
+        BoolTarget::new_unsafe(Target::wire(
+            row,
+            KeccakColumnParityGate::wire_ith_output(i),
+        ))
+    }
+
+    fn keccak_theta(
+        &mut self,
+        column: &[BoolTarget],
+        left_parity: BoolTarget,
+        right_parity: BoolTarget,
+    ) -> Vec<BoolTarget> {
+        let gate = KeccakThetaGate::new_from_config(&self.config);
+        let (row, i) = self.find_slot(gate, &[], &[]);
+        for (j, bit) in column.iter().enumerate() {
+            self.connect(
+                bit.target,
+                Target::wire(row, KeccakThetaGate::wire_ith_input_jth_bit(i, j)),
+            );
+        }
+        self.connect(
+            left_parity.target,
+            Target::wire(row, KeccakThetaGate::wire_ith_left_parity(i)),
+        );
+        self.connect(
+            right_parity.target,
+            Target::wire(row, KeccakThetaGate::wire_ith_right_parity(i)),
+        );
+        // The XOR of bits is a bit.
+        (0..5)
+            .map(|j| {
+                BoolTarget::new_unsafe(Target::wire(
+                    row,
+                    KeccakThetaGate::wire_ith_output_jth_bit(i, j),
+                ))
+            })
+            .collect()
+    }
+
+    fn keccak_chi(&mut self, row_bits: &[BoolTarget]) -> Vec<BoolTarget> {
+        let gate = KeccakChiGate::new_from_config(&self.config);
+        let (row, i) = self.find_slot(gate, &[], &[]);
+        for (j, bit) in row_bits.iter().enumerate() {
+            self.connect(
+                bit.target,
+                Target::wire(row, KeccakChiGate::wire_ith_input_jth_bit(i, j)),
+            );
+        }
+        // The outputs are bits, since the inputs are.
+        (0..5)
+            .map(|j| {
+                BoolTarget::new_unsafe(Target::wire(
+                    row,
+                    KeccakChiGate::wire_ith_output_jth_bit(i, j),
+                ))
+            })
+            .collect()
+    }
+
+    /// `a ^ b`, without any gates if either bit is constant.
+    fn xor_bits(&mut self, a: BoolTarget, b: BoolTarget) -> BoolTarget {
+        match (
+            self.target_as_constant(a.target),
+            self.target_as_constant(b.target),
+        ) {
+            (Some(a), _) if a.is_zero() => b,
+            (Some(_), _) => self.not(b),
+            (_, Some(b)) if b.is_zero() => a,
+            (_, Some(_)) => self.not(a),
+            (None, None) => {
+                let sum = self.add(a.target, b.target);
+                let two = F::TWO;
+                BoolTarget::new_unsafe(self.arithmetic(-two, F::ONE, a.target, b.target, sum))
+            }
+        }
+    }
+
+    /// Computes the Keccak-256 digest, as used by Ethereum, of `message` given as bits, where bit
+    /// `j` of byte `k` is at index `8 * k + j`. Returns the digest in the same order.
+    ///
+    /// The message bits are assumed to be boolean, and their number is fixed when building the
+    /// circuit.
+    pub fn keccak256_bits(&mut self, message: &[BoolTarget]) -> Vec<BoolTarget> {
+        // pad10*1: a one, then zeros, then a one at the end of the last block.
+        let padded_len =
+            ceil_div_usize(message.len() + 2, KECCAK256_RATE_BITS) * KECCAK256_RATE_BITS;
+        let mut padded = message.to_vec();
+        padded.push(self._true());
+        padded.resize(padded_len - 1, self._false());
+        padded.push(self._true());
+
+        let mut state = vec![self._false(); KECCAK_WIDTH_BITS];
+        for block in padded.chunks(KECCAK256_RATE_BITS) {
+            for (s, &m) in state.iter_mut().zip(block) {
+                *s = self.xor_bits(*s, m);
+            }
+            state = self.keccak_f(&state);
+        }
+        state.truncate(256);
+        state
+    }
+
+    /// Computes the Keccak-256 digest, as used by Ethereum, of `message` given as bytes. The bytes
+    /// are range checked. Returns the 32 bytes of the digest.
+    pub fn keccak256(&mut self, message: &[Target]) -> Vec<Target> {
+        let mut bits = Vec::with_capacity(8 * message.len());
+        for &byte in message {
+            bits.extend(self.split_le(byte, 8));
+        }
+        let digest_bits = self.keccak256_bits(&bits);
+        digest_bits
+            .chunks(8)
+            .map(|byte_bits| self.le_sum(byte_bits.iter()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use keccak_hash::keccak;
+    use rand::rngs::OsRng;
+    use rand::Rng;
+
+    use super::*;
+    use crate::field::types::Field;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    #[test]
+    fn test_keccak_f() -> Result<()> {
+        let mut rng = OsRng;
+        let input: [u64; 25] = rng.gen();
+        let mut expected = input;
+        tiny_keccak::keccakf(&mut expected);
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let state = (0..KECCAK_WIDTH_BITS)
+            .map(|_| builder.add_virtual_bool_target_safe())
+            .collect_vec();
+        let output = builder.keccak_f(&state);
+        for (i, bit) in output.into_iter().enumerate() {
+            let expected_bit = builder.constant_bool((expected[i / 64] >> (i % 64)) & 1 == 1);
+            builder.connect(bit.target, expected_bit.target);
+        }
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        for (i, &bit) in state.iter().enumerate() {
+            pw.set_bool_target(bit, (input[i / 64] >> (i % 64)) & 1 == 1);
+        }
+        let proof = data.prove(pw)?;
+        data.verify(proof)
+    }
+
+    /// Proves that the circuit computes the Keccak-256 digest of a random message of `len` bytes.
+    fn test_keccak256_len(len: usize) -> Result<()> {
+        let mut rng = OsRng;
+        let message = (0..len).map(|_| rng.gen()).collect::<Vec<u8>>();
+        let expected = keccak(&message).0;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let message_targets = builder.add_virtual_targets(len);
+        let digest = builder.keccak256(&message_targets);
+        for (byte, expected_byte) in digest.into_iter().zip(expected) {
+            let expected_byte = builder.constant(F::from_canonical_u8(expected_byte));
+            builder.connect(byte, expected_byte);
+        }
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        for (&target, &byte) in message_targets.iter().zip(&message) {
+            pw.set_target(target, F::from_canonical_u8(byte));
+        }
+        let proof = data.prove(pw)?;
+        data.verify(proof)
+    }
+
+    #[test]
+    fn test_keccak256_empty() -> Result<()> {
+        test_keccak256_len(0)
+    }
+
+    #[test]
+    fn test_keccak256_two_blocks() -> Result<()> {
+        // A full block of message leaves the padding to a second block.
+        test_keccak256_len(136)
+    }
+}
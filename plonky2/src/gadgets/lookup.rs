@@ -0,0 +1,232 @@
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use itertools::Itertools;
+
+use crate::field::extension::Extendable;
+use crate::gates::gate::GateRef;
+use crate::gates::lookup::LookupGate;
+use crate::gates::lookup_table::{LookupTable, LookupTableGate, LookupTableGenerator};
+use crate::hash::hash_types::RichField;
+use crate::iop::target::Target;
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Adds a lookup table to the circuit, and returns its index. Adding the same table twice
+    /// returns the index of the existing one.
+    pub fn add_lookup_table(&mut self, lut: LookupTable) -> usize {
+        assert!(!lut.is_empty(), "Lookup tables cannot be empty.");
+        if let Some(index) = self.luts.iter().position(|l| *l == lut) {
+            return index;
+        }
+        self.luts.push(lut);
+        self.lut_to_lookups.push(Vec::new());
+        self.luts.len() - 1
+    }
+
+    /// Adds the lookup table with entries `(inp, f(inp))` for each of the given inputs, and returns
+    /// its index.
+    pub fn add_lookup_table_from_fn(&mut self, f: fn(u16) -> u16, inputs: &[u16]) -> usize {
+        let lut = inputs.iter().map(|&inp| (inp, f(inp))).collect();
+        self.add_lookup_table(Arc::new(lut))
+    }
+
+    /// Adds the lookup table with entries `(inputs[i], outputs[i])`, and returns its index.
+    pub fn add_lookup_table_from_pairs(&mut self, inputs: &[u16], outputs: &[u16]) -> usize {
+        assert_eq!(inputs.len(), outputs.len());
+        let lut = inputs
+            .iter()
+            .copied()
+            .zip_eq(outputs.iter().copied())
+            .collect();
+        self.add_lookup_table(Arc::new(lut))
+    }
+
+    /// Looks up `looking_in` in the table with the given index, and returns the matching output.
+    /// The proof fails if the input is not in the table.
+    pub fn add_lookup_from_index(&mut self, looking_in: Target, lut_index: usize) -> Target {
+        let lut = self.luts[lut_index].clone();
+        let gate = LookupGate::new_from_table(&self.config, lut);
+        let (row, slot) = self.find_slot(gate, &[], &[]);
+
+        let inp = Target::wire(row, LookupGate::wire_ith_looking_inp(slot));
+        let out = Target::wire(row, LookupGate::wire_ith_looking_out(slot));
+        self.connect(looking_in, inp);
+        self.lut_to_lookups[lut_index].push((inp, out));
+        out
+    }
+
+    /// Fills the incomplete `LookupGate`s with lookups of the first entry of their table, and adds
+    /// the `LookupTableGate` rows of all the tables. Returns the `(row, lut_index)` pairs of the
+    /// `LookupGate` rows, and the range of `LookupTableGate` rows of each table.
+    pub(crate) fn add_all_lookups(&mut self) -> (Vec<(usize, usize)>, Vec<Range<usize>>) {
+        for lut_index in 0..self.luts.len() {
+            let lut = self.luts[lut_index].clone();
+            let gate_ref = GateRef::new(LookupGate::new_from_table(&self.config, lut.clone()));
+            while self
+                .current_slots
+                .get(&gate_ref)
+                .is_some_and(|slot| !slot.current_slot.is_empty())
+            {
+                let dummy = self.constant(F::from_canonical_u16(lut[0].0));
+                self.add_lookup_from_index(dummy, lut_index);
+            }
+        }
+
+        let lookup_rows = self
+            .lut_to_lookups
+            .iter()
+            .enumerate()
+            .flat_map(|(lut_index, lookups)| {
+                lookups
+                    .iter()
+                    .map(move |&(inp, _)| match inp {
+                        Target::Wire(wire) => (wire.row, lut_index),
+                        Target::VirtualTarget { .. } => unreachable!(),
+                    })
+                    .dedup()
+            })
+            .collect();
+
+        let num_slots = LookupTableGate::num_slots(&self.config);
+        let mut table_rows = Vec::with_capacity(self.luts.len());
+        for lut_index in 0..self.luts.len() {
+            let lut = self.luts[lut_index].clone();
+            let first_row = self.num_gates();
+            for _ in 0..LookupTableGate::num_rows(lut.len(), &self.config) {
+                self.add_gate(LookupTableGate::new_from_config(&self.config), vec![]);
+            }
+            table_rows.push(first_row..self.num_gates());
+
+            let looking_inputs = self.lut_to_lookups[lut_index]
+                .iter()
+                .map(|&(inp, _)| inp)
+                .collect();
+            self.add_simple_generator(LookupTableGenerator {
+                lut,
+                looking_inputs,
+                first_row,
+                num_slots,
+            });
+        }
+
+        (lookup_rows, table_rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::sync::Arc;
+
+    use anyhow::Result;
+    use hashbrown::HashMap;
+
+    use crate::field::types::Field;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::{CircuitConfig, CircuitData};
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::recursion::dummy_circuit::{dummy_circuit, dummy_proof};
+    use crate::util::serialization::{DefaultGateSerializer, DefaultGeneratorSerializer};
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    /// A circuit looking up `x` in a table of squares and `x + 1` in a table of cubes, for each
+    /// of the given inputs.
+    fn lookup_circuit(inputs: &[u16]) -> Result<(CircuitData<F, C, D>, PartialWitness<F>)> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let squares = builder.add_lookup_table_from_fn(|x| x * x, &(0..256).collect::<Vec<_>>());
+        let cubes = builder.add_lookup_table(Arc::new((0..40).map(|x| (x, x * x * x)).collect()));
+
+        let mut pw = PartialWitness::new();
+        for &x in inputs {
+            let inp = builder.add_virtual_target();
+            let square = builder.add_lookup_from_index(inp, squares);
+            let expected_square = builder.constant(F::from_canonical_u16(x * x));
+            builder.connect(square, expected_square);
+
+            let one = builder.one();
+            let inp_plus_one = builder.add(inp, one);
+            let cube = builder.add_lookup_from_index(inp_plus_one, cubes);
+            builder.register_public_input(cube);
+
+            pw.set_target(inp, F::from_canonical_u16(x));
+        }
+
+        Ok((builder.build::<C>(), pw))
+    }
+
+    #[test]
+    fn test_lookup() -> Result<()> {
+        let (data, pw) = lookup_circuit(&[3, 14, 15, 9, 26, 5, 35, 3])?;
+        let proof = data.prove(pw)?;
+        assert_eq!(proof.public_inputs[1], F::from_canonical_u16(15 * 15 * 15));
+        data.verify(proof)
+    }
+
+    #[test]
+    fn test_duplicate_tables() {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let lut = Arc::new(vec![(1, 2), (3, 4)]);
+        let a = builder.add_lookup_table(lut);
+        let b = builder.add_lookup_table_from_pairs(&[1, 3], &[2, 4]);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_lookup_not_in_table() {
+        let (data, pw) = lookup_circuit(&[3, 40]).unwrap();
+        let _ = data.prove(pw);
+    }
+
+    #[test]
+    fn test_recursive_lookup() -> Result<()> {
+        let (inner_data, pw) = lookup_circuit(&[1, 2, 3])?;
+        let inner_proof = inner_data.prove(pw)?;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let proof_t = builder.add_virtual_proof_with_pis(&inner_data.common);
+        let vd_t = builder.constant_verifier_data(&inner_data.verifier_only);
+        builder.verify_proof::<C>(&proof_t, &vd_t, &inner_data.common);
+
+        let mut pw = PartialWitness::new();
+        pw.set_proof_with_pis_target(&proof_t, &inner_proof);
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)
+    }
+
+    #[test]
+    fn test_dummy_circuit_with_lookups() -> Result<()> {
+        let (data, _) = lookup_circuit(&[1, 2, 3])?;
+        let dummy_data = dummy_circuit::<F, C, D>(&data.common);
+        let proof = dummy_proof(&dummy_data, HashMap::new())?;
+        dummy_data.verify(proof)
+    }
+
+    #[test]
+    fn test_lookup_serialization() -> Result<()> {
+        let (data, pw) = lookup_circuit(&[7, 8])?;
+        let gate_serializer = DefaultGateSerializer;
+        let generator_serializer = DefaultGeneratorSerializer;
+        let bytes = data
+            .to_bytes(&gate_serializer, &generator_serializer)
+            .unwrap();
+        let data_from_bytes =
+            CircuitData::<F, C, D>::from_bytes(&bytes, &gate_serializer, &generator_serializer)
+                .unwrap();
+        assert_eq!(data.common, data_from_bytes.common);
+
+        let proof = data_from_bytes.prove(pw)?;
+        data.verify(proof)
+    }
+}
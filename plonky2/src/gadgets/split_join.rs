@@ -9,6 +9,7 @@ use crate::iop::target::{BoolTarget, Target};
 use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
 use crate::plonk::circuit_builder::CircuitBuilder;
 use crate::util::ceil_div_usize;
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
 
 impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     /// Split the given integer into a list of wires, where each one represents a
@@ -56,7 +57,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
 }
 
 #[derive(Debug)]
-struct SplitGenerator {
+pub struct SplitGenerator {
     integer: Target,
     bits: Vec<Target>,
 }
@@ -80,10 +81,21 @@ impl<F: RichField> SimpleGenerator<F> for SplitGenerator {
             "Integer too large to fit in given number of bits"
         );
     }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_target(self.integer)?;
+        dst.write_target_vec(&self.bits)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let integer = src.read_target()?;
+        let bits = src.read_target_vec()?;
+        Ok(Self { integer, bits })
+    }
 }
 
 #[derive(Debug)]
-struct WireSplitGenerator {
+pub struct WireSplitGenerator {
     integer: Target,
     gates: Vec<usize>,
     num_limbs: usize,
@@ -120,4 +132,21 @@ impl<F: RichField> SimpleGenerator<F> for WireSplitGenerator {
             self.gates.len()
         );
     }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_target(self.integer)?;
+        dst.write_usize_vec(&self.gates)?;
+        dst.write_usize(self.num_limbs)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let integer = src.read_target()?;
+        let gates = src.read_usize_vec()?;
+        let num_limbs = src.read_usize()?;
+        Ok(Self {
+            integer,
+            gates,
+            num_limbs,
+        })
+    }
 }
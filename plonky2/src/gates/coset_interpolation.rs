@@ -1,4 +1,3 @@
-use alloc::boxed::Box;
 use alloc::string::String;
 use alloc::vec::Vec;
 use alloc::{format, vec};
@@ -13,12 +12,13 @@ use crate::gates::gate::Gate;
 use crate::gates::util::StridedConstraintConsumer;
 use crate::hash::hash_types::RichField;
 use crate::iop::ext_target::{ExtensionAlgebraTarget, ExtensionTarget};
-use crate::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGenerator};
+use crate::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGeneratorRef};
 use crate::iop::target::Target;
 use crate::iop::wire::Wire;
 use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
 use crate::plonk::circuit_builder::CircuitBuilder;
 use crate::plonk::vars::{EvaluationTargets, EvaluationVars, EvaluationVarsBase};
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
 
 /// One of the instantiations of `InterpolationGate`: allows constraints of variable
 /// degree, up to `1<<subgroup_bits`.
@@ -70,6 +70,10 @@ impl<F: RichField + Extendable<D>, const D: usize> CosetInterpolationGate<F, D>
         // Minimizing the degree this way allows the gate to be in a larger selector group
         let degree = (n_points - 2) / (n_intermediates + 1) + 2;
 
+        Self::with_degree(subgroup_bits, degree)
+    }
+
+    fn with_degree(subgroup_bits: usize, degree: usize) -> Self {
         let barycentric_weights = barycentric_weights(
             &F::two_adic_subgroup(subgroup_bits)
                 .into_iter()
@@ -168,6 +172,17 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for CosetInterpola
         format!("{self:?}<D={D}>")
     }
 
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.subgroup_bits)?;
+        dst.write_usize(self.degree)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let subgroup_bits = src.read_usize()?;
+        let degree = src.read_usize()?;
+        Ok(Self::with_degree(subgroup_bits, degree))
+    }
+
     fn eval_unfiltered(&self, vars: EvaluationVars<F, D>) -> Vec<F::Extension> {
         let mut constraints = Vec::with_capacity(self.num_constraints());
 
@@ -342,9 +357,9 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for CosetInterpola
         constraints
     }
 
-    fn generators(&self, row: usize, _local_constants: &[F]) -> Vec<Box<dyn WitnessGenerator<F>>> {
+    fn generators(&self, row: usize, _local_constants: &[F]) -> Vec<WitnessGeneratorRef<F>> {
         let gen = InterpolationGenerator::<F, D>::new(row, self.clone());
-        vec![Box::new(gen.adapter())]
+        vec![WitnessGeneratorRef::new(gen.adapter())]
     }
 
     fn num_wires(&self) -> usize {
@@ -364,10 +379,76 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for CosetInterpola
         // constraints for the evaluation value.
         D + D + 2 * D * self.num_intermediates()
     }
+
+    fn export_solidity_verification_code(&self) -> Option<String> {
+        let to_list = |xs: &[F]| {
+            xs.iter()
+                .map(|x| format!("uint64({})", x.to_canonical_u64()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let degree = self.degree();
+        let mut code = format!(
+            "
+uint64[{n}] memory domain = [{domain}];
+uint64[{n}] memory weights = [{weights}];
+Ext shift = w[{shift}];
+Ext[2] memory zs = [w[{shifted}], w[{shifted} + 1]];
+out[0] = w[{point}] - zs[0] * shift;
+out[1] = w[{point} + 1] - zs[1] * shift;
+Ext[4] memory st = [EXT_ZERO, EXT_ZERO, EXT_ONE, EXT_ZERO];",
+            n = self.num_points(),
+            domain = to_list(&F::two_adic_subgroup(self.subgroup_bits)),
+            weights = to_list(&self.barycentric_weights),
+            shift = self.wire_shift(),
+            shifted = self.wires_shifted_evaluation_point().start,
+            point = self.wires_evaluation_point().start,
+        );
+        let mut start = 0;
+        let mut end = degree;
+        for i in 0..=self.num_intermediates() {
+            if i > 0 {
+                let eval = self.wires_intermediate_eval(i - 1).start;
+                let prod = self.wires_intermediate_prod(i - 1).start;
+                let k = 2 + 4 * (i - 1);
+                code += &format!(
+                    "
+out[{k}] = w[{eval}] - st[0];
+out[{k} + 1] = w[{eval} + 1] - st[1];
+out[{k} + 2] = w[{prod}] - st[2];
+out[{k} + 3] = w[{prod} + 1] - st[3];
+st[0] = w[{eval}];
+st[1] = w[{eval} + 1];
+st[2] = w[{prod}];
+st[3] = w[{prod} + 1];"
+                );
+                start = 1 + (degree - 1) * i;
+                end = (start + degree - 1).min(self.num_points());
+            }
+            code += &format!(
+                "
+for (uint256 i = {start}; i < {end}; i++) {{
+    Ext weight = fromBase(weights[i]);
+    Ext v0 = w[{values} + 2 * i] * weight;
+    Ext v1 = w[{values} + 2 * i + 1] * weight;
+    interpolationStep(st, zs[0] - fromBase(domain[i]), zs[1], v0, v1);
+}}",
+                values = self.wires_value(0).start,
+            );
+        }
+        let k = 2 + 4 * self.num_intermediates();
+        let value = self.wires_evaluation_value().start;
+        code += &format!(
+            "
+out[{k}] = w[{value}] - st[0];
+out[{k} + 1] = w[{value} + 1] - st[1];"
+        );
+        Some(code)
+    }
 }
 
 #[derive(Debug)]
-struct InterpolationGenerator<F: RichField + Extendable<D>, const D: usize> {
+pub struct InterpolationGenerator<F: RichField + Extendable<D>, const D: usize> {
     row: usize,
     gate: CosetInterpolationGate<F, D>,
     interpolation_domain: Vec<F>,
@@ -471,6 +552,17 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F>
         let evaluation_value_wires = self.gate.wires_evaluation_value().map(local_wire);
         out_buffer.set_ext_wires(evaluation_value_wires, computed_eval);
     }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.row)?;
+        self.gate.serialize(dst)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let row = src.read_usize()?;
+        let gate = CosetInterpolationGate::deserialize(src)?;
+        Ok(Self::new(row, gate))
+    }
 }
 
 /// Interpolate the polynomial defined by its values on an arbitrary domain at the given point `x`.
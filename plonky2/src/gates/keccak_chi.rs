@@ -0,0 +1,223 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::field::extension::Extendable;
+use crate::field::packed::PackedField;
+use crate::gates::gate::Gate;
+use crate::gates::keccak_theta::xor_circuit;
+use crate::gates::packed_util::PackedEvaluableBase;
+use crate::gates::util::StridedConstraintConsumer;
+use crate::hash::hash_types::RichField;
+use crate::iop::ext_target::ExtensionTarget;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGeneratorRef};
+use crate::iop::target::Target;
+use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::CircuitConfig;
+use crate::plonk::vars::{
+    EvaluationTargets, EvaluationVars, EvaluationVarsBase, EvaluationVarsBaseBatch,
+    EvaluationVarsBasePacked,
+};
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
+
+/// A gate which applies the Keccak chi step to a row of the state, i.e. computes
+/// `B[x, y, z] ^ (!B[x + 1, y, z] & B[x + 2, y, z])` for `x = 0, ..., 4`. The inputs must be bits.
+/// If the config supports enough routed wires, several rows fit in one gate.
+#[derive(Debug, Clone)]
+pub struct KeccakChiGate {
+    pub num_ops: usize,
+}
+
+impl KeccakChiGate {
+    pub fn new_from_config(config: &CircuitConfig) -> Self {
+        Self {
+            num_ops: Self::num_ops(config),
+        }
+    }
+
+    /// Determine the maximum number of operations that can fit in one gate for the given config.
+    pub(crate) fn num_ops(config: &CircuitConfig) -> usize {
+        let wires_per_op = 10;
+        config.num_routed_wires / wires_per_op
+    }
+
+    pub fn wire_ith_input_jth_bit(i: usize, j: usize) -> usize {
+        debug_assert!(j < 5);
+        10 * i + j
+    }
+    pub fn wire_ith_output_jth_bit(i: usize, j: usize) -> usize {
+        debug_assert!(j < 5);
+        10 * i + 5 + j
+    }
+
+    fn eval<P: PackedField>(
+        &self,
+        local_wires: impl Fn(usize) -> P,
+        mut yield_constr: impl FnMut(P),
+    ) {
+        for i in 0..self.num_ops {
+            let inputs: [P; 5] =
+                core::array::from_fn(|j| local_wires(Self::wire_ith_input_jth_bit(i, j)));
+            for j in 0..5 {
+                let a = inputs[j];
+                let not_and = (P::ONES - inputs[(j + 1) % 5]) * inputs[(j + 2) % 5];
+                let computed_output = a + not_and - (a * not_and).doubles();
+                let output = local_wires(Self::wire_ith_output_jth_bit(i, j));
+                yield_constr(output - computed_output);
+            }
+        }
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for KeccakChiGate {
+    fn id(&self) -> String {
+        format!("{self:?}")
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.num_ops)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let num_ops = src.read_usize()?;
+        Ok(Self { num_ops })
+    }
+
+    fn eval_unfiltered(&self, vars: EvaluationVars<F, D>) -> Vec<F::Extension> {
+        let mut constraints = Vec::with_capacity(<Self as Gate<F, D>>::num_constraints(self));
+        self.eval(|i| vars.local_wires[i], |c| constraints.push(c));
+        constraints
+    }
+
+    fn eval_unfiltered_base_one(
+        &self,
+        _vars: EvaluationVarsBase<F>,
+        _yield_constr: StridedConstraintConsumer<F>,
+    ) {
+        panic!("use eval_unfiltered_base_packed instead");
+    }
+
+    fn eval_unfiltered_base_batch(&self, vars_base: EvaluationVarsBaseBatch<F>) -> Vec<F> {
+        self.eval_unfiltered_base_batch_packed(vars_base)
+    }
+
+    fn eval_unfiltered_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: EvaluationTargets<D>,
+    ) -> Vec<ExtensionTarget<D>> {
+        let mut constraints = Vec::with_capacity(<Self as Gate<F, D>>::num_constraints(self));
+        for i in 0..self.num_ops {
+            let inputs: [ExtensionTarget<D>; 5] =
+                core::array::from_fn(|j| vars.local_wires[Self::wire_ith_input_jth_bit(i, j)]);
+            for j in 0..5 {
+                // (1 - b) c = c - bc
+                let next = inputs[(j + 2) % 5];
+                let not_and =
+                    builder.arithmetic_extension(-F::ONE, F::ONE, inputs[(j + 1) % 5], next, next);
+                let computed_output = xor_circuit(builder, inputs[j], not_and);
+                let output = vars.local_wires[Self::wire_ith_output_jth_bit(i, j)];
+                constraints.push(builder.sub_extension(output, computed_output));
+            }
+        }
+        constraints
+    }
+
+    fn generators(&self, row: usize, _local_constants: &[F]) -> Vec<WitnessGeneratorRef<F>> {
+        (0..self.num_ops)
+            .map(|i| WitnessGeneratorRef::new(KeccakChiGenerator { row, i }.adapter()))
+            .collect()
+    }
+
+    fn num_wires(&self) -> usize {
+        self.num_ops * 10
+    }
+
+    fn num_constants(&self) -> usize {
+        0
+    }
+
+    fn degree(&self) -> usize {
+        3
+    }
+
+    fn num_constraints(&self) -> usize {
+        self.num_ops * 5
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> PackedEvaluableBase<F, D> for KeccakChiGate {
+    fn eval_unfiltered_base_packed<P: PackedField<Scalar = F>>(
+        &self,
+        vars: EvaluationVarsBasePacked<P>,
+        mut yield_constr: StridedConstraintConsumer<P>,
+    ) {
+        self.eval(|i| vars.local_wires[i], |c| yield_constr.one(c));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct KeccakChiGenerator {
+    row: usize,
+    i: usize,
+}
+
+impl<F: RichField> SimpleGenerator<F> for KeccakChiGenerator {
+    fn dependencies(&self) -> Vec<Target> {
+        (0..5)
+            .map(|j| Target::wire(self.row, KeccakChiGate::wire_ith_input_jth_bit(self.i, j)))
+            .collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let inputs: [u64; 5] = core::array::from_fn(|j| {
+            let wire = Target::wire(self.row, KeccakChiGate::wire_ith_input_jth_bit(self.i, j));
+            witness.get_target(wire).to_canonical_u64()
+        });
+        for j in 0..5 {
+            let output = inputs[j] ^ ((inputs[(j + 1) % 5] ^ 1) & inputs[(j + 2) % 5]);
+            out_buffer.set_target(
+                Target::wire(self.row, KeccakChiGate::wire_ith_output_jth_bit(self.i, j)),
+                F::from_canonical_u64(output),
+            );
+        }
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.row)?;
+        dst.write_usize(self.i)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let row = src.read_usize()?;
+        let i = src.read_usize()?;
+        Ok(Self { row, i })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::field::goldilocks_field::GoldilocksField;
+    use crate::gates::gate_testing::{test_eval_fns, test_low_degree};
+    use crate::gates::keccak_chi::KeccakChiGate;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    #[test]
+    fn low_degree() {
+        let gate = KeccakChiGate::new_from_config(&CircuitConfig::standard_recursion_config());
+        test_low_degree::<GoldilocksField, _, 4>(gate);
+    }
+
+    #[test]
+    fn eval_fns() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let gate = KeccakChiGate::new_from_config(&CircuitConfig::standard_recursion_config());
+        test_eval_fns::<F, C, _, D>(gate)
+    }
+}
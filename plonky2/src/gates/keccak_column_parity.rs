@@ -0,0 +1,269 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::field::extension::Extendable;
+use crate::field::packed::PackedField;
+use crate::field::types::Field;
+use crate::gates::gate::Gate;
+use crate::gates::packed_util::PackedEvaluableBase;
+use crate::gates::util::StridedConstraintConsumer;
+use crate::hash::hash_types::RichField;
+use crate::iop::ext_target::ExtensionTarget;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGeneratorRef};
+use crate::iop::target::Target;
+use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::CircuitConfig;
+use crate::plonk::vars::{
+    EvaluationTargets, EvaluationVars, EvaluationVarsBase, EvaluationVarsBaseBatch,
+    EvaluationVarsBasePacked,
+};
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
+
+/// A gate which computes the parity `C[x, z] = A[x, 0, z] ^ ... ^ A[x, 4, z]` of a column of the
+/// Keccak state, the first half of the theta step. The inputs must be bits.
+///
+/// The sum of the five bits is written as `parity + 2 * half_sum`, where the unrouted `half_sum`
+/// is at most 2. If the config supports enough wires, several columns fit in one gate.
+#[derive(Debug, Clone)]
+pub struct KeccakColumnParityGate {
+    pub num_ops: usize,
+}
+
+impl KeccakColumnParityGate {
+    pub fn new_from_config(config: &CircuitConfig) -> Self {
+        Self {
+            num_ops: Self::num_ops(config),
+        }
+    }
+
+    /// Determine the maximum number of operations that can fit in one gate for the given config.
+    pub(crate) fn num_ops(config: &CircuitConfig) -> usize {
+        let routed_wires_per_op = 6;
+        let wires_per_op = 7;
+        (config.num_routed_wires / routed_wires_per_op).min(config.num_wires / wires_per_op)
+    }
+
+    pub fn wire_ith_input_jth_bit(i: usize, j: usize) -> usize {
+        debug_assert!(j < 5);
+        6 * i + j
+    }
+    pub fn wire_ith_output(i: usize) -> usize {
+        6 * i + 5
+    }
+    pub fn wire_ith_half_sum(&self, i: usize) -> usize {
+        6 * self.num_ops + i
+    }
+
+    fn eval<P: PackedField>(
+        &self,
+        local_wires: impl Fn(usize) -> P,
+        mut yield_constr: impl FnMut(P),
+    ) {
+        let two = P::Scalar::TWO;
+        for i in 0..self.num_ops {
+            let sum = (0..5)
+                .map(|j| local_wires(Self::wire_ith_input_jth_bit(i, j)))
+                .fold(P::ZEROS, |acc, bit| acc + bit);
+            let output = local_wires(Self::wire_ith_output(i));
+            let half_sum = local_wires(self.wire_ith_half_sum(i));
+
+            yield_constr(sum - output - half_sum * two);
+            yield_constr(output * (output - P::ONES));
+            yield_constr(half_sum * (half_sum - P::ONES) * (half_sum - P::ONES * two));
+        }
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for KeccakColumnParityGate {
+    fn id(&self) -> String {
+        format!("{self:?}")
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.num_ops)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let num_ops = src.read_usize()?;
+        Ok(Self { num_ops })
+    }
+
+    fn eval_unfiltered(&self, vars: EvaluationVars<F, D>) -> Vec<F::Extension> {
+        let mut constraints = Vec::with_capacity(<Self as Gate<F, D>>::num_constraints(self));
+        self.eval(|i| vars.local_wires[i], |c| constraints.push(c));
+        constraints
+    }
+
+    fn eval_unfiltered_base_one(
+        &self,
+        _vars: EvaluationVarsBase<F>,
+        _yield_constr: StridedConstraintConsumer<F>,
+    ) {
+        panic!("use eval_unfiltered_base_packed instead");
+    }
+
+    fn eval_unfiltered_base_batch(&self, vars_base: EvaluationVarsBaseBatch<F>) -> Vec<F> {
+        self.eval_unfiltered_base_batch_packed(vars_base)
+    }
+
+    fn eval_unfiltered_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: EvaluationTargets<D>,
+    ) -> Vec<ExtensionTarget<D>> {
+        let mut constraints = Vec::with_capacity(<Self as Gate<F, D>>::num_constraints(self));
+        let one = builder.one_extension();
+        let two = builder.two_extension();
+        for i in 0..self.num_ops {
+            let inputs = (0..5)
+                .map(|j| vars.local_wires[Self::wire_ith_input_jth_bit(i, j)])
+                .collect::<Vec<_>>();
+            let sum = builder.add_many_extension(inputs);
+            let output = vars.local_wires[Self::wire_ith_output(i)];
+            let half_sum = vars.local_wires[self.wire_ith_half_sum(i)];
+
+            let computed_sum = builder.mul_add_extension(half_sum, two, output);
+            constraints.push(builder.sub_extension(sum, computed_sum));
+            constraints.push(builder.mul_sub_extension(output, output, output));
+            let half_sum_minus_one = builder.sub_extension(half_sum, one);
+            let half_sum_minus_two = builder.sub_extension(half_sum, two);
+            constraints.push(builder.mul_many_extension([
+                half_sum,
+                half_sum_minus_one,
+                half_sum_minus_two,
+            ]));
+        }
+        constraints
+    }
+
+    fn generators(&self, row: usize, _local_constants: &[F]) -> Vec<WitnessGeneratorRef<F>> {
+        (0..self.num_ops)
+            .map(|i| {
+                WitnessGeneratorRef::new(
+                    KeccakColumnParityGenerator {
+                        row,
+                        num_ops: self.num_ops,
+                        i,
+                    }
+                    .adapter(),
+                )
+            })
+            .collect()
+    }
+
+    fn num_wires(&self) -> usize {
+        self.num_ops * 7
+    }
+
+    fn num_constants(&self) -> usize {
+        0
+    }
+
+    fn degree(&self) -> usize {
+        3
+    }
+
+    fn num_constraints(&self) -> usize {
+        self.num_ops * 3
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> PackedEvaluableBase<F, D>
+    for KeccakColumnParityGate
+{
+    fn eval_unfiltered_base_packed<P: PackedField<Scalar = F>>(
+        &self,
+        vars: EvaluationVarsBasePacked<P>,
+        mut yield_constr: StridedConstraintConsumer<P>,
+    ) {
+        self.eval(|i| vars.local_wires[i], |c| yield_constr.one(c));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct KeccakColumnParityGenerator {
+    row: usize,
+    num_ops: usize,
+    i: usize,
+}
+
+impl<F: RichField> SimpleGenerator<F> for KeccakColumnParityGenerator {
+    fn dependencies(&self) -> Vec<Target> {
+        (0..5)
+            .map(|j| {
+                Target::wire(
+                    self.row,
+                    KeccakColumnParityGate::wire_ith_input_jth_bit(self.i, j),
+                )
+            })
+            .collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let gate = KeccakColumnParityGate {
+            num_ops: self.num_ops,
+        };
+        let sum = (0..5)
+            .map(|j| {
+                let bit = Target::wire(
+                    self.row,
+                    KeccakColumnParityGate::wire_ith_input_jth_bit(self.i, j),
+                );
+                witness.get_target(bit).to_canonical_u64()
+            })
+            .sum::<u64>();
+        debug_assert!(sum <= 5, "Inputs are not bits.");
+
+        out_buffer.set_target(
+            Target::wire(self.row, KeccakColumnParityGate::wire_ith_output(self.i)),
+            F::from_canonical_u64(sum & 1),
+        );
+        out_buffer.set_target(
+            Target::wire(self.row, gate.wire_ith_half_sum(self.i)),
+            F::from_canonical_u64(sum >> 1),
+        );
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.row)?;
+        dst.write_usize(self.num_ops)?;
+        dst.write_usize(self.i)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let row = src.read_usize()?;
+        let num_ops = src.read_usize()?;
+        let i = src.read_usize()?;
+        Ok(Self { row, num_ops, i })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::field::goldilocks_field::GoldilocksField;
+    use crate::gates::gate_testing::{test_eval_fns, test_low_degree};
+    use crate::gates::keccak_column_parity::KeccakColumnParityGate;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    #[test]
+    fn low_degree() {
+        let gate =
+            KeccakColumnParityGate::new_from_config(&CircuitConfig::standard_recursion_config());
+        test_low_degree::<GoldilocksField, _, 4>(gate);
+    }
+
+    #[test]
+    fn eval_fns() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let gate =
+            KeccakColumnParityGate::new_from_config(&CircuitConfig::standard_recursion_config());
+        test_eval_fns::<F, C, _, D>(gate)
+    }
+}
@@ -0,0 +1,251 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::field::extension::Extendable;
+use crate::field::packed::PackedField;
+use crate::gates::gate::Gate;
+use crate::gates::packed_util::PackedEvaluableBase;
+use crate::gates::util::StridedConstraintConsumer;
+use crate::hash::hash_types::RichField;
+use crate::iop::ext_target::ExtensionTarget;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGeneratorRef};
+use crate::iop::target::Target;
+use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::CircuitConfig;
+use crate::plonk::vars::{
+    EvaluationTargets, EvaluationVars, EvaluationVarsBase, EvaluationVarsBaseBatch,
+    EvaluationVarsBasePacked,
+};
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
+
+/// A gate which applies the Keccak theta step to a column of the state, i.e. computes
+/// `A[x, y, z] ^ C[x - 1, z] ^ C[x + 1, z - 1]` for `y = 0, ..., 4`, given the column parities
+/// `C` from `KeccakColumnParityGate`. The inputs must be bits. If the config supports enough
+/// routed wires, several columns fit in one gate.
+#[derive(Debug, Clone)]
+pub struct KeccakThetaGate {
+    pub num_ops: usize,
+}
+
+impl KeccakThetaGate {
+    pub fn new_from_config(config: &CircuitConfig) -> Self {
+        Self {
+            num_ops: Self::num_ops(config),
+        }
+    }
+
+    /// Determine the maximum number of operations that can fit in one gate for the given config.
+    pub(crate) fn num_ops(config: &CircuitConfig) -> usize {
+        let wires_per_op = 12;
+        config.num_routed_wires / wires_per_op
+    }
+
+    pub fn wire_ith_input_jth_bit(i: usize, j: usize) -> usize {
+        debug_assert!(j < 5);
+        12 * i + j
+    }
+    /// The parity `C[x - 1, z]`.
+    pub fn wire_ith_left_parity(i: usize) -> usize {
+        12 * i + 5
+    }
+    /// The parity `C[x + 1, z - 1]`.
+    pub fn wire_ith_right_parity(i: usize) -> usize {
+        12 * i + 6
+    }
+    pub fn wire_ith_output_jth_bit(i: usize, j: usize) -> usize {
+        debug_assert!(j < 5);
+        12 * i + 7 + j
+    }
+
+    fn eval<P: PackedField>(
+        &self,
+        local_wires: impl Fn(usize) -> P,
+        mut yield_constr: impl FnMut(P),
+    ) {
+        let xor = |a: P, b: P| a + b - (a * b).doubles();
+        for i in 0..self.num_ops {
+            let left = local_wires(Self::wire_ith_left_parity(i));
+            let right = local_wires(Self::wire_ith_right_parity(i));
+            let d = xor(left, right);
+            for j in 0..5 {
+                let input = local_wires(Self::wire_ith_input_jth_bit(i, j));
+                let output = local_wires(Self::wire_ith_output_jth_bit(i, j));
+                yield_constr(output - xor(input, d));
+            }
+        }
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for KeccakThetaGate {
+    fn id(&self) -> String {
+        format!("{self:?}")
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.num_ops)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let num_ops = src.read_usize()?;
+        Ok(Self { num_ops })
+    }
+
+    fn eval_unfiltered(&self, vars: EvaluationVars<F, D>) -> Vec<F::Extension> {
+        let mut constraints = Vec::with_capacity(<Self as Gate<F, D>>::num_constraints(self));
+        self.eval(|i| vars.local_wires[i], |c| constraints.push(c));
+        constraints
+    }
+
+    fn eval_unfiltered_base_one(
+        &self,
+        _vars: EvaluationVarsBase<F>,
+        _yield_constr: StridedConstraintConsumer<F>,
+    ) {
+        panic!("use eval_unfiltered_base_packed instead");
+    }
+
+    fn eval_unfiltered_base_batch(&self, vars_base: EvaluationVarsBaseBatch<F>) -> Vec<F> {
+        self.eval_unfiltered_base_batch_packed(vars_base)
+    }
+
+    fn eval_unfiltered_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: EvaluationTargets<D>,
+    ) -> Vec<ExtensionTarget<D>> {
+        let mut constraints = Vec::with_capacity(<Self as Gate<F, D>>::num_constraints(self));
+        for i in 0..self.num_ops {
+            let left = vars.local_wires[Self::wire_ith_left_parity(i)];
+            let right = vars.local_wires[Self::wire_ith_right_parity(i)];
+            let d = xor_circuit(builder, left, right);
+            for j in 0..5 {
+                let input = vars.local_wires[Self::wire_ith_input_jth_bit(i, j)];
+                let output = vars.local_wires[Self::wire_ith_output_jth_bit(i, j)];
+                let computed_output = xor_circuit(builder, input, d);
+                constraints.push(builder.sub_extension(output, computed_output));
+            }
+        }
+        constraints
+    }
+
+    fn generators(&self, row: usize, _local_constants: &[F]) -> Vec<WitnessGeneratorRef<F>> {
+        (0..self.num_ops)
+            .map(|i| WitnessGeneratorRef::new(KeccakThetaGenerator { row, i }.adapter()))
+            .collect()
+    }
+
+    fn num_wires(&self) -> usize {
+        self.num_ops * 12
+    }
+
+    fn num_constants(&self) -> usize {
+        0
+    }
+
+    fn degree(&self) -> usize {
+        3
+    }
+
+    fn num_constraints(&self) -> usize {
+        self.num_ops * 5
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> PackedEvaluableBase<F, D> for KeccakThetaGate {
+    fn eval_unfiltered_base_packed<P: PackedField<Scalar = F>>(
+        &self,
+        vars: EvaluationVarsBasePacked<P>,
+        mut yield_constr: StridedConstraintConsumer<P>,
+    ) {
+        self.eval(|i| vars.local_wires[i], |c| yield_constr.one(c));
+    }
+}
+
+/// `a ^ b` for bits `a` and `b`, i.e. `a + b - 2ab`.
+pub(crate) fn xor_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    a: ExtensionTarget<D>,
+    b: ExtensionTarget<D>,
+) -> ExtensionTarget<D> {
+    let sum = builder.add_extension(a, b);
+    builder.arithmetic_extension(-F::TWO, F::ONE, a, b, sum)
+}
+
+#[derive(Clone, Debug)]
+pub struct KeccakThetaGenerator {
+    row: usize,
+    i: usize,
+}
+
+impl<F: RichField> SimpleGenerator<F> for KeccakThetaGenerator {
+    fn dependencies(&self) -> Vec<Target> {
+        (0..5)
+            .map(|j| KeccakThetaGate::wire_ith_input_jth_bit(self.i, j))
+            .chain([
+                KeccakThetaGate::wire_ith_left_parity(self.i),
+                KeccakThetaGate::wire_ith_right_parity(self.i),
+            ])
+            .map(|column| Target::wire(self.row, column))
+            .collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let get_bit = |column| {
+            witness
+                .get_target(Target::wire(self.row, column))
+                .to_canonical_u64()
+        };
+
+        let d = get_bit(KeccakThetaGate::wire_ith_left_parity(self.i))
+            ^ get_bit(KeccakThetaGate::wire_ith_right_parity(self.i));
+        for j in 0..5 {
+            let input = get_bit(KeccakThetaGate::wire_ith_input_jth_bit(self.i, j));
+            out_buffer.set_target(
+                Target::wire(
+                    self.row,
+                    KeccakThetaGate::wire_ith_output_jth_bit(self.i, j),
+                ),
+                F::from_canonical_u64(input ^ d),
+            );
+        }
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.row)?;
+        dst.write_usize(self.i)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let row = src.read_usize()?;
+        let i = src.read_usize()?;
+        Ok(Self { row, i })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::field::goldilocks_field::GoldilocksField;
+    use crate::gates::gate_testing::{test_eval_fns, test_low_degree};
+    use crate::gates::keccak_theta::KeccakThetaGate;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    #[test]
+    fn low_degree() {
+        let gate = KeccakThetaGate::new_from_config(&CircuitConfig::standard_recursion_config());
+        test_low_degree::<GoldilocksField, _, 4>(gate);
+    }
+
+    #[test]
+    fn eval_fns() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let gate = KeccakThetaGate::new_from_config(&CircuitConfig::standard_recursion_config());
+        test_eval_fns::<F, C, _, D>(gate)
+    }
+}
@@ -0,0 +1,211 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use itertools::Itertools;
+use keccak_hash::keccak;
+
+use crate::field::extension::Extendable;
+use crate::gates::gate::Gate;
+use crate::gates::lookup_table::LookupTable;
+use crate::hash::hash_types::RichField;
+use crate::iop::ext_target::ExtensionTarget;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGeneratorRef};
+use crate::iop::target::Target;
+use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::CircuitConfig;
+use crate::plonk::vars::{EvaluationTargets, EvaluationVars, EvaluationVarsBaseBatch};
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
+
+/// A gate holding lookups into a single table, as `(input, output)` pairs of routed wires.
+///
+/// The gate itself has no constraints: the lookups are checked by the lookup argument, which is
+/// part of the vanishing polynomial.
+#[derive(Clone, Debug)]
+pub struct LookupGate {
+    /// Number of lookups per row.
+    pub num_slots: usize,
+    /// The table being looked up.
+    pub lut: LookupTable,
+    /// A hash of the table, used to distinguish the gates of different tables.
+    lut_hash: [u8; 32],
+}
+
+impl LookupGate {
+    pub fn new_from_table(config: &CircuitConfig, lut: LookupTable) -> Self {
+        Self::new(Self::num_slots(config), lut)
+    }
+
+    fn new(num_slots: usize, lut: LookupTable) -> Self {
+        let lut_hash = keccak(
+            lut.iter()
+                .flat_map(|&(inp, out)| [inp.to_le_bytes(), out.to_le_bytes()])
+                .flatten()
+                .collect_vec(),
+        )
+        .0;
+        Self {
+            num_slots,
+            lut,
+            lut_hash,
+        }
+    }
+
+    pub(crate) fn num_slots(config: &CircuitConfig) -> usize {
+        config.num_routed_wires / 2
+    }
+
+    pub fn wire_ith_looking_inp(i: usize) -> usize {
+        2 * i
+    }
+
+    pub fn wire_ith_looking_out(i: usize) -> usize {
+        2 * i + 1
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for LookupGate {
+    fn id(&self) -> String {
+        // The table itself can be large, so we only include its hash.
+        format!(
+            "LookupGate {{num_slots: {}, lut_hash: {:?}}}",
+            self.num_slots, self.lut_hash
+        )
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.num_slots)?;
+        dst.write_lut(&self.lut)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let num_slots = src.read_usize()?;
+        let lut = src.read_lut()?;
+        Ok(Self::new(num_slots, lut))
+    }
+
+    fn eval_unfiltered(&self, _vars: EvaluationVars<F, D>) -> Vec<F::Extension> {
+        Vec::new()
+    }
+
+    fn eval_unfiltered_base_batch(&self, _vars: EvaluationVarsBaseBatch<F>) -> Vec<F> {
+        Vec::new()
+    }
+
+    fn eval_unfiltered_circuit(
+        &self,
+        _builder: &mut CircuitBuilder<F, D>,
+        _vars: EvaluationTargets<D>,
+    ) -> Vec<ExtensionTarget<D>> {
+        Vec::new()
+    }
+
+    fn generators(&self, row: usize, _local_constants: &[F]) -> Vec<WitnessGeneratorRef<F>> {
+        (0..self.num_slots)
+            .map(|slot| {
+                WitnessGeneratorRef::new(
+                    LookupGenerator {
+                        row,
+                        lut: self.lut.clone(),
+                        slot,
+                    }
+                    .adapter(),
+                )
+            })
+            .collect()
+    }
+
+    fn num_wires(&self) -> usize {
+        2 * self.num_slots
+    }
+
+    fn num_constants(&self) -> usize {
+        0
+    }
+
+    fn degree(&self) -> usize {
+        0
+    }
+
+    fn num_constraints(&self) -> usize {
+        0
+    }
+}
+
+/// Sets the output of a lookup, given its input.
+#[derive(Clone, Debug)]
+pub struct LookupGenerator {
+    row: usize,
+    lut: LookupTable,
+    slot: usize,
+}
+
+impl<F: RichField> SimpleGenerator<F> for LookupGenerator {
+    fn dependencies(&self) -> Vec<Target> {
+        vec![Target::wire(
+            self.row,
+            LookupGate::wire_ith_looking_inp(self.slot),
+        )]
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let input = witness
+            .get_target(Target::wire(
+                self.row,
+                LookupGate::wire_ith_looking_inp(self.slot),
+            ))
+            .to_canonical_u64();
+        let (_, output) = self
+            .lut
+            .iter()
+            .find(|&&(inp, _)| u64::from(inp) == input)
+            .unwrap_or_else(|| panic!("Lookup input {input} is not in the table"));
+        out_buffer.set_target(
+            Target::wire(self.row, LookupGate::wire_ith_looking_out(self.slot)),
+            F::from_canonical_u16(*output),
+        );
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.row)?;
+        dst.write_lut(&self.lut)?;
+        dst.write_usize(self.slot)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let row = src.read_usize()?;
+        let lut = src.read_lut()?;
+        let slot = src.read_usize()?;
+        Ok(Self { row, lut, slot })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::sync::Arc;
+
+    use crate::field::goldilocks_field::GoldilocksField;
+    use crate::gates::gate_testing::{test_eval_fns, test_low_degree};
+    use crate::gates::lookup::LookupGate;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    fn gate() -> LookupGate {
+        let lut = Arc::new((0..16).map(|i| (i, i * i)).collect());
+        LookupGate::new_from_table(&CircuitConfig::standard_recursion_config(), lut)
+    }
+
+    #[test]
+    fn low_degree() {
+        test_low_degree::<GoldilocksField, _, 4>(gate())
+    }
+
+    #[test]
+    fn eval_fns() -> anyhow::Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        test_eval_fns::<F, C, _, D>(gate())
+    }
+}
@@ -0,0 +1,213 @@
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use hashbrown::HashMap;
+
+use crate::field::extension::Extendable;
+use crate::gates::gate::Gate;
+use crate::hash::hash_types::RichField;
+use crate::iop::ext_target::ExtensionTarget;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGeneratorRef};
+use crate::iop::target::Target;
+use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::CircuitConfig;
+use crate::plonk::vars::{EvaluationTargets, EvaluationVars, EvaluationVarsBaseBatch};
+use crate::util::ceil_div_usize;
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
+
+/// A lookup table, given as a list of `(input, output)` pairs.
+pub type LookupTable = Arc<Vec<(u16, u16)>>;
+
+/// A gate holding some entries of a lookup table, along with the number of times each entry is
+/// looked up. The rows of a table are consecutive, and padded with copies of its first entry.
+///
+/// The gate itself has no constraints. The entries are bound to the table stored in the
+/// `CommonCircuitData` by the lookup argument, which also checks the multiplicities.
+#[derive(Copy, Clone, Debug)]
+pub struct LookupTableGate {
+    /// Number of table entries per row.
+    pub num_slots: usize,
+}
+
+impl LookupTableGate {
+    pub fn new_from_config(config: &CircuitConfig) -> Self {
+        Self {
+            num_slots: Self::num_slots(config),
+        }
+    }
+
+    pub(crate) fn num_slots(config: &CircuitConfig) -> usize {
+        config.num_wires / 3
+    }
+
+    /// The number of rows needed to hold a table with `table_len` entries.
+    pub(crate) fn num_rows(table_len: usize, config: &CircuitConfig) -> usize {
+        ceil_div_usize(table_len, Self::num_slots(config))
+    }
+
+    pub fn wire_ith_looked_inp(i: usize) -> usize {
+        3 * i
+    }
+
+    pub fn wire_ith_looked_out(i: usize) -> usize {
+        3 * i + 1
+    }
+
+    pub fn wire_ith_multiplicity(i: usize) -> usize {
+        3 * i + 2
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for LookupTableGate {
+    fn id(&self) -> String {
+        format!("{self:?}")
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.num_slots)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let num_slots = src.read_usize()?;
+        Ok(Self { num_slots })
+    }
+
+    fn eval_unfiltered(&self, _vars: EvaluationVars<F, D>) -> Vec<F::Extension> {
+        Vec::new()
+    }
+
+    fn eval_unfiltered_base_batch(&self, _vars: EvaluationVarsBaseBatch<F>) -> Vec<F> {
+        Vec::new()
+    }
+
+    fn eval_unfiltered_circuit(
+        &self,
+        _builder: &mut CircuitBuilder<F, D>,
+        _vars: EvaluationTargets<D>,
+    ) -> Vec<ExtensionTarget<D>> {
+        Vec::new()
+    }
+
+    fn generators(&self, _row: usize, _local_constants: &[F]) -> Vec<WitnessGeneratorRef<F>> {
+        // The table rows are filled by a single `LookupTableGenerator` per table, since the
+        // multiplicities depend on all the lookups.
+        Vec::new()
+    }
+
+    fn num_wires(&self) -> usize {
+        3 * self.num_slots
+    }
+
+    fn num_constants(&self) -> usize {
+        0
+    }
+
+    fn degree(&self) -> usize {
+        0
+    }
+
+    fn num_constraints(&self) -> usize {
+        0
+    }
+}
+
+/// Fills the `LookupTableGate` rows of a table, counting how many times each entry is looked up.
+#[derive(Clone, Debug)]
+pub struct LookupTableGenerator {
+    pub(crate) lut: LookupTable,
+    /// The input wires of all the lookups into this table.
+    pub(crate) looking_inputs: Vec<Target>,
+    pub(crate) first_row: usize,
+    pub(crate) num_slots: usize,
+}
+
+impl<F: RichField> SimpleGenerator<F> for LookupTableGenerator {
+    fn dependencies(&self) -> Vec<Target> {
+        self.looking_inputs.clone()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let mut positions = HashMap::new();
+        for (i, &(inp, _)) in self.lut.iter().enumerate().rev() {
+            positions.insert(inp, i);
+        }
+
+        let mut multiplicities = vec![0usize; self.lut.len()];
+        for &input in &self.looking_inputs {
+            let input = witness.get_target(input).to_canonical_u64();
+            let position = u16::try_from(input)
+                .ok()
+                .and_then(|inp| positions.get(&inp))
+                .unwrap_or_else(|| panic!("Lookup input {input} is not in the table"));
+            multiplicities[*position] += 1;
+        }
+
+        let num_rows = ceil_div_usize(self.lut.len(), self.num_slots);
+        for i in 0..num_rows * self.num_slots {
+            let row = self.first_row + i / self.num_slots;
+            let slot = i % self.num_slots;
+            // Padding entries repeat the first entry, but are never counted.
+            let (inp, out) = self.lut.get(i).copied().unwrap_or(self.lut[0]);
+            let multiplicity = multiplicities.get(i).copied().unwrap_or(0);
+            out_buffer.set_target(
+                Target::wire(row, LookupTableGate::wire_ith_looked_inp(slot)),
+                F::from_canonical_u16(inp),
+            );
+            out_buffer.set_target(
+                Target::wire(row, LookupTableGate::wire_ith_looked_out(slot)),
+                F::from_canonical_u16(out),
+            );
+            out_buffer.set_target(
+                Target::wire(row, LookupTableGate::wire_ith_multiplicity(slot)),
+                F::from_canonical_usize(multiplicity),
+            );
+        }
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_lut(&self.lut)?;
+        dst.write_target_vec(&self.looking_inputs)?;
+        dst.write_usize(self.first_row)?;
+        dst.write_usize(self.num_slots)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let lut = src.read_lut()?;
+        let looking_inputs = src.read_target_vec()?;
+        let first_row = src.read_usize()?;
+        let num_slots = src.read_usize()?;
+        Ok(Self {
+            lut,
+            looking_inputs,
+            first_row,
+            num_slots,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::field::goldilocks_field::GoldilocksField;
+    use crate::gates::gate_testing::{test_eval_fns, test_low_degree};
+    use crate::gates::lookup_table::LookupTableGate;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    #[test]
+    fn low_degree() {
+        let gate = LookupTableGate::new_from_config(&CircuitConfig::standard_recursion_config());
+        test_low_degree::<GoldilocksField, _, 4>(gate)
+    }
+
+    #[test]
+    fn eval_fns() -> anyhow::Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let gate = LookupTableGate::new_from_config(&CircuitConfig::standard_recursion_config());
+        test_eval_fns::<F, C, _, D>(gate)
+    }
+}
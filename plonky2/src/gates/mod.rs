@@ -8,15 +8,22 @@ pub mod constant;
 pub mod coset_interpolation;
 pub mod exponentiation;
 pub mod gate;
+pub mod keccak_chi;
+pub mod keccak_column_parity;
+pub mod keccak_theta;
+pub mod lookup;
+pub mod lookup_table;
 pub mod multiplication_extension;
 pub mod noop;
 pub mod packed_util;
 pub mod poseidon;
+pub mod poseidon2;
 pub mod poseidon_mds;
 pub mod public_input;
 pub mod random_access;
 pub mod reducing;
 pub mod reducing_extension;
+pub mod rescue_prime;
 pub(crate) mod selectors;
 pub mod util;
 
@@ -1,4 +1,3 @@
-use alloc::boxed::Box;
 use alloc::string::String;
 use alloc::vec::Vec;
 use alloc::{format, vec};
@@ -14,12 +13,13 @@ use crate::hash::hashing::SPONGE_WIDTH;
 use crate::hash::poseidon;
 use crate::hash::poseidon::Poseidon;
 use crate::iop::ext_target::ExtensionTarget;
-use crate::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGenerator};
+use crate::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGeneratorRef};
 use crate::iop::target::Target;
 use crate::iop::wire::Wire;
 use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
 use crate::plonk::circuit_builder::CircuitBuilder;
 use crate::plonk::vars::{EvaluationTargets, EvaluationVars, EvaluationVarsBase};
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
 
 /// Evaluates a full Poseidon permutation with 12 state elements.
 ///
@@ -100,6 +100,14 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for PoseidonGate<F
         format!("{self:?}<WIDTH={SPONGE_WIDTH}>")
     }
 
+    fn serialize(&self, _dst: &mut Vec<u8>) -> IoResult<()> {
+        Ok(())
+    }
+
+    fn deserialize(_src: &mut Buffer) -> IoResult<Self> {
+        Ok(PoseidonGate::new())
+    }
+
     fn eval_unfiltered(&self, vars: EvaluationVars<F, D>) -> Vec<F::Extension> {
         let mut constraints = Vec::with_capacity(self.num_constraints());
 
@@ -373,12 +381,12 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for PoseidonGate<F
         constraints
     }
 
-    fn generators(&self, row: usize, _local_constants: &[F]) -> Vec<Box<dyn WitnessGenerator<F>>> {
+    fn generators(&self, row: usize, _local_constants: &[F]) -> Vec<WitnessGeneratorRef<F>> {
         let gen = PoseidonGenerator::<F, D> {
             row,
             _phantom: PhantomData,
         };
-        vec![Box::new(gen.adapter())]
+        vec![WitnessGeneratorRef::new(gen.adapter())]
     }
 
     fn num_wires(&self) -> usize {
@@ -400,10 +408,72 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for PoseidonGate<F
             + 1
             + 4
     }
+
+    fn export_solidity_verification_code(&self) -> Option<String> {
+        // The partial rounds are computed naively rather than with the optimized matrices of
+        // `eval_unfiltered`; both give the same S-box inputs, hence the same constraints.
+        let half_n_full_rounds = poseidon::HALF_N_FULL_ROUNDS;
+        let n_partial_rounds = poseidon::N_PARTIAL_ROUNDS;
+        Some(format!(
+            "
+Ext[12] memory state;
+bytes memory rc = POSEIDON_ROUND_CONSTANTS;
+Ext swap = w[{swap}];
+out[0] = swap * (swap - EXT_ONE);
+for (uint256 i = 0; i < 4; i++) {{
+    Ext delta = w[{delta} + i];
+    out[1 + i] = swap * (w[i + 4] - w[i]) - delta;
+    state[i] = w[i] + delta;
+    state[i + 4] = w[i + 4] - delta;
+}}
+for (uint256 i = 8; i < 12; i++) {{
+    state[i] = w[i];
+}}
+uint256 k = 5;
+for (uint256 r = 0; r < {half_n_full_rounds}; r++) {{
+    poseidonConstantLayer(state, rc, r);
+    for (uint256 i = 0; i < 12; i++) {{
+        if (r != 0) {{
+            Ext sboxIn = w[{full_0} + 12 * (r - 1) + i];
+            out[k++] = state[i] - sboxIn;
+            state[i] = sboxIn;
+        }}
+        state[i] = poseidonSbox(state[i]);
+    }}
+    poseidonMdsLayer(state);
+}}
+for (uint256 r = 0; r < {n_partial_rounds}; r++) {{
+    poseidonConstantLayer(state, rc, {half_n_full_rounds} + r);
+    Ext sboxIn = w[{partial} + r];
+    out[k++] = state[0] - sboxIn;
+    state[0] = poseidonSbox(sboxIn);
+    poseidonMdsLayer(state);
+}}
+for (uint256 r = 0; r < {half_n_full_rounds}; r++) {{
+    poseidonConstantLayer(state, rc, {second_full_rounds} + r);
+    for (uint256 i = 0; i < 12; i++) {{
+        Ext sboxIn = w[{full_1} + 12 * r + i];
+        out[k++] = state[i] - sboxIn;
+        state[i] = poseidonSbox(sboxIn);
+    }}
+    poseidonMdsLayer(state);
+}}
+for (uint256 i = 0; i < 12; i++) {{
+    out[k++] = state[i] - w[{output} + i];
+}}",
+            swap = Self::WIRE_SWAP,
+            delta = Self::wire_delta(0),
+            full_0 = Self::wire_full_sbox_0(1, 0),
+            partial = Self::wire_partial_sbox(0),
+            second_full_rounds = half_n_full_rounds + n_partial_rounds,
+            full_1 = Self::wire_full_sbox_1(0, 0),
+            output = Self::wire_output(0),
+        ))
+    }
 }
 
 #[derive(Debug)]
-struct PoseidonGenerator<F: RichField + Extendable<D> + Poseidon, const D: usize> {
+pub struct PoseidonGenerator<F: RichField + Extendable<D> + Poseidon, const D: usize> {
     row: usize,
     _phantom: PhantomData<F>,
 }
@@ -500,6 +570,18 @@ impl<F: RichField + Extendable<D> + Poseidon, const D: usize> SimpleGenerator<F>
             out_buffer.set_wire(local_wire(PoseidonGate::<F, D>::wire_output(i)), state[i]);
         }
     }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.row)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let row = src.read_usize()?;
+        Ok(Self {
+            row,
+            _phantom: PhantomData,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -1,4 +1,3 @@
-use alloc::boxed::Box;
 use alloc::string::String;
 use alloc::vec::Vec;
 use alloc::{format, vec};
@@ -14,7 +13,7 @@ use crate::gates::packed_util::PackedEvaluableBase;
 use crate::gates::util::StridedConstraintConsumer;
 use crate::hash::hash_types::RichField;
 use crate::iop::ext_target::ExtensionTarget;
-use crate::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGenerator};
+use crate::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGeneratorRef};
 use crate::iop::target::Target;
 use crate::iop::wire::Wire;
 use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
@@ -24,6 +23,7 @@ use crate::plonk::vars::{
     EvaluationTargets, EvaluationVars, EvaluationVarsBase, EvaluationVarsBaseBatch,
     EvaluationVarsBasePacked,
 };
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
 
 /// A gate for checking that a particular element of a list matches a given value.
 #[derive(Copy, Clone, Debug)]
@@ -122,6 +122,19 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for RandomAccessGa
         format!("{self:?}<D={D}>")
     }
 
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.bits)?;
+        dst.write_usize(self.num_copies)?;
+        dst.write_usize(self.num_extra_constants)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let bits = src.read_usize()?;
+        let num_copies = src.read_usize()?;
+        let num_extra_constants = src.read_usize()?;
+        Ok(Self::new(num_copies, bits, num_extra_constants))
+    }
+
     fn eval_unfiltered(&self, vars: EvaluationVars<F, D>) -> Vec<F::Extension> {
         let mut constraints = Vec::with_capacity(self.num_constraints());
 
@@ -238,18 +251,17 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for RandomAccessGa
         constraints
     }
 
-    fn generators(&self, row: usize, _local_constants: &[F]) -> Vec<Box<dyn WitnessGenerator<F>>> {
+    fn generators(&self, row: usize, _local_constants: &[F]) -> Vec<WitnessGeneratorRef<F>> {
         (0..self.num_copies)
             .map(|copy| {
-                let g: Box<dyn WitnessGenerator<F>> = Box::new(
+                WitnessGeneratorRef::new(
                     RandomAccessGenerator {
                         row,
                         gate: *self,
                         copy,
                     }
                     .adapter(),
-                );
-                g
+                )
             })
             .collect()
     }
@@ -276,6 +288,48 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for RandomAccessGa
             .map(|i| (i, self.wire_extra_constant(i)))
             .collect()
     }
+
+    fn export_solidity_verification_code(&self) -> Option<String> {
+        Some(format!(
+            "
+uint256 k = 0;
+for (uint256 copy = 0; copy < {num_copies}; copy++) {{
+    uint256 start = {copy_stride} * copy;
+    uint256 bitsStart = {bits_start} + {bits} * copy;
+    {{
+        Ext index = EXT_ZERO;
+        for (uint256 i = {bits}; i > 0; i--) {{
+            Ext b = w[bitsStart + i - 1];
+            out[k + i - 1] = b * (b - EXT_ONE);
+            index = index + index + b;
+        }}
+        k += {bits};
+        out[k++] = index - w[start];
+    }}
+    Ext[] memory items = new Ext[]({vec_size});
+    for (uint256 i = 0; i < {vec_size}; i++) {{
+        items[i] = w[start + 2 + i];
+    }}
+    for (uint256 i = 0; i < {bits}; i++) {{
+        Ext b = w[bitsStart + i];
+        for (uint256 j = 0; j < ({vec_size} >> (i + 1)); j++) {{
+            items[j] = items[2 * j] + b * (items[2 * j + 1] - items[2 * j]);
+        }}
+    }}
+    out[k++] = items[0] - w[start + 1];
+}}
+for (uint256 i = 0; i < {num_extra_constants}; i++) {{
+    out[k + i] = c[i] - w[{extra_start} + i];
+}}",
+            num_copies = self.num_copies,
+            copy_stride = 2 + self.vec_size(),
+            bits_start = self.num_routed_wires(),
+            bits = self.bits,
+            vec_size = self.vec_size(),
+            num_extra_constants = self.num_extra_constants,
+            extra_start = self.start_extra_constants(),
+        ))
+    }
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> PackedEvaluableBase<F, D>
@@ -326,7 +380,7 @@ impl<F: RichField + Extendable<D>, const D: usize> PackedEvaluableBase<F, D>
 }
 
 #[derive(Debug)]
-struct RandomAccessGenerator<F: RichField + Extendable<D>, const D: usize> {
+pub struct RandomAccessGenerator<F: RichField + Extendable<D>, const D: usize> {
     row: usize,
     gate: RandomAccessGate<F, D>,
     copy: usize,
@@ -376,6 +430,19 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F>
             set_local_wire(self.gate.wire_bit(i, copy), bit);
         }
     }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.row)?;
+        self.gate.serialize(dst)?;
+        dst.write_usize(self.copy)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let row = src.read_usize()?;
+        let gate = RandomAccessGate::<F, D>::deserialize(src)?;
+        let copy = src.read_usize()?;
+        Ok(Self { row, gate, copy })
+    }
 }
 
 #[cfg(test)]
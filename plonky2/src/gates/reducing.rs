@@ -1,4 +1,3 @@
-use alloc::boxed::Box;
 use alloc::string::String;
 use alloc::vec::Vec;
 use alloc::{format, vec};
@@ -9,11 +8,12 @@ use crate::gates::gate::Gate;
 use crate::gates::util::StridedConstraintConsumer;
 use crate::hash::hash_types::RichField;
 use crate::iop::ext_target::ExtensionTarget;
-use crate::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGenerator};
+use crate::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGeneratorRef};
 use crate::iop::target::Target;
 use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
 use crate::plonk::circuit_builder::CircuitBuilder;
 use crate::plonk::vars::{EvaluationTargets, EvaluationVars, EvaluationVarsBase};
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
 
 /// Computes `sum alpha^i c_i` for a vector `c_i` of `num_coeffs` elements of the base field.
 #[derive(Debug, Clone)]
@@ -60,6 +60,15 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for ReducingGate<D
         format!("{self:?}")
     }
 
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.num_coeffs)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let num_coeffs = src.read_usize()?;
+        Ok(Self::new(num_coeffs))
+    }
+
     fn eval_unfiltered(&self, vars: EvaluationVars<F, D>) -> Vec<F::Extension> {
         let alpha = vars.get_local_ext_algebra(Self::wires_alpha());
         let old_acc = vars.get_local_ext_algebra(Self::wires_old_acc());
@@ -137,8 +146,8 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for ReducingGate<D
             .collect()
     }
 
-    fn generators(&self, row: usize, _local_constants: &[F]) -> Vec<Box<dyn WitnessGenerator<F>>> {
-        vec![Box::new(
+    fn generators(&self, row: usize, _local_constants: &[F]) -> Vec<WitnessGeneratorRef<F>> {
+        vec![WitnessGeneratorRef::new(
             ReducingGenerator {
                 row,
                 gate: self.clone(),
@@ -162,10 +171,34 @@ impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for ReducingGate<D
     fn num_constraints(&self) -> usize {
         D * self.num_coeffs
     }
+
+    fn export_solidity_verification_code(&self) -> Option<String> {
+        Some(format!(
+            "
+Ext alpha0 = w[{alpha}];
+Ext alpha1 = w[{alpha} + 1];
+Ext acc0 = w[{old_acc}];
+Ext acc1 = w[{old_acc} + 1];
+for (uint256 i = 0; i < {n}; i++) {{
+    uint256 j = i == {n} - 1 ? {output} : {accs} + 2 * i;
+    (Ext p0, Ext p1) = algebraMul(acc0, acc1, alpha0, alpha1);
+    out[2 * i] = p0 + w[{coeffs} + i] - w[j];
+    out[2 * i + 1] = p1 - w[j + 1];
+    acc0 = w[j];
+    acc1 = w[j + 1];
+}}",
+            alpha = Self::wires_alpha().start,
+            old_acc = Self::wires_old_acc().start,
+            n = self.num_coeffs,
+            output = Self::wires_output().start,
+            accs = self.start_accs(),
+            coeffs = Self::START_COEFFS,
+        ))
+    }
 }
 
 #[derive(Debug)]
-struct ReducingGenerator<const D: usize> {
+pub struct ReducingGenerator<const D: usize> {
     row: usize,
     gate: ReducingGate<D>,
 }
@@ -207,6 +240,17 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F> for Reduci
         }
         out_buffer.set_extension_target(output, acc);
     }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.row)?;
+        <ReducingGate<D> as Gate<F, D>>::serialize(&self.gate, dst)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let row = src.read_usize()?;
+        let gate = <ReducingGate<D> as Gate<F, D>>::deserialize(src)?;
+        Ok(Self { row, gate })
+    }
 }
 
 #[cfg(test)]
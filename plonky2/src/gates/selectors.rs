@@ -4,6 +4,7 @@ use core::ops::Range;
 
 use crate::field::extension::Extendable;
 use crate::field::polynomial::PolynomialValues;
+use crate::field::types::Field;
 use crate::gates::gate::{GateInstance, GateRef};
 use crate::hash::hash_types::RichField;
 
@@ -110,3 +111,61 @@ pub(crate) fn selector_polynomials<F: RichField + Extendable<D>, const D: usize>
         },
     )
 }
+
+/// The selector polynomials used by the lookup argument. When a circuit has lookups, they are
+/// stored after the other constant polynomials, in this order.
+pub(crate) enum LookupSelectors {
+    /// 1 on `LookupGate` rows.
+    Lookup = 0,
+    /// 1 on `LookupTableGate` rows, except the last one.
+    TableTransition,
+    /// 1 on the last `LookupTableGate` row.
+    TableLast,
+    /// 1 on the first `LookupTableGate` row.
+    TableFirst,
+    /// The index of the table used by each `LookupGate` and `LookupTableGate` row.
+    Tag,
+}
+
+pub(crate) const NUM_LOOKUP_SELECTORS: usize = 5;
+
+/// Returns the lookup selector polynomials, given the `(row, lut_index)` pairs of the `LookupGate`
+/// rows, and the `LookupTableGate` rows of each table. The table rows must be consecutive.
+pub(crate) fn lookup_selector_polynomials<F: Field>(
+    n: usize,
+    lookup_rows: &[(usize, usize)],
+    table_rows: &[Range<usize>],
+) -> Vec<PolynomialValues<F>> {
+    let mut polynomials = vec![PolynomialValues::zero(n); NUM_LOOKUP_SELECTORS];
+    let mut set = |selector: LookupSelectors, row: usize, value: F| {
+        polynomials[selector as usize].values[row] = value;
+    };
+
+    for &(row, lut_index) in lookup_rows {
+        set(LookupSelectors::Lookup, row, F::ONE);
+        set(
+            LookupSelectors::Tag,
+            row,
+            F::from_canonical_usize(lut_index),
+        );
+    }
+
+    for (lut_index, rows) in table_rows.iter().enumerate() {
+        for row in rows.clone() {
+            set(LookupSelectors::TableTransition, row, F::ONE);
+            set(
+                LookupSelectors::Tag,
+                row,
+                F::from_canonical_usize(lut_index),
+            );
+        }
+    }
+    debug_assert!(table_rows.windows(2).all(|w| w[0].end == w[1].start));
+    let first_row = table_rows.first().expect("No lookup tables?").start;
+    let last_row = table_rows.last().unwrap().end - 1;
+    set(LookupSelectors::TableTransition, last_row, F::ZERO);
+    set(LookupSelectors::TableLast, last_row, F::ONE);
+    set(LookupSelectors::TableFirst, first_row, F::ONE);
+
+    polynomials
+}
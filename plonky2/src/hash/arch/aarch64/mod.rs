@@ -1,2 +1,4 @@
 #[cfg(target_feature = "neon")]
+pub(crate) mod poseidon2_goldilocks_neon;
+#[cfg(target_feature = "neon")]
 pub(crate) mod poseidon_goldilocks_neon;
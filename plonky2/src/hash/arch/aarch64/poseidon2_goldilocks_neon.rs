@@ -0,0 +1,186 @@
+//! Poseidon2 over Goldilocks for NEON. As in the Poseidon implementation, S-boxes and the
+//! multiplications by the diagonal of the internal layer are done in scalar, since vector 64-bit
+//! multiplications are slow. The external linear layer only multiplies by small constants, so it
+//! is computed in vector, by accumulating the products of the 32-bit halves of its inputs and
+//! reducing each output once.
+#![allow(clippy::assertions_on_constants)]
+
+use core::arch::aarch64::*;
+
+use static_assertions::const_assert;
+use unroll::unroll_for_loops;
+
+use crate::field::goldilocks_field::GoldilocksField;
+use crate::field::types::{Field, Field64, PrimeField64};
+use crate::hash::arch::aarch64::poseidon_goldilocks_neon::{
+    add_with_wraparound, multiply, sbox_layer_full,
+};
+use crate::hash::poseidon2::{
+    Poseidon2, HALF_N_FULL_ROUNDS, M4, N_FULL_ROUNDS_TOTAL, N_PARTIAL_ROUNDS,
+};
+
+const WIDTH: usize = 12;
+
+// ========================================== CONSTANTS ===========================================
+
+/// The entries of the external linear layer `circ(2 M4, M4, M4)`, by pairs of consecutive rows:
+/// `EXTERNAL_MATRIX_ROW_PAIRS[p][j]` holds the coefficients of input `j` in outputs `2p` and
+/// `2p + 1`.
+const EXTERNAL_MATRIX_ROW_PAIRS: [[[u32; 2]; WIDTH]; WIDTH / 2] = make_external_matrix_row_pairs();
+
+const fn make_external_matrix_row_pairs() -> [[[u32; 2]; WIDTH]; WIDTH / 2] {
+    let mut res = [[[0; 2]; WIDTH]; WIDTH / 2];
+    let mut p = 0;
+    while p < WIDTH / 2 {
+        let chunk = 2 * p / 4;
+        let r = 2 * p % 4;
+        let mut j = 0;
+        while j < WIDTH {
+            let weight = if j / 4 == chunk { 2 } else { 1 };
+            res[p][j] = [
+                (M4[r][j % 4] * weight) as u32,
+                (M4[r + 1][j % 4] * weight) as u32,
+            ];
+            j += 1;
+        }
+        p += 1;
+    }
+    res
+}
+
+// ===================================== COMPILE-TIME CHECKS ======================================
+
+/// Ensure that the round constants are in canonical form, as `add_with_wraparound` requires.
+#[allow(dead_code)]
+const fn check_round_const_bounds() -> bool {
+    let mut r = 0;
+    while r < N_FULL_ROUNDS_TOTAL {
+        let mut i = 0;
+        while i < WIDTH {
+            if <GoldilocksField as Poseidon2>::EXTERNAL_ROUND_CONSTANTS[r][i]
+                >= GoldilocksField::ORDER
+            {
+                return false;
+            }
+            i += 1;
+        }
+        r += 1;
+    }
+    let mut r = 0;
+    while r < N_PARTIAL_ROUNDS {
+        if <GoldilocksField as Poseidon2>::INTERNAL_ROUND_CONSTANTS[r] >= GoldilocksField::ORDER {
+            return false;
+        }
+        r += 1;
+    }
+    true
+}
+const_assert!(check_round_const_bounds());
+
+// ====================================== VECTOR ARITHMETIC =======================================
+
+/// Lane-wise addition modulo ORDER accounting for wraparound. Correct only when
+/// a + b < 2**64 + ORDER.
+#[inline(always)]
+unsafe fn add_with_wraparound_vec(a: uint64x2_t, b: uint64x2_t) -> uint64x2_t {
+    let res = vaddq_u64(a, b);
+    let wraparound_mask = vcgtq_u64(a, res);
+    vsraq_n_u64::<32>(res, wraparound_mask) // Add EPSILON on wraparound.
+}
+
+/// Reduces `lo + hi * 2**32` lane-wise, to a not necessarily canonical `u64`.
+#[inline(always)]
+unsafe fn reduce_halves(lo: uint64x2_t, hi: uint64x2_t) -> uint64x2_t {
+    // Split `hi` into `hi_lo + hi_hi * 2**32`, so that the result is
+    // `lo + hi_lo * 2**32 + hi_hi * EPSILON (mod Goldilocks)`.
+    let hi_lo_shifted = vshlq_n_u64::<32>(hi);
+    let hi_hi = vshrq_n_u64::<32>(hi);
+    let hi_hi_mul_epsilon = vsubq_u64(vshlq_n_u64::<32>(hi_hi), hi_hi);
+    // Both additions are safe: `hi_lo_shifted <= 2**64 - 2**32`, and
+    // `hi_hi_mul_epsilon < EPSILON**2 < ORDER`.
+    let res = add_with_wraparound_vec(lo, hi_lo_shifted);
+    add_with_wraparound_vec(res, hi_hi_mul_epsilon)
+}
+
+// ======================================== LINEAR LAYERS ==========================================
+
+/// The external linear layer. The coefficients of each output sum to at most 64, so accumulating
+/// the products of the 32-bit halves of the inputs can't overflow.
+#[inline(always)]
+#[unroll_for_loops]
+unsafe fn external_linear_layer(state: &mut [u64; WIDTH]) {
+    let lo = state.map(|x| x as u32);
+    let hi = state.map(|x| (x >> 32) as u32);
+    for p in 0..6 {
+        let mut acc_lo = vdupq_n_u64(0);
+        let mut acc_hi = vdupq_n_u64(0);
+        for j in 0..12 {
+            let coeffs = vld1_u32(EXTERNAL_MATRIX_ROW_PAIRS[p][j].as_ptr());
+            acc_lo = vmlal_n_u32(acc_lo, coeffs, lo[j]);
+            acc_hi = vmlal_n_u32(acc_hi, coeffs, hi[j]);
+        }
+        vst1q_u64(state[2 * p..].as_mut_ptr(), reduce_halves(acc_lo, acc_hi));
+    }
+}
+
+/// The internal linear layer, `x_i -> sum(x) + INTERNAL_MATRIX_DIAG_M_1[i] * x_i`.
+#[inline(always)]
+#[unroll_for_loops]
+unsafe fn internal_linear_layer(state: &mut [u64; WIDTH]) {
+    let mut sum = 0u128;
+    for i in 0..12 {
+        sum += state[i] as u128;
+    }
+    let sum = GoldilocksField::from_noncanonical_u128(sum).to_canonical_u64();
+    for i in 0..12 {
+        let d = <GoldilocksField as Poseidon2>::INTERNAL_MATRIX_DIAG_M_1[i];
+        // add_with_wraparound is safe, as sum is in canonical form.
+        state[i] = add_with_wraparound(multiply(state[i], d), sum);
+    }
+}
+
+// ============================================ ROUNDS ============================================
+
+#[inline(always)]
+#[unroll_for_loops]
+unsafe fn full_round(state: &mut [u64; WIDTH], round: usize) {
+    let round_constants = &<GoldilocksField as Poseidon2>::EXTERNAL_ROUND_CONSTANTS[round];
+    for i in 0..12 {
+        // add_with_wraparound is safe, because the round constants are in canonical form.
+        state[i] = add_with_wraparound(state[i], round_constants[i]);
+    }
+    *state = sbox_layer_full(*state);
+    external_linear_layer(state);
+}
+
+#[inline(always)]
+unsafe fn partial_round(state: &mut [u64; WIDTH], round: usize) {
+    let round_constant = <GoldilocksField as Poseidon2>::INTERNAL_ROUND_CONSTANTS[round];
+    // add_with_wraparound is safe, because the round constants are in canonical form.
+    let x = add_with_wraparound(state[0], round_constant);
+    let x2 = multiply(x, x);
+    let x3 = multiply(x, x2);
+    let x4 = multiply(x2, x2);
+    state[0] = multiply(x3, x4);
+    internal_linear_layer(state);
+}
+
+// ========================================== GLUE CODE ===========================================
+
+#[inline]
+pub unsafe fn poseidon2(input: &[GoldilocksField; WIDTH]) -> [GoldilocksField; WIDTH] {
+    let mut state = input.map(|x| x.0);
+
+    external_linear_layer(&mut state);
+    for r in 0..HALF_N_FULL_ROUNDS {
+        full_round(&mut state, r);
+    }
+    for r in 0..N_PARTIAL_ROUNDS {
+        partial_round(&mut state, r);
+    }
+    for r in HALF_N_FULL_ROUNDS..2 * HALF_N_FULL_ROUNDS {
+        full_round(&mut state, r);
+    }
+
+    state.map(GoldilocksField)
+}
@@ -75,7 +75,7 @@ const_assert!(check_round_const_bounds_init());
 
 /// Addition modulo ORDER accounting for wraparound. Correct only when a + b < 2**64 + ORDER.
 #[inline(always)]
-unsafe fn add_with_wraparound(a: u64, b: u64) -> u64 {
+pub(crate) unsafe fn add_with_wraparound(a: u64, b: u64) -> u64 {
     let res: u64;
     let adj: u64;
     asm!(
@@ -136,7 +136,7 @@ unsafe fn mul_epsilon(x: u64) -> u64 {
 }
 
 #[inline(always)]
-unsafe fn multiply(x: u64, y: u64) -> u64 {
+pub(crate) unsafe fn multiply(x: u64, y: u64) -> u64 {
     let xy = (x as u128) * (y as u128);
     let xy_lo = xy as u64;
     let xy_hi = (xy >> 64) as u64;
@@ -174,7 +174,7 @@ unsafe fn const_layer_full(
 /// Full S-box.
 #[inline(always)]
 #[unroll_for_loops]
-unsafe fn sbox_layer_full(state: [u64; WIDTH]) -> [u64; WIDTH] {
+pub(crate) unsafe fn sbox_layer_full(state: [u64; WIDTH]) -> [u64; WIDTH] {
     // This is done in scalar. S-boxes in vector are only slightly slower throughput-wise but have
     // an insane latency (~100 cycles) on the M1.
 
@@ -0,0 +1,96 @@
+//! Poseidon2 over Goldilocks, vectorized with the AVX2 packed Goldilocks field. The state is
+//! viewed as three vectors of four elements, which matches the chunks of the external linear
+//! layer: round constants, S-boxes and the diagonal of the internal layer are applied vertically,
+//! and only the 4x4 `M4` products and the internal sum are computed per element.
+
+use crate::field::goldilocks_field::GoldilocksField;
+use crate::field::ops::Square;
+use crate::field::packable::Packable;
+use crate::field::packed::PackedField;
+use crate::field::types::{Field, PrimeField64};
+use crate::hash::poseidon2::{Poseidon2, HALF_N_FULL_ROUNDS, M4, N_PARTIAL_ROUNDS};
+
+type F = GoldilocksField;
+type P = <F as Packable>::Packing;
+
+const WIDTH: usize = 12;
+
+#[inline(always)]
+fn to_field<const N: usize>(values: &[u64; N]) -> [F; N] {
+    values.map(F::from_canonical_u64)
+}
+
+#[inline(always)]
+fn sbox_packed(x: P) -> P {
+    // x |--> x^7
+    let x2 = x.square();
+    let x4 = x2.square();
+    let x3 = x * x2;
+    x3 * x4
+}
+
+/// `M4` applied to a chunk of four elements. The entries of `M4` are at most 7, so the sums fit in
+/// a `u128` without intermediate reductions.
+#[inline(always)]
+fn m4(chunk: &mut [F]) {
+    let x = [0, 1, 2, 3].map(|i| chunk[i].to_noncanonical_u64() as u128);
+    for r in 0..4 {
+        let y = (0..4).map(|i| x[i] * (M4[r][i] as u128)).sum::<u128>();
+        chunk[r] = F::from_noncanonical_u128(y);
+    }
+}
+
+/// The external linear layer `circ(2 M4, M4, M4)`, computed as `M4(x_c + x_0 + x_1 + x_2)` for
+/// each chunk `x_c`.
+#[inline(always)]
+fn external_linear_layer(state: &mut [F; WIDTH]) {
+    let packed = P::pack_slice_mut(state);
+    let sum = packed[0] + packed[1] + packed[2];
+    for x in packed.iter_mut() {
+        *x += sum;
+        m4(x.as_slice_mut());
+    }
+}
+
+#[inline(always)]
+fn full_round(state: &mut [F; WIDTH], round: usize) {
+    let rc = to_field(&F::EXTERNAL_ROUND_CONSTANTS[round]);
+    for (x, rc) in P::pack_slice_mut(state).iter_mut().zip(P::pack_slice(&rc)) {
+        *x = sbox_packed(*x + *rc);
+    }
+    external_linear_layer(state);
+}
+
+#[inline(always)]
+fn partial_round(state: &mut [F; WIDTH], diag: &[F; WIDTH], round: usize) {
+    state[0] += F::from_canonical_u64(F::INTERNAL_ROUND_CONSTANTS[round]);
+    state[0] = <F as Poseidon2>::sbox_monomial(state[0]);
+
+    let sum = state
+        .iter()
+        .map(|x| x.to_noncanonical_u64() as u128)
+        .sum::<u128>();
+    let sum = P::from(F::from_noncanonical_u128(sum));
+    for (x, d) in P::pack_slice_mut(state).iter_mut().zip(P::pack_slice(diag)) {
+        *x = *x * *d + sum;
+    }
+}
+
+#[inline]
+pub fn poseidon2(input: &[F; WIDTH]) -> [F; WIDTH] {
+    let mut state = *input;
+    let diag = to_field(&F::INTERNAL_MATRIX_DIAG_M_1);
+
+    external_linear_layer(&mut state);
+    for r in 0..HALF_N_FULL_ROUNDS {
+        full_round(&mut state, r);
+    }
+    for r in 0..N_PARTIAL_ROUNDS {
+        partial_round(&mut state, &diag, r);
+    }
+    for r in HALF_N_FULL_ROUNDS..2 * HALF_N_FULL_ROUNDS {
+        full_round(&mut state, r);
+    }
+
+    state
+}
@@ -1,5 +1,7 @@
 use alloc::vec::Vec;
 use core::mem::MaybeUninit;
+#[cfg(feature = "disk_storage")]
+use core::ops::{Deref, Index};
 use core::slice;
 
 use plonky2_maybe_rayon::*;
@@ -8,6 +10,8 @@ use serde::{Deserialize, Serialize};
 use crate::hash::hash_types::RichField;
 use crate::hash::merkle_proofs::MerkleProof;
 use crate::plonk::config::{GenericHashOut, Hasher};
+#[cfg(feature = "disk_storage")]
+use crate::util::disk_vec::DiskVec;
 use crate::util::log2_strict;
 
 /// The Merkle cap of height `h` of a Merkle tree is the `h`-th layer (from the root) of the tree.
@@ -35,10 +39,198 @@ impl<F: RichField, H: Hasher<F>> MerkleCap<F, H> {
     }
 }
 
+/// The leaves of a Merkle tree, one vector per leaf.
+#[cfg(not(feature = "disk_storage"))]
+pub type MerkleLeaves<F> = Vec<Vec<F>>;
+
+/// The leaves of a Merkle tree.
+#[cfg(feature = "disk_storage")]
+#[derive(Debug)]
+pub enum MerkleLeaves<F> {
+    /// One vector per leaf, in memory.
+    Memory(Vec<Vec<F>>),
+    /// The concatenation of all leaves, each of `leaf_len` elements, in a file-backed buffer.
+    Disk { values: DiskVec<F>, leaf_len: usize },
+}
+
+#[cfg(feature = "disk_storage")]
+impl<F> MerkleLeaves<F> {
+    pub fn len(&self) -> usize {
+        self.view().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &[F]> {
+        let view = self.view();
+        (0..view.len()).map(move |i| view.get(i))
+    }
+
+    fn view(&self) -> LeavesView<F> {
+        match self {
+            Self::Memory(leaves) => LeavesView::Nested(leaves),
+            Self::Disk { values, leaf_len } => LeavesView::Flat {
+                values,
+                leaf_len: *leaf_len,
+            },
+        }
+    }
+}
+
+#[cfg(feature = "disk_storage")]
+impl<F: Copy> MerkleLeaves<F> {
+    /// Like `clone`, but keeps disk-backed leaves on disk, in a new file next to the current one.
+    pub fn try_clone(&self) -> std::io::Result<Self> {
+        Ok(match self {
+            Self::Memory(leaves) => Self::Memory(leaves.clone()),
+            Self::Disk { values, leaf_len } => Self::Disk {
+                values: values.try_clone()?,
+                leaf_len: *leaf_len,
+            },
+        })
+    }
+}
+
+/// Cloning disk-backed leaves copies them into memory, which can't fail like creating a file can.
+/// See [`try_clone`](Self::try_clone) to keep them on disk.
+#[cfg(feature = "disk_storage")]
+impl<F: Copy> Clone for MerkleLeaves<F> {
+    fn clone(&self) -> Self {
+        Self::Memory(self.iter().map(<[F]>::to_vec).collect())
+    }
+}
+
+#[cfg(feature = "disk_storage")]
+impl<F> Index<usize> for MerkleLeaves<F> {
+    type Output = [F];
+
+    fn index(&self, i: usize) -> &[F] {
+        self.view().get(i)
+    }
+}
+
+/// The digests of a Merkle tree, see [`MerkleTree::digests`].
+#[cfg(not(feature = "disk_storage"))]
+pub type MerkleDigests<T> = Vec<T>;
+
+/// The digests of a Merkle tree, see [`MerkleTree::digests`].
+#[cfg(feature = "disk_storage")]
+#[derive(Debug)]
+pub enum MerkleDigests<T> {
+    Memory(Vec<T>),
+    Disk(DiskVec<T>),
+}
+
+#[cfg(feature = "disk_storage")]
+impl<T: Copy> MerkleDigests<T> {
+    /// Like `clone`, but keeps disk-backed digests on disk, in a new file next to the current one.
+    pub fn try_clone(&self) -> std::io::Result<Self> {
+        Ok(match self {
+            Self::Memory(digests) => Self::Memory(digests.clone()),
+            Self::Disk(digests) => Self::Disk(digests.try_clone()?),
+        })
+    }
+}
+
+/// Cloning disk-backed digests copies them into memory, which can't fail like creating a file
+/// can. See [`try_clone`](Self::try_clone) to keep them on disk.
+#[cfg(feature = "disk_storage")]
+impl<T: Copy> Clone for MerkleDigests<T> {
+    fn clone(&self) -> Self {
+        Self::Memory(self.to_vec())
+    }
+}
+
+#[cfg(feature = "disk_storage")]
+impl<T> Deref for MerkleDigests<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match self {
+            Self::Memory(digests) => digests,
+            Self::Disk(digests) => digests,
+        }
+    }
+}
+
+/// A borrowed range of leaves, in either layout of [`MerkleLeaves`].
+enum LeavesView<'a, F> {
+    Nested(&'a [Vec<F>]),
+    #[cfg(feature = "disk_storage")]
+    Flat {
+        values: &'a [F],
+        leaf_len: usize,
+    },
+}
+
+impl<'a, F> Clone for LeavesView<'a, F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, F> Copy for LeavesView<'a, F> {}
+
+impl<'a, F> LeavesView<'a, F> {
+    fn len(self) -> usize {
+        match self {
+            Self::Nested(leaves) => leaves.len(),
+            #[cfg(feature = "disk_storage")]
+            Self::Flat { values, leaf_len } => values.len() / leaf_len,
+        }
+    }
+
+    fn get(self, i: usize) -> &'a [F] {
+        match self {
+            Self::Nested(leaves) => &leaves[i],
+            #[cfg(feature = "disk_storage")]
+            Self::Flat { values, leaf_len } => &values[i * leaf_len..(i + 1) * leaf_len],
+        }
+    }
+
+    fn split_at(self, mid: usize) -> (Self, Self) {
+        match self {
+            Self::Nested(leaves) => {
+                let (left, right) = leaves.split_at(mid);
+                (Self::Nested(left), Self::Nested(right))
+            }
+            #[cfg(feature = "disk_storage")]
+            Self::Flat { values, leaf_len } => {
+                let (left, right) = values.split_at(mid * leaf_len);
+                (
+                    Self::Flat {
+                        values: left,
+                        leaf_len,
+                    },
+                    Self::Flat {
+                        values: right,
+                        leaf_len,
+                    },
+                )
+            }
+        }
+    }
+
+    /// Splits the leaves into consecutive chunks of `chunk_len` leaves.
+    fn chunks(self, chunk_len: usize) -> Vec<Self> {
+        let mut chunks = Vec::with_capacity(self.len() / chunk_len);
+        let mut rest = self;
+        while rest.len() > 0 {
+            let (chunk, tail) = rest.split_at(chunk_len);
+            chunks.push(chunk);
+            rest = tail;
+        }
+        chunks
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct MerkleTree<F: RichField, H: Hasher<F>> {
-    /// The data in the leaves of the Merkle tree.
-    pub leaves: Vec<Vec<F>>,
+    /// The data in the leaves of the Merkle tree. Without the `disk_storage` feature, this is a
+    /// `Vec<Vec<F>>`.
+    pub leaves: MerkleLeaves<F>,
 
     /// The digests in the tree. Consists of `cap.len()` sub-trees, each corresponding to one
     /// element in `cap`. Each subtree is contiguous and located at
@@ -48,7 +240,8 @@ pub struct MerkleTree<F: RichField, H: Hasher<F>> {
     /// left_child_digest and right_child_digest are H::Hash and left_child_subtree and
     /// right_child_subtree recurse. Observe that the digest of a node is stored by its _parent_.
     /// Consequently, the digests of the roots are not stored here (they can be found in `cap`).
-    pub digests: Vec<H::Hash>,
+    /// Without the `disk_storage` feature, this is a `Vec<H::Hash>`.
+    pub digests: MerkleDigests<H::Hash>,
 
     /// The Merkle cap.
     pub cap: MerkleCap<F, H>,
@@ -68,11 +261,11 @@ fn capacity_up_to_mut<T>(v: &mut Vec<T>, len: usize) -> &mut [MaybeUninit<T>] {
 
 fn fill_subtree<F: RichField, H: Hasher<F>>(
     digests_buf: &mut [MaybeUninit<H::Hash>],
-    leaves: &[Vec<F>],
+    leaves: LeavesView<F>,
 ) -> H::Hash {
     assert_eq!(leaves.len(), digests_buf.len() / 2 + 1);
     if digests_buf.is_empty() {
-        H::hash_or_noop(&leaves[0])
+        H::hash_or_noop(leaves.get(0))
     } else {
         // Layout is: left recursive output || left child digest
         //             || right child digest || right recursive output.
@@ -98,7 +291,7 @@ fn fill_subtree<F: RichField, H: Hasher<F>>(
 fn fill_digests_buf<F: RichField, H: Hasher<F>>(
     digests_buf: &mut [MaybeUninit<H::Hash>],
     cap_buf: &mut [MaybeUninit<H::Hash>],
-    leaves: &[Vec<F>],
+    leaves: LeavesView<F>,
     cap_height: usize,
 ) {
     // Special case of a tree that's all cap. The usual case will panic because we'll try to split
@@ -106,19 +299,16 @@ fn fill_digests_buf<F: RichField, H: Hasher<F>>(
     // `blah` chunks as opposed to chunks _of_ `blah`.)
     if digests_buf.is_empty() {
         debug_assert_eq!(cap_buf.len(), leaves.len());
-        cap_buf
-            .par_iter_mut()
-            .zip(leaves)
-            .for_each(|(cap_buf, leaf)| {
-                cap_buf.write(H::hash_or_noop(leaf));
-            });
+        cap_buf.par_iter_mut().enumerate().for_each(|(i, cap_buf)| {
+            cap_buf.write(H::hash_or_noop(leaves.get(i)));
+        });
         return;
     }
 
     let subtree_digests_len = digests_buf.len() >> cap_height;
     let subtree_leaves_len = leaves.len() >> cap_height;
     let digests_chunks = digests_buf.par_chunks_exact_mut(subtree_digests_len);
-    let leaves_chunks = leaves.par_chunks_exact(subtree_leaves_len);
+    let leaves_chunks = leaves.chunks(subtree_leaves_len);
     assert_eq!(digests_chunks.len(), cap_buf.len());
     assert_eq!(digests_chunks.len(), leaves_chunks.len());
     digests_chunks.zip(cap_buf).zip(leaves_chunks).for_each(
@@ -133,36 +323,87 @@ fn fill_digests_buf<F: RichField, H: Hasher<F>>(
 
 impl<F: RichField, H: Hasher<F>> MerkleTree<F, H> {
     pub fn new(leaves: Vec<Vec<F>>, cap_height: usize) -> Self {
-        let log2_leaves_len = log2_strict(leaves.len());
+        let num_digests = Self::num_digests(leaves.len(), cap_height);
+        let mut digests = Vec::with_capacity(num_digests);
+        let digests_buf = capacity_up_to_mut(&mut digests, num_digests);
+        let cap = Self::fill(digests_buf, LeavesView::Nested(&leaves), cap_height);
+
+        unsafe {
+            // SAFETY: `fill` initialized the spare capacity up to `num_digests`.
+            digests.set_len(num_digests);
+        }
+
+        Self::from_memory(leaves, digests, cap)
+    }
+
+    /// Assembles a tree from leaves and digests held in memory.
+    pub(crate) fn from_memory(
+        leaves: Vec<Vec<F>>,
+        digests: Vec<H::Hash>,
+        cap: MerkleCap<F, H>,
+    ) -> Self {
+        #[cfg(feature = "disk_storage")]
+        let (leaves, digests) = (MerkleLeaves::Memory(leaves), MerkleDigests::Memory(digests));
+        Self {
+            leaves,
+            digests,
+            cap,
+        }
+    }
+
+    /// Builds a Merkle tree whose leaves are the consecutive chunks of `leaf_len` elements of
+    /// `values`. The digests are stored in a file-backed buffer next to `values`, so that neither
+    /// needs to fit in memory.
+    #[cfg(feature = "disk_storage")]
+    pub fn new_on_disk(
+        values: DiskVec<F>,
+        leaf_len: usize,
+        cap_height: usize,
+    ) -> std::io::Result<Self> {
+        assert!(leaf_len > 0 && values.len() % leaf_len == 0);
+        let leaves = LeavesView::Flat {
+            values: &values,
+            leaf_len,
+        };
+        let num_digests = Self::num_digests(leaves.len(), cap_height);
+        let mut digests = DiskVec::<H::Hash>::new_uninit(values.dir(), num_digests)?;
+        let cap = Self::fill(&mut digests, leaves, cap_height);
+
+        Ok(Self {
+            leaves: MerkleLeaves::Disk { values, leaf_len },
+            // SAFETY: `fill` initialized all the digests.
+            digests: MerkleDigests::Disk(unsafe { digests.assume_init() }),
+            cap,
+        })
+    }
+
+    fn num_digests(num_leaves: usize, cap_height: usize) -> usize {
+        let log2_leaves_len = log2_strict(num_leaves);
         assert!(
             cap_height <= log2_leaves_len,
             "cap_height={} should be at most log2(leaves.len())={}",
             cap_height,
             log2_leaves_len
         );
+        2 * (num_leaves - (1 << cap_height))
+    }
 
-        let num_digests = 2 * (leaves.len() - (1 << cap_height));
-        let mut digests = Vec::with_capacity(num_digests);
-
+    /// Fills `digests_buf` with the digests of the tree, and returns its cap.
+    fn fill(
+        digests_buf: &mut [MaybeUninit<H::Hash>],
+        leaves: LeavesView<F>,
+        cap_height: usize,
+    ) -> MerkleCap<F, H> {
         let len_cap = 1 << cap_height;
         let mut cap = Vec::with_capacity(len_cap);
-
-        let digests_buf = capacity_up_to_mut(&mut digests, num_digests);
         let cap_buf = capacity_up_to_mut(&mut cap, len_cap);
-        fill_digests_buf::<F, H>(digests_buf, cap_buf, &leaves[..], cap_height);
+        fill_digests_buf::<F, H>(digests_buf, cap_buf, leaves, cap_height);
 
         unsafe {
-            // SAFETY: `fill_digests_buf` and `cap` initialized the spare capacity up to
-            // `num_digests` and `len_cap`, resp.
-            digests.set_len(num_digests);
+            // SAFETY: `fill_digests_buf` initialized the spare capacity up to `len_cap`.
             cap.set_len(len_cap);
         }
-
-        Self {
-            leaves,
-            digests,
-            cap: MerkleCap(cap),
-        }
+        MerkleCap(cap)
     }
 
     pub fn get(&self, i: usize) -> &[F] {
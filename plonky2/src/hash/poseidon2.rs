@@ -0,0 +1,398 @@
+//! Implementation of the Poseidon2 permutation, as described in
+//! <https://eprint.iacr.org/2023/323.pdf>
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use unroll::unroll_for_loops;
+
+use crate::field::extension::{Extendable, FieldExtension};
+use crate::field::types::{Field, PrimeField64};
+use crate::gates::poseidon2::Poseidon2Gate;
+use crate::hash::hash_types::{HashOut, RichField};
+use crate::hash::hashing::{compress, hash_n_to_hash_no_pad, PlonkyPermutation, SPONGE_WIDTH};
+use crate::iop::ext_target::ExtensionTarget;
+use crate::iop::target::{BoolTarget, Target};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::config::{AlgebraicHasher, Hasher};
+
+// We use the same number of rounds and the same S-box `x^7` as for Poseidon, which gives the
+// same security level for width 12 over Goldilocks.
+pub const HALF_N_FULL_ROUNDS: usize = 4;
+pub(crate) const N_FULL_ROUNDS_TOTAL: usize = 2 * HALF_N_FULL_ROUNDS;
+pub const N_PARTIAL_ROUNDS: usize = 22;
+pub const N_ROUNDS: usize = N_FULL_ROUNDS_TOTAL + N_PARTIAL_ROUNDS;
+
+const WIDTH: usize = SPONGE_WIDTH;
+
+/// The 4x4 MDS matrix from which the external linear layer is built. Its entries are small, so
+/// products can be accumulated without intermediate reductions.
+pub(crate) const M4: [[u64; 4]; 4] = [[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]];
+
+pub trait Poseidon2: PrimeField64 {
+    /// The round constants of the full rounds, first and second halves concatenated.
+    const EXTERNAL_ROUND_CONSTANTS: [[u64; WIDTH]; N_FULL_ROUNDS_TOTAL];
+
+    /// The round constants of the partial rounds, which are only added to the first element.
+    const INTERNAL_ROUND_CONSTANTS: [u64; N_PARTIAL_ROUNDS];
+
+    /// The internal linear layer is `J + diag(INTERNAL_MATRIX_DIAG_M_1)`, where `J` is the
+    /// all-ones matrix.
+    const INTERNAL_MATRIX_DIAG_M_1: [u64; WIDTH];
+
+    /// Applies the external linear layer `circ(2 M4, M4, M4)`, i.e. `M4` on each chunk of four
+    /// elements, followed by adding to each element the sum of the elements at the same position
+    /// in all chunks.
+    #[inline(always)]
+    #[unroll_for_loops]
+    fn external_linear_layer(state: &mut [Self; WIDTH]) {
+        // The entries of `M4` are at most 7, so the sums below fit comfortably in a `u128`.
+        let mut products = [0u128; WIDTH];
+        for c in 0..3 {
+            for r in 0..4 {
+                for i in 0..4 {
+                    products[4 * c + r] +=
+                        (state[4 * c + i].to_noncanonical_u64() as u128) * (M4[r][i] as u128);
+                }
+            }
+        }
+
+        let mut sums = [0u128; 4];
+        for r in 0..4 {
+            for c in 0..3 {
+                sums[r] += products[4 * c + r];
+            }
+        }
+
+        for i in 0..12 {
+            state[i] = Self::from_noncanonical_u128(products[i] + sums[i % 4]);
+        }
+    }
+
+    /// Same as `external_linear_layer` for field extensions of `Self`.
+    fn external_linear_layer_field<F: FieldExtension<D, BaseField = Self>, const D: usize>(
+        state: &mut [F; WIDTH],
+    ) {
+        let mut products = [F::ZERO; WIDTH];
+        for c in 0..3 {
+            for r in 0..4 {
+                for i in 0..4 {
+                    products[4 * c + r] += state[4 * c + i] * F::from_canonical_u64(M4[r][i]);
+                }
+            }
+        }
+
+        for i in 0..WIDTH {
+            let r = i % 4;
+            state[i] = products[i] + products[r] + products[4 + r] + products[8 + r];
+        }
+    }
+
+    /// Recursive version of `external_linear_layer`.
+    fn external_linear_layer_circuit<const D: usize>(
+        builder: &mut CircuitBuilder<Self, D>,
+        state: &mut [ExtensionTarget<D>; WIDTH],
+    ) where
+        Self: RichField + Extendable<D>,
+    {
+        let mut products = [builder.zero_extension(); WIDTH];
+        for c in 0..3 {
+            for r in 0..4 {
+                for i in 0..4 {
+                    let m = Self::from_canonical_u64(M4[r][i]);
+                    products[4 * c + r] =
+                        builder.mul_const_add_extension(m, state[4 * c + i], products[4 * c + r]);
+                }
+            }
+        }
+
+        for i in 0..WIDTH {
+            let r = i % 4;
+            let sum = builder.add_many_extension([products[r], products[4 + r], products[8 + r]]);
+            state[i] = builder.add_extension(products[i], sum);
+        }
+    }
+
+    /// Applies the internal linear layer, `x_i -> sum(x) + INTERNAL_MATRIX_DIAG_M_1[i] * x_i`.
+    #[inline(always)]
+    #[unroll_for_loops]
+    fn internal_linear_layer(state: &mut [Self; WIDTH]) {
+        let mut sum = 0u128;
+        for i in 0..12 {
+            sum += state[i].to_noncanonical_u64() as u128;
+        }
+        let sum = Self::from_noncanonical_u128(sum);
+
+        for i in 0..12 {
+            let d = Self::from_canonical_u64(Self::INTERNAL_MATRIX_DIAG_M_1[i]);
+            state[i] = sum.multiply_accumulate(state[i], d);
+        }
+    }
+
+    /// Same as `internal_linear_layer` for field extensions of `Self`.
+    fn internal_linear_layer_field<F: FieldExtension<D, BaseField = Self>, const D: usize>(
+        state: &mut [F; WIDTH],
+    ) {
+        let sum = state.iter().copied().sum::<F>();
+        for i in 0..WIDTH {
+            let d = F::from_canonical_u64(Self::INTERNAL_MATRIX_DIAG_M_1[i]);
+            state[i] = sum + state[i] * d;
+        }
+    }
+
+    /// Recursive version of `internal_linear_layer`.
+    fn internal_linear_layer_circuit<const D: usize>(
+        builder: &mut CircuitBuilder<Self, D>,
+        state: &mut [ExtensionTarget<D>; WIDTH],
+    ) where
+        Self: RichField + Extendable<D>,
+    {
+        let sum = builder.add_many_extension(*state);
+        for i in 0..WIDTH {
+            let d = Self::from_canonical_u64(<Self as Poseidon2>::INTERNAL_MATRIX_DIAG_M_1[i]);
+            state[i] = builder.mul_const_add_extension(d, state[i], sum);
+        }
+    }
+
+    /// Adds the round constants of the `round`-th full round.
+    #[inline(always)]
+    #[unroll_for_loops]
+    fn external_constant_layer(state: &mut [Self; WIDTH], round: usize) {
+        for i in 0..12 {
+            state[i] += Self::from_canonical_u64(Self::EXTERNAL_ROUND_CONSTANTS[round][i]);
+        }
+    }
+
+    /// Same as `external_constant_layer` for field extensions of `Self`.
+    fn external_constant_layer_field<F: FieldExtension<D, BaseField = Self>, const D: usize>(
+        state: &mut [F; WIDTH],
+        round: usize,
+    ) {
+        for i in 0..WIDTH {
+            state[i] += F::from_canonical_u64(Self::EXTERNAL_ROUND_CONSTANTS[round][i]);
+        }
+    }
+
+    /// Recursive version of `external_constant_layer`.
+    fn external_constant_layer_circuit<const D: usize>(
+        builder: &mut CircuitBuilder<Self, D>,
+        state: &mut [ExtensionTarget<D>; WIDTH],
+        round: usize,
+    ) where
+        Self: RichField + Extendable<D>,
+    {
+        for i in 0..WIDTH {
+            let c = <Self as Poseidon2>::EXTERNAL_ROUND_CONSTANTS[round][i];
+            let c = Self::Extension::from_canonical_u64(c);
+            let c = builder.constant_extension(c);
+            state[i] = builder.add_extension(state[i], c);
+        }
+    }
+
+    #[inline(always)]
+    fn sbox_monomial<F: FieldExtension<D, BaseField = Self>, const D: usize>(x: F) -> F {
+        // x |--> x^7
+        let x2 = x.square();
+        let x4 = x2.square();
+        let x3 = x * x2;
+        x3 * x4
+    }
+
+    /// Recursive version of `sbox_monomial`.
+    fn sbox_monomial_circuit<const D: usize>(
+        builder: &mut CircuitBuilder<Self, D>,
+        x: ExtensionTarget<D>,
+    ) -> ExtensionTarget<D>
+    where
+        Self: RichField + Extendable<D>,
+    {
+        // x |--> x^7
+        builder.exp_u64_extension(x, 7)
+    }
+
+    #[inline(always)]
+    #[unroll_for_loops]
+    fn sbox_layer(state: &mut [Self; WIDTH]) {
+        for i in 0..12 {
+            state[i] = Self::sbox_monomial(state[i]);
+        }
+    }
+
+    /// Same as `sbox_layer` for field extensions of `Self`.
+    fn sbox_layer_field<F: FieldExtension<D, BaseField = Self>, const D: usize>(
+        state: &mut [F; WIDTH],
+    ) {
+        for i in 0..WIDTH {
+            state[i] = Self::sbox_monomial(state[i]);
+        }
+    }
+
+    /// Recursive version of `sbox_layer`.
+    fn sbox_layer_circuit<const D: usize>(
+        builder: &mut CircuitBuilder<Self, D>,
+        state: &mut [ExtensionTarget<D>; WIDTH],
+    ) where
+        Self: RichField + Extendable<D>,
+    {
+        for i in 0..WIDTH {
+            state[i] = <Self as Poseidon2>::sbox_monomial_circuit(builder, state[i]);
+        }
+    }
+
+    #[inline]
+    fn full_rounds(state: &mut [Self; WIDTH], round_ctr: &mut usize) {
+        for _ in 0..HALF_N_FULL_ROUNDS {
+            Self::external_constant_layer(state, *round_ctr);
+            Self::sbox_layer(state);
+            Self::external_linear_layer(state);
+            *round_ctr += 1;
+        }
+    }
+
+    #[inline]
+    fn partial_rounds(state: &mut [Self; WIDTH]) {
+        for r in 0..N_PARTIAL_ROUNDS {
+            state[0] += Self::from_canonical_u64(Self::INTERNAL_ROUND_CONSTANTS[r]);
+            state[0] = Self::sbox_monomial(state[0]);
+            Self::internal_linear_layer(state);
+        }
+    }
+
+    #[inline]
+    fn poseidon2(input: [Self; WIDTH]) -> [Self; WIDTH] {
+        let mut state = input;
+        let mut round_ctr = 0;
+
+        // The initial linear layer prevents the first full round from being partially skipped.
+        Self::external_linear_layer(&mut state);
+        Self::full_rounds(&mut state, &mut round_ctr);
+        Self::partial_rounds(&mut state);
+        Self::full_rounds(&mut state, &mut round_ctr);
+        debug_assert_eq!(round_ctr, N_FULL_ROUNDS_TOTAL);
+
+        state
+    }
+}
+
+pub struct Poseidon2Permutation;
+impl<F: RichField + Poseidon2> PlonkyPermutation<F> for Poseidon2Permutation {
+    fn permute(input: [F; SPONGE_WIDTH]) -> [F; SPONGE_WIDTH] {
+        F::poseidon2(input)
+    }
+}
+
+/// Poseidon2 hash function.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Poseidon2Hash;
+impl<F: RichField + Poseidon2> Hasher<F> for Poseidon2Hash {
+    const HASH_SIZE: usize = 4 * 8;
+    type Hash = HashOut<F>;
+    type Permutation = Poseidon2Permutation;
+
+    fn hash_no_pad(input: &[F]) -> Self::Hash {
+        hash_n_to_hash_no_pad::<F, Self::Permutation>(input)
+    }
+
+    fn two_to_one(left: Self::Hash, right: Self::Hash) -> Self::Hash {
+        compress::<F, Self::Permutation>(left, right)
+    }
+}
+
+impl<F: RichField + Poseidon2> AlgebraicHasher<F> for Poseidon2Hash {
+    fn permute_swapped<const D: usize>(
+        inputs: [Target; SPONGE_WIDTH],
+        swap: BoolTarget,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> [Target; SPONGE_WIDTH]
+    where
+        F: RichField + Extendable<D>,
+    {
+        let gate_type = Poseidon2Gate::<F, D>::new();
+        let gate = builder.add_gate(gate_type, vec![]);
+
+        let swap_wire = Poseidon2Gate::<F, D>::WIRE_SWAP;
+        let swap_wire = Target::wire(gate, swap_wire);
+        builder.connect(swap.target, swap_wire);
+
+        // Route input wires.
+        for i in 0..SPONGE_WIDTH {
+            let in_wire = Poseidon2Gate::<F, D>::wire_input(i);
+            let in_wire = Target::wire(gate, in_wire);
+            builder.connect(inputs[i], in_wire);
+        }
+
+        // Collect output wires.
+        (0..SPONGE_WIDTH)
+            .map(|i| Target::wire(gate, Poseidon2Gate::<F, D>::wire_output(i)))
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_helpers {
+    use crate::field::types::Field;
+    use crate::hash::hashing::SPONGE_WIDTH;
+    use crate::hash::poseidon2::Poseidon2;
+
+    pub(crate) fn check_test_vectors<F: Field>(
+        test_vectors: Vec<([u64; SPONGE_WIDTH], [u64; SPONGE_WIDTH])>,
+    ) where
+        F: Poseidon2,
+    {
+        for (input_, expected_output_) in test_vectors.into_iter() {
+            let mut input = [F::ZERO; SPONGE_WIDTH];
+            for i in 0..SPONGE_WIDTH {
+                input[i] = F::from_canonical_u64(input_[i]);
+            }
+            let output = F::poseidon2(input);
+            for i in 0..SPONGE_WIDTH {
+                let ex_output = F::from_canonical_u64(expected_output_[i]);
+                assert_eq!(output[i], ex_output);
+            }
+        }
+    }
+
+    /// Computes the permutation with the scalar layers, whichever implementation
+    /// `Poseidon2::poseidon2` uses.
+    pub(crate) fn poseidon2_scalar<F: Poseidon2>(input: [F; SPONGE_WIDTH]) -> [F; SPONGE_WIDTH] {
+        let mut state = input;
+        let mut round_ctr = 0;
+        F::external_linear_layer(&mut state);
+        F::full_rounds(&mut state, &mut round_ctr);
+        F::partial_rounds(&mut state);
+        F::full_rounds(&mut state, &mut round_ctr);
+        state
+    }
+
+    /// Checks that the permutation agrees with its scalar version, and that the base field layers
+    /// agree with their versions for field extensions.
+    pub(crate) fn check_consistency<F: Field>()
+    where
+        F: Poseidon2,
+    {
+        let mut input = [F::ZERO; SPONGE_WIDTH];
+        for i in 0..SPONGE_WIDTH {
+            input[i] = F::from_canonical_u64(i as u64);
+        }
+        let output = F::poseidon2(input);
+        assert_eq!(output, poseidon2_scalar(input));
+
+        let mut state = input;
+        F::external_linear_layer_field::<F, 1>(&mut state);
+        for r in 0..super::N_FULL_ROUNDS_TOTAL {
+            if r == super::HALF_N_FULL_ROUNDS {
+                for i in 0..super::N_PARTIAL_ROUNDS {
+                    state[0] += F::from_canonical_u64(F::INTERNAL_ROUND_CONSTANTS[i]);
+                    state[0] = <F as Poseidon2>::sbox_monomial(state[0]);
+                    F::internal_linear_layer_field::<F, 1>(&mut state);
+                }
+            }
+            F::external_constant_layer_field::<F, 1>(&mut state, r);
+            <F as Poseidon2>::sbox_layer_field::<F, 1>(&mut state);
+            F::external_linear_layer_field::<F, 1>(&mut state);
+        }
+        assert_eq!(output, state);
+    }
+}
@@ -0,0 +1,321 @@
+//! Implementation of the Rescue-Prime permutation, as described in
+//! <https://eprint.iacr.org/2020/1143.pdf>
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use unroll::unroll_for_loops;
+
+use crate::field::extension::{Extendable, FieldExtension};
+use crate::field::types::{Field, PrimeField64};
+use crate::gates::rescue_prime::RescuePrimeGate;
+use crate::hash::hash_types::{HashOut, RichField};
+use crate::hash::hashing::{compress, hash_n_to_hash_no_pad, PlonkyPermutation, SPONGE_WIDTH};
+use crate::iop::ext_target::ExtensionTarget;
+use crate::iop::target::{BoolTarget, Target};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::config::{AlgebraicHasher, Hasher};
+
+/// The number of rounds. Each round applies the S-box `x^ALPHA` and its inverse, each followed by
+/// the MDS matrix and a layer of round constants. Seven rounds give 128-bit security with a 40%
+/// margin for width 12 over Goldilocks, as in Winterfell's `Rp64_256`.
+pub const N_ROUNDS: usize = 7;
+
+const WIDTH: usize = SPONGE_WIDTH;
+
+pub trait RescuePrime: PrimeField64 {
+    /// The first row of the circulant MDS matrix.
+    const MDS_FIRST_ROW: [u64; WIDTH];
+
+    /// The round constants added after the `x^ALPHA` half of each round.
+    const ARK1: [[u64; WIDTH]; N_ROUNDS];
+
+    /// The round constants added after the `x^(1/ALPHA)` half of each round.
+    const ARK2: [[u64; WIDTH]; N_ROUNDS];
+
+    /// The exponent `1/ALPHA mod (p - 1)` of the inverse S-box, with `ALPHA = 7`.
+    const INV_ALPHA: u64;
+
+    /// Multiplies the state by the circulant MDS matrix.
+    #[inline(always)]
+    #[unroll_for_loops]
+    fn mds_multiply(state: &mut [Self; WIDTH]) {
+        // The matrix entries are small, so the sums fit in a `u128` without intermediate reductions.
+        let mut result = [0u128; WIDTH];
+        for r in 0..12 {
+            for i in 0..12 {
+                result[r] += (state[i].to_noncanonical_u64() as u128)
+                    * (Self::MDS_FIRST_ROW[(WIDTH + i - r) % WIDTH] as u128);
+            }
+        }
+
+        for r in 0..12 {
+            state[r] = Self::from_noncanonical_u128(result[r]);
+        }
+    }
+
+    /// Same as `mds_multiply` for field extensions of `Self`.
+    fn mds_multiply_field<F: FieldExtension<D, BaseField = Self>, const D: usize>(
+        state: &mut [F; WIDTH],
+    ) {
+        let mut result = [F::ZERO; WIDTH];
+        for r in 0..WIDTH {
+            for i in 0..WIDTH {
+                let m = Self::MDS_FIRST_ROW[(WIDTH + i - r) % WIDTH];
+                result[r] += state[i] * F::from_canonical_u64(m);
+            }
+        }
+        *state = result;
+    }
+
+    /// Recursive version of `mds_multiply`.
+    fn mds_multiply_circuit<const D: usize>(
+        builder: &mut CircuitBuilder<Self, D>,
+        state: &mut [ExtensionTarget<D>; WIDTH],
+    ) where
+        Self: RichField + Extendable<D>,
+    {
+        let mut result = [builder.zero_extension(); WIDTH];
+        for r in 0..WIDTH {
+            for i in 0..WIDTH {
+                let m = Self::MDS_FIRST_ROW[(WIDTH + i - r) % WIDTH];
+                let m = Self::from_canonical_u64(m);
+                result[r] = builder.mul_const_add_extension(m, state[i], result[r]);
+            }
+        }
+        *state = result;
+    }
+
+    /// Adds the given round constants.
+    #[inline(always)]
+    #[unroll_for_loops]
+    fn add_round_constants(state: &mut [Self; WIDTH], constants: &[u64; WIDTH]) {
+        for i in 0..12 {
+            state[i] += Self::from_canonical_u64(constants[i]);
+        }
+    }
+
+    /// Same as `add_round_constants` for field extensions of `Self`.
+    fn add_round_constants_field<F: FieldExtension<D, BaseField = Self>, const D: usize>(
+        state: &mut [F; WIDTH],
+        constants: &[u64; WIDTH],
+    ) {
+        for i in 0..WIDTH {
+            state[i] += F::from_canonical_u64(constants[i]);
+        }
+    }
+
+    /// Recursive version of `add_round_constants`.
+    fn add_round_constants_circuit<const D: usize>(
+        builder: &mut CircuitBuilder<Self, D>,
+        state: &mut [ExtensionTarget<D>; WIDTH],
+        constants: &[u64; WIDTH],
+    ) where
+        Self: RichField + Extendable<D>,
+    {
+        for i in 0..WIDTH {
+            let c = Self::Extension::from_canonical_u64(constants[i]);
+            let c = builder.constant_extension(c);
+            state[i] = builder.add_extension(state[i], c);
+        }
+    }
+
+    #[inline(always)]
+    fn forward_sbox<F: FieldExtension<D, BaseField = Self>, const D: usize>(x: F) -> F {
+        // x |--> x^7
+        let x2 = x.square();
+        let x4 = x2.square();
+        let x3 = x * x2;
+        x3 * x4
+    }
+
+    /// Recursive version of `forward_sbox`.
+    fn forward_sbox_circuit<const D: usize>(
+        builder: &mut CircuitBuilder<Self, D>,
+        x: ExtensionTarget<D>,
+    ) -> ExtensionTarget<D>
+    where
+        Self: RichField + Extendable<D>,
+    {
+        // x |--> x^7
+        builder.exp_u64_extension(x, 7)
+    }
+
+    #[inline(always)]
+    #[unroll_for_loops]
+    fn forward_sbox_layer(state: &mut [Self; WIDTH]) {
+        for i in 0..12 {
+            state[i] = Self::forward_sbox(state[i]);
+        }
+    }
+
+    /// Same as `forward_sbox_layer` for field extensions of `Self`.
+    fn forward_sbox_layer_field<F: FieldExtension<D, BaseField = Self>, const D: usize>(
+        state: &mut [F; WIDTH],
+    ) {
+        for i in 0..WIDTH {
+            state[i] = Self::forward_sbox(state[i]);
+        }
+    }
+
+    /// Recursive version of `forward_sbox_layer`.
+    fn forward_sbox_layer_circuit<const D: usize>(
+        builder: &mut CircuitBuilder<Self, D>,
+        state: &mut [ExtensionTarget<D>; WIDTH],
+    ) where
+        Self: RichField + Extendable<D>,
+    {
+        for i in 0..WIDTH {
+            state[i] = Self::forward_sbox_circuit(builder, state[i]);
+        }
+    }
+
+    /// Applies the inverse S-box `x^(1/7)`. This is only computed natively; circuits instead take
+    /// its outputs as witnesses and check that their seventh powers are the inputs.
+    #[inline(always)]
+    #[unroll_for_loops]
+    fn inverse_sbox_layer(state: &mut [Self; WIDTH]) {
+        for i in 0..12 {
+            state[i] = state[i].exp_u64(Self::INV_ALPHA);
+        }
+    }
+
+    /// Applies the first half of the `round`-th round, up to the input of the inverse S-box.
+    #[inline]
+    fn first_half_round(state: &mut [Self; WIDTH], round: usize) {
+        Self::forward_sbox_layer(state);
+        Self::mds_multiply(state);
+        Self::add_round_constants(state, &Self::ARK1[round]);
+    }
+
+    /// Applies the second half of the `round`-th round, given the output of the inverse S-box.
+    #[inline]
+    fn second_half_round(state: &mut [Self; WIDTH], round: usize) {
+        Self::mds_multiply(state);
+        Self::add_round_constants(state, &Self::ARK2[round]);
+    }
+
+    #[inline]
+    fn rescue_prime(input: [Self; WIDTH]) -> [Self; WIDTH] {
+        let mut state = input;
+        for r in 0..N_ROUNDS {
+            Self::first_half_round(&mut state, r);
+            Self::inverse_sbox_layer(&mut state);
+            Self::second_half_round(&mut state, r);
+        }
+        state
+    }
+}
+
+pub struct RescuePrimePermutation;
+impl<F: RichField + RescuePrime> PlonkyPermutation<F> for RescuePrimePermutation {
+    fn permute(input: [F; SPONGE_WIDTH]) -> [F; SPONGE_WIDTH] {
+        F::rescue_prime(input)
+    }
+}
+
+/// Rescue-Prime hash function. It uses the same sponge construction as the other hashers of this
+/// crate, so its digests differ from those of Winterfell's `Rp64_256`, which places the capacity
+/// first and pads differently, even though the permutations are the same.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct RescuePrimeHash;
+impl<F: RichField + RescuePrime> Hasher<F> for RescuePrimeHash {
+    const HASH_SIZE: usize = 4 * 8;
+    type Hash = HashOut<F>;
+    type Permutation = RescuePrimePermutation;
+
+    fn hash_no_pad(input: &[F]) -> Self::Hash {
+        hash_n_to_hash_no_pad::<F, Self::Permutation>(input)
+    }
+
+    fn two_to_one(left: Self::Hash, right: Self::Hash) -> Self::Hash {
+        compress::<F, Self::Permutation>(left, right)
+    }
+}
+
+impl<F: RichField + RescuePrime> AlgebraicHasher<F> for RescuePrimeHash {
+    fn permute_swapped<const D: usize>(
+        inputs: [Target; SPONGE_WIDTH],
+        swap: BoolTarget,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> [Target; SPONGE_WIDTH]
+    where
+        F: RichField + Extendable<D>,
+    {
+        let gate_type = RescuePrimeGate::<F, D>::new();
+        let gate = builder.add_gate(gate_type, vec![]);
+
+        let swap_wire = RescuePrimeGate::<F, D>::WIRE_SWAP;
+        let swap_wire = Target::wire(gate, swap_wire);
+        builder.connect(swap.target, swap_wire);
+
+        // Route input wires.
+        for i in 0..SPONGE_WIDTH {
+            let in_wire = RescuePrimeGate::<F, D>::wire_input(i);
+            let in_wire = Target::wire(gate, in_wire);
+            builder.connect(inputs[i], in_wire);
+        }
+
+        // Collect output wires.
+        (0..SPONGE_WIDTH)
+            .map(|i| Target::wire(gate, RescuePrimeGate::<F, D>::wire_output(i)))
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_helpers {
+    use crate::field::types::Field;
+    use crate::hash::hashing::SPONGE_WIDTH;
+    use crate::hash::rescue_prime::{RescuePrime, N_ROUNDS};
+
+    pub(crate) fn check_test_vectors<F: Field>(
+        test_vectors: Vec<([u64; SPONGE_WIDTH], [u64; SPONGE_WIDTH])>,
+    ) where
+        F: RescuePrime,
+    {
+        for (input_, expected_output_) in test_vectors.into_iter() {
+            let mut input = [F::ZERO; SPONGE_WIDTH];
+            for i in 0..SPONGE_WIDTH {
+                input[i] = F::from_canonical_u64(input_[i]);
+            }
+            let output = F::rescue_prime(input);
+            for i in 0..SPONGE_WIDTH {
+                let ex_output = F::from_canonical_u64(expected_output_[i]);
+                assert_eq!(output[i], ex_output);
+            }
+        }
+    }
+
+    /// Checks that the base field layers agree with their versions for field extensions, and that
+    /// the inverse S-box inverts the S-box.
+    pub(crate) fn check_consistency<F: Field>()
+    where
+        F: RescuePrime,
+    {
+        let mut input = [F::ZERO; SPONGE_WIDTH];
+        for i in 0..SPONGE_WIDTH {
+            input[i] = F::from_canonical_u64(i as u64);
+        }
+        let output = F::rescue_prime(input);
+
+        let mut state = input;
+        for r in 0..N_ROUNDS {
+            F::forward_sbox_layer_field::<F, 1>(&mut state);
+            F::mds_multiply_field::<F, 1>(&mut state);
+            F::add_round_constants_field::<F, 1>(&mut state, &F::ARK1[r]);
+
+            let sbox_in = state;
+            F::inverse_sbox_layer(&mut state);
+            let mut sbox_out = state;
+            F::forward_sbox_layer_field::<F, 1>(&mut sbox_out);
+            assert_eq!(sbox_out, sbox_in);
+
+            F::mds_multiply_field::<F, 1>(&mut state);
+            F::add_round_constants_field::<F, 1>(&mut state, &F::ARK2[r]);
+        }
+        assert_eq!(output, state);
+    }
+}
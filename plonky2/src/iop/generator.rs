@@ -1,8 +1,13 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
 use alloc::vec;
 use alloc::vec::Vec;
-use core::fmt::Debug;
+use core::any::Any;
+use core::fmt::{Debug, Formatter};
 use core::marker::PhantomData;
 
+use plonky2_maybe_rayon::*;
+
 use crate::field::extension::Extendable;
 use crate::field::types::Field;
 use crate::hash::hash_types::RichField;
@@ -12,6 +17,7 @@ use crate::iop::wire::Wire;
 use crate::iop::witness::{PartialWitness, PartitionWitness, Witness, WitnessWrite};
 use crate::plonk::circuit_data::{CommonCircuitData, ProverOnlyCircuitData};
 use crate::plonk::config::GenericConfig;
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
 
 /// Given a `PartitionWitness` that has only inputs set, populates the rest of the witness using the
 /// given set of generators.
@@ -39,6 +45,21 @@ pub(crate) fn generate_partial_witness<
         witness.set_target(t, v);
     }
 
+    #[cfg(feature = "parallel")]
+    run_generators_parallel(&mut witness, generators, generator_indices_by_watches);
+    #[cfg(not(feature = "parallel"))]
+    run_generators_sequential(&mut witness, generators, generator_indices_by_watches);
+
+    witness
+}
+
+/// Runs generators one at a time, until none of them can make progress.
+#[cfg_attr(feature = "parallel", allow(dead_code))]
+fn run_generators_sequential<F: Field>(
+    witness: &mut PartitionWitness<F>,
+    generators: &[WitnessGeneratorRef<F>],
+    generator_indices_by_watches: &BTreeMap<usize, Vec<usize>>,
+) {
     // Build a list of "pending" generators which are queued to be run. Initially, all generators
     // are queued.
     let mut pending_generator_indices: Vec<_> = (0..generators.len()).collect();
@@ -58,7 +79,7 @@ pub(crate) fn generate_partial_witness<
                 continue;
             }
 
-            let finished = generators[generator_idx].run(&witness, &mut buffer);
+            let finished = generators[generator_idx].0.run(witness, &mut buffer);
             if finished {
                 generator_is_expired[generator_idx] = true;
                 remaining_generators -= 1;
@@ -92,8 +113,83 @@ pub(crate) fn generate_partial_witness<
         "{} generators weren't run",
         remaining_generators,
     );
+}
 
-    witness
+/// Number of generators run by each task of the parallel scheduler.
+#[cfg_attr(not(feature = "parallel"), allow(dead_code))]
+const GENERATOR_CHUNK_SIZE: usize = 64;
+
+/// Runs generators in waves. All generators of a wave run concurrently against the same witness,
+/// then their outputs are merged in generator order, which queues the next wave. A generator whose
+/// inputs are produced within its own wave simply returns unfinished and runs again in the next
+/// one, so this reaches the same witness as `run_generators_sequential`.
+#[cfg_attr(not(feature = "parallel"), allow(dead_code))]
+fn run_generators_parallel<F: Field>(
+    witness: &mut PartitionWitness<F>,
+    generators: &[WitnessGeneratorRef<F>],
+    generator_indices_by_watches: &BTreeMap<usize, Vec<usize>>,
+) {
+    let mut pending_generator_indices: Vec<_> = (0..generators.len()).collect();
+    let mut generator_is_expired = vec![false; generators.len()];
+    // Whether a generator is already part of the next wave, to avoid running it twice.
+    let mut generator_is_queued = vec![false; generators.len()];
+    let mut remaining_generators = generators.len();
+
+    while !pending_generator_indices.is_empty() {
+        let shared_witness = &*witness;
+        let results = pending_generator_indices
+            .par_chunks(GENERATOR_CHUNK_SIZE)
+            .map(|chunk| {
+                let mut buffer = GeneratedValues::empty();
+                let finished = chunk
+                    .iter()
+                    .copied()
+                    .filter(|&i| generators[i].0.run(shared_witness, &mut buffer))
+                    .collect::<Vec<_>>();
+                (finished, buffer)
+            })
+            .collect::<Vec<_>>();
+
+        for (finished, _) in &results {
+            for &generator_idx in finished {
+                generator_is_expired[generator_idx] = true;
+                remaining_generators -= 1;
+            }
+        }
+
+        let mut next_pending_generator_indices = Vec::new();
+        for (_, buffer) in results {
+            let new_target_reps = buffer
+                .target_values
+                .into_iter()
+                .flat_map(|(t, v)| witness.set_target_returning_rep(t, v));
+
+            // Enqueue unfinished generators that were watching one of the newly populated targets.
+            for watch in new_target_reps {
+                if let Some(watchers) = generator_indices_by_watches.get(&watch) {
+                    for &watching_generator_idx in watchers {
+                        if !generator_is_expired[watching_generator_idx]
+                            && !generator_is_queued[watching_generator_idx]
+                        {
+                            generator_is_queued[watching_generator_idx] = true;
+                            next_pending_generator_indices.push(watching_generator_idx);
+                        }
+                    }
+                }
+            }
+        }
+
+        for &generator_idx in &next_pending_generator_indices {
+            generator_is_queued[generator_idx] = false;
+        }
+        pending_generator_indices = next_pending_generator_indices;
+    }
+
+    assert_eq!(
+        remaining_generators, 0,
+        "{} generators weren't run",
+        remaining_generators,
+    );
 }
 
 /// A generator participates in the generation of the witness.
@@ -106,6 +202,40 @@ pub trait WitnessGenerator<F: Field>: 'static + Send + Sync + Debug {
     /// flag is true, the generator will never be run again, otherwise it will be queued for another
     /// run next time a target in its watch list is populated.
     fn run(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) -> bool;
+
+    /// Serializes this generator, so that it can be rebuilt by `deserialize`.
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()>;
+
+    /// Rebuilds a generator from the data written by `serialize`.
+    fn deserialize(src: &mut Buffer) -> IoResult<Self>
+    where
+        Self: Sized;
+}
+
+/// A wrapper trait over a `WitnessGenerator`, to allow for generator serialization.
+pub trait AnyWitnessGenerator<F: Field>: WitnessGenerator<F> {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: WitnessGenerator<F>, F: Field> AnyWitnessGenerator<F> for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// A wrapper around a `Box<AnyWitnessGenerator>`.
+pub struct WitnessGeneratorRef<F: Field>(pub Box<dyn AnyWitnessGenerator<F>>);
+
+impl<F: Field> WitnessGeneratorRef<F> {
+    pub fn new<G: WitnessGenerator<F>>(generator: G) -> WitnessGeneratorRef<F> {
+        WitnessGeneratorRef(Box::new(generator))
+    }
+}
+
+impl<F: Field> Debug for WitnessGeneratorRef<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
 }
 
 /// Values generated by a generator invocation.
@@ -162,6 +292,12 @@ pub trait SimpleGenerator<F: Field>: 'static + Send + Sync + Debug {
 
     fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>);
 
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()>;
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self>
+    where
+        Self: Sized;
+
     fn adapter(self) -> SimpleGeneratorAdapter<F, Self>
     where
         Self: Sized,
@@ -192,11 +328,19 @@ impl<F: Field, SG: SimpleGenerator<F>> WitnessGenerator<F> for SimpleGeneratorAd
             false
         }
     }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        self.inner.serialize(dst)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        Ok(SG::deserialize(src)?.adapter())
+    }
 }
 
 /// A generator which copies one wire to another.
 #[derive(Debug)]
-pub(crate) struct CopyGenerator {
+pub struct CopyGenerator {
     pub(crate) src: Target,
     pub(crate) dst: Target,
 }
@@ -210,11 +354,22 @@ impl<F: Field> SimpleGenerator<F> for CopyGenerator {
         let value = witness.get_target(self.src);
         out_buffer.set_target(self.dst, value);
     }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_target(self.src)?;
+        dst.write_target(self.dst)
+    }
+
+    fn deserialize(source: &mut Buffer) -> IoResult<Self> {
+        let src = source.read_target()?;
+        let dst = source.read_target()?;
+        Ok(Self { src, dst })
+    }
 }
 
 /// A generator for including a random value
 #[derive(Debug)]
-pub(crate) struct RandomValueGenerator {
+pub struct RandomValueGenerator {
     pub(crate) target: Target,
 }
 
@@ -227,11 +382,20 @@ impl<F: Field> SimpleGenerator<F> for RandomValueGenerator {
         let random_value = F::rand();
         out_buffer.set_target(self.target, random_value);
     }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_target(self.target)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let target = src.read_target()?;
+        Ok(Self { target })
+    }
 }
 
 /// A generator for testing if a value equals zero
 #[derive(Debug)]
-pub(crate) struct NonzeroTestGenerator {
+pub struct NonzeroTestGenerator {
     pub(crate) to_test: Target,
     pub(crate) dummy: Target,
 }
@@ -252,11 +416,22 @@ impl<F: Field> SimpleGenerator<F> for NonzeroTestGenerator {
 
         out_buffer.set_target(self.dummy, dummy_value);
     }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_target(self.to_test)?;
+        dst.write_target(self.dummy)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let to_test = src.read_target()?;
+        let dummy = src.read_target()?;
+        Ok(Self { to_test, dummy })
+    }
 }
 
 /// Generator used to fill an extra constant.
 #[derive(Debug, Clone)]
-pub(crate) struct ConstantGenerator<F: Field> {
+pub struct ConstantGenerator<F: Field> {
     pub row: usize,
     pub constant_index: usize,
     pub wire_index: usize,
@@ -269,7 +444,7 @@ impl<F: Field> ConstantGenerator<F> {
     }
 }
 
-impl<F: Field> SimpleGenerator<F> for ConstantGenerator<F> {
+impl<F: RichField> SimpleGenerator<F> for ConstantGenerator<F> {
     fn dependencies(&self) -> Vec<Target> {
         vec![]
     }
@@ -277,4 +452,104 @@ impl<F: Field> SimpleGenerator<F> for ConstantGenerator<F> {
     fn run_once(&self, _witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
         out_buffer.set_target(Target::wire(self.row, self.wire_index), self.constant);
     }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.row)?;
+        dst.write_usize(self.constant_index)?;
+        dst.write_usize(self.wire_index)?;
+        dst.write_field(self.constant)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let row = src.read_usize()?;
+        let constant_index = src.read_usize()?;
+        let wire_index = src.read_usize()?;
+        let constant = src.read_field()?;
+        Ok(Self {
+            row,
+            constant_index,
+            wire_index,
+            constant,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::poseidon::PoseidonHash;
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::PoseidonGoldilocksConfig;
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let inputs = builder.add_virtual_targets(4);
+        let mut acc = inputs[0];
+        for i in 0..200 {
+            let x = inputs[i % inputs.len()];
+            acc = builder.mul_add(acc, x, inputs[(i + 1) % inputs.len()]);
+            if i % 50 == 0 {
+                acc = builder
+                    .hash_n_to_hash_no_pad::<PoseidonHash>(vec![acc, x])
+                    .elements[0];
+            }
+        }
+        let exp = builder.exp(acc, inputs[1], 64);
+        let index = builder.constant(F::from_canonical_usize(3));
+        let selected = builder.random_access(index, inputs.clone());
+        let out = builder.add(exp, selected);
+        builder.register_public_input(out);
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        for (i, &t) in inputs.iter().enumerate() {
+            pw.set_target(t, F::from_canonical_usize(i + 7));
+        }
+
+        let mut sequential = PartitionWitness::new(
+            data.common.config.num_wires,
+            data.common.degree(),
+            &data.prover_only.representative_map,
+        );
+        for (&t, &v) in &pw.target_values {
+            sequential.set_target(t, v);
+        }
+        run_generators_sequential(
+            &mut sequential,
+            &data.prover_only.generators,
+            &data.prover_only.generator_indices_by_watches,
+        );
+
+        let mut parallel = sequential.clone();
+        parallel.values.iter_mut().for_each(|v| *v = None);
+        for (&t, &v) in &pw.target_values {
+            parallel.set_target(t, v);
+        }
+        run_generators_parallel(
+            &mut parallel,
+            &data.prover_only.generators,
+            &data.prover_only.generator_indices_by_watches,
+        );
+
+        // Random values differ between runs, so ignore them.
+        for generator in &data.prover_only.generators {
+            if let Some(random) = generator
+                .0
+                .as_any()
+                .downcast_ref::<SimpleGeneratorAdapter<F, RandomValueGenerator>>()
+            {
+                let rep =
+                    sequential.representative_map[sequential.target_index(random.inner.target)];
+                sequential.values[rep] = None;
+                parallel.values[rep] = None;
+            }
+        }
+        assert_eq!(sequential.values, parallel.values);
+    }
 }
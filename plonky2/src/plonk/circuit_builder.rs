@@ -1,4 +1,3 @@
-use alloc::boxed::Box;
 use alloc::collections::BTreeMap;
 use alloc::vec;
 use alloc::vec::Vec;
@@ -24,15 +23,19 @@ use crate::gates::arithmetic_base::ArithmeticGate;
 use crate::gates::arithmetic_extension::ArithmeticExtensionGate;
 use crate::gates::constant::ConstantGate;
 use crate::gates::gate::{CurrentSlot, Gate, GateInstance, GateRef};
+use crate::gates::lookup::LookupGate;
+use crate::gates::lookup_table::{LookupTable, LookupTableGate};
 use crate::gates::noop::NoopGate;
 use crate::gates::public_input::PublicInputGate;
-use crate::gates::selectors::selector_polynomials;
+use crate::gates::selectors::{
+    lookup_selector_polynomials, selector_polynomials, NUM_LOOKUP_SELECTORS,
+};
 use crate::hash::hash_types::{HashOut, HashOutTarget, MerkleCapTarget, RichField};
 use crate::hash::merkle_proofs::MerkleProofTarget;
 use crate::hash::merkle_tree::MerkleCap;
 use crate::iop::ext_target::ExtensionTarget;
 use crate::iop::generator::{
-    ConstantGenerator, CopyGenerator, RandomValueGenerator, SimpleGenerator, WitnessGenerator,
+    ConstantGenerator, CopyGenerator, RandomValueGenerator, SimpleGenerator, WitnessGeneratorRef,
 };
 use crate::iop::target::{BoolTarget, Target};
 use crate::iop::wire::Wire;
@@ -48,7 +51,7 @@ use crate::timed;
 use crate::util::context_tree::ContextTree;
 use crate::util::partial_products::num_partial_products;
 use crate::util::timing::TimingTree;
-use crate::util::{log2_ceil, log2_strict, transpose, transpose_poly_values};
+use crate::util::{ceil_div_usize, log2_ceil, log2_strict, transpose, transpose_poly_values};
 
 pub struct CircuitBuilder<F: RichField + Extendable<D>, const D: usize> {
     pub config: CircuitConfig,
@@ -76,7 +79,7 @@ pub struct CircuitBuilder<F: RichField + Extendable<D>, const D: usize> {
     context_log: ContextTree,
 
     /// Generators used to generate the witness.
-    generators: Vec<Box<dyn WitnessGenerator<F>>>,
+    generators: Vec<WitnessGeneratorRef<F>>,
 
     constants_to_targets: HashMap<F, Target>,
     targets_to_constants: HashMap<Target, F>,
@@ -88,7 +91,7 @@ pub struct CircuitBuilder<F: RichField + Extendable<D>, const D: usize> {
     pub(crate) arithmetic_results: HashMap<ExtensionArithmeticOperation<F, D>, ExtensionTarget<D>>,
 
     /// Map between gate type and the current gate of this type with available slots.
-    current_slots: HashMap<GateRef<F, D>, CurrentSlot<F, D>>,
+    pub(crate) current_slots: HashMap<GateRef<F, D>, CurrentSlot<F, D>>,
 
     /// List of constant generators used to fill the constant wires.
     constant_generators: Vec<ConstantGenerator<F>>,
@@ -101,6 +104,12 @@ pub struct CircuitBuilder<F: RichField + Extendable<D>, const D: usize> {
     /// Optional verifier data that is registered as public inputs.
     /// This is used in cyclic recursion to hold the circuit's own verifier key.
     pub(crate) verifier_data_public_input: Option<VerifierCircuitTarget>,
+
+    /// The lookup tables used in this circuit.
+    pub(crate) luts: Vec<LookupTable>,
+
+    /// For each lookup table, the `(input, output)` wires of the lookups into it.
+    pub(crate) lut_to_lookups: Vec<Vec<(Target, Target)>>,
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
@@ -123,6 +132,8 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             constant_generators: Vec::new(),
             goal_common_data: None,
             verifier_data_public_input: None,
+            luts: Vec::new(),
+            lut_to_lookups: Vec::new(),
         };
         builder.check_config();
         builder
@@ -245,6 +256,13 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         t
     }
 
+    /// Add virtual targets and register them as public inputs.
+    pub fn add_virtual_public_input_arr<const N: usize>(&mut self) -> [Target; N] {
+        let ts = self.add_virtual_target_arr::<N>();
+        self.register_public_inputs(&ts);
+        ts
+    }
+
     pub fn add_virtual_verifier_data(&mut self, cap_height: usize) -> VerifierCircuitTarget {
         VerifierCircuitTarget {
             constants_sigmas_cap: self.add_virtual_cap(cap_height),
@@ -366,12 +384,13 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         self.connect(x, one);
     }
 
-    pub fn add_generators(&mut self, generators: Vec<Box<dyn WitnessGenerator<F>>>) {
+    pub fn add_generators(&mut self, generators: Vec<WitnessGeneratorRef<F>>) {
         self.generators.extend(generators);
     }
 
     pub fn add_simple_generator<G: SimpleGenerator<F>>(&mut self, generator: G) {
-        self.generators.push(Box::new(generator.adapter()));
+        self.generators
+            .push(WitnessGeneratorRef::new(generator.adapter()));
     }
 
     /// Returns a routable target with a value of 0.
@@ -759,6 +778,13 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         }
         self.randomize_unused_pi_wires(pi_gate);
 
+        assert!(
+            self.luts.is_empty() || !self.config.zero_knowledge,
+            "Lookups are not supported with zero-knowledge."
+        );
+        // Fill the incomplete lookup gates, and add the rows of the lookup tables.
+        let (lookup_rows, table_rows) = self.add_all_lookups();
+
         // Make sure we have enough constant generators. If not, add a `ConstantGate`.
         while self.constants_to_targets.len() > self.constant_generators.len() {
             self.add_gate(
@@ -809,6 +835,16 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         let (mut constant_vecs, selectors_info) =
             selector_polynomials(&gates, &self.gate_instances, quotient_degree_factor + 1);
         constant_vecs.extend(self.constant_polys());
+        let num_lookup_selectors = if self.luts.is_empty() {
+            0
+        } else {
+            constant_vecs.extend(lookup_selector_polynomials(
+                degree,
+                &lookup_rows,
+                &table_rows,
+            ));
+            NUM_LOOKUP_SELECTORS
+        };
         let num_constants = constant_vecs.len();
 
         let subgroup = F::two_adic_subgroup(degree_bits);
@@ -860,7 +896,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         // Index generator indices by their watched targets.
         let mut generator_indices_by_watches = BTreeMap::new();
         for (i, generator) in self.generators.iter().enumerate() {
-            for watch in generator.watch_list() {
+            for watch in generator.0.watch_list() {
                 let watch_index = forest.target_index(watch);
                 let watch_rep_index = forest.parents[watch_index];
                 generator_indices_by_watches
@@ -883,6 +919,20 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         let num_partial_products =
             num_partial_products(self.config.num_routed_wires, quotient_degree_factor);
 
+        // The lookup argument uses a running evaluation `RE`, a running sum `Z` and the helpers
+        // holding the partial sums of each row.
+        let num_lookup_polys = if self.luts.is_empty() {
+            0
+        } else {
+            let num_looking_helpers =
+                ceil_div_usize(LookupGate::num_slots(&self.config), quotient_degree_factor);
+            let num_table_helpers = ceil_div_usize(
+                LookupTableGate::num_slots(&self.config),
+                quotient_degree_factor,
+            );
+            2 + num_looking_helpers + num_table_helpers
+        };
+
         let constants_sigmas_cap = constants_sigmas_commitment.merkle_tree.cap.clone();
         let domain_separator = self.domain_separator.unwrap_or_default();
         let domain_separator_digest = C::Hasher::hash_pad(&domain_separator);
@@ -908,6 +958,9 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             num_public_inputs,
             k_is,
             num_partial_products,
+            luts: self.luts,
+            num_lookup_polys,
+            num_lookup_selectors,
         };
         if let Some(goal_data) = self.goal_common_data {
             assert_eq!(goal_data, common, "The expected circuit data passed to cyclic recursion method did not match the actual circuit");
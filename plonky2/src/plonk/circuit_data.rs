@@ -1,8 +1,7 @@
-use alloc::boxed::Box;
 use alloc::collections::BTreeMap;
 use alloc::vec;
 use alloc::vec::Vec;
-use core::ops::{Range, RangeFrom};
+use core::ops::Range;
 
 use anyhow::Result;
 
@@ -11,25 +10,34 @@ use crate::field::fft::FftRootTable;
 use crate::field::types::Field;
 use crate::fri::oracle::PolynomialBatch;
 use crate::fri::reduction_strategies::FriReductionStrategy;
+#[cfg(feature = "std")]
+use crate::fri::security::{security_report, SecurityReport, SoundnessParams};
 use crate::fri::structure::{
     FriBatchInfo, FriBatchInfoTarget, FriInstanceInfo, FriInstanceInfoTarget, FriOracleInfo,
     FriPolynomialInfo,
 };
 use crate::fri::{FriConfig, FriParams};
 use crate::gates::gate::GateRef;
+use crate::gates::lookup::LookupGate;
+use crate::gates::lookup_table::{LookupTable, LookupTableGate};
 use crate::gates::selectors::SelectorsInfo;
 use crate::hash::hash_types::{HashOutTarget, MerkleCapTarget, RichField};
 use crate::hash::merkle_tree::MerkleCap;
 use crate::iop::ext_target::ExtensionTarget;
-use crate::iop::generator::WitnessGenerator;
+use crate::iop::generator::WitnessGeneratorRef;
 use crate::iop::target::Target;
 use crate::iop::witness::PartialWitness;
 use crate::plonk::circuit_builder::CircuitBuilder;
 use crate::plonk::config::{GenericConfig, Hasher};
 use crate::plonk::plonk_common::PlonkOracle;
 use crate::plonk::proof::{CompressedProofWithPublicInputs, ProofWithPublicInputs};
-use crate::plonk::prover::prove;
+use crate::plonk::prover::{prove, prove_with_handle};
+use crate::plonk::prover_handle::ProverHandle;
 use crate::plonk::verifier::verify;
+use crate::util::ceil_div_usize;
+use crate::util::serialization::{
+    Buffer, GateSerializer, IoResult, Read, WitnessGeneratorSerializer, Write,
+};
 use crate::util::timing::TimingTree;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -115,6 +123,27 @@ pub struct CircuitData<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>,
 impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
     CircuitData<F, C, D>
 {
+    pub fn to_bytes(
+        &self,
+        gate_serializer: &dyn GateSerializer<F, D>,
+        generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
+    ) -> IoResult<Vec<u8>> {
+        let mut buffer = Vec::new();
+        buffer.write_format_version()?;
+        buffer.write_circuit_data(self, gate_serializer, generator_serializer)?;
+        Ok(buffer)
+    }
+
+    pub fn from_bytes(
+        bytes: &[u8],
+        gate_serializer: &dyn GateSerializer<F, D>,
+        generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
+    ) -> IoResult<Self> {
+        let mut buffer = Buffer::new(bytes);
+        buffer.read_format_version()?;
+        buffer.read_circuit_data(gate_serializer, generator_serializer)
+    }
+
     pub fn prove(&self, inputs: PartialWitness<F>) -> Result<ProofWithPublicInputs<F, C, D>> {
         prove(
             &self.prover_only,
@@ -124,6 +153,22 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         )
     }
 
+    /// Proves with a [`ProverHandle`] which reports progress, can cancel the proof, and can store
+    /// checkpoints to resume from.
+    pub fn prove_with_handle(
+        &self,
+        inputs: PartialWitness<F>,
+        handle: &ProverHandle,
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        prove_with_handle(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            handle,
+            &mut TimingTree::default(),
+        )
+    }
+
     pub fn verify(&self, proof_with_pis: ProofWithPublicInputs<F, C, D>) -> Result<()> {
         verify(proof_with_pis, &self.verifier_only, &self.common)
     }
@@ -193,6 +238,27 @@ pub struct ProverCircuitData<
 impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
     ProverCircuitData<F, C, D>
 {
+    pub fn to_bytes(
+        &self,
+        gate_serializer: &dyn GateSerializer<F, D>,
+        generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
+    ) -> IoResult<Vec<u8>> {
+        let mut buffer = Vec::new();
+        buffer.write_format_version()?;
+        buffer.write_prover_circuit_data(self, gate_serializer, generator_serializer)?;
+        Ok(buffer)
+    }
+
+    pub fn from_bytes(
+        bytes: &[u8],
+        gate_serializer: &dyn GateSerializer<F, D>,
+        generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
+    ) -> IoResult<Self> {
+        let mut buffer = Buffer::new(bytes);
+        buffer.read_format_version()?;
+        buffer.read_prover_circuit_data(gate_serializer, generator_serializer)
+    }
+
     pub fn prove(&self, inputs: PartialWitness<F>) -> Result<ProofWithPublicInputs<F, C, D>> {
         prove(
             &self.prover_only,
@@ -201,6 +267,22 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
             &mut TimingTree::default(),
         )
     }
+
+    /// Proves with a [`ProverHandle`] which reports progress, can cancel the proof, and can store
+    /// checkpoints to resume from.
+    pub fn prove_with_handle(
+        &self,
+        inputs: PartialWitness<F>,
+        handle: &ProverHandle,
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        prove_with_handle(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            handle,
+            &mut TimingTree::default(),
+        )
+    }
 }
 
 /// Circuit data required by the prover.
@@ -217,6 +299,19 @@ pub struct VerifierCircuitData<
 impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
     VerifierCircuitData<F, C, D>
 {
+    pub fn to_bytes(&self, gate_serializer: &dyn GateSerializer<F, D>) -> IoResult<Vec<u8>> {
+        let mut buffer = Vec::new();
+        buffer.write_format_version()?;
+        buffer.write_verifier_circuit_data(self, gate_serializer)?;
+        Ok(buffer)
+    }
+
+    pub fn from_bytes(bytes: &[u8], gate_serializer: &dyn GateSerializer<F, D>) -> IoResult<Self> {
+        let mut buffer = Buffer::new(bytes);
+        buffer.read_format_version()?;
+        buffer.read_verifier_circuit_data(gate_serializer)
+    }
+
     pub fn verify(&self, proof_with_pis: ProofWithPublicInputs<F, C, D>) -> Result<()> {
         verify(proof_with_pis, &self.verifier_only, &self.common)
     }
@@ -235,7 +330,7 @@ pub struct ProverOnlyCircuitData<
     C: GenericConfig<D, F = F>,
     const D: usize,
 > {
-    pub generators: Vec<Box<dyn WitnessGenerator<F>>>,
+    pub generators: Vec<WitnessGeneratorRef<F>>,
     /// Generator indices (within the `Vec` above), indexed by the representative of each target
     /// they watch.
     pub generator_indices_by_watches: BTreeMap<usize, Vec<usize>>,
@@ -267,6 +362,21 @@ pub struct VerifierOnlyCircuitData<C: GenericConfig<D>, const D: usize> {
     pub circuit_digest: <<C as GenericConfig<D>>::Hasher as Hasher<C::F>>::Hash,
 }
 
+impl<C: GenericConfig<D>, const D: usize> VerifierOnlyCircuitData<C, D> {
+    pub fn to_bytes(&self) -> IoResult<Vec<u8>> {
+        let mut buffer = Vec::new();
+        buffer.write_format_version()?;
+        buffer.write_verifier_only_circuit_data(self)?;
+        Ok(buffer)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IoResult<Self> {
+        let mut buffer = Buffer::new(bytes);
+        buffer.read_format_version()?;
+        buffer.read_verifier_only_circuit_data()
+    }
+}
+
 /// Circuit data required by both the prover and the verifier.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct CommonCircuitData<F: RichField + Extendable<D>, const D: usize> {
@@ -296,9 +406,32 @@ pub struct CommonCircuitData<F: RichField + Extendable<D>, const D: usize> {
 
     /// The number of partial products needed to compute the `Z` polynomials.
     pub num_partial_products: usize,
+
+    /// The lookup tables used in this circuit.
+    pub luts: Vec<LookupTable>,
+
+    /// The number of polynomials used by the lookup argument, per challenge.
+    pub num_lookup_polys: usize,
+
+    /// The number of selector polynomials used by the lookup argument. They are stored after the
+    /// other constant polynomials.
+    pub num_lookup_selectors: usize,
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> CommonCircuitData<F, D> {
+    pub fn to_bytes(&self, gate_serializer: &dyn GateSerializer<F, D>) -> IoResult<Vec<u8>> {
+        let mut buffer = Vec::new();
+        buffer.write_format_version()?;
+        buffer.write_common_circuit_data(self, gate_serializer)?;
+        Ok(buffer)
+    }
+
+    pub fn from_bytes(bytes: &[u8], gate_serializer: &dyn GateSerializer<F, D>) -> IoResult<Self> {
+        let mut buffer = Buffer::new(bytes);
+        buffer.read_format_version()?;
+        buffer.read_common_circuit_data(gate_serializer)
+    }
+
     pub const fn degree_bits(&self) -> usize {
         self.fri_params.degree_bits
     }
@@ -327,6 +460,46 @@ impl<F: RichField + Extendable<D>, const D: usize> CommonCircuitData<F, D> {
         self.quotient_degree_factor * self.degree()
     }
 
+    /// The parameters on which the soundness of proofs of this circuit depends; see
+    /// [`security_report`](Self::security_report).
+    #[cfg(feature = "std")]
+    pub fn soundness_params(&self) -> SoundnessParams {
+        let lookup_width = if self.luts.is_empty() {
+            0
+        } else {
+            LookupGate::num_slots(&self.config) + LookupTableGate::num_slots(&self.config)
+        };
+        SoundnessParams {
+            field_bits: F::ORDER.ilog2() as usize,
+            extension_degree: D,
+            degree_bits: self.degree_bits(),
+            num_polys: self
+                .fri_oracles()
+                .iter()
+                .map(|oracle| oracle.num_polys)
+                .sum(),
+            // The partial products constraints have degree `quotient_degree_factor + 1`.
+            constraint_degree: self
+                .constraint_degree()
+                .max(self.quotient_degree_factor + 1),
+            // Besides the gate constraints, the vanishing polynomial checks `Z(1) = 1`, each partial
+            // product and each lookup polynomial.
+            num_constraints: self.num_gate_constraints
+                + 1
+                + (self.num_partial_products + 1)
+                + self.num_lookup_polys,
+            num_challenges: self.config.num_challenges,
+            permutation_width: self.config.num_routed_wires,
+            lookup_width,
+        }
+    }
+
+    /// The proven and conjectured soundness of proofs of this circuit.
+    #[cfg(feature = "std")]
+    pub fn security_report(&self) -> SecurityReport {
+        security_report(&self.config.fri_config, &self.soundness_params())
+    }
+
     /// Range of the constants polynomials in the `constants_sigmas_commitment`.
     pub fn constants_range(&self) -> Range<usize> {
         0..self.num_constants
@@ -343,8 +516,37 @@ impl<F: RichField + Extendable<D>, const D: usize> CommonCircuitData<F, D> {
     }
 
     /// Range of the partial products polynomials in the `zs_partial_products_commitment`.
-    pub fn partial_products_range(&self) -> RangeFrom<usize> {
-        self.config.num_challenges..
+    pub fn partial_products_range(&self) -> Range<usize> {
+        self.config.num_challenges..self.config.num_challenges * (1 + self.num_partial_products)
+    }
+
+    /// Range of the lookup polynomials in the `zs_partial_products_commitment`.
+    pub fn lookup_range(&self) -> Range<usize> {
+        let start = self.partial_products_range().end;
+        start..start + self.config.num_challenges * self.num_lookup_polys
+    }
+
+    /// Range of the lookup selector polynomials in the `constants_sigmas_commitment`.
+    pub fn lookup_selectors_range(&self) -> Range<usize> {
+        self.num_constants - self.num_lookup_selectors..self.num_constants
+    }
+
+    /// The number of helper polynomials holding partial sums of the `LookupGate` side of the
+    /// lookup argument.
+    pub(crate) fn num_looking_helpers(&self) -> usize {
+        ceil_div_usize(
+            LookupGate::num_slots(&self.config),
+            self.quotient_degree_factor,
+        )
+    }
+
+    /// The number of helper polynomials holding partial sums of the `LookupTableGate` side of the
+    /// lookup argument.
+    pub(crate) fn num_table_helpers(&self) -> usize {
+        ceil_div_usize(
+            LookupTableGate::num_slots(&self.config),
+            self.quotient_degree_factor,
+        )
     }
 
     pub(crate) fn get_fri_instance(&self, zeta: F::Extension) -> FriInstanceInfo<F, D> {
@@ -354,7 +556,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CommonCircuitData<F, D> {
             polynomials: self.fri_all_polys(),
         };
 
-        // The Z polynomials are also opened at g * zeta.
+        // The Z polynomials and the lookup polynomials are also opened at g * zeta.
         let g = F::Extension::primitive_root_of_unity(self.degree_bits());
         let zeta_next = g * zeta;
         let zeta_next_batch = FriBatchInfo {
@@ -380,7 +582,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CommonCircuitData<F, D> {
             polynomials: self.fri_all_polys(),
         };
 
-        // The Z polynomials are also opened at g * zeta.
+        // The Z polynomials and the lookup polynomials are also opened at g * zeta.
         let g = F::primitive_root_of_unity(self.degree_bits());
         let zeta_next = builder.mul_const_extension(g, zeta);
         let zeta_next_batch = FriBatchInfoTarget {
@@ -440,11 +642,18 @@ impl<F: RichField + Extendable<D>, const D: usize> CommonCircuitData<F, D> {
     }
 
     pub(crate) fn num_zs_partial_products_polys(&self) -> usize {
-        self.config.num_challenges * (1 + self.num_partial_products)
+        self.lookup_range().end
     }
 
     fn fri_zs_polys(&self) -> Vec<FriPolynomialInfo> {
-        FriPolynomialInfo::from_range(PlonkOracle::ZS_PARTIAL_PRODUCTS.index, self.zs_range())
+        [
+            FriPolynomialInfo::from_range(PlonkOracle::ZS_PARTIAL_PRODUCTS.index, self.zs_range()),
+            FriPolynomialInfo::from_range(
+                PlonkOracle::ZS_PARTIAL_PRODUCTS.index,
+                self.lookup_range(),
+            ),
+        ]
+        .concat()
     }
 
     fn fri_quotient_polys(&self) -> Vec<FriPolynomialInfo> {
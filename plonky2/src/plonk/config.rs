@@ -12,6 +12,9 @@ use crate::hash::hash_types::{HashOut, RichField};
 use crate::hash::hashing::{PlonkyPermutation, SPONGE_WIDTH};
 use crate::hash::keccak::KeccakHash;
 use crate::hash::poseidon::PoseidonHash;
+use crate::hash::poseidon2::Poseidon2Hash;
+use crate::hash::poseidon_bn254::PoseidonBn254Hash;
+use crate::hash::rescue_prime::RescuePrimeHash;
 use crate::iop::target::{BoolTarget, Target};
 use crate::plonk::circuit_builder::CircuitBuilder;
 
@@ -108,6 +111,26 @@ impl GenericConfig<2> for PoseidonGoldilocksConfig {
     type InnerHasher = PoseidonHash;
 }
 
+/// Configuration using Poseidon2 over the Goldilocks field.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Poseidon2GoldilocksConfig;
+impl GenericConfig<2> for Poseidon2GoldilocksConfig {
+    type F = GoldilocksField;
+    type FE = QuadraticExtension<Self::F>;
+    type Hasher = Poseidon2Hash;
+    type InnerHasher = Poseidon2Hash;
+}
+
+/// Configuration using Rescue-Prime over the Goldilocks field.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RescuePrimeGoldilocksConfig;
+impl GenericConfig<2> for RescuePrimeGoldilocksConfig {
+    type F = GoldilocksField;
+    type FE = QuadraticExtension<Self::F>;
+    type Hasher = RescuePrimeHash;
+    type InnerHasher = RescuePrimeHash;
+}
+
 /// Configuration using truncated Keccak over the Goldilocks field.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct KeccakGoldilocksConfig;
@@ -117,3 +140,15 @@ impl GenericConfig<2> for KeccakGoldilocksConfig {
     type Hasher = KeccakHash<25>;
     type InnerHasher = PoseidonHash;
 }
+
+/// Configuration using Poseidon over BN254 for Merkle trees and the challenger, over the Goldilocks
+/// field. Proofs with this configuration cannot be verified recursively in plonky2; it is meant for
+/// the last layer of recursion, whose verifier runs in a SNARK over BN254.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct PoseidonBn254GoldilocksConfig;
+impl GenericConfig<2> for PoseidonBn254GoldilocksConfig {
+    type F = GoldilocksField;
+    type FE = QuadraticExtension<Self::F>;
+    type Hasher = PoseidonBn254Hash;
+    type InnerHasher = PoseidonHash;
+}
@@ -20,6 +20,7 @@ use crate::plonk::proof::{
     OpeningSetTarget, Proof, ProofChallenges, ProofChallengesTarget, ProofTarget,
     ProofWithPublicInputs, ProofWithPublicInputsTarget,
 };
+use crate::plonk::vanishing_poly::NUM_LOOKUP_CHALLENGES;
 use crate::util::reverse_bits;
 
 fn get_challenges<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
@@ -46,6 +47,11 @@ fn get_challenges<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, cons
     challenger.observe_cap(wires_cap);
     let plonk_betas = challenger.get_n_challenges(num_challenges);
     let plonk_gammas = challenger.get_n_challenges(num_challenges);
+    let plonk_deltas = if common_data.luts.is_empty() {
+        vec![]
+    } else {
+        challenger.get_n_challenges(NUM_LOOKUP_CHALLENGES * num_challenges)
+    };
 
     challenger.observe_cap(plonk_zs_partial_products_cap);
     let plonk_alphas = challenger.get_n_challenges(num_challenges);
@@ -59,6 +65,7 @@ fn get_challenges<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, cons
         plonk_betas,
         plonk_gammas,
         plonk_alphas,
+        plonk_deltas,
         plonk_zeta,
         fri_challenges: challenger.fri_challenges::<C, D>(
             commit_phase_merkle_caps,
@@ -263,6 +270,11 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         challenger.observe_cap(wires_cap);
         let plonk_betas = challenger.get_n_challenges(self, num_challenges);
         let plonk_gammas = challenger.get_n_challenges(self, num_challenges);
+        let plonk_deltas = if inner_common_data.luts.is_empty() {
+            vec![]
+        } else {
+            challenger.get_n_challenges(self, NUM_LOOKUP_CHALLENGES * num_challenges)
+        };
 
         challenger.observe_cap(plonk_zs_partial_products_cap);
         let plonk_alphas = challenger.get_n_challenges(self, num_challenges);
@@ -276,6 +288,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             plonk_betas,
             plonk_gammas,
             plonk_alphas,
+            plonk_deltas,
             plonk_zeta,
             fri_challenges: challenger.fri_challenges(
                 self,
@@ -7,6 +7,8 @@ pub(crate) mod permutation_argument;
 pub mod plonk_common;
 pub mod proof;
 pub mod prover;
+pub mod prover_handle;
+pub mod solidity;
 mod validate_shape;
 pub(crate) mod vanishing_poly;
 pub mod vars;
@@ -116,7 +116,7 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         bytes: Vec<u8>,
         common_data: &CommonCircuitData<F, D>,
     ) -> anyhow::Result<Self> {
-        let mut buffer = Buffer::new(bytes);
+        let mut buffer = Buffer::new(&bytes);
         let proof = buffer
             .read_proof_with_public_inputs(common_data)
             .map_err(anyhow::Error::msg)?;
@@ -246,7 +246,7 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         bytes: Vec<u8>,
         common_data: &CommonCircuitData<F, D>,
     ) -> anyhow::Result<Self> {
-        let mut buffer = Buffer::new(bytes);
+        let mut buffer = Buffer::new(&bytes);
         let proof = buffer
             .read_compressed_proof_with_public_inputs(common_data)
             .map_err(anyhow::Error::msg)?;
@@ -264,6 +264,9 @@ pub(crate) struct ProofChallenges<F: RichField + Extendable<D>, const D: usize>
     /// Random values used to combine PLONK constraints.
     pub plonk_alphas: Vec<F>,
 
+    /// Random values used in the lookup argument. Empty if the circuit has no lookups.
+    pub plonk_deltas: Vec<F>,
+
     /// Point at which the PLONK polynomials are opened.
     pub plonk_zeta: F::Extension,
 
@@ -274,6 +277,7 @@ pub(crate) struct ProofChallengesTarget<const D: usize> {
     pub plonk_betas: Vec<Target>,
     pub plonk_gammas: Vec<Target>,
     pub plonk_alphas: Vec<Target>,
+    pub plonk_deltas: Vec<Target>,
     pub plonk_zeta: ExtensionTarget<D>,
     pub fri_challenges: FriChallengesTarget<D>,
 }
@@ -299,6 +303,8 @@ pub struct OpeningSet<F: RichField + Extendable<D>, const D: usize> {
     pub plonk_zs_next: Vec<F::Extension>,
     pub partial_products: Vec<F::Extension>,
     pub quotient_polys: Vec<F::Extension>,
+    pub lookup_zs: Vec<F::Extension>,
+    pub lookup_zs_next: Vec<F::Extension>,
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> OpeningSet<F, D> {
@@ -319,17 +325,19 @@ impl<F: RichField + Extendable<D>, const D: usize> OpeningSet<F, D> {
         };
         let constants_sigmas_eval = eval_commitment(zeta, constants_sigmas_commitment);
         let zs_partial_products_eval = eval_commitment(zeta, zs_partial_products_commitment);
+        let zs_partial_products_next_eval =
+            eval_commitment(g * zeta, zs_partial_products_commitment);
         Self {
             constants: constants_sigmas_eval[common_data.constants_range()].to_vec(),
             plonk_sigmas: constants_sigmas_eval[common_data.sigmas_range()].to_vec(),
             wires: eval_commitment(zeta, wires_commitment),
             plonk_zs: zs_partial_products_eval[common_data.zs_range()].to_vec(),
-            plonk_zs_next: eval_commitment(g * zeta, zs_partial_products_commitment)
-                [common_data.zs_range()]
-            .to_vec(),
+            plonk_zs_next: zs_partial_products_next_eval[common_data.zs_range()].to_vec(),
             partial_products: zs_partial_products_eval[common_data.partial_products_range()]
                 .to_vec(),
             quotient_polys: eval_commitment(zeta, quotient_polys_commitment),
+            lookup_zs: zs_partial_products_eval[common_data.lookup_range()].to_vec(),
+            lookup_zs_next: zs_partial_products_next_eval[common_data.lookup_range()].to_vec(),
         }
     }
 
@@ -341,12 +349,17 @@ impl<F: RichField + Extendable<D>, const D: usize> OpeningSet<F, D> {
                 self.wires.as_slice(),
                 self.plonk_zs.as_slice(),
                 self.partial_products.as_slice(),
+                self.lookup_zs.as_slice(),
                 self.quotient_polys.as_slice(),
             ]
             .concat(),
         };
         let zeta_next_batch = FriOpeningBatch {
-            values: self.plonk_zs_next.clone(),
+            values: [
+                self.plonk_zs_next.as_slice(),
+                self.lookup_zs_next.as_slice(),
+            ]
+            .concat(),
         };
         FriOpenings {
             batches: vec![zeta_batch, zeta_next_batch],
@@ -364,6 +377,8 @@ pub struct OpeningSetTarget<const D: usize> {
     pub plonk_zs_next: Vec<ExtensionTarget<D>>,
     pub partial_products: Vec<ExtensionTarget<D>>,
     pub quotient_polys: Vec<ExtensionTarget<D>>,
+    pub lookup_zs: Vec<ExtensionTarget<D>>,
+    pub lookup_zs_next: Vec<ExtensionTarget<D>>,
 }
 
 impl<const D: usize> OpeningSetTarget<D> {
@@ -375,12 +390,17 @@ impl<const D: usize> OpeningSetTarget<D> {
                 self.wires.as_slice(),
                 self.plonk_zs.as_slice(),
                 self.partial_products.as_slice(),
+                self.lookup_zs.as_slice(),
                 self.quotient_polys.as_slice(),
             ]
             .concat(),
         };
         let zeta_next_batch = FriOpeningBatchTarget {
-            values: self.plonk_zs_next.clone(),
+            values: [
+                self.plonk_zs_next.as_slice(),
+                self.lookup_zs_next.as_slice(),
+            ]
+            .concat(),
         };
         FriOpeningsTarget {
             batches: vec![zeta_batch, zeta_next_batch],
@@ -10,6 +10,9 @@ use crate::field::polynomial::{PolynomialCoeffs, PolynomialValues};
 use crate::field::types::Field;
 use crate::field::zero_poly_coset::ZeroPolyOnCoset;
 use crate::fri::oracle::PolynomialBatch;
+use crate::gates::lookup::LookupGate;
+use crate::gates::lookup_table::LookupTableGate;
+use crate::gates::selectors::LookupSelectors;
 use crate::hash::hash_types::RichField;
 use crate::iop::challenger::Challenger;
 use crate::iop::generator::generate_partial_witness;
@@ -18,7 +21,10 @@ use crate::plonk::circuit_data::{CommonCircuitData, ProverOnlyCircuitData};
 use crate::plonk::config::{GenericConfig, Hasher};
 use crate::plonk::plonk_common::PlonkOracle;
 use crate::plonk::proof::{OpeningSet, Proof, ProofWithPublicInputs};
-use crate::plonk::vanishing_poly::eval_vanishing_poly_base_batch;
+use crate::plonk::prover_handle::{checkpoint_id, ProverHandle, ProvingPhase};
+use crate::plonk::vanishing_poly::{
+    eval_luts, eval_vanishing_poly_base_batch, lookup_challenges, NUM_LOOKUP_CHALLENGES,
+};
 use crate::plonk::vars::EvaluationVarsBaseBatch;
 use crate::timed;
 use crate::util::partial_products::{partial_products_and_z_gx, quotient_chunk_products};
@@ -30,49 +36,105 @@ pub fn prove<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D:
     common_data: &CommonCircuitData<F, D>,
     inputs: PartialWitness<F>,
     timing: &mut TimingTree,
+) -> Result<ProofWithPublicInputs<F, C, D>> {
+    prove_with_handle(
+        prover_data,
+        common_data,
+        inputs,
+        &ProverHandle::default(),
+        timing,
+    )
+}
+
+/// Like [`prove`], but reports progress to `handle`, stops with a
+/// [`ProvingCancelled`](crate::plonk::prover_handle::ProvingCancelled) error once it is cancelled,
+/// and stores or resumes from checkpoints if it has a checkpoint directory. Checkpoints hold the
+/// witness in the clear, so they are refused for `zero_knowledge` circuits.
+pub fn prove_with_handle<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+    inputs: PartialWitness<F>,
+    handle: &ProverHandle,
+    timing: &mut TimingTree,
 ) -> Result<ProofWithPublicInputs<F, C, D>> {
     let config = &common_data.config;
     let num_challenges = config.num_challenges;
     let quotient_degree = common_data.quotient_degree();
     let degree = common_data.degree();
 
-    let partition_witness = timed!(
-        timing,
-        &format!("run {} generators", prover_data.generators.len()),
-        generate_partial_witness(inputs, prover_data, common_data)
+    ensure!(
+        !(config.zero_knowledge && handle.has_checkpoint_dir()),
+        "Checkpoints would store the witness of a zero-knowledge proof in the clear"
     );
 
-    let public_inputs = partition_witness.get_targets(&prover_data.public_inputs);
+    handle.enter_phase(ProvingPhase::Witness)?;
+    let checkpoint_id = checkpoint_id::<F, C, D>(prover_data.circuit_digest, &inputs);
+    let (public_inputs, witness, mut saved_commitments) =
+        match handle.load_checkpoint::<F, C, D>(&checkpoint_id) {
+            Some(checkpoint) => (
+                checkpoint.public_inputs,
+                MatrixWitness {
+                    wire_values: checkpoint.witness,
+                },
+                checkpoint.commitments.into_iter(),
+            ),
+            None => {
+                let partition_witness = timed!(
+                    timing,
+                    &format!("run {} generators", prover_data.generators.len()),
+                    generate_partial_witness(inputs, prover_data, common_data)
+                );
+                let public_inputs = partition_witness.get_targets(&prover_data.public_inputs);
+                let witness = timed!(
+                    timing,
+                    "compute full witness",
+                    partition_witness.full_witness()
+                );
+                (public_inputs, witness, Vec::new().into_iter())
+            }
+        };
     let public_inputs_hash = C::InnerHasher::hash_no_pad(&public_inputs);
 
-    let witness = timed!(
-        timing,
-        "compute full witness",
-        partition_witness.full_witness()
-    );
-
-    let wires_values: Vec<PolynomialValues<F>> = timed!(
-        timing,
-        "compute wire polynomials",
-        witness
-            .wire_values
-            .par_iter()
-            .map(|column| PolynomialValues::new(column.clone()))
-            .collect()
-    );
+    handle.enter_phase(ProvingPhase::Commitment)?;
+    let wires_commitment = match saved_commitments.next() {
+        Some(commitment) => commitment,
+        None => {
+            let wires_values: Vec<PolynomialValues<F>> = timed!(
+                timing,
+                "compute wire polynomials",
+                witness
+                    .wire_values
+                    .par_iter()
+                    .map(|column| PolynomialValues::new(column.clone()))
+                    .collect()
+            );
 
-    let wires_commitment = timed!(
-        timing,
-        "compute wires commitment",
-        PolynomialBatch::from_values(
-            wires_values,
-            config.fri_config.rate_bits,
-            config.zero_knowledge && PlonkOracle::WIRES.blinding,
-            config.fri_config.cap_height,
-            timing,
-            prover_data.fft_root_table.as_ref(),
-        )
-    );
+            let wires_commitment = timed!(
+                timing,
+                "compute wires commitment",
+                PolynomialBatch::from_values_with_storage(
+                    wires_values,
+                    config.fri_config.rate_bits,
+                    config.zero_knowledge && PlonkOracle::WIRES.blinding,
+                    config.fri_config.cap_height,
+                    timing,
+                    prover_data.fft_root_table.as_ref(),
+                    handle.commitment_storage(),
+                )?
+            );
+            handle.save_checkpoint(
+                &checkpoint_id,
+                &public_inputs,
+                &witness.wire_values,
+                &[&wires_commitment],
+            );
+            wires_commitment
+        }
+    };
 
     let mut challenger = Challenger::<F, C::Hasher>::new();
 
@@ -83,84 +145,140 @@ pub fn prove<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D:
     challenger.observe_cap(&wires_commitment.merkle_tree.cap);
     let betas = challenger.get_n_challenges(num_challenges);
     let gammas = challenger.get_n_challenges(num_challenges);
+    let has_lookup = !common_data.luts.is_empty();
+    let deltas = if has_lookup {
+        challenger.get_n_challenges(NUM_LOOKUP_CHALLENGES * num_challenges)
+    } else {
+        vec![]
+    };
 
-    assert!(
-        common_data.quotient_degree_factor < common_data.config.num_routed_wires,
-        "When the number of routed wires is smaller that the degree, we should change the logic to avoid computing partial products."
-    );
-    let mut partial_products_and_zs = timed!(
-        timing,
-        "compute partial products",
-        all_wires_permutation_partial_products(&witness, &betas, &gammas, prover_data, common_data)
-    );
-
-    // Z is expected at the front of our batch; see `zs_range` and `partial_products_range`.
-    let plonk_z_vecs = partial_products_and_zs
-        .iter_mut()
-        .map(|partial_products_and_z| partial_products_and_z.pop().unwrap())
-        .collect();
-    let zs_partial_products = [plonk_z_vecs, partial_products_and_zs.concat()].concat();
+    let partial_products_and_zs_commitment = match saved_commitments.next() {
+        Some(commitment) => commitment,
+        None => {
+            handle.check_cancelled()?;
+            assert!(
+                common_data.quotient_degree_factor < common_data.config.num_routed_wires,
+                "When the number of routed wires is smaller that the degree, we should change the logic to avoid computing partial products."
+            );
+            let mut partial_products_and_zs = timed!(
+                timing,
+                "compute partial products",
+                all_wires_permutation_partial_products(
+                    &witness,
+                    &betas,
+                    &gammas,
+                    prover_data,
+                    common_data
+                )
+            );
 
-    let partial_products_and_zs_commitment = timed!(
-        timing,
-        "commit to partial products and Z's",
-        PolynomialBatch::from_values(
-            zs_partial_products,
-            config.fri_config.rate_bits,
-            config.zero_knowledge && PlonkOracle::ZS_PARTIAL_PRODUCTS.blinding,
-            config.fri_config.cap_height,
-            timing,
-            prover_data.fft_root_table.as_ref(),
-        )
-    );
+            // Z is expected at the front of our batch; see `zs_range` and `partial_products_range`.
+            let plonk_z_vecs = partial_products_and_zs
+                .iter_mut()
+                .map(|partial_products_and_z| partial_products_and_z.pop().unwrap())
+                .collect();
+            let lookup_polys = if has_lookup {
+                timed!(
+                    timing,
+                    "compute lookup polys",
+                    compute_all_lookup_polys(&witness, &deltas, prover_data, common_data)
+                )
+            } else {
+                vec![]
+            };
+            // The lookup polys come after the partial products; see `lookup_range`.
+            let zs_partial_products =
+                [plonk_z_vecs, partial_products_and_zs.concat(), lookup_polys].concat();
+
+            let partial_products_and_zs_commitment = timed!(
+                timing,
+                "commit to partial products and Z's",
+                PolynomialBatch::from_values_with_storage(
+                    zs_partial_products,
+                    config.fri_config.rate_bits,
+                    config.zero_knowledge && PlonkOracle::ZS_PARTIAL_PRODUCTS.blinding,
+                    config.fri_config.cap_height,
+                    timing,
+                    prover_data.fft_root_table.as_ref(),
+                    handle.commitment_storage(),
+                )?
+            );
+            handle.save_checkpoint(
+                &checkpoint_id,
+                &public_inputs,
+                &witness.wire_values,
+                &[&wires_commitment, &partial_products_and_zs_commitment],
+            );
+            partial_products_and_zs_commitment
+        }
+    };
 
     challenger.observe_cap(&partial_products_and_zs_commitment.merkle_tree.cap);
 
     let alphas = challenger.get_n_challenges(num_challenges);
 
-    let quotient_polys = timed!(
-        timing,
-        "compute quotient polys",
-        compute_quotient_polys(
-            common_data,
-            prover_data,
-            &public_inputs_hash,
-            &wires_commitment,
-            &partial_products_and_zs_commitment,
-            &betas,
-            &gammas,
-            &alphas,
-        )
-    );
+    handle.enter_phase(ProvingPhase::Quotient)?;
+    let quotient_polys_commitment = match saved_commitments.next() {
+        Some(commitment) => commitment,
+        None => {
+            let quotient_polys = timed!(
+                timing,
+                "compute quotient polys",
+                compute_quotient_polys(
+                    common_data,
+                    prover_data,
+                    &public_inputs_hash,
+                    &wires_commitment,
+                    &partial_products_and_zs_commitment,
+                    &betas,
+                    &gammas,
+                    &deltas,
+                    &alphas,
+                )
+            );
 
-    // Compute the quotient polynomials, aka `t` in the Plonk paper.
-    let all_quotient_poly_chunks = timed!(
-        timing,
-        "split up quotient polys",
-        quotient_polys
-            .into_par_iter()
-            .flat_map(|mut quotient_poly| {
-                quotient_poly.trim_to_len(quotient_degree).expect(
-                    "Quotient has failed, the vanishing polynomial is not divisible by Z_H",
-                );
-                // Split quotient into degree-n chunks.
-                quotient_poly.chunks(degree)
-            })
-            .collect()
-    );
+            // Compute the quotient polynomials, aka `t` in the Plonk paper.
+            let all_quotient_poly_chunks = timed!(
+                timing,
+                "split up quotient polys",
+                quotient_polys
+                    .into_par_iter()
+                    .flat_map(|mut quotient_poly| {
+                        quotient_poly.trim_to_len(quotient_degree).expect(
+                            "Quotient has failed, the vanishing polynomial is not divisible by Z_H",
+                        );
+                        // Split quotient into degree-n chunks.
+                        quotient_poly.chunks(degree)
+                    })
+                    .collect()
+            );
 
-    let quotient_polys_commitment = timed!(
-        timing,
-        "commit to quotient polys",
-        PolynomialBatch::from_coeffs(
-            all_quotient_poly_chunks,
-            config.fri_config.rate_bits,
-            config.zero_knowledge && PlonkOracle::QUOTIENT.blinding,
-            config.fri_config.cap_height,
-            timing,
-            prover_data.fft_root_table.as_ref(),
-        )
-    );
+            let quotient_polys_commitment = timed!(
+                timing,
+                "commit to quotient polys",
+                PolynomialBatch::from_coeffs_with_storage(
+                    all_quotient_poly_chunks,
+                    config.fri_config.rate_bits,
+                    config.zero_knowledge && PlonkOracle::QUOTIENT.blinding,
+                    config.fri_config.cap_height,
+                    timing,
+                    prover_data.fft_root_table.as_ref(),
+                    handle.commitment_storage(),
+                )?
+            );
+            handle.save_checkpoint(
+                &checkpoint_id,
+                &public_inputs,
+                &witness.wire_values,
+                &[
+                    &wires_commitment,
+                    &partial_products_and_zs_commitment,
+                    &quotient_polys_commitment,
+                ],
+            );
+            quotient_polys_commitment
+        }
+    };
 
     challenger.observe_cap(&quotient_polys_commitment.merkle_tree.cap);
 
@@ -189,6 +307,7 @@ pub fn prove<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D:
     );
     challenger.observe_openings(&openings.to_fri_openings());
 
+    handle.enter_phase(ProvingPhase::Fri)?;
     let opening_proof = timed!(
         timing,
         "compute opening proofs",
@@ -213,6 +332,7 @@ pub fn prove<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D:
         openings,
         opening_proof,
     };
+    handle.remove_checkpoint(&checkpoint_id);
     Ok(ProofWithPublicInputs {
         proof,
         public_inputs,
@@ -306,6 +426,120 @@ fn wires_permutation_partial_products_and_zs<
         .collect()
 }
 
+/// Computes the lookup polynomials `[RE, Z, helpers..]` of each repetition of the lookup
+/// argument. See `check_lookup_constraints` for the relations they satisfy.
+fn compute_all_lookup_polys<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    witness: &MatrixWitness<F>,
+    deltas: &[F],
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+) -> Vec<PolynomialValues<F>> {
+    let degree = common_data.degree();
+    let max_degree = common_data.quotient_degree_factor;
+    let num_lookup_slots = LookupGate::num_slots(&common_data.config);
+    let num_table_slots = LookupTableGate::num_slots(&common_data.config);
+
+    let lookup_selectors = common_data
+        .lookup_selectors_range()
+        .map(|i| {
+            prover_data.constants_sigmas_commitment.polynomials[i]
+                .clone()
+                .fft()
+                .values
+        })
+        .collect::<Vec<_>>();
+    let selector = |s: LookupSelectors, row: usize| lookup_selectors[s as usize][row];
+
+    (0..common_data.config.num_challenges)
+        .flat_map(|i| {
+            let [a, alpha, b, delta] = lookup_challenges(deltas, i);
+
+            // The looking helpers, followed by the table helpers, of each row.
+            let helpers = (0..degree)
+                .into_par_iter()
+                .map(|row| {
+                    let tag = selector(LookupSelectors::Tag, row) * a.square();
+                    let combo = |inp, out| alpha - (inp + a * out + tag);
+                    let denominators = (0..num_lookup_slots)
+                        .map(|j| {
+                            combo(
+                                witness.get_wire(row, LookupGate::wire_ith_looking_inp(j)),
+                                witness.get_wire(row, LookupGate::wire_ith_looking_out(j)),
+                            )
+                        })
+                        .chain((0..num_table_slots).map(|j| {
+                            combo(
+                                witness.get_wire(row, LookupTableGate::wire_ith_looked_inp(j)),
+                                witness.get_wire(row, LookupTableGate::wire_ith_looked_out(j)),
+                            )
+                        }))
+                        .collect::<Vec<_>>();
+                    let inverses = F::batch_multiplicative_inverse(&denominators);
+                    let (looking_inverses, table_inverses) = inverses.split_at(num_lookup_slots);
+                    let multiplicities = (0..num_table_slots)
+                        .map(|j| witness.get_wire(row, LookupTableGate::wire_ith_multiplicity(j)))
+                        .collect::<Vec<_>>();
+
+                    looking_inverses
+                        .chunks(max_degree)
+                        .map(|chunk| chunk.iter().copied().sum())
+                        .chain(
+                            table_inverses
+                                .chunks(max_degree)
+                                .zip(multiplicities.chunks(max_degree))
+                                .map(|(invs, ms)| {
+                                    invs.iter().zip(ms).map(|(&inv, &m)| inv * m).sum()
+                                }),
+                        )
+                        .collect::<Vec<F>>()
+                })
+                .collect::<Vec<_>>();
+            let num_looking_helpers = common_data.num_looking_helpers();
+
+            // The logUp running sum starts at 0.
+            let mut z = Vec::with_capacity(degree);
+            let mut z_x = F::ZERO;
+            for (row, row_helpers) in helpers.iter().enumerate() {
+                z.push(z_x);
+                let (looking, table) = row_helpers.split_at(num_looking_helpers);
+                let table_row = selector(LookupSelectors::TableTransition, row)
+                    + selector(LookupSelectors::TableLast, row);
+                z_x += selector(LookupSelectors::Lookup, row) * looking.iter().copied().sum()
+                    - table_row * table.iter().copied().sum();
+            }
+
+            // The running evaluation is computed backwards, from the last table row.
+            let delta_pow = delta.exp_u64(num_table_slots as u64);
+            let mut re = vec![F::ZERO; degree];
+            let mut re_next = F::ZERO;
+            for row in (0..degree).rev() {
+                let transition = selector(LookupSelectors::TableTransition, row);
+                let last = selector(LookupSelectors::TableLast, row);
+                if transition.is_zero() && last.is_zero() {
+                    continue;
+                }
+                let row_eval = (0..num_table_slots).rev().fold(F::ZERO, |acc, j| {
+                    acc * delta
+                        + witness.get_wire(row, LookupTableGate::wire_ith_looked_inp(j))
+                        + b * witness.get_wire(row, LookupTableGate::wire_ith_looked_out(j))
+                });
+                re[row] = row_eval + transition * delta_pow * re_next;
+                re_next = re[row];
+            }
+
+            let helper_polys = transpose(&helpers).into_iter().map(PolynomialValues::new);
+            [PolynomialValues::new(re), PolynomialValues::new(z)]
+                .into_iter()
+                .chain(helper_polys)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
 const BATCH_SIZE: usize = 32;
 
 fn compute_quotient_polys<
@@ -321,9 +555,23 @@ fn compute_quotient_polys<
     zs_partial_products_commitment: &'a PolynomialBatch<F, C, D>,
     betas: &[F],
     gammas: &[F],
+    deltas: &[F],
     alphas: &[F],
 ) -> Vec<PolynomialCoeffs<F>> {
     let num_challenges = common_data.config.num_challenges;
+    // The evaluations of the lookup tables, which only depend on the challenges.
+    let lut_evals = deltas
+        .chunks(NUM_LOOKUP_CHALLENGES)
+        .map(|challenges| {
+            let [_, _, b, delta]: [F; NUM_LOOKUP_CHALLENGES] = challenges.try_into().unwrap();
+            eval_luts(
+                &common_data.luts,
+                LookupTableGate::num_slots(&common_data.config),
+                b,
+                delta,
+            )
+        })
+        .collect::<Vec<_>>();
     let quotient_degree_bits = log2_ceil(common_data.quotient_degree_factor);
     assert!(
         quotient_degree_bits <= common_data.config.fri_config.rate_bits,
@@ -362,6 +610,8 @@ fn compute_quotient_polys<
             let mut next_zs_batch = Vec::with_capacity(xs_batch.len());
             let mut partial_products_batch = Vec::with_capacity(xs_batch.len());
             let mut s_sigmas_batch = Vec::with_capacity(xs_batch.len());
+            let mut local_lookup_zs_batch = Vec::with_capacity(xs_batch.len());
+            let mut next_lookup_zs_batch = Vec::with_capacity(xs_batch.len());
 
             let mut local_constants_batch_refs = Vec::with_capacity(xs_batch.len());
             let mut local_wires_batch_refs = Vec::with_capacity(xs_batch.len());
@@ -377,11 +627,14 @@ fn compute_quotient_polys<
                 let local_wires = wires_commitment.get_lde_values(i, step);
                 let local_zs_partial_products =
                     zs_partial_products_commitment.get_lde_values(i, step);
+                let next_zs_partial_products =
+                    zs_partial_products_commitment.get_lde_values(i_next, step);
                 let local_zs = &local_zs_partial_products[common_data.zs_range()];
-                let next_zs = &zs_partial_products_commitment.get_lde_values(i_next, step)
-                    [common_data.zs_range()];
+                let next_zs = &next_zs_partial_products[common_data.zs_range()];
                 let partial_products =
                     &local_zs_partial_products[common_data.partial_products_range()];
+                let local_lookup_zs = &local_zs_partial_products[common_data.lookup_range()];
+                let next_lookup_zs = &next_zs_partial_products[common_data.lookup_range()];
 
                 debug_assert_eq!(local_wires.len(), common_data.config.num_wires);
                 debug_assert_eq!(local_zs.len(), num_challenges);
@@ -394,6 +647,8 @@ fn compute_quotient_polys<
                 next_zs_batch.push(next_zs);
                 partial_products_batch.push(partial_products);
                 s_sigmas_batch.push(s_sigmas);
+                local_lookup_zs_batch.push(local_lookup_zs);
+                next_lookup_zs_batch.push(next_lookup_zs);
             }
 
             // NB (JN): I'm not sure how (in)efficient the below is. It needs measuring.
@@ -429,8 +684,12 @@ fn compute_quotient_polys<
                 &next_zs_batch,
                 &partial_products_batch,
                 &s_sigmas_batch,
+                &local_lookup_zs_batch,
+                &next_lookup_zs_batch,
                 betas,
                 gammas,
+                deltas,
+                &lut_evals,
                 alphas,
                 &z_h_on_coset,
             );
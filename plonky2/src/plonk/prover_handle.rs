@@ -0,0 +1,484 @@
+//! Control over a running proof: progress reporting, cooperative cancellation and, with the `std`
+//! feature, on-disk checkpoints from which a proof interrupted by a crash can be resumed.
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::field::extension::Extendable;
+use crate::fri::oracle::{CommitmentStorage, PolynomialBatch};
+use crate::hash::hash_types::RichField;
+use crate::iop::target::Target;
+use crate::iop::witness::PartialWitness;
+use crate::plonk::config::{GenericConfig, GenericHashOut, Hasher};
+#[cfg(feature = "std")]
+use crate::util::serialization::{Buffer, IoError, IoResult, Read, Remaining, Write};
+
+/// Version of the binary layout of prover checkpoints. Checkpoints written with another version
+/// are ignored.
+pub const CHECKPOINT_FORMAT_VERSION: u32 = 1;
+
+/// The phases of a proof, in the order in which a prover goes through them. Provers which commit
+/// to several batches of polynomials, like the EVM prover with one batch per table, may enter the
+/// same phase several times.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ProvingPhase {
+    /// Witness generation, or trace generation for STARKs.
+    Witness,
+    /// Committing to the witness and to the permutation and lookup polynomials.
+    Commitment,
+    /// Computing and committing to the quotient polynomials.
+    Quotient,
+    /// Computing the FRI opening proof.
+    Fri,
+}
+
+/// The error returned by a prover whose [`ProverHandle`] was cancelled. It can be recovered from
+/// the returned `anyhow::Error` with `downcast_ref::<ProvingCancelled>()`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct ProvingCancelled;
+
+impl Display for ProvingCancelled {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "proving was cancelled")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ProvingCancelled {}
+
+type ProgressCallback = Arc<dyn Fn(ProvingPhase) + Send + Sync>;
+
+/// A handle to a running proof. Clones share the same cancellation flag, so a clone can be kept
+/// by another thread to [`cancel`](Self::cancel) the proof.
+///
+/// Cancellation is cooperative: the prover checks the flag whenever it enters a new
+/// [`ProvingPhase`], and then returns a [`ProvingCancelled`] error.
+#[derive(Clone, Default)]
+pub struct ProverHandle {
+    cancelled: Arc<AtomicBool>,
+    progress: Option<ProgressCallback>,
+    commitment_storage: CommitmentStorage,
+    #[cfg(feature = "std")]
+    checkpoint_dir: Option<PathBuf>,
+}
+
+impl Debug for ProverHandle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("ProverHandle");
+        s.field("cancelled", &self.is_cancelled());
+        s.field("commitment_storage", &self.commitment_storage);
+        #[cfg(feature = "std")]
+        s.field("checkpoint_dir", &self.checkpoint_dir);
+        s.finish_non_exhaustive()
+    }
+}
+
+impl ProverHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Calls `callback` whenever the prover enters a new phase. The callback runs on the proving
+    /// thread, so it should be cheap, e.g. sending the phase over a channel.
+    pub fn with_progress<P: Fn(ProvingPhase) + Send + Sync + 'static>(
+        mut self,
+        callback: P,
+    ) -> Self {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Stores a checkpoint in `dir` after each commitment round, from which a later proof of the
+    /// same circuit with the same inputs resumes. The checkpoint is deleted once the proof is done.
+    ///
+    /// Checkpoints contain the full witness, unencrypted, so anyone who can read `dir` learns the
+    /// prover's secret inputs. For this reason, proving a `zero_knowledge` circuit with a
+    /// checkpoint directory fails.
+    #[cfg(feature = "std")]
+    pub fn with_checkpoint_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.checkpoint_dir = Some(dir.into());
+        self
+    }
+
+    /// Stores the prover's polynomial commitments in `storage`, e.g. on disk for circuits whose
+    /// LDEs do not fit in memory.
+    pub fn with_commitment_storage(mut self, storage: CommitmentStorage) -> Self {
+        self.commitment_storage = storage;
+        self
+    }
+
+    pub fn commitment_storage(&self) -> &CommitmentStorage {
+        &self.commitment_storage
+    }
+
+    /// Whether the prover should store checkpoints.
+    pub fn has_checkpoint_dir(&self) -> bool {
+        #[cfg(feature = "std")]
+        return self.checkpoint_dir.is_some();
+        #[cfg(not(feature = "std"))]
+        return false;
+    }
+
+    /// Asks the prover to stop when it next enters a phase.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Fails with [`ProvingCancelled`] if the handle was cancelled.
+    pub fn check_cancelled(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(anyhow::Error::msg(ProvingCancelled))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Called by provers when entering `phase`. Reports the phase to the progress callback, then
+    /// fails if the handle was cancelled, possibly by the callback itself.
+    pub fn enter_phase(&self, phase: ProvingPhase) -> Result<()> {
+        if let Some(progress) = &self.progress {
+            progress(phase);
+        }
+        self.check_cancelled()
+    }
+}
+
+/// The state of a proof after one or more commitment rounds: its public inputs, its witness as a
+/// list of columns, and the commitments of the rounds which have been completed, in order.
+/// Everything else the prover needs, including all challenges, is recomputed from them.
+///
+/// The Plonk prover stores the wires, partial products and quotient batches. Provers outside this
+/// crate, like the EVM prover, store their own commitments with [`ProverHandle::save_checkpoint`].
+pub struct ProverCheckpoint<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+> {
+    pub public_inputs: Vec<F>,
+    pub witness: Vec<Vec<F>>,
+    pub commitments: Vec<PolynomialBatch<F, C, D>>,
+}
+
+/// Identifies a proof by its circuit and inputs, so that a checkpoint is only resumed by a proof
+/// of the same statement.
+pub(crate) fn checkpoint_id<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    circuit_digest: <C::Hasher as Hasher<F>>::Hash,
+    inputs: &PartialWitness<F>,
+) -> Vec<u8> {
+    let mut entries = inputs
+        .target_values
+        .iter()
+        .map(|(&target, &value)| {
+            let (kind, a, b) = match target {
+                Target::Wire(wire) => (0, wire.row, wire.column),
+                Target::VirtualTarget { index } => (1, index, 0),
+            };
+            ((kind, a, b), value)
+        })
+        .collect::<Vec<_>>();
+    entries.sort_unstable_by_key(|&(key, _)| key);
+
+    let mut elements = circuit_digest.to_vec();
+    for ((kind, a, b), value) in entries {
+        elements.extend([
+            F::from_canonical_usize(kind),
+            F::from_canonical_usize(a),
+            F::from_canonical_usize(b),
+            value,
+        ]);
+    }
+    C::InnerHasher::hash_no_pad(&elements).to_bytes()
+}
+
+#[cfg(feature = "std")]
+impl ProverHandle {
+    fn checkpoint_path(&self, id: &[u8]) -> Option<PathBuf> {
+        let name = id.iter().map(|b| format!("{b:02x}")).collect::<String>();
+        self.checkpoint_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{name}.ckpt")))
+    }
+
+    /// Loads the checkpoint of the proof identified by `id`, if there is a valid one.
+    pub fn load_checkpoint<
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+        const D: usize,
+    >(
+        &self,
+        id: &[u8],
+    ) -> Option<ProverCheckpoint<F, C, D>> {
+        let path = self.checkpoint_path(id)?;
+        let bytes = std::fs::read(&path).ok()?;
+        match read_checkpoint(&mut Buffer::new(&bytes), id) {
+            Ok(checkpoint) => Some(checkpoint),
+            Err(_) => {
+                log::warn!("Ignoring invalid prover checkpoint {}", path.display());
+                None
+            }
+        }
+    }
+
+    /// Stores a checkpoint of the proof identified by `id`, replacing any previous one. Failing to
+    /// store it only loses the ability to resume, so it is logged rather than returned.
+    pub fn save_checkpoint<
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+        const D: usize,
+    >(
+        &self,
+        id: &[u8],
+        public_inputs: &[F],
+        witness: &[Vec<F>],
+        commitments: &[&PolynomialBatch<F, C, D>],
+    ) {
+        let Some(path) = self.checkpoint_path(id) else {
+            return;
+        };
+        let mut bytes = Vec::new();
+        write_checkpoint(&mut bytes, id, public_inputs, witness, commitments)
+            .expect("Writing to a vector can't fail");
+        if let Err(e) = write_atomically(&path, &bytes) {
+            log::warn!("Failed to store prover checkpoint {}: {e}", path.display());
+        }
+    }
+
+    /// Deletes the checkpoint of the proof identified by `id`, once the proof is done.
+    pub fn remove_checkpoint(&self, id: &[u8]) {
+        if let Some(path) = self.checkpoint_path(id) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl ProverHandle {
+    pub fn load_checkpoint<
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+        const D: usize,
+    >(
+        &self,
+        _id: &[u8],
+    ) -> Option<ProverCheckpoint<F, C, D>> {
+        None
+    }
+
+    pub fn save_checkpoint<
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+        const D: usize,
+    >(
+        &self,
+        _id: &[u8],
+        _public_inputs: &[F],
+        _witness: &[Vec<F>],
+        _commitments: &[&PolynomialBatch<F, C, D>],
+    ) {
+    }
+
+    pub fn remove_checkpoint(&self, _id: &[u8]) {}
+}
+
+/// Writes `bytes` to a temporary file which is then renamed to `path`, so that a crash never
+/// leaves a truncated checkpoint behind.
+#[cfg(feature = "std")]
+fn write_atomically(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let tmp_path = path.with_extension("ckpt.tmp");
+    std::fs::write(&tmp_path, bytes)?;
+    std::fs::rename(tmp_path, path)
+}
+
+#[cfg(feature = "std")]
+fn write_checkpoint<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
+    buffer: &mut Vec<u8>,
+    id: &[u8],
+    public_inputs: &[F],
+    witness: &[Vec<F>],
+    commitments: &[&PolynomialBatch<F, C, D>],
+) -> IoResult<()> {
+    buffer.write_u32(CHECKPOINT_FORMAT_VERSION)?;
+    buffer.write_usize(id.len())?;
+    buffer.write_all(id)?;
+    buffer.write_field_vec_with_length(public_inputs)?;
+    buffer.write_usize(witness.len())?;
+    for column in witness {
+        buffer.write_field_vec_with_length(column)?;
+    }
+    buffer.write_usize(commitments.len())?;
+    for commitment in commitments {
+        buffer.write_polynomial_batch(commitment)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+fn read_checkpoint<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
+    buffer: &mut Buffer,
+    id: &[u8],
+) -> IoResult<ProverCheckpoint<F, C, D>> {
+    if buffer.read_u32()? != CHECKPOINT_FORMAT_VERSION {
+        return Err(IoError);
+    }
+    let mut stored_id = vec![0; buffer.read_usize()?];
+    buffer.read_exact(&mut stored_id)?;
+    if stored_id != id {
+        return Err(IoError);
+    }
+    let public_inputs = buffer.read_field_vec_with_length()?;
+    let num_columns = buffer.read_usize()?;
+    let witness = (0..num_columns)
+        .map(|_| buffer.read_field_vec_with_length())
+        .collect::<IoResult<Vec<_>>>()?;
+    let num_commitments = buffer.read_usize()?;
+    let commitments = (0..num_commitments)
+        .map(|_| buffer.read_polynomial_batch())
+        .collect::<IoResult<Vec<_>>>()?;
+    if !buffer.is_empty() {
+        return Err(IoError);
+    }
+    Ok(ProverCheckpoint {
+        public_inputs,
+        witness,
+        commitments,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::types::Field;
+    use crate::iop::witness::WitnessWrite;
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::{CircuitConfig, CircuitData};
+    use crate::plonk::config::PoseidonGoldilocksConfig;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    /// A circuit proving knowledge of `x` such that `x^3 + x` is public.
+    fn circuit() -> (CircuitData<F, C, D>, PartialWitness<F>) {
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let x = builder.add_virtual_target();
+        let x3 = builder.exp_u64(x, 3);
+        let y = builder.add(x3, x);
+        builder.register_public_input(y);
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target(x, F::from_canonical_u64(7));
+        (data, pw)
+    }
+
+    #[test]
+    fn test_progress() -> Result<()> {
+        let (data, pw) = circuit();
+        let phases = Arc::new(Mutex::new(Vec::new()));
+        let handle = ProverHandle::new().with_progress({
+            let phases = phases.clone();
+            move |phase| phases.lock().unwrap().push(phase)
+        });
+        let proof = data.prove_with_handle(pw, &handle)?;
+        assert_eq!(
+            *phases.lock().unwrap(),
+            [
+                ProvingPhase::Witness,
+                ProvingPhase::Commitment,
+                ProvingPhase::Quotient,
+                ProvingPhase::Fri
+            ]
+        );
+        data.verify(proof)
+    }
+
+    #[test]
+    fn test_cancel() {
+        let (data, pw) = circuit();
+        let handle = ProverHandle::new();
+        let handle_clone = handle.clone();
+        let handle = handle.with_progress(move |phase| {
+            if phase == ProvingPhase::Quotient {
+                handle_clone.cancel();
+            }
+        });
+        let err = data.prove_with_handle(pw, &handle).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ProvingCancelled>(),
+            Some(&ProvingCancelled)
+        );
+    }
+
+    #[test]
+    fn test_resume_from_checkpoint() -> Result<()> {
+        let (data, pw) = circuit();
+        let dir = std::env::temp_dir().join(format!("plonky2_checkpoints_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+
+        // Interrupt the proof once all commitments are checkpointed.
+        let handle = ProverHandle::new().with_checkpoint_dir(&dir);
+        let handle_clone = handle.clone();
+        let handle = handle.with_progress(move |phase| {
+            if phase == ProvingPhase::Fri {
+                handle_clone.cancel();
+            }
+        });
+        assert!(data.prove_with_handle(pw.clone(), &handle).is_err());
+        assert_eq!(std::fs::read_dir(&dir)?.count(), 1);
+        let id = checkpoint_id::<F, C, D>(data.prover_only.circuit_digest, &pw);
+        let checkpoint = handle.load_checkpoint::<F, C, D>(&id).unwrap();
+        assert_eq!(checkpoint.commitments.len(), 3);
+
+        // Resuming reuses the checkpointed commitments, and removes the checkpoint when done.
+        let handle = ProverHandle::new().with_checkpoint_dir(&dir);
+        let resumed = data.prove_with_handle(pw, &handle)?;
+        assert_eq!(std::fs::read_dir(&dir)?.count(), 0);
+        std::fs::remove_dir(&dir)?;
+
+        let caps = [
+            &resumed.proof.wires_cap,
+            &resumed.proof.plonk_zs_partial_products_cap,
+            &resumed.proof.quotient_polys_cap,
+        ];
+        for (commitment, cap) in checkpoint.commitments.iter().zip(caps) {
+            assert_eq!(&commitment.merkle_tree.cap, cap);
+        }
+        data.verify(resumed)
+    }
+
+    #[test]
+    fn test_checkpoint_refused_with_zero_knowledge() {
+        let mut builder =
+            CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_zk_config());
+        let x = builder.add_virtual_target();
+        let x3 = builder.exp_u64(x, 3);
+        builder.register_public_input(x3);
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+        pw.set_target(x, F::from_canonical_u64(7));
+
+        let dir =
+            std::env::temp_dir().join(format!("plonky2_zk_checkpoints_{}", std::process::id()));
+        let handle = ProverHandle::new().with_checkpoint_dir(&dir);
+        assert!(data.prove_with_handle(pw, &handle).is_err());
+        assert!(!dir.exists());
+    }
+}
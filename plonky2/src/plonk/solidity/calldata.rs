@@ -0,0 +1,136 @@
+use alloc::vec::Vec;
+
+use anyhow::{ensure, Result};
+use keccak_hash::keccak;
+
+use super::{ensure_supported, ProofLayout, C, D, F};
+use crate::field::extension::{Extendable, FieldExtension};
+use crate::field::types::PrimeField64;
+use crate::hash::hash_types::BytesHash;
+use crate::hash::merkle_proofs::MerkleProof;
+use crate::hash::merkle_tree::MerkleCap;
+use crate::plonk::circuit_data::CommonCircuitData;
+use crate::plonk::config::{GenericConfig, Hasher};
+use crate::plonk::proof::ProofWithPublicInputs;
+use crate::plonk::validate_shape::validate_proof_with_pis_shape;
+
+type H = <C as GenericConfig<D>>::Hasher;
+
+/// The signature of the verifier contract's entry point.
+const VERIFY_SIGNATURE: &str = "verify(uint64[],bytes)";
+
+/// Encodes the proof of `proof_with_pis` in the flat layout read by the verifier contracts of
+/// [`generate_verifier_contract`](super::generate_verifier_contract): field elements as 8
+/// little-endian bytes, extension field elements as their two limbs, and hashes as their 25 bytes.
+///
+/// The Merkle caps come first, then the openings in the order of `OpeningSet::to_fri_openings`,
+/// the FRI commit phase caps, the final polynomial, the proof of work witness, and finally each
+/// query round, with every Merkle proof following the leaf it opens.
+pub fn encode_proof(
+    proof_with_pis: &ProofWithPublicInputs<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+) -> Result<Vec<u8>> {
+    ensure_supported(common_data)?;
+    validate_proof_with_pis_shape(proof_with_pis, common_data)?;
+    let layout = ProofLayout::new(common_data);
+    let proof = &proof_with_pis.proof;
+    let fri_proof = &proof.opening_proof;
+    let fri_config = &common_data.config.fri_config;
+    ensure!(fri_proof.commit_phase_merkle_caps.len() == layout.steps.len());
+    ensure!(fri_proof.final_poly.len() == common_data.fri_params.final_poly_len());
+    ensure!(fri_proof.query_round_proofs.len() == fri_config.num_query_rounds);
+
+    let mut out = Vec::with_capacity(layout.proof_size);
+    write_cap(&mut out, &proof.wires_cap);
+    write_cap(&mut out, &proof.plonk_zs_partial_products_cap);
+    write_cap(&mut out, &proof.quotient_polys_cap);
+    for batch in proof.openings.to_fri_openings().batches {
+        write_extension_elements(&mut out, &batch.values);
+    }
+    for cap in &fri_proof.commit_phase_merkle_caps {
+        ensure!(cap.height() == fri_config.cap_height);
+        write_cap(&mut out, cap);
+    }
+    write_extension_elements(&mut out, &fri_proof.final_poly.coeffs);
+    write_elements(&mut out, &[fri_proof.pow_witness]);
+
+    for round in &fri_proof.query_round_proofs {
+        let evals_proofs = &round.initial_trees_proof.evals_proofs;
+        ensure!(evals_proofs.len() == layout.leaf_lengths.len());
+        for ((evals, merkle_proof), &leaf_length) in evals_proofs.iter().zip(&layout.leaf_lengths) {
+            ensure!(evals.len() == leaf_length);
+            ensure!(merkle_proof.siblings.len() == layout.initial_siblings);
+            write_elements(&mut out, evals);
+            write_merkle_proof(&mut out, merkle_proof);
+        }
+        ensure!(round.steps.len() == layout.steps.len());
+        for (step, &(arity, num_siblings)) in round.steps.iter().zip(&layout.steps) {
+            ensure!(step.evals.len() == arity);
+            ensure!(step.merkle_proof.siblings.len() == num_siblings);
+            write_extension_elements(&mut out, &step.evals);
+            write_merkle_proof(&mut out, &step.merkle_proof);
+        }
+    }
+    ensure!(out.len() == layout.proof_size);
+    Ok(out)
+}
+
+/// ABI-encodes a call to `verify(uint64[] publicInputs, bytes proof)` on a verifier contract,
+/// with the proof encoded by [`encode_proof`].
+pub fn encode_verify_calldata(
+    proof_with_pis: &ProofWithPublicInputs<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+) -> Result<Vec<u8>> {
+    let proof = encode_proof(proof_with_pis, common_data)?;
+    let public_inputs = &proof_with_pis.public_inputs;
+
+    let mut out = keccak(VERIFY_SIGNATURE).0[..4].to_vec();
+    // The heads of the two dynamic arguments are the offsets of their tails.
+    write_word(&mut out, 64);
+    write_word(&mut out, 64 + 32 * (1 + public_inputs.len() as u64));
+    write_word(&mut out, public_inputs.len() as u64);
+    for x in public_inputs {
+        write_word(&mut out, x.to_canonical_u64());
+    }
+    write_word(&mut out, proof.len() as u64);
+    let padded_len = (proof.len() + 31) / 32 * 32;
+    out.extend_from_slice(&proof);
+    out.resize(out.len() + padded_len - proof.len(), 0);
+    Ok(out)
+}
+
+/// Writes a big-endian, 32-byte ABI word.
+fn write_word(out: &mut Vec<u8>, x: u64) {
+    out.extend_from_slice(&[0; 24]);
+    out.extend_from_slice(&x.to_be_bytes());
+}
+
+fn write_elements(out: &mut Vec<u8>, xs: &[F]) {
+    for x in xs {
+        out.extend_from_slice(&x.to_canonical_u64().to_le_bytes());
+    }
+}
+
+fn write_extension_elements(out: &mut Vec<u8>, xs: &[<F as Extendable<D>>::Extension]) {
+    for x in xs {
+        let limbs: [F; D] = x.to_basefield_array();
+        write_elements(out, &limbs);
+    }
+}
+
+fn write_hash(out: &mut Vec<u8>, hash: &<H as Hasher<F>>::Hash) {
+    let BytesHash(bytes) = hash;
+    out.extend_from_slice(bytes);
+}
+
+fn write_cap(out: &mut Vec<u8>, cap: &MerkleCap<F, H>) {
+    for hash in &cap.0 {
+        write_hash(out, hash);
+    }
+}
+
+fn write_merkle_proof(out: &mut Vec<u8>, merkle_proof: &MerkleProof<F, H>) {
+    for hash in &merkle_proof.siblings {
+        write_hash(out, hash);
+    }
+}
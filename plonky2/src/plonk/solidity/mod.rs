@@ -0,0 +1,653 @@
+//! Generation of Solidity verifier contracts for proofs made with [`KeccakGoldilocksConfig`].
+//!
+//! [`generate_verifier_contract`] specializes the template `verifier.sol` to a circuit: the
+//! verifier key, the proof layout and the constraints of the circuit's gates are baked into the
+//! contract. Proofs are passed to the contract's `verify(uint64[], bytes)` function in the flat
+//! encoding of [`encode_proof`], and [`encode_verify_calldata`] builds the whole call.
+//!
+//! Only circuits without lookups, whose gates all implement
+//! [`Gate::export_solidity_verification_code`](crate::gates::gate::Gate::export_solidity_verification_code),
+//! are supported.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use anyhow::{bail, ensure, Result};
+
+pub use self::calldata::{encode_proof, encode_verify_calldata};
+use crate::field::extension::{Extendable, FieldExtension};
+use crate::field::types::{Field, PrimeField64};
+use crate::gates::selectors::UNUSED_SELECTOR;
+use crate::hash::poseidon::{
+    Poseidon, ALL_ROUND_CONSTANTS, HALF_N_FULL_ROUNDS, N_PARTIAL_ROUNDS, N_ROUNDS,
+};
+use crate::plonk::circuit_data::{CommonCircuitData, VerifierCircuitData};
+use crate::plonk::config::{GenericConfig, KeccakGoldilocksConfig};
+use crate::plonk::plonk_common::{salt_size, PlonkOracle};
+
+mod calldata;
+
+const D: usize = 2;
+type C = KeccakGoldilocksConfig;
+type F = <C as GenericConfig<D>>::F;
+
+/// The size in bytes of a `KeccakHash<25>` digest.
+const HASH_SIZE: usize = 25;
+/// The size in bytes of an encoded extension field element.
+const EXT_SIZE: usize = 16;
+/// The size in bytes of an encoded field element.
+const ELEMENT_SIZE: usize = 8;
+
+const TEMPLATE: &str = include_str!("verifier.sol");
+
+/// Byte offsets of the parts of a proof in the encoding of [`encode_proof`].
+struct ProofLayout {
+    cap_length: usize,
+    /// The number of Merkle siblings of each initial tree opening.
+    initial_siblings: usize,
+    /// The leaf lengths of the four Plonk oracles, salt included.
+    leaf_lengths: [usize; 4],
+    /// For each FRI reduction, the arity and the number of Merkle siblings.
+    steps: Vec<(usize, usize)>,
+
+    wires_cap: usize,
+    zs_partial_products_cap: usize,
+    quotient_cap: usize,
+    openings: usize,
+    sigmas_openings: usize,
+    wires_openings: usize,
+    zs_openings: usize,
+    partial_products_openings: usize,
+    quotient_openings: usize,
+    zs_next_openings: usize,
+    commit_phase_caps: usize,
+    final_poly: usize,
+    pow_witness: usize,
+    query_rounds: usize,
+    /// The offsets of the four initial tree openings within a query round.
+    oracles: [usize; 4],
+    initial_trees_size: usize,
+    query_round_size: usize,
+    proof_size: usize,
+}
+
+impl ProofLayout {
+    fn new(common_data: &CommonCircuitData<F, D>) -> Self {
+        let config = &common_data.config;
+        let fri_params = &common_data.fri_params;
+        let num_challenges = config.num_challenges;
+        let cap_height = config.fri_config.cap_height;
+        let cap_length = 1 << cap_height;
+        let lde_bits = fri_params.lde_bits();
+
+        let wires_cap = 0;
+        let zs_partial_products_cap = wires_cap + HASH_SIZE * cap_length;
+        let quotient_cap = zs_partial_products_cap + HASH_SIZE * cap_length;
+        let openings = quotient_cap + HASH_SIZE * cap_length;
+        let sigmas_openings = openings + EXT_SIZE * common_data.num_constants;
+        let wires_openings = sigmas_openings + EXT_SIZE * config.num_routed_wires;
+        let zs_openings = wires_openings + EXT_SIZE * config.num_wires;
+        let partial_products_openings = zs_openings + EXT_SIZE * num_challenges;
+        let quotient_openings = partial_products_openings
+            + EXT_SIZE * num_challenges * common_data.num_partial_products;
+        let zs_next_openings = quotient_openings + EXT_SIZE * common_data.num_quotient_polys();
+        let commit_phase_caps = zs_next_openings + EXT_SIZE * num_challenges;
+        let final_poly =
+            commit_phase_caps + HASH_SIZE * cap_length * fri_params.reduction_arity_bits.len();
+        let pow_witness = final_poly + EXT_SIZE * fri_params.final_poly_len();
+        let query_rounds = pow_witness + ELEMENT_SIZE;
+
+        let initial_siblings = lde_bits - cap_height;
+        let oracle_salt = |oracle: PlonkOracle| salt_size(fri_params.hiding && oracle.blinding);
+        let leaf_lengths = [
+            common_data.num_preprocessed_polys() + oracle_salt(PlonkOracle::CONSTANTS_SIGMAS),
+            config.num_wires + oracle_salt(PlonkOracle::WIRES),
+            common_data.num_zs_partial_products_polys()
+                + oracle_salt(PlonkOracle::ZS_PARTIAL_PRODUCTS),
+            common_data.num_quotient_polys() + oracle_salt(PlonkOracle::QUOTIENT),
+        ];
+        let mut oracles = [0; 4];
+        let mut initial_trees_size = 0;
+        for (oracle, leaf_length) in oracles.iter_mut().zip(leaf_lengths) {
+            *oracle = initial_trees_size;
+            initial_trees_size += ELEMENT_SIZE * leaf_length + HASH_SIZE * initial_siblings;
+        }
+
+        let mut reduced_bits = 0;
+        let mut query_round_size = initial_trees_size;
+        let steps = fri_params
+            .reduction_arity_bits
+            .iter()
+            .map(|&arity_bits| {
+                reduced_bits += arity_bits;
+                let arity = 1 << arity_bits;
+                let num_siblings = lde_bits - reduced_bits - cap_height;
+                query_round_size += EXT_SIZE * arity + HASH_SIZE * num_siblings;
+                (arity, num_siblings)
+            })
+            .collect();
+        let proof_size = query_rounds + config.fri_config.num_query_rounds * query_round_size;
+
+        Self {
+            cap_length,
+            initial_siblings,
+            leaf_lengths,
+            steps,
+            wires_cap,
+            zs_partial_products_cap,
+            quotient_cap,
+            openings,
+            sigmas_openings,
+            wires_openings,
+            zs_openings,
+            partial_products_openings,
+            quotient_openings,
+            zs_next_openings,
+            commit_phase_caps,
+            final_poly,
+            pow_witness,
+            query_rounds,
+            oracles,
+            initial_trees_size,
+            query_round_size,
+            proof_size,
+        }
+    }
+}
+
+fn ensure_supported(common_data: &CommonCircuitData<F, D>) -> Result<()> {
+    ensure!(
+        common_data.luts.is_empty(),
+        "Solidity verifiers do not support lookups"
+    );
+    Ok(())
+}
+
+/// Generates the source of a Solidity contract named `contract_name` verifying proofs of the
+/// circuit described by `verifier_data`.
+pub fn generate_verifier_contract(
+    verifier_data: &VerifierCircuitData<F, C, D>,
+    contract_name: &str,
+) -> Result<String> {
+    let common_data = &verifier_data.common;
+    ensure_supported(common_data)?;
+    ensure!(
+        contract_name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '$')
+            && !contract_name.starts_with(|ch: char| ch.is_ascii_digit())
+            && !contract_name.is_empty(),
+        "Invalid contract name {contract_name:?}"
+    );
+
+    let (gate_constraints, gate_functions) = gate_code(common_data)?;
+    Ok(TEMPLATE
+        .replace(
+            "{{POWER_OF_TWO_GENERATOR}}",
+            &F::POWER_OF_TWO_GENERATOR.to_canonical_u64().to_string(),
+        )
+        .replace(
+            "{{MULTIPLICATIVE_GROUP_GENERATOR}}",
+            &F::MULTIPLICATIVE_GROUP_GENERATOR
+                .to_canonical_u64()
+                .to_string(),
+        )
+        .replace(
+            "{{POSEIDON_ROUND_CONSTANTS}}",
+            &hex_u64s(&ALL_ROUND_CONSTANTS),
+        )
+        .replace(
+            "{{POSEIDON_MDS_MATRIX_CIRC}}",
+            &hex_u64s(&<F as Poseidon>::MDS_MATRIX_CIRC),
+        )
+        .replace(
+            "{{POSEIDON_MDS_MATRIX_DIAG}}",
+            &hex_u64s(&<F as Poseidon>::MDS_MATRIX_DIAG),
+        )
+        .replace("{{POSEIDON_N_ROUNDS}}", &N_ROUNDS.to_string())
+        .replace(
+            "{{POSEIDON_HALF_N_FULL_ROUNDS}}",
+            &HALF_N_FULL_ROUNDS.to_string(),
+        )
+        .replace(
+            "{{POSEIDON_N_PARTIAL_ROUNDS}}",
+            &N_PARTIAL_ROUNDS.to_string(),
+        )
+        .replace("{{CONTRACT_NAME}}", contract_name)
+        .replace(
+            "{{CIRCUIT_CONSTANTS}}",
+            &circuit_constants(verifier_data, &ProofLayout::new(common_data))?,
+        )
+        .replace("{{GATE_CONSTRAINTS}}", &gate_constraints)
+        .replace("{{GATE_FUNCTIONS}}", &gate_functions))
+}
+
+/// The contract constants describing the circuit and the proof layout.
+fn circuit_constants(
+    verifier_data: &VerifierCircuitData<F, C, D>,
+    layout: &ProofLayout,
+) -> Result<String> {
+    let verifier_only = &verifier_data.verifier_only;
+    let common_data = &verifier_data.common;
+    let config = &common_data.config;
+    let fri_params = &common_data.fri_params;
+
+    let subgroup_generator: [F; D] =
+        <F as Extendable<D>>::Extension::primitive_root_of_unity(common_data.degree_bits())
+            .to_basefield_array();
+    ensure!(
+        subgroup_generator[1] == F::ZERO,
+        "The subgroup generator should be in the base field"
+    );
+    let num_polys = [
+        common_data.num_preprocessed_polys(),
+        config.num_wires,
+        common_data.num_zs_partial_products_polys(),
+        common_data.num_quotient_polys(),
+    ];
+
+    let mut uints = vec![
+        ("NUM_PUBLIC_INPUTS", common_data.num_public_inputs as u64),
+        ("PROOF_SIZE", layout.proof_size as u64),
+        ("CAP_HEIGHT", config.fri_config.cap_height as u64),
+        ("CAP_LENGTH", layout.cap_length as u64),
+        ("DEGREE_BITS", common_data.degree_bits() as u64),
+        ("LDE_BITS", fri_params.lde_bits() as u64),
+        (
+            "LDE_ROOT_OF_UNITY",
+            F::primitive_root_of_unity(fri_params.lde_bits()).to_canonical_u64(),
+        ),
+        (
+            "SUBGROUP_GENERATOR",
+            subgroup_generator[0].to_canonical_u64(),
+        ),
+        ("NUM_CHALLENGES", config.num_challenges as u64),
+        ("NUM_CONSTANTS", common_data.num_constants as u64),
+        (
+            "NUM_SELECTORS",
+            common_data.selectors_info.num_selectors() as u64,
+        ),
+        ("NUM_ROUTED_WIRES", config.num_routed_wires as u64),
+        ("NUM_WIRES", config.num_wires as u64),
+        (
+            "NUM_PARTIAL_PRODUCTS",
+            common_data.num_partial_products as u64,
+        ),
+        (
+            "QUOTIENT_DEGREE_FACTOR",
+            common_data.quotient_degree_factor as u64,
+        ),
+        (
+            "NUM_GATE_CONSTRAINTS",
+            common_data.num_gate_constraints as u64,
+        ),
+        (
+            "NUM_OPENINGS_AT_ZETA",
+            num_polys.iter().sum::<usize>() as u64,
+        ),
+        (
+            "NUM_QUERY_ROUNDS",
+            config.fri_config.num_query_rounds as u64,
+        ),
+        ("POW_BITS", config.fri_config.proof_of_work_bits as u64),
+        (
+            "NUM_FRI_REDUCTIONS",
+            fri_params.reduction_arity_bits.len() as u64,
+        ),
+        ("FINAL_POLY_LENGTH", fri_params.final_poly_len() as u64),
+        ("WIRES_CAP_OFFSET", layout.wires_cap as u64),
+        (
+            "ZS_PARTIAL_PRODUCTS_CAP_OFFSET",
+            layout.zs_partial_products_cap as u64,
+        ),
+        ("QUOTIENT_CAP_OFFSET", layout.quotient_cap as u64),
+        ("OPENINGS_OFFSET", layout.openings as u64),
+        ("CONSTANTS_OPENINGS_OFFSET", layout.openings as u64),
+        ("SIGMAS_OPENINGS_OFFSET", layout.sigmas_openings as u64),
+        ("WIRES_OPENINGS_OFFSET", layout.wires_openings as u64),
+        ("ZS_OPENINGS_OFFSET", layout.zs_openings as u64),
+        (
+            "PARTIAL_PRODUCTS_OPENINGS_OFFSET",
+            layout.partial_products_openings as u64,
+        ),
+        ("QUOTIENT_OPENINGS_OFFSET", layout.quotient_openings as u64),
+        ("ZS_NEXT_OPENINGS_OFFSET", layout.zs_next_openings as u64),
+        ("COMMIT_PHASE_CAPS_OFFSET", layout.commit_phase_caps as u64),
+        ("FINAL_POLY_OFFSET", layout.final_poly as u64),
+        ("POW_WITNESS_OFFSET", layout.pow_witness as u64),
+        ("QUERY_ROUNDS_OFFSET", layout.query_rounds as u64),
+        ("QUERY_ROUND_SIZE", layout.query_round_size as u64),
+        ("INITIAL_TREES_SIZE", layout.initial_trees_size as u64),
+    ]
+    .into_iter()
+    .map(|(name, value)| (String::from(name), value))
+    .collect::<Vec<_>>();
+    for i in 0..4 {
+        uints.push((format!("NUM_POLYS_{i}"), num_polys[i] as u64));
+        uints.push((format!("LEAF_LENGTH_{i}"), layout.leaf_lengths[i] as u64));
+        uints.push((format!("ORACLE_{i}_OFFSET"), layout.oracles[i] as u64));
+    }
+
+    let mut code = String::new();
+    for (name, value) in uints {
+        code += &format!("    uint256 internal constant {name} = {value};\n");
+    }
+    let circuit_digest = hex_bytes(&verifier_only.circuit_digest.0);
+    code += &format!("    bytes25 internal constant CIRCUIT_DIGEST = 0x{circuit_digest};\n");
+    let cap = verifier_only
+        .constants_sigmas_cap
+        .0
+        .iter()
+        .flat_map(|h| h.0)
+        .collect::<Vec<_>>();
+    let k_is = common_data
+        .k_is
+        .iter()
+        .map(|k| k.to_canonical_u64())
+        .collect::<Vec<_>>();
+    let arity_bits = fri_params
+        .reduction_arity_bits
+        .iter()
+        .map(|&bits| bits as u8)
+        .collect::<Vec<_>>();
+    for (name, value) in [
+        ("CONSTANTS_SIGMAS_CAP", hex_bytes(&cap)),
+        ("K_IS", hex_u64s(&k_is)),
+        ("FRI_REDUCTION_ARITY_BITS", hex_bytes(&arity_bits)),
+    ] {
+        code += &format!("    bytes internal constant {name} = hex\"{value}\";\n");
+    }
+    Ok(code)
+}
+
+/// The statements of `evaluateGateConstraints` accumulating the filtered constraints of each gate,
+/// and the functions `evalGate{i}` computing the unfiltered constraints of the `i`th gate.
+fn gate_code(common_data: &CommonCircuitData<F, D>) -> Result<(String, String)> {
+    let selectors_info = &common_data.selectors_info;
+    let num_selectors = selectors_info.num_selectors();
+    let mut constraints = Vec::new();
+    let mut functions = String::new();
+    for (row, gate) in common_data.gates.iter().enumerate() {
+        let Some(body) = gate.0.export_solidity_verification_code() else {
+            bail!("Gate {} has no Solidity implementation", gate.0.id());
+        };
+
+        // See `compute_filter`.
+        let selector_index = selectors_info.selector_indices[row];
+        let filter = selectors_info.groups[selector_index]
+            .clone()
+            .filter(|&j| j != row)
+            .chain((num_selectors > 1).then_some(UNUSED_SELECTOR))
+            .map(|j| format!("(fromBase({j}) - constants[{selector_index}])"))
+            .collect::<Vec<_>>();
+        let filter = if filter.is_empty() {
+            String::from("EXT_ONE")
+        } else {
+            filter.join(" * ")
+        };
+        constraints.push(format!(
+            "        filter = {filter};\n        accumulateConstraints(constraints, evalGate{row}(wires, c, pih), filter);"
+        ));
+
+        let param = |name: &str| {
+            if body.contains(&format!("{name}[")) {
+                format!(" {name}")
+            } else {
+                String::new()
+            }
+        };
+        functions += &format!(
+            "\n    function evalGate{row}(Ext[] memory{}, Ext[] memory{}, uint256[4] memory{})\n        internal\n        pure\n        returns (Ext[] memory out)\n    {{\n        out = new Ext[]({});\n",
+            param("w"),
+            param("c"),
+            param("pih"),
+            gate.0.num_constraints(),
+        );
+        for line in body.trim().lines() {
+            if !line.is_empty() {
+                functions += "        ";
+                functions += line;
+            }
+            functions += "\n";
+        }
+        functions += "    }\n";
+    }
+    Ok((constraints.join("\n"), functions))
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// The concatenation of the big-endian encodings of `values`, as read by `readTable`.
+fn hex_u64s(values: &[u64]) -> String {
+    values.iter().map(|v| format!("{v:016x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use anyhow::Result;
+    use keccak_hash::keccak;
+
+    use super::*;
+    use crate::gates::coset_interpolation::CosetInterpolationGate;
+    use crate::hash::poseidon::PoseidonHash;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::{CircuitConfig, CircuitData};
+    use crate::plonk::proof::ProofWithPublicInputs;
+    use crate::util::reducing::ReducingFactorTarget;
+
+    /// A circuit using each gate with a Solidity implementation.
+    fn test_proof(
+        config: CircuitConfig,
+    ) -> Result<(CircuitData<F, C, D>, ProofWithPublicInputs<F, C, D>)> {
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let index = builder.add_virtual_target();
+
+        let three = builder.constant(F::from_canonical_u64(3));
+        let y = builder.mul_add(x, x, three);
+        let hash = builder.hash_n_to_hash_no_pad::<PoseidonHash>(vec![x, y]);
+        builder.register_public_inputs(&hash.elements);
+        let z = builder.exp(x, index, 2);
+        let w = builder.random_access(index, vec![x, y, z, three]);
+        builder.split_le(w, 64);
+
+        let [xe, ye, ze] = [x, y, z].map(|t| builder.convert_to_ext(t));
+        let product = builder.mul_extension(xe, ye);
+        let sum = builder.mul_add_extension(product, ze, xe);
+        let gate = CosetInterpolationGate::with_max_degree(2, 3);
+        let interpolated = builder.interpolate_coset(gate, three, &[xe, ye, ze, sum], product);
+        let mut alpha = ReducingFactorTarget::new(interpolated);
+        let reduced_base = alpha.reduce_base(&[x, y, z, w].repeat(4), &mut builder);
+        let reduced = alpha.reduce(&[xe, ye, ze, reduced_base].repeat(4), &mut builder);
+        builder.register_public_inputs(&reduced.0);
+
+        let mut pw = PartialWitness::new();
+        pw.set_target(x, F::from_canonical_u64(5));
+        pw.set_target(index, F::from_canonical_u64(2));
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        Ok((data, proof))
+    }
+
+    #[test]
+    fn test_generate_verifier_contract() -> Result<()> {
+        for config in [
+            CircuitConfig::standard_recursion_config(),
+            CircuitConfig::standard_recursion_zk_config(),
+        ] {
+            let (data, proof) = test_proof(config)?;
+            let contract = generate_verifier_contract(&data.verifier_data(), "Verifier")?;
+            assert!(!contract.contains("{{"));
+            assert!(contract.contains("contract Verifier {"));
+            assert_eq!(
+                contract.matches("function evalGate").count(),
+                data.common.gates.len()
+            );
+
+            let encoded = encode_proof(&proof, &data.common)?;
+            let proof_size = format!("uint256 internal constant PROOF_SIZE = {};", encoded.len());
+            assert!(contract.contains(&proof_size));
+            data.verify(proof)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_verify_calldata() -> Result<()> {
+        let (data, proof) = test_proof(CircuitConfig::standard_recursion_config())?;
+        let encoded = encode_proof(&proof, &data.common)?;
+        let calldata = encode_verify_calldata(&proof, &data.common)?;
+
+        let word = |i: usize| {
+            let bytes = &calldata[4 + 32 * i..4 + 32 * (i + 1)];
+            assert!(bytes[..24].iter().all(|&b| b == 0));
+            u64::from_be_bytes(bytes[24..].try_into().unwrap()) as usize
+        };
+        let num_public_inputs = proof.public_inputs.len();
+        assert_eq!(calldata[..4], keccak("verify(uint64[],bytes)").0[..4]);
+        assert_eq!(word(0), 64);
+        assert_eq!(word(1), 64 + 32 * (1 + num_public_inputs));
+        assert_eq!(word(2), num_public_inputs);
+        for (i, x) in proof.public_inputs.iter().enumerate() {
+            assert_eq!(word(3 + i) as u64, x.to_canonical_u64());
+        }
+        let proof_start = 4 + 32 * (4 + num_public_inputs);
+        assert_eq!(word(3 + num_public_inputs), encoded.len());
+        assert_eq!(calldata[proof_start..proof_start + encoded.len()], encoded);
+        assert_eq!((calldata.len() - 4) % 32, 0);
+        assert!(calldata[proof_start + encoded.len()..]
+            .iter()
+            .all(|&b| b == 0));
+        Ok(())
+    }
+
+    /// The `solc` binary named by the `SOLC` environment variable, or else the one in `PATH`. Fails
+    /// if it can't be run.
+    fn solc() -> Result<String> {
+        let solc = std::env::var("SOLC").unwrap_or_else(|_| "solc".into());
+        let runs = std::process::Command::new(&solc)
+            .arg("--version")
+            .output()
+            .is_ok_and(|output| output.status.success());
+        ensure!(
+            runs,
+            "Could not run `{solc}`; set SOLC to a solc binary, version 0.8.19 or later."
+        );
+        Ok(solc)
+    }
+
+    /// Compiles `contract` with `solc`, and returns the runtime bytecode of its contract `name`.
+    fn compile_contract(solc: &str, contract: &str, name: &str) -> Result<Vec<u8>> {
+        let dir = std::env::temp_dir().join(format!("plonky2_solidity_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let source = dir.join(format!("{name}.sol"));
+        std::fs::write(&source, contract)?;
+
+        let output = std::process::Command::new(solc)
+            .args([
+                "--optimize",
+                "--via-ir",
+                "--bin-runtime",
+                "--overwrite",
+                "-o",
+            ])
+            .arg(&dir)
+            .arg(&source)
+            .output()?;
+        ensure!(
+            output.status.success(),
+            "solc failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let hex = std::fs::read_to_string(dir.join(format!("{name}.bin-runtime")))?;
+        std::fs::remove_dir_all(&dir)?;
+        let hex = hex.trim();
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| Ok(u8::from_str_radix(&hex[i..i + 2], 16)?))
+            .collect()
+    }
+
+    /// Calls the contract with runtime bytecode `code` on revm, and returns whether the call
+    /// succeeded and returned `true`.
+    fn call_verify(code: &[u8], calldata: Vec<u8>) -> bool {
+        use revm::primitives::{
+            AccountInfo, Address, Bytecode, Bytes, ExecutionResult, Output, TransactTo,
+        };
+        use revm::{InMemoryDB, EVM};
+
+        let address = Address::repeat_byte(0x42);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            address,
+            AccountInfo {
+                code: Some(Bytecode::new_raw(Bytes::from(code.to_vec()))),
+                ..Default::default()
+            },
+        );
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.tx.transact_to = TransactTo::Call(address);
+        evm.env.tx.data = calldata.into();
+        evm.env.tx.gas_limit = 1 << 32;
+
+        match evm.transact_ref().expect("EVM error").result {
+            ExecutionResult::Success {
+                output: Output::Call(output),
+                gas_used,
+                ..
+            } => {
+                log::info!("verify used {gas_used} gas");
+                let mut expected = [0u8; 32];
+                expected[31] = 1;
+                output[..] == expected
+            }
+            _ => false,
+        }
+    }
+
+    /// Compiles the generated verifier and runs it on revm. CI runs it with the `--ignored` flag.
+    #[test]
+    #[ignore = "needs solc 0.8.19 or later, in PATH or named by SOLC"]
+    fn test_verifier_contract_on_evm() -> Result<()> {
+        let solc = solc()?;
+        let (data, proof) = test_proof(CircuitConfig::standard_recursion_config())?;
+        let contract = generate_verifier_contract(&data.verifier_data(), "Verifier")?;
+        let code = compile_contract(&solc, &contract, "Verifier")?;
+        let calldata = encode_verify_calldata(&proof, &data.common)?;
+        assert!(call_verify(&code, calldata.clone()));
+
+        // Change the first public input, which is the fourth word of the calldata.
+        let mut tampered_public_inputs = calldata.clone();
+        tampered_public_inputs[4 + 32 * 4 - 1] ^= 1;
+        assert!(!call_verify(&code, tampered_public_inputs));
+
+        // Change a byte in the middle of the proof.
+        let num_public_inputs = proof.public_inputs.len();
+        let proof_start = 4 + 32 * (4 + num_public_inputs);
+        let proof_len = encode_proof(&proof, &data.common)?.len();
+        let mut tampered_proof = calldata;
+        tampered_proof[proof_start + proof_len / 2] ^= 1;
+        assert!(!call_verify(&code, tampered_proof));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lookups_unsupported() {
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let x = builder.add_virtual_target();
+        let lut = builder.add_lookup_table_from_pairs(&[0, 1], &[1, 0]);
+        let y = builder.add_lookup_from_index(x, lut);
+        builder.register_public_input(y);
+        let data = builder.build::<C>();
+        assert!(generate_verifier_contract(&data.verifier_data(), "Verifier").is_err());
+    }
+}
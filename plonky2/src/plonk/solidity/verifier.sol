@@ -0,0 +1,768 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// Generated by plonky2; do not edit by hand.
+pragma solidity ^0.8.19;
+
+// The Goldilocks prime, `2^64 - 2^32 + 1`.
+uint256 constant P = 0xFFFFFFFF00000001;
+// The quadratic extension is `F[X] / (X^2 - W)`.
+uint256 constant W = 7;
+uint256 constant MASK_64 = 0xFFFFFFFFFFFFFFFF;
+uint256 constant TWO_ADICITY = 32;
+uint256 constant POWER_OF_TWO_GENERATOR = {{POWER_OF_TWO_GENERATOR}};
+uint256 constant MULTIPLICATIVE_GROUP_GENERATOR = {{MULTIPLICATIVE_GROUP_GENERATOR}};
+
+// Parameters of the challenger, and of `KeccakHash<25>`.
+uint256 constant SPONGE_WIDTH = 12;
+uint256 constant SPONGE_RATE = 8;
+uint256 constant HASH_SIZE = 25;
+
+// Poseidon constants, as big-endian `u64`s.
+bytes constant POSEIDON_ROUND_CONSTANTS = hex"{{POSEIDON_ROUND_CONSTANTS}}";
+bytes constant POSEIDON_MDS_MATRIX_CIRC = hex"{{POSEIDON_MDS_MATRIX_CIRC}}";
+bytes constant POSEIDON_MDS_MATRIX_DIAG = hex"{{POSEIDON_MDS_MATRIX_DIAG}}";
+
+// An element `a0 + a1 X` of the quadratic extension, packed as `a0 | a1 << 64`. Both limbs are
+// always kept canonical, so equality of the packed words is equality of the elements.
+type Ext is uint256;
+
+using {extAdd as +, extSub as -, extMul as *, extEq as ==} for Ext global;
+
+Ext constant EXT_ZERO = Ext.wrap(0);
+Ext constant EXT_ONE = Ext.wrap(1);
+
+function ext(uint256 a0, uint256 a1) pure returns (Ext) {
+    return Ext.wrap(a0 | (a1 << 64));
+}
+
+function fromBase(uint256 a) pure returns (Ext) {
+    return Ext.wrap(a);
+}
+
+function limbs(Ext a) pure returns (uint256, uint256) {
+    uint256 v = Ext.unwrap(a);
+    return (v & MASK_64, v >> 64);
+}
+
+function extAdd(Ext a, Ext b) pure returns (Ext) {
+    (uint256 a0, uint256 a1) = limbs(a);
+    (uint256 b0, uint256 b1) = limbs(b);
+    return ext(addmod(a0, b0, P), addmod(a1, b1, P));
+}
+
+function extSub(Ext a, Ext b) pure returns (Ext) {
+    (uint256 a0, uint256 a1) = limbs(a);
+    (uint256 b0, uint256 b1) = limbs(b);
+    return ext(addmod(a0, P - b0, P), addmod(a1, P - b1, P));
+}
+
+function extMul(Ext a, Ext b) pure returns (Ext) {
+    (uint256 a0, uint256 a1) = limbs(a);
+    (uint256 b0, uint256 b1) = limbs(b);
+    return ext(
+        addmod(mulmod(a0, b0, P), mulmod(W, mulmod(a1, b1, P), P), P),
+        addmod(mulmod(a0, b1, P), mulmod(a1, b0, P), P)
+    );
+}
+
+function extEq(Ext a, Ext b) pure returns (bool) {
+    return Ext.unwrap(a) == Ext.unwrap(b);
+}
+
+function baseExp(uint256 x, uint256 e) pure returns (uint256 r) {
+    r = 1;
+    while (e > 0) {
+        if ((e & 1) == 1) {
+            r = mulmod(r, x, P);
+        }
+        x = mulmod(x, x, P);
+        e >>= 1;
+    }
+}
+
+function baseExpPowerOf2(uint256 x, uint256 k) pure returns (uint256) {
+    for (uint256 i = 0; i < k; i++) {
+        x = mulmod(x, x, P);
+    }
+    return x;
+}
+
+function baseInverse(uint256 x) pure returns (uint256) {
+    require(x != 0, "inverse of zero");
+    return baseExp(x, P - 2);
+}
+
+function extExp(Ext x, uint256 e) pure returns (Ext r) {
+    r = EXT_ONE;
+    while (e > 0) {
+        if ((e & 1) == 1) {
+            r = r * x;
+        }
+        x = x * x;
+        e >>= 1;
+    }
+}
+
+function extExpPowerOf2(Ext x, uint256 k) pure returns (Ext) {
+    for (uint256 i = 0; i < k; i++) {
+        x = x * x;
+    }
+    return x;
+}
+
+// `(a0 - a1 X) / (a0^2 - W a1^2)`.
+function extInverse(Ext a) pure returns (Ext) {
+    (uint256 a0, uint256 a1) = limbs(a);
+    uint256 norm = addmod(mulmod(a0, a0, P), P - mulmod(W, mulmod(a1, a1, P), P), P);
+    uint256 normInverse = baseInverse(norm);
+    return ext(mulmod(a0, normInverse, P), mulmod(P - a1, normInverse, P));
+}
+
+// Inverts every element of `v` in place, with a single field inversion.
+function batchInverse(Ext[] memory v) pure {
+    Ext[] memory prefix = new Ext[](v.length);
+    Ext acc = EXT_ONE;
+    for (uint256 i = 0; i < v.length; i++) {
+        prefix[i] = acc;
+        acc = acc * v[i];
+    }
+    Ext inverse = extInverse(acc);
+    for (uint256 i = v.length; i > 0; i--) {
+        Ext x = v[i - 1];
+        v[i - 1] = inverse * prefix[i - 1];
+        inverse = inverse * x;
+    }
+}
+
+// Multiplies `a0 + a1 Y` by `b0 + b1 Y` in the extension algebra `Ext[Y] / (Y^2 - W)`.
+function algebraMul(Ext a0, Ext a1, Ext b0, Ext b1) pure returns (Ext, Ext) {
+    return (a0 * b0 + fromBase(W) * a1 * b1, a0 * b1 + a1 * b0);
+}
+
+// One step of a partial interpolation over extension algebra elements, with `st = [e, p]`:
+// `e <- e t + v p` and `p <- p t`.
+function interpolationStep(Ext[4] memory st, Ext t0, Ext t1, Ext v0, Ext v1) pure {
+    (Ext e0, Ext e1) = algebraMul(st[0], st[1], t0, t1);
+    (Ext u0, Ext u1) = algebraMul(v0, v1, st[2], st[3]);
+    st[0] = e0 + u0;
+    st[1] = e1 + u1;
+    (st[2], st[3]) = algebraMul(st[2], st[3], t0, t1);
+}
+
+function reverseBits(uint256 x, uint256 bits) pure returns (uint256 r) {
+    for (uint256 i = 0; i < bits; i++) {
+        r = (r << 1) | (x & 1);
+        x >>= 1;
+    }
+}
+
+function reverseBytes64(uint256 x) pure returns (uint256) {
+    x = ((x & 0xFF00FF00FF00FF00) >> 8) | ((x & 0x00FF00FF00FF00FF) << 8);
+    x = ((x & 0xFFFF0000FFFF0000) >> 16) | ((x & 0x0000FFFF0000FFFF) << 16);
+    return (x >> 32) | ((x & 0xFFFFFFFF) << 32);
+}
+
+// Reads the `i`th big-endian `u64` of a constant table.
+function readTable(bytes memory table, uint256 i) pure returns (uint256 v) {
+    assembly ("memory-safe") {
+        v := shr(192, mload(add(add(table, 32), mul(i, 8))))
+    }
+}
+
+// Reads the `i`th hash of a constant table of 25-byte hashes.
+function readTableHash(bytes memory table, uint256 i) pure returns (bytes25) {
+    bytes32 word;
+    assembly ("memory-safe") {
+        word := mload(add(add(table, 32), mul(i, 25)))
+    }
+    return bytes25(word);
+}
+
+// Reads a little-endian, canonical field element from the proof.
+function readElement(bytes calldata proof, uint256 offset) pure returns (uint256 x) {
+    x = reverseBytes64(uint256(uint64(bytes8(proof[offset:offset + 8]))));
+    require(x < P, "non-canonical field element");
+}
+
+function readExt(bytes calldata proof, uint256 offset) pure returns (Ext) {
+    return ext(readElement(proof, offset), readElement(proof, offset + 8));
+}
+
+function readHash(bytes calldata proof, uint256 offset) pure returns (bytes25) {
+    return bytes25(proof[offset:offset + HASH_SIZE]);
+}
+
+function readExtVector(bytes calldata proof, uint256 offset, uint256 length) pure returns (Ext[] memory v) {
+    v = new Ext[](length);
+    for (uint256 i = 0; i < length; i++) {
+        v[i] = readExt(proof, offset + 16 * i);
+    }
+}
+
+// `KeccakHash::hash_or_noop` of `numElements` little-endian field elements stored in the proof.
+function hashOrNoop(bytes calldata proof, uint256 offset, uint256 numElements) pure returns (bytes25) {
+    bytes calldata data = proof[offset:offset + 8 * numElements];
+    if (8 * numElements <= HASH_SIZE) {
+        return bytes25(data);
+    }
+    return bytes25(keccak256(data));
+}
+
+function twoToOne(bytes25 left, bytes25 right) pure returns (bytes25) {
+    return bytes25(keccak256(abi.encodePacked(left, right)));
+}
+
+// Hashes a leaf up to the cap, returning the root it reaches and its index in the cap.
+function merkleRoot(
+    bytes calldata proof,
+    bytes25 leaf,
+    uint256 index,
+    uint256 siblingsOffset,
+    uint256 numSiblings
+) pure returns (bytes25, uint256) {
+    for (uint256 i = 0; i < numSiblings; i++) {
+        bytes25 sibling = readHash(proof, siblingsOffset + HASH_SIZE * i);
+        leaf = (index & 1) == 1 ? twoToOne(sibling, leaf) : twoToOne(leaf, sibling);
+        index >>= 1;
+    }
+    return (leaf, index);
+}
+
+// The Keccak pseudo-permutation of `KeccakPermutation`: the little-endian state is hashed
+// repeatedly, and the state is refilled with the output words that are canonical field elements.
+function keccakPermute(uint256[12] memory state) pure {
+    bytes memory input = new bytes(96);
+    for (uint256 i = 0; i < SPONGE_WIDTH; i++) {
+        uint256 word = reverseBytes64(state[i]) << 192;
+        assembly ("memory-safe") {
+            mstore(add(add(input, 32), mul(i, 8)), word)
+        }
+    }
+    bytes32 h = keccak256(input);
+    uint256 n = 0;
+    while (true) {
+        for (uint256 j = 0; j < 4; j++) {
+            uint256 word = reverseBytes64((uint256(h) >> (192 - 64 * j)) & MASK_64);
+            if (word < P) {
+                state[n] = word;
+                n += 1;
+                if (n == SPONGE_WIDTH) {
+                    return;
+                }
+            }
+        }
+        h = keccak256(abi.encodePacked(h));
+    }
+}
+
+// The Fiat-Shamir duplex sponge of `Challenger<F, KeccakHash<25>>`.
+struct Challenger {
+    uint256[12] state;
+    uint256[8] inputBuffer;
+    uint256 inputLength;
+    uint256[8] outputBuffer;
+    uint256 outputLength;
+}
+
+function duplexing(Challenger memory c) pure {
+    for (uint256 i = 0; i < c.inputLength; i++) {
+        c.state[i] = c.inputBuffer[i];
+    }
+    c.inputLength = 0;
+    keccakPermute(c.state);
+    for (uint256 i = 0; i < SPONGE_RATE; i++) {
+        c.outputBuffer[i] = c.state[i];
+    }
+    c.outputLength = SPONGE_RATE;
+}
+
+function observeElement(Challenger memory c, uint256 x) pure {
+    c.outputLength = 0;
+    c.inputBuffer[c.inputLength] = x;
+    c.inputLength += 1;
+    if (c.inputLength == SPONGE_RATE) {
+        duplexing(c);
+    }
+}
+
+// Observes `count` field elements stored in the proof.
+function observeElements(Challenger memory c, bytes calldata proof, uint256 offset, uint256 count) pure {
+    for (uint256 i = 0; i < count; i++) {
+        observeElement(c, readElement(proof, offset + 8 * i));
+    }
+}
+
+// Observes a hash as the field elements of `BytesHash::to_vec`, i.e. little-endian 7-byte chunks.
+function observeHash(Challenger memory c, bytes25 h) pure {
+    for (uint256 i = 0; i < HASH_SIZE; i += 7) {
+        uint256 x = 0;
+        for (uint256 j = 0; j < 7 && i + j < HASH_SIZE; j++) {
+            x |= uint256(uint8(h[i + j])) << (8 * j);
+        }
+        observeElement(c, x);
+    }
+}
+
+function observeCap(Challenger memory c, bytes calldata proof, uint256 offset, uint256 length) pure {
+    for (uint256 i = 0; i < length; i++) {
+        observeHash(c, readHash(proof, offset + HASH_SIZE * i));
+    }
+}
+
+function getChallenge(Challenger memory c) pure returns (uint256) {
+    if (c.inputLength != 0 || c.outputLength == 0) {
+        duplexing(c);
+    }
+    c.outputLength -= 1;
+    return c.outputBuffer[c.outputLength];
+}
+
+function getNChallenges(Challenger memory c, uint256 n) pure returns (uint256[] memory v) {
+    v = new uint256[](n);
+    for (uint256 i = 0; i < n; i++) {
+        v[i] = getChallenge(c);
+    }
+}
+
+function getExtensionChallenge(Challenger memory c) pure returns (Ext) {
+    uint256 a0 = getChallenge(c);
+    uint256 a1 = getChallenge(c);
+    return ext(a0, a1);
+}
+
+function poseidonSbox(Ext x) pure returns (Ext) {
+    Ext x2 = x * x;
+    Ext x4 = x2 * x2;
+    return x * x2 * x4;
+}
+
+function poseidonConstantLayer(Ext[12] memory state, bytes memory roundConstants, uint256 round) pure {
+    for (uint256 i = 0; i < SPONGE_WIDTH; i++) {
+        state[i] = state[i] + fromBase(readTable(roundConstants, i + SPONGE_WIDTH * round));
+    }
+}
+
+// Applies the Poseidon MDS matrix to `state` in place.
+function poseidonMdsLayer(Ext[12] memory state) pure {
+    bytes memory circ = POSEIDON_MDS_MATRIX_CIRC;
+    bytes memory diag = POSEIDON_MDS_MATRIX_DIAG;
+    Ext[12] memory result;
+    for (uint256 r = 0; r < SPONGE_WIDTH; r++) {
+        Ext acc = state[r] * fromBase(readTable(diag, r));
+        for (uint256 i = 0; i < SPONGE_WIDTH; i++) {
+            acc = acc + state[(i + r) % SPONGE_WIDTH] * fromBase(readTable(circ, i));
+        }
+        result[r] = acc;
+    }
+    for (uint256 i = 0; i < SPONGE_WIDTH; i++) {
+        state[i] = result[i];
+    }
+}
+
+// The Poseidon permutation, computed with the naive partial rounds.
+function poseidonPermute(Ext[12] memory state) pure {
+    bytes memory roundConstants = POSEIDON_ROUND_CONSTANTS;
+    for (uint256 round = 0; round < {{POSEIDON_N_ROUNDS}}; round++) {
+        poseidonConstantLayer(state, roundConstants, round);
+        if (round < {{POSEIDON_HALF_N_FULL_ROUNDS}} || round >= {{POSEIDON_HALF_N_FULL_ROUNDS}} + {{POSEIDON_N_PARTIAL_ROUNDS}}) {
+            for (uint256 i = 0; i < SPONGE_WIDTH; i++) {
+                state[i] = poseidonSbox(state[i]);
+            }
+        } else {
+            state[0] = poseidonSbox(state[0]);
+        }
+        poseidonMdsLayer(state);
+    }
+}
+
+// `PoseidonHash::hash_no_pad` of the public inputs.
+function publicInputsHash(uint64[] calldata publicInputs) pure returns (uint256[4] memory h) {
+    Ext[12] memory state;
+    for (uint256 i = 0; i < publicInputs.length; i += SPONGE_RATE) {
+        for (uint256 j = 0; j < SPONGE_RATE && i + j < publicInputs.length; j++) {
+            require(publicInputs[i + j] < P, "non-canonical public input");
+            state[j] = fromBase(publicInputs[i + j]);
+        }
+        poseidonPermute(state);
+    }
+    for (uint256 i = 0; i < 4; i++) {
+        h[i] = Ext.unwrap(state[i]);
+    }
+}
+
+// Adds `filter * constraints[i]` to `acc[i]`.
+function accumulateConstraints(Ext[] memory acc, Ext[] memory constraints, Ext filter) pure {
+    for (uint256 i = 0; i < constraints.length; i++) {
+        acc[i] = acc[i] + filter * constraints[i];
+    }
+}
+
+/// Verifies plonky2 proofs of a single circuit, proven with `KeccakGoldilocksConfig`.
+contract {{CONTRACT_NAME}} {
+{{CIRCUIT_CONSTANTS}}
+    struct Openings {
+        Ext[] constants;
+        Ext[] sigmas;
+        Ext[] wires;
+        Ext[] zs;
+        Ext[] partialProducts;
+        Ext[] quotients;
+        Ext[] zsNext;
+    }
+
+    struct ProofChallenges {
+        uint256[] betas;
+        uint256[] gammas;
+        uint256[] alphas;
+        Ext zeta;
+        Ext friAlpha;
+        Ext[] friBetas;
+        uint256 powResponse;
+        uint256[] queryIndices;
+    }
+
+    // The state of a FRI query round, threaded through the folding steps.
+    struct QueryState {
+        uint256 xIndex;
+        uint256 offset;
+        uint256 x;
+        Ext oldEval;
+        uint256 reducedBits;
+    }
+
+    /// Returns true if `proof`, as encoded by `plonky2::plonk::solidity::encode_proof`, is a
+    /// valid proof with the given public inputs, and reverts otherwise.
+    function verify(uint64[] calldata publicInputs, bytes calldata proof) external pure returns (bool) {
+        require(publicInputs.length == NUM_PUBLIC_INPUTS, "invalid number of public inputs");
+        require(proof.length == PROOF_SIZE, "invalid proof size");
+
+        uint256[4] memory pih = publicInputsHash(publicInputs);
+        ProofChallenges memory ch = getChallenges(proof, pih);
+        checkVanishingPolynomial(readOpenings(proof), ch, pih);
+        verifyFri(proof, ch);
+        return true;
+    }
+
+    function getChallenges(bytes calldata proof, uint256[4] memory pih)
+        internal
+        pure
+        returns (ProofChallenges memory ch)
+    {
+        Challenger memory c;
+        observeHash(c, CIRCUIT_DIGEST);
+        for (uint256 i = 0; i < 4; i++) {
+            observeElement(c, pih[i]);
+        }
+
+        observeCap(c, proof, WIRES_CAP_OFFSET, CAP_LENGTH);
+        ch.betas = getNChallenges(c, NUM_CHALLENGES);
+        ch.gammas = getNChallenges(c, NUM_CHALLENGES);
+        observeCap(c, proof, ZS_PARTIAL_PRODUCTS_CAP_OFFSET, CAP_LENGTH);
+        ch.alphas = getNChallenges(c, NUM_CHALLENGES);
+        observeCap(c, proof, QUOTIENT_CAP_OFFSET, CAP_LENGTH);
+        ch.zeta = getExtensionChallenge(c);
+        observeElements(c, proof, OPENINGS_OFFSET, 2 * (NUM_OPENINGS_AT_ZETA + NUM_CHALLENGES));
+
+        ch.friAlpha = getExtensionChallenge(c);
+        ch.friBetas = new Ext[](NUM_FRI_REDUCTIONS);
+        for (uint256 i = 0; i < NUM_FRI_REDUCTIONS; i++) {
+            observeCap(c, proof, COMMIT_PHASE_CAPS_OFFSET + HASH_SIZE * CAP_LENGTH * i, CAP_LENGTH);
+            ch.friBetas[i] = getExtensionChallenge(c);
+        }
+        observeElements(c, proof, FINAL_POLY_OFFSET, 2 * FINAL_POLY_LENGTH);
+        observeElements(c, proof, POW_WITNESS_OFFSET, 1);
+        ch.powResponse = getChallenge(c);
+        ch.queryIndices = new uint256[](NUM_QUERY_ROUNDS);
+        for (uint256 i = 0; i < NUM_QUERY_ROUNDS; i++) {
+            ch.queryIndices[i] = getChallenge(c) % (1 << LDE_BITS);
+        }
+    }
+
+    function readOpenings(bytes calldata proof) internal pure returns (Openings memory op) {
+        op.constants = readExtVector(proof, CONSTANTS_OPENINGS_OFFSET, NUM_CONSTANTS);
+        op.sigmas = readExtVector(proof, SIGMAS_OPENINGS_OFFSET, NUM_ROUTED_WIRES);
+        op.wires = readExtVector(proof, WIRES_OPENINGS_OFFSET, NUM_WIRES);
+        op.zs = readExtVector(proof, ZS_OPENINGS_OFFSET, NUM_CHALLENGES);
+        op.partialProducts = readExtVector(
+            proof,
+            PARTIAL_PRODUCTS_OPENINGS_OFFSET,
+            NUM_CHALLENGES * NUM_PARTIAL_PRODUCTS
+        );
+        op.quotients = readExtVector(
+            proof,
+            QUOTIENT_OPENINGS_OFFSET,
+            NUM_CHALLENGES * QUOTIENT_DEGREE_FACTOR
+        );
+        op.zsNext = readExtVector(proof, ZS_NEXT_OPENINGS_OFFSET, NUM_CHALLENGES);
+    }
+
+    // Checks `vanishing(zeta) = Z_H(zeta) quotient(zeta)` for each challenge.
+    function checkVanishingPolynomial(Openings memory op, ProofChallenges memory ch, uint256[4] memory pih)
+        internal
+        pure
+    {
+        Ext[] memory terms = vanishingTerms(op, ch, pih);
+        Ext zetaPowN = extExpPowerOf2(ch.zeta, DEGREE_BITS);
+        Ext zH = zetaPowN - EXT_ONE;
+        for (uint256 i = 0; i < NUM_CHALLENGES; i++) {
+            Ext alpha = fromBase(ch.alphas[i]);
+            Ext vanishing = EXT_ZERO;
+            for (uint256 j = terms.length; j > 0; j--) {
+                vanishing = vanishing * alpha + terms[j - 1];
+            }
+            Ext quotient = EXT_ZERO;
+            for (uint256 j = QUOTIENT_DEGREE_FACTOR; j > 0; j--) {
+                quotient = quotient * zetaPowN + op.quotients[i * QUOTIENT_DEGREE_FACTOR + j - 1];
+            }
+            require(vanishing == zH * quotient, "vanishing polynomial check failed");
+        }
+    }
+
+    // The `L_0(x) (Z(x) - 1)` terms, the partial products terms and the gate constraints.
+    function vanishingTerms(Openings memory op, ProofChallenges memory ch, uint256[4] memory pih)
+        internal
+        pure
+        returns (Ext[] memory terms)
+    {
+        terms = new Ext[](NUM_CHALLENGES * (2 + NUM_PARTIAL_PRODUCTS) + NUM_GATE_CONSTRAINTS);
+        Ext l0 = EXT_ONE;
+        if (!(ch.zeta == EXT_ONE)) {
+            Ext zH = extExpPowerOf2(ch.zeta, DEGREE_BITS) - EXT_ONE;
+            l0 = zH * extInverse(fromBase(1 << DEGREE_BITS) * (ch.zeta - EXT_ONE));
+        }
+        for (uint256 i = 0; i < NUM_CHALLENGES; i++) {
+            terms[i] = l0 * (op.zs[i] - EXT_ONE);
+            partialProductTerms(terms, op, ch, i);
+        }
+        Ext[] memory gateConstraints = evaluateGateConstraints(op.constants, op.wires, pih);
+        uint256 start = NUM_CHALLENGES * (2 + NUM_PARTIAL_PRODUCTS);
+        for (uint256 i = 0; i < NUM_GATE_CONSTRAINTS; i++) {
+            terms[start + i] = gateConstraints[i];
+        }
+    }
+
+    function partialProductTerms(Ext[] memory terms, Openings memory op, ProofChallenges memory ch, uint256 i)
+        internal
+        pure
+    {
+        Ext prev = op.zs[i];
+        for (uint256 k = 0; k <= NUM_PARTIAL_PRODUCTS; k++) {
+            (Ext numerator, Ext denominator) = chunkProducts(op, ch, i, k);
+            Ext next = k == NUM_PARTIAL_PRODUCTS
+                ? op.zsNext[i]
+                : op.partialProducts[i * NUM_PARTIAL_PRODUCTS + k];
+            terms[NUM_CHALLENGES + i * (NUM_PARTIAL_PRODUCTS + 1) + k] = prev * numerator - next * denominator;
+            prev = next;
+        }
+    }
+
+    // The products of the `k`th chunk of the permutation argument's numerators and denominators.
+    function chunkProducts(Openings memory op, ProofChallenges memory ch, uint256 i, uint256 k)
+        internal
+        pure
+        returns (Ext numerator, Ext denominator)
+    {
+        bytes memory kIs = K_IS;
+        Ext beta = fromBase(ch.betas[i]);
+        Ext gamma = fromBase(ch.gammas[i]);
+        numerator = EXT_ONE;
+        denominator = EXT_ONE;
+        uint256 end = (k + 1) * QUOTIENT_DEGREE_FACTOR;
+        if (end > NUM_ROUTED_WIRES) {
+            end = NUM_ROUTED_WIRES;
+        }
+        for (uint256 j = k * QUOTIENT_DEGREE_FACTOR; j < end; j++) {
+            Ext wire = op.wires[j];
+            Ext sId = ch.zeta * fromBase(readTable(kIs, j));
+            numerator = numerator * (wire + sId * beta + gamma);
+            denominator = denominator * (wire + op.sigmas[j] * beta + gamma);
+        }
+    }
+
+    function evaluateGateConstraints(Ext[] memory constants, Ext[] memory wires, uint256[4] memory pih)
+        internal
+        pure
+        returns (Ext[] memory constraints)
+    {
+        constraints = new Ext[](NUM_GATE_CONSTRAINTS);
+        // The gate constants, without the selectors.
+        Ext[] memory c = new Ext[](NUM_CONSTANTS - NUM_SELECTORS);
+        for (uint256 i = 0; i < c.length; i++) {
+            c[i] = constants[NUM_SELECTORS + i];
+        }
+        Ext filter;
+{{GATE_CONSTRAINTS}}
+    }
+{{GATE_FUNCTIONS}}
+    function verifyFri(bytes calldata proof, ProofChallenges memory ch) internal pure {
+        require(
+            POW_BITS == 0 || (ch.powResponse >> (64 - POW_BITS)) == 0,
+            "invalid proof of work witness"
+        );
+        Ext[2] memory reducedOpenings = [
+            reduceExtensions(proof, OPENINGS_OFFSET, NUM_OPENINGS_AT_ZETA, ch.friAlpha),
+            reduceExtensions(proof, ZS_NEXT_OPENINGS_OFFSET, NUM_CHALLENGES, ch.friAlpha)
+        ];
+        for (uint256 q = 0; q < NUM_QUERY_ROUNDS; q++) {
+            verifyQueryRound(proof, ch, reducedOpenings, q);
+        }
+    }
+
+    // `sum_i alpha^i v_i` for `count` extension elements `v_i` stored in the proof.
+    function reduceExtensions(bytes calldata proof, uint256 offset, uint256 count, Ext alpha)
+        internal
+        pure
+        returns (Ext acc)
+    {
+        for (uint256 i = count; i > 0; i--) {
+            acc = acc * alpha + readExt(proof, offset + 16 * (i - 1));
+        }
+    }
+
+    // Like `reduceExtensions`, for base field elements, continuing from `acc`.
+    function reduceElements(bytes calldata proof, uint256 offset, uint256 count, Ext alpha, Ext acc)
+        internal
+        pure
+        returns (Ext)
+    {
+        for (uint256 i = count; i > 0; i--) {
+            acc = acc * alpha + fromBase(readElement(proof, offset + 8 * (i - 1)));
+        }
+        return acc;
+    }
+
+    function verifyQueryRound(
+        bytes calldata proof,
+        ProofChallenges memory ch,
+        Ext[2] memory reducedOpenings,
+        uint256 q
+    ) internal pure {
+        QueryState memory s;
+        s.xIndex = ch.queryIndices[q];
+        s.offset = QUERY_ROUNDS_OFFSET + q * QUERY_ROUND_SIZE;
+        verifyInitialTrees(proof, s);
+        s.x = mulmod(
+            MULTIPLICATIVE_GROUP_GENERATOR,
+            baseExp(LDE_ROOT_OF_UNITY, reverseBits(s.xIndex, LDE_BITS)),
+            P
+        );
+        s.oldEval = combineInitial(proof, s, ch, reducedOpenings);
+        s.offset += INITIAL_TREES_SIZE;
+        for (uint256 i = 0; i < NUM_FRI_REDUCTIONS; i++) {
+            friStep(proof, s, ch.friBetas[i], i);
+        }
+        require(evalFinalPoly(proof, s.x) == s.oldEval, "final polynomial evaluation is invalid");
+    }
+
+    function verifyInitialTrees(bytes calldata proof, QueryState memory s) internal pure {
+        (bytes25 root, uint256 capIndex) = initialTreeRoot(proof, s, ORACLE_0_OFFSET, LEAF_LENGTH_0);
+        require(root == readTableHash(CONSTANTS_SIGMAS_CAP, capIndex), "invalid initial Merkle proof");
+        (root, capIndex) = initialTreeRoot(proof, s, ORACLE_1_OFFSET, LEAF_LENGTH_1);
+        require(root == readHash(proof, WIRES_CAP_OFFSET + HASH_SIZE * capIndex), "invalid initial Merkle proof");
+        (root, capIndex) = initialTreeRoot(proof, s, ORACLE_2_OFFSET, LEAF_LENGTH_2);
+        require(
+            root == readHash(proof, ZS_PARTIAL_PRODUCTS_CAP_OFFSET + HASH_SIZE * capIndex),
+            "invalid initial Merkle proof"
+        );
+        (root, capIndex) = initialTreeRoot(proof, s, ORACLE_3_OFFSET, LEAF_LENGTH_3);
+        require(root == readHash(proof, QUOTIENT_CAP_OFFSET + HASH_SIZE * capIndex), "invalid initial Merkle proof");
+    }
+
+    function initialTreeRoot(bytes calldata proof, QueryState memory s, uint256 oracleOffset, uint256 leafLength)
+        internal
+        pure
+        returns (bytes25, uint256)
+    {
+        uint256 offset = s.offset + oracleOffset;
+        bytes25 leaf = hashOrNoop(proof, offset, leafLength);
+        return merkleRoot(proof, leaf, s.xIndex, offset + 8 * leafLength, LDE_BITS - CAP_HEIGHT);
+    }
+
+    // Combines the initial openings into the evaluation of the first FRI polynomial at `s.x`.
+    function combineInitial(
+        bytes calldata proof,
+        QueryState memory s,
+        ProofChallenges memory ch,
+        Ext[2] memory reducedOpenings
+    ) internal pure returns (Ext) {
+        Ext alpha = ch.friAlpha;
+        Ext acc = reduceElements(proof, s.offset + ORACLE_3_OFFSET, NUM_POLYS_3, alpha, EXT_ZERO);
+        acc = reduceElements(proof, s.offset + ORACLE_2_OFFSET, NUM_POLYS_2, alpha, acc);
+        acc = reduceElements(proof, s.offset + ORACLE_1_OFFSET, NUM_POLYS_1, alpha, acc);
+        acc = reduceElements(proof, s.offset + ORACLE_0_OFFSET, NUM_POLYS_0, alpha, acc);
+        Ext sum = (acc - reducedOpenings[0]) * extInverse(fromBase(s.x) - ch.zeta);
+
+        Ext zs = reduceElements(proof, s.offset + ORACLE_2_OFFSET, NUM_CHALLENGES, alpha, EXT_ZERO);
+        Ext zetaNext = ch.zeta * fromBase(SUBGROUP_GENERATOR);
+        return sum * extExp(alpha, NUM_CHALLENGES)
+            + (zs - reducedOpenings[1]) * extInverse(fromBase(s.x) - zetaNext);
+    }
+
+    function friStep(bytes calldata proof, QueryState memory s, Ext beta, uint256 step) internal pure {
+        uint256 arityBits = uint8(FRI_REDUCTION_ARITY_BITS[step]);
+        uint256 arity = 1 << arityBits;
+        uint256 xIndexWithinCoset = s.xIndex & (arity - 1);
+        require(readExt(proof, s.offset + 16 * xIndexWithinCoset) == s.oldEval, "inconsistent FRI evaluations");
+        s.oldEval = computeEvaluation(proof, s, xIndexWithinCoset, arityBits, beta);
+
+        s.reducedBits += arityBits;
+        s.xIndex >>= arityBits;
+        bytes25 leaf = hashOrNoop(proof, s.offset, 2 * arity);
+        s.offset += 16 * arity;
+        uint256 numSiblings = LDE_BITS - s.reducedBits - CAP_HEIGHT;
+        (bytes25 root, uint256 capIndex) = merkleRoot(proof, leaf, s.xIndex, s.offset, numSiblings);
+        require(
+            root == readHash(proof, COMMIT_PHASE_CAPS_OFFSET + HASH_SIZE * (CAP_LENGTH * step + capIndex)),
+            "invalid FRI Merkle proof"
+        );
+        s.offset += HASH_SIZE * numSiblings;
+        s.x = baseExpPowerOf2(s.x, arityBits);
+    }
+
+    // Interpolates the coset evaluations stored at `s.offset` and evaluates the interpolant at
+    // `beta`. The coset is `c <g>` with `c^n = x^n`, so the barycentric weights are
+    // `x_j / (n x^n)` and the vanishing polynomial at `beta` is `beta^n - x^n`.
+    function computeEvaluation(
+        bytes calldata proof,
+        QueryState memory s,
+        uint256 xIndexWithinCoset,
+        uint256 arityBits,
+        Ext beta
+    ) internal pure returns (Ext) {
+        uint256[] memory points = cosetPoints(s.x, xIndexWithinCoset, arityBits);
+        Ext[] memory denominators = new Ext[](points.length);
+        for (uint256 j = 0; j < points.length; j++) {
+            denominators[j] = beta - fromBase(points[j]);
+        }
+        batchInverse(denominators);
+        Ext sum = EXT_ZERO;
+        for (uint256 j = 0; j < points.length; j++) {
+            Ext eval = readExt(proof, s.offset + 16 * reverseBits(j, arityBits));
+            sum = sum + eval * fromBase(points[j]) * denominators[j];
+        }
+        uint256 xPowN = baseExpPowerOf2(s.x, arityBits);
+        Ext vanishing = extExpPowerOf2(beta, arityBits) - fromBase(xPowN);
+        return vanishing * sum * fromBase(baseInverse(mulmod(points.length, xPowN, P)));
+    }
+
+    // The points `c g^j` of the coset containing `x`, in the order matching the reversed evals.
+    function cosetPoints(uint256 x, uint256 xIndexWithinCoset, uint256 arityBits)
+        internal
+        pure
+        returns (uint256[] memory points)
+    {
+        uint256 arity = 1 << arityBits;
+        uint256 g = baseExpPowerOf2(POWER_OF_TWO_GENERATOR, TWO_ADICITY - arityBits);
+        uint256 point = mulmod(x, baseExp(g, arity - reverseBits(xIndexWithinCoset, arityBits)), P);
+        points = new uint256[](arity);
+        for (uint256 j = 0; j < arity; j++) {
+            points[j] = point;
+            point = mulmod(point, g, P);
+        }
+    }
+
+    function evalFinalPoly(bytes calldata proof, uint256 x) internal pure returns (Ext acc) {
+        for (uint256 i = FINAL_POLY_LENGTH; i > 0; i--) {
+            acc = acc * fromBase(x) + readExt(proof, FINAL_POLY_OFFSET + 16 * (i - 1));
+        }
+    }
+}
@@ -5,6 +5,9 @@ use crate::field::batch_util::batch_add_inplace;
 use crate::field::extension::{Extendable, FieldExtension};
 use crate::field::types::Field;
 use crate::field::zero_poly_coset::ZeroPolyOnCoset;
+use crate::gates::lookup::LookupGate;
+use crate::gates::lookup_table::{LookupTable, LookupTableGate};
+use crate::gates::selectors::LookupSelectors;
 use crate::hash::hash_types::RichField;
 use crate::iop::ext_target::ExtensionTarget;
 use crate::iop::target::Target;
@@ -13,11 +16,19 @@ use crate::plonk::circuit_data::CommonCircuitData;
 use crate::plonk::plonk_common;
 use crate::plonk::plonk_common::eval_l_0_circuit;
 use crate::plonk::vars::{EvaluationTargets, EvaluationVars, EvaluationVarsBaseBatch};
+use crate::util::ceil_div_usize;
 use crate::util::partial_products::{check_partial_products, check_partial_products_circuit};
 use crate::util::reducing::ReducingFactorTarget;
 use crate::util::strided_view::PackedStridedView;
 use crate::with_context;
 
+/// The number of lookup challenges drawn for each repetition of the lookup argument. They are, in
+/// order: `A`, combining the input, output and tag of an entry in the logUp sums; `alpha`, the
+/// point at which the logUp fractions are evaluated; `B`, combining the input and output of an
+/// entry in the running evaluation of the tables; and `delta`, the point at which the tables are
+/// evaluated.
+pub(crate) const NUM_LOOKUP_CHALLENGES: usize = 4;
+
 /// Evaluate the vanishing polynomial at `x`. In this context, the vanishing polynomial is a random
 /// linear combination of gate constraints, plus some other terms relating to the permutation
 /// argument. All such terms should vanish on `H`.
@@ -29,12 +40,17 @@ pub(crate) fn eval_vanishing_poly<F: RichField + Extendable<D>, const D: usize>(
     next_zs: &[F::Extension],
     partial_products: &[F::Extension],
     s_sigmas: &[F::Extension],
+    lookup_zs: &[F::Extension],
+    next_lookup_zs: &[F::Extension],
     betas: &[F],
     gammas: &[F],
+    deltas: &[F],
     alphas: &[F],
 ) -> Vec<F::Extension> {
     let max_degree = common_data.quotient_degree_factor;
     let num_prods = common_data.num_partial_products;
+    let num_lookup_polys = common_data.num_lookup_polys;
+    let has_lookup = !common_data.luts.is_empty();
 
     let constraint_terms = evaluate_gate_constraints::<F, D>(common_data, vars);
 
@@ -42,6 +58,8 @@ pub(crate) fn eval_vanishing_poly<F: RichField + Extendable<D>, const D: usize>(
     let mut vanishing_z_1_terms = Vec::new();
     // The terms checking the partial products.
     let mut vanishing_partial_products_terms = Vec::new();
+    // The terms checking the lookup argument.
+    let mut vanishing_lookup_terms = Vec::new();
 
     let l_0_x = plonk_common::eval_l_0(common_data.degree(), x);
 
@@ -78,11 +96,34 @@ pub(crate) fn eval_vanishing_poly<F: RichField + Extendable<D>, const D: usize>(
             max_degree,
         );
         vanishing_partial_products_terms.extend(partial_product_checks);
+
+        if has_lookup {
+            let challenges = lookup_challenges(deltas, i).map(F::Extension::from_basefield);
+            let [_, _, b, delta] = challenges;
+            let lut_eval = eval_luts(
+                &common_data.luts,
+                LookupTableGate::num_slots(&common_data.config),
+                b,
+                delta,
+            );
+            let lookup_range = i * num_lookup_polys..(i + 1) * num_lookup_polys;
+            vanishing_lookup_terms.extend(check_lookup_constraints(
+                common_data,
+                vars.local_wires,
+                &vars.local_constants[common_data.lookup_selectors_range()],
+                &lookup_zs[lookup_range.clone()],
+                &next_lookup_zs[lookup_range],
+                challenges,
+                lut_eval,
+                l_0_x,
+            ));
+        }
     }
 
     let vanishing_terms = [
         vanishing_z_1_terms,
         vanishing_partial_products_terms,
+        vanishing_lookup_terms,
         constraint_terms,
     ]
     .concat();
@@ -101,8 +142,12 @@ pub(crate) fn eval_vanishing_poly_base_batch<F: RichField + Extendable<D>, const
     next_zs_batch: &[&[F]],
     partial_products_batch: &[&[F]],
     s_sigmas_batch: &[&[F]],
+    lookup_zs_batch: &[&[F]],
+    next_lookup_zs_batch: &[&[F]],
     betas: &[F],
     gammas: &[F],
+    deltas: &[F],
+    lut_evals: &[F],
     alphas: &[F],
     z_h_on_coset: &ZeroPolyOnCoset<F>,
 ) -> Vec<Vec<F>> {
@@ -113,9 +158,13 @@ pub(crate) fn eval_vanishing_poly_base_batch<F: RichField + Extendable<D>, const
     assert_eq!(next_zs_batch.len(), n);
     assert_eq!(partial_products_batch.len(), n);
     assert_eq!(s_sigmas_batch.len(), n);
+    assert_eq!(lookup_zs_batch.len(), n);
+    assert_eq!(next_lookup_zs_batch.len(), n);
 
     let max_degree = common_data.quotient_degree_factor;
     let num_prods = common_data.num_partial_products;
+    let num_lookup_polys = common_data.num_lookup_polys;
+    let has_lookup = !common_data.luts.is_empty();
 
     let num_gate_constraints = common_data.num_gate_constraints;
 
@@ -133,6 +182,13 @@ pub(crate) fn eval_vanishing_poly_base_batch<F: RichField + Extendable<D>, const
     let mut vanishing_z_1_terms = Vec::with_capacity(num_challenges);
     // The terms checking the partial products.
     let mut vanishing_partial_products_terms = Vec::new();
+    // The terms checking the lookup argument.
+    let mut vanishing_lookup_terms = Vec::new();
+
+    // The lookup argument needs the wires and lookup selectors of a single point, which are
+    // strided in the batch.
+    let mut local_wires = Vec::new();
+    let mut lookup_selectors = Vec::new();
 
     let mut res_batch: Vec<Vec<F>> = Vec::with_capacity(n);
     for k in 0..n {
@@ -143,9 +199,20 @@ pub(crate) fn eval_vanishing_poly_base_batch<F: RichField + Extendable<D>, const
         let next_zs = next_zs_batch[k];
         let partial_products = partial_products_batch[k];
         let s_sigmas = s_sigmas_batch[k];
+        let lookup_zs = lookup_zs_batch[k];
+        let next_lookup_zs = next_lookup_zs_batch[k];
 
         let constraint_terms = PackedStridedView::new(&constraint_terms_batch, n, k);
 
+        if has_lookup {
+            local_wires.extend(vars.local_wires.iter());
+            lookup_selectors.extend(
+                common_data
+                    .lookup_selectors_range()
+                    .map(|j| vars.local_constants[j]),
+            );
+        }
+
         let l_0_x = z_h_on_coset.eval_l_0(index, x);
         for i in 0..num_challenges {
             let z_x = local_zs[i];
@@ -179,17 +246,35 @@ pub(crate) fn eval_vanishing_poly_base_batch<F: RichField + Extendable<D>, const
 
             numerator_values.clear();
             denominator_values.clear();
+
+            if has_lookup {
+                let lookup_range = i * num_lookup_polys..(i + 1) * num_lookup_polys;
+                vanishing_lookup_terms.extend(check_lookup_constraints(
+                    common_data,
+                    &local_wires,
+                    &lookup_selectors,
+                    &lookup_zs[lookup_range.clone()],
+                    &next_lookup_zs[lookup_range],
+                    lookup_challenges(deltas, i),
+                    lut_evals[i],
+                    l_0_x,
+                ));
+            }
         }
 
         let vanishing_terms = vanishing_z_1_terms
             .iter()
             .chain(vanishing_partial_products_terms.iter())
+            .chain(vanishing_lookup_terms.iter())
             .chain(constraint_terms);
         let res = plonk_common::reduce_with_powers_multi(vanishing_terms, alphas);
         res_batch.push(res);
 
         vanishing_z_1_terms.clear();
         vanishing_partial_products_terms.clear();
+        vanishing_lookup_terms.clear();
+        local_wires.clear();
+        lookup_selectors.clear();
     }
     res_batch
 }
@@ -297,12 +382,17 @@ pub(crate) fn eval_vanishing_poly_circuit<F: RichField + Extendable<D>, const D:
     next_zs: &[ExtensionTarget<D>],
     partial_products: &[ExtensionTarget<D>],
     s_sigmas: &[ExtensionTarget<D>],
+    lookup_zs: &[ExtensionTarget<D>],
+    next_lookup_zs: &[ExtensionTarget<D>],
     betas: &[Target],
     gammas: &[Target],
+    deltas: &[Target],
     alphas: &[Target],
 ) -> Vec<ExtensionTarget<D>> {
     let max_degree = common_data.quotient_degree_factor;
     let num_prods = common_data.num_partial_products;
+    let num_lookup_polys = common_data.num_lookup_polys;
+    let has_lookup = !common_data.luts.is_empty();
 
     let constraint_terms = with_context!(
         builder,
@@ -314,6 +404,8 @@ pub(crate) fn eval_vanishing_poly_circuit<F: RichField + Extendable<D>, const D:
     let mut vanishing_z_1_terms = Vec::new();
     // The terms checking the partial products.
     let mut vanishing_partial_products_terms = Vec::new();
+    // The terms checking the lookup argument.
+    let mut vanishing_lookup_terms = Vec::new();
 
     let l_0_x = eval_l_0_circuit(builder, common_data.degree(), x, x_pow_deg);
 
@@ -362,11 +454,30 @@ pub(crate) fn eval_vanishing_poly_circuit<F: RichField + Extendable<D>, const D:
             max_degree,
         );
         vanishing_partial_products_terms.extend(partial_product_checks);
+
+        if has_lookup {
+            let lookup_range = i * num_lookup_polys..(i + 1) * num_lookup_polys;
+            let lookup_checks = with_context!(
+                builder,
+                "check lookup argument",
+                check_lookup_constraints_circuit(
+                    builder,
+                    common_data,
+                    vars,
+                    &lookup_zs[lookup_range.clone()],
+                    &next_lookup_zs[lookup_range],
+                    lookup_challenges(deltas, i),
+                    l_0_x,
+                )
+            );
+            vanishing_lookup_terms.extend(lookup_checks);
+        }
     }
 
     let vanishing_terms = [
         vanishing_z_1_terms,
         vanishing_partial_products_terms,
+        vanishing_lookup_terms,
         constraint_terms,
     ]
     .concat();
@@ -380,3 +491,299 @@ pub(crate) fn eval_vanishing_poly_circuit<F: RichField + Extendable<D>, const D:
         })
         .collect()
 }
+
+/// Returns the lookup challenges `[A, alpha, B, delta]` of the `i`th repetition.
+pub(crate) fn lookup_challenges<T: Copy>(deltas: &[T], i: usize) -> [T; NUM_LOOKUP_CHALLENGES] {
+    deltas[i * NUM_LOOKUP_CHALLENGES..(i + 1) * NUM_LOOKUP_CHALLENGES]
+        .try_into()
+        .unwrap()
+}
+
+/// Returns the entries of all the tables, as they are laid out in the `LookupTableGate` rows.
+fn lut_entries(luts: &[LookupTable], num_slots: usize) -> Vec<(u16, u16)> {
+    luts.iter()
+        .flat_map(|lut| {
+            let padded_len = ceil_div_usize(lut.len(), num_slots) * num_slots;
+            (0..padded_len).map(|i| lut.get(i).copied().unwrap_or(lut[0]))
+        })
+        .collect()
+}
+
+/// Evaluates `sum_i delta^i (inp_i + b * out_i)` over the entries of all the tables, as they are
+/// laid out in the `LookupTableGate` rows.
+pub(crate) fn eval_luts<F: Field>(luts: &[LookupTable], num_slots: usize, b: F, delta: F) -> F {
+    lut_entries(luts, num_slots)
+        .into_iter()
+        .rev()
+        .fold(F::ZERO, |acc, (inp, out)| {
+            acc * delta + F::from_canonical_u16(inp) + b * F::from_canonical_u16(out)
+        })
+}
+
+/// Circuit version of `eval_luts`.
+pub(crate) fn eval_luts_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    luts: &[LookupTable],
+    num_slots: usize,
+    b: Target,
+    delta: Target,
+) -> Target {
+    let one = builder.one();
+    lut_entries(luts, num_slots)
+        .into_iter()
+        .rev()
+        .fold(builder.zero(), |acc, (inp, out)| {
+            // `acc * delta + inp + b * out`, without turning the entries into constant targets.
+            let acc = builder.arithmetic(F::ONE, F::from_canonical_u16(inp), acc, delta, one);
+            builder.arithmetic(F::from_canonical_u16(out), F::ONE, b, one, acc)
+        })
+}
+
+/// Returns `helper * prod_i d_i - sum_i n_i prod_{j != i} d_j`, which vanishes iff
+/// `helper = sum_i n_i / d_i`, given nonzero denominators `d_i`.
+fn eval_helper_constraint<T: Field>(helper: T, numerators: &[T], denominators: &[T]) -> T {
+    let mut product = T::ONE;
+    let mut sum = T::ZERO;
+    for (&n, &d) in numerators.iter().zip(denominators) {
+        sum = sum * d + n * product;
+        product *= d;
+    }
+    helper * product - sum
+}
+
+/// Checks the lookup argument for a single repetition, given its `[RE, Z, helpers..]` polynomials.
+///
+/// `Z` is a logUp running sum, accumulating `1 / (alpha - combo)` for each lookup and subtracting
+/// `m / (alpha - combo)` for each table entry looked up `m` times, where
+/// `combo = inp + A * out + A^2 * tag`. The helper polynomials hold these sums for chunks of
+/// slots, to keep the constraint degree within the quotient degree factor. `RE` is a running
+/// evaluation of the table entries at `delta`, whose first value must match `lut_eval`, the
+/// evaluation of the tables stored in the common circuit data.
+fn check_lookup_constraints<F: RichField + Extendable<D>, const D: usize, T: Field>(
+    common_data: &CommonCircuitData<F, D>,
+    local_wires: &[T],
+    lookup_selectors: &[T],
+    lookup_zs: &[T],
+    next_lookup_zs: &[T],
+    challenges: [T; NUM_LOOKUP_CHALLENGES],
+    lut_eval: T,
+    l_0_x: T,
+) -> Vec<T> {
+    let max_degree = common_data.quotient_degree_factor;
+    let num_lookup_slots = LookupGate::num_slots(&common_data.config);
+    let num_table_slots = LookupTableGate::num_slots(&common_data.config);
+    let num_looking_helpers = common_data.num_looking_helpers();
+
+    let [a, alpha, b, delta] = challenges;
+    let selector = |s: LookupSelectors| lookup_selectors[s as usize];
+    let tag = selector(LookupSelectors::Tag) * a.square();
+
+    let re = lookup_zs[0];
+    let z = lookup_zs[1];
+    let next_re = next_lookup_zs[0];
+    let next_z = next_lookup_zs[1];
+    let (looking_helpers, table_helpers) = lookup_zs[2..].split_at(num_looking_helpers);
+    debug_assert_eq!(table_helpers.len(), common_data.num_table_helpers());
+
+    let mut constraints = Vec::with_capacity(common_data.num_lookup_polys + 3);
+
+    // The running sum starts at 0, and must wrap around to 0.
+    constraints.push(l_0_x * z);
+    let table_row =
+        selector(LookupSelectors::TableTransition) + selector(LookupSelectors::TableLast);
+    constraints.push(
+        next_z - z - selector(LookupSelectors::Lookup) * looking_helpers.iter().copied().sum()
+            + table_row * table_helpers.iter().copied().sum(),
+    );
+
+    let looking_denominators = (0..num_lookup_slots)
+        .map(|i| {
+            let inp = local_wires[LookupGate::wire_ith_looking_inp(i)];
+            let out = local_wires[LookupGate::wire_ith_looking_out(i)];
+            alpha - (inp + a * out + tag)
+        })
+        .collect::<Vec<_>>();
+    for (&helper, denominators) in looking_helpers
+        .iter()
+        .zip(looking_denominators.chunks(max_degree))
+    {
+        let numerators = vec![T::ONE; denominators.len()];
+        constraints.push(eval_helper_constraint(helper, &numerators, denominators));
+    }
+
+    let table_denominators = (0..num_table_slots)
+        .map(|i| {
+            let inp = local_wires[LookupTableGate::wire_ith_looked_inp(i)];
+            let out = local_wires[LookupTableGate::wire_ith_looked_out(i)];
+            alpha - (inp + a * out + tag)
+        })
+        .collect::<Vec<_>>();
+    let multiplicities = (0..num_table_slots)
+        .map(|i| local_wires[LookupTableGate::wire_ith_multiplicity(i)])
+        .collect::<Vec<_>>();
+    for ((&helper, numerators), denominators) in table_helpers
+        .iter()
+        .zip(multiplicities.chunks(max_degree))
+        .zip(table_denominators.chunks(max_degree))
+    {
+        constraints.push(eval_helper_constraint(helper, numerators, denominators));
+    }
+
+    // The running evaluation goes through the table rows from last to first.
+    let row_eval = (0..num_table_slots).rev().fold(T::ZERO, |acc, i| {
+        let inp = local_wires[LookupTableGate::wire_ith_looked_inp(i)];
+        let out = local_wires[LookupTableGate::wire_ith_looked_out(i)];
+        acc * delta + inp + b * out
+    });
+    let delta_pow = delta.exp_u64(num_table_slots as u64);
+    constraints
+        .push(selector(LookupSelectors::TableTransition) * (re - row_eval - delta_pow * next_re));
+    constraints.push(selector(LookupSelectors::TableLast) * (re - row_eval));
+    constraints.push(selector(LookupSelectors::TableFirst) * (re - lut_eval));
+
+    constraints
+}
+
+/// Circuit version of `eval_helper_constraint`. A `None` numerator stands for 1.
+fn eval_helper_constraint_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    helper: ExtensionTarget<D>,
+    numerators: Option<&[ExtensionTarget<D>]>,
+    denominators: &[ExtensionTarget<D>],
+) -> ExtensionTarget<D> {
+    let mut product = builder.one_extension();
+    let mut sum = builder.zero_extension();
+    for (i, &d) in denominators.iter().enumerate() {
+        let term = match numerators {
+            Some(numerators) => builder.mul_extension(numerators[i], product),
+            None => product,
+        };
+        sum = builder.mul_add_extension(sum, d, term);
+        product = builder.mul_extension(product, d);
+    }
+    builder.mul_sub_extension(helper, product, sum)
+}
+
+/// Circuit version of `check_lookup_constraints`.
+fn check_lookup_constraints_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    common_data: &CommonCircuitData<F, D>,
+    vars: EvaluationTargets<D>,
+    lookup_zs: &[ExtensionTarget<D>],
+    next_lookup_zs: &[ExtensionTarget<D>],
+    challenges: [Target; NUM_LOOKUP_CHALLENGES],
+    l_0_x: ExtensionTarget<D>,
+) -> Vec<ExtensionTarget<D>> {
+    let max_degree = common_data.quotient_degree_factor;
+    let num_lookup_slots = LookupGate::num_slots(&common_data.config);
+    let num_table_slots = LookupTableGate::num_slots(&common_data.config);
+    let num_looking_helpers = common_data.num_looking_helpers();
+
+    let [a, alpha, b, delta] = challenges;
+    let lut_eval = eval_luts_circuit(builder, &common_data.luts, num_table_slots, b, delta);
+    let lut_eval = builder.convert_to_ext(lut_eval);
+    let a_squared = builder.square(a);
+    let a_squared = builder.convert_to_ext(a_squared);
+    let delta_pow = builder.exp_u64(delta, num_table_slots as u64);
+    let delta_pow = builder.convert_to_ext(delta_pow);
+    let a = builder.convert_to_ext(a);
+    let alpha = builder.convert_to_ext(alpha);
+    let b = builder.convert_to_ext(b);
+    let delta = builder.convert_to_ext(delta);
+
+    let lookup_selectors = &vars.local_constants[common_data.lookup_selectors_range()];
+    let selector = |s: LookupSelectors| lookup_selectors[s as usize];
+    let tag = builder.mul_extension(selector(LookupSelectors::Tag), a_squared);
+    let local_wires = vars.local_wires;
+
+    let re = lookup_zs[0];
+    let z = lookup_zs[1];
+    let next_re = next_lookup_zs[0];
+    let next_z = next_lookup_zs[1];
+    let (looking_helpers, table_helpers) = lookup_zs[2..].split_at(num_looking_helpers);
+    debug_assert_eq!(table_helpers.len(), common_data.num_table_helpers());
+
+    let mut constraints = Vec::with_capacity(common_data.num_lookup_polys + 3);
+
+    constraints.push(builder.mul_extension(l_0_x, z));
+    let table_row = builder.add_extension(
+        selector(LookupSelectors::TableTransition),
+        selector(LookupSelectors::TableLast),
+    );
+    let looking_sum = builder.add_many_extension(looking_helpers);
+    let table_sum = builder.add_many_extension(table_helpers);
+    let z_diff = builder.sub_extension(next_z, z);
+    let z_diff = builder.arithmetic_extension(
+        F::NEG_ONE,
+        F::ONE,
+        selector(LookupSelectors::Lookup),
+        looking_sum,
+        z_diff,
+    );
+    constraints.push(builder.mul_add_extension(table_row, table_sum, z_diff));
+
+    let denominator = |builder: &mut CircuitBuilder<F, D>, inp, out| {
+        let combo = builder.mul_add_extension(a, out, inp);
+        let combo = builder.add_extension(combo, tag);
+        builder.sub_extension(alpha, combo)
+    };
+
+    let looking_denominators = (0..num_lookup_slots)
+        .map(|i| {
+            let inp = local_wires[LookupGate::wire_ith_looking_inp(i)];
+            let out = local_wires[LookupGate::wire_ith_looking_out(i)];
+            denominator(builder, inp, out)
+        })
+        .collect::<Vec<_>>();
+    for (&helper, denominators) in looking_helpers
+        .iter()
+        .zip(looking_denominators.chunks(max_degree))
+    {
+        constraints.push(eval_helper_constraint_circuit(
+            builder,
+            helper,
+            None,
+            denominators,
+        ));
+    }
+
+    let table_denominators = (0..num_table_slots)
+        .map(|i| {
+            let inp = local_wires[LookupTableGate::wire_ith_looked_inp(i)];
+            let out = local_wires[LookupTableGate::wire_ith_looked_out(i)];
+            denominator(builder, inp, out)
+        })
+        .collect::<Vec<_>>();
+    let multiplicities = (0..num_table_slots)
+        .map(|i| local_wires[LookupTableGate::wire_ith_multiplicity(i)])
+        .collect::<Vec<_>>();
+    for ((&helper, numerators), denominators) in table_helpers
+        .iter()
+        .zip(multiplicities.chunks(max_degree))
+        .zip(table_denominators.chunks(max_degree))
+    {
+        constraints.push(eval_helper_constraint_circuit(
+            builder,
+            helper,
+            Some(numerators),
+            denominators,
+        ));
+    }
+
+    let mut row_eval = builder.zero_extension();
+    for i in (0..num_table_slots).rev() {
+        let inp = local_wires[LookupTableGate::wire_ith_looked_inp(i)];
+        let out = local_wires[LookupTableGate::wire_ith_looked_out(i)];
+        let combo = builder.mul_add_extension(b, out, inp);
+        row_eval = builder.mul_add_extension(row_eval, delta, combo);
+    }
+    let re_minus_row = builder.sub_extension(re, row_eval);
+    let transition =
+        builder.arithmetic_extension(F::NEG_ONE, F::ONE, delta_pow, next_re, re_minus_row);
+    constraints.push(builder.mul_extension(selector(LookupSelectors::TableTransition), transition));
+    constraints.push(builder.mul_extension(selector(LookupSelectors::TableLast), re_minus_row));
+    let re_minus_lut = builder.sub_extension(re, lut_eval);
+    constraints.push(builder.mul_extension(selector(LookupSelectors::TableFirst), re_minus_lut));
+
+    constraints
+}
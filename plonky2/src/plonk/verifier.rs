@@ -57,6 +57,8 @@ pub(crate) fn verify_with_challenges<
     let next_zs = &proof.openings.plonk_zs_next;
     let s_sigmas = &proof.openings.plonk_sigmas;
     let partial_products = &proof.openings.partial_products;
+    let lookup_zs = &proof.openings.lookup_zs;
+    let next_lookup_zs = &proof.openings.lookup_zs_next;
 
     // Evaluate the vanishing polynomial at our challenge point, zeta.
     let vanishing_polys_zeta = eval_vanishing_poly::<F, D>(
@@ -67,8 +69,11 @@ pub(crate) fn verify_with_challenges<
         next_zs,
         partial_products,
         s_sigmas,
+        lookup_zs,
+        next_lookup_zs,
         &challenges.plonk_betas,
         &challenges.plonk_gammas,
+        &challenges.plonk_deltas,
         &challenges.plonk_alphas,
     );
 
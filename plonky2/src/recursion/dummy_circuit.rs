@@ -5,6 +5,7 @@ use hashbrown::HashMap;
 use plonky2_field::extension::Extendable;
 use plonky2_util::ceil_div_usize;
 
+use crate::gates::lookup_table::LookupTableGate;
 use crate::gates::noop::NoopGate;
 use crate::hash::hash_types::RichField;
 use crate::iop::generator::{GeneratedValues, SimpleGenerator};
@@ -16,6 +17,7 @@ use crate::plonk::circuit_data::{
 };
 use crate::plonk::config::{AlgebraicHasher, GenericConfig};
 use crate::plonk::proof::{ProofWithPublicInputs, ProofWithPublicInputsTarget};
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
 
 /// Creates a dummy proof which is suitable for use as a base proof in a cyclic recursion tree.
 /// Such a base proof will not actually be verified, so most of its data is arbitrary. However, its
@@ -83,11 +85,21 @@ pub(crate) fn dummy_circuit<
     );
 
     // Number of `NoopGate`s to add to get a circuit of size `degree` in the end.
-    // Need to account for public input hashing, a `PublicInputGate` and a `ConstantGate`.
+    // Need to account for public input hashing, a `PublicInputGate`, a `ConstantGate` and the
+    // rows of the lookup tables.
     let degree = common_data.degree();
-    let num_noop_gate = degree - ceil_div_usize(common_data.num_public_inputs, 8) - 2;
+    let num_table_rows = common_data
+        .luts
+        .iter()
+        .map(|lut| LookupTableGate::num_rows(lut.len(), &config))
+        .sum::<usize>();
+    let num_noop_gate =
+        degree - ceil_div_usize(common_data.num_public_inputs, 8) - 2 - num_table_rows;
 
     let mut builder = CircuitBuilder::<F, D>::new(config);
+    for lut in &common_data.luts {
+        builder.add_lookup_table(lut.clone());
+    }
     for _ in 0..num_noop_gate {
         builder.add_gate(NoopGate, vec![]);
     }
@@ -117,41 +129,50 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         let dummy_verifier_data_target =
             self.add_virtual_verifier_data(self.config.fri_config.cap_height);
 
+        let mut dummy_values = GeneratedValues::empty();
+        dummy_values.set_proof_with_pis_target(&dummy_proof_with_pis_target, &dummy_proof_with_pis);
+        dummy_values
+            .set_verifier_data_target(&dummy_verifier_data_target, &dummy_circuit.verifier_only);
         self.add_simple_generator(DummyProofGenerator {
-            proof_with_pis_target: dummy_proof_with_pis_target.clone(),
-            proof_with_pis: dummy_proof_with_pis,
-            verifier_data_target: dummy_verifier_data_target.clone(),
-            verifier_data: dummy_circuit.verifier_only,
+            target_values: dummy_values.target_values,
         });
 
         Ok((dummy_proof_with_pis_target, dummy_verifier_data_target))
     }
 }
 
+/// Sets the targets of a dummy proof and its verifier data. The values are computed when the
+/// generator is created, so that it doesn't need to hold the (circuit-specific) proof itself.
 #[derive(Debug)]
-pub(crate) struct DummyProofGenerator<F, C, const D: usize>
-where
-    F: RichField + Extendable<D>,
-    C: GenericConfig<D, F = F>,
-{
-    pub(crate) proof_with_pis_target: ProofWithPublicInputsTarget<D>,
-    pub(crate) proof_with_pis: ProofWithPublicInputs<F, C, D>,
-    pub(crate) verifier_data_target: VerifierCircuitTarget,
-    pub(crate) verifier_data: VerifierOnlyCircuitData<C, D>,
+pub struct DummyProofGenerator<F: RichField> {
+    pub(crate) target_values: Vec<(Target, F)>,
 }
 
-impl<F, C, const D: usize> SimpleGenerator<F> for DummyProofGenerator<F, C, D>
-where
-    F: RichField + Extendable<D>,
-    C: GenericConfig<D, F = F> + 'static,
-    C::Hasher: AlgebraicHasher<F>,
-{
+impl<F: RichField> SimpleGenerator<F> for DummyProofGenerator<F> {
     fn dependencies(&self) -> Vec<Target> {
         vec![]
     }
 
     fn run_once(&self, _witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
-        out_buffer.set_proof_with_pis_target(&self.proof_with_pis_target, &self.proof_with_pis);
-        out_buffer.set_verifier_data_target(&self.verifier_data_target, &self.verifier_data);
+        for &(target, value) in &self.target_values {
+            out_buffer.set_target(target, value);
+        }
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.target_values.len())?;
+        for &(target, value) in &self.target_values {
+            dst.write_target(target)?;
+            dst.write_field(value)?;
+        }
+        Ok(())
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let len = src.read_usize()?;
+        let target_values = (0..len)
+            .map(|_| Ok((src.read_target()?, src.read_field()?)))
+            .collect::<IoResult<Vec<_>>>()?;
+        Ok(Self { target_values })
     }
 }
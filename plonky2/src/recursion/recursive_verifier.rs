@@ -68,6 +68,8 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         let next_zs = &proof.openings.plonk_zs_next;
         let s_sigmas = &proof.openings.plonk_sigmas;
         let partial_products = &proof.openings.partial_products;
+        let lookup_zs = &proof.openings.lookup_zs;
+        let next_lookup_zs = &proof.openings.lookup_zs_next;
 
         let zeta_pow_deg =
             self.exp_power_of_2_extension(challenges.plonk_zeta, inner_common_data.degree_bits());
@@ -84,8 +86,11 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
                 next_zs,
                 partial_products,
                 s_sigmas,
+                lookup_zs,
+                next_lookup_zs,
                 &challenges.plonk_betas,
                 &challenges.plonk_gammas,
+                &challenges.plonk_deltas,
                 &challenges.plonk_alphas,
             )
         );
@@ -164,6 +169,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         let config = &common_data.config;
         let num_challenges = config.num_challenges;
         let total_partial_products = num_challenges * common_data.num_partial_products;
+        let total_lookup_polys = num_challenges * common_data.num_lookup_polys;
         OpeningSetTarget {
             constants: self.add_virtual_extension_targets(common_data.num_constants),
             plonk_sigmas: self.add_virtual_extension_targets(config.num_routed_wires),
@@ -172,6 +178,8 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             plonk_zs_next: self.add_virtual_extension_targets(num_challenges),
             partial_products: self.add_virtual_extension_targets(total_partial_products),
             quotient_polys: self.add_virtual_extension_targets(common_data.num_quotient_polys()),
+            lookup_zs: self.add_virtual_extension_targets(total_lookup_polys),
+            lookup_zs_next: self.add_virtual_extension_targets(total_lookup_polys),
         }
     }
 }
@@ -185,11 +193,19 @@ mod tests {
     use crate::fri::reduction_strategies::FriReductionStrategy;
     use crate::fri::FriConfig;
     use crate::gates::noop::NoopGate;
+    use crate::hash::poseidon2::Poseidon2;
+    use crate::hash::rescue_prime::RescuePrime;
     use crate::iop::witness::{PartialWitness, WitnessWrite};
-    use crate::plonk::circuit_data::{CircuitConfig, VerifierOnlyCircuitData};
-    use crate::plonk::config::{GenericConfig, KeccakGoldilocksConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::circuit_data::{CircuitConfig, CircuitData, VerifierOnlyCircuitData};
+    use crate::plonk::config::{
+        GenericConfig, KeccakGoldilocksConfig, Poseidon2GoldilocksConfig, PoseidonGoldilocksConfig,
+        RescuePrimeGoldilocksConfig,
+    };
     use crate::plonk::proof::{CompressedProofWithPublicInputs, ProofWithPublicInputs};
     use crate::plonk::prover::prove;
+    use crate::util::serialization::{
+        DefaultGateSerializer, DefaultGeneratorSerializer, FullGateSerializer,
+    };
     use crate::util::timing::TimingTree;
 
     #[test]
@@ -236,6 +252,44 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_circuit_data_serialization() -> Result<()> {
+        init_logger();
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let config = CircuitConfig::standard_recursion_config();
+
+        let (inner_proof, inner_vd, inner_cd) = dummy_proof::<F, C, D>(&config, 4_000)?;
+
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let pt = builder.add_virtual_proof_with_pis(&inner_cd);
+        let inner_data = builder.add_virtual_verifier_data(inner_cd.config.fri_config.cap_height);
+        builder.verify_proof::<C>(&pt, &inner_data, &inner_cd);
+        let data = builder.build::<C>();
+
+        let gate_serializer = DefaultGateSerializer;
+        let generator_serializer = DefaultGeneratorSerializer;
+        let data_bytes = data
+            .to_bytes(&gate_serializer, &generator_serializer)
+            .map_err(anyhow::Error::msg)?;
+        info!("Circuit data length: {} bytes", data_bytes.len());
+        let data_from_bytes = CircuitData::<F, C, D>::from_bytes(
+            &data_bytes,
+            &gate_serializer,
+            &generator_serializer,
+        )
+        .map_err(anyhow::Error::msg)?;
+        assert_eq!(data.common, data_from_bytes.common);
+        assert_eq!(data.verifier_only, data_from_bytes.verifier_only);
+
+        let mut pw = PartialWitness::new();
+        pw.set_proof_with_pis_target(&pt, &inner_proof);
+        pw.set_verifier_data_target(&inner_data, &inner_vd);
+        let proof = data_from_bytes.prove(pw)?;
+        data.verify(proof)
+    }
+
     /// Creates a chain of recursive proofs where the last proof is made as small as reasonably
     /// possible, using a high rate, high PoW bits, etc.
     #[test]
@@ -314,6 +368,38 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_recursive_verifier_poseidon2() -> Result<()> {
+        init_logger();
+        const D: usize = 2;
+        type C = Poseidon2GoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let (proof, vd, cd) = dummy_proof::<F, C, D>(&config, 4_000)?;
+        let (proof, vd, cd) =
+            recursive_proof::<F, C, C, D>(proof, vd, cd, &config, None, false, false)?;
+        test_serialization(&proof, &vd, &cd)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_recursive_verifier_rescue_prime() -> Result<()> {
+        init_logger();
+        const D: usize = 2;
+        type C = RescuePrimeGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let (proof, vd, cd) = dummy_proof::<F, C, D>(&config, 4_000)?;
+        let (proof, vd, cd) =
+            recursive_proof::<F, C, C, D>(proof, vd, cd, &config, None, false, false)?;
+        test_serialization(&proof, &vd, &cd)?;
+
+        Ok(())
+    }
+
     type Proof<F, C, const D: usize> = (
         ProofWithPublicInputs<F, C, D>,
         VerifierOnlyCircuitData<C, D>,
@@ -398,7 +484,7 @@ mod tests {
 
     /// Test serialization and print some size info.
     fn test_serialization<
-        F: RichField + Extendable<D>,
+        F: RichField + Extendable<D> + Poseidon2 + RescuePrime,
         C: GenericConfig<D, F = F>,
         const D: usize,
     >(
@@ -428,6 +514,23 @@ mod tests {
             CompressedProofWithPublicInputs::from_bytes(compressed_proof_bytes, cd)?;
         assert_eq!(compressed_proof, compressed_proof_from_bytes);
 
+        let gate_serializer = FullGateSerializer;
+        let common_data_bytes = cd.to_bytes(&gate_serializer).map_err(anyhow::Error::msg)?;
+        info!(
+            "Common circuit data length: {} bytes",
+            common_data_bytes.len()
+        );
+        let common_data_from_bytes =
+            CommonCircuitData::<F, D>::from_bytes(&common_data_bytes, &gate_serializer)
+                .map_err(anyhow::Error::msg)?;
+        assert_eq!(cd, &common_data_from_bytes);
+
+        let verifier_data_bytes = vd.to_bytes().map_err(anyhow::Error::msg)?;
+        let verifier_data_from_bytes =
+            VerifierOnlyCircuitData::<C, D>::from_bytes(&verifier_data_bytes)
+                .map_err(anyhow::Error::msg)?;
+        assert_eq!(vd, &verifier_data_from_bytes);
+
         Ok(())
     }
 
@@ -0,0 +1,182 @@
+//! Fixed-length buffers stored in memory-mapped files, so that large prover data can be paged out
+//! to disk instead of being capped by the available RAM. Only Unix platforms are supported.
+
+use core::fmt::{self, Debug, Formatter};
+use core::mem::{size_of, ManuallyDrop, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, NonNull};
+use core::slice;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::fs::{self, OpenOptions};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::{io, process};
+
+static NEXT_FILE_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A fixed-length buffer of `T`s in a shared memory mapping of a file. The OS loads pages on
+/// access and writes dirty pages back to the file under memory pressure.
+///
+/// The file is unlinked as soon as it is mapped, so it never outlives the process, and its space
+/// is reclaimed when the buffer is dropped.
+pub struct DiskVec<T> {
+    ptr: NonNull<T>,
+    len: usize,
+    /// The directory holding the file, where copies and related buffers are created.
+    dir: PathBuf,
+}
+
+// SAFETY: `DiskVec` owns its mapping exclusively, like a `Vec` owns its allocation.
+unsafe impl<T: Send> Send for DiskVec<T> {}
+unsafe impl<T: Sync> Sync for DiskVec<T> {}
+
+impl<T: Copy> DiskVec<T> {
+    /// Creates a buffer of `len` uninitialized elements, backed by a new file in `dir`.
+    pub fn new_uninit(dir: &Path, len: usize) -> io::Result<DiskVec<MaybeUninit<T>>> {
+        assert!(size_of::<T>() > 0, "Zero-sized types are not supported.");
+        let num_bytes = len
+            .checked_mul(size_of::<T>())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "buffer too large"))?;
+        if num_bytes == 0 {
+            return Ok(DiskVec {
+                ptr: NonNull::dangling(),
+                len,
+                dir: dir.to_path_buf(),
+            });
+        }
+
+        let path = dir.join(format!(
+            "plonky2-{}-{}.bin",
+            process::id(),
+            NEXT_FILE_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        let mapped = file.set_len(num_bytes as u64).and_then(|()| {
+            // SAFETY: We map a fresh file of `num_bytes` bytes, which nothing else accesses.
+            let ptr = unsafe {
+                libc::mmap(
+                    ptr::null_mut(),
+                    num_bytes,
+                    libc::PROT_READ | libc::PROT_WRITE,
+                    libc::MAP_SHARED,
+                    file.as_raw_fd(),
+                    0,
+                )
+            };
+            if ptr == libc::MAP_FAILED {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(ptr)
+            }
+        });
+        // The mapping keeps the file's data alive after it is unlinked and closed.
+        fs::remove_file(&path)?;
+        let ptr = mapped?;
+
+        // Mappings are page-aligned, hence aligned for `T`.
+        debug_assert_eq!(ptr as usize % core::mem::align_of::<T>(), 0);
+        Ok(DiskVec {
+            ptr: NonNull::new(ptr.cast()).unwrap(),
+            len,
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    /// Copies `values` into a new buffer backed by a file in `dir`.
+    pub fn from_slice(dir: &Path, values: &[T]) -> io::Result<Self> {
+        let mut buffer = Self::new_uninit(dir, values.len())?;
+        for (dst, &src) in buffer.iter_mut().zip(values) {
+            dst.write(src);
+        }
+        // SAFETY: Every element was written above.
+        Ok(unsafe { buffer.assume_init() })
+    }
+
+    /// Copies this buffer into a new file in the same directory. Unlike `Vec`, this doesn't
+    /// implement `Clone`, since creating and mapping the new file can fail.
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Self::from_slice(&self.dir, self)
+    }
+
+    /// The directory holding the file backing this buffer.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl<T> DiskVec<MaybeUninit<T>> {
+    /// Converts to a buffer of initialized elements.
+    ///
+    /// # Safety
+    /// Every element must have been initialized.
+    pub unsafe fn assume_init(self) -> DiskVec<T> {
+        let this = ManuallyDrop::new(self);
+        DiskVec {
+            ptr: this.ptr.cast(),
+            len: this.len,
+            dir: ptr::read(&this.dir),
+        }
+    }
+}
+
+impl<T> Deref for DiskVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // SAFETY: `ptr` points to `len` elements which live as long as `self`.
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> DerefMut for DiskVec<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        // SAFETY: `ptr` points to `len` elements which live as long as `self`, which we borrow
+        // mutably.
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> Drop for DiskVec<T> {
+    fn drop(&mut self) {
+        let num_bytes = self.len * size_of::<T>();
+        if num_bytes > 0 {
+            // SAFETY: This is the mapping created in `new_uninit`, which is no longer borrowed.
+            unsafe {
+                libc::munmap(self.ptr.as_ptr().cast(), num_bytes);
+            }
+        }
+    }
+}
+
+impl<T> Debug for DiskVec<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DiskVec")
+            .field("len", &self.len)
+            .field("dir", &self.dir)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disk_vec() {
+        let dir = std::env::temp_dir();
+        let mut buffer = DiskVec::<u64>::new_uninit(&dir, 1 << 16).unwrap();
+        for (i, x) in buffer.iter_mut().enumerate() {
+            x.write(i as u64);
+        }
+        let buffer = unsafe { buffer.assume_init() };
+        let clone = buffer.try_clone().unwrap();
+        drop(buffer);
+        assert!(clone.iter().enumerate().all(|(i, &x)| x == i as u64));
+
+        assert!(DiskVec::<u64>::from_slice(&dir, &[]).unwrap().is_empty());
+    }
+}
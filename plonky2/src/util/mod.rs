@@ -10,6 +10,10 @@ use crate::field::types::Field;
 pub(crate) mod context_tree;
 pub(crate) mod partial_products;
 
+#[cfg(feature = "disk_storage")]
+pub mod disk_vec;
+#[cfg(all(feature = "disk_storage", not(unix)))]
+compile_error!("The `disk_storage` feature relies on `mmap`, so it is only supported on Unix.");
 pub mod reducing;
 pub mod serialization;
 pub mod strided_view;
@@ -0,0 +1,247 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::field::extension::Extendable;
+use crate::gates::arithmetic_base::ArithmeticGate;
+use crate::gates::arithmetic_extension::ArithmeticExtensionGate;
+use crate::gates::base_sum::BaseSumGate;
+use crate::gates::constant::ConstantGate;
+use crate::gates::coset_interpolation::CosetInterpolationGate;
+use crate::gates::exponentiation::ExponentiationGate;
+use crate::gates::gate::{Gate, GateRef};
+use crate::gates::keccak_chi::KeccakChiGate;
+use crate::gates::keccak_column_parity::KeccakColumnParityGate;
+use crate::gates::keccak_theta::KeccakThetaGate;
+use crate::gates::lookup::LookupGate;
+use crate::gates::lookup_table::LookupTableGate;
+use crate::gates::multiplication_extension::MulExtensionGate;
+use crate::gates::noop::NoopGate;
+use crate::gates::poseidon::PoseidonGate;
+use crate::gates::poseidon2::Poseidon2Gate;
+use crate::gates::poseidon_mds::PoseidonMdsGate;
+use crate::gates::public_input::PublicInputGate;
+use crate::gates::random_access::RandomAccessGate;
+use crate::gates::reducing::ReducingGate;
+use crate::gates::reducing_extension::ReducingExtensionGate;
+use crate::gates::rescue_prime::RescuePrimeGate;
+use crate::hash::hash_types::RichField;
+use crate::hash::poseidon2::Poseidon2;
+use crate::hash::rescue_prime::RescuePrime;
+use crate::iop::ext_target::ExtensionTarget;
+use crate::iop::generator::WitnessGeneratorRef;
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::vars::{EvaluationTargets, EvaluationVars};
+use crate::util::serialization::{Buffer, IoError, IoResult};
+
+/// A registry of the gates that can appear in serialized circuit data. Each gate type is associated
+/// with a tag, which is written before the gate's own parameters.
+pub trait GateSerializer<F: RichField + Extendable<D>, const D: usize> {
+    fn read_gate(&self, buf: &mut Buffer) -> IoResult<GateRef<F, D>>;
+    fn write_gate(&self, buf: &mut Vec<u8>, gate: &GateRef<F, D>) -> IoResult<()>;
+}
+
+#[macro_export]
+macro_rules! read_gate_impl {
+    ($buf:expr, $tag:expr, $($gate_types:ty),+) => {{
+        let tag = $tag;
+        let buf = $buf;
+        let mut i = 0u32..;
+        $(if tag == i.next().unwrap() {
+            let gate = <$gate_types as $crate::gates::gate::Gate<F, D>>::deserialize(buf)?;
+            Ok($crate::gates::gate::GateRef::<F, D>::new(gate))
+        } else)*
+        {
+            Err($crate::util::serialization::IoError)
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! get_gate_tag_impl {
+    ($gate:expr, $($gate_types:ty),+) => {{
+        let gate_any = $gate.0.as_any();
+        let mut i = 0u32..;
+        $(if let (tag, true) = (i.next().unwrap(), gate_any.is::<$gate_types>()) {
+            Ok(tag)
+        } else)*
+        {
+            Err($crate::util::serialization::IoError)
+        }
+    }};
+}
+
+/// Implements the methods of [`GateSerializer`] for the given list of gate types. It is meant to be
+/// invoked inside an `impl<F: RichField + Extendable<D>, const D: usize> GateSerializer<F, D>`
+/// block. The position of each gate in the list is used as its tag, so gates should only ever be
+/// appended to an existing list, to keep previously serialized data readable.
+#[macro_export]
+macro_rules! impl_gate_serializer {
+    ($($gate_types:ty),+ $(,)?) => {
+        fn read_gate(
+            &self,
+            buf: &mut $crate::util::serialization::Buffer,
+        ) -> $crate::util::serialization::IoResult<$crate::gates::gate::GateRef<F, D>> {
+            let tag = $crate::util::serialization::Read::read_u32(buf)?;
+            $crate::read_gate_impl!(buf, tag, $($gate_types),+)
+        }
+
+        fn write_gate(
+            &self,
+            buf: &mut Vec<u8>,
+            gate: &$crate::gates::gate::GateRef<F, D>,
+        ) -> $crate::util::serialization::IoResult<()> {
+            let tag = $crate::get_gate_tag_impl!(gate, $($gate_types),+)?;
+            $crate::util::serialization::Write::write_u32(buf, tag)?;
+            gate.0.serialize(buf)
+        }
+    };
+}
+
+/// Stands for a gate which a serializer doesn't support, such as `Poseidon2Gate` in
+/// [`DefaultGateSerializer`], so that the gates following it keep the tags they have in
+/// [`FullGateSerializer`]. It has no values, so reading its tag fails.
+#[derive(Debug)]
+pub(crate) enum ReservedGate {}
+
+impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for ReservedGate {
+    fn id(&self) -> String {
+        match *self {}
+    }
+
+    fn serialize(&self, _dst: &mut Vec<u8>) -> IoResult<()> {
+        match *self {}
+    }
+
+    fn deserialize(_src: &mut Buffer) -> IoResult<Self> {
+        Err(IoError)
+    }
+
+    fn eval_unfiltered(&self, _vars: EvaluationVars<F, D>) -> Vec<F::Extension> {
+        match *self {}
+    }
+
+    fn eval_unfiltered_circuit(
+        &self,
+        _builder: &mut CircuitBuilder<F, D>,
+        _vars: EvaluationTargets<D>,
+    ) -> Vec<ExtensionTarget<D>> {
+        match *self {}
+    }
+
+    fn generators(&self, _row: usize, _local_constants: &[F]) -> Vec<WitnessGeneratorRef<F>> {
+        match *self {}
+    }
+
+    fn num_wires(&self) -> usize {
+        match *self {}
+    }
+
+    fn num_constants(&self) -> usize {
+        match *self {}
+    }
+
+    fn degree(&self) -> usize {
+        match *self {}
+    }
+
+    fn num_constraints(&self) -> usize {
+        match *self {}
+    }
+}
+
+/// Implements [`GateSerializer`] for `$serializer`, with the gates of [`DefaultGateSerializer`],
+/// `$poseidon2_gate` in the slot of `Poseidon2Gate`, and the given gates at the end. Each gate has
+/// the same tag in all serializers, so that [`FullGateSerializer`] can read anything
+/// [`DefaultGateSerializer`] writes.
+macro_rules! impl_default_gate_serializer {
+    (
+        $serializer:ty,
+        [$($bounds:tt)*],
+        $poseidon2_gate:ty,
+        $($extra_gate_types:ty),* $(,)?
+    ) => {
+        impl<F: RichField + Extendable<D> $($bounds)*, const D: usize> GateSerializer<F, D>
+            for $serializer
+        {
+            impl_gate_serializer! {
+                ArithmeticGate,
+                ArithmeticExtensionGate<D>,
+                BaseSumGate<2>,
+                ConstantGate,
+                CosetInterpolationGate<F, D>,
+                ExponentiationGate<F, D>,
+                MulExtensionGate<D>,
+                NoopGate,
+                PoseidonMdsGate<F, D>,
+                PoseidonGate<F, D>,
+                PublicInputGate,
+                RandomAccessGate<F, D>,
+                ReducingExtensionGate<D>,
+                ReducingGate<D>,
+                LookupGate,
+                LookupTableGate,
+                $poseidon2_gate,
+                KeccakColumnParityGate,
+                KeccakThetaGate,
+                KeccakChiGate,
+                $($extra_gate_types,)*
+            }
+        }
+    };
+}
+
+/// A gate serializer supporting all the gates defined in this crate which work over any
+/// [`RichField`], i.e. all of them except the Poseidon2 and Rescue-Prime gates.
+#[derive(Debug, Default)]
+pub struct DefaultGateSerializer;
+
+impl_default_gate_serializer!(DefaultGateSerializer, [], ReservedGate,);
+
+/// A gate serializer supporting all the gates defined in this crate, for fields implementing
+/// Poseidon2 and Rescue-Prime, like Goldilocks.
+#[derive(Debug, Default)]
+pub struct FullGateSerializer;
+
+impl_default_gate_serializer!(
+    FullGateSerializer,
+    [+ Poseidon2 + RescuePrime],
+    Poseidon2Gate<F, D>,
+    RescuePrimeGate<F, D>,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::goldilocks_field::GoldilocksField;
+    use crate::plonk::circuit_data::CircuitConfig;
+
+    const D: usize = 2;
+    type F = GoldilocksField;
+
+    #[test]
+    fn default_and_full_serializers_share_tags() {
+        let config = CircuitConfig::standard_recursion_config();
+        let gates = [
+            GateRef::<F, D>::new(LookupTableGate::new_from_config(&config)),
+            GateRef::new(KeccakColumnParityGate::new_from_config(&config)),
+            GateRef::new(KeccakThetaGate::new_from_config(&config)),
+            GateRef::new(KeccakChiGate::new_from_config(&config)),
+        ];
+        for gate in gates {
+            let mut default_bytes = Vec::new();
+            DefaultGateSerializer
+                .write_gate(&mut default_bytes, &gate)
+                .unwrap();
+            let mut full_bytes = Vec::new();
+            FullGateSerializer
+                .write_gate(&mut full_bytes, &gate)
+                .unwrap();
+            assert_eq!(default_bytes, full_bytes);
+
+            let read = FullGateSerializer
+                .read_gate(&mut Buffer::new(&default_bytes))
+                .unwrap();
+            assert_eq!(read, gate);
+        }
+    }
+}
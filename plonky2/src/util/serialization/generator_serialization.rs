@@ -0,0 +1,209 @@
+use alloc::vec::Vec;
+
+use crate::field::extension::Extendable;
+use crate::gadgets::arithmetic::EqualityGenerator;
+use crate::gadgets::arithmetic_extension::QuotientGeneratorExtension;
+use crate::gadgets::range_check::LowHighGenerator;
+use crate::gadgets::split_base::BaseSumGenerator;
+use crate::gadgets::split_join::{SplitGenerator, WireSplitGenerator};
+use crate::gates::arithmetic_base::ArithmeticBaseGenerator;
+use crate::gates::arithmetic_extension::ArithmeticExtensionGenerator;
+use crate::gates::base_sum::BaseSplitGenerator;
+use crate::gates::coset_interpolation::InterpolationGenerator;
+use crate::gates::exponentiation::ExponentiationGenerator;
+use crate::gates::keccak_chi::KeccakChiGenerator;
+use crate::gates::keccak_column_parity::KeccakColumnParityGenerator;
+use crate::gates::keccak_theta::KeccakThetaGenerator;
+use crate::gates::lookup::LookupGenerator;
+use crate::gates::lookup_table::LookupTableGenerator;
+use crate::gates::multiplication_extension::MulExtensionGenerator;
+use crate::gates::poseidon::PoseidonGenerator;
+use crate::gates::poseidon2::Poseidon2Generator;
+use crate::gates::poseidon_mds::PoseidonMdsGenerator;
+use crate::gates::random_access::RandomAccessGenerator;
+use crate::gates::reducing::ReducingGenerator;
+use crate::gates::reducing_extension::ReducingGenerator as ReducingExtensionGenerator;
+use crate::gates::rescue_prime::RescuePrimeGenerator;
+use crate::hash::hash_types::RichField;
+use crate::hash::poseidon2::Poseidon2;
+use crate::hash::rescue_prime::RescuePrime;
+use crate::iop::generator::{
+    ConstantGenerator, CopyGenerator, GeneratedValues, NonzeroTestGenerator, RandomValueGenerator,
+    SimpleGenerator, WitnessGeneratorRef,
+};
+use crate::iop::target::Target;
+use crate::iop::witness::PartitionWitness;
+use crate::recursion::dummy_circuit::DummyProofGenerator;
+use crate::util::serialization::{Buffer, IoError, IoResult};
+
+/// A registry of the witness generators that can appear in serialized prover data. Each generator
+/// type is associated with a tag, which is written before the generator's own data.
+pub trait WitnessGeneratorSerializer<F: RichField + Extendable<D>, const D: usize> {
+    fn read_generator(&self, buf: &mut Buffer) -> IoResult<WitnessGeneratorRef<F>>;
+    fn write_generator(
+        &self,
+        buf: &mut Vec<u8>,
+        generator: &WitnessGeneratorRef<F>,
+    ) -> IoResult<()>;
+}
+
+#[macro_export]
+macro_rules! read_generator_impl {
+    ($buf:expr, $tag:expr, $($generator_types:ty),+) => {{
+        let tag = $tag;
+        let buf = $buf;
+        let mut i = 0u32..;
+        $(if tag == i.next().unwrap() {
+            let generator =
+                <$generator_types as $crate::iop::generator::SimpleGenerator<F>>::deserialize(buf)?;
+            Ok($crate::iop::generator::WitnessGeneratorRef::<F>::new(
+                $crate::iop::generator::SimpleGenerator::<F>::adapter(generator),
+            ))
+        } else)*
+        {
+            Err($crate::util::serialization::IoError)
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! get_generator_tag_impl {
+    ($generator:expr, $($generator_types:ty),+) => {{
+        let generator_any = $generator.0.as_any();
+        let mut i = 0u32..;
+        $(if let (tag, true) = (
+            i.next().unwrap(),
+            generator_any.is::<$crate::iop::generator::SimpleGeneratorAdapter<F, $generator_types>>(),
+        ) {
+            Ok(tag)
+        } else)*
+        {
+            Err($crate::util::serialization::IoError)
+        }
+    }};
+}
+
+/// Implements the methods of [`WitnessGeneratorSerializer`] for the given list of simple generator
+/// types. It is meant to be invoked inside an
+/// `impl<F: RichField + Extendable<D>, const D: usize> WitnessGeneratorSerializer<F, D>` block. As
+/// with gates, the position of each generator in the list is used as its tag.
+///
+/// Only [`SimpleGenerator`](crate::iop::generator::SimpleGenerator)s are supported, since the
+/// macro matches the `SimpleGeneratorAdapter` wrapping each of them. A registry containing a type
+/// which implements [`WitnessGenerator`](crate::iop::generator::WitnessGenerator) directly must
+/// implement `read_generator` and `write_generator` by hand, e.g. by handling that type's tag and
+/// delegating the others to `read_generator_impl!` and `get_generator_tag_impl!`.
+#[macro_export]
+macro_rules! impl_generator_serializer {
+    ($($generator_types:ty),+ $(,)?) => {
+        fn read_generator(
+            &self,
+            buf: &mut $crate::util::serialization::Buffer,
+        ) -> $crate::util::serialization::IoResult<$crate::iop::generator::WitnessGeneratorRef<F>> {
+            let tag = $crate::util::serialization::Read::read_u32(buf)?;
+            $crate::read_generator_impl!(buf, tag, $($generator_types),+)
+        }
+
+        fn write_generator(
+            &self,
+            buf: &mut Vec<u8>,
+            generator: &$crate::iop::generator::WitnessGeneratorRef<F>,
+        ) -> $crate::util::serialization::IoResult<()> {
+            let tag = $crate::get_generator_tag_impl!(generator, $($generator_types),+)?;
+            $crate::util::serialization::Write::write_u32(buf, tag)?;
+            generator.0.serialize(buf)
+        }
+    };
+}
+
+/// Stands for a generator which a serializer doesn't support, such as `Poseidon2Generator` in
+/// [`DefaultGeneratorSerializer`], so that the generators following it keep the tags they have in
+/// [`FullGeneratorSerializer`]. It has no values, so reading its tag fails.
+#[derive(Debug)]
+pub(crate) enum ReservedGenerator {}
+
+impl<F: RichField> SimpleGenerator<F> for ReservedGenerator {
+    fn dependencies(&self) -> Vec<Target> {
+        match *self {}
+    }
+
+    fn run_once(&self, _witness: &PartitionWitness<F>, _out_buffer: &mut GeneratedValues<F>) {
+        match *self {}
+    }
+
+    fn serialize(&self, _dst: &mut Vec<u8>) -> IoResult<()> {
+        match *self {}
+    }
+
+    fn deserialize(_src: &mut Buffer) -> IoResult<Self> {
+        Err(IoError)
+    }
+}
+
+/// Implements [`WitnessGeneratorSerializer`] for `$serializer`, with the generators of
+/// [`DefaultGeneratorSerializer`], `$poseidon2_generator` in the slot of `Poseidon2Generator`, and
+/// the given generators at the end. Each generator has the same tag in all serializers, so that
+/// [`FullGeneratorSerializer`] can read anything [`DefaultGeneratorSerializer`] writes.
+macro_rules! impl_default_generator_serializer {
+    (
+        $serializer:ty,
+        [$($bounds:tt)*],
+        $poseidon2_generator:ty,
+        $($extra_generator_types:ty),* $(,)?
+    ) => {
+        impl<F: RichField + Extendable<D> $($bounds)*, const D: usize>
+            WitnessGeneratorSerializer<F, D> for $serializer
+        {
+            impl_generator_serializer! {
+                ArithmeticBaseGenerator<F, D>,
+                ArithmeticExtensionGenerator<F, D>,
+                BaseSplitGenerator<2>,
+                BaseSumGenerator<2>,
+                ConstantGenerator<F>,
+                CopyGenerator,
+                DummyProofGenerator<F>,
+                EqualityGenerator,
+                ExponentiationGenerator<F, D>,
+                InterpolationGenerator<F, D>,
+                LowHighGenerator,
+                MulExtensionGenerator<F, D>,
+                NonzeroTestGenerator,
+                PoseidonGenerator<F, D>,
+                PoseidonMdsGenerator<D>,
+                QuotientGeneratorExtension<D>,
+                RandomAccessGenerator<F, D>,
+                RandomValueGenerator,
+                ReducingGenerator<D>,
+                ReducingExtensionGenerator<D>,
+                SplitGenerator,
+                WireSplitGenerator,
+                LookupGenerator,
+                LookupTableGenerator,
+                $poseidon2_generator,
+                KeccakColumnParityGenerator,
+                KeccakThetaGenerator,
+                KeccakChiGenerator,
+                $($extra_generator_types,)*
+            }
+        }
+    };
+}
+
+/// A generator serializer supporting all the generators defined in this crate which work over any
+/// [`RichField`], i.e. all of them except those of the Poseidon2 and Rescue-Prime gates.
+#[derive(Debug, Default)]
+pub struct DefaultGeneratorSerializer;
+
+impl_default_generator_serializer!(DefaultGeneratorSerializer, [], ReservedGenerator,);
+
+/// A generator serializer supporting all the generators defined in this crate, for fields
+/// implementing Poseidon2 and Rescue-Prime, like Goldilocks.
+#[derive(Debug, Default)]
+pub struct FullGeneratorSerializer;
+
+impl_default_generator_serializer!(
+    FullGeneratorSerializer,
+    [+ Poseidon2 + RescuePrime],
+    Poseidon2Generator<F, D>,
+    RescuePrimeGenerator<F, D>,
+);
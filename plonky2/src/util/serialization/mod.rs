@@ -0,0 +1,1816 @@
+//! Binary serialization of proofs and circuit data, through the no_std [`Read`] and [`Write`]
+//! traits.
+//!
+//! Gates and witness generators are trait objects, so reading them back needs a registry mapping
+//! tags to concrete types: a [`GateSerializer`] and a [`WitnessGeneratorSerializer`]. The
+//! [`DefaultGateSerializer`] and [`DefaultGeneratorSerializer`] cover the types of this crate;
+//! crates with their own gates or generators list them in their own registries with the
+//! `impl_gate_serializer!` and `impl_generator_serializer!` macros.
+//!
+//! # Changes from earlier versions
+//!
+//! Code written against earlier versions of this module needs the following updates.
+//! - [`Buffer`] now borrows the bytes it reads from: `Buffer::new` takes a `&[u8]` instead of a
+//!   `Vec<u8>`, and `Buffer::bytes` returns that slice instead of consuming the buffer. It is also
+//!   available without the `std` feature.
+//! - [`Gate`](crate::gates::gate::Gate), [`WitnessGenerator`](crate::iop::generator::WitnessGenerator)
+//!   and [`SimpleGenerator`](crate::iop::generator::SimpleGenerator) have new required `serialize`
+//!   and `deserialize` methods. Types which are never serialized can return an [`IoError`] from
+//!   both.
+//! - [`Gate::generators`](crate::gates::gate::Gate::generators) returns
+//!   [`WitnessGeneratorRef`]s, built with `WitnessGeneratorRef::new`, instead of
+//!   `Box<dyn WitnessGenerator>`s.
+//! - The `to_bytes` and `from_bytes` methods of the circuit data types take the registries above,
+//!   and prefix their output with [`CIRCUIT_DATA_FORMAT_VERSION`].
+
+pub mod gate_serialization;
+pub mod generator_serialization;
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::fmt::{Debug, Display, Formatter};
+use core::mem::size_of;
+
+use hashbrown::HashMap;
+
+use crate::field::extension::{Extendable, FieldExtension};
+use crate::field::polynomial::PolynomialCoeffs;
+use crate::field::types::{Field64, PrimeField64};
+use crate::fri::oracle::PolynomialBatch;
+use crate::fri::proof::{
+    CompressedFriProof, CompressedFriQueryRounds, FriInitialTreeProof, FriProof, FriQueryRound,
+    FriQueryStep,
+};
+use crate::fri::reduction_strategies::FriReductionStrategy;
+use crate::fri::{FriConfig, FriParams};
+use crate::gates::gate::GateRef;
+use crate::gates::lookup_table::LookupTable;
+use crate::gates::selectors::SelectorsInfo;
+use crate::hash::hash_types::{HashOutTarget, RichField};
+use crate::hash::merkle_proofs::MerkleProof;
+use crate::hash::merkle_tree::{MerkleCap, MerkleTree};
+use crate::iop::ext_target::ExtensionTarget;
+use crate::iop::generator::WitnessGeneratorRef;
+use crate::iop::target::{BoolTarget, Target};
+use crate::iop::wire::Wire;
+use crate::plonk::circuit_data::{
+    CircuitConfig, CircuitData, CommonCircuitData, ProverCircuitData, ProverOnlyCircuitData,
+    VerifierCircuitData, VerifierOnlyCircuitData,
+};
+use crate::plonk::config::{GenericConfig, GenericHashOut, Hasher};
+use crate::plonk::plonk_common::salt_size;
+use crate::plonk::proof::{
+    CompressedProof, CompressedProofWithPublicInputs, OpeningSet, Proof, ProofWithPublicInputs,
+};
+pub use crate::util::serialization::gate_serialization::{
+    DefaultGateSerializer, FullGateSerializer, GateSerializer,
+};
+pub use crate::util::serialization::generator_serialization::{
+    DefaultGeneratorSerializer, FullGeneratorSerializer, WitnessGeneratorSerializer,
+};
+
+/// Version of the binary layout used for circuit data. It is written at the start of the output of
+/// the `to_bytes` methods of the circuit data types, and checked when reading them back, so that
+/// files written by an incompatible version of this crate are rejected instead of misread. The tags
+/// of [`DefaultGateSerializer`], [`FullGateSerializer`] and their generator counterparts are part
+/// of this layout: a new type gets a new tag, and the tags of existing types never change.
+pub const CIRCUIT_DATA_FORMAT_VERSION: u32 = 1;
+
+/// A no_std compatible variant of `std::io::Error`
+#[derive(Debug)]
+pub struct IoError;
+
+impl Display for IoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+/// A no_std compatible variant of `std::io::Result`
+pub type IoResult<T> = Result<T, IoError>;
+
+/// A `Read` which is able to report how many bytes are remaining.
+pub trait Remaining: Read {
+    /// Returns the number of bytes remaining in the buffer.
+    fn remaining(&self) -> usize;
+
+    /// Returns whether zero bytes are remaining.
+    fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+}
+
+/// Similar to `std::io::Read`, but works with no_std.
+pub trait Read {
+    /// Reads exactly the length of `bytes` from `self` and writes it to `bytes`.
+    fn read_exact(&mut self, bytes: &mut [u8]) -> IoResult<()>;
+
+    /// Reads a `u8` value from `self`.
+    #[inline]
+    fn read_u8(&mut self) -> IoResult<u8> {
+        let mut buf = [0; size_of::<u8>()];
+        self.read_exact(&mut buf)?;
+        Ok(buf[0])
+    }
+
+    /// Reads a `u16` value from `self`.
+    #[inline]
+    fn read_u16(&mut self) -> IoResult<u16> {
+        let mut buf = [0; size_of::<u16>()];
+        self.read_exact(&mut buf)?;
+        Ok(u16::from_le_bytes(buf))
+    }
+
+    /// Reads a `u32` value from `self`.
+    #[inline]
+    fn read_u32(&mut self) -> IoResult<u32> {
+        let mut buf = [0; size_of::<u32>()];
+        self.read_exact(&mut buf)?;
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    /// Reads a `u64` value from `self`.
+    #[inline]
+    fn read_u64(&mut self) -> IoResult<u64> {
+        let mut buf = [0; size_of::<u64>()];
+        self.read_exact(&mut buf)?;
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    /// Reads a `usize` value from `self`.
+    #[inline]
+    fn read_usize(&mut self) -> IoResult<usize> {
+        self.read_u64()?.try_into().map_err(|_| IoError)
+    }
+
+    /// Reads a vector of `usize` values from `self`, prefixed by its length.
+    #[inline]
+    fn read_usize_vec(&mut self) -> IoResult<Vec<usize>> {
+        let length = self.read_usize()?;
+        (0..length).map(|_| self.read_usize()).collect()
+    }
+
+    /// Reads a `bool` value from `self`.
+    #[inline]
+    fn read_bool(&mut self) -> IoResult<bool> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(IoError),
+        }
+    }
+
+    /// Reads the circuit data format version from `self`, failing if it differs from
+    /// [`CIRCUIT_DATA_FORMAT_VERSION`].
+    #[inline]
+    fn read_format_version(&mut self) -> IoResult<()> {
+        if self.read_u32()? == CIRCUIT_DATA_FORMAT_VERSION {
+            Ok(())
+        } else {
+            Err(IoError)
+        }
+    }
+
+    /// Reads a element from the field `F` with size less than `2^64` from `self.`
+    #[inline]
+    fn read_field<F>(&mut self) -> IoResult<F>
+    where
+        F: Field64,
+    {
+        let mut buf = [0; size_of::<u64>()];
+        self.read_exact(&mut buf)?;
+        Ok(F::from_canonical_u64(u64::from_le_bytes(buf)))
+    }
+
+    /// Reads a vector of elements from the field `F` from `self`.
+    #[inline]
+    fn read_field_vec<F>(&mut self, length: usize) -> IoResult<Vec<F>>
+    where
+        F: Field64,
+    {
+        (0..length)
+            .map(|_| self.read_field())
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// Reads an element from the field extension of `F` from `self.`
+    #[inline]
+    fn read_field_ext<F, const D: usize>(&mut self) -> IoResult<F::Extension>
+    where
+        F: Field64 + Extendable<D>,
+    {
+        let mut arr = [F::ZERO; D];
+        for a in arr.iter_mut() {
+            *a = self.read_field()?;
+        }
+        Ok(<F::Extension as FieldExtension<D>>::from_basefield_array(
+            arr,
+        ))
+    }
+
+    /// Reads a vector of elements from the field extension of `F` from `self`.
+    #[inline]
+    fn read_field_ext_vec<F, const D: usize>(
+        &mut self,
+        length: usize,
+    ) -> IoResult<Vec<F::Extension>>
+    where
+        F: RichField + Extendable<D>,
+    {
+        (0..length).map(|_| self.read_field_ext::<F, D>()).collect()
+    }
+
+    /// Reads a vector of elements from the field `F` from `self`, prefixed by its length.
+    #[inline]
+    fn read_field_vec_with_length<F>(&mut self) -> IoResult<Vec<F>>
+    where
+        F: Field64,
+    {
+        let length = self.read_usize()?;
+        self.read_field_vec(length)
+    }
+
+    /// Reads a value of type [`Target`] from `self`.
+    #[inline]
+    fn read_target(&mut self) -> IoResult<Target> {
+        match self.read_u8()? {
+            0 => {
+                let row = self.read_usize()?;
+                let column = self.read_usize()?;
+                Ok(Target::Wire(Wire { row, column }))
+            }
+            1 => {
+                let index = self.read_usize()?;
+                Ok(Target::VirtualTarget { index })
+            }
+            _ => Err(IoError),
+        }
+    }
+
+    /// Reads a vector of [`Target`]s from `self`, prefixed by its length.
+    #[inline]
+    fn read_target_vec(&mut self) -> IoResult<Vec<Target>> {
+        let length = self.read_usize()?;
+        (0..length).map(|_| self.read_target()).collect()
+    }
+
+    /// Reads an array of `N` [`Target`]s from `self`.
+    #[inline]
+    fn read_target_array<const N: usize>(&mut self) -> IoResult<[Target; N]> {
+        let mut arr = [Target::VirtualTarget { index: 0 }; N];
+        for t in arr.iter_mut() {
+            *t = self.read_target()?;
+        }
+        Ok(arr)
+    }
+
+    /// Reads a value of type [`BoolTarget`] from `self`.
+    #[inline]
+    fn read_bool_target(&mut self) -> IoResult<BoolTarget> {
+        Ok(BoolTarget::new_unsafe(self.read_target()?))
+    }
+
+    /// Reads a vector of [`BoolTarget`]s from `self`, prefixed by its length.
+    #[inline]
+    fn read_bool_target_vec(&mut self) -> IoResult<Vec<BoolTarget>> {
+        let length = self.read_usize()?;
+        (0..length).map(|_| self.read_bool_target()).collect()
+    }
+
+    /// Reads a value of type [`ExtensionTarget`] from `self`.
+    #[inline]
+    fn read_target_ext<const D: usize>(&mut self) -> IoResult<ExtensionTarget<D>> {
+        Ok(ExtensionTarget(self.read_target_array()?))
+    }
+
+    /// Reads a vector of [`ExtensionTarget`]s from `self`, prefixed by its length.
+    #[inline]
+    fn read_target_ext_vec<const D: usize>(&mut self) -> IoResult<Vec<ExtensionTarget<D>>> {
+        let length = self.read_usize()?;
+        (0..length).map(|_| self.read_target_ext()).collect()
+    }
+
+    /// Reads a value of type [`HashOutTarget`] from `self`.
+    #[inline]
+    fn read_target_hash(&mut self) -> IoResult<HashOutTarget> {
+        Ok(HashOutTarget {
+            elements: self.read_target_array()?,
+        })
+    }
+
+    /// Reads a [`LookupTable`] from `self`, prefixed by its length.
+    #[inline]
+    fn read_lut(&mut self) -> IoResult<LookupTable> {
+        let length = self.read_usize()?;
+        let lut = (0..length)
+            .map(|_| Ok((self.read_u16()?, self.read_u16()?)))
+            .collect::<IoResult<Vec<_>>>()?;
+        Ok(Arc::new(lut))
+    }
+
+    /// Reads a hash value from `self`.
+    #[inline]
+    fn read_hash<F, H>(&mut self) -> IoResult<H::Hash>
+    where
+        F: RichField,
+        H: Hasher<F>,
+    {
+        let mut buf = vec![0; H::HASH_SIZE];
+        self.read_exact(&mut buf)?;
+        Ok(H::Hash::from_bytes(&buf))
+    }
+
+    /// Reads a value of type [`MerkleCap`] from `self` with the given `cap_height`.
+    #[inline]
+    fn read_merkle_cap<F, H>(&mut self, cap_height: usize) -> IoResult<MerkleCap<F, H>>
+    where
+        F: RichField,
+        H: Hasher<F>,
+    {
+        let cap_length = 1 << cap_height;
+        Ok(MerkleCap(
+            (0..cap_length)
+                .map(|_| self.read_hash::<F, H>())
+                .collect::<Result<Vec<_>, _>>()?,
+        ))
+    }
+
+    /// Reads a value of type [`MerkleTree`] from `self`.
+    #[inline]
+    fn read_merkle_tree<F, H>(&mut self) -> IoResult<MerkleTree<F, H>>
+    where
+        F: RichField,
+        H: Hasher<F>,
+    {
+        let num_leaves = self.read_usize()?;
+        let leaves = (0..num_leaves)
+            .map(|_| self.read_field_vec_with_length())
+            .collect::<Result<Vec<_>, _>>()?;
+        let num_digests = self.read_usize()?;
+        let digests = (0..num_digests)
+            .map(|_| self.read_hash::<F, H>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let cap_height = self.read_usize()?;
+        let cap = self.read_merkle_cap(cap_height)?;
+        Ok(MerkleTree::from_memory(leaves, digests, cap))
+    }
+
+    /// Reads a value of type [`PolynomialBatch`] from `self`.
+    #[inline]
+    fn read_polynomial_batch<F, C, const D: usize>(&mut self) -> IoResult<PolynomialBatch<F, C, D>>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let num_polynomials = self.read_usize()?;
+        let polynomials = (0..num_polynomials)
+            .map(|_| self.read_field_vec_with_length().map(PolynomialCoeffs::new))
+            .collect::<Result<Vec<_>, _>>()?;
+        let merkle_tree = self.read_merkle_tree()?;
+        let degree_log = self.read_usize()?;
+        let rate_bits = self.read_usize()?;
+        let blinding = self.read_bool()?;
+        Ok(PolynomialBatch {
+            polynomials,
+            merkle_tree,
+            degree_log,
+            rate_bits,
+            blinding,
+        })
+    }
+
+    /// Reads a value of type [`FriConfig`] from `self`.
+    #[inline]
+    fn read_fri_config(&mut self) -> IoResult<FriConfig> {
+        let rate_bits = self.read_usize()?;
+        let cap_height = self.read_usize()?;
+        let proof_of_work_bits = self.read_u32()?;
+        let reduction_strategy = match self.read_u8()? {
+            0 => FriReductionStrategy::Fixed(self.read_usize_vec()?),
+            1 => {
+                let arity_bits = self.read_usize()?;
+                let final_poly_bits = self.read_usize()?;
+                FriReductionStrategy::ConstantArityBits(arity_bits, final_poly_bits)
+            }
+            2 => FriReductionStrategy::MinSize(if self.read_bool()? {
+                Some(self.read_usize()?)
+            } else {
+                None
+            }),
+            _ => return Err(IoError),
+        };
+        let num_query_rounds = self.read_usize()?;
+        Ok(FriConfig {
+            rate_bits,
+            cap_height,
+            proof_of_work_bits,
+            reduction_strategy,
+            num_query_rounds,
+        })
+    }
+
+    /// Reads a value of type [`FriParams`] from `self`.
+    #[inline]
+    fn read_fri_params(&mut self) -> IoResult<FriParams> {
+        let config = self.read_fri_config()?;
+        let hiding = self.read_bool()?;
+        let degree_bits = self.read_usize()?;
+        let reduction_arity_bits = self.read_usize_vec()?;
+        Ok(FriParams {
+            config,
+            hiding,
+            degree_bits,
+            reduction_arity_bits,
+        })
+    }
+
+    /// Reads a value of type [`CircuitConfig`] from `self`.
+    #[inline]
+    fn read_circuit_config(&mut self) -> IoResult<CircuitConfig> {
+        let num_wires = self.read_usize()?;
+        let num_routed_wires = self.read_usize()?;
+        let num_constants = self.read_usize()?;
+        let use_base_arithmetic_gate = self.read_bool()?;
+        let security_bits = self.read_usize()?;
+        let num_challenges = self.read_usize()?;
+        let zero_knowledge = self.read_bool()?;
+        let max_quotient_degree_factor = self.read_usize()?;
+        let fri_config = self.read_fri_config()?;
+        Ok(CircuitConfig {
+            num_wires,
+            num_routed_wires,
+            num_constants,
+            use_base_arithmetic_gate,
+            security_bits,
+            num_challenges,
+            zero_knowledge,
+            max_quotient_degree_factor,
+            fri_config,
+        })
+    }
+
+    /// Reads a value of type [`SelectorsInfo`] from `self`.
+    #[inline]
+    fn read_selectors_info(&mut self) -> IoResult<SelectorsInfo> {
+        let selector_indices = self.read_usize_vec()?;
+        let num_groups = self.read_usize()?;
+        let groups = (0..num_groups)
+            .map(|_| Ok(self.read_usize()?..self.read_usize()?))
+            .collect::<IoResult<Vec<_>>>()?;
+        Ok(SelectorsInfo {
+            selector_indices,
+            groups,
+        })
+    }
+
+    /// Reads a value of type [`GateRef`] from `self`, using `gate_serializer` to decode the gate.
+    fn read_gate<F, const D: usize>(
+        &mut self,
+        gate_serializer: &dyn GateSerializer<F, D>,
+    ) -> IoResult<GateRef<F, D>>
+    where
+        F: RichField + Extendable<D>;
+
+    /// Reads a value of type [`WitnessGeneratorRef`] from `self`, using `generator_serializer` to
+    /// decode the generator.
+    fn read_generator<F, const D: usize>(
+        &mut self,
+        generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
+    ) -> IoResult<WitnessGeneratorRef<F>>
+    where
+        F: RichField + Extendable<D>;
+
+    /// Reads a value of type [`CommonCircuitData`] from `self`.
+    #[inline]
+    fn read_common_circuit_data<F, const D: usize>(
+        &mut self,
+        gate_serializer: &dyn GateSerializer<F, D>,
+    ) -> IoResult<CommonCircuitData<F, D>>
+    where
+        F: RichField + Extendable<D>,
+    {
+        let config = self.read_circuit_config()?;
+        let fri_params = self.read_fri_params()?;
+        let num_gates = self.read_usize()?;
+        let gates = (0..num_gates)
+            .map(|_| self.read_gate(gate_serializer))
+            .collect::<Result<Vec<_>, _>>()?;
+        let selectors_info = self.read_selectors_info()?;
+        let quotient_degree_factor = self.read_usize()?;
+        let num_gate_constraints = self.read_usize()?;
+        let num_constants = self.read_usize()?;
+        let num_public_inputs = self.read_usize()?;
+        let k_is = self.read_field_vec_with_length()?;
+        let num_partial_products = self.read_usize()?;
+        let num_luts = self.read_usize()?;
+        let luts = (0..num_luts)
+            .map(|_| self.read_lut())
+            .collect::<Result<Vec<_>, _>>()?;
+        let num_lookup_polys = self.read_usize()?;
+        let num_lookup_selectors = self.read_usize()?;
+        Ok(CommonCircuitData {
+            config,
+            fri_params,
+            gates,
+            selectors_info,
+            quotient_degree_factor,
+            num_gate_constraints,
+            num_constants,
+            num_public_inputs,
+            k_is,
+            num_partial_products,
+            luts,
+            num_lookup_polys,
+            num_lookup_selectors,
+        })
+    }
+
+    /// Reads a value of type [`ProverOnlyCircuitData`] from `self`.
+    #[inline]
+    fn read_prover_only_circuit_data<F, C, const D: usize>(
+        &mut self,
+        generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
+    ) -> IoResult<ProverOnlyCircuitData<F, C, D>>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let num_generators = self.read_usize()?;
+        let generators = (0..num_generators)
+            .map(|_| self.read_generator(generator_serializer))
+            .collect::<Result<Vec<_>, _>>()?;
+        let num_watches = self.read_usize()?;
+        let generator_indices_by_watches = (0..num_watches)
+            .map(|_| Ok((self.read_usize()?, self.read_usize_vec()?)))
+            .collect::<IoResult<BTreeMap<_, _>>>()?;
+        let constants_sigmas_commitment = self.read_polynomial_batch()?;
+        let num_sigmas = self.read_usize()?;
+        let sigmas = (0..num_sigmas)
+            .map(|_| self.read_field_vec_with_length())
+            .collect::<Result<Vec<_>, _>>()?;
+        let subgroup = self.read_field_vec_with_length()?;
+        let public_inputs = self.read_target_vec()?;
+        let representative_map = self.read_usize_vec()?;
+        let fft_root_table = if self.read_bool()? {
+            let num_layers = self.read_usize()?;
+            Some(
+                (0..num_layers)
+                    .map(|_| self.read_field_vec_with_length())
+                    .collect::<Result<Vec<_>, _>>()?,
+            )
+        } else {
+            None
+        };
+        let circuit_digest = self.read_hash::<F, C::Hasher>()?;
+        Ok(ProverOnlyCircuitData {
+            generators,
+            generator_indices_by_watches,
+            constants_sigmas_commitment,
+            sigmas,
+            subgroup,
+            public_inputs,
+            representative_map,
+            fft_root_table,
+            circuit_digest,
+        })
+    }
+
+    /// Reads a value of type [`VerifierOnlyCircuitData`] from `self`.
+    #[inline]
+    fn read_verifier_only_circuit_data<F, C, const D: usize>(
+        &mut self,
+    ) -> IoResult<VerifierOnlyCircuitData<C, D>>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let cap_height = self.read_usize()?;
+        let constants_sigmas_cap = self.read_merkle_cap(cap_height)?;
+        let circuit_digest = self.read_hash::<F, C::Hasher>()?;
+        Ok(VerifierOnlyCircuitData {
+            constants_sigmas_cap,
+            circuit_digest,
+        })
+    }
+
+    /// Reads a value of type [`CircuitData`] from `self`.
+    #[inline]
+    fn read_circuit_data<F, C, const D: usize>(
+        &mut self,
+        gate_serializer: &dyn GateSerializer<F, D>,
+        generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
+    ) -> IoResult<CircuitData<F, C, D>>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let common = self.read_common_circuit_data(gate_serializer)?;
+        let prover_only = self.read_prover_only_circuit_data(generator_serializer)?;
+        let verifier_only = self.read_verifier_only_circuit_data()?;
+        Ok(CircuitData {
+            prover_only,
+            verifier_only,
+            common,
+        })
+    }
+
+    /// Reads a value of type [`ProverCircuitData`] from `self`.
+    #[inline]
+    fn read_prover_circuit_data<F, C, const D: usize>(
+        &mut self,
+        gate_serializer: &dyn GateSerializer<F, D>,
+        generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
+    ) -> IoResult<ProverCircuitData<F, C, D>>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let common = self.read_common_circuit_data(gate_serializer)?;
+        let prover_only = self.read_prover_only_circuit_data(generator_serializer)?;
+        Ok(ProverCircuitData {
+            prover_only,
+            common,
+        })
+    }
+
+    /// Reads a value of type [`VerifierCircuitData`] from `self`.
+    #[inline]
+    fn read_verifier_circuit_data<F, C, const D: usize>(
+        &mut self,
+        gate_serializer: &dyn GateSerializer<F, D>,
+    ) -> IoResult<VerifierCircuitData<F, C, D>>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let common = self.read_common_circuit_data(gate_serializer)?;
+        let verifier_only = self.read_verifier_only_circuit_data()?;
+        Ok(VerifierCircuitData {
+            verifier_only,
+            common,
+        })
+    }
+
+    /// Reads a value of type [`OpeningSet`] from `self` with the given `common_data`.
+    #[inline]
+    fn read_opening_set<F, C, const D: usize>(
+        &mut self,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<OpeningSet<F, D>>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let config = &common_data.config;
+        let constants = self.read_field_ext_vec::<F, D>(common_data.num_constants)?;
+        let plonk_sigmas = self.read_field_ext_vec::<F, D>(config.num_routed_wires)?;
+        let wires = self.read_field_ext_vec::<F, D>(config.num_wires)?;
+        let plonk_zs = self.read_field_ext_vec::<F, D>(config.num_challenges)?;
+        let plonk_zs_next = self.read_field_ext_vec::<F, D>(config.num_challenges)?;
+        let partial_products = self
+            .read_field_ext_vec::<F, D>(common_data.num_partial_products * config.num_challenges)?;
+        let quotient_polys = self.read_field_ext_vec::<F, D>(
+            common_data.quotient_degree_factor * config.num_challenges,
+        )?;
+        let lookup_zs =
+            self.read_field_ext_vec::<F, D>(common_data.num_lookup_polys * config.num_challenges)?;
+        let lookup_zs_next =
+            self.read_field_ext_vec::<F, D>(common_data.num_lookup_polys * config.num_challenges)?;
+        Ok(OpeningSet {
+            constants,
+            plonk_sigmas,
+            wires,
+            plonk_zs,
+            plonk_zs_next,
+            partial_products,
+            quotient_polys,
+            lookup_zs,
+            lookup_zs_next,
+        })
+    }
+
+    /// Reads a value of type [`MerkleProof`] from `self`.
+    #[inline]
+    fn read_merkle_proof<F, H>(&mut self) -> IoResult<MerkleProof<F, H>>
+    where
+        F: RichField,
+        H: Hasher<F>,
+    {
+        let length = self.read_u8()?;
+        Ok(MerkleProof {
+            siblings: (0..length)
+                .map(|_| self.read_hash::<F, H>())
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Reads a value of type [`FriInitialTreeProof`] from `self` with the given `common_data`.
+    #[inline]
+    fn read_fri_initial_proof<F, C, const D: usize>(
+        &mut self,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<FriInitialTreeProof<F, C::Hasher>>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let config = &common_data.config;
+        let salt = salt_size(common_data.fri_params.hiding);
+        let mut evals_proofs = Vec::with_capacity(4);
+
+        let constants_sigmas_v =
+            self.read_field_vec(common_data.num_constants + config.num_routed_wires)?;
+        let constants_sigmas_p = self.read_merkle_proof()?;
+        evals_proofs.push((constants_sigmas_v, constants_sigmas_p));
+
+        let wires_v = self.read_field_vec(config.num_wires + salt)?;
+        let wires_p = self.read_merkle_proof()?;
+        evals_proofs.push((wires_v, wires_p));
+
+        let zs_partial_v =
+            self.read_field_vec(common_data.num_zs_partial_products_polys() + salt)?;
+        let zs_partial_p = self.read_merkle_proof()?;
+        evals_proofs.push((zs_partial_v, zs_partial_p));
+
+        let quotient_v =
+            self.read_field_vec(config.num_challenges * common_data.quotient_degree_factor + salt)?;
+        let quotient_p = self.read_merkle_proof()?;
+        evals_proofs.push((quotient_v, quotient_p));
+
+        Ok(FriInitialTreeProof { evals_proofs })
+    }
+
+    /// Reads a value of type [`FriQueryStep`] from `self` with the given `arity` and `compressed`
+    /// flag.
+    #[inline]
+    fn read_fri_query_step<F, C, const D: usize>(
+        &mut self,
+        arity: usize,
+        compressed: bool,
+    ) -> IoResult<FriQueryStep<F, C::Hasher, D>>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let evals = self.read_field_ext_vec::<F, D>(arity - usize::from(compressed))?;
+        let merkle_proof = self.read_merkle_proof()?;
+        Ok(FriQueryStep {
+            evals,
+            merkle_proof,
+        })
+    }
+
+    /// Reads a vector of [`FriQueryRound`]s from `self` with `common_data`.
+    #[inline]
+    fn read_fri_query_rounds<F, C, const D: usize>(
+        &mut self,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<Vec<FriQueryRound<F, C::Hasher, D>>>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let config = &common_data.config;
+        let mut fqrs = Vec::with_capacity(config.fri_config.num_query_rounds);
+        for _ in 0..config.fri_config.num_query_rounds {
+            let initial_trees_proof = self.read_fri_initial_proof::<F, C, D>(common_data)?;
+            let steps = common_data
+                .fri_params
+                .reduction_arity_bits
+                .iter()
+                .map(|&ar| self.read_fri_query_step::<F, C, D>(1 << ar, false))
+                .collect::<Result<_, _>>()?;
+            fqrs.push(FriQueryRound {
+                initial_trees_proof,
+                steps,
+            })
+        }
+        Ok(fqrs)
+    }
+
+    /// Reads a value of type [`FriProof`] from `self` with `common_data`.
+    #[inline]
+    fn read_fri_proof<F, C, const D: usize>(
+        &mut self,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<FriProof<F, C::Hasher, D>>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let config = &common_data.config;
+        let commit_phase_merkle_caps = (0..common_data.fri_params.reduction_arity_bits.len())
+            .map(|_| self.read_merkle_cap(config.fri_config.cap_height))
+            .collect::<Result<Vec<_>, _>>()?;
+        let query_round_proofs = self.read_fri_query_rounds::<F, C, D>(common_data)?;
+        let final_poly = PolynomialCoeffs::new(
+            self.read_field_ext_vec::<F, D>(common_data.fri_params.final_poly_len())?,
+        );
+        let pow_witness = self.read_field()?;
+        Ok(FriProof {
+            commit_phase_merkle_caps,
+            query_round_proofs,
+            final_poly,
+            pow_witness,
+        })
+    }
+
+    /// Reads a value of type [`Proof`] from `self` with `common_data`.
+    #[inline]
+    fn read_proof<F, C, const D: usize>(
+        &mut self,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<Proof<F, C, D>>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let config = &common_data.config;
+        let wires_cap = self.read_merkle_cap(config.fri_config.cap_height)?;
+        let plonk_zs_partial_products_cap = self.read_merkle_cap(config.fri_config.cap_height)?;
+        let quotient_polys_cap = self.read_merkle_cap(config.fri_config.cap_height)?;
+        let openings = self.read_opening_set::<F, C, D>(common_data)?;
+        let opening_proof = self.read_fri_proof::<F, C, D>(common_data)?;
+        Ok(Proof {
+            wires_cap,
+            plonk_zs_partial_products_cap,
+            quotient_polys_cap,
+            openings,
+            opening_proof,
+        })
+    }
+
+    /// Reads a value of type [`ProofWithPublicInputs`] from `self` with `common_data`.
+    #[inline]
+    fn read_proof_with_public_inputs<F, C, const D: usize>(
+        &mut self,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<ProofWithPublicInputs<F, C, D>>
+    where
+        Self: Remaining,
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let proof = self.read_proof(common_data)?;
+        let public_inputs = self.read_field_vec(self.remaining() / size_of::<u64>())?;
+        Ok(ProofWithPublicInputs {
+            proof,
+            public_inputs,
+        })
+    }
+
+    /// Reads a value of type [`CompressedFriQueryRounds`] from `self` with `common_data`.
+    #[inline]
+    fn read_compressed_fri_query_rounds<F, C, const D: usize>(
+        &mut self,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<CompressedFriQueryRounds<F, C::Hasher, D>>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let config = &common_data.config;
+        let original_indices = (0..config.fri_config.num_query_rounds)
+            .map(|_| self.read_u32().map(|i| i as usize))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut indices = original_indices.clone();
+        indices.sort_unstable();
+        indices.dedup();
+        let mut pairs = Vec::new();
+        for &i in &indices {
+            pairs.push((i, self.read_fri_initial_proof::<F, C, D>(common_data)?));
+        }
+        let initial_trees_proofs = HashMap::from_iter(pairs);
+
+        let mut steps = Vec::with_capacity(common_data.fri_params.reduction_arity_bits.len());
+        for &a in &common_data.fri_params.reduction_arity_bits {
+            indices.iter_mut().for_each(|x| {
+                *x >>= a;
+            });
+            indices.dedup();
+            let query_steps = (0..indices.len())
+                .map(|_| self.read_fri_query_step::<F, C, D>(1 << a, true))
+                .collect::<Result<Vec<_>, _>>()?;
+            steps.push(
+                indices
+                    .iter()
+                    .copied()
+                    .zip(query_steps)
+                    .collect::<HashMap<_, _>>(),
+            );
+        }
+
+        Ok(CompressedFriQueryRounds {
+            indices: original_indices,
+            initial_trees_proofs,
+            steps,
+        })
+    }
+
+    /// Reads a value of type [`CompressedFriProof`] from `self` with `common_data`.
+    #[inline]
+    fn read_compressed_fri_proof<F, C, const D: usize>(
+        &mut self,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<CompressedFriProof<F, C::Hasher, D>>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let config = &common_data.config;
+        let commit_phase_merkle_caps = (0..common_data.fri_params.reduction_arity_bits.len())
+            .map(|_| self.read_merkle_cap(config.fri_config.cap_height))
+            .collect::<Result<Vec<_>, _>>()?;
+        let query_round_proofs = self.read_compressed_fri_query_rounds::<F, C, D>(common_data)?;
+        let final_poly = PolynomialCoeffs::new(
+            self.read_field_ext_vec::<F, D>(common_data.fri_params.final_poly_len())?,
+        );
+        let pow_witness = self.read_field()?;
+        Ok(CompressedFriProof {
+            commit_phase_merkle_caps,
+            query_round_proofs,
+            final_poly,
+            pow_witness,
+        })
+    }
+
+    /// Reads a value of type [`CompressedProof`] from `self` with `common_data`.
+    #[inline]
+    fn read_compressed_proof<F, C, const D: usize>(
+        &mut self,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<CompressedProof<F, C, D>>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let config = &common_data.config;
+        let wires_cap = self.read_merkle_cap(config.fri_config.cap_height)?;
+        let plonk_zs_partial_products_cap = self.read_merkle_cap(config.fri_config.cap_height)?;
+        let quotient_polys_cap = self.read_merkle_cap(config.fri_config.cap_height)?;
+        let openings = self.read_opening_set::<F, C, D>(common_data)?;
+        let opening_proof = self.read_compressed_fri_proof::<F, C, D>(common_data)?;
+        Ok(CompressedProof {
+            wires_cap,
+            plonk_zs_partial_products_cap,
+            quotient_polys_cap,
+            openings,
+            opening_proof,
+        })
+    }
+
+    /// Reads a value of type [`CompressedProofWithPublicInputs`] from `self` with `common_data`.
+    #[inline]
+    fn read_compressed_proof_with_public_inputs<F, C, const D: usize>(
+        &mut self,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<CompressedProofWithPublicInputs<F, C, D>>
+    where
+        Self: Remaining,
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let proof = self.read_compressed_proof(common_data)?;
+        let public_inputs = self.read_field_vec(self.remaining() / size_of::<u64>())?;
+        Ok(CompressedProofWithPublicInputs {
+            proof,
+            public_inputs,
+        })
+    }
+}
+
+/// Writing
+pub trait Write {
+    /// Error Type
+    type Error;
+
+    /// Writes all `bytes` to `self`.
+    fn write_all(&mut self, bytes: &[u8]) -> IoResult<()>;
+
+    /// Writes a byte `x` to `self`.
+    #[inline]
+    fn write_u8(&mut self, x: u8) -> IoResult<()> {
+        self.write_all(&[x])
+    }
+
+    /// Writes a `u16` value `x` to `self`.
+    #[inline]
+    fn write_u16(&mut self, x: u16) -> IoResult<()> {
+        self.write_all(&x.to_le_bytes())
+    }
+
+    /// Writes a word `x` to `self.`
+    #[inline]
+    fn write_u32(&mut self, x: u32) -> IoResult<()> {
+        self.write_all(&x.to_le_bytes())
+    }
+
+    /// Writes a `u64` value `x` to `self`.
+    #[inline]
+    fn write_u64(&mut self, x: u64) -> IoResult<()> {
+        self.write_all(&x.to_le_bytes())
+    }
+
+    /// Writes a `usize` value `x` to `self`.
+    #[inline]
+    fn write_usize(&mut self, x: usize) -> IoResult<()> {
+        self.write_u64(x as u64)
+    }
+
+    /// Writes a vector `v` of `usize` values to `self`, prefixed by its length.
+    #[inline]
+    fn write_usize_vec(&mut self, v: &[usize]) -> IoResult<()> {
+        self.write_usize(v.len())?;
+        for &a in v {
+            self.write_usize(a)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a `bool` value `x` to `self`.
+    #[inline]
+    fn write_bool(&mut self, x: bool) -> IoResult<()> {
+        self.write_u8(u8::from(x))
+    }
+
+    /// Writes [`CIRCUIT_DATA_FORMAT_VERSION`] to `self`.
+    #[inline]
+    fn write_format_version(&mut self) -> IoResult<()> {
+        self.write_u32(CIRCUIT_DATA_FORMAT_VERSION)
+    }
+
+    /// Writes an element `x` from the field `F` to `self`.
+    #[inline]
+    fn write_field<F>(&mut self, x: F) -> IoResult<()>
+    where
+        F: PrimeField64,
+    {
+        self.write_all(&x.to_canonical_u64().to_le_bytes())
+    }
+
+    /// Writes a vector `v` of elements from the field `F` to `self`.
+    #[inline]
+    fn write_field_vec<F>(&mut self, v: &[F]) -> IoResult<()>
+    where
+        F: PrimeField64,
+    {
+        for &a in v {
+            self.write_field(a)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a vector `v` of elements from the field `F` to `self`, prefixed by its length.
+    #[inline]
+    fn write_field_vec_with_length<F>(&mut self, v: &[F]) -> IoResult<()>
+    where
+        F: PrimeField64,
+    {
+        self.write_usize(v.len())?;
+        self.write_field_vec(v)
+    }
+
+    /// Writes a target `x` to `self`.
+    #[inline]
+    fn write_target(&mut self, x: Target) -> IoResult<()> {
+        match x {
+            Target::Wire(Wire { row, column }) => {
+                self.write_u8(0)?;
+                self.write_usize(row)?;
+                self.write_usize(column)
+            }
+            Target::VirtualTarget { index } => {
+                self.write_u8(1)?;
+                self.write_usize(index)
+            }
+        }
+    }
+
+    /// Writes a slice of targets `v` to `self`, without any length prefix.
+    #[inline]
+    fn write_target_array(&mut self, v: &[Target]) -> IoResult<()> {
+        for &a in v {
+            self.write_target(a)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a vector `v` of targets to `self`, prefixed by its length.
+    #[inline]
+    fn write_target_vec(&mut self, v: &[Target]) -> IoResult<()> {
+        self.write_usize(v.len())?;
+        self.write_target_array(v)
+    }
+
+    /// Writes a boolean target `x` to `self`.
+    #[inline]
+    fn write_bool_target(&mut self, x: BoolTarget) -> IoResult<()> {
+        self.write_target(x.target)
+    }
+
+    /// Writes a vector `v` of boolean targets to `self`, prefixed by its length.
+    #[inline]
+    fn write_bool_target_vec(&mut self, v: &[BoolTarget]) -> IoResult<()> {
+        self.write_usize(v.len())?;
+        for &a in v {
+            self.write_bool_target(a)?;
+        }
+        Ok(())
+    }
+
+    /// Writes an extension target `x` to `self`.
+    #[inline]
+    fn write_target_ext<const D: usize>(&mut self, x: ExtensionTarget<D>) -> IoResult<()> {
+        self.write_target_array(&x.0)
+    }
+
+    /// Writes a vector `v` of extension targets to `self`, prefixed by its length.
+    #[inline]
+    fn write_target_ext_vec<const D: usize>(&mut self, v: &[ExtensionTarget<D>]) -> IoResult<()> {
+        self.write_usize(v.len())?;
+        for &a in v {
+            self.write_target_ext(a)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a hash target `x` to `self`.
+    #[inline]
+    fn write_target_hash(&mut self, x: &HashOutTarget) -> IoResult<()> {
+        self.write_target_array(&x.elements)
+    }
+
+    /// Writes a [`LookupTable`] `lut` to `self`, prefixed by its length.
+    #[inline]
+    fn write_lut(&mut self, lut: &LookupTable) -> IoResult<()> {
+        self.write_usize(lut.len())?;
+        for &(inp, out) in lut.iter() {
+            self.write_u16(inp)?;
+            self.write_u16(out)?;
+        }
+        Ok(())
+    }
+
+    /// Writes an element `x` from the field extension of `F` to `self`.
+    #[inline]
+    fn write_field_ext<F, const D: usize>(&mut self, x: F::Extension) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+    {
+        for &a in &x.to_basefield_array() {
+            self.write_field(a)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a vector `v` of elements from the field extension of `F` to `self`.
+    #[inline]
+    fn write_field_ext_vec<F, const D: usize>(&mut self, v: &[F::Extension]) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+    {
+        for &a in v {
+            self.write_field_ext::<F, D>(a)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a hash `h` to `self`.
+    #[inline]
+    fn write_hash<F, H>(&mut self, h: H::Hash) -> IoResult<()>
+    where
+        F: RichField,
+        H: Hasher<F>,
+    {
+        self.write_all(&h.to_bytes())
+    }
+
+    /// Writes `cap`, a value of type [`MerkleCap`], to `self`.
+    #[inline]
+    fn write_merkle_cap<F, H>(&mut self, cap: &MerkleCap<F, H>) -> IoResult<()>
+    where
+        F: RichField,
+        H: Hasher<F>,
+    {
+        for &a in &cap.0 {
+            self.write_hash::<F, H>(a)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a value `tree` of type [`MerkleTree`] to `self.`
+    #[inline]
+    fn write_merkle_tree<F, H>(&mut self, tree: &MerkleTree<F, H>) -> IoResult<()>
+    where
+        F: RichField,
+        H: Hasher<F>,
+    {
+        self.write_usize(tree.leaves.len())?;
+        for leaf in tree.leaves.iter() {
+            self.write_field_vec_with_length(leaf)?;
+        }
+        self.write_usize(tree.digests.len())?;
+        for &digest in tree.digests.iter() {
+            self.write_hash::<F, H>(digest)?;
+        }
+        self.write_usize(tree.cap.height())?;
+        self.write_merkle_cap(&tree.cap)
+    }
+
+    /// Writes a value `batch` of type [`PolynomialBatch`] to `self.`
+    #[inline]
+    fn write_polynomial_batch<F, C, const D: usize>(
+        &mut self,
+        batch: &PolynomialBatch<F, C, D>,
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        self.write_usize(batch.polynomials.len())?;
+        for poly in &batch.polynomials {
+            self.write_field_vec_with_length(&poly.coeffs)?;
+        }
+        self.write_merkle_tree(&batch.merkle_tree)?;
+        self.write_usize(batch.degree_log)?;
+        self.write_usize(batch.rate_bits)?;
+        self.write_bool(batch.blinding)
+    }
+
+    /// Writes a value `config` of type [`FriConfig`] to `self.`
+    #[inline]
+    fn write_fri_config(&mut self, config: &FriConfig) -> IoResult<()> {
+        let FriConfig {
+            rate_bits,
+            cap_height,
+            proof_of_work_bits,
+            reduction_strategy,
+            num_query_rounds,
+        } = config;
+        self.write_usize(*rate_bits)?;
+        self.write_usize(*cap_height)?;
+        self.write_u32(*proof_of_work_bits)?;
+        match reduction_strategy {
+            FriReductionStrategy::Fixed(reduction_arity_bits) => {
+                self.write_u8(0)?;
+                self.write_usize_vec(reduction_arity_bits)?;
+            }
+            &FriReductionStrategy::ConstantArityBits(arity_bits, final_poly_bits) => {
+                self.write_u8(1)?;
+                self.write_usize(arity_bits)?;
+                self.write_usize(final_poly_bits)?;
+            }
+            &FriReductionStrategy::MinSize(opt_max_arity_bits) => {
+                self.write_u8(2)?;
+                self.write_bool(opt_max_arity_bits.is_some())?;
+                if let Some(max_arity_bits) = opt_max_arity_bits {
+                    self.write_usize(max_arity_bits)?;
+                }
+            }
+        }
+        self.write_usize(*num_query_rounds)
+    }
+
+    /// Writes a value `params` of type [`FriParams`] to `self.`
+    #[inline]
+    fn write_fri_params(&mut self, params: &FriParams) -> IoResult<()> {
+        self.write_fri_config(&params.config)?;
+        self.write_bool(params.hiding)?;
+        self.write_usize(params.degree_bits)?;
+        self.write_usize_vec(&params.reduction_arity_bits)
+    }
+
+    /// Writes a value `config` of type [`CircuitConfig`] to `self.`
+    #[inline]
+    fn write_circuit_config(&mut self, config: &CircuitConfig) -> IoResult<()> {
+        let CircuitConfig {
+            num_wires,
+            num_routed_wires,
+            num_constants,
+            use_base_arithmetic_gate,
+            security_bits,
+            num_challenges,
+            zero_knowledge,
+            max_quotient_degree_factor,
+            fri_config,
+        } = config;
+        self.write_usize(*num_wires)?;
+        self.write_usize(*num_routed_wires)?;
+        self.write_usize(*num_constants)?;
+        self.write_bool(*use_base_arithmetic_gate)?;
+        self.write_usize(*security_bits)?;
+        self.write_usize(*num_challenges)?;
+        self.write_bool(*zero_knowledge)?;
+        self.write_usize(*max_quotient_degree_factor)?;
+        self.write_fri_config(fri_config)
+    }
+
+    /// Writes a value `info` of type [`SelectorsInfo`] to `self.`
+    #[inline]
+    fn write_selectors_info(&mut self, info: &SelectorsInfo) -> IoResult<()> {
+        self.write_usize_vec(&info.selector_indices)?;
+        self.write_usize(info.groups.len())?;
+        for group in &info.groups {
+            self.write_usize(group.start)?;
+            self.write_usize(group.end)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a value `gate` of type [`GateRef`] to `self`, using `gate_serializer` to encode the
+    /// gate.
+    fn write_gate<F, const D: usize>(
+        &mut self,
+        gate: &GateRef<F, D>,
+        gate_serializer: &dyn GateSerializer<F, D>,
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>;
+
+    /// Writes a value `generator` of type [`WitnessGeneratorRef`] to `self`, using
+    /// `generator_serializer` to encode the generator.
+    fn write_generator<F, const D: usize>(
+        &mut self,
+        generator: &WitnessGeneratorRef<F>,
+        generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>;
+
+    /// Writes a value `common_data` of type [`CommonCircuitData`] to `self.`
+    #[inline]
+    fn write_common_circuit_data<F, const D: usize>(
+        &mut self,
+        common_data: &CommonCircuitData<F, D>,
+        gate_serializer: &dyn GateSerializer<F, D>,
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+    {
+        let CommonCircuitData {
+            config,
+            fri_params,
+            gates,
+            selectors_info,
+            quotient_degree_factor,
+            num_gate_constraints,
+            num_constants,
+            num_public_inputs,
+            k_is,
+            num_partial_products,
+            luts,
+            num_lookup_polys,
+            num_lookup_selectors,
+        } = common_data;
+        self.write_circuit_config(config)?;
+        self.write_fri_params(fri_params)?;
+        self.write_usize(gates.len())?;
+        for gate in gates {
+            self.write_gate(gate, gate_serializer)?;
+        }
+        self.write_selectors_info(selectors_info)?;
+        self.write_usize(*quotient_degree_factor)?;
+        self.write_usize(*num_gate_constraints)?;
+        self.write_usize(*num_constants)?;
+        self.write_usize(*num_public_inputs)?;
+        self.write_field_vec_with_length(k_is)?;
+        self.write_usize(*num_partial_products)?;
+        self.write_usize(luts.len())?;
+        for lut in luts {
+            self.write_lut(lut)?;
+        }
+        self.write_usize(*num_lookup_polys)?;
+        self.write_usize(*num_lookup_selectors)
+    }
+
+    /// Writes a value `prover_only_data` of type [`ProverOnlyCircuitData`] to `self.`
+    #[inline]
+    fn write_prover_only_circuit_data<F, C, const D: usize>(
+        &mut self,
+        prover_only_data: &ProverOnlyCircuitData<F, C, D>,
+        generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let ProverOnlyCircuitData {
+            generators,
+            generator_indices_by_watches,
+            constants_sigmas_commitment,
+            sigmas,
+            subgroup,
+            public_inputs,
+            representative_map,
+            fft_root_table,
+            circuit_digest,
+        } = prover_only_data;
+        self.write_usize(generators.len())?;
+        for generator in generators {
+            self.write_generator(generator, generator_serializer)?;
+        }
+        self.write_usize(generator_indices_by_watches.len())?;
+        for (&watch, generator_indices) in generator_indices_by_watches {
+            self.write_usize(watch)?;
+            self.write_usize_vec(generator_indices)?;
+        }
+        self.write_polynomial_batch(constants_sigmas_commitment)?;
+        self.write_usize(sigmas.len())?;
+        for sigma in sigmas {
+            self.write_field_vec_with_length(sigma)?;
+        }
+        self.write_field_vec_with_length(subgroup)?;
+        self.write_target_vec(public_inputs)?;
+        self.write_usize_vec(representative_map)?;
+        self.write_bool(fft_root_table.is_some())?;
+        if let Some(table) = fft_root_table {
+            self.write_usize(table.len())?;
+            for layer in table {
+                self.write_field_vec_with_length(layer)?;
+            }
+        }
+        self.write_hash::<F, C::Hasher>(*circuit_digest)
+    }
+
+    /// Writes a value `verifier_only_data` of type [`VerifierOnlyCircuitData`] to `self.`
+    #[inline]
+    fn write_verifier_only_circuit_data<F, C, const D: usize>(
+        &mut self,
+        verifier_only_data: &VerifierOnlyCircuitData<C, D>,
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        self.write_usize(verifier_only_data.constants_sigmas_cap.height())?;
+        self.write_merkle_cap(&verifier_only_data.constants_sigmas_cap)?;
+        self.write_hash::<F, C::Hasher>(verifier_only_data.circuit_digest)
+    }
+
+    /// Writes a value `circuit_data` of type [`CircuitData`] to `self.`
+    #[inline]
+    fn write_circuit_data<F, C, const D: usize>(
+        &mut self,
+        circuit_data: &CircuitData<F, C, D>,
+        gate_serializer: &dyn GateSerializer<F, D>,
+        generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        self.write_common_circuit_data(&circuit_data.common, gate_serializer)?;
+        self.write_prover_only_circuit_data(&circuit_data.prover_only, generator_serializer)?;
+        self.write_verifier_only_circuit_data(&circuit_data.verifier_only)
+    }
+
+    /// Writes a value `prover_data` of type [`ProverCircuitData`] to `self.`
+    #[inline]
+    fn write_prover_circuit_data<F, C, const D: usize>(
+        &mut self,
+        prover_data: &ProverCircuitData<F, C, D>,
+        gate_serializer: &dyn GateSerializer<F, D>,
+        generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        self.write_common_circuit_data(&prover_data.common, gate_serializer)?;
+        self.write_prover_only_circuit_data(&prover_data.prover_only, generator_serializer)
+    }
+
+    /// Writes a value `verifier_data` of type [`VerifierCircuitData`] to `self.`
+    #[inline]
+    fn write_verifier_circuit_data<F, C, const D: usize>(
+        &mut self,
+        verifier_data: &VerifierCircuitData<F, C, D>,
+        gate_serializer: &dyn GateSerializer<F, D>,
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        self.write_common_circuit_data(&verifier_data.common, gate_serializer)?;
+        self.write_verifier_only_circuit_data(&verifier_data.verifier_only)
+    }
+
+    /// Writes a value `os` of type [`OpeningSet`] to `self.`
+    #[inline]
+    fn write_opening_set<F, const D: usize>(&mut self, os: &OpeningSet<F, D>) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+    {
+        self.write_field_ext_vec::<F, D>(&os.constants)?;
+        self.write_field_ext_vec::<F, D>(&os.plonk_sigmas)?;
+        self.write_field_ext_vec::<F, D>(&os.wires)?;
+        self.write_field_ext_vec::<F, D>(&os.plonk_zs)?;
+        self.write_field_ext_vec::<F, D>(&os.plonk_zs_next)?;
+        self.write_field_ext_vec::<F, D>(&os.partial_products)?;
+        self.write_field_ext_vec::<F, D>(&os.quotient_polys)?;
+        self.write_field_ext_vec::<F, D>(&os.lookup_zs)?;
+        self.write_field_ext_vec::<F, D>(&os.lookup_zs_next)
+    }
+
+    /// Writes a value `p` of type [`MerkleProof`] to `self.`
+    #[inline]
+    fn write_merkle_proof<F, H>(&mut self, p: &MerkleProof<F, H>) -> IoResult<()>
+    where
+        F: RichField,
+        H: Hasher<F>,
+    {
+        let length = p.siblings.len();
+        self.write_u8(
+            length
+                .try_into()
+                .expect("Merkle proof length must fit in u8."),
+        )?;
+        for &h in &p.siblings {
+            self.write_hash::<F, H>(h)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a value `fitp` of type [`FriInitialTreeProof`] to `self.`
+    #[inline]
+    fn write_fri_initial_proof<F, C, const D: usize>(
+        &mut self,
+        fitp: &FriInitialTreeProof<F, C::Hasher>,
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        for (v, p) in &fitp.evals_proofs {
+            self.write_field_vec(v)?;
+            self.write_merkle_proof(p)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a value `fqs` of type [`FriQueryStep`] to `self.`
+    #[inline]
+    fn write_fri_query_step<F, C, const D: usize>(
+        &mut self,
+        fqs: &FriQueryStep<F, C::Hasher, D>,
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        self.write_field_ext_vec::<F, D>(&fqs.evals)?;
+        self.write_merkle_proof(&fqs.merkle_proof)
+    }
+
+    /// Writes a value `fqrs` of type [`FriQueryRound`] to `self.`
+    #[inline]
+    fn write_fri_query_rounds<F, C, const D: usize>(
+        &mut self,
+        fqrs: &[FriQueryRound<F, C::Hasher, D>],
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        for fqr in fqrs {
+            self.write_fri_initial_proof::<F, C, D>(&fqr.initial_trees_proof)?;
+            for fqs in &fqr.steps {
+                self.write_fri_query_step::<F, C, D>(fqs)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a value `fq` of type [`FriProof`] to `self.`
+    #[inline]
+    fn write_fri_proof<F, C, const D: usize>(
+        &mut self,
+        fp: &FriProof<F, C::Hasher, D>,
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        for cap in &fp.commit_phase_merkle_caps {
+            self.write_merkle_cap(cap)?;
+        }
+        self.write_fri_query_rounds::<F, C, D>(&fp.query_round_proofs)?;
+        self.write_field_ext_vec::<F, D>(&fp.final_poly.coeffs)?;
+        self.write_field(fp.pow_witness)
+    }
+
+    /// Writes a value `proof` of type [`Proof`] to `self.`
+    #[inline]
+    fn write_proof<F, C, const D: usize>(&mut self, proof: &Proof<F, C, D>) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        self.write_merkle_cap(&proof.wires_cap)?;
+        self.write_merkle_cap(&proof.plonk_zs_partial_products_cap)?;
+        self.write_merkle_cap(&proof.quotient_polys_cap)?;
+        self.write_opening_set(&proof.openings)?;
+        self.write_fri_proof::<F, C, D>(&proof.opening_proof)
+    }
+
+    /// Writes a value `proof_with_pis` of type [`ProofWithPublicInputs`] to `self.`
+    #[inline]
+    fn write_proof_with_public_inputs<F, C, const D: usize>(
+        &mut self,
+        proof_with_pis: &ProofWithPublicInputs<F, C, D>,
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let ProofWithPublicInputs {
+            proof,
+            public_inputs,
+        } = proof_with_pis;
+        self.write_proof(proof)?;
+        self.write_field_vec(public_inputs)
+    }
+
+    /// Writes a value `cfqrs` of type [`CompressedFriQueryRounds`] to `self.`
+    #[inline]
+    fn write_compressed_fri_query_rounds<F, C, const D: usize>(
+        &mut self,
+        cfqrs: &CompressedFriQueryRounds<F, C::Hasher, D>,
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        for &i in &cfqrs.indices {
+            self.write_u32(i as u32)?;
+        }
+        let mut initial_trees_proofs = cfqrs.initial_trees_proofs.iter().collect::<Vec<_>>();
+        initial_trees_proofs.sort_by_key(|&x| x.0);
+        for (_, itp) in initial_trees_proofs {
+            self.write_fri_initial_proof::<F, C, D>(itp)?;
+        }
+        for h in &cfqrs.steps {
+            let mut fri_query_steps = h.iter().collect::<Vec<_>>();
+            fri_query_steps.sort_by_key(|&x| x.0);
+            for (_, fqs) in fri_query_steps {
+                self.write_fri_query_step::<F, C, D>(fqs)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a value `fq` of type [`CompressedFriProof`] to `self.`
+    #[inline]
+    fn write_compressed_fri_proof<F, C, const D: usize>(
+        &mut self,
+        fp: &CompressedFriProof<F, C::Hasher, D>,
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        for cap in &fp.commit_phase_merkle_caps {
+            self.write_merkle_cap(cap)?;
+        }
+        self.write_compressed_fri_query_rounds::<F, C, D>(&fp.query_round_proofs)?;
+        self.write_field_ext_vec::<F, D>(&fp.final_poly.coeffs)?;
+        self.write_field(fp.pow_witness)
+    }
+
+    /// Writes a value `proof` of type [`CompressedProof`] to `self.`
+    #[inline]
+    fn write_compressed_proof<F, C, const D: usize>(
+        &mut self,
+        proof: &CompressedProof<F, C, D>,
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        self.write_merkle_cap(&proof.wires_cap)?;
+        self.write_merkle_cap(&proof.plonk_zs_partial_products_cap)?;
+        self.write_merkle_cap(&proof.quotient_polys_cap)?;
+        self.write_opening_set(&proof.openings)?;
+        self.write_compressed_fri_proof::<F, C, D>(&proof.opening_proof)
+    }
+
+    /// Writes a value `proof_with_pis` of type [`CompressedProofWithPublicInputs`] to `self.`
+    #[inline]
+    fn write_compressed_proof_with_public_inputs<F, C, const D: usize>(
+        &mut self,
+        proof_with_pis: &CompressedProofWithPublicInputs<F, C, D>,
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+    {
+        let CompressedProofWithPublicInputs {
+            proof,
+            public_inputs,
+        } = proof_with_pis;
+        self.write_compressed_proof(proof)?;
+        self.write_field_vec(public_inputs)
+    }
+}
+
+impl Write for Vec<u8> {
+    type Error = Infallible;
+
+    #[inline]
+    fn write_all(&mut self, bytes: &[u8]) -> IoResult<()> {
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn write_gate<F, const D: usize>(
+        &mut self,
+        gate: &GateRef<F, D>,
+        gate_serializer: &dyn GateSerializer<F, D>,
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+    {
+        gate_serializer.write_gate(self, gate)
+    }
+
+    fn write_generator<F, const D: usize>(
+        &mut self,
+        generator: &WitnessGeneratorRef<F>,
+        generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
+    ) -> IoResult<()>
+    where
+        F: RichField + Extendable<D>,
+    {
+        generator_serializer.write_generator(self, generator)
+    }
+}
+
+/// A read cursor over a byte slice.
+#[derive(Debug)]
+pub struct Buffer<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Buffer<'a> {
+    /// Builds a new [`Buffer`] over `bytes`.
+    #[inline]
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// Returns the inner buffer.
+    #[inline]
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the bytes which have not been read yet.
+    #[inline]
+    pub fn unread_bytes(&self) -> &'a [u8] {
+        &self.bytes[self.pos..]
+    }
+}
+
+impl<'a> Remaining for Buffer<'a> {
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+}
+
+impl<'a> Read for Buffer<'a> {
+    #[inline]
+    fn read_exact(&mut self, bytes: &mut [u8]) -> IoResult<()> {
+        let n = bytes.len();
+        if self.remaining() < n {
+            Err(IoError)
+        } else {
+            bytes.copy_from_slice(&self.bytes[self.pos..][..n]);
+            self.pos += n;
+            Ok(())
+        }
+    }
+
+    fn read_gate<F, const D: usize>(
+        &mut self,
+        gate_serializer: &dyn GateSerializer<F, D>,
+    ) -> IoResult<GateRef<F, D>>
+    where
+        F: RichField + Extendable<D>,
+    {
+        gate_serializer.read_gate(self)
+    }
+
+    fn read_generator<F, const D: usize>(
+        &mut self,
+        generator_serializer: &dyn WitnessGeneratorSerializer<F, D>,
+    ) -> IoResult<WitnessGeneratorRef<F>>
+    where
+        F: RichField + Extendable<D>,
+    {
+        generator_serializer.read_generator(self)
+    }
+}
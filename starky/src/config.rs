@@ -1,5 +1,21 @@
+#[cfg(feature = "std")]
+use alloc::vec;
+
+#[cfg(feature = "std")]
+use plonky2::field::extension::Extendable;
 use plonky2::fri::reduction_strategies::FriReductionStrategy;
+#[cfg(feature = "std")]
+use plonky2::fri::security::{security_report, SecurityReport, SoundnessParams};
 use plonky2::fri::{FriConfig, FriParams};
+#[cfg(feature = "std")]
+use plonky2::hash::hash_types::RichField;
+
+#[cfg(feature = "std")]
+use crate::constraint_consumer::ConstraintConsumer;
+#[cfg(feature = "std")]
+use crate::stark::Stark;
+#[cfg(feature = "std")]
+use crate::vars::StarkEvaluationVars;
 
 pub struct StarkConfig {
     pub security_bits: usize,
@@ -9,6 +25,16 @@ pub struct StarkConfig {
     pub num_challenges: usize,
 
     pub fri_config: FriConfig,
+
+    /// Whether proofs should hide the trace. If so, each committed polynomial `p` is randomized as
+    /// `p + Z_H r`, where `Z_H` is the zero polynomial of the trace domain `H` and `r` is random
+    /// of degree less than `n = |H|`. This leaves `p` unchanged on `H`, but doubles its degree, so
+    /// FRI is run for degree `2n`. Merkle leaves are also salted, so that commitments are hiding.
+    ///
+    /// Openings reveal nothing about the trace as long as `n` exceeds the number of evaluations
+    /// revealed per polynomial, i.e. the out-of-domain openings plus the FRI queries; provers
+    /// reject shorter traces.
+    pub zero_knowledge: bool,
 }
 
 impl StarkConfig {
@@ -25,10 +51,110 @@ impl StarkConfig {
                 reduction_strategy: FriReductionStrategy::ConstantArityBits(4, 5),
                 num_query_rounds: 84,
             },
+            zero_knowledge: false,
+        }
+    }
+
+    /// A zero-knowledge variant of `standard_fast_config`. It uses a rate of 4, since the
+    /// quotient polynomials have a higher degree relative to the committed polynomials, which
+    /// also halves the number of queries.
+    /// Targets ~100 bit conjectured security.
+    pub fn standard_fast_zk_config() -> Self {
+        Self {
+            security_bits: 100,
+            num_challenges: 2,
+            fri_config: FriConfig {
+                rate_bits: 2,
+                cap_height: 4,
+                proof_of_work_bits: 16,
+                reduction_strategy: FriReductionStrategy::ConstantArityBits(4, 5),
+                num_query_rounds: 42,
+            },
+            zero_knowledge: true,
         }
     }
 
+    /// The log of the ratio between the degree of the committed polynomials and the trace length.
+    pub fn blinding_degree_bits(&self) -> usize {
+        usize::from(self.zero_knowledge)
+    }
+
+    /// The number of evaluations of each committed polynomial revealed by a proof: the openings
+    /// at `zeta` and `g * zeta`, and one per FRI query. In zero-knowledge mode, traces must be
+    /// longer than this for the openings to hide them.
+    pub fn num_revealed_evaluations(&self) -> usize {
+        2 + self.fri_config.num_query_rounds
+    }
+
+    /// The FRI parameters for a trace of length `2^degree_bits`.
     pub(crate) fn fri_params(&self, degree_bits: usize) -> FriParams {
-        self.fri_config.fri_params(degree_bits, false)
+        self.fri_config.fri_params(
+            degree_bits + self.blinding_degree_bits(),
+            self.zero_knowledge,
+        )
+    }
+
+    /// The parameters on which the soundness of proofs of `stark` over a trace of length
+    /// `2^degree_bits` depends; see [`security_report`](Self::security_report).
+    #[cfg(feature = "std")]
+    pub fn soundness_params<F, S, const D: usize>(
+        &self,
+        stark: &S,
+        degree_bits: usize,
+    ) -> SoundnessParams
+    where
+        F: RichField + Extendable<D>,
+        S: Stark<F, D>,
+        [(); S::COLUMNS]:,
+        [(); S::PUBLIC_INPUTS]:,
+    {
+        // Count the constraints by evaluating them once, at arbitrary values.
+        let mut consumer = ConstraintConsumer::<F>::new(vec![F::ONE], F::ONE, F::ONE, F::ONE);
+        stark.eval_packed_base(
+            StarkEvaluationVars {
+                local_values: &[F::ZERO; S::COLUMNS],
+                next_values: &[F::ZERO; S::COLUMNS],
+                public_inputs: &[F::ZERO; S::PUBLIC_INPUTS],
+            },
+            &mut consumer,
+        );
+        let num_permutation_zs = if stark.uses_permutation_args() {
+            stark.num_permutation_batches(self)
+        } else {
+            0
+        };
+
+        SoundnessParams {
+            field_bits: F::ORDER.ilog2() as usize,
+            extension_degree: D,
+            degree_bits: degree_bits + self.blinding_degree_bits(),
+            num_polys: S::COLUMNS + num_permutation_zs + stark.num_quotient_polys(self),
+            constraint_degree: stark.constraint_degree(),
+            num_constraints: consumer.num_constraints() + num_permutation_zs,
+            num_challenges: self.num_challenges,
+            permutation_width: stark
+                .permutation_pairs()
+                .iter()
+                .map(|pair| pair.column_pairs.len())
+                .sum(),
+            lookup_width: 0,
+        }
+    }
+
+    /// The proven and conjectured soundness of proofs of `stark` over a trace of length
+    /// `2^degree_bits`.
+    #[cfg(feature = "std")]
+    pub fn security_report<F, S, const D: usize>(
+        &self,
+        stark: &S,
+        degree_bits: usize,
+    ) -> SecurityReport
+    where
+        F: RichField + Extendable<D>,
+        S: Stark<F, D>,
+        [(); S::COLUMNS]:,
+        [(); S::PUBLIC_INPUTS]:,
+    {
+        security_report(&self.fri_config, &self.soundness_params(stark, degree_bits))
     }
 }
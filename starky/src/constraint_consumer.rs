@@ -18,6 +18,9 @@ pub struct ConstraintConsumer<P: PackedField> {
     // result, it should be made private.
     pub constraint_accs: Vec<P>,
 
+    /// The number of constraints emitted so far.
+    num_constraints: usize,
+
     /// The evaluation of `X - g^(n-1)`.
     z_last: P,
 
@@ -40,6 +43,7 @@ impl<P: PackedField> ConstraintConsumer<P> {
         Self {
             constraint_accs: vec![P::ZEROS; alphas.len()],
             alphas,
+            num_constraints: 0,
             z_last,
             lagrange_basis_first,
             lagrange_basis_last,
@@ -50,6 +54,10 @@ impl<P: PackedField> ConstraintConsumer<P> {
         self.constraint_accs
     }
 
+    pub fn num_constraints(&self) -> usize {
+        self.num_constraints
+    }
+
     /// Add one constraint valid on all rows except the last.
     pub fn constraint_transition(&mut self, constraint: P) {
         self.constraint(constraint * self.z_last);
@@ -57,6 +65,7 @@ impl<P: PackedField> ConstraintConsumer<P> {
 
     /// Add one constraint on all rows.
     pub fn constraint(&mut self, constraint: P) {
+        self.num_constraints += 1;
         for (&alpha, acc) in self.alphas.iter().zip(&mut self.constraint_accs) {
             *acc *= alpha;
             *acc += constraint;
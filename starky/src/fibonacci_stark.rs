@@ -174,6 +174,66 @@ mod tests {
         verify_stark_proof(stark, proof, &config)
     }
 
+    #[test]
+    fn test_fibonacci_stark_zk() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type S = FibonacciStark<F, D>;
+
+        let config = StarkConfig::standard_fast_zk_config();
+        let num_rows = 1 << 6;
+        let public_inputs = [F::ZERO, F::ONE, fibonacci(num_rows - 1, F::ZERO, F::ONE)];
+        let stark = S::new(num_rows);
+        let trace = stark.generate_trace(public_inputs[0], public_inputs[1]);
+        let proof = prove::<F, C, S, D>(
+            stark,
+            &config,
+            trace.clone(),
+            public_inputs,
+            &mut TimingTree::default(),
+        )?;
+        // Proofs of the same trace differ, since the committed polynomials are randomized.
+        let other_proof = prove::<F, C, S, D>(
+            stark,
+            &config,
+            trace,
+            public_inputs,
+            &mut TimingTree::default(),
+        )?;
+        assert_ne!(proof.proof.trace_cap, other_proof.proof.trace_cap);
+        assert_ne!(
+            proof.proof.openings.local_values,
+            other_proof.proof.openings.local_values
+        );
+
+        verify_stark_proof(stark, proof, &config)?;
+        verify_stark_proof(stark, other_proof, &config)
+    }
+
+    #[test]
+    #[should_panic(expected = "can't hide")]
+    fn test_fibonacci_stark_zk_short_trace() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type S = FibonacciStark<F, D>;
+
+        // 32 rows are too few to hide the 2 openings and 42 FRI queries of this config.
+        let config = StarkConfig::standard_fast_zk_config();
+        let num_rows = 1 << 5;
+        let public_inputs = [F::ZERO, F::ONE, fibonacci(num_rows - 1, F::ZERO, F::ONE)];
+        let stark = S::new(num_rows);
+        let trace = stark.generate_trace(public_inputs[0], public_inputs[1]);
+        let _ = prove::<F, C, S, D>(
+            stark,
+            &config,
+            trace,
+            public_inputs,
+            &mut TimingTree::default(),
+        );
+    }
+
     #[test]
     fn test_fibonacci_stark_degree() -> Result<()> {
         const D: usize = 2;
@@ -186,6 +246,30 @@ mod tests {
         test_stark_low_degree(stark)
     }
 
+    #[test]
+    fn test_fibonacci_stark_security() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type S = FibonacciStark<F, D>;
+
+        let config = StarkConfig::standard_fast_config();
+        let num_rows = 1 << 5;
+        let stark = S::new(num_rows);
+        let params = config.soundness_params(&stark, 5);
+        // The five constraints of `eval_packed_generic`, and one per permutation Z.
+        assert_eq!(
+            params.num_constraints,
+            5 + stark.num_permutation_batches(&config)
+        );
+        assert_eq!(params.permutation_width, 1);
+
+        let report = config.security_report(&stark, 5);
+        // 84 queries at rate 1/2 with 16 bits of proof of work.
+        assert_eq!(report.conjectured.fri_queries, 100.0);
+        assert!(report.proven.total() < report.conjectured.total());
+    }
+
     #[test]
     fn test_fibonacci_stark_circuit() -> Result<()> {
         const D: usize = 2;
@@ -223,6 +307,31 @@ mod tests {
         recursive_proof::<F, C, S, C, D>(stark, proof, &config, true)
     }
 
+    #[test]
+    fn test_recursive_stark_verifier_zk() -> Result<()> {
+        init_logger();
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type S = FibonacciStark<F, D>;
+
+        let config = StarkConfig::standard_fast_zk_config();
+        let num_rows = 1 << 6;
+        let public_inputs = [F::ZERO, F::ONE, fibonacci(num_rows - 1, F::ZERO, F::ONE)];
+        let stark = S::new(num_rows);
+        let trace = stark.generate_trace(public_inputs[0], public_inputs[1]);
+        let proof = prove::<F, C, S, D>(
+            stark,
+            &config,
+            trace,
+            public_inputs,
+            &mut TimingTree::default(),
+        )?;
+        verify_stark_proof(stark, proof.clone(), &config)?;
+
+        recursive_proof::<F, C, S, C, D>(stark, proof, &config, false)
+    }
+
     fn recursive_proof<
         F: RichField + Extendable<D>,
         C: GenericConfig<D, F = F>,
@@ -245,7 +354,8 @@ mod tests {
         let mut builder = CircuitBuilder::<F, D>::new(circuit_config);
         let mut pw = PartialWitness::new();
         let degree_bits = inner_proof.proof.recover_degree_bits(inner_config);
-        let pt = add_virtual_stark_proof_with_pis(&mut builder, stark, inner_config, degree_bits);
+        let pt =
+            add_virtual_stark_proof_with_pis(&mut builder, &stark, inner_config, degree_bits, 0);
         set_stark_proof_with_pis_target(&mut pw, &pt, &inner_proof);
 
         verify_stark_proof_circuit::<F, InnerC, S, D>(&mut builder, stark, pt, inner_config);
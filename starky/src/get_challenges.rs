@@ -13,15 +13,15 @@ use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
 
 use crate::config::StarkConfig;
 use crate::permutation::{
-    get_n_permutation_challenge_sets, get_n_permutation_challenge_sets_target,
+    get_n_grand_product_challenge_sets, get_n_grand_product_challenge_sets_target,
 };
 use crate::proof::*;
 use crate::stark::Stark;
 
 fn get_challenges<F, C, S, const D: usize>(
+    challenger: &mut Challenger<F, C::Hasher>,
     stark: &S,
-    trace_cap: &MerkleCap<F, C::Hasher>,
-    permutation_zs_cap: Option<&MerkleCap<F, C::Hasher>>,
+    permutation_ctl_zs_cap: Option<&MerkleCap<F, C::Hasher>>,
     quotient_polys_cap: &MerkleCap<F, C::Hasher>,
     openings: &StarkOpeningSet<F, D>,
     commit_phase_merkle_caps: &[MerkleCap<F, C::Hasher>],
@@ -37,19 +37,16 @@ where
 {
     let num_challenges = config.num_challenges;
 
-    let mut challenger = Challenger::<F, C::Hasher>::new();
-
-    challenger.observe_cap(trace_cap);
-
-    let permutation_challenge_sets = permutation_zs_cap.map(|permutation_zs_cap| {
-        let tmp = get_n_permutation_challenge_sets(
-            &mut challenger,
+    let permutation_challenge_sets = stark.uses_permutation_args().then(|| {
+        get_n_grand_product_challenge_sets(
+            challenger,
             num_challenges,
             stark.permutation_batch_size(),
-        );
-        challenger.observe_cap(permutation_zs_cap);
-        tmp
+        )
     });
+    if let Some(cap) = permutation_ctl_zs_cap {
+        challenger.observe_cap(cap);
+    }
 
     let stark_alphas = challenger.get_n_challenges(num_challenges);
 
@@ -66,7 +63,7 @@ where
             commit_phase_merkle_caps,
             final_poly,
             pow_witness,
-            degree_bits,
+            degree_bits + config.blinding_degree_bits(),
             &config.fri_config,
         ),
     }
@@ -96,10 +93,30 @@ where
         stark: &S,
         config: &StarkConfig,
         degree_bits: usize,
+    ) -> StarkProofChallenges<F, D> {
+        let mut challenger = Challenger::<F, C::Hasher>::new();
+        challenger.observe_cap(&self.proof.trace_cap);
+        self.proof
+            .get_challenges(&mut challenger, stark, config, degree_bits)
+    }
+}
+
+impl<F, C, const D: usize> StarkProof<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    /// Computes the Fiat-Shamir challenges used in the STARK proof, starting from a challenger
+    /// which has already observed the trace cap(s).
+    pub(crate) fn get_challenges<S: Stark<F, D>>(
+        &self,
+        challenger: &mut Challenger<F, C::Hasher>,
+        stark: &S,
+        config: &StarkConfig,
+        degree_bits: usize,
     ) -> StarkProofChallenges<F, D> {
         let StarkProof {
-            trace_cap,
-            permutation_zs_cap,
+            permutation_ctl_zs_cap,
             quotient_polys_cap,
             openings,
             opening_proof:
@@ -109,12 +126,13 @@ where
                     pow_witness,
                     ..
                 },
-        } = &self.proof;
+            ..
+        } = self;
 
         get_challenges::<F, C, S, D>(
+            challenger,
             stark,
-            trace_cap,
-            permutation_zs_cap.as_ref(),
+            permutation_ctl_zs_cap.as_ref(),
             quotient_polys_cap,
             openings,
             commit_phase_merkle_caps,
@@ -134,9 +152,9 @@ pub(crate) fn get_challenges_target<
     const D: usize,
 >(
     builder: &mut CircuitBuilder<F, D>,
+    challenger: &mut RecursiveChallenger<F, C::Hasher, D>,
     stark: &S,
-    trace_cap: &MerkleCapTarget,
-    permutation_zs_cap: Option<&MerkleCapTarget>,
+    permutation_ctl_zs_cap: Option<&MerkleCapTarget>,
     quotient_polys_cap: &MerkleCapTarget,
     openings: &StarkOpeningSetTarget<D>,
     commit_phase_merkle_caps: &[MerkleCapTarget],
@@ -149,27 +167,24 @@ where
 {
     let num_challenges = config.num_challenges;
 
-    let mut challenger = RecursiveChallenger::<F, C::Hasher, D>::new(builder);
-
-    challenger.observe_cap(trace_cap);
-
-    let permutation_challenge_sets = permutation_zs_cap.map(|permutation_zs_cap| {
-        let tmp = get_n_permutation_challenge_sets_target(
+    let permutation_challenge_sets = stark.uses_permutation_args().then(|| {
+        get_n_grand_product_challenge_sets_target(
             builder,
-            &mut challenger,
+            challenger,
             num_challenges,
             stark.permutation_batch_size(),
-        );
-        challenger.observe_cap(permutation_zs_cap);
-        tmp
+        )
     });
+    if let Some(cap) = permutation_ctl_zs_cap {
+        challenger.observe_cap(cap);
+    }
 
     let stark_alphas = challenger.get_n_challenges(builder, num_challenges);
 
     challenger.observe_cap(quotient_polys_cap);
     let stark_zeta = challenger.get_extension_challenge(builder);
 
-    challenger.observe_openings(&openings.to_fri_openings());
+    challenger.observe_openings(&openings.to_fri_openings(builder.zero()));
 
     StarkProofChallengesTarget {
         permutation_challenge_sets,
@@ -196,12 +211,35 @@ impl<const D: usize> StarkProofWithPublicInputsTarget<D> {
         stark: &S,
         config: &StarkConfig,
     ) -> StarkProofChallengesTarget<D>
+    where
+        C::Hasher: AlgebraicHasher<F>,
+    {
+        let mut challenger = RecursiveChallenger::<F, C::Hasher, D>::new(builder);
+        challenger.observe_cap(&self.proof.trace_cap);
+        self.proof
+            .get_challenges::<F, C, S>(builder, &mut challenger, stark, config)
+    }
+}
+
+impl<const D: usize> StarkProofTarget<D> {
+    /// Computes the Fiat-Shamir challenges used in the STARK proof, starting from a challenger
+    /// which has already observed the trace cap(s).
+    pub(crate) fn get_challenges<
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+        S: Stark<F, D>,
+    >(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        challenger: &mut RecursiveChallenger<F, C::Hasher, D>,
+        stark: &S,
+        config: &StarkConfig,
+    ) -> StarkProofChallengesTarget<D>
     where
         C::Hasher: AlgebraicHasher<F>,
     {
         let StarkProofTarget {
-            trace_cap,
-            permutation_zs_cap,
+            permutation_ctl_zs_cap,
             quotient_polys_cap,
             openings,
             opening_proof:
@@ -211,13 +249,14 @@ impl<const D: usize> StarkProofWithPublicInputsTarget<D> {
                     pow_witness,
                     ..
                 },
-        } = &self.proof;
+            ..
+        } = self;
 
         get_challenges_target::<F, C, S, D>(
             builder,
+            challenger,
             stark,
-            trace_cap,
-            permutation_zs_cap.as_ref(),
+            permutation_ctl_zs_cap.as_ref(),
             quotient_polys_cap,
             openings,
             commit_phase_merkle_caps,
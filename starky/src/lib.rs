@@ -10,6 +10,8 @@ mod get_challenges;
 
 pub mod config;
 pub mod constraint_consumer;
+pub mod cross_table_lookup;
+pub mod multi_stark;
 pub mod permutation;
 pub mod proof;
 pub mod prover;
@@ -23,3 +25,5 @@ pub mod verifier;
 
 #[cfg(test)]
 pub mod fibonacci_stark;
+#[cfg(test)]
+pub mod squares_stark;
@@ -0,0 +1,165 @@
+//! Support for systems of several STARKs, or tables, whose traces are connected by cross-table
+//! lookups. The tables of a system usually have different types, so the multi-STARK prover and
+//! verifiers take them as [`StarkTable`] trait objects, in the order of their table indices.
+
+use alloc::vec::Vec;
+
+use anyhow::{ensure, Result};
+use plonky2::field::extension::Extendable;
+use plonky2::field::polynomial::PolynomialValues;
+use plonky2::fri::oracle::PolynomialBatch;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::challenger::{Challenger, RecursiveChallenger};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
+use plonky2::util::timing::TimingTree;
+
+use crate::config::StarkConfig;
+use crate::cross_table_lookup::{CtlCheckVars, CtlCheckVarsTarget, CtlData};
+use crate::proof::{StarkProofWithPublicInputs, StarkProofWithPublicInputsTarget};
+use crate::prover::prove_with_commitment;
+use crate::recursive_verifier::{
+    add_virtual_stark_proof_with_pis, verify_stark_proof_with_ctl_circuit,
+};
+use crate::stark::Stark;
+use crate::verifier::verify_stark_proof_with_ctl;
+
+/// One table of a multi-STARK system. This is implemented for every [`Stark`], and only exists so
+/// that tables of different types can be passed together to `prove_with_ctls` and the matching
+/// verifiers.
+pub trait StarkTable<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> {
+    fn num_permutation_batches(&self, config: &StarkConfig) -> usize;
+
+    /// See [`prove_with_commitment`].
+    fn prove_with_commitment(
+        &self,
+        config: &StarkConfig,
+        trace_poly_values: &[PolynomialValues<F>],
+        trace_commitment: &PolynomialBatch<F, C, D>,
+        ctl_data: &CtlData<F>,
+        public_inputs: &[F],
+        challenger: &mut Challenger<F, C::Hasher>,
+        timing: &mut TimingTree,
+    ) -> Result<StarkProofWithPublicInputs<F, C, D>>;
+
+    /// See [`verify_stark_proof_with_ctl`].
+    fn verify_with_ctl(
+        &self,
+        proof_with_pis: StarkProofWithPublicInputs<F, C, D>,
+        ctl_vars: &[CtlCheckVars<F, F::Extension, F::Extension, D>],
+        challenger: &mut Challenger<F, C::Hasher>,
+        config: &StarkConfig,
+    ) -> Result<()>;
+
+    /// See [`add_virtual_stark_proof_with_pis`].
+    fn add_virtual_proof_with_pis(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        config: &StarkConfig,
+        degree_bits: usize,
+        num_ctl_zs: usize,
+    ) -> StarkProofWithPublicInputsTarget<D>;
+
+    /// See [`verify_stark_proof_with_ctl_circuit`].
+    fn verify_with_ctl_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        proof_with_pis: StarkProofWithPublicInputsTarget<D>,
+        ctl_vars: &[CtlCheckVarsTarget<F, D>],
+        challenger: &mut RecursiveChallenger<F, C::Hasher, D>,
+        inner_config: &StarkConfig,
+    ) where
+        C::Hasher: AlgebraicHasher<F>;
+}
+
+impl<F, C, S, const D: usize> StarkTable<F, C, D> for S
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    S: Stark<F, D>,
+    [(); S::COLUMNS]:,
+    [(); S::PUBLIC_INPUTS]:,
+    [(); C::Hasher::HASH_SIZE]:,
+{
+    fn num_permutation_batches(&self, config: &StarkConfig) -> usize {
+        Stark::num_permutation_batches(self, config)
+    }
+
+    fn prove_with_commitment(
+        &self,
+        config: &StarkConfig,
+        trace_poly_values: &[PolynomialValues<F>],
+        trace_commitment: &PolynomialBatch<F, C, D>,
+        ctl_data: &CtlData<F>,
+        public_inputs: &[F],
+        challenger: &mut Challenger<F, C::Hasher>,
+        timing: &mut TimingTree,
+    ) -> Result<StarkProofWithPublicInputs<F, C, D>> {
+        ensure!(public_inputs.len() == S::PUBLIC_INPUTS);
+        prove_with_commitment(
+            self,
+            config,
+            trace_poly_values,
+            trace_commitment,
+            ctl_data,
+            public_inputs.try_into().unwrap(),
+            challenger,
+            timing,
+        )
+    }
+
+    fn verify_with_ctl(
+        &self,
+        proof_with_pis: StarkProofWithPublicInputs<F, C, D>,
+        ctl_vars: &[CtlCheckVars<F, F::Extension, F::Extension, D>],
+        challenger: &mut Challenger<F, C::Hasher>,
+        config: &StarkConfig,
+    ) -> Result<()> {
+        verify_stark_proof_with_ctl(self, proof_with_pis, ctl_vars, challenger, config)
+    }
+
+    fn add_virtual_proof_with_pis(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        config: &StarkConfig,
+        degree_bits: usize,
+        num_ctl_zs: usize,
+    ) -> StarkProofWithPublicInputsTarget<D> {
+        add_virtual_stark_proof_with_pis(builder, self, config, degree_bits, num_ctl_zs)
+    }
+
+    fn verify_with_ctl_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        proof_with_pis: StarkProofWithPublicInputsTarget<D>,
+        ctl_vars: &[CtlCheckVarsTarget<F, D>],
+        challenger: &mut RecursiveChallenger<F, C::Hasher, D>,
+        inner_config: &StarkConfig,
+    ) where
+        C::Hasher: AlgebraicHasher<F>,
+    {
+        verify_stark_proof_with_ctl_circuit::<F, C, S, D>(
+            builder,
+            self,
+            proof_with_pis,
+            ctl_vars,
+            challenger,
+            inner_config,
+        )
+    }
+}
+
+/// The number of permutation `Z` polynomials of each table.
+pub(crate) fn num_permutation_zs<F, C, const D: usize>(
+    tables: &[&dyn StarkTable<F, C, D>],
+    config: &StarkConfig,
+) -> Vec<usize>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    tables
+        .iter()
+        .map(|table| table.num_permutation_batches(config))
+        .collect()
+}
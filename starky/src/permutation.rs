@@ -2,6 +2,7 @@
 
 use alloc::vec;
 use alloc::vec::Vec;
+use core::fmt::Debug;
 
 use itertools::Itertools;
 use plonky2::field::batch_util::batch_multiply_inplace;
@@ -15,6 +16,7 @@ use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::iop::target::Target;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::config::{AlgebraicHasher, Hasher};
+use plonky2::plonk::plonk_common::{reduce_with_powers, reduce_with_powers_ext_circuit};
 use plonky2::util::reducing::{ReducingFactor, ReducingFactorTarget};
 use plonky2_maybe_rayon::*;
 
@@ -42,24 +44,50 @@ impl PermutationPair {
 }
 
 /// A single instance of a permutation check protocol.
-pub(crate) struct PermutationInstance<'a, T: Copy> {
+pub(crate) struct PermutationInstance<'a, T: Copy + Eq + PartialEq + Debug> {
     pub(crate) pair: &'a PermutationPair,
-    pub(crate) challenge: PermutationChallenge<T>,
+    pub(crate) challenge: GrandProductChallenge<T>,
 }
 
 /// Randomness for a single instance of a permutation check protocol.
-#[derive(Copy, Clone)]
-pub(crate) struct PermutationChallenge<T: Copy> {
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct GrandProductChallenge<T: Copy + Eq + PartialEq + Debug> {
     /// Randomness used to combine multiple columns into one.
     pub(crate) beta: T,
     /// Random offset that's added to the beta-reduced column values.
     pub(crate) gamma: T,
 }
 
-/// Like `PermutationChallenge`, but with `num_challenges` copies to boost soundness.
-#[derive(Clone)]
-pub(crate) struct PermutationChallengeSet<T: Copy> {
-    pub(crate) challenges: Vec<PermutationChallenge<T>>,
+impl<F: Field> GrandProductChallenge<F> {
+    pub(crate) fn combine<'a, FE, P, T: IntoIterator<Item = &'a P>, const D2: usize>(
+        &self,
+        terms: T,
+    ) -> P
+    where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>,
+        T::IntoIter: DoubleEndedIterator,
+    {
+        reduce_with_powers(terms, FE::from_basefield(self.beta)) + FE::from_basefield(self.gamma)
+    }
+}
+
+impl GrandProductChallenge<Target> {
+    pub(crate) fn combine_circuit<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        terms: &[ExtensionTarget<D>],
+    ) -> ExtensionTarget<D> {
+        let reduced = reduce_with_powers_ext_circuit(builder, terms, self.beta);
+        let gamma = builder.convert_to_ext(self.gamma);
+        builder.add_extension(reduced, gamma)
+    }
+}
+
+/// Like `GrandProductChallenge`, but with `num_challenges` copies to boost soundness.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct GrandProductChallengeSet<T: Copy + Eq + PartialEq + Debug> {
+    pub(crate) challenges: Vec<GrandProductChallenge<T>>,
 }
 
 /// Compute all Z polynomials (for permutation arguments).
@@ -67,7 +95,7 @@ pub(crate) fn compute_permutation_z_polys<F, S, const D: usize>(
     stark: &S,
     config: &StarkConfig,
     trace_poly_values: &[PolynomialValues<F>],
-    permutation_challenge_sets: &[PermutationChallengeSet<F>],
+    permutation_challenge_sets: &[GrandProductChallengeSet<F>],
 ) -> Vec<PolynomialValues<F>>
 where
     F: RichField + Extendable<D>,
@@ -125,7 +153,7 @@ fn permutation_reduced_polys<F: Field>(
 ) -> (PolynomialValues<F>, PolynomialValues<F>) {
     let PermutationInstance {
         pair: PermutationPair { column_pairs },
-        challenge: PermutationChallenge { beta, gamma },
+        challenge: GrandProductChallenge { beta, gamma },
     } = instance;
 
     let mut reduced_lhs = PolynomialValues::constant(*gamma, degree);
@@ -149,48 +177,48 @@ fn poly_product_elementwise<F: Field>(
     product
 }
 
-fn get_permutation_challenge<F: RichField, H: Hasher<F>>(
+fn get_grand_product_challenge<F: RichField, H: Hasher<F>>(
     challenger: &mut Challenger<F, H>,
-) -> PermutationChallenge<F> {
+) -> GrandProductChallenge<F> {
     let beta = challenger.get_challenge();
     let gamma = challenger.get_challenge();
-    PermutationChallenge { beta, gamma }
+    GrandProductChallenge { beta, gamma }
 }
 
-fn get_permutation_challenge_set<F: RichField, H: Hasher<F>>(
+pub fn get_grand_product_challenge_set<F: RichField, H: Hasher<F>>(
     challenger: &mut Challenger<F, H>,
     num_challenges: usize,
-) -> PermutationChallengeSet<F> {
+) -> GrandProductChallengeSet<F> {
     let challenges = (0..num_challenges)
-        .map(|_| get_permutation_challenge(challenger))
+        .map(|_| get_grand_product_challenge(challenger))
         .collect();
-    PermutationChallengeSet { challenges }
+    GrandProductChallengeSet { challenges }
 }
 
-pub(crate) fn get_n_permutation_challenge_sets<F: RichField, H: Hasher<F>>(
+pub(crate) fn get_n_grand_product_challenge_sets<F: RichField, H: Hasher<F>>(
     challenger: &mut Challenger<F, H>,
     num_challenges: usize,
     num_sets: usize,
-) -> Vec<PermutationChallengeSet<F>> {
+) -> Vec<GrandProductChallengeSet<F>> {
     (0..num_sets)
-        .map(|_| get_permutation_challenge_set(challenger, num_challenges))
+        .map(|_| get_grand_product_challenge_set(challenger, num_challenges))
         .collect()
 }
 
-fn get_permutation_challenge_target<
+fn get_grand_product_challenge_target<
     F: RichField + Extendable<D>,
     H: AlgebraicHasher<F>,
     const D: usize,
 >(
     builder: &mut CircuitBuilder<F, D>,
     challenger: &mut RecursiveChallenger<F, H, D>,
-) -> PermutationChallenge<Target> {
+) -> GrandProductChallenge<Target> {
     let beta = challenger.get_challenge(builder);
     let gamma = challenger.get_challenge(builder);
-    PermutationChallenge { beta, gamma }
+    GrandProductChallenge { beta, gamma }
 }
 
-fn get_permutation_challenge_set_target<
+pub fn get_grand_product_challenge_set_target<
     F: RichField + Extendable<D>,
     H: AlgebraicHasher<F>,
     const D: usize,
@@ -198,14 +226,14 @@ fn get_permutation_challenge_set_target<
     builder: &mut CircuitBuilder<F, D>,
     challenger: &mut RecursiveChallenger<F, H, D>,
     num_challenges: usize,
-) -> PermutationChallengeSet<Target> {
+) -> GrandProductChallengeSet<Target> {
     let challenges = (0..num_challenges)
-        .map(|_| get_permutation_challenge_target(builder, challenger))
+        .map(|_| get_grand_product_challenge_target(builder, challenger))
         .collect();
-    PermutationChallengeSet { challenges }
+    GrandProductChallengeSet { challenges }
 }
 
-pub(crate) fn get_n_permutation_challenge_sets_target<
+pub(crate) fn get_n_grand_product_challenge_sets_target<
     F: RichField + Extendable<D>,
     H: AlgebraicHasher<F>,
     const D: usize,
@@ -214,9 +242,9 @@ pub(crate) fn get_n_permutation_challenge_sets_target<
     challenger: &mut RecursiveChallenger<F, H, D>,
     num_challenges: usize,
     num_sets: usize,
-) -> Vec<PermutationChallengeSet<Target>> {
+) -> Vec<GrandProductChallengeSet<Target>> {
     (0..num_sets)
-        .map(|_| get_permutation_challenge_set_target(builder, challenger, num_challenges))
+        .map(|_| get_grand_product_challenge_set_target(builder, challenger, num_challenges))
         .collect()
 }
 
@@ -225,9 +253,9 @@ pub(crate) fn get_n_permutation_challenge_sets_target<
 /// Before batching, each permutation pair leads to `num_challenges` permutation arguments, so we
 /// start with the cartesian product of `permutation_pairs` and `0..num_challenges`. Then we
 /// chunk these arguments based on our batch size.
-pub(crate) fn get_permutation_batches<'a, T: Copy>(
+pub(crate) fn get_permutation_batches<'a, T: Copy + Eq + PartialEq + Debug>(
     permutation_pairs: &'a [PermutationPair],
-    permutation_challenge_sets: &[PermutationChallengeSet<T>],
+    permutation_challenge_sets: &[GrandProductChallengeSet<T>],
     num_challenges: usize,
     batch_size: usize,
 ) -> Vec<Vec<PermutationInstance<'a, T>>> {
@@ -256,7 +284,7 @@ where
 {
     pub(crate) local_zs: Vec<P>,
     pub(crate) next_zs: Vec<P>,
-    pub(crate) permutation_challenge_sets: Vec<PermutationChallengeSet<F>>,
+    pub(crate) permutation_challenge_sets: Vec<GrandProductChallengeSet<F>>,
 }
 
 pub(crate) fn eval_permutation_checks<F, FE, P, S, const D: usize, const D2: usize>(
@@ -301,7 +329,7 @@ pub(crate) fn eval_permutation_checks<F, FE, P, S, const D: usize, const D2: usi
             .map(|instance| {
                 let PermutationInstance {
                     pair: PermutationPair { column_pairs },
-                    challenge: PermutationChallenge { beta, gamma },
+                    challenge: GrandProductChallenge { beta, gamma },
                 } = instance;
                 let mut factor = ReducingFactor::new(*beta);
                 let (lhs, rhs): (Vec<_>, Vec<_>) = column_pairs
@@ -323,7 +351,7 @@ pub(crate) fn eval_permutation_checks<F, FE, P, S, const D: usize, const D2: usi
 pub struct PermutationCheckDataTarget<const D: usize> {
     pub(crate) local_zs: Vec<ExtensionTarget<D>>,
     pub(crate) next_zs: Vec<ExtensionTarget<D>>,
-    pub(crate) permutation_challenge_sets: Vec<PermutationChallengeSet<Target>>,
+    pub(crate) permutation_challenge_sets: Vec<GrandProductChallengeSet<Target>>,
 }
 
 pub(crate) fn eval_permutation_checks_circuit<F, S, const D: usize>(
@@ -369,7 +397,7 @@ pub(crate) fn eval_permutation_checks_circuit<F, S, const D: usize>(
                 .map(|instance| {
                     let PermutationInstance {
                         pair: PermutationPair { column_pairs },
-                        challenge: PermutationChallenge { beta, gamma },
+                        challenge: GrandProductChallenge { beta, gamma },
                     } = instance;
                     let beta_ext = builder.convert_to_ext(*beta);
                     let gamma_ext = builder.convert_to_ext(*gamma);
@@ -20,9 +20,11 @@ use plonky2_maybe_rayon::*;
 
 use crate::config::StarkConfig;
 use crate::constraint_consumer::ConstraintConsumer;
+use crate::cross_table_lookup::{cross_table_lookup_data, CrossTableLookup, CtlCheckVars, CtlData};
+use crate::multi_stark::StarkTable;
 use crate::permutation::{
-    compute_permutation_z_polys, get_n_permutation_challenge_sets, PermutationChallengeSet,
-    PermutationCheckVars,
+    compute_permutation_z_polys, get_grand_product_challenge_set,
+    get_n_grand_product_challenge_sets, GrandProductChallengeSet, PermutationCheckVars,
 };
 use crate::proof::{StarkOpeningSet, StarkProof, StarkProofWithPublicInputs};
 use crate::stark::Stark;
@@ -36,6 +38,161 @@ pub fn prove<F, C, S, const D: usize>(
     public_inputs: [F; S::PUBLIC_INPUTS],
     timing: &mut TimingTree,
 ) -> Result<StarkProofWithPublicInputs<F, C, D>>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    S: Stark<F, D>,
+    [(); S::COLUMNS]:,
+    [(); S::PUBLIC_INPUTS]:,
+    [(); C::Hasher::HASH_SIZE]:,
+{
+    let trace_commitment = timed!(
+        timing,
+        "compute trace commitment",
+        // TODO: Cloning this isn't great; consider having `commit_values` accept a reference,
+        // or having `compute_permutation_z_polys` read trace values from the `PolynomialBatch`.
+        commit_values::<F, C, D>(trace_poly_values.clone(), config, timing)
+    );
+
+    let mut challenger = Challenger::new();
+    challenger.observe_cap(&trace_commitment.merkle_tree.cap);
+
+    prove_with_commitment(
+        &stark,
+        config,
+        &trace_poly_values,
+        &trace_commitment,
+        &CtlData::default(),
+        public_inputs,
+        &mut challenger,
+        timing,
+    )
+}
+
+/// Proves a multi-STARK system whose tables are connected by the cross-table lookups `ctls`. The
+/// `i`-th table is proven from `traces[i]` and `public_inputs[i]`, and the proofs are returned in
+/// the same order. They share one Fiat-Shamir transcript, so they can only be verified together,
+/// with `verify_stark_proofs_with_ctls`.
+pub fn prove_with_ctls<F, C, const D: usize>(
+    tables: &[&dyn StarkTable<F, C, D>],
+    ctls: &[CrossTableLookup<F>],
+    config: &StarkConfig,
+    traces: Vec<Vec<PolynomialValues<F>>>,
+    public_inputs: &[Vec<F>],
+    timing: &mut TimingTree,
+) -> Result<Vec<StarkProofWithPublicInputs<F, C, D>>>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    ensure!(
+        traces.len() == tables.len() && public_inputs.len() == tables.len(),
+        "Expected one trace and one set of public inputs per table"
+    );
+
+    let trace_commitments = timed!(
+        timing,
+        "compute all trace commitments",
+        traces
+            .iter()
+            .map(|trace| commit_values::<F, C, D>(trace.clone(), config, timing))
+            .collect::<Vec<_>>()
+    );
+
+    let mut challenger = Challenger::<F, C::Hasher>::new();
+    for commitment in &trace_commitments {
+        challenger.observe_cap(&commitment.merkle_tree.cap);
+    }
+    let ctl_challenges = get_grand_product_challenge_set(&mut challenger, config.num_challenges);
+    let ctl_data = timed!(
+        timing,
+        "compute CTL data",
+        cross_table_lookup_data::<F, D>(&traces, ctls, &ctl_challenges)
+    );
+
+    tables
+        .iter()
+        .zip(&traces)
+        .zip(&trace_commitments)
+        .zip(&ctl_data)
+        .zip(public_inputs)
+        .map(
+            |((((table, trace), commitment), ctl_data), public_inputs)| {
+                table.prove_with_commitment(
+                    config,
+                    trace,
+                    commitment,
+                    ctl_data,
+                    public_inputs,
+                    &mut challenger,
+                    timing,
+                )
+            },
+        )
+        .collect()
+}
+
+/// Commits to the polynomials interpolating `values` over the trace domain `H`.
+///
+/// In zero-knowledge mode, each polynomial `p` is replaced by `p + Z_H r` for a random `r` of
+/// degree less than `|H|`, which has the same values on `H`, and the Merkle leaves are salted.
+pub fn commit_values<F, C, const D: usize>(
+    values: Vec<PolynomialValues<F>>,
+    config: &StarkConfig,
+    timing: &mut TimingTree,
+) -> PolynomialBatch<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    let rate_bits = config.fri_config.rate_bits;
+    let cap_height = config.fri_config.cap_height;
+    if !config.zero_knowledge {
+        return PolynomialBatch::from_values(values, rate_bits, false, cap_height, timing, None);
+    }
+
+    let revealed = config.num_revealed_evaluations();
+    if let Some(v) = values.first() {
+        assert!(
+            v.len() > revealed,
+            "A trace of length {} can't hide {revealed} revealed evaluations",
+            v.len()
+        );
+    }
+
+    let coeffs = timed!(
+        timing,
+        "IFFT + randomize",
+        values
+            .into_par_iter()
+            .map(|v| {
+                let n = v.len();
+                let coeffs = v.ifft().coeffs;
+                let r = F::rand_vec(n);
+                // `p + (X^n - 1) r` has coefficients `p_i - r_i` and `r_i` at `i` and `n + i`.
+                let low = coeffs.iter().zip(&r).map(|(&c, &r)| c - r);
+                PolynomialCoeffs::new(low.chain(r.iter().copied()).collect())
+            })
+            .collect::<Vec<_>>()
+    );
+    PolynomialBatch::from_coeffs(coeffs, rate_bits, true, cap_height, timing, None)
+}
+
+/// Computes a proof for a single STARK, given a commitment to its trace.
+///
+/// When the STARK is one table of a multi-STARK system, `ctl_data` holds its cross-table lookup
+/// data, and `challenger` must already have observed the trace caps of all the tables. The same
+/// challenger is then used to prove the tables one after the other.
+pub fn prove_with_commitment<F, C, S, const D: usize>(
+    stark: &S,
+    config: &StarkConfig,
+    trace_poly_values: &[PolynomialValues<F>],
+    trace_commitment: &PolynomialBatch<F, C, D>,
+    ctl_data: &CtlData<F>,
+    public_inputs: [F; S::PUBLIC_INPUTS],
+    challenger: &mut Challenger<F, C::Hasher>,
+    timing: &mut TimingTree,
+) -> Result<StarkProofWithPublicInputs<F, C, D>>
 where
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
@@ -50,85 +207,76 @@ where
     let rate_bits = config.fri_config.rate_bits;
     let cap_height = config.fri_config.cap_height;
     assert!(
-        fri_params.total_arities() <= degree_bits + rate_bits - cap_height,
+        fri_params.total_arities() <= fri_params.lde_bits() - cap_height,
         "FRI total reduction arity is too large.",
     );
 
-    let trace_commitment = timed!(
-        timing,
-        "compute trace commitment",
-        PolynomialBatch::<F, C, D>::from_values(
-            // TODO: Cloning this isn't great; consider having `from_values` accept a reference,
-            // or having `compute_permutation_z_polys` read trace values from the `PolynomialBatch`.
-            trace_poly_values.clone(),
-            rate_bits,
-            false,
-            cap_height,
-            timing,
-            None,
-        )
-    );
-
-    let trace_cap = trace_commitment.merkle_tree.cap.clone();
-    let mut challenger = Challenger::new();
-    challenger.observe_cap(&trace_cap);
-
     // Permutation arguments.
-    let permutation_zs_commitment_challenges = stark.uses_permutation_args().then(|| {
-        let permutation_challenge_sets = get_n_permutation_challenge_sets(
-            &mut challenger,
+    let permutation_challenges = stark.uses_permutation_args().then(|| {
+        get_n_grand_product_challenge_sets(
+            challenger,
             config.num_challenges,
             stark.permutation_batch_size(),
-        );
-        let permutation_z_polys = compute_permutation_z_polys::<F, S, D>(
-            &stark,
-            config,
-            &trace_poly_values,
-            &permutation_challenge_sets,
-        );
+        )
+    });
+    let permutation_zs = permutation_challenges.as_ref().map(|challenges| {
+        timed!(
+            timing,
+            "compute permutation Z(x) polys",
+            compute_permutation_z_polys::<F, S, D>(stark, config, trace_poly_values, challenges)
+        )
+    });
+    let num_permutation_zs = permutation_zs.as_ref().map(|v| v.len()).unwrap_or(0);
 
-        let permutation_zs_commitment = timed!(
+    let z_polys = match permutation_zs {
+        None => ctl_data.z_polys(),
+        Some(mut permutation_zs) => {
+            permutation_zs.extend(ctl_data.z_polys());
+            permutation_zs
+        }
+    };
+
+    let permutation_ctl_zs_commitment = (!z_polys.is_empty()).then(|| {
+        timed!(
             timing,
-            "compute permutation Z commitments",
-            PolynomialBatch::from_values(
-                permutation_z_polys,
-                rate_bits,
-                false,
-                config.fri_config.cap_height,
-                timing,
-                None,
-            )
-        );
-        (permutation_zs_commitment, permutation_challenge_sets)
+            "compute permutation and CTL Z commitments",
+            commit_values(z_polys, config, timing)
+        )
     });
-    let permutation_zs_commitment = permutation_zs_commitment_challenges
-        .as_ref()
-        .map(|(comm, _)| comm);
-    let permutation_zs_cap = permutation_zs_commitment
+    let permutation_ctl_zs_cap = permutation_ctl_zs_commitment
         .as_ref()
         .map(|commit| commit.merkle_tree.cap.clone());
-    if let Some(cap) = &permutation_zs_cap {
+    if let Some(cap) = &permutation_ctl_zs_cap {
         challenger.observe_cap(cap);
     }
 
     let alphas = challenger.get_n_challenges(config.num_challenges);
-    let quotient_polys = compute_quotient_polys::<F, <F as Packable>::Packing, C, S, D>(
-        &stark,
-        &trace_commitment,
-        &permutation_zs_commitment_challenges,
-        public_inputs,
-        alphas,
-        degree_bits,
-        config,
+    let quotient_polys = timed!(
+        timing,
+        "compute quotient polys",
+        compute_quotient_polys::<F, <F as Packable>::Packing, C, S, D>(
+            stark,
+            trace_commitment,
+            permutation_ctl_zs_commitment.as_ref(),
+            permutation_challenges.as_ref(),
+            ctl_data,
+            public_inputs,
+            alphas,
+            degree_bits,
+            num_permutation_zs,
+            config,
+        )
     );
+    // The committed polynomials have degree `chunk_size`, which is `2n` in zero-knowledge mode.
+    let chunk_size = degree << config.blinding_degree_bits();
     let all_quotient_chunks = quotient_polys
         .into_par_iter()
         .flat_map(|mut quotient_poly| {
             quotient_poly
-                .trim_to_len(degree * stark.quotient_degree_factor())
+                .trim_to_len(chunk_size * stark.num_quotient_chunks(config))
                 .expect("Quotient has failed, the vanishing polynomial is not divisible by Z_H");
-            // Split quotient into degree-n chunks.
-            quotient_poly.chunks(degree)
+            // Split quotient into chunks of the committed degree.
+            quotient_poly.chunks(chunk_size)
         })
         .collect();
     let quotient_commitment = timed!(
@@ -137,8 +285,8 @@ where
         PolynomialBatch::from_coeffs(
             all_quotient_chunks,
             rate_bits,
-            false,
-            config.fri_config.cap_height,
+            config.zero_knowledge,
+            cap_height,
             timing,
             None,
         )
@@ -158,14 +306,16 @@ where
     let openings = StarkOpeningSet::new(
         zeta,
         g,
-        &trace_commitment,
-        permutation_zs_commitment,
+        trace_commitment,
+        permutation_ctl_zs_commitment.as_ref(),
         &quotient_commitment,
+        degree_bits,
+        num_permutation_zs,
     );
     challenger.observe_openings(&openings.to_fri_openings());
 
-    let initial_merkle_trees = once(&trace_commitment)
-        .chain(permutation_zs_commitment)
+    let initial_merkle_trees = once(trace_commitment)
+        .chain(&permutation_ctl_zs_commitment)
         .chain(once(&quotient_commitment))
         .collect_vec();
 
@@ -173,16 +323,16 @@ where
         timing,
         "compute openings proof",
         PolynomialBatch::prove_openings(
-            &stark.fri_instance(zeta, g, config),
+            &stark.fri_instance(zeta, g, ctl_data.len(), config),
             &initial_merkle_trees,
-            &mut challenger,
+            challenger,
             &fri_params,
             timing,
         )
     );
     let proof = StarkProof {
-        trace_cap,
-        permutation_zs_cap,
+        trace_cap: trace_commitment.merkle_tree.cap.clone(),
+        permutation_ctl_zs_cap,
         quotient_polys_cap,
         openings,
         opening_proof,
@@ -199,13 +349,13 @@ where
 fn compute_quotient_polys<'a, F, P, C, S, const D: usize>(
     stark: &S,
     trace_commitment: &'a PolynomialBatch<F, C, D>,
-    permutation_zs_commitment_challenges: &'a Option<(
-        PolynomialBatch<F, C, D>,
-        Vec<PermutationChallengeSet<F>>,
-    )>,
+    permutation_ctl_zs_commitment: Option<&'a PolynomialBatch<F, C, D>>,
+    permutation_challenges: Option<&'a Vec<GrandProductChallengeSet<F>>>,
+    ctl_data: &CtlData<F>,
     public_inputs: [F; S::PUBLIC_INPUTS],
     alphas: Vec<F>,
     degree_bits: usize,
+    num_permutation_zs: usize,
     config: &StarkConfig,
 ) -> Vec<PolynomialCoeffs<F>>
 where
@@ -219,12 +369,16 @@ where
     let degree = 1 << degree_bits;
     let rate_bits = config.fri_config.rate_bits;
 
-    let quotient_degree_bits = log2_ceil(stark.quotient_degree_factor());
+    // The LDEs of the committed polynomials have `2^lde_bits` points per point of `H`.
+    let lde_bits = rate_bits + config.blinding_degree_bits();
+
+    let quotient_degree_bits =
+        log2_ceil(stark.num_quotient_chunks(config)) + config.blinding_degree_bits();
     assert!(
-        quotient_degree_bits <= rate_bits,
+        quotient_degree_bits <= lde_bits,
         "Having constraints of degree higher than the rate is not supported yet."
     );
-    let step = 1 << (rate_bits - quotient_degree_bits);
+    let step = 1 << (lde_bits - quotient_degree_bits);
     // When opening the `Z`s polys at the "next" point, need to look at the point `next_step` steps away.
     let next_step = 1 << quotient_degree_bits;
 
@@ -278,18 +432,38 @@ where
                 next_values: &get_trace_values_packed(i_next_start),
                 public_inputs: &public_inputs,
             };
-            let permutation_check_data = permutation_zs_commitment_challenges.as_ref().map(
-                |(permutation_zs_commitment, permutation_challenge_sets)| PermutationCheckVars {
-                    local_zs: permutation_zs_commitment.get_lde_values_packed(i_start, step),
-                    next_zs: permutation_zs_commitment.get_lde_values_packed(i_next_start, step),
+            let (local_zs, next_zs) = permutation_ctl_zs_commitment
+                .map(|commitment| {
+                    (
+                        commitment.get_lde_values_packed(i_start, step),
+                        commitment.get_lde_values_packed(i_next_start, step),
+                    )
+                })
+                .unwrap_or_default();
+            let permutation_check_data =
+                permutation_challenges.map(|permutation_challenge_sets| PermutationCheckVars {
+                    local_zs: local_zs[..num_permutation_zs].to_vec(),
+                    next_zs: next_zs[..num_permutation_zs].to_vec(),
                     permutation_challenge_sets: permutation_challenge_sets.to_vec(),
-                },
-            );
+                });
+            let ctl_vars = ctl_data
+                .zs_columns
+                .iter()
+                .enumerate()
+                .map(|(i, zs_columns)| CtlCheckVars::<F, F, P, 1> {
+                    local_z: local_zs[num_permutation_zs + i],
+                    next_z: next_zs[num_permutation_zs + i],
+                    challenges: zs_columns.challenge,
+                    columns: &zs_columns.columns,
+                    filter_column: &zs_columns.filter_column,
+                })
+                .collect::<Vec<_>>();
             eval_vanishing_poly::<F, F, P, S, D, 1>(
                 stark,
                 config,
                 vars,
                 permutation_check_data,
+                &ctl_vars,
                 &mut consumer,
             );
 
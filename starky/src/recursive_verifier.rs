@@ -7,19 +7,25 @@ use plonky2::field::extension::Extendable;
 use plonky2::field::types::Field;
 use plonky2::fri::witness_util::set_fri_proof_target;
 use plonky2::hash::hash_types::RichField;
+use plonky2::iop::challenger::RecursiveChallenger;
 use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::iop::witness::Witness;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::plonk_common::salt_size;
 use plonky2::util::reducing::ReducingFactorTarget;
 use plonky2::with_context;
 
 use crate::config::StarkConfig;
 use crate::constraint_consumer::RecursiveConstraintConsumer;
-use crate::permutation::PermutationCheckDataTarget;
+use crate::cross_table_lookup::{
+    verify_cross_table_lookups_circuit, CrossTableLookup, CtlCheckVarsTarget,
+};
+use crate::multi_stark::StarkTable;
+use crate::permutation::{get_grand_product_challenge_set_target, PermutationCheckDataTarget};
 use crate::proof::{
-    StarkOpeningSetTarget, StarkProof, StarkProofChallengesTarget, StarkProofTarget,
-    StarkProofWithPublicInputs, StarkProofWithPublicInputsTarget,
+    StarkOpeningSet, StarkOpeningSetTarget, StarkProof, StarkProofChallengesTarget,
+    StarkProofTarget, StarkProofWithPublicInputs, StarkProofWithPublicInputsTarget,
 };
 use crate::stark::Stark;
 use crate::vanishing_poly::eval_vanishing_poly_circuit;
@@ -48,11 +54,103 @@ pub fn verify_stark_proof_circuit<
         proof_with_pis.get_challenges::<F, C, S>(builder, &stark, inner_config)
     );
 
+    verify_stark_proof_with_challenges_circuit::<F, C, S, D>(
+        builder,
+        &stark,
+        proof_with_pis,
+        challenges,
+        &[],
+        inner_config,
+        degree_bits,
+    );
+}
+
+/// Circuit version of `verify_stark_proofs_with_ctls`. The proof targets can be created with
+/// `StarkTable::add_virtual_proof_with_pis`.
+pub fn verify_stark_proofs_with_ctls_circuit<F, C, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    tables: &[&dyn StarkTable<F, C, D>],
+    ctls: &[CrossTableLookup<F>],
+    proofs: Vec<StarkProofWithPublicInputsTarget<D>>,
+    inner_config: &StarkConfig,
+) where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    C::Hasher: AlgebraicHasher<F>,
+{
+    assert_eq!(proofs.len(), tables.len(), "Expected one proof per table");
+
+    let mut challenger = RecursiveChallenger::<F, C::Hasher, D>::new(builder);
+    for proof in &proofs {
+        challenger.observe_cap(&proof.proof.trace_cap);
+    }
+    let ctl_challenges = get_grand_product_challenge_set_target(
+        builder,
+        &mut challenger,
+        inner_config.num_challenges,
+    );
+    let ctl_vars = proofs
+        .iter()
+        .zip(tables)
+        .enumerate()
+        .map(|(i, (proof, table))| {
+            CtlCheckVarsTarget::from_proof(
+                i,
+                &proof.proof,
+                ctls,
+                &ctl_challenges,
+                table.num_permutation_batches(inner_config),
+            )
+        })
+        .collect::<Vec<_>>();
+    let ctl_zs_lasts = proofs
+        .iter()
+        .map(|p| p.proof.openings.ctl_zs_last.clone())
+        .collect::<Vec<_>>();
+
+    for ((table, proof), ctl_vars) in tables.iter().zip(proofs).zip(&ctl_vars) {
+        table.verify_with_ctl_circuit(builder, proof, ctl_vars, &mut challenger, inner_config);
+    }
+    verify_cross_table_lookups_circuit(builder, ctls, &ctl_zs_lasts, inner_config);
+}
+
+/// Recursively verifies the proof of one table of a multi-STARK system. `challenger` must have
+/// observed the trace caps of all the tables, and the tables must be verified in the order in which
+/// they were proven. The cross-table lookups themselves are checked separately, using
+/// `verify_cross_table_lookups_circuit`.
+pub fn verify_stark_proof_with_ctl_circuit<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    S: Stark<F, D>,
+    const D: usize,
+>(
+    builder: &mut CircuitBuilder<F, D>,
+    stark: &S,
+    proof_with_pis: StarkProofWithPublicInputsTarget<D>,
+    ctl_vars: &[CtlCheckVarsTarget<F, D>],
+    challenger: &mut RecursiveChallenger<F, C::Hasher, D>,
+    inner_config: &StarkConfig,
+) where
+    C::Hasher: AlgebraicHasher<F>,
+    [(); S::COLUMNS]:,
+    [(); S::PUBLIC_INPUTS]:,
+{
+    assert_eq!(proof_with_pis.public_inputs.len(), S::PUBLIC_INPUTS);
+    let degree_bits = proof_with_pis.proof.recover_degree_bits(inner_config);
+    let challenges = with_context!(
+        builder,
+        "compute challenges",
+        proof_with_pis
+            .proof
+            .get_challenges::<F, C, S>(builder, challenger, stark, inner_config)
+    );
+
     verify_stark_proof_with_challenges_circuit::<F, C, S, D>(
         builder,
         stark,
         proof_with_pis,
         challenges,
+        ctl_vars,
         inner_config,
         degree_bits,
     );
@@ -66,9 +164,10 @@ fn verify_stark_proof_with_challenges_circuit<
     const D: usize,
 >(
     builder: &mut CircuitBuilder<F, D>,
-    stark: S,
+    stark: &S,
     proof_with_pis: StarkProofWithPublicInputsTarget<D>,
     challenges: StarkProofChallengesTarget<D>,
+    ctl_vars: &[CtlCheckVarsTarget<F, D>],
     inner_config: &StarkConfig,
     degree_bits: usize,
 ) where
@@ -76,7 +175,8 @@ fn verify_stark_proof_with_challenges_circuit<
     [(); S::COLUMNS]:,
     [(); S::PUBLIC_INPUTS]:,
 {
-    check_permutation_options(&stark, &proof_with_pis, &challenges).unwrap();
+    assert_eq!(proof_with_pis.proof.num_ctl_zs(), ctl_vars.len());
+    check_permutation_options(stark, &proof_with_pis, &challenges, ctl_vars.len()).unwrap();
     let one = builder.one_extension();
 
     let StarkProofWithPublicInputsTarget {
@@ -86,8 +186,9 @@ fn verify_stark_proof_with_challenges_circuit<
     let StarkOpeningSetTarget {
         local_values,
         next_values,
-        permutation_zs,
-        permutation_zs_next,
+        permutation_ctl_zs,
+        permutation_ctl_zs_next,
+        ctl_zs_last: _,
         quotient_polys,
     } = &proof.openings;
     let vars = StarkEvaluationTargets {
@@ -117,11 +218,12 @@ fn verify_stark_proof_with_challenges_circuit<
         l_last,
     );
 
+    let num_permutation_zs = stark.num_permutation_batches(inner_config);
     let permutation_data = stark
         .uses_permutation_args()
         .then(|| PermutationCheckDataTarget {
-            local_zs: permutation_zs.as_ref().unwrap().clone(),
-            next_zs: permutation_zs_next.as_ref().unwrap().clone(),
+            local_zs: permutation_ctl_zs.as_ref().unwrap()[..num_permutation_zs].to_vec(),
+            next_zs: permutation_ctl_zs_next.as_ref().unwrap()[..num_permutation_zs].to_vec(),
             permutation_challenge_sets: challenges.permutation_challenge_sets.unwrap(),
         });
 
@@ -130,19 +232,24 @@ fn verify_stark_proof_with_challenges_circuit<
         "evaluate vanishing polynomial",
         eval_vanishing_poly_circuit::<F, S, D>(
             builder,
-            &stark,
+            stark,
             inner_config,
             vars,
             permutation_data,
+            ctl_vars,
             &mut consumer,
         )
     );
     let vanishing_polys_zeta = consumer.accumulators();
 
     // Check each polynomial identity, of the form `vanishing(x) = Z_H(x) quotient(x)`, at zeta.
-    let mut scale = ReducingFactorTarget::new(zeta_pow_deg);
+    // In zero-knowledge mode, the quotient chunks have degree `2n`, so they are recombined with
+    // powers of `zeta^{2n}`.
+    let zeta_pow_chunk_deg =
+        builder.exp_power_of_2_extension(zeta_pow_deg, inner_config.blinding_degree_bits());
+    let mut scale = ReducingFactorTarget::new(zeta_pow_chunk_deg);
     for (i, chunk) in quotient_polys
-        .chunks(stark.quotient_degree_factor())
+        .chunks(stark.num_quotient_chunks(inner_config))
         .enumerate()
     {
         let recombined_quotient = scale.reduce(chunk, builder);
@@ -151,7 +258,7 @@ fn verify_stark_proof_with_challenges_circuit<
     }
 
     let merkle_caps = once(proof.trace_cap)
-        .chain(proof.permutation_zs_cap)
+        .chain(proof.permutation_ctl_zs_cap)
         .chain(once(proof.quotient_polys_cap))
         .collect_vec();
 
@@ -159,11 +266,13 @@ fn verify_stark_proof_with_challenges_circuit<
         builder,
         challenges.stark_zeta,
         F::primitive_root_of_unity(degree_bits),
+        ctl_vars.len(),
         inner_config,
     );
+    let zero = builder.zero();
     builder.verify_fri_proof::<C>(
         &fri_instance,
-        &proof.openings.to_fri_openings(),
+        &proof.openings.to_fri_openings(zero),
         &challenges.fri_challenges,
         &merkle_caps,
         &proof.opening_proof,
@@ -196,11 +305,12 @@ pub fn add_virtual_stark_proof_with_pis<
     const D: usize,
 >(
     builder: &mut CircuitBuilder<F, D>,
-    stark: S,
+    stark: &S,
     config: &StarkConfig,
     degree_bits: usize,
+    num_ctl_zs: usize,
 ) -> StarkProofWithPublicInputsTarget<D> {
-    let proof = add_virtual_stark_proof::<F, S, D>(builder, stark, config, degree_bits);
+    let proof = add_virtual_stark_proof::<F, S, D>(builder, stark, config, degree_bits, num_ctl_zs);
     let public_inputs = builder.add_virtual_targets(S::PUBLIC_INPUTS);
     StarkProofWithPublicInputsTarget {
         proof,
@@ -210,52 +320,49 @@ pub fn add_virtual_stark_proof_with_pis<
 
 pub fn add_virtual_stark_proof<F: RichField + Extendable<D>, S: Stark<F, D>, const D: usize>(
     builder: &mut CircuitBuilder<F, D>,
-    stark: S,
+    stark: &S,
     config: &StarkConfig,
     degree_bits: usize,
+    num_ctl_zs: usize,
 ) -> StarkProofTarget<D> {
     let fri_params = config.fri_params(degree_bits);
     let cap_height = fri_params.config.cap_height;
 
+    let num_permutation_ctl_zs = stark.num_permutation_batches(config) + num_ctl_zs;
     let num_leaves_per_oracle = once(S::COLUMNS)
-        .chain(
-            stark
-                .uses_permutation_args()
-                .then(|| stark.num_permutation_batches(config)),
-        )
-        .chain(once(stark.quotient_degree_factor() * config.num_challenges))
+        .chain((num_permutation_ctl_zs > 0).then_some(num_permutation_ctl_zs))
+        .chain(once(stark.num_quotient_polys(config)))
+        .map(|num_polys| num_polys + salt_size(config.zero_knowledge))
         .collect_vec();
 
-    let permutation_zs_cap = stark
-        .uses_permutation_args()
-        .then(|| builder.add_virtual_cap(cap_height));
+    let permutation_ctl_zs_cap =
+        (num_permutation_ctl_zs > 0).then(|| builder.add_virtual_cap(cap_height));
 
     StarkProofTarget {
         trace_cap: builder.add_virtual_cap(cap_height),
-        permutation_zs_cap,
+        permutation_ctl_zs_cap,
         quotient_polys_cap: builder.add_virtual_cap(cap_height),
-        openings: add_stark_opening_set_target::<F, S, D>(builder, stark, config),
+        openings: add_stark_opening_set_target::<F, S, D>(builder, stark, num_ctl_zs, config),
         opening_proof: builder.add_virtual_fri_proof(&num_leaves_per_oracle, &fri_params),
     }
 }
 
 fn add_stark_opening_set_target<F: RichField + Extendable<D>, S: Stark<F, D>, const D: usize>(
     builder: &mut CircuitBuilder<F, D>,
-    stark: S,
+    stark: &S,
+    num_ctl_zs: usize,
     config: &StarkConfig,
 ) -> StarkOpeningSetTarget<D> {
-    let num_challenges = config.num_challenges;
+    let num_permutation_ctl_zs = stark.num_permutation_batches(config) + num_ctl_zs;
     StarkOpeningSetTarget {
         local_values: builder.add_virtual_extension_targets(S::COLUMNS),
         next_values: builder.add_virtual_extension_targets(S::COLUMNS),
-        permutation_zs: stark
-            .uses_permutation_args()
-            .then(|| builder.add_virtual_extension_targets(stark.num_permutation_batches(config))),
-        permutation_zs_next: stark
-            .uses_permutation_args()
-            .then(|| builder.add_virtual_extension_targets(stark.num_permutation_batches(config))),
-        quotient_polys: builder
-            .add_virtual_extension_targets(stark.quotient_degree_factor() * num_challenges),
+        permutation_ctl_zs: (num_permutation_ctl_zs > 0)
+            .then(|| builder.add_virtual_extension_targets(num_permutation_ctl_zs)),
+        permutation_ctl_zs_next: (num_permutation_ctl_zs > 0)
+            .then(|| builder.add_virtual_extension_targets(num_permutation_ctl_zs)),
+        ctl_zs_last: builder.add_virtual_targets(num_ctl_zs),
+        quotient_polys: builder.add_virtual_extension_targets(stark.num_quotient_polys(config)),
     }
 }
 
@@ -297,37 +404,72 @@ pub fn set_stark_proof_target<F, C: GenericConfig<D, F = F>, W, const D: usize>(
     witness.set_cap_target(&proof_target.trace_cap, &proof.trace_cap);
     witness.set_cap_target(&proof_target.quotient_polys_cap, &proof.quotient_polys_cap);
 
-    witness.set_fri_openings(
-        &proof_target.openings.to_fri_openings(),
-        &proof.openings.to_fri_openings(),
-    );
+    set_stark_opening_set_target(witness, &proof_target.openings, &proof.openings);
 
-    if let (Some(permutation_zs_cap_target), Some(permutation_zs_cap)) =
-        (&proof_target.permutation_zs_cap, &proof.permutation_zs_cap)
-    {
-        witness.set_cap_target(permutation_zs_cap_target, permutation_zs_cap);
+    if let (Some(permutation_ctl_zs_cap_target), Some(permutation_ctl_zs_cap)) = (
+        &proof_target.permutation_ctl_zs_cap,
+        &proof.permutation_ctl_zs_cap,
+    ) {
+        witness.set_cap_target(permutation_ctl_zs_cap_target, permutation_ctl_zs_cap);
     }
 
     set_fri_proof_target(witness, &proof_target.opening_proof, &proof.opening_proof);
 }
 
-/// Utility function to check that all permutation data wrapped in `Option`s are `Some` iff
-/// the Stark uses a permutation argument.
+fn set_stark_opening_set_target<F, W, const D: usize>(
+    witness: &mut W,
+    openings_target: &StarkOpeningSetTarget<D>,
+    openings: &StarkOpeningSet<F, D>,
+) where
+    F: RichField + Extendable<D>,
+    W: Witness<F>,
+{
+    witness.set_extension_targets(&openings_target.local_values, &openings.local_values);
+    witness.set_extension_targets(&openings_target.next_values, &openings.next_values);
+    if let (Some(zs_target), Some(zs)) = (
+        &openings_target.permutation_ctl_zs,
+        &openings.permutation_ctl_zs,
+    ) {
+        witness.set_extension_targets(zs_target, zs);
+    }
+    if let (Some(zs_next_target), Some(zs_next)) = (
+        &openings_target.permutation_ctl_zs_next,
+        &openings.permutation_ctl_zs_next,
+    ) {
+        witness.set_extension_targets(zs_next_target, zs_next);
+    }
+    for (&t, &v) in openings_target
+        .ctl_zs_last
+        .iter()
+        .zip_eq(&openings.ctl_zs_last)
+    {
+        witness.set_target(t, v);
+    }
+    witness.set_extension_targets(&openings_target.quotient_polys, &openings.quotient_polys);
+}
+
+/// Utility function to check that all permutation and cross-table lookup data wrapped in `Option`s
+/// are `Some` iff the Stark uses a permutation argument or cross-table lookups.
 fn check_permutation_options<F: RichField + Extendable<D>, S: Stark<F, D>, const D: usize>(
     stark: &S,
     proof_with_pis: &StarkProofWithPublicInputsTarget<D>,
     challenges: &StarkProofChallengesTarget<D>,
+    num_ctl_zs: usize,
 ) -> Result<()> {
-    let options_is_some = [
-        proof_with_pis.proof.permutation_zs_cap.is_some(),
-        proof_with_pis.proof.openings.permutation_zs.is_some(),
-        proof_with_pis.proof.openings.permutation_zs_next.is_some(),
-        challenges.permutation_challenge_sets.is_some(),
+    let uses_permutation_args = stark.uses_permutation_args();
+    let uses_zs = uses_permutation_args || num_ctl_zs > 0;
+    let zs_options_is_some = [
+        proof_with_pis.proof.permutation_ctl_zs_cap.is_some(),
+        proof_with_pis.proof.openings.permutation_ctl_zs.is_some(),
+        proof_with_pis
+            .proof
+            .openings
+            .permutation_ctl_zs_next
+            .is_some(),
     ];
     ensure!(
-        options_is_some
-            .into_iter()
-            .all(|b| b == stark.uses_permutation_args()),
+        zs_options_is_some.into_iter().all(|b| b == uses_zs)
+            && challenges.permutation_challenge_sets.is_some() == uses_permutation_args,
         "Permutation data doesn't match with Stark configuration."
     );
     Ok(())
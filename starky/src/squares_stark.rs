@@ -0,0 +1,404 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::field::polynomial::PolynomialValues;
+use plonky2::hash::hash_types::RichField;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use crate::cross_table_lookup::{Column, CrossTableLookup, TableWithColumns};
+use crate::permutation::PermutationPair;
+use crate::stark::Stark;
+use crate::util::trace_rows_to_poly_values;
+use crate::vars::{StarkEvaluationTargets, StarkEvaluationVars};
+
+/// Index of `CounterStark` in the toy multi-STARK system.
+const COUNTER_TABLE: usize = 0;
+/// Index of `SquaresStark` in the toy multi-STARK system.
+const SQUARES_TABLE: usize = 1;
+
+/// Toy STARK used for testing cross-table lookups.
+/// Computes a counter with state `[x, x^2, f, i, j]` using the state transition
+/// `x' <- x + 1, f' <- 1 - f, i' <- i + 1, j' <- j + 1`, where `f` is 1 on the first row.
+/// The pairs `(x, x^2)` of the rows where `f = 1` are looked up in `SquaresStark`.
+/// Note: The `i, j` columns are only used to test the permutation argument alongside the
+/// cross-table lookups.
+#[derive(Copy, Clone)]
+struct CounterStark<F: RichField + Extendable<D>, const D: usize> {
+    num_rows: usize,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CounterStark<F, D> {
+    // The public input is the initial value of the counter.
+    const PI_INDEX_X0: usize = 0;
+
+    fn new(num_rows: usize) -> Self {
+        Self {
+            num_rows,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Generate the trace using `x0, x0^2, 1, 0, 1` as initial state values.
+    fn generate_trace(&self, x0: F) -> Vec<PolynomialValues<F>> {
+        let mut trace_rows = (0..self.num_rows)
+            .scan([x0, x0 * x0, F::ONE, F::ZERO, F::ONE], |acc, _| {
+                let tmp = *acc;
+                acc[0] = tmp[0] + F::ONE;
+                acc[1] = acc[0] * acc[0];
+                acc[2] = F::ONE - tmp[2];
+                acc[3] = tmp[3] + F::ONE;
+                acc[4] = tmp[4] + F::ONE;
+                Some(tmp)
+            })
+            .collect::<Vec<_>>();
+        trace_rows[self.num_rows - 1][4] = F::ZERO; // So that column 3 and 4 are permutation of one another.
+        trace_rows_to_poly_values(trace_rows)
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for CounterStark<F, D> {
+    const COLUMNS: usize = 5;
+    const PUBLIC_INPUTS: usize = 1;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: StarkEvaluationVars<FE, P, { Self::COLUMNS }, { Self::PUBLIC_INPUTS }>,
+        yield_constr: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>,
+    {
+        let local = vars.local_values;
+        let next = vars.next_values;
+
+        // Check public inputs.
+        yield_constr.constraint_first_row(local[0] - vars.public_inputs[Self::PI_INDEX_X0]);
+        yield_constr.constraint_first_row(local[2] - FE::ONE);
+
+        // x' <- x + 1
+        yield_constr.constraint_transition(next[0] - local[0] - FE::ONE);
+        // x^2 = x * x
+        yield_constr.constraint(local[1] - local[0] * local[0]);
+        // f' <- 1 - f
+        yield_constr.constraint_transition(next[2] + local[2] - FE::ONE);
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: StarkEvaluationTargets<D, { Self::COLUMNS }, { Self::PUBLIC_INPUTS }>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let local = vars.local_values;
+        let next = vars.next_values;
+        let one = builder.one_extension();
+
+        // Check public inputs.
+        let x0_constraint = builder.sub_extension(local[0], vars.public_inputs[Self::PI_INDEX_X0]);
+        yield_constr.constraint_first_row(builder, x0_constraint);
+        let f0_constraint = builder.sub_extension(local[2], one);
+        yield_constr.constraint_first_row(builder, f0_constraint);
+
+        // x' <- x + 1
+        let x_constraint = {
+            let tmp = builder.sub_extension(next[0], local[0]);
+            builder.sub_extension(tmp, one)
+        };
+        yield_constr.constraint_transition(builder, x_constraint);
+        // x^2 = x * x
+        let square_constraint = {
+            let square = builder.mul_extension(local[0], local[0]);
+            builder.sub_extension(local[1], square)
+        };
+        yield_constr.constraint(builder, square_constraint);
+        // f' <- 1 - f
+        let f_constraint = {
+            let tmp = builder.add_extension(next[2], local[2]);
+            builder.sub_extension(tmp, one)
+        };
+        yield_constr.constraint_transition(builder, f_constraint);
+    }
+
+    fn constraint_degree(&self) -> usize {
+        3
+    }
+
+    fn permutation_pairs(&self) -> Vec<PermutationPair> {
+        vec![PermutationPair::singletons(3, 4)]
+    }
+}
+
+/// Toy STARK used for testing cross-table lookups.
+/// Holds pairs `[y, y^2]`, and is looked up by `CounterStark`.
+#[derive(Copy, Clone)]
+struct SquaresStark<F: RichField + Extendable<D>, const D: usize> {
+    _phantom: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SquaresStark<F, D> {
+    fn new() -> Self {
+        Self {
+            _phantom: PhantomData,
+        }
+    }
+
+    fn generate_trace(&self, ys: &[F]) -> Vec<PolynomialValues<F>> {
+        let trace_rows = ys.iter().map(|&y| [y, y * y]).collect();
+        trace_rows_to_poly_values(trace_rows)
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for SquaresStark<F, D> {
+    const COLUMNS: usize = 2;
+    const PUBLIC_INPUTS: usize = 0;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: StarkEvaluationVars<FE, P, { Self::COLUMNS }, { Self::PUBLIC_INPUTS }>,
+        yield_constr: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>,
+    {
+        let local = vars.local_values;
+        yield_constr.constraint(local[1] - local[0] * local[0]);
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: StarkEvaluationTargets<D, { Self::COLUMNS }, { Self::PUBLIC_INPUTS }>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let local = vars.local_values;
+        let square_constraint = {
+            let square = builder.mul_extension(local[0], local[0]);
+            builder.sub_extension(local[1], square)
+        };
+        yield_constr.constraint(builder, square_constraint);
+    }
+
+    fn constraint_degree(&self) -> usize {
+        2
+    }
+}
+
+/// The pairs `(x, x^2)` of `CounterStark` where `f = 1` are rows of `SquaresStark`.
+fn ctl_squares<F: RichField>() -> CrossTableLookup<F> {
+    CrossTableLookup::new(
+        vec![TableWithColumns::new(
+            COUNTER_TABLE,
+            Column::singles([0, 1]).collect(),
+            Some(Column::single(2)),
+        )],
+        TableWithColumns::new(SQUARES_TABLE, Column::singles([0, 1]).collect(), None),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use anyhow::Result;
+    use plonky2::field::extension::Extendable;
+    use plonky2::field::types::Field;
+    use plonky2::hash::hash_types::RichField;
+    use plonky2::iop::witness::PartialWitness;
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::{
+        AlgebraicHasher, GenericConfig, Hasher, PoseidonGoldilocksConfig,
+    };
+    use plonky2::util::timing::TimingTree;
+
+    use crate::config::StarkConfig;
+    use crate::cross_table_lookup::CrossTableLookup;
+    use crate::multi_stark::StarkTable;
+    use crate::proof::StarkProofWithPublicInputs;
+    use crate::prover::prove_with_ctls;
+    use crate::recursive_verifier::{
+        set_stark_proof_with_pis_target, verify_stark_proofs_with_ctls_circuit,
+    };
+    use crate::squares_stark::{
+        ctl_squares, CounterStark, SquaresStark, COUNTER_TABLE, SQUARES_TABLE,
+    };
+    use crate::stark::Stark;
+    use crate::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
+    use crate::verifier::verify_stark_proofs_with_ctls;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type S1 = CounterStark<F, D>;
+    type S2 = SquaresStark<F, D>;
+
+    const NUM_ROWS: usize = 1 << 7;
+
+    /// Proves both tables of the toy system, and returns their proofs.
+    fn prove_counter_and_squares(
+        config: &StarkConfig,
+        ctls: &[CrossTableLookup<F>],
+        x0: F,
+    ) -> Result<Vec<StarkProofWithPublicInputs<F, C, D>>> {
+        let counter_stark = S1::new(NUM_ROWS);
+        let squares_stark = S2::new();
+        // The squares looked up by `CounterStark`, in reverse order.
+        let ys = (0..NUM_ROWS / 2)
+            .rev()
+            .map(|i| x0 + F::from_canonical_usize(2 * i))
+            .collect::<Vec<_>>();
+        let traces = vec![
+            counter_stark.generate_trace(x0),
+            squares_stark.generate_trace(&ys),
+        ];
+
+        prove_with_ctls::<F, C, D>(
+            &[&counter_stark, &squares_stark],
+            ctls,
+            config,
+            traces,
+            &[vec![x0], vec![]],
+            &mut TimingTree::default(),
+        )
+    }
+
+    fn verify_counter_and_squares(
+        config: &StarkConfig,
+        ctls: &[CrossTableLookup<F>],
+        proofs: Vec<StarkProofWithPublicInputs<F, C, D>>,
+    ) -> Result<()> {
+        verify_stark_proofs_with_ctls::<F, C, D>(
+            &[&S1::new(NUM_ROWS), &S2::new()],
+            ctls,
+            proofs,
+            config,
+        )
+    }
+
+    #[test]
+    fn test_ctl_starks() -> Result<()> {
+        let config = StarkConfig::standard_fast_config();
+        let ctls = vec![ctl_squares()];
+        let proofs = prove_counter_and_squares(&config, &ctls, F::from_canonical_u64(3))?;
+        verify_counter_and_squares(&config, &ctls, proofs)
+    }
+
+    #[test]
+    fn test_ctl_starks_zk() -> Result<()> {
+        let config = StarkConfig::standard_fast_zk_config();
+        let ctls = vec![ctl_squares()];
+        let proofs = prove_counter_and_squares(&config, &ctls, F::from_canonical_u64(3))?;
+        verify_counter_and_squares(&config, &ctls, proofs)
+    }
+
+    #[test]
+    fn test_ctl_starks_wrong_looked_table() -> Result<()> {
+        let config = StarkConfig::standard_fast_config();
+        let ctls = vec![ctl_squares()];
+        let proofs = prove_counter_and_squares(&config, &ctls, F::from_canonical_u64(3))?;
+        // Proofs of a different `SquaresStark` trace are valid on their own, but not together
+        // with the `CounterStark` proof.
+        let other_proofs = prove_counter_and_squares(&config, &ctls, F::from_canonical_u64(5))?;
+        let mixed_proofs = vec![
+            proofs[COUNTER_TABLE].clone(),
+            other_proofs[SQUARES_TABLE].clone(),
+        ];
+        assert!(verify_counter_and_squares(&config, &ctls, mixed_proofs).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ctl_starks_missing_ctl_openings() -> Result<()> {
+        let config = StarkConfig::standard_fast_config();
+        let ctls = vec![ctl_squares()];
+        let mut proofs = prove_counter_and_squares(&config, &ctls, F::from_canonical_u64(3))?;
+        // A malformed proof must be rejected with an error rather than panic the verifier.
+        let openings = &mut proofs[SQUARES_TABLE].proof.openings;
+        openings.permutation_ctl_zs = None;
+        openings.permutation_ctl_zs_next = None;
+        openings.ctl_zs_last.clear();
+        assert!(verify_counter_and_squares(&config, &ctls, proofs).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ctl_starks_degree() -> Result<()> {
+        test_stark_low_degree(S1::new(NUM_ROWS))?;
+        test_stark_low_degree(S2::new())
+    }
+
+    #[test]
+    fn test_ctl_starks_circuit() -> Result<()> {
+        test_stark_circuit_constraints::<F, C, S1, D>(S1::new(NUM_ROWS))?;
+        test_stark_circuit_constraints::<F, C, S2, D>(S2::new())
+    }
+
+    #[test]
+    fn test_recursive_ctl_stark_verifier() -> Result<()> {
+        let inner_config = StarkConfig::standard_fast_config();
+        let ctls = vec![ctl_squares()];
+        let proofs = prove_counter_and_squares(&inner_config, &ctls, F::from_canonical_u64(3))?;
+
+        recursive_proof::<F, C, C, D>(proofs, &ctls, &inner_config)
+    }
+
+    fn recursive_proof<
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+        InnerC: GenericConfig<D, F = F>,
+        const D: usize,
+    >(
+        inner_proofs: Vec<StarkProofWithPublicInputs<F, InnerC, D>>,
+        ctls: &[CrossTableLookup<F>],
+        inner_config: &StarkConfig,
+    ) -> Result<()>
+    where
+        InnerC::Hasher: AlgebraicHasher<F>,
+        [(); CounterStark::<F, D>::COLUMNS]:,
+        [(); CounterStark::<F, D>::PUBLIC_INPUTS]:,
+        [(); SquaresStark::<F, D>::COLUMNS]:,
+        [(); SquaresStark::<F, D>::PUBLIC_INPUTS]:,
+        [(); InnerC::Hasher::HASH_SIZE]:,
+    {
+        let counter_stark = CounterStark::<F, D>::new(NUM_ROWS);
+        let squares_stark = SquaresStark::<F, D>::new();
+        let tables: [&dyn StarkTable<F, InnerC, D>; 2] = [&counter_stark, &squares_stark];
+
+        let circuit_config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(circuit_config);
+        let mut pw = PartialWitness::new();
+
+        let proof_targets = tables
+            .iter()
+            .zip(&inner_proofs)
+            .enumerate()
+            .map(|(i, (table, proof))| {
+                let proof_target = table.add_virtual_proof_with_pis(
+                    &mut builder,
+                    inner_config,
+                    proof.proof.recover_degree_bits(inner_config),
+                    CrossTableLookup::num_ctl_zs(ctls, i, inner_config.num_challenges),
+                );
+                set_stark_proof_with_pis_target(&mut pw, &proof_target, proof);
+                proof_target
+            })
+            .collect::<Vec<_>>();
+        verify_stark_proofs_with_ctls_circuit::<F, InnerC, D>(
+            &mut builder,
+            &tables,
+            ctls,
+            proof_targets,
+            inner_config,
+        );
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)
+    }
+}
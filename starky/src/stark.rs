@@ -80,8 +80,14 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         1.max(self.constraint_degree() - 1)
     }
 
+    /// The number of chunks each quotient polynomial is split into. In zero-knowledge mode, the
+    /// committed polynomials have twice the trace length, so one more chunk is needed.
+    fn num_quotient_chunks(&self, config: &StarkConfig) -> usize {
+        1.max(self.constraint_degree() - 1 + config.blinding_degree_bits())
+    }
+
     fn num_quotient_polys(&self, config: &StarkConfig) -> usize {
-        self.quotient_degree_factor() * config.num_challenges
+        self.num_quotient_chunks(config) * config.num_challenges
     }
 
     /// Computes the FRI instance used to prove this Stark.
@@ -89,6 +95,7 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         &self,
         zeta: F::Extension,
         g: F,
+        num_ctl_zs: usize,
         config: &StarkConfig,
     ) -> FriInstanceInfo<F, D> {
         let mut oracles = vec![];
@@ -96,42 +103,55 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         let trace_info = FriPolynomialInfo::from_range(oracles.len(), 0..Self::COLUMNS);
         oracles.push(FriOracleInfo {
             num_polys: Self::COLUMNS,
-            blinding: false,
+            blinding: config.zero_knowledge,
         });
 
-        let permutation_zs_info = if self.uses_permutation_args() {
-            let num_z_polys = self.num_permutation_batches(config);
-            let polys = FriPolynomialInfo::from_range(oracles.len(), 0..num_z_polys);
+        let num_permutation_batches = self.num_permutation_batches(config);
+        let num_permutation_ctl_polys = num_permutation_batches + num_ctl_zs;
+        let (permutation_ctl_zs_info, ctl_zs_info) = if num_permutation_ctl_polys > 0 {
+            let permutation_ctl_index = oracles.len();
             oracles.push(FriOracleInfo {
-                num_polys: num_z_polys,
-                blinding: false,
+                num_polys: num_permutation_ctl_polys,
+                blinding: config.zero_knowledge,
             });
-            polys
+            (
+                FriPolynomialInfo::from_range(permutation_ctl_index, 0..num_permutation_ctl_polys),
+                FriPolynomialInfo::from_range(
+                    permutation_ctl_index,
+                    num_permutation_batches..num_permutation_ctl_polys,
+                ),
+            )
         } else {
-            vec![]
+            (vec![], vec![])
         };
 
-        let num_quotient_polys = self.quotient_degree_factor() * config.num_challenges;
+        let num_quotient_polys = self.num_quotient_polys(config);
         let quotient_info = FriPolynomialInfo::from_range(oracles.len(), 0..num_quotient_polys);
         oracles.push(FriOracleInfo {
             num_polys: num_quotient_polys,
-            blinding: false,
+            blinding: config.zero_knowledge,
         });
 
         let zeta_batch = FriBatchInfo {
             point: zeta,
             polynomials: [
                 trace_info.clone(),
-                permutation_zs_info.clone(),
+                permutation_ctl_zs_info.clone(),
                 quotient_info,
             ]
             .concat(),
         };
         let zeta_next_batch = FriBatchInfo {
             point: zeta.scalar_mul(g),
-            polynomials: [trace_info, permutation_zs_info].concat(),
+            polynomials: [trace_info, permutation_ctl_zs_info].concat(),
         };
-        let batches = vec![zeta_batch, zeta_next_batch];
+        let mut batches = vec![zeta_batch, zeta_next_batch];
+        if num_ctl_zs > 0 {
+            batches.push(FriBatchInfo {
+                point: F::Extension::from_basefield(g.inverse()),
+                polynomials: ctl_zs_info,
+            });
+        }
 
         FriInstanceInfo { oracles, batches }
     }
@@ -142,6 +162,7 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         builder: &mut CircuitBuilder<F, D>,
         zeta: ExtensionTarget<D>,
         g: F,
+        num_ctl_zs: usize,
         config: &StarkConfig,
     ) -> FriInstanceInfoTarget<D> {
         let mut oracles = vec![];
@@ -149,33 +170,40 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         let trace_info = FriPolynomialInfo::from_range(oracles.len(), 0..Self::COLUMNS);
         oracles.push(FriOracleInfo {
             num_polys: Self::COLUMNS,
-            blinding: false,
+            blinding: config.zero_knowledge,
         });
 
-        let permutation_zs_info = if self.uses_permutation_args() {
-            let num_z_polys = self.num_permutation_batches(config);
-            let polys = FriPolynomialInfo::from_range(oracles.len(), 0..num_z_polys);
+        let num_permutation_batches = self.num_permutation_batches(config);
+        let num_permutation_ctl_polys = num_permutation_batches + num_ctl_zs;
+        let (permutation_ctl_zs_info, ctl_zs_info) = if num_permutation_ctl_polys > 0 {
+            let permutation_ctl_index = oracles.len();
             oracles.push(FriOracleInfo {
-                num_polys: num_z_polys,
-                blinding: false,
+                num_polys: num_permutation_ctl_polys,
+                blinding: config.zero_knowledge,
             });
-            polys
+            (
+                FriPolynomialInfo::from_range(permutation_ctl_index, 0..num_permutation_ctl_polys),
+                FriPolynomialInfo::from_range(
+                    permutation_ctl_index,
+                    num_permutation_batches..num_permutation_ctl_polys,
+                ),
+            )
         } else {
-            vec![]
+            (vec![], vec![])
         };
 
-        let num_quotient_polys = self.quotient_degree_factor() * config.num_challenges;
+        let num_quotient_polys = self.num_quotient_polys(config);
         let quotient_info = FriPolynomialInfo::from_range(oracles.len(), 0..num_quotient_polys);
         oracles.push(FriOracleInfo {
             num_polys: num_quotient_polys,
-            blinding: false,
+            blinding: config.zero_knowledge,
         });
 
         let zeta_batch = FriBatchInfoTarget {
             point: zeta,
             polynomials: [
                 trace_info.clone(),
-                permutation_zs_info.clone(),
+                permutation_ctl_zs_info.clone(),
                 quotient_info,
             ]
             .concat(),
@@ -183,9 +211,16 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         let zeta_next = builder.mul_const_extension(g, zeta);
         let zeta_next_batch = FriBatchInfoTarget {
             point: zeta_next,
-            polynomials: [trace_info, permutation_zs_info].concat(),
+            polynomials: [trace_info, permutation_ctl_zs_info].concat(),
         };
-        let batches = vec![zeta_batch, zeta_next_batch];
+        let mut batches = vec![zeta_batch, zeta_next_batch];
+        if num_ctl_zs > 0 {
+            let ctl_last = builder.constant_extension(F::Extension::from_basefield(g.inverse()));
+            batches.push(FriBatchInfoTarget {
+                point: ctl_last,
+                polynomials: ctl_zs_info,
+            });
+        }
 
         FriInstanceInfoTarget { oracles, batches }
     }
@@ -7,12 +7,15 @@ use plonky2::field::extension::{Extendable, FieldExtension};
 use plonky2::field::types::Field;
 use plonky2::fri::verifier::verify_fri_proof;
 use plonky2::hash::hash_types::RichField;
+use plonky2::iop::challenger::Challenger;
 use plonky2::plonk::config::{GenericConfig, Hasher};
 use plonky2::plonk::plonk_common::reduce_with_powers;
 
 use crate::config::StarkConfig;
 use crate::constraint_consumer::ConstraintConsumer;
-use crate::permutation::PermutationCheckVars;
+use crate::cross_table_lookup::{verify_cross_table_lookups, CrossTableLookup, CtlCheckVars};
+use crate::multi_stark::{num_permutation_zs, StarkTable};
+use crate::permutation::{get_grand_product_challenge_set, PermutationCheckVars};
 use crate::proof::{StarkOpeningSet, StarkProof, StarkProofChallenges, StarkProofWithPublicInputs};
 use crate::stark::Stark;
 use crate::vanishing_poly::eval_vanishing_poly;
@@ -36,7 +39,75 @@ where
     ensure!(proof_with_pis.public_inputs.len() == S::PUBLIC_INPUTS);
     let degree_bits = proof_with_pis.proof.recover_degree_bits(config);
     let challenges = proof_with_pis.get_challenges(&stark, config, degree_bits);
-    verify_stark_proof_with_challenges(stark, proof_with_pis, challenges, degree_bits, config)
+    verify_stark_proof_with_challenges(&stark, proof_with_pis, challenges, &[], degree_bits, config)
+}
+
+/// Verifies the proofs of a multi-STARK system produced by `prove_with_ctls`, including its
+/// cross-table lookups. `proofs[i]` is the proof of the `i`-th table.
+pub fn verify_stark_proofs_with_ctls<F, C, const D: usize>(
+    tables: &[&dyn StarkTable<F, C, D>],
+    ctls: &[CrossTableLookup<F>],
+    proofs: Vec<StarkProofWithPublicInputs<F, C, D>>,
+    config: &StarkConfig,
+) -> Result<()>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    ensure!(proofs.len() == tables.len(), "Expected one proof per table");
+
+    let mut challenger = Challenger::<F, C::Hasher>::new();
+    for proof in &proofs {
+        challenger.observe_cap(&proof.proof.trace_cap);
+    }
+    let ctl_challenges = get_grand_product_challenge_set(&mut challenger, config.num_challenges);
+    let num_permutation_zs = num_permutation_zs(tables, config);
+    let ctl_vars = CtlCheckVars::from_proofs(&proofs, ctls, &ctl_challenges, &num_permutation_zs)?;
+    let ctl_zs_lasts = proofs
+        .iter()
+        .map(|p| p.proof.openings.ctl_zs_last.clone())
+        .collect::<Vec<_>>();
+
+    for ((table, proof), ctl_vars) in tables.iter().zip(proofs).zip(&ctl_vars) {
+        table.verify_with_ctl(proof, ctl_vars, &mut challenger, config)?;
+    }
+    verify_cross_table_lookups::<F, D>(ctls, &ctl_zs_lasts, config)
+}
+
+/// Verifies the proof of one table of a multi-STARK system. `challenger` must have observed the
+/// trace caps of all the tables, and the tables must be verified in the order in which they were
+/// proven. The cross-table lookups themselves are checked separately, using
+/// `verify_cross_table_lookups`.
+pub fn verify_stark_proof_with_ctl<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    S: Stark<F, D>,
+    const D: usize,
+>(
+    stark: &S,
+    proof_with_pis: StarkProofWithPublicInputs<F, C, D>,
+    ctl_vars: &[CtlCheckVars<F, F::Extension, F::Extension, D>],
+    challenger: &mut Challenger<F, C::Hasher>,
+    config: &StarkConfig,
+) -> Result<()>
+where
+    [(); S::COLUMNS]:,
+    [(); S::PUBLIC_INPUTS]:,
+    [(); C::Hasher::HASH_SIZE]:,
+{
+    ensure!(proof_with_pis.public_inputs.len() == S::PUBLIC_INPUTS);
+    let degree_bits = proof_with_pis.proof.recover_degree_bits(config);
+    let challenges = proof_with_pis
+        .proof
+        .get_challenges(challenger, stark, config, degree_bits);
+    verify_stark_proof_with_challenges(
+        stark,
+        proof_with_pis,
+        challenges,
+        ctl_vars,
+        degree_bits,
+        config,
+    )
 }
 
 pub(crate) fn verify_stark_proof_with_challenges<
@@ -45,9 +116,10 @@ pub(crate) fn verify_stark_proof_with_challenges<
     S: Stark<F, D>,
     const D: usize,
 >(
-    stark: S,
+    stark: &S,
     proof_with_pis: StarkProofWithPublicInputs<F, C, D>,
     challenges: StarkProofChallenges<F, D>,
+    ctl_vars: &[CtlCheckVars<F, F::Extension, F::Extension, D>],
     degree_bits: usize,
     config: &StarkConfig,
 ) -> Result<()>
@@ -56,8 +128,8 @@ where
     [(); S::PUBLIC_INPUTS]:,
     [(); C::Hasher::HASH_SIZE]:,
 {
-    validate_proof_shape(&stark, &proof_with_pis, config)?;
-    check_permutation_options(&stark, &proof_with_pis, &challenges)?;
+    validate_proof_shape(stark, &proof_with_pis, ctl_vars.len(), config)?;
+    check_permutation_options(stark, &proof_with_pis, &challenges, ctl_vars.len())?;
     let StarkProofWithPublicInputs {
         proof,
         public_inputs,
@@ -65,8 +137,9 @@ where
     let StarkOpeningSet {
         local_values,
         next_values,
-        permutation_zs,
-        permutation_zs_next,
+        permutation_ctl_zs,
+        permutation_ctl_zs_next,
+        ctl_zs_last: _,
         quotient_polys,
     } = &proof.openings;
     let vars = StarkEvaluationVars {
@@ -93,16 +166,18 @@ where
         l_0,
         l_last,
     );
+    let num_permutation_zs = stark.num_permutation_batches(config);
     let permutation_data = stark.uses_permutation_args().then(|| PermutationCheckVars {
-        local_zs: permutation_zs.as_ref().unwrap().clone(),
-        next_zs: permutation_zs_next.as_ref().unwrap().clone(),
+        local_zs: permutation_ctl_zs.as_ref().unwrap()[..num_permutation_zs].to_vec(),
+        next_zs: permutation_ctl_zs_next.as_ref().unwrap()[..num_permutation_zs].to_vec(),
         permutation_challenge_sets: challenges.permutation_challenge_sets.unwrap(),
     });
     eval_vanishing_poly::<F, F::Extension, F::Extension, S, D, D>(
-        &stark,
+        stark,
         config,
         vars,
         permutation_data,
+        ctl_vars,
         &mut consumer,
     );
     let vanishing_polys_zeta = consumer.accumulators();
@@ -110,23 +185,27 @@ where
     // Check each polynomial identity, of the form `vanishing(x) = Z_H(x) quotient(x)`, at zeta.
     let zeta_pow_deg = challenges.stark_zeta.exp_power_of_2(degree_bits);
     let z_h_zeta = zeta_pow_deg - F::Extension::ONE;
-    // `quotient_polys_zeta` holds `num_challenges * quotient_degree_factor` evaluations.
-    // Each chunk of `quotient_degree_factor` holds the evaluations of `t_0(zeta),...,t_{quotient_degree_factor-1}(zeta)`
-    // where the "real" quotient polynomial is `t(X) = t_0(X) + t_1(X)*X^n + t_2(X)*X^{2n} + ...`.
-    // So to reconstruct `t(zeta)` we can compute `reduce_with_powers(chunk, zeta^n)` for each
-    // `quotient_degree_factor`-sized chunk of the original evaluations.
+    // `quotient_polys_zeta` holds `num_challenges * num_quotient_chunks` evaluations.
+    // Each chunk of `num_quotient_chunks` holds the evaluations of `t_0(zeta),...,t_{num_quotient_chunks-1}(zeta)`
+    // where the "real" quotient polynomial is `t(X) = t_0(X) + t_1(X)*X^m + t_2(X)*X^{2m} + ...`,
+    // and `m` is the degree of the committed polynomials, i.e. `n`, or `2n` in zero-knowledge mode.
+    // So to reconstruct `t(zeta)` we can compute `reduce_with_powers(chunk, zeta^m)` for each
+    // `num_quotient_chunks`-sized chunk of the original evaluations.
+    let zeta_pow_chunk_deg = challenges
+        .stark_zeta
+        .exp_power_of_2(degree_bits + config.blinding_degree_bits());
     for (i, chunk) in quotient_polys
-        .chunks(stark.quotient_degree_factor())
+        .chunks(stark.num_quotient_chunks(config))
         .enumerate()
     {
         ensure!(
-            vanishing_polys_zeta[i] == z_h_zeta * reduce_with_powers(chunk, zeta_pow_deg),
+            vanishing_polys_zeta[i] == z_h_zeta * reduce_with_powers(chunk, zeta_pow_chunk_deg),
             "Mismatch between evaluation and opening of quotient polynomial"
         );
     }
 
     let merkle_caps = once(proof.trace_cap)
-        .chain(proof.permutation_zs_cap)
+        .chain(proof.permutation_ctl_zs_cap)
         .chain(once(proof.quotient_polys_cap))
         .collect_vec();
 
@@ -134,6 +213,7 @@ where
         &stark.fri_instance(
             challenges.stark_zeta,
             F::primitive_root_of_unity(degree_bits),
+            ctl_vars.len(),
             config,
         ),
         &proof.openings.to_fri_openings(),
@@ -149,6 +229,7 @@ where
 fn validate_proof_shape<F, C, S, const D: usize>(
     stark: &S,
     proof_with_pis: &StarkProofWithPublicInputs<F, C, D>,
+    num_ctl_zs: usize,
     config: &StarkConfig,
 ) -> anyhow::Result<()>
 where
@@ -166,7 +247,7 @@ where
 
     let StarkProof {
         trace_cap,
-        permutation_zs_cap,
+        permutation_ctl_zs_cap,
         quotient_polys_cap,
         openings,
         // The shape of the opening proof will be checked in the FRI verifier (see
@@ -177,8 +258,9 @@ where
     let StarkOpeningSet {
         local_values,
         next_values,
-        permutation_zs,
-        permutation_zs_next,
+        permutation_ctl_zs,
+        permutation_ctl_zs_next,
+        ctl_zs_last,
         quotient_polys,
     } = openings;
 
@@ -186,7 +268,7 @@ where
 
     let fri_params = config.fri_params(degree_bits);
     let cap_height = fri_params.config.cap_height;
-    let num_zs = stark.num_permutation_batches(config);
+    let num_zs = stark.num_permutation_batches(config) + num_ctl_zs;
 
     ensure!(trace_cap.height() == cap_height);
     ensure!(quotient_polys_cap.height() == cap_height);
@@ -194,25 +276,26 @@ where
     ensure!(local_values.len() == S::COLUMNS);
     ensure!(next_values.len() == S::COLUMNS);
     ensure!(quotient_polys.len() == stark.num_quotient_polys(config));
+    ensure!(ctl_zs_last.len() == num_ctl_zs);
 
-    if stark.uses_permutation_args() {
-        let permutation_zs_cap = permutation_zs_cap
+    if num_zs > 0 {
+        let permutation_ctl_zs_cap = permutation_ctl_zs_cap
             .as_ref()
             .ok_or_else(|| anyhow!("Missing Zs cap"))?;
-        let permutation_zs = permutation_zs
+        let permutation_ctl_zs = permutation_ctl_zs
             .as_ref()
-            .ok_or_else(|| anyhow!("Missing permutation_zs"))?;
-        let permutation_zs_next = permutation_zs_next
+            .ok_or_else(|| anyhow!("Missing permutation_ctl_zs"))?;
+        let permutation_ctl_zs_next = permutation_ctl_zs_next
             .as_ref()
-            .ok_or_else(|| anyhow!("Missing permutation_zs_next"))?;
+            .ok_or_else(|| anyhow!("Missing permutation_ctl_zs_next"))?;
 
-        ensure!(permutation_zs_cap.height() == cap_height);
-        ensure!(permutation_zs.len() == num_zs);
-        ensure!(permutation_zs_next.len() == num_zs);
+        ensure!(permutation_ctl_zs_cap.height() == cap_height);
+        ensure!(permutation_ctl_zs.len() == num_zs);
+        ensure!(permutation_ctl_zs_next.len() == num_zs);
     } else {
-        ensure!(permutation_zs_cap.is_none());
-        ensure!(permutation_zs.is_none());
-        ensure!(permutation_zs_next.is_none());
+        ensure!(permutation_ctl_zs_cap.is_none());
+        ensure!(permutation_ctl_zs.is_none());
+        ensure!(permutation_ctl_zs_next.is_none());
     }
 
     Ok(())
@@ -230,8 +313,8 @@ fn eval_l_0_and_l_last<F: Field>(log_n: usize, x: F) -> (F, F) {
     (z_x * invs[0], z_x * invs[1])
 }
 
-/// Utility function to check that all permutation data wrapped in `Option`s are `Some` iff
-/// the Stark uses a permutation argument.
+/// Utility function to check that all permutation and cross-table lookup data wrapped in `Option`s
+/// are `Some` iff the Stark uses a permutation argument or cross-table lookups.
 fn check_permutation_options<
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
@@ -241,17 +324,22 @@ fn check_permutation_options<
     stark: &S,
     proof_with_pis: &StarkProofWithPublicInputs<F, C, D>,
     challenges: &StarkProofChallenges<F, D>,
+    num_ctl_zs: usize,
 ) -> Result<()> {
-    let options_is_some = [
-        proof_with_pis.proof.permutation_zs_cap.is_some(),
-        proof_with_pis.proof.openings.permutation_zs.is_some(),
-        proof_with_pis.proof.openings.permutation_zs_next.is_some(),
-        challenges.permutation_challenge_sets.is_some(),
+    let uses_permutation_args = stark.uses_permutation_args();
+    let uses_zs = uses_permutation_args || num_ctl_zs > 0;
+    let zs_options_is_some = [
+        proof_with_pis.proof.permutation_ctl_zs_cap.is_some(),
+        proof_with_pis.proof.openings.permutation_ctl_zs.is_some(),
+        proof_with_pis
+            .proof
+            .openings
+            .permutation_ctl_zs_next
+            .is_some(),
     ];
     ensure!(
-        options_is_some
-            .into_iter()
-            .all(|b| b == stark.uses_permutation_args()),
+        zs_options_is_some.into_iter().all(|b| b == uses_zs)
+            && challenges.permutation_challenge_sets.is_some() == uses_permutation_args,
         "Permutation data doesn't match with Stark configuration."
     );
     Ok(())
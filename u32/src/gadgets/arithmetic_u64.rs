@@ -0,0 +1,340 @@
+use alloc::vec::Vec;
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use crate::gadgets::arithmetic_u32::{CircuitBuilderU32, U32Target};
+use crate::gadgets::bitwise_u32::CircuitBuilderBitwiseU32;
+
+/// A 64-bit value, as two 32-bit limbs in little-endian order.
+#[derive(Clone, Copy, Debug)]
+pub struct U64Target(pub [U32Target; 2]);
+
+impl U64Target {
+    pub fn lo(&self) -> U32Target {
+        self.0[0]
+    }
+
+    pub fn hi(&self) -> U32Target {
+        self.0[1]
+    }
+}
+
+pub trait CircuitBuilderU64<F: RichField + Extendable<D>, const D: usize> {
+    fn add_virtual_u64_target(&mut self) -> U64Target;
+
+    fn add_virtual_u64_targets(&mut self, n: usize) -> Vec<U64Target>;
+
+    fn constant_u64(&mut self, c: u64) -> U64Target;
+
+    fn zero_u64(&mut self) -> U64Target;
+
+    fn one_u64(&mut self) -> U64Target;
+
+    fn connect_u64(&mut self, x: U64Target, y: U64Target);
+
+    // Returns a + b + carry, as a pair (result, carry), where carry is 0 or 1.
+    fn add_u64_with_carry(
+        &mut self,
+        a: U64Target,
+        b: U64Target,
+        carry: U32Target,
+    ) -> (U64Target, U32Target);
+
+    fn add_u64(&mut self, a: U64Target, b: U64Target) -> (U64Target, U32Target);
+
+    // Returns the sum of `to_add`, as a pair (result, carry).
+    fn add_many_u64(&mut self, to_add: &[U64Target]) -> (U64Target, U32Target);
+
+    // Returns x - y - borrow, as a pair (result, borrow), where borrow is 0 or 1 depending on whether borrowing from the next digit is required (iff y + borrow > x).
+    fn sub_u64(&mut self, x: U64Target, y: U64Target, borrow: U32Target) -> (U64Target, U32Target);
+
+    // Returns the full product a * b, as a pair (low, high).
+    fn mul_u64(&mut self, a: U64Target, b: U64Target) -> (U64Target, U64Target);
+
+    fn and_u64(&mut self, x: U64Target, y: U64Target) -> U64Target;
+
+    fn or_u64(&mut self, x: U64Target, y: U64Target) -> U64Target;
+
+    fn xor_u64(&mut self, x: U64Target, y: U64Target) -> U64Target;
+
+    fn not_u64(&mut self, x: U64Target) -> U64Target;
+
+    /// Returns `x << n`. Panics if `n >= 64`.
+    fn shift_left_u64(&mut self, x: U64Target, n: usize) -> U64Target;
+
+    /// Returns `x >> n`. Panics if `n >= 64`.
+    fn shift_right_u64(&mut self, x: U64Target, n: usize) -> U64Target;
+
+    fn rotate_left_u64(&mut self, x: U64Target, n: usize) -> U64Target;
+
+    fn rotate_right_u64(&mut self, x: U64Target, n: usize) -> U64Target;
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderU64<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn add_virtual_u64_target(&mut self) -> U64Target {
+        U64Target([self.add_virtual_u32_target(), self.add_virtual_u32_target()])
+    }
+
+    fn add_virtual_u64_targets(&mut self, n: usize) -> Vec<U64Target> {
+        (0..n).map(|_| self.add_virtual_u64_target()).collect()
+    }
+
+    fn constant_u64(&mut self, c: u64) -> U64Target {
+        U64Target([
+            self.constant_u32(c as u32),
+            self.constant_u32((c >> 32) as u32),
+        ])
+    }
+
+    fn zero_u64(&mut self) -> U64Target {
+        U64Target([self.zero_u32(); 2])
+    }
+
+    fn one_u64(&mut self) -> U64Target {
+        U64Target([self.one_u32(), self.zero_u32()])
+    }
+
+    fn connect_u64(&mut self, x: U64Target, y: U64Target) {
+        for (x_limb, y_limb) in x.0.into_iter().zip(y.0) {
+            self.connect_u32(x_limb, y_limb);
+        }
+    }
+
+    fn add_u64_with_carry(
+        &mut self,
+        a: U64Target,
+        b: U64Target,
+        carry: U32Target,
+    ) -> (U64Target, U32Target) {
+        let (lo, carry) = self.add_u32s_with_carry(&[a.lo(), b.lo()], carry);
+        let (hi, carry) = self.add_u32s_with_carry(&[a.hi(), b.hi()], carry);
+        (U64Target([lo, hi]), carry)
+    }
+
+    fn add_u64(&mut self, a: U64Target, b: U64Target) -> (U64Target, U32Target) {
+        let zero = self.zero_u32();
+        self.add_u64_with_carry(a, b, zero)
+    }
+
+    fn add_many_u64(&mut self, to_add: &[U64Target]) -> (U64Target, U32Target) {
+        match to_add.len() {
+            0 => (self.zero_u64(), self.zero_u32()),
+            1 => (to_add[0], self.zero_u32()),
+            _ => {
+                let lo_limbs = to_add.iter().map(U64Target::lo).collect::<Vec<_>>();
+                let hi_limbs = to_add.iter().map(U64Target::hi).collect::<Vec<_>>();
+                let (lo, carry) = self.add_many_u32(&lo_limbs);
+                let (hi, carry) = self.add_u32s_with_carry(&hi_limbs, carry);
+                (U64Target([lo, hi]), carry)
+            }
+        }
+    }
+
+    fn sub_u64(&mut self, x: U64Target, y: U64Target, borrow: U32Target) -> (U64Target, U32Target) {
+        let (lo, borrow) = self.sub_u32(x.lo(), y.lo(), borrow);
+        let (hi, borrow) = self.sub_u32(x.hi(), y.hi(), borrow);
+        (U64Target([lo, hi]), borrow)
+    }
+
+    fn mul_u64(&mut self, a: U64Target, b: U64Target) -> (U64Target, U64Target) {
+        // Schoolbook multiplication. Each `mul_add_u32` is at most `(2^32 - 1)^2 + 2^32 - 1`, so
+        // it fits in 64 bits.
+        let (limb_0, carry) = self.mul_u32(a.lo(), b.lo());
+        let (cross_lo, cross_hi) = self.mul_add_u32(a.lo(), b.hi(), carry);
+        let (limb_1, carry) = self.mul_add_u32(a.hi(), b.lo(), cross_lo);
+        let (top_lo, top_hi) = self.mul_add_u32(a.hi(), b.hi(), cross_hi);
+        let (limb_2, carry) = self.add_u32(top_lo, carry);
+        // The full product is less than `2^128`, so this cannot overflow.
+        let (limb_3, _) = self.add_u32(top_hi, carry);
+        (U64Target([limb_0, limb_1]), U64Target([limb_2, limb_3]))
+    }
+
+    fn and_u64(&mut self, x: U64Target, y: U64Target) -> U64Target {
+        U64Target([self.and_u32(x.lo(), y.lo()), self.and_u32(x.hi(), y.hi())])
+    }
+
+    fn or_u64(&mut self, x: U64Target, y: U64Target) -> U64Target {
+        U64Target([self.or_u32(x.lo(), y.lo()), self.or_u32(x.hi(), y.hi())])
+    }
+
+    fn xor_u64(&mut self, x: U64Target, y: U64Target) -> U64Target {
+        U64Target([self.xor_u32(x.lo(), y.lo()), self.xor_u32(x.hi(), y.hi())])
+    }
+
+    fn not_u64(&mut self, x: U64Target) -> U64Target {
+        U64Target([self.not_u32(x.lo()), self.not_u32(x.hi())])
+    }
+
+    fn shift_left_u64(&mut self, x: U64Target, n: usize) -> U64Target {
+        assert!(n < 64, "Shift amount must be less than 64.");
+        if n == 0 {
+            return x;
+        }
+        if n >= 32 {
+            let zero = self.zero_u32();
+            return U64Target([zero, self.shift_left_u32(x.lo(), n - 32)]);
+        }
+        let multiplier = self.constant_u32(1 << n);
+        let (lo, carry) = self.mul_u32(x.lo(), multiplier);
+        let (hi, _) = self.mul_add_u32(x.hi(), multiplier, carry);
+        U64Target([lo, hi])
+    }
+
+    fn shift_right_u64(&mut self, x: U64Target, n: usize) -> U64Target {
+        assert!(n < 64, "Shift amount must be less than 64.");
+        if n == 0 {
+            return x;
+        }
+        if n >= 32 {
+            let zero = self.zero_u32();
+            return U64Target([self.shift_right_u32(x.hi(), n - 32), zero]);
+        }
+        // Shift the whole value left by `32 - n`, and keep the middle two limbs.
+        let multiplier = self.constant_u32(1 << (32 - n));
+        let (_, carry) = self.mul_u32(x.lo(), multiplier);
+        let (lo, hi) = self.mul_add_u32(x.hi(), multiplier, carry);
+        U64Target([lo, hi])
+    }
+
+    fn rotate_left_u64(&mut self, x: U64Target, n: usize) -> U64Target {
+        let n = n % 64;
+        let x = if n >= 32 {
+            U64Target([x.hi(), x.lo()])
+        } else {
+            x
+        };
+        let n = n % 32;
+        if n == 0 {
+            return x;
+        }
+        let multiplier = self.constant_u32(1 << n);
+        let (lo_low, lo_high) = self.mul_u32(x.lo(), multiplier);
+        let (hi_low, hi_high) = self.mul_u32(x.hi(), multiplier);
+        // As in `rotate_left_u32`, the two halves of each sum have disjoint bits.
+        U64Target([
+            U32Target(self.add(lo_low.0, hi_high.0)),
+            U32Target(self.add(hi_low.0, lo_high.0)),
+        ])
+    }
+
+    fn rotate_right_u64(&mut self, x: U64Target, n: usize) -> U64Target {
+        self.rotate_left_u64(x, 64 - n % 64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use anyhow::Result;
+    use plonky2::iop::witness::PartialWitness;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use rand::rngs::OsRng;
+    use rand::Rng;
+
+    use super::*;
+    use crate::witness::WitnessU32;
+
+    #[test]
+    fn test_arithmetic_u64() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let mut rng = OsRng;
+        // Make sure both the carry and the borrow are exercised.
+        let x_value = rng.gen::<u64>() | (1 << 63);
+        let y_value = rng.gen::<u64>() | (1 << 63);
+        let x = builder.add_virtual_u64_target();
+        let y = builder.add_virtual_u64_target();
+        pw.set_u64_target(x, x_value);
+        pw.set_u64_target(y, y_value);
+
+        let (sum, carry) = builder.add_u64(x, y);
+        let (sum_value, carry_value) = x_value.overflowing_add(y_value);
+        let expected_sum = builder.constant_u64(sum_value);
+        let expected_carry = builder.constant_u32(carry_value as u32);
+        builder.connect_u64(sum, expected_sum);
+        builder.connect_u32(carry, expected_carry);
+
+        let one = builder.one_u32();
+        let (difference, borrow) = builder.sub_u64(x, y, one);
+        let (difference_value, borrow_0) = x_value.overflowing_sub(y_value);
+        let (difference_value, borrow_1) = difference_value.overflowing_sub(1);
+        let expected_difference = builder.constant_u64(difference_value);
+        let expected_borrow = builder.constant_u32((borrow_0 || borrow_1) as u32);
+        builder.connect_u64(difference, expected_difference);
+        builder.connect_u32(borrow, expected_borrow);
+
+        let (low, high) = builder.mul_u64(x, y);
+        let product_value = x_value as u128 * y_value as u128;
+        let expected_low = builder.constant_u64(product_value as u64);
+        let expected_high = builder.constant_u64((product_value >> 64) as u64);
+        builder.connect_u64(low, expected_low);
+        builder.connect_u64(high, expected_high);
+
+        let to_add = [x, y, x, y, x];
+        let (many_sum, many_carry) = builder.add_many_u64(&to_add);
+        let many_sum_value = 3 * x_value as u128 + 2 * y_value as u128;
+        let expected_many_sum = builder.constant_u64(many_sum_value as u64);
+        let expected_many_carry = builder.constant_u32((many_sum_value >> 64) as u32);
+        builder.connect_u64(many_sum, expected_many_sum);
+        builder.connect_u32(many_carry, expected_many_carry);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)
+    }
+
+    #[test]
+    fn test_bitwise_u64() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let mut rng = OsRng;
+        let (x_value, y_value) = (rng.gen::<u64>(), rng.gen::<u64>());
+        let x = builder.add_virtual_u64_target();
+        let y = builder.add_virtual_u64_target();
+        pw.set_u64_target(x, x_value);
+        pw.set_u64_target(y, y_value);
+
+        let mut results = vec![
+            (builder.and_u64(x, y), x_value & y_value),
+            (builder.or_u64(x, y), x_value | y_value),
+            (builder.xor_u64(x, y), x_value ^ y_value),
+            (builder.not_u64(x), !x_value),
+        ];
+        for n in [0, 1, 31, 32, 45, 63] {
+            results.push((builder.shift_left_u64(x, n), x_value << n));
+            results.push((builder.shift_right_u64(x, n), x_value >> n));
+            results.push((builder.rotate_left_u64(x, n), x_value.rotate_left(n as u32)));
+            results.push((
+                builder.rotate_right_u64(x, n),
+                x_value.rotate_right(n as u32),
+            ));
+        }
+
+        for (result, expected) in results {
+            let expected = builder.constant_u64(expected);
+            builder.connect_u64(result, expected);
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)
+    }
+}
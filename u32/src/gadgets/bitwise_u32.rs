@@ -0,0 +1,162 @@
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use crate::gadgets::arithmetic_u32::{CircuitBuilderU32, U32Target};
+use crate::gates::bitwise_u32::{U32BitwiseGate, U32BitwiseOp};
+
+/// Bitwise operations on 32-bit values. Like the rest of this crate, the inputs are assumed to be
+/// range-checked; all outputs are 32-bit values.
+///
+/// `and`, `or` and `xor` use a [`U32BitwiseGate`]. Shifts and rotations by a constant `n` multiply
+/// by `2^n` with a `U32ArithmeticGate`, whose low and high outputs are `x << n` and
+/// `x >> (32 - n)`. `not` is a single arithmetic operation.
+pub trait CircuitBuilderBitwiseU32<F: RichField + Extendable<D>, const D: usize> {
+    fn bitwise_u32(&mut self, op: U32BitwiseOp, x: U32Target, y: U32Target) -> U32Target;
+
+    fn and_u32(&mut self, x: U32Target, y: U32Target) -> U32Target;
+
+    fn or_u32(&mut self, x: U32Target, y: U32Target) -> U32Target;
+
+    fn xor_u32(&mut self, x: U32Target, y: U32Target) -> U32Target;
+
+    fn not_u32(&mut self, x: U32Target) -> U32Target;
+
+    /// Returns `x << n`. Panics if `n >= 32`.
+    fn shift_left_u32(&mut self, x: U32Target, n: usize) -> U32Target;
+
+    /// Returns `x >> n`. Panics if `n >= 32`.
+    fn shift_right_u32(&mut self, x: U32Target, n: usize) -> U32Target;
+
+    fn rotate_left_u32(&mut self, x: U32Target, n: usize) -> U32Target;
+
+    fn rotate_right_u32(&mut self, x: U32Target, n: usize) -> U32Target;
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderBitwiseU32<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn bitwise_u32(&mut self, op: U32BitwiseOp, x: U32Target, y: U32Target) -> U32Target {
+        if let (Some(x), Some(y)) = (self.target_as_constant(x.0), self.target_as_constant(y.0)) {
+            let result = op.eval_u32(x.to_canonical_u64() as u32, y.to_canonical_u64() as u32);
+            return self.constant_u32(result);
+        }
+
+        let gate = U32BitwiseGate::<F, D>::new_from_config(&self.config, op);
+        let (row, copy) = self.find_slot(gate, &[], &[]);
+
+        self.connect(Target::wire(row, gate.wire_ith_input_x(copy)), x.0);
+        self.connect(Target::wire(row, gate.wire_ith_input_y(copy)), y.0);
+
+        U32Target(Target::wire(row, gate.wire_ith_output(copy)))
+    }
+
+    fn and_u32(&mut self, x: U32Target, y: U32Target) -> U32Target {
+        self.bitwise_u32(U32BitwiseOp::And, x, y)
+    }
+
+    fn or_u32(&mut self, x: U32Target, y: U32Target) -> U32Target {
+        self.bitwise_u32(U32BitwiseOp::Or, x, y)
+    }
+
+    fn xor_u32(&mut self, x: U32Target, y: U32Target) -> U32Target {
+        self.bitwise_u32(U32BitwiseOp::Xor, x, y)
+    }
+
+    fn not_u32(&mut self, x: U32Target) -> U32Target {
+        let max = self.constant(F::from_canonical_u32(u32::MAX));
+        U32Target(self.sub(max, x.0))
+    }
+
+    fn shift_left_u32(&mut self, x: U32Target, n: usize) -> U32Target {
+        assert!(n < 32, "Shift amount must be less than 32.");
+        if n == 0 {
+            return x;
+        }
+        let multiplier = self.constant_u32(1 << n);
+        self.mul_u32(x, multiplier).0
+    }
+
+    fn shift_right_u32(&mut self, x: U32Target, n: usize) -> U32Target {
+        assert!(n < 32, "Shift amount must be less than 32.");
+        if n == 0 {
+            return x;
+        }
+        let multiplier = self.constant_u32(1 << (32 - n));
+        self.mul_u32(x, multiplier).1
+    }
+
+    fn rotate_left_u32(&mut self, x: U32Target, n: usize) -> U32Target {
+        let n = n % 32;
+        if n == 0 {
+            return x;
+        }
+        let multiplier = self.constant_u32(1 << n);
+        let (low, high) = self.mul_u32(x, multiplier);
+        // The low half has its `n` lowest bits clear, and the high half is less than `2^n`.
+        U32Target(self.add(low.0, high.0))
+    }
+
+    fn rotate_right_u32(&mut self, x: U32Target, n: usize) -> U32Target {
+        self.rotate_left_u32(x, 32 - n % 32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use anyhow::Result;
+    use plonky2::iop::witness::PartialWitness;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use rand::rngs::OsRng;
+    use rand::Rng;
+
+    use super::*;
+    use crate::witness::WitnessU32;
+
+    #[test]
+    fn test_bitwise_u32() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let mut rng = OsRng;
+        let (x_value, y_value) = (rng.gen::<u32>(), rng.gen::<u32>());
+        let x = builder.add_virtual_u32_target();
+        let y = builder.add_virtual_u32_target();
+        pw.set_u32_target(x, x_value);
+        pw.set_u32_target(y, y_value);
+
+        let mut results = vec![
+            (builder.and_u32(x, y), x_value & y_value),
+            (builder.or_u32(x, y), x_value | y_value),
+            (builder.xor_u32(x, y), x_value ^ y_value),
+            (builder.not_u32(x), !x_value),
+        ];
+        for n in [0, 1, 7, 31] {
+            results.push((builder.shift_left_u32(x, n), x_value << n));
+            results.push((builder.shift_right_u32(x, n), x_value >> n));
+            results.push((builder.rotate_left_u32(x, n), x_value.rotate_left(n as u32)));
+            results.push((
+                builder.rotate_right_u32(x, n),
+                x_value.rotate_right(n as u32),
+            ));
+        }
+
+        for (result, expected) in results {
+            let expected = builder.constant_u32(expected);
+            builder.connect_u32(result, expected);
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        data.verify(proof)
+    }
+}
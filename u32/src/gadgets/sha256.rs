@@ -0,0 +1,253 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::util::ceil_div_usize;
+
+use crate::gadgets::arithmetic_u32::{CircuitBuilderU32, U32Target};
+use crate::gates::sha256::{Sha256Functions, Sha256Gate};
+
+/// The initial hash value of SHA-256.
+const INITIAL_STATE: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// The round constants of SHA-256.
+const ROUND_CONSTANTS: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+pub trait CircuitBuilderSha256<F: RichField + Extendable<D>, const D: usize> {
+    /// Returns the SHA-256 digest of `message`, given as bits in big-endian order, as eight
+    /// big-endian words. The message length is fixed when building the circuit.
+    fn sha256(&mut self, message: &[BoolTarget]) -> [U32Target; 8];
+
+    /// Returns the SHA-256 digest of the message of `4 * message.len()` bytes whose big-endian
+    /// words are `message`. The words are assumed to be at most 32 bits.
+    fn sha256_u32(&mut self, message: &[U32Target]) -> [U32Target; 8];
+
+    /// Applies the SHA-256 compression function to `state` and a block of 16 words, which are
+    /// assumed to be at most 32 bits.
+    fn sha256_compress(&mut self, state: [U32Target; 8], block: &[U32Target; 16])
+        -> [U32Target; 8];
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderSha256<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn sha256(&mut self, message: &[BoolTarget]) -> [U32Target; 8] {
+        let len = message.len();
+        let padded_len = ceil_div_usize(len + 65, 512) * 512;
+
+        let mut bits = message.to_vec();
+        bits.push(self._true());
+        bits.resize(padded_len - 64, self._false());
+        for i in (0..64).rev() {
+            bits.push(self.constant_bool((len as u64 >> i) & 1 == 1));
+        }
+
+        let words = bits
+            .chunks(32)
+            .map(|word_bits| word_from_be_bits(self, word_bits))
+            .collect::<Vec<_>>();
+        sha256_padded(self, &words)
+    }
+
+    fn sha256_u32(&mut self, message: &[U32Target]) -> [U32Target; 8] {
+        let len = 32 * message.len() as u64;
+        let padded_len = ceil_div_usize(message.len() + 3, 16) * 16;
+
+        let mut words = message.to_vec();
+        words.push(self.constant_u32(1 << 31));
+        words.resize(padded_len - 2, self.zero_u32());
+        words.push(self.constant_u32((len >> 32) as u32));
+        words.push(self.constant_u32(len as u32));
+        sha256_padded(self, &words)
+    }
+
+    fn sha256_compress(
+        &mut self,
+        state: [U32Target; 8],
+        block: &[U32Target; 16],
+    ) -> [U32Target; 8] {
+        let mut w = block.to_vec();
+        for t in 16..64 {
+            let [s0, s1] = sha256_functions(
+                self,
+                Sha256Functions::MessageSchedule,
+                &[w[t - 15], w[t - 2]],
+            );
+            let (w_t, _) = self.add_many_u32(&[s1, w[t - 7], s0, w[t - 16]]);
+            w.push(w_t);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for t in 0..64 {
+            let [s1, ch] = sha256_functions(self, Sha256Functions::Sigma1Ch, &[e, f, g]);
+            let [s0, maj] = sha256_functions(self, Sha256Functions::Sigma0Maj, &[a, b, c]);
+            let k = self.constant_u32(ROUND_CONSTANTS[t]);
+            // e' = d + T1 and a' = T1 + T2, where T1 = h + Σ1(e) + Ch(e, f, g) + k + w and
+            // T2 = Σ0(a) + Maj(a, b, c). Each is a single addition, so T1 is never materialized.
+            let (new_e, _) = self.add_many_u32(&[d, h, s1, ch, k, w[t]]);
+            let (new_a, _) = self.add_many_u32(&[h, s1, ch, k, w[t], s0, maj]);
+            h = g;
+            g = f;
+            f = e;
+            e = new_e;
+            d = c;
+            c = b;
+            b = a;
+            a = new_a;
+        }
+
+        let mut new_state = state;
+        for (x, y) in new_state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *x = self.add_u32(*x, y).0;
+        }
+        new_state
+    }
+}
+
+/// Hashes a message which has already been padded to a multiple of 16 words.
+fn sha256_padded<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    words: &[U32Target],
+) -> [U32Target; 8] {
+    let mut state = INITIAL_STATE.map(|x| builder.constant_u32(x));
+    for block in words.chunks_exact(16) {
+        state = builder.sha256_compress(state, block.try_into().unwrap());
+    }
+    state
+}
+
+/// Computes the functions `functions` of `inputs` with a [`Sha256Gate`].
+fn sha256_functions<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    functions: Sha256Functions,
+    inputs: &[U32Target],
+) -> [U32Target; 2] {
+    let gate = Sha256Gate::<F, D>::new(functions);
+    let row = builder.add_gate(gate, vec![]);
+    for (i, input) in inputs.iter().enumerate() {
+        builder.connect(Target::wire(row, gate.wire_ith_input(i)), input.0);
+    }
+    [0, 1].map(|i| U32Target(Target::wire(row, gate.wire_ith_output(i))))
+}
+
+/// Packs 32 big-endian bits into a word. Words of constant bits, like most of the padding, are
+/// packed without any gates.
+pub(crate) fn word_from_be_bits<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    bits: &[BoolTarget],
+) -> U32Target {
+    let constant_bits = bits
+        .iter()
+        .map(|bit| builder.target_as_constant(bit.target))
+        .collect::<Option<Vec<_>>>();
+    match constant_bits {
+        Some(constant_bits) => {
+            let word = constant_bits
+                .into_iter()
+                .fold(0, |acc, bit| (acc << 1) | bit.is_one() as u32);
+            builder.constant_u32(word)
+        }
+        None => U32Target(builder.le_sum(bits.iter().rev())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use rand::rngs::OsRng;
+    use rand::Rng;
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+    use crate::witness::WitnessU32;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    fn expected_digest(message: &[u8]) -> Vec<u32> {
+        Sha256::digest(message)
+            .chunks(4)
+            .map(|word| u32::from_be_bytes(word.try_into().unwrap()))
+            .collect()
+    }
+
+    /// Proves that the circuit computes the digest of `message`, given as bits.
+    fn test_sha256_bits(message: &[u8]) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let bits = (0..8 * message.len())
+            .map(|_| builder.add_virtual_bool_target_safe())
+            .collect::<Vec<_>>();
+        let digest = builder.sha256(&bits);
+        for (word, expected) in digest.into_iter().zip(expected_digest(message)) {
+            let expected = builder.constant_u32(expected);
+            builder.connect_u32(word, expected);
+        }
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        for (i, &bit) in bits.iter().enumerate() {
+            pw.set_bool_target(bit, (message[i / 8] >> (7 - i % 8)) & 1 == 1);
+        }
+        let proof = data.prove(pw)?;
+        data.verify(proof)
+    }
+
+    #[test]
+    fn test_sha256_abc() -> Result<()> {
+        test_sha256_bits(b"abc")
+    }
+
+    #[test]
+    fn test_sha256_two_blocks() -> Result<()> {
+        // 56 bytes don't leave room for the length, so the padding spills into a second block.
+        let mut rng = OsRng;
+        let message = (0..56).map(|_| rng.gen()).collect::<Vec<u8>>();
+        test_sha256_bits(&message)
+    }
+
+    #[test]
+    fn test_sha256_u32() -> Result<()> {
+        let mut rng = OsRng;
+        let message = (0..20).map(|_| rng.gen()).collect::<Vec<u32>>();
+        let bytes = message
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect::<Vec<_>>();
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let words = builder.add_virtual_u32_targets(message.len());
+        let digest = builder.sha256_u32(&words);
+        for (word, expected) in digest.into_iter().zip(expected_digest(&bytes)) {
+            let expected = builder.constant_u32(expected);
+            builder.connect_u32(word, expected);
+        }
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        for (&target, &word) in words.iter().zip(&message) {
+            pw.set_u32_target(target, word);
+        }
+        let proof = data.prove(pw)?;
+        data.verify(proof)
+    }
+}
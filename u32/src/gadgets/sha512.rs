@@ -0,0 +1,273 @@
+use alloc::vec::Vec;
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::BoolTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::util::ceil_div_usize;
+
+use crate::gadgets::arithmetic_u64::{CircuitBuilderU64, U64Target};
+use crate::gadgets::sha256::word_from_be_bits;
+
+/// The initial hash value of SHA-512.
+const INITIAL_STATE: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// The round constants of SHA-512.
+const ROUND_CONSTANTS: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+pub trait CircuitBuilderSha512<F: RichField + Extendable<D>, const D: usize> {
+    /// Returns the SHA-512 digest of `message`, given as bits in big-endian order, as eight
+    /// big-endian words. The message length is fixed when building the circuit.
+    fn sha512(&mut self, message: &[BoolTarget]) -> [U64Target; 8];
+
+    /// Applies the SHA-512 compression function to `state` and a block of 16 words, which are
+    /// assumed to be range-checked.
+    fn sha512_compress(&mut self, state: [U64Target; 8], block: &[U64Target; 16])
+        -> [U64Target; 8];
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderSha512<F, D>
+    for CircuitBuilder<F, D>
+{
+    fn sha512(&mut self, message: &[BoolTarget]) -> [U64Target; 8] {
+        let len = message.len();
+        let padded_len = ceil_div_usize(len + 129, 1024) * 1024;
+
+        let mut bits = message.to_vec();
+        bits.push(self._true());
+        bits.resize(padded_len - 128, self._false());
+        for i in (0..128).rev() {
+            bits.push(self.constant_bool((len as u128 >> i) & 1 == 1));
+        }
+
+        let words = bits
+            .chunks(64)
+            .map(|word_bits| {
+                let hi = word_from_be_bits(self, &word_bits[..32]);
+                let lo = word_from_be_bits(self, &word_bits[32..]);
+                U64Target([lo, hi])
+            })
+            .collect::<Vec<_>>();
+
+        let mut state = INITIAL_STATE.map(|x| self.constant_u64(x));
+        for block in words.chunks_exact(16) {
+            state = self.sha512_compress(state, block.try_into().unwrap());
+        }
+        state
+    }
+
+    fn sha512_compress(
+        &mut self,
+        state: [U64Target; 8],
+        block: &[U64Target; 16],
+    ) -> [U64Target; 8] {
+        let mut w = block.to_vec();
+        for t in 16..80 {
+            let s0 = sigma(self, w[t - 15], [1, 8], 7);
+            let s1 = sigma(self, w[t - 2], [19, 61], 6);
+            let (w_t, _) = self.add_many_u64(&[s1, w[t - 7], s0, w[t - 16]]);
+            w.push(w_t);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for t in 0..80 {
+            let s1 = big_sigma(self, e, [14, 18, 41]);
+            // Ch(e, f, g) = g ^ (e & (f ^ g))
+            let f_xor_g = self.xor_u64(f, g);
+            let e_and_f_xor_g = self.and_u64(e, f_xor_g);
+            let ch = self.xor_u64(g, e_and_f_xor_g);
+
+            let s0 = big_sigma(self, a, [28, 34, 39]);
+            // Maj(a, b, c) = (a & b) ^ (c & (a ^ b))
+            let a_and_b = self.and_u64(a, b);
+            let a_xor_b = self.xor_u64(a, b);
+            let c_and_a_xor_b = self.and_u64(c, a_xor_b);
+            let maj = self.xor_u64(a_and_b, c_and_a_xor_b);
+
+            let k = self.constant_u64(ROUND_CONSTANTS[t]);
+            // As in SHA-256, T1 = h + Σ1(e) + Ch(e, f, g) + k + w is never materialized.
+            let (new_e, _) = self.add_many_u64(&[d, h, s1, ch, k, w[t]]);
+            let (new_a, _) = self.add_many_u64(&[h, s1, ch, k, w[t], s0, maj]);
+            h = g;
+            g = f;
+            f = e;
+            e = new_e;
+            d = c;
+            c = b;
+            b = a;
+            a = new_a;
+        }
+
+        let mut new_state = state;
+        for (x, y) in new_state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *x = self.add_u64(*x, y).0;
+        }
+        new_state
+    }
+}
+
+/// One of the SHA-512 `Σ` functions, i.e. the XOR of three right rotations of `x`.
+fn big_sigma<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    x: U64Target,
+    rotations: [usize; 3],
+) -> U64Target {
+    let [r0, r1, r2] = rotations.map(|n| builder.rotate_right_u64(x, n));
+    let r0_xor_r1 = builder.xor_u64(r0, r1);
+    builder.xor_u64(r0_xor_r1, r2)
+}
+
+/// One of the SHA-512 `σ` functions, i.e. the XOR of two right rotations and a right shift of `x`.
+fn sigma<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    x: U64Target,
+    rotations: [usize; 2],
+    shift: usize,
+) -> U64Target {
+    let [r0, r1] = rotations.map(|n| builder.rotate_right_u64(x, n));
+    let s = builder.shift_right_u64(x, shift);
+    let r0_xor_r1 = builder.xor_u64(r0, r1);
+    builder.xor_u64(r0_xor_r1, s)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use rand::rngs::OsRng;
+    use rand::Rng;
+    use sha2::{Digest, Sha512};
+
+    use super::*;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    /// Proves that the circuit computes the digest of `message`, given as bits.
+    fn test_sha512_bits(message: &[u8]) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let bits = (0..8 * message.len())
+            .map(|_| builder.add_virtual_bool_target_safe())
+            .collect::<Vec<_>>();
+        let digest = builder.sha512(&bits);
+        for (word, expected) in digest.into_iter().zip(Sha512::digest(message).chunks(8)) {
+            let expected = builder.constant_u64(u64::from_be_bytes(expected.try_into().unwrap()));
+            builder.connect_u64(word, expected);
+        }
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        for (i, &bit) in bits.iter().enumerate() {
+            pw.set_bool_target(bit, (message[i / 8] >> (7 - i % 8)) & 1 == 1);
+        }
+        let proof = data.prove(pw)?;
+        data.verify(proof)
+    }
+
+    #[test]
+    fn test_sha512_abc() -> Result<()> {
+        test_sha512_bits(b"abc")
+    }
+
+    #[test]
+    fn test_sha512_two_blocks() -> Result<()> {
+        // 112 bytes don't leave room for the length, so the padding spills into a second block.
+        let mut rng = OsRng;
+        let message = (0..112).map(|_| rng.gen()).collect::<Vec<u8>>();
+        test_sha512_bits(&message)
+    }
+}
@@ -0,0 +1,412 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::marker::PhantomData;
+
+use plonky2::field::extension::Extendable;
+use plonky2::field::packed::PackedField;
+use plonky2::gates::gate::Gate;
+use plonky2::gates::packed_util::PackedEvaluableBase;
+use plonky2::gates::util::StridedConstraintConsumer;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGeneratorRef};
+use plonky2::iop::target::Target;
+use plonky2::iop::wire::Wire;
+use plonky2::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::CircuitConfig;
+use plonky2::plonk::vars::{
+    EvaluationTargets, EvaluationVars, EvaluationVarsBase, EvaluationVarsBaseBatch,
+    EvaluationVarsBasePacked,
+};
+use plonky2::util::serialization::{Buffer, IoError, IoResult, Read, Write};
+
+/// The bitwise operation computed by a [`U32BitwiseGate`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum U32BitwiseOp {
+    And,
+    Or,
+    Xor,
+}
+
+impl U32BitwiseOp {
+    /// Applies the operation to two bits, represented as field elements.
+    fn eval_bits<P: PackedField>(self, x: P, y: P) -> P {
+        match self {
+            U32BitwiseOp::And => x * y,
+            U32BitwiseOp::Or => x + y - x * y,
+            U32BitwiseOp::Xor => x + y - (x * y).doubles(),
+        }
+    }
+
+    fn eval_bits_circuit<F: RichField + Extendable<D>, const D: usize>(
+        self,
+        builder: &mut CircuitBuilder<F, D>,
+        x: ExtensionTarget<D>,
+        y: ExtensionTarget<D>,
+    ) -> ExtensionTarget<D> {
+        let sum = builder.add_extension(x, y);
+        match self {
+            U32BitwiseOp::And => builder.mul_extension(x, y),
+            U32BitwiseOp::Or => builder.arithmetic_extension(-F::ONE, F::ONE, x, y, sum),
+            U32BitwiseOp::Xor => builder.arithmetic_extension(-F::TWO, F::ONE, x, y, sum),
+        }
+    }
+
+    pub(crate) fn eval_u32(self, x: u32, y: u32) -> u32 {
+        match self {
+            U32BitwiseOp::And => x & y,
+            U32BitwiseOp::Or => x | y,
+            U32BitwiseOp::Xor => x ^ y,
+        }
+    }
+}
+
+/// A gate to perform a bitwise operation on pairs of 32-bit values. Both inputs are decomposed
+/// into bits, which range checks them as a side effect; the output is recombined from the
+/// results, so it is a 32-bit value too.
+#[derive(Copy, Clone, Debug)]
+pub struct U32BitwiseGate<F: RichField + Extendable<D>, const D: usize> {
+    pub op: U32BitwiseOp,
+    pub num_ops: usize,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> U32BitwiseGate<F, D> {
+    pub fn new_from_config(config: &CircuitConfig, op: U32BitwiseOp) -> Self {
+        Self {
+            op,
+            num_ops: Self::num_ops(config),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub(crate) fn num_ops(config: &CircuitConfig) -> usize {
+        let wires_per_op = Self::routed_wires_per_op() + 64;
+        (config.num_wires / wires_per_op).min(config.num_routed_wires / Self::routed_wires_per_op())
+    }
+
+    pub fn routed_wires_per_op() -> usize {
+        3
+    }
+
+    pub fn wire_ith_input_x(&self, i: usize) -> usize {
+        debug_assert!(i < self.num_ops);
+        Self::routed_wires_per_op() * i
+    }
+    pub fn wire_ith_input_y(&self, i: usize) -> usize {
+        debug_assert!(i < self.num_ops);
+        Self::routed_wires_per_op() * i + 1
+    }
+    pub fn wire_ith_output(&self, i: usize) -> usize {
+        debug_assert!(i < self.num_ops);
+        Self::routed_wires_per_op() * i + 2
+    }
+
+    pub fn wire_ith_x_jth_bit(&self, i: usize, j: usize) -> usize {
+        debug_assert!(i < self.num_ops);
+        debug_assert!(j < 32);
+        Self::routed_wires_per_op() * self.num_ops + 64 * i + j
+    }
+    pub fn wire_ith_y_jth_bit(&self, i: usize, j: usize) -> usize {
+        debug_assert!(i < self.num_ops);
+        debug_assert!(j < 32);
+        Self::routed_wires_per_op() * self.num_ops + 64 * i + 32 + j
+    }
+
+    fn eval<P: PackedField>(
+        &self,
+        local_wires: impl Fn(usize) -> P,
+        mut yield_constr: impl FnMut(P),
+    ) {
+        for i in 0..self.num_ops {
+            let mut computed_x = P::ZEROS;
+            let mut computed_y = P::ZEROS;
+            let mut computed_output = P::ZEROS;
+            for j in (0..32).rev() {
+                let x_bit = local_wires(self.wire_ith_x_jth_bit(i, j));
+                let y_bit = local_wires(self.wire_ith_y_jth_bit(i, j));
+                yield_constr(x_bit * (x_bit - P::ONES));
+                yield_constr(y_bit * (y_bit - P::ONES));
+
+                computed_x = computed_x.doubles() + x_bit;
+                computed_y = computed_y.doubles() + y_bit;
+                computed_output = computed_output.doubles() + self.op.eval_bits(x_bit, y_bit);
+            }
+            yield_constr(computed_x - local_wires(self.wire_ith_input_x(i)));
+            yield_constr(computed_y - local_wires(self.wire_ith_input_y(i)));
+            yield_constr(computed_output - local_wires(self.wire_ith_output(i)));
+        }
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for U32BitwiseGate<F, D> {
+    fn id(&self) -> String {
+        format!("{self:?}")
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_u8(self.op as u8)?;
+        dst.write_usize(self.num_ops)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let op = match src.read_u8()? {
+            0 => U32BitwiseOp::And,
+            1 => U32BitwiseOp::Or,
+            2 => U32BitwiseOp::Xor,
+            _ => return Err(IoError),
+        };
+        let num_ops = src.read_usize()?;
+        Ok(Self {
+            op,
+            num_ops,
+            _phantom: PhantomData,
+        })
+    }
+
+    fn eval_unfiltered(&self, vars: EvaluationVars<F, D>) -> Vec<F::Extension> {
+        let mut constraints = Vec::with_capacity(self.num_constraints());
+        self.eval(|i| vars.local_wires[i], |c| constraints.push(c));
+        constraints
+    }
+
+    fn eval_unfiltered_base_one(
+        &self,
+        _vars: EvaluationVarsBase<F>,
+        _yield_constr: StridedConstraintConsumer<F>,
+    ) {
+        panic!("use eval_unfiltered_base_packed instead");
+    }
+
+    fn eval_unfiltered_base_batch(&self, vars_base: EvaluationVarsBaseBatch<F>) -> Vec<F> {
+        self.eval_unfiltered_base_batch_packed(vars_base)
+    }
+
+    fn eval_unfiltered_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: EvaluationTargets<D>,
+    ) -> Vec<ExtensionTarget<D>> {
+        let mut constraints = Vec::with_capacity(self.num_constraints());
+        let two = builder.two_extension();
+
+        for i in 0..self.num_ops {
+            let mut computed_x = builder.zero_extension();
+            let mut computed_y = builder.zero_extension();
+            let mut computed_output = builder.zero_extension();
+            for j in (0..32).rev() {
+                let x_bit = vars.local_wires[self.wire_ith_x_jth_bit(i, j)];
+                let y_bit = vars.local_wires[self.wire_ith_y_jth_bit(i, j)];
+                constraints.push(builder.mul_sub_extension(x_bit, x_bit, x_bit));
+                constraints.push(builder.mul_sub_extension(y_bit, y_bit, y_bit));
+
+                computed_x = builder.mul_add_extension(two, computed_x, x_bit);
+                computed_y = builder.mul_add_extension(two, computed_y, y_bit);
+                let output_bit = self.op.eval_bits_circuit(builder, x_bit, y_bit);
+                computed_output = builder.mul_add_extension(two, computed_output, output_bit);
+            }
+
+            let x = vars.local_wires[self.wire_ith_input_x(i)];
+            let y = vars.local_wires[self.wire_ith_input_y(i)];
+            let output = vars.local_wires[self.wire_ith_output(i)];
+            constraints.push(builder.sub_extension(computed_x, x));
+            constraints.push(builder.sub_extension(computed_y, y));
+            constraints.push(builder.sub_extension(computed_output, output));
+        }
+
+        constraints
+    }
+
+    fn generators(&self, row: usize, _local_constants: &[F]) -> Vec<WitnessGeneratorRef<F>> {
+        (0..self.num_ops)
+            .map(|i| {
+                WitnessGeneratorRef::new(
+                    U32BitwiseGenerator {
+                        gate: *self,
+                        row,
+                        i,
+                    }
+                    .adapter(),
+                )
+            })
+            .collect()
+    }
+
+    fn num_wires(&self) -> usize {
+        self.num_ops * (Self::routed_wires_per_op() + 64)
+    }
+
+    fn num_constants(&self) -> usize {
+        0
+    }
+
+    fn degree(&self) -> usize {
+        2
+    }
+
+    fn num_constraints(&self) -> usize {
+        self.num_ops * (64 + 3)
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> PackedEvaluableBase<F, D>
+    for U32BitwiseGate<F, D>
+{
+    fn eval_unfiltered_base_packed<P: PackedField<Scalar = F>>(
+        &self,
+        vars: EvaluationVarsBasePacked<P>,
+        mut yield_constr: StridedConstraintConsumer<P>,
+    ) {
+        self.eval(|i| vars.local_wires[i], |c| yield_constr.one(c));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct U32BitwiseGenerator<F: RichField + Extendable<D>, const D: usize> {
+    gate: U32BitwiseGate<F, D>,
+    row: usize,
+    i: usize,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F>
+    for U32BitwiseGenerator<F, D>
+{
+    fn dependencies(&self) -> Vec<Target> {
+        let local_target = |column| Target::wire(self.row, column);
+
+        vec![
+            local_target(self.gate.wire_ith_input_x(self.i)),
+            local_target(self.gate.wire_ith_input_y(self.i)),
+        ]
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let local_wire = |column| Wire {
+            row: self.row,
+            column,
+        };
+        let get_u32 = |column| {
+            let value = witness.get_wire(local_wire(column)).to_canonical_u64();
+            debug_assert!(value < 1 << 32, "Input is not a 32-bit value.");
+            value as u32
+        };
+
+        let x = get_u32(self.gate.wire_ith_input_x(self.i));
+        let y = get_u32(self.gate.wire_ith_input_y(self.i));
+        for j in 0..32 {
+            out_buffer.set_wire(
+                local_wire(self.gate.wire_ith_x_jth_bit(self.i, j)),
+                F::from_bool((x >> j) & 1 == 1),
+            );
+            out_buffer.set_wire(
+                local_wire(self.gate.wire_ith_y_jth_bit(self.i, j)),
+                F::from_bool((y >> j) & 1 == 1),
+            );
+        }
+        out_buffer.set_wire(
+            local_wire(self.gate.wire_ith_output(self.i)),
+            F::from_canonical_u32(self.gate.op.eval_u32(x, y)),
+        );
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.row)?;
+        dst.write_usize(self.i)?;
+        self.gate.serialize(dst)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let row = src.read_usize()?;
+        let i = src.read_usize()?;
+        let gate = U32BitwiseGate::<F, D>::deserialize(src)?;
+        Ok(Self { gate, row, i })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::{Field, Sample};
+    use plonky2::gates::gate_testing::{test_eval_fns, test_low_degree};
+    use plonky2::hash::hash_types::HashOut;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use rand::rngs::OsRng;
+    use rand::Rng;
+
+    use super::*;
+
+    const ALL_OPS: [U32BitwiseOp; 3] = [U32BitwiseOp::And, U32BitwiseOp::Or, U32BitwiseOp::Xor];
+
+    #[test]
+    fn low_degree() {
+        for op in ALL_OPS {
+            test_low_degree::<GoldilocksField, _, 4>(
+                U32BitwiseGate::<GoldilocksField, 4>::new_from_config(
+                    &CircuitConfig::standard_recursion_config(),
+                    op,
+                ),
+            )
+        }
+    }
+
+    #[test]
+    fn eval_fns() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        for op in ALL_OPS {
+            test_eval_fns::<F, C, _, D>(U32BitwiseGate::<F, D>::new_from_config(
+                &CircuitConfig::standard_recursion_config(),
+                op,
+            ))?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_gate_constraint() {
+        type F = GoldilocksField;
+        const D: usize = 2;
+        type FF = <F as Extendable<D>>::Extension;
+
+        let mut rng = OsRng;
+        for op in ALL_OPS {
+            let gate = U32BitwiseGate::<F, D>::new_from_config(
+                &CircuitConfig::standard_recursion_config(),
+                op,
+            );
+            let mut wires = vec![FF::ZERO; gate.num_wires()];
+            for i in 0..gate.num_ops {
+                let (x, y) = (rng.gen::<u32>(), rng.gen::<u32>());
+                wires[gate.wire_ith_input_x(i)] = FF::from_canonical_u32(x);
+                wires[gate.wire_ith_input_y(i)] = FF::from_canonical_u32(y);
+                wires[gate.wire_ith_output(i)] = FF::from_canonical_u32(op.eval_u32(x, y));
+                for j in 0..32 {
+                    wires[gate.wire_ith_x_jth_bit(i, j)] = FF::from_bool((x >> j) & 1 == 1);
+                    wires[gate.wire_ith_y_jth_bit(i, j)] = FF::from_bool((y >> j) & 1 == 1);
+                }
+            }
+
+            let vars = EvaluationVars {
+                local_constants: &[],
+                local_wires: &wires,
+                public_inputs_hash: &HashOut::rand(),
+            };
+            assert!(
+                gate.eval_unfiltered(vars).iter().all(|x| x.is_zero()),
+                "Gate constraints are not satisfied."
+            );
+
+            let mut bad_wires = wires.clone();
+            bad_wires[gate.wire_ith_output(0)] += FF::ONE;
+            let vars = EvaluationVars {
+                local_wires: &bad_wires,
+                ..vars
+            };
+            assert!(!gate.eval_unfiltered(vars).iter().all(|x| x.is_zero()));
+        }
+    }
+}
@@ -0,0 +1,548 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::marker::PhantomData;
+
+use plonky2::field::extension::Extendable;
+use plonky2::field::types::Field;
+use plonky2::gates::gate::Gate;
+use plonky2::gates::util::StridedConstraintConsumer;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGeneratorRef};
+use plonky2::iop::target::Target;
+use plonky2::iop::wire::Wire;
+use plonky2::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::vars::{EvaluationTargets, EvaluationVars, EvaluationVarsBase};
+use plonky2::util::serialization::{Buffer, IoError, IoResult, Read, Write};
+
+/// The pairs of bitwise functions computed by a [`Sha256Gate`]. Each pair shares its inputs, so
+/// that the inputs are decomposed into bits only once.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Sha256Functions {
+    /// `Σ0(x)` and `Maj(x, y, z)`, on the words `a, b, c` of a compression round.
+    Sigma0Maj,
+    /// `Σ1(x)` and `Ch(x, y, z)`, on the words `e, f, g` of a compression round.
+    Sigma1Ch,
+    /// `σ0(x)` and `σ1(y)`, on the words `w[t - 15]` and `w[t - 2]` of the message schedule.
+    MessageSchedule,
+}
+
+/// A term `x >>> n` or `x >> n` of an XOR in one of the SHA-256 `Σ` and `σ` functions.
+#[derive(Copy, Clone)]
+enum Shift {
+    Rotate(usize),
+    Shift(usize),
+}
+
+impl Shift {
+    /// The bit of the input which ends up at bit `i` of the output, if any.
+    fn source_bit(self, i: usize) -> Option<usize> {
+        match self {
+            Shift::Rotate(n) => Some((i + n) % 32),
+            Shift::Shift(n) => (i + n < 32).then_some(i + n),
+        }
+    }
+}
+
+const BIG_SIGMA_0: [Shift; 3] = [Shift::Rotate(2), Shift::Rotate(13), Shift::Rotate(22)];
+const BIG_SIGMA_1: [Shift; 3] = [Shift::Rotate(6), Shift::Rotate(11), Shift::Rotate(25)];
+const SMALL_SIGMA_0: [Shift; 3] = [Shift::Rotate(7), Shift::Rotate(18), Shift::Shift(3)];
+const SMALL_SIGMA_1: [Shift; 3] = [Shift::Rotate(17), Shift::Rotate(19), Shift::Shift(10)];
+
+/// Applies one of the SHA-256 `Σ` or `σ` functions to `x`.
+fn sigma_u32(x: u32, shifts: [Shift; 3]) -> u32 {
+    shifts
+        .into_iter()
+        .map(|shift| match shift {
+            Shift::Rotate(n) => x.rotate_right(n as u32),
+            Shift::Shift(n) => x >> n,
+        })
+        .fold(0, |acc, term| acc ^ term)
+}
+
+/// `a ^ b` for bits `a` and `b`.
+fn xor<T: Field>(a: T, b: T) -> T {
+    a + b - (a * b).double()
+}
+
+/// One of the SHA-256 `Σ` or `σ` functions, applied to the little-endian `bits` of a word.
+fn sigma<T: Field>(bits: &[T], shifts: [Shift; 3]) -> T {
+    (0..32).rev().fold(T::ZERO, |acc, i| {
+        let bit = shifts
+            .iter()
+            .filter_map(|shift| shift.source_bit(i))
+            .map(|j| bits[j])
+            .reduce(xor)
+            .unwrap();
+        acc.double() + bit
+    })
+}
+
+/// Applies `f` to the little-endian bits of three words, and recombines the results into a word.
+fn bitwise<T: Field>(x: &[T], y: &[T], z: &[T], f: impl Fn(T, T, T) -> T) -> T {
+    (0..32)
+        .rev()
+        .fold(T::ZERO, |acc, i| acc.double() + f(x[i], y[i], z[i]))
+}
+
+fn maj<T: Field>(x: T, y: T, z: T) -> T {
+    x * y + x * z + y * z - (x * y * z).double()
+}
+
+fn ch<T: Field>(x: T, y: T, z: T) -> T {
+    x * (y - z) + z
+}
+
+/// A gate which computes two of the bitwise functions of SHA-256, see [`Sha256Functions`].
+///
+/// The inputs are decomposed into little-endian bits, which range checks them as a side effect.
+/// The outputs are recombined from the output bits, so they are 32-bit values too.
+#[derive(Copy, Clone, Debug)]
+pub struct Sha256Gate<F: RichField + Extendable<D>, const D: usize> {
+    pub functions: Sha256Functions,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Sha256Gate<F, D> {
+    pub fn new(functions: Sha256Functions) -> Self {
+        Self {
+            functions,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn num_inputs(&self) -> usize {
+        match self.functions {
+            Sha256Functions::Sigma0Maj | Sha256Functions::Sigma1Ch => 3,
+            Sha256Functions::MessageSchedule => 2,
+        }
+    }
+
+    pub fn wire_ith_input(&self, i: usize) -> usize {
+        debug_assert!(i < self.num_inputs());
+        i
+    }
+
+    pub fn wire_ith_output(&self, i: usize) -> usize {
+        debug_assert!(i < 2);
+        self.num_inputs() + i
+    }
+
+    pub fn wire_ith_input_jth_bit(&self, i: usize, j: usize) -> usize {
+        debug_assert!(i < self.num_inputs());
+        debug_assert!(j < 32);
+        self.num_inputs() + 2 + 32 * i + j
+    }
+
+    /// The two outputs of the gate, given the input bits.
+    fn outputs<T: Field>(&self, bits: &[Vec<T>]) -> [T; 2] {
+        match self.functions {
+            Sha256Functions::Sigma0Maj => [
+                sigma(&bits[0], BIG_SIGMA_0),
+                bitwise(&bits[0], &bits[1], &bits[2], maj),
+            ],
+            Sha256Functions::Sigma1Ch => [
+                sigma(&bits[0], BIG_SIGMA_1),
+                bitwise(&bits[0], &bits[1], &bits[2], ch),
+            ],
+            Sha256Functions::MessageSchedule => [
+                sigma(&bits[0], SMALL_SIGMA_0),
+                sigma(&bits[1], SMALL_SIGMA_1),
+            ],
+        }
+    }
+
+    fn eval<T: Field>(&self, local_wires: &[T], mut yield_constr: impl FnMut(T)) {
+        let bits = (0..self.num_inputs())
+            .map(|i| {
+                (0..32)
+                    .map(|j| local_wires[self.wire_ith_input_jth_bit(i, j)])
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        for (i, input_bits) in bits.iter().enumerate() {
+            for &bit in input_bits {
+                yield_constr(bit * (bit - T::ONE));
+            }
+            let computed_input = input_bits
+                .iter()
+                .rev()
+                .fold(T::ZERO, |acc, &bit| acc.double() + bit);
+            yield_constr(computed_input - local_wires[self.wire_ith_input(i)]);
+        }
+
+        for (i, computed_output) in self.outputs(&bits).into_iter().enumerate() {
+            yield_constr(computed_output - local_wires[self.wire_ith_output(i)]);
+        }
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for Sha256Gate<F, D> {
+    fn id(&self) -> String {
+        format!("{self:?}")
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_u8(self.functions as u8)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let functions = match src.read_u8()? {
+            0 => Sha256Functions::Sigma0Maj,
+            1 => Sha256Functions::Sigma1Ch,
+            2 => Sha256Functions::MessageSchedule,
+            _ => return Err(IoError),
+        };
+        Ok(Self::new(functions))
+    }
+
+    fn eval_unfiltered(&self, vars: EvaluationVars<F, D>) -> Vec<F::Extension> {
+        let mut constraints = Vec::with_capacity(self.num_constraints());
+        self.eval(vars.local_wires, |c| constraints.push(c));
+        constraints
+    }
+
+    fn eval_unfiltered_base_one(
+        &self,
+        vars: EvaluationVarsBase<F>,
+        mut yield_constr: StridedConstraintConsumer<F>,
+    ) {
+        let local_wires = (0..self.num_wires())
+            .map(|i| vars.local_wires[i])
+            .collect::<Vec<_>>();
+        self.eval(&local_wires, |c| yield_constr.one(c));
+    }
+
+    fn eval_unfiltered_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: EvaluationTargets<D>,
+    ) -> Vec<ExtensionTarget<D>> {
+        let mut constraints = Vec::with_capacity(self.num_constraints());
+        let two = builder.two_extension();
+
+        let bits = (0..self.num_inputs())
+            .map(|i| {
+                (0..32)
+                    .map(|j| vars.local_wires[self.wire_ith_input_jth_bit(i, j)])
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        for (i, input_bits) in bits.iter().enumerate() {
+            for &bit in input_bits {
+                constraints.push(builder.mul_sub_extension(bit, bit, bit));
+            }
+            let computed_input = recombine_circuit(builder, input_bits.iter().copied(), two);
+            constraints.push(
+                builder.sub_extension(computed_input, vars.local_wires[self.wire_ith_input(i)]),
+            );
+        }
+
+        let outputs = match self.functions {
+            Sha256Functions::Sigma0Maj => [
+                sigma_circuit(builder, &bits[0], BIG_SIGMA_0, two),
+                bitwise_circuit(builder, &bits, maj_circuit, two),
+            ],
+            Sha256Functions::Sigma1Ch => [
+                sigma_circuit(builder, &bits[0], BIG_SIGMA_1, two),
+                bitwise_circuit(builder, &bits, ch_circuit, two),
+            ],
+            Sha256Functions::MessageSchedule => [
+                sigma_circuit(builder, &bits[0], SMALL_SIGMA_0, two),
+                sigma_circuit(builder, &bits[1], SMALL_SIGMA_1, two),
+            ],
+        };
+        for (i, computed_output) in outputs.into_iter().enumerate() {
+            constraints.push(
+                builder.sub_extension(computed_output, vars.local_wires[self.wire_ith_output(i)]),
+            );
+        }
+
+        constraints
+    }
+
+    fn generators(&self, row: usize, _local_constants: &[F]) -> Vec<WitnessGeneratorRef<F>> {
+        let gen = Sha256Generator::<F, D> { gate: *self, row };
+        vec![WitnessGeneratorRef::new(gen.adapter())]
+    }
+
+    fn num_wires(&self) -> usize {
+        self.num_inputs() * 33 + 2
+    }
+
+    fn num_constants(&self) -> usize {
+        0
+    }
+
+    fn degree(&self) -> usize {
+        3
+    }
+
+    fn num_constraints(&self) -> usize {
+        self.num_inputs() * 33 + 2
+    }
+}
+
+/// Recombines little-endian bits into a word.
+fn recombine_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    bits: impl DoubleEndedIterator<Item = ExtensionTarget<D>>,
+    two: ExtensionTarget<D>,
+) -> ExtensionTarget<D> {
+    let mut acc = builder.zero_extension();
+    for bit in bits.rev() {
+        acc = builder.mul_add_extension(two, acc, bit);
+    }
+    acc
+}
+
+fn xor_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    a: ExtensionTarget<D>,
+    b: ExtensionTarget<D>,
+) -> ExtensionTarget<D> {
+    // a + b - 2ab
+    let sum = builder.add_extension(a, b);
+    builder.arithmetic_extension(-F::TWO, F::ONE, a, b, sum)
+}
+
+fn sigma_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    bits: &[ExtensionTarget<D>],
+    shifts: [Shift; 3],
+    two: ExtensionTarget<D>,
+) -> ExtensionTarget<D> {
+    let output_bits = (0..32)
+        .map(|i| {
+            let mut terms = shifts
+                .iter()
+                .filter_map(|shift| shift.source_bit(i))
+                .map(|j| bits[j]);
+            let first = terms.next().unwrap();
+            terms.fold(first, |acc, bit| xor_circuit(builder, acc, bit))
+        })
+        .collect::<Vec<_>>();
+    recombine_circuit(builder, output_bits.into_iter(), two)
+}
+
+fn bitwise_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    bits: &[Vec<ExtensionTarget<D>>],
+    f: fn(
+        &mut CircuitBuilder<F, D>,
+        ExtensionTarget<D>,
+        ExtensionTarget<D>,
+        ExtensionTarget<D>,
+    ) -> ExtensionTarget<D>,
+    two: ExtensionTarget<D>,
+) -> ExtensionTarget<D> {
+    let output_bits = (0..32)
+        .map(|i| f(builder, bits[0][i], bits[1][i], bits[2][i]))
+        .collect::<Vec<_>>();
+    recombine_circuit(builder, output_bits.into_iter(), two)
+}
+
+fn maj_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    x: ExtensionTarget<D>,
+    y: ExtensionTarget<D>,
+    z: ExtensionTarget<D>,
+) -> ExtensionTarget<D> {
+    // xy + z(x + y - 2xy), i.e. z ? x | y : x & y.
+    let xy = builder.mul_extension(x, y);
+    let x_plus_y = builder.add_extension(x, y);
+    let x_xor_y = builder.arithmetic_extension(-F::TWO, F::ONE, x, y, x_plus_y);
+    builder.mul_add_extension(z, x_xor_y, xy)
+}
+
+fn ch_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    x: ExtensionTarget<D>,
+    y: ExtensionTarget<D>,
+    z: ExtensionTarget<D>,
+) -> ExtensionTarget<D> {
+    let y_minus_z = builder.sub_extension(y, z);
+    builder.mul_add_extension(x, y_minus_z, z)
+}
+
+#[derive(Clone, Debug)]
+pub struct Sha256Generator<F: RichField + Extendable<D>, const D: usize> {
+    gate: Sha256Gate<F, D>,
+    row: usize,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F> for Sha256Generator<F, D> {
+    fn dependencies(&self) -> Vec<Target> {
+        (0..self.gate.num_inputs())
+            .map(|i| Target::wire(self.row, self.gate.wire_ith_input(i)))
+            .collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let local_wire = |column| Wire {
+            row: self.row,
+            column,
+        };
+
+        let inputs = (0..self.gate.num_inputs())
+            .map(|i| {
+                let input = witness.get_wire(local_wire(self.gate.wire_ith_input(i)));
+                let input = input.to_canonical_u64();
+                debug_assert!(input < 1 << 32, "Input is not a 32-bit value.");
+                input as u32
+            })
+            .collect::<Vec<_>>();
+
+        for (i, &input) in inputs.iter().enumerate() {
+            for j in 0..32 {
+                let bit = F::from_bool((input >> j) & 1 == 1);
+                out_buffer.set_wire(local_wire(self.gate.wire_ith_input_jth_bit(i, j)), bit);
+            }
+        }
+
+        let outputs = match self.gate.functions {
+            Sha256Functions::Sigma0Maj => {
+                let (x, y, z) = (inputs[0], inputs[1], inputs[2]);
+                [sigma_u32(x, BIG_SIGMA_0), (x & y) ^ (x & z) ^ (y & z)]
+            }
+            Sha256Functions::Sigma1Ch => {
+                let (x, y, z) = (inputs[0], inputs[1], inputs[2]);
+                [sigma_u32(x, BIG_SIGMA_1), (x & y) ^ (!x & z)]
+            }
+            Sha256Functions::MessageSchedule => [
+                sigma_u32(inputs[0], SMALL_SIGMA_0),
+                sigma_u32(inputs[1], SMALL_SIGMA_1),
+            ],
+        };
+        for (i, output) in outputs.into_iter().enumerate() {
+            out_buffer.set_wire(
+                local_wire(self.gate.wire_ith_output(i)),
+                F::from_canonical_u32(output),
+            );
+        }
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.row)?;
+        self.gate.serialize(dst)
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let row = src.read_usize()?;
+        let gate = Sha256Gate::<F, D>::deserialize(src)?;
+        Ok(Self { gate, row })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Sample;
+    use plonky2::gates::gate_testing::{test_eval_fns, test_low_degree};
+    use plonky2::hash::hash_types::HashOut;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use rand::rngs::OsRng;
+    use rand::Rng;
+
+    use super::*;
+
+    const ALL_FUNCTIONS: [Sha256Functions; 3] = [
+        Sha256Functions::Sigma0Maj,
+        Sha256Functions::Sigma1Ch,
+        Sha256Functions::MessageSchedule,
+    ];
+
+    #[test]
+    fn low_degree() {
+        for functions in ALL_FUNCTIONS {
+            test_low_degree::<GoldilocksField, _, 4>(Sha256Gate::<GoldilocksField, 4>::new(
+                functions,
+            ))
+        }
+    }
+
+    #[test]
+    fn eval_fns() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        for functions in ALL_FUNCTIONS {
+            test_eval_fns::<F, C, _, D>(Sha256Gate::<F, D>::new(functions))?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_gate_constraint() {
+        type F = GoldilocksField;
+        const D: usize = 2;
+        type FF = <F as Extendable<D>>::Extension;
+
+        let mut rng = OsRng;
+        let (x, y, z) = (rng.gen::<u32>(), rng.gen::<u32>(), rng.gen::<u32>());
+        let cases = [
+            (
+                Sha256Functions::Sigma0Maj,
+                vec![x, y, z],
+                [
+                    x.rotate_right(2) ^ x.rotate_right(13) ^ x.rotate_right(22),
+                    (x & y) ^ (x & z) ^ (y & z),
+                ],
+            ),
+            (
+                Sha256Functions::Sigma1Ch,
+                vec![x, y, z],
+                [
+                    x.rotate_right(6) ^ x.rotate_right(11) ^ x.rotate_right(25),
+                    (x & y) ^ (!x & z),
+                ],
+            ),
+            (
+                Sha256Functions::MessageSchedule,
+                vec![x, y],
+                [
+                    x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3),
+                    y.rotate_right(17) ^ y.rotate_right(19) ^ (y >> 10),
+                ],
+            ),
+        ];
+
+        for (functions, inputs, outputs) in cases {
+            let gate = Sha256Gate::<F, D>::new(functions);
+            let bits = inputs
+                .iter()
+                .flat_map(|&input| (0..32).map(move |j| (input >> j) & 1));
+            let wires = inputs
+                .iter()
+                .chain(&outputs)
+                .copied()
+                .chain(bits)
+                .map(FF::from_canonical_u32)
+                .collect::<Vec<_>>();
+            assert_eq!(wires.len(), gate.num_wires());
+
+            let vars = EvaluationVars {
+                local_constants: &[],
+                local_wires: &wires,
+                public_inputs_hash: &HashOut::rand(),
+            };
+            assert!(
+                gate.eval_unfiltered(vars).iter().all(|x| x.is_zero()),
+                "Gate constraints are not satisfied."
+            );
+
+            let mut bad_wires = wires.clone();
+            bad_wires[gate.wire_ith_output(1)] += FF::ONE;
+            let vars = EvaluationVars {
+                local_wires: &bad_wires,
+                ..vars
+            };
+            assert!(!gate.eval_unfiltered(vars).iter().all(|x| x.is_zero()));
+        }
+    }
+}
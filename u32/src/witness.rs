@@ -3,10 +3,13 @@ use plonky2::iop::generator::GeneratedValues;
 use plonky2::iop::witness::{Witness, WitnessWrite};
 
 use crate::gadgets::arithmetic_u32::U32Target;
+use crate::gadgets::arithmetic_u64::U64Target;
 
 pub trait WitnessU32<F: PrimeField64>: Witness<F> {
     fn set_u32_target(&mut self, target: U32Target, value: u32);
     fn get_u32_target(&self, target: U32Target) -> (u32, u32);
+    fn set_u64_target(&mut self, target: U64Target, value: u64);
+    fn get_u64_target(&self, target: U64Target) -> u64;
 }
 
 impl<T: Witness<F>, F: PrimeField64> WitnessU32<F> for T {
@@ -20,14 +23,31 @@ impl<T: Witness<F>, F: PrimeField64> WitnessU32<F> for T {
         let high = (x_u64 >> 32) as u32;
         (low, high)
     }
+
+    fn set_u64_target(&mut self, target: U64Target, value: u64) {
+        self.set_u32_target(target.lo(), value as u32);
+        self.set_u32_target(target.hi(), (value >> 32) as u32);
+    }
+
+    fn get_u64_target(&self, target: U64Target) -> u64 {
+        let (lo, _) = self.get_u32_target(target.lo());
+        let (hi, _) = self.get_u32_target(target.hi());
+        ((hi as u64) << 32) | lo as u64
+    }
 }
 
 pub trait GeneratedValuesU32<F: Field> {
     fn set_u32_target(&mut self, target: U32Target, value: u32);
+    fn set_u64_target(&mut self, target: U64Target, value: u64);
 }
 
 impl<F: Field> GeneratedValuesU32<F> for GeneratedValues<F> {
     fn set_u32_target(&mut self, target: U32Target, value: u32) {
         self.set_target(target.0, F::from_canonical_u32(value))
     }
+
+    fn set_u64_target(&mut self, target: U64Target, value: u64) {
+        self.set_u32_target(target.lo(), value as u32);
+        self.set_u32_target(target.hi(), (value >> 32) as u32);
+    }
 }
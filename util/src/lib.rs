@@ -182,6 +182,20 @@ unsafe fn reverse_index_bits_in_place_chunks<T>(
     }
 }
 
+/// Transposes in place each `2^lb_size` by `2^lb_size` block of the row-major matrix `arr`, which
+/// has `2^lb_size` rows and is made of such blocks side by side.
+pub fn transpose_square_blocks_in_place<T>(arr: &mut [T], lb_size: usize) {
+    let lb_num_blocks = log2_strict(arr.len()) - 2 * lb_size;
+    let lb_stride = lb_size + lb_num_blocks;
+    for block in 0..1 << lb_num_blocks {
+        // SAFETY: Block `block` starts at `block << lb_size`, and its last entry is at
+        // `(block << lb_size) + ((2^lb_size - 1) << lb_stride) + 2^lb_size - 1 < arr.len()`.
+        unsafe {
+            transpose_in_place_square(&mut arr[block << lb_size..], lb_stride, lb_size, 0);
+        }
+    }
+}
+
 // Ensure that SMALL_ARR_SIZE >= 4 * BIG_T_SIZE.
 const BIG_T_SIZE: usize = 1 << 14;
 const SMALL_ARR_SIZE: usize = 1 << 16;
@@ -315,6 +329,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_transpose_square_blocks_in_place() {
+        for lb_size in [0, 1, 3, 6] {
+            for lb_num_blocks in [0, 1, 2] {
+                let n = 1 << lb_size;
+                let row_len = n << lb_num_blocks;
+                let mut arr = (0..n * row_len).collect::<Vec<usize>>();
+                super::transpose_square_blocks_in_place(&mut arr, lb_size);
+                for i in 0..n {
+                    for block in 0..1 << lb_num_blocks {
+                        for j in 0..n {
+                            let col = block * n + j;
+                            assert_eq!(arr[i * row_len + col], j * row_len + block * n + i);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_reverse_index_bits_in_place() {
         let lengths = [32, 128, 1 << 16];
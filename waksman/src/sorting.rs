@@ -8,6 +8,7 @@ use plonky2::iop::generator::{GeneratedValues, SimpleGenerator};
 use plonky2::iop::target::{BoolTarget, Target};
 use plonky2::iop::witness::{PartitionWitness, Witness, WitnessWrite};
 use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::util::serialization::{Buffer, IoResult, Read, Write};
 use plonky2_util::ceil_div_usize;
 
 use crate::gates::assert_le::AssertLessThanGate;
@@ -127,7 +128,7 @@ pub fn sort_memory_ops_circuit<F: RichField + Extendable<D>, const D: usize>(
 }
 
 #[derive(Debug)]
-struct MemoryOpSortGenerator<F: RichField + Extendable<D>, const D: usize> {
+pub struct MemoryOpSortGenerator<F: RichField + Extendable<D>, const D: usize> {
     input_ops: Vec<MemoryOpTarget>,
     output_ops: Vec<MemoryOpTarget>,
     _phantom: PhantomData<F>,
@@ -178,6 +179,36 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F>
             out_buffer.set_target(out_op.value, op.value);
         }
     }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_usize(self.input_ops.len())?;
+        for op in self.input_ops.iter().chain(&self.output_ops) {
+            dst.write_bool_target(op.is_write)?;
+            dst.write_target(op.address)?;
+            dst.write_target(op.timestamp)?;
+            dst.write_target(op.value)?;
+        }
+        Ok(())
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let n = src.read_usize()?;
+        let mut read_op = || -> IoResult<MemoryOpTarget> {
+            Ok(MemoryOpTarget {
+                is_write: src.read_bool_target()?,
+                address: src.read_target()?,
+                timestamp: src.read_target()?,
+                value: src.read_target()?,
+            })
+        };
+        let input_ops = (0..n).map(|_| read_op()).collect::<IoResult<Vec<_>>>()?;
+        let output_ops = (0..n).map(|_| read_op()).collect::<IoResult<Vec<_>>>()?;
+        Ok(Self {
+            input_ops,
+            output_ops,
+            _phantom: PhantomData,
+        })
+    }
 }
 
 #[cfg(test)]